@@ -0,0 +1,323 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use colored::Colorize;
+use tracing::{debug, warn};
+
+use crate::output::status;
+use crate::provider::CoinPrice;
+
+/// Process exit code used when `--alert-once` stops on a triggered alert; kept apart
+/// from every [`Error::exit_code`](crate::error::Error::exit_code).
+pub const ALERT_EXIT_CODE: i32 = 10;
+
+/// Value an alert condition is compared against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMetric {
+    /// Latest price in the quote currency.
+    Price,
+    /// 24h change in percent (written as `btc%>5`).
+    Change24h,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Above,
+    Below,
+    AtLeast,
+    AtMost,
+}
+
+impl Comparison {
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Above => value > threshold,
+            Self::Below => value < threshold,
+            Self::AtLeast => value >= threshold,
+            Self::AtMost => value <= threshold,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Above => ">",
+            Self::Below => "<",
+            Self::AtLeast => ">=",
+            Self::AtMost => "<=",
+        }
+    }
+}
+
+/// A parsed alert expression such as `btc>70000` or `eth%<-5`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertCondition {
+    pub symbol: String,
+    pub metric: AlertMetric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl AlertCondition {
+    /// Whether this condition targets the given price's symbol.
+    pub fn applies_to(&self, price: &CoinPrice) -> bool {
+        crate::provider::symbol_answers(&self.symbol, &price.symbol)
+    }
+
+    /// Evaluate the condition against a price; missing 24h change never fires.
+    pub fn evaluate(&self, price: &CoinPrice) -> bool {
+        if !self.applies_to(price) {
+            return false;
+        }
+
+        let value = match self.metric {
+            AlertMetric::Price => Some(price.price),
+            AlertMetric::Change24h => price.change_24h,
+        };
+        value.is_some_and(|v| self.comparison.holds(v, self.threshold))
+    }
+}
+
+impl fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.metric {
+            AlertMetric::Price => "",
+            AlertMetric::Change24h => "%",
+        };
+        write!(
+            f,
+            "{}{}{}{}",
+            self.symbol,
+            marker,
+            self.comparison.as_str(),
+            self.threshold
+        )
+    }
+}
+
+impl FromStr for AlertCondition {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            format!(
+                "invalid alert '{}': {} -- expected SYMBOL[%]OP VALUE with OP one of >, <, >=, <= (e.g. btc>70000, eth%<-5)",
+                raw, reason
+            )
+        };
+
+        let trimmed = raw.trim();
+        let op_start = trimmed
+            .find(['<', '>'])
+            .ok_or_else(|| invalid("missing comparison operator"))?;
+
+        let (lhs, rest) = trimmed.split_at(op_start);
+        let (comparison, rhs) = if let Some(rhs) = rest.strip_prefix(">=") {
+            (Comparison::AtLeast, rhs)
+        } else if let Some(rhs) = rest.strip_prefix("<=") {
+            (Comparison::AtMost, rhs)
+        } else if let Some(rhs) = rest.strip_prefix('>') {
+            (Comparison::Above, rhs)
+        } else {
+            (Comparison::Below, &rest[1..])
+        };
+
+        let lhs = lhs.trim();
+        let (symbol, metric) = match lhs.strip_suffix('%') {
+            Some(symbol) => (symbol.trim(), AlertMetric::Change24h),
+            None => (lhs, AlertMetric::Price),
+        };
+        if symbol.is_empty() {
+            return Err(invalid("missing symbol"));
+        }
+        if symbol.contains(['<', '>', '%', '=']) {
+            return Err(invalid("unexpected operator in symbol"));
+        }
+
+        let value = rhs.trim().replace('_', "");
+        if value.is_empty() {
+            return Err(invalid("missing threshold value"));
+        }
+        let threshold = value
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| invalid("threshold is not a number"))?;
+
+        Ok(Self {
+            symbol: symbol.to_uppercase(),
+            metric,
+            comparison,
+            threshold,
+        })
+    }
+}
+
+/// Parse an alert expression; used as a clap value parser.
+pub fn parse_alert(raw: &str) -> Result<AlertCondition, String> {
+    raw.parse()
+}
+
+/// Tracks which conditions currently hold so alerts fire once per crossing.
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    active: HashSet<usize>,
+}
+
+impl AlertTracker {
+    /// Return conditions that started holding since the previous check.
+    pub fn check<'a>(
+        &mut self,
+        conditions: &'a [AlertCondition],
+        prices: &'a [CoinPrice],
+    ) -> Vec<(&'a AlertCondition, &'a CoinPrice)> {
+        let mut fired = Vec::new();
+
+        for (idx, condition) in conditions.iter().enumerate() {
+            let Some(price) = prices.iter().find(|p| condition.applies_to(p)) else {
+                // Keep the previous state when the symbol is missing from this refresh.
+                continue;
+            };
+
+            if condition.evaluate(price) {
+                if self.active.insert(idx) {
+                    fired.push((condition, price));
+                }
+            } else {
+                self.active.remove(&idx);
+            }
+        }
+
+        fired
+    }
+}
+
+/// Highlighted one-line notice for a triggered alert.
+pub fn format_alert_line(condition: &AlertCondition, price: &CoinPrice) -> String {
+    let current = match condition.metric {
+        AlertMetric::Price => status::format_compact_price(price.price, &price.currency),
+        AlertMetric::Change24h => price
+            .change_24h
+            .map(|c| format!("{:+.2}%", c))
+            .unwrap_or_default(),
+    };
+    format!("ALERT {} (now {})", condition, current)
+        .bold()
+        .yellow()
+        .to_string()
+}
+
+/// Run the user's `--alert-exec` command through `sh -c` without waiting for it.
+///
+/// The command supports the same `{placeholder}` fields as `--template`.
+pub fn spawn_exec(command: &str, price: &CoinPrice) {
+    let rendered = status::render_template(command, price);
+    debug!(command = %rendered, "running alert command");
+
+    if let Err(err) = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(&rendered)
+        .spawn()
+    {
+        warn!(command = %rendered, error = %err, "failed to run alert command");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(symbol: &str, value: f64, change_24h: Option<f64>) -> CoinPrice {
+        CoinPrice {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            price: value,
+            change_24h,
+            change_7d: None,
+            change_30d: None,
+            market_cap: None,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
+            currency: "usd".to_string(),
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::Utc::now(),
+            quote_time_estimated: false,
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        }
+    }
+
+    #[test]
+    fn parses_price_and_change_expressions() {
+        let above: AlertCondition = "btc>70000".parse().unwrap();
+        assert_eq!(above.symbol, "BTC");
+        assert_eq!(above.metric, AlertMetric::Price);
+        assert_eq!(above.comparison, Comparison::Above);
+        assert_eq!(above.threshold, 70_000.0);
+
+        let at_most: AlertCondition = " eth <= 3_000.5 ".parse().unwrap();
+        assert_eq!(at_most.comparison, Comparison::AtMost);
+        assert_eq!(at_most.threshold, 3_000.5);
+
+        let change: AlertCondition = "sol%<-5".parse().unwrap();
+        assert_eq!(change.metric, AlertMetric::Change24h);
+        assert_eq!(change.comparison, Comparison::Below);
+        assert_eq!(change.threshold, -5.0);
+        assert_eq!(change.to_string(), "SOL%<-5");
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for raw in [
+            "btc", ">5", "btc>", "btc>abc", "btc=>5", "btc>>5", "%>5", "btc>inf",
+        ] {
+            let err = parse_alert(raw).unwrap_err();
+            assert!(err.contains("expected SYMBOL[%]OP VALUE"), "{raw}: {err}");
+        }
+    }
+
+    #[test]
+    fn evaluate_compares_against_synthetic_prices() {
+        let above: AlertCondition = "btc>70000".parse().unwrap();
+        assert!(above.evaluate(&price("BTC", 70_001.0, None)));
+        assert!(!above.evaluate(&price("BTC", 70_000.0, None)));
+        assert!(!above.evaluate(&price("ETH", 80_000.0, None)));
+
+        let at_least: AlertCondition = "btc>=70000".parse().unwrap();
+        assert!(at_least.evaluate(&price("btc", 70_000.0, None)));
+
+        let change: AlertCondition = "eth%>5".parse().unwrap();
+        assert!(change.evaluate(&price("ETH", 1.0, Some(5.5))));
+        assert!(!change.evaluate(&price("ETH", 1.0, Some(4.0))));
+        assert!(!change.evaluate(&price("ETH", 1.0, None)));
+    }
+
+    #[test]
+    fn tracker_fires_once_per_crossing() {
+        let conditions = vec![parse_alert("btc>100").unwrap()];
+        let mut tracker = AlertTracker::default();
+
+        let high = vec![price("BTC", 101.0, None)];
+        assert_eq!(tracker.check(&conditions, &high).len(), 1);
+        assert!(tracker.check(&conditions, &high).is_empty());
+
+        let low = vec![price("BTC", 99.0, None)];
+        assert!(tracker.check(&conditions, &low).is_empty());
+        assert_eq!(tracker.check(&conditions, &high).len(), 1);
+    }
+}
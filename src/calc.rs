@@ -1,11 +1,17 @@
+pub mod stats;
+
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::provider::{CoinPrice, HistoryInterval, PricePoint};
+
 /// Recognized fiat currency codes. Prevents false positives on tokens like `1inch` or `3btc`.
 const KNOWN_FIAT: &[&str] = &[
     "USD", "EUR", "GBP", "JPY", "CNY", "CAD", "AUD", "CHF", "KRW", "INR", "BRL", "RUB", "TRY",
     "ZAR", "MXN", "SGD", "HKD", "NOK", "SEK", "DKK", "NZD", "PLN", "THB", "TWD", "CZK", "HUF",
     "ILS", "PHP", "MYR", "ARS", "CLP", "COP", "IDR", "SAR", "AED", "NGN", "VND", "PKR", "BDT",
-    "EGP",
+    "EGP", "BGN", "RON", "ISK",
 ];
 
 /// A parsed fiat amount from user input (e.g. `3.5EUR`).
@@ -23,7 +29,7 @@ pub struct CryptoAmount {
 }
 
 /// Result of a fiat-to-crypto conversion.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Conversion {
     pub from_amount: f64,
     pub from_currency: String,
@@ -32,28 +38,114 @@ pub struct Conversion {
     pub to_amount: f64,
     pub rate: f64,
     pub provider: String,
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
-/// Try to parse a string like `3.5EUR` or `100usd` into a `FiatAmount`.
+/// A lump sum bought at a past price and valued at today's, as printed by `pricr roi`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Investment {
+    pub symbol: String,
+    pub name: String,
+    pub currency: String,
+    pub provider: String,
+    pub invested: f64,
+    /// Timestamp of the historical point the sum was bought at.
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
+    pub bought_at: DateTime<Utc>,
+    pub buy_price: f64,
+    pub units: f64,
+    pub current_price: f64,
+    pub value: f64,
+    pub gain: f64,
+    pub gain_pct: f64,
+    /// Compound annual growth rate in percent; `None` when less than a day has passed.
+    pub cagr_pct: Option<f64>,
+}
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Value `invested` bought at `bought.price` at the `current` quote.
 ///
-/// Returns `None` when the input does not match `<number><fiat_code>`, letting
-/// the caller fall through to normal price-lookup mode.
-pub fn parse_fiat_amount(s: &str) -> Option<FiatAmount> {
-    // Find where the alphabetic suffix starts.
-    let alpha_start = s.find(|c: char| c.is_ascii_alphabetic())?;
-    if alpha_start == 0 {
+/// `units = invested / buy_price`, `value = units * current_price`, and the gain is
+/// `value - invested`. CAGR spans the time between the two prices.
+pub fn lump_sum(invested: f64, bought: &PricePoint, current: &CoinPrice) -> Investment {
+    let units = invested / bought.price;
+    let value = units * current.price;
+    let years = (current.timestamp - bought.timestamp).num_seconds() as f64 / SECONDS_PER_YEAR;
+    Investment {
+        symbol: current.symbol.clone(),
+        name: current.name.clone(),
+        currency: current.currency.clone(),
+        provider: current.provider.clone(),
+        invested,
+        bought_at: bought.timestamp,
+        buy_price: bought.price,
+        units,
+        current_price: current.price,
+        value,
+        gain: value - invested,
+        gain_pct: (value - invested) / invested * 100.0,
+        cagr_pct: (years * 365.25 >= 1.0)
+            .then(|| cagr_pct(invested, value, years))
+            .flatten(),
+    }
+}
+
+/// Compound annual growth rate in percent: `((end / start)^(1 / years) - 1) * 100`.
+///
+/// `None` for a non-positive start or span, or a negative end.
+pub fn cagr_pct(start: f64, end: f64, years: f64) -> Option<f64> {
+    if start <= 0.0 || end < 0.0 || years <= 0.0 {
         return None;
     }
+    Some(((end / start).powf(1.0 / years) - 1.0) * 100.0)
+}
 
-    let (num_part, code_part) = s.split_at(alpha_start);
-    let code_upper = code_part.to_uppercase();
+/// The point nearest to `at`, preferring the earlier one on a tie.
+pub fn closest_point(points: &[PricePoint], at: DateTime<Utc>) -> Option<&PricePoint> {
+    points
+        .iter()
+        .min_by_key(|point| ((point.timestamp - at).abs(), point.timestamp))
+}
+
+/// Currency signs accepted in front of an amount, e.g. `$100`.
+const CURRENCY_SIGNS: &[(char, &str)] = &[('$', "USD"), ('€', "EUR"), ('£', "GBP"), ('¥', "JPY")];
+
+/// Try to parse a string like `3.5EUR`, `1,000usd`, `$100`, or `100 usd` into a `FiatAmount`.
+///
+/// `,` and `_` group thousands; a `,` that does not split off exactly three digits
+/// (as in `1,5eur`) is rejected rather than guessed at. Returns `None` when the input
+/// does not match `<number><fiat_code>` or `<sign><number>`, letting the caller fall
+/// through to normal price-lookup mode.
+pub fn parse_fiat_amount(s: &str) -> Option<FiatAmount> {
+    parse_fiat_amount_in(s, false)
+}
+
+/// [`parse_fiat_amount`], reading `,` as the decimal separator and `.` as the group
+/// separator (`1.000,5eur`) when `decimal_comma` is set.
+pub fn parse_fiat_amount_in(s: &str, decimal_comma: bool) -> Option<FiatAmount> {
+    let s = s.trim();
+    let (num_part, code_upper) = match CURRENCY_SIGNS.iter().find(|(sign, _)| s.starts_with(*sign))
+    {
+        Some((sign, code)) => (&s[sign.len_utf8()..], code.to_string()),
+        None => {
+            // Find where the alphabetic suffix starts.
+            let alpha_start = s.find(|c: char| c.is_ascii_alphabetic())?;
+            if alpha_start == 0 {
+                return None;
+            }
+            let (num_part, code_part) = s.split_at(alpha_start);
+            // A quoted `"100 usd"` arrives as one argument.
+            (num_part.trim_end(), code_part.to_uppercase())
+        }
+    };
 
     if !KNOWN_FIAT.contains(&code_upper.as_str()) {
         return None;
     }
 
-    let amount: f64 = num_part.parse().ok()?;
+    let amount = parse_grouped_number(num_part, decimal_comma)?;
     if amount <= 0.0 || !amount.is_finite() {
         return None;
     }
@@ -64,6 +156,170 @@ pub fn parse_fiat_amount(s: &str) -> Option<FiatAmount> {
     })
 }
 
+/// Split a `SYMBOL/FIAT` pair such as `btc/eur` into the symbol and the uppercased fiat code.
+///
+/// Only splits when the suffix is a known fiat code, so slash-containing tickers like
+/// `BRK/B` stay whole.
+pub fn parse_pair(token: &str) -> Option<(&str, String)> {
+    let (symbol, quote) = token.trim().rsplit_once('/')?;
+    let symbol = symbol.trim();
+    let quote = quote.trim();
+    (!symbol.is_empty() && is_known_fiat(quote)).then(|| (symbol, quote.to_uppercase()))
+}
+
+/// A fiat conversion request: `pricr 100usd 50eur btc eth`.
+#[derive(Debug, Clone)]
+pub struct CalcRequest {
+    /// Leading `<number><fiat>` amounts, in input order.
+    pub sources: Vec<FiatAmount>,
+    /// Targets that are fiat codes, converted through exchange rates.
+    pub fiat_targets: Vec<String>,
+    /// Every other target, priced through the crypto providers.
+    pub crypto_targets: Vec<String>,
+}
+
+impl CalcRequest {
+    /// Distinct source currencies in first-seen order; rates are fetched once per entry.
+    pub fn source_currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = Vec::new();
+        for source in &self.sources {
+            if !currencies.contains(&source.currency) {
+                currencies.push(source.currency.clone());
+            }
+        }
+        currencies
+    }
+
+    pub fn has_targets(&self) -> bool {
+        !self.fiat_targets.is_empty() || !self.crypto_targets.is_empty()
+    }
+}
+
+/// Split positional arguments into leading fiat source amounts and the targets after them.
+///
+/// Returns `None` when the first argument is not a fiat amount, i.e. the invocation is not
+/// a fiat conversion. Sources end at the first argument that does not parse as an amount.
+pub fn parse_invocation(symbols: &[String], decimal_comma: bool) -> Option<CalcRequest> {
+    let sources: Vec<FiatAmount> = symbols
+        .iter()
+        .map_while(|symbol| parse_fiat_amount_in(symbol, decimal_comma))
+        .collect();
+    if sources.is_empty() {
+        return None;
+    }
+    let (fiat_targets, crypto_targets) = symbols[sources.len()..]
+        .iter()
+        .cloned()
+        .partition(|target| is_known_fiat(target));
+    Some(CalcRequest {
+        sources,
+        fiat_targets,
+        crypto_targets,
+    })
+}
+
+/// Parse a plain number that may group thousands with `_` anywhere or with the
+/// locale's group separator between groups of exactly three digits.
+fn parse_grouped_number(s: &str, decimal_comma: bool) -> Option<f64> {
+    let (group, decimal) = if decimal_comma {
+        ('.', ',')
+    } else {
+        (',', '.')
+    };
+    let s = s.replace('_', "");
+    let (int_part, frac_part) = match s.split_once(decimal) {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (s.as_str(), None),
+    };
+
+    let mut groups = int_part.split(group);
+    let mut digits = groups.next()?.to_string();
+    for rest in groups {
+        if digits.is_empty() || rest.len() != 3 {
+            return None;
+        }
+        digits.push_str(rest);
+    }
+    if let Some(frac) = frac_part {
+        digits.push('.');
+        digits.push_str(frac);
+    }
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// A held quantity of one asset, valued at its looked-up price.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Position {
+    pub symbol: String,
+    pub amount: f64,
+    /// `amount × price`; `None` when the symbol has no price.
+    pub value: Option<f64>,
+}
+
+/// Holdings from `--amount` valued at one lookup's prices.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PortfolioValue {
+    pub currency: String,
+    pub positions: Vec<Position>,
+    /// Sum of the position values quoted in `currency`.
+    pub total: f64,
+    /// Symbols left out of the total because their price is in another currency.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excluded: Vec<String>,
+}
+
+impl PortfolioValue {
+    /// Position held in the row reported as `symbol`.
+    pub fn position(&self, symbol: &str) -> Option<&Position> {
+        self.positions
+            .iter()
+            .find(|position| crate::provider::symbol_answers(&position.symbol, symbol))
+    }
+}
+
+/// Value each `(symbol, amount)` at the matching row of `prices`.
+///
+/// Rows quoted in a currency other than `currency` (an unconverted equity, say) are
+/// still valued per row but left out of the total.
+pub fn value_positions(
+    prices: &[CoinPrice],
+    amounts: &[(String, f64)],
+    currency: &str,
+) -> PortfolioValue {
+    let mut total = 0.0;
+    let mut excluded = Vec::new();
+    let positions = amounts
+        .iter()
+        .map(|(symbol, amount)| {
+            let price = prices
+                .iter()
+                .find(|price| crate::provider::symbol_answers(symbol, &price.symbol));
+            let value = price.map(|price| price.price * amount);
+            if let (Some(price), Some(value)) = (price, value) {
+                if price.currency.eq_ignore_ascii_case(currency) {
+                    total += value;
+                } else {
+                    excluded.push(price.symbol.clone());
+                }
+            }
+            Position {
+                symbol: symbol.to_uppercase(),
+                amount: *amount,
+                value,
+            }
+        })
+        .collect();
+    PortfolioValue {
+        currency: currency.to_uppercase(),
+        positions,
+        total,
+        excluded,
+    }
+}
+
 /// Try to parse a string like `2.5XMR` or `0.1btc` into a `CryptoAmount`.
 ///
 /// Succeeds when the alphabetic suffix is NOT a known fiat currency, treating it
@@ -98,6 +354,56 @@ pub fn is_known_fiat(s: &str) -> bool {
     KNOWN_FIAT.contains(&s.to_uppercase().as_str())
 }
 
+/// All recognized fiat currency codes, uppercase.
+pub fn known_fiat_codes() -> &'static [&'static str] {
+    KNOWN_FIAT
+}
+
+/// The candidate closest to `needle` by edit distance, compared case-insensitively.
+///
+/// Only near misses are suggested: one edit per three characters, at least one. Ties
+/// go to the earlier candidate.
+pub fn closest_match<'a>(
+    needle: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let needle = needle.to_lowercase();
+    let max_distance = (needle.chars().count() / 3).max(1);
+    let mut best: Option<(usize, &str)> = None;
+    for candidate in candidates {
+        let distance = edit_distance(&needle, &candidate.to_lowercase());
+        if distance <= max_distance && best.is_none_or(|(found, _)| distance < found) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and
+/// adjacent transpositions each cost one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
 /// Human-readable name for a fiat currency code. Falls back to the code itself.
 pub fn fiat_name(code: &str) -> &str {
     match code.to_uppercase().as_str() {
@@ -141,14 +447,191 @@ pub fn fiat_name(code: &str) -> &str {
         "PKR" => "Pakistani Rupee",
         "BDT" => "Bangladeshi Taka",
         "EGP" => "Egyptian Pound",
+        "BGN" => "Bulgarian Lev",
+        "RON" => "Romanian Leu",
+        "ISK" => "Icelandic Krona",
         _ => code,
     }
 }
 
+/// How `resample` collapses the points of one bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResampleMethod {
+    /// Last price in the bucket, like a candle close.
+    #[default]
+    Last,
+    /// Arithmetic mean of the bucket's prices.
+    Mean,
+}
+
+/// Bucket a price series into `interval` buckets, one point per bucket stamped at its start.
+///
+/// Buckets align to UTC: 4-hour blocks from midnight, weeks from Monday, months from the
+/// 1st. Intervals that providers fetch natively (`Auto`, `Hourly`, `Daily`) are returned
+/// unchanged. A bucket is flagged `filled` only when every point in it was filled.
+/// `points` must be sorted by timestamp.
+pub fn resample(
+    points: &[PricePoint],
+    interval: HistoryInterval,
+    method: ResampleMethod,
+) -> Vec<PricePoint> {
+    if interval.fetch_interval() == interval {
+        return points.to_vec();
+    }
+
+    let mut buckets: Vec<(DateTime<Utc>, Vec<f64>, bool)> = Vec::new();
+    for point in points {
+        let start = bucket_start(point.timestamp, interval);
+        match buckets.last_mut() {
+            Some((bucket, prices, filled)) if *bucket == start => {
+                prices.push(point.price);
+                *filled &= point.filled;
+            }
+            _ => buckets.push((start, vec![point.price], point.filled)),
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(timestamp, prices, filled)| {
+            let price = match method {
+                ResampleMethod::Last => prices[prices.len() - 1],
+                ResampleMethod::Mean => prices.iter().sum::<f64>() / prices.len() as f64,
+            };
+            PricePoint {
+                timestamp,
+                price,
+                filled,
+            }
+        })
+        .collect()
+}
+
+/// How `fill_gaps` completes missing days.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FillMethod {
+    /// Leave gaps as they are.
+    #[default]
+    None,
+    /// Carry the last real price forward.
+    Previous,
+    /// Draw a straight line between the prices on either side of the gap.
+    Interpolate,
+}
+
+/// Most synthetic days `fill_gaps` adds inside a single gap.
+pub const MAX_FILLED_DAYS: i64 = 7;
+
+/// Output of `fill_gaps`.
+#[derive(Debug, Clone)]
+pub struct GapFill {
+    pub points: Vec<PricePoint>,
+    /// Gaps longer than `MAX_FILLED_DAYS` days; only their first days were filled.
+    pub truncated_gaps: usize,
+}
+
+/// Complete a price series to a daily grid anchored at its first point.
+///
+/// Only gaps between two real points are filled, so a series that starts late keeps its
+/// leading gap. Synthetic points are flagged `filled`. `points` must be sorted by timestamp.
+pub fn fill_gaps(points: &[PricePoint], method: FillMethod) -> GapFill {
+    let mut filled = Vec::with_capacity(points.len());
+    let mut truncated_gaps = 0;
+    for (idx, point) in points.iter().enumerate() {
+        if method != FillMethod::None
+            && let Some(prev) = idx.checked_sub(1).map(|i| &points[i])
+        {
+            let span = point.timestamp - prev.timestamp;
+            let missing = span.num_days() - 1;
+            if missing > MAX_FILLED_DAYS {
+                truncated_gaps += 1;
+            }
+            for day in 1..=missing.min(MAX_FILLED_DAYS) {
+                let offset = Duration::days(day);
+                let price = match method {
+                    FillMethod::Interpolate => {
+                        let fraction = offset.num_seconds() as f64 / span.num_seconds() as f64;
+                        prev.price + (point.price - prev.price) * fraction
+                    }
+                    _ => prev.price,
+                };
+                filled.push(PricePoint {
+                    timestamp: prev.timestamp + offset,
+                    price,
+                    filled: true,
+                });
+            }
+        }
+        filled.push(point.clone());
+    }
+
+    GapFill {
+        points: filled,
+        truncated_gaps,
+    }
+}
+
+fn bucket_start(ts: DateTime<Utc>, interval: HistoryInterval) -> DateTime<Utc> {
+    let midnight = ts.date_naive().and_time(chrono::NaiveTime::MIN).and_utc();
+    match interval {
+        HistoryInterval::FourHour => midnight + Duration::hours(i64::from(ts.hour() / 4 * 4)),
+        HistoryInterval::Weekly => {
+            midnight - Duration::days(i64::from(ts.weekday().num_days_from_monday()))
+        }
+        HistoryInterval::Monthly => midnight - Duration::days(i64::from(ts.day0())),
+        _ => ts,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_pair_splits_only_on_fiat_suffix() {
+        assert_eq!(parse_pair("btc/eur"), Some(("btc", "EUR".to_string())));
+        assert_eq!(parse_pair(" ETH/gbp "), Some(("ETH", "GBP".to_string())));
+        assert_eq!(parse_pair("BRK/B"), None);
+        assert_eq!(parse_pair("/usd"), None);
+        assert_eq!(parse_pair("btc"), None);
+    }
+
+    #[test]
+    fn parse_invocation_collects_every_leading_source() {
+        let request = parse_invocation(
+            &args(&["100usd", "50eur", "$20", "btc", "gbp", "eth"]),
+            false,
+        )
+        .unwrap();
+        let sources: Vec<(f64, &str)> = request
+            .sources
+            .iter()
+            .map(|source| (source.amount, source.currency.as_str()))
+            .collect();
+        assert_eq!(sources, vec![(100.0, "USD"), (50.0, "EUR"), (20.0, "USD")]);
+        assert_eq!(request.fiat_targets, vec!["gbp"]);
+        assert_eq!(request.crypto_targets, vec!["btc", "eth"]);
+        assert_eq!(request.source_currencies(), vec!["USD", "EUR"]);
+    }
+
+    #[test]
+    fn parse_invocation_stops_at_first_target() {
+        // An amount after a target is a target, not another source.
+        let request = parse_invocation(&args(&["100usd", "btc", "50eur"]), false).unwrap();
+        assert_eq!(request.sources.len(), 1);
+        assert_eq!(request.crypto_targets, vec!["btc", "50eur"]);
+
+        let bare = parse_invocation(&args(&["100usd", "50eur"]), false).unwrap();
+        assert!(!bare.has_targets());
+
+        assert!(parse_invocation(&args(&["btc", "100usd"]), false).is_none());
+        assert!(parse_invocation(&args(&["1,5eur", "btc"]), true).is_some());
+    }
+
     #[test]
     fn parse_basic_cases() {
         let fa = parse_fiat_amount("3.5EUR").unwrap();
@@ -187,6 +670,81 @@ mod tests {
     #[test]
     fn rejects_no_number() {
         assert!(parse_fiat_amount("EUR").is_none());
+        assert!(parse_fiat_amount("$").is_none());
+    }
+
+    #[test]
+    fn parse_group_separators() {
+        assert_eq!(parse_fiat_amount("1,000usd").unwrap().amount, 1000.0);
+        assert_eq!(parse_fiat_amount("1_000eur").unwrap().amount, 1000.0);
+        assert_eq!(
+            parse_fiat_amount("12,345,678.5usd").unwrap().amount,
+            12_345_678.5
+        );
+        assert!(parse_fiat_amount("1,00usd").is_none());
+        assert!(parse_fiat_amount(",100usd").is_none());
+        assert!(parse_fiat_amount("1.5.0usd").is_none());
+    }
+
+    #[test]
+    fn parse_leading_currency_sign() {
+        let fa = parse_fiat_amount("$100").unwrap();
+        assert_eq!((fa.amount, fa.currency.as_str()), (100.0, "USD"));
+        let fa = parse_fiat_amount("€1,250.75").unwrap();
+        assert_eq!((fa.amount, fa.currency.as_str()), (1250.75, "EUR"));
+        assert_eq!(parse_fiat_amount("£42").unwrap().currency, "GBP");
+        assert_eq!(parse_fiat_amount("¥5000").unwrap().currency, "JPY");
+        assert!(parse_fiat_amount("$100usd").is_none());
+        assert!(parse_fiat_amount("$-5").is_none());
+    }
+
+    #[test]
+    fn parse_space_between_number_and_code() {
+        let fa = parse_fiat_amount("100 usd").unwrap();
+        assert_eq!((fa.amount, fa.currency.as_str()), (100.0, "USD"));
+        assert_eq!(parse_fiat_amount(" 2,500 eur ").unwrap().amount, 2500.0);
+        assert!(parse_fiat_amount("1 inch").is_none());
+        assert!(parse_fiat_amount("1 000usd").is_none());
+    }
+
+    #[test]
+    fn decimal_comma_is_opt_in() {
+        assert!(parse_fiat_amount("1,5eur").is_none());
+        assert_eq!(parse_fiat_amount_in("1,5eur", true).unwrap().amount, 1.5);
+        assert_eq!(
+            parse_fiat_amount_in("1.234,56eur", true).unwrap().amount,
+            1234.56
+        );
+        assert!(parse_fiat_amount_in("1.5eur", true).is_none());
+    }
+
+    #[test]
+    fn value_positions_totals_rows_in_the_requested_currency() {
+        let price = |symbol: &str, value: f64, currency: &str| CoinPrice {
+            symbol: symbol.to_string(),
+            price: value,
+            currency: currency.to_string(),
+            ..Default::default()
+        };
+        let prices = [
+            price("BTC", 60_000.0, "USD"),
+            price("ETH", 3_000.0, "USD"),
+            price("VOD.L", 70.0, "GBp"),
+        ];
+        let amounts = [
+            ("btc".to_string(), 0.5),
+            ("eth".to_string(), 3.0),
+            ("vod.l".to_string(), 100.0),
+            ("doge".to_string(), 1000.0),
+        ];
+
+        let portfolio = value_positions(&prices, &amounts, "usd");
+        assert_eq!(portfolio.currency, "USD");
+        assert_eq!(portfolio.total, 39_000.0);
+        assert_eq!(portfolio.excluded, ["VOD.L"]);
+        assert_eq!(portfolio.position("BTC").unwrap().value, Some(30_000.0));
+        assert_eq!(portfolio.position("VOD.L").unwrap().value, Some(7_000.0));
+        assert_eq!(portfolio.position("doge").unwrap().value, None);
     }
 
     #[test]
@@ -234,4 +792,218 @@ mod tests {
         assert!(parse_crypto_amount("0BTC").is_none());
         assert!(parse_crypto_amount("-1ETH").is_none());
     }
+
+    fn hourly_series(start: DateTime<Utc>, hours: i64) -> Vec<PricePoint> {
+        (0..hours)
+            .map(|i| PricePoint {
+                timestamp: start + Duration::hours(i),
+                price: i as f64,
+                filled: false,
+            })
+            .collect()
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn resample_four_hour_buckets_align_to_midnight() {
+        // Starts at 02:00, so the first bucket only holds 02:00 and 03:00.
+        let points = hourly_series(utc(2024, 1, 1, 2), 24);
+        let last = resample(&points, HistoryInterval::FourHour, ResampleMethod::Last);
+        let mean = resample(&points, HistoryInterval::FourHour, ResampleMethod::Mean);
+
+        assert_eq!(last.len(), 7);
+        assert_eq!(last[0].timestamp, utc(2024, 1, 1, 0));
+        assert_eq!(last[0].price, 1.0);
+        assert_eq!(last[1].timestamp, utc(2024, 1, 1, 4));
+        assert_eq!(last[1].price, 5.0);
+        assert_eq!(mean[1].price, 3.5);
+        assert_eq!(last[6].timestamp, utc(2024, 1, 2, 0));
+        assert_eq!(last[6].price, 23.0);
+    }
+
+    #[test]
+    fn resample_weekly_buckets_start_on_monday() {
+        // 2024-01-03 is a Wednesday; 14 days of hourly data span three ISO weeks.
+        let points = hourly_series(utc(2024, 1, 3, 0), 14 * 24);
+        let weekly = resample(&points, HistoryInterval::Weekly, ResampleMethod::Last);
+
+        let starts: Vec<_> = weekly.iter().map(|p| p.timestamp).collect();
+        assert_eq!(
+            starts,
+            vec![utc(2024, 1, 1, 0), utc(2024, 1, 8, 0), utc(2024, 1, 15, 0)]
+        );
+        // Last hour before Monday 2024-01-08 00:00 is 5 days in.
+        assert_eq!(weekly[0].price, (5 * 24 - 1) as f64);
+        assert_eq!(weekly[2].price, (14 * 24 - 1) as f64);
+    }
+
+    #[test]
+    fn resample_monthly_buckets_start_on_the_first() {
+        let points = hourly_series(utc(2024, 1, 30, 0), 4 * 24);
+        let monthly = resample(&points, HistoryInterval::Monthly, ResampleMethod::Mean);
+
+        assert_eq!(monthly.len(), 2);
+        assert_eq!(monthly[0].timestamp, utc(2024, 1, 1, 0));
+        assert_eq!(monthly[1].timestamp, utc(2024, 2, 1, 0));
+        // January holds hours 0..=47.
+        assert_eq!(monthly[0].price, 23.5);
+    }
+
+    #[test]
+    fn resample_leaves_native_intervals_untouched() {
+        let points = hourly_series(utc(2024, 1, 1, 0), 5);
+        let same = resample(&points, HistoryInterval::Hourly, ResampleMethod::Mean);
+        assert_eq!(same.len(), 5);
+        assert_eq!(same[4].timestamp, points[4].timestamp);
+    }
+
+    fn daily(points: &[(u32, f64)]) -> Vec<PricePoint> {
+        points
+            .iter()
+            .map(|&(day, price)| PricePoint {
+                timestamp: utc(2024, 1, day, 21),
+                price,
+                filled: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fill_gaps_carries_previous_close_over_weekend() {
+        // Friday the 5th, then Monday the 8th.
+        let result = fill_gaps(&daily(&[(5, 100.0), (8, 106.0)]), FillMethod::Previous);
+
+        let summary: Vec<(DateTime<Utc>, f64, bool)> = result
+            .points
+            .iter()
+            .map(|p| (p.timestamp, p.price, p.filled))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (utc(2024, 1, 5, 21), 100.0, false),
+                (utc(2024, 1, 6, 21), 100.0, true),
+                (utc(2024, 1, 7, 21), 100.0, true),
+                (utc(2024, 1, 8, 21), 106.0, false),
+            ]
+        );
+        assert_eq!(result.truncated_gaps, 0);
+
+        let real = serde_json::to_value(&result.points[0]).unwrap();
+        let synthetic = serde_json::to_value(&result.points[1]).unwrap();
+        assert!(real.get("filled").is_none());
+        assert_eq!(synthetic["filled"], true);
+    }
+
+    #[test]
+    fn fill_gaps_interpolates_linearly() {
+        let result = fill_gaps(&daily(&[(5, 100.0), (8, 106.0)]), FillMethod::Interpolate);
+        let prices: Vec<f64> = result.points.iter().map(|p| p.price).collect();
+        assert_eq!(prices, vec![100.0, 102.0, 104.0, 106.0]);
+    }
+
+    #[test]
+    fn fill_gaps_caps_long_gaps_and_keeps_leading_gap() {
+        let points = daily(&[(3, 10.0), (20, 20.0)]);
+        let result = fill_gaps(&points, FillMethod::Previous);
+
+        assert_eq!(
+            result.points.first().unwrap().timestamp,
+            points[0].timestamp
+        );
+        assert_eq!(result.points.len(), 2 + MAX_FILLED_DAYS as usize);
+        assert_eq!(result.truncated_gaps, 1);
+
+        let untouched = fill_gaps(&points, FillMethod::None);
+        assert_eq!(untouched.points.len(), 2);
+        assert_eq!(untouched.truncated_gaps, 0);
+    }
+
+    #[test]
+    fn cagr_compounds_over_fractional_years() {
+        // Doubling over two years: sqrt(2) - 1.
+        let doubled = cagr_pct(1000.0, 2000.0, 2.0).unwrap();
+        assert!((doubled - (2.0_f64.sqrt() - 1.0) * 100.0).abs() < 1e-9);
+        // Halving over one year is -50%.
+        assert!((cagr_pct(1000.0, 500.0, 1.0).unwrap() + 50.0).abs() < 1e-9);
+        // Six months at +21% annualizes to 1.21^2 - 1.
+        assert!((cagr_pct(100.0, 121.0, 0.5).unwrap() - 46.41).abs() < 1e-9);
+        assert_eq!(cagr_pct(0.0, 100.0, 1.0), None);
+        assert_eq!(cagr_pct(100.0, 100.0, 0.0), None);
+    }
+
+    #[test]
+    fn lump_sum_values_units_at_current_price() {
+        let bought = PricePoint {
+            timestamp: utc(2021, 5, 1, 0),
+            price: 50_000.0,
+            filled: false,
+        };
+        let current = CoinPrice {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            price: 75_000.0,
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            // 1461 days: exactly four years of 365.25 days.
+            timestamp: utc(2025, 5, 1, 0),
+            ..CoinPrice::default()
+        };
+        let investment = lump_sum(1000.0, &bought, &current);
+
+        assert_eq!(investment.units, 0.02);
+        assert_eq!(investment.value, 1500.0);
+        assert_eq!(investment.gain, 500.0);
+        assert_eq!(investment.gain_pct, 50.0);
+        let expected = (1.5_f64.powf(0.25) - 1.0) * 100.0;
+        assert!((investment.cagr_pct.unwrap() - expected).abs() < 1e-9);
+
+        let same_day = CoinPrice {
+            timestamp: utc(2021, 5, 1, 12),
+            ..current
+        };
+        assert_eq!(lump_sum(1000.0, &bought, &same_day).cagr_pct, None);
+    }
+
+    #[test]
+    fn closest_point_prefers_earlier_on_ties() {
+        let points = daily(&[(5, 100.0), (8, 106.0), (9, 107.0)]);
+        // Day 6 21:00 is one day after day 5 and two before day 8.
+        assert_eq!(
+            closest_point(&points, utc(2024, 1, 6, 21)).unwrap().price,
+            100.0
+        );
+        // Day 7 09:00 is 1.5 days from both day 5 and day 8; the earlier point wins.
+        assert_eq!(
+            closest_point(&points, utc(2024, 1, 7, 9)).unwrap().price,
+            100.0
+        );
+        assert_eq!(
+            closest_point(&points, utc(2024, 1, 20, 0)).unwrap().price,
+            107.0
+        );
+        assert!(closest_point(&[], utc(2024, 1, 1, 0)).is_none());
+    }
+
+    #[test]
+    fn closest_match_suggests_near_misses_only() {
+        let codes = ["usd", "eur", "gbp", "btc"];
+        assert_eq!(closest_match("USDD", codes), Some("usd"));
+        assert_eq!(closest_match("eru", codes), Some("eur"));
+        assert_eq!(closest_match("GPB", codes), Some("gbp"));
+        assert_eq!(closest_match("xyz", codes), None);
+        assert_eq!(closest_match("dogecoin", codes), None);
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(edit_distance("usd", "usd"), 0);
+        assert_eq!(edit_distance("usdd", "usd"), 1);
+        assert_eq!(edit_distance("eur", "eru"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
 }
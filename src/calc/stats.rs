@@ -0,0 +1,666 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{PriceHistory, PricePoint};
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Fewest shared days a series needs with the first one to enter a comparison.
+pub const MIN_COMPARISON_DAYS: usize = 3;
+
+/// Summary of one price series over the charted window.
+///
+/// Fields that need at least two points (three for volatility) are `None` for shorter series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryStats {
+    pub min: f64,
+    pub max: f64,
+    /// Where the series peaked; `max` with its timestamp.
+    pub high: Extreme,
+    /// Where the series bottomed out; `min` with its timestamp.
+    pub low: Extreme,
+    pub mean: f64,
+    pub start: f64,
+    pub end: f64,
+    pub change: Option<f64>,
+    pub change_pct: Option<f64>,
+    /// Deepest peak-to-trough fall, as a non-positive percentage.
+    pub max_drawdown_pct: Option<f64>,
+    /// Annualized standard deviation of log returns, in percent.
+    pub volatility_pct: Option<f64>,
+}
+
+/// The highest or lowest point of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Extreme {
+    pub value: f64,
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
+    pub timestamp: DateTime<Utc>,
+    /// Position in the series, for placing chart markers.
+    #[serde(skip)]
+    pub index: usize,
+}
+
+/// The `(high, low)` points of a series, or `None` when it is empty.
+///
+/// Ties resolve to the first occurrence.
+pub fn extremes(points: &[PricePoint]) -> Option<(Extreme, Extreme)> {
+    let extreme = |index: usize| Extreme {
+        value: points[index].price,
+        timestamp: points[index].timestamp,
+        index,
+    };
+    let mut high = 0;
+    let mut low = 0;
+    for (index, point) in points.iter().enumerate().skip(1) {
+        if point.price > points[high].price {
+            high = index;
+        }
+        if point.price < points[low].price {
+            low = index;
+        }
+    }
+    (!points.is_empty()).then(|| (extreme(high), extreme(low)))
+}
+
+/// Compute summary statistics, or `None` for an empty series. `points` must be sorted.
+pub fn summarize(points: &[PricePoint]) -> Option<HistoryStats> {
+    let first = points.first()?;
+    let last = points.last()?;
+    let (high, low) = extremes(points)?;
+    let prices: Vec<f64> = points.iter().map(|p| p.price).collect();
+    let mean = prices.iter().sum::<f64>() / prices.len() as f64;
+
+    let multi = points.len() > 1;
+    let change = multi.then_some(last.price - first.price);
+    let change_pct = change.and_then(|change| percent_of(change, first.price));
+
+    Some(HistoryStats {
+        min: low.value,
+        max: high.value,
+        high,
+        low,
+        mean,
+        start: first.price,
+        end: last.price,
+        change,
+        change_pct,
+        max_drawdown_pct: multi.then(|| max_drawdown_pct(&prices)),
+        volatility_pct: annualized_volatility_pct(points),
+    })
+}
+
+/// Deepest fall from a running peak, in percent (`-25.0` for a 25% drawdown).
+pub fn max_drawdown_pct(prices: &[f64]) -> f64 {
+    let mut peak = f64::NEG_INFINITY;
+    let mut worst: f64 = 0.0;
+    for &price in prices {
+        peak = peak.max(price);
+        if peak > 0.0 {
+            worst = worst.min((price - peak) / peak * 100.0);
+        }
+    }
+    worst
+}
+
+/// Sample standard deviation of log returns scaled to a year by the median point spacing.
+///
+/// `None` with fewer than two returns or when a price is not positive.
+pub fn annualized_volatility_pct(points: &[PricePoint]) -> Option<f64> {
+    if points.len() < 3 || points.iter().any(|p| p.price <= 0.0) {
+        return None;
+    }
+
+    let returns: Vec<f64> = points
+        .windows(2)
+        .map(|pair| (pair[1].price / pair[0].price).ln())
+        .collect();
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+
+    let mut spacings: Vec<i64> = points
+        .windows(2)
+        .map(|pair| (pair[1].timestamp - pair[0].timestamp).num_seconds())
+        .collect();
+    spacings.sort_unstable();
+    let median_spacing = spacings[spacings.len() / 2];
+    if median_spacing <= 0 {
+        return None;
+    }
+    let periods_per_year = SECONDS_PER_YEAR / median_spacing as f64;
+
+    Some(variance.sqrt() * periods_per_year.sqrt() * 100.0)
+}
+
+/// One row of the multi-asset comparison.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub symbol: String,
+    /// First-to-last change over the window, in percent.
+    pub return_pct: Option<f64>,
+    pub best_day_pct: Option<f64>,
+    pub worst_day_pct: Option<f64>,
+    pub max_drawdown_pct: f64,
+    /// Pearson correlation of daily returns against the first series (`1.0` for itself).
+    pub correlation: Option<f64>,
+}
+
+/// Rows for `histories` compared against the first one, plus the symbols left out.
+///
+/// Series are aligned by UTC day on their last price of the day; points on days the other
+/// series lacks are dropped. A series sharing fewer than `MIN_COMPARISON_DAYS` days with the
+/// first one is left out instead of getting a meaningless correlation.
+pub fn compare(histories: &[PriceHistory]) -> (Vec<Comparison>, Vec<String>) {
+    let Some(reference) = histories.first() else {
+        return (Vec::new(), Vec::new());
+    };
+    let reference_closes = daily_closes(&reference.points);
+
+    let mut rows = Vec::new();
+    let mut excluded = Vec::new();
+    for history in histories {
+        let closes = daily_closes(&history.points);
+        let shared: Vec<(f64, f64)> = reference_closes
+            .iter()
+            .filter_map(|(day, reference_price)| {
+                closes.get(day).map(|price| (*reference_price, *price))
+            })
+            .collect();
+        if shared.len() < MIN_COMPARISON_DAYS {
+            excluded.push(history.symbol.clone());
+            continue;
+        }
+
+        let reference_returns = returns_pct(shared.iter().map(|(r, _)| *r));
+        let own_returns = returns_pct(shared.iter().map(|(_, p)| *p));
+        let daily_returns = returns_pct(closes.values().copied());
+        let prices: Vec<f64> = history.points.iter().map(|p| p.price).collect();
+
+        rows.push(Comparison {
+            symbol: history.symbol.clone(),
+            return_pct: match (history.points.first(), history.points.last()) {
+                (Some(first), Some(last)) => percent_of(last.price - first.price, first.price),
+                _ => None,
+            },
+            best_day_pct: daily_returns.iter().copied().reduce(f64::max),
+            worst_day_pct: daily_returns.iter().copied().reduce(f64::min),
+            max_drawdown_pct: max_drawdown_pct(&prices),
+            correlation: pearson(&reference_returns, &own_returns),
+        });
+    }
+    (rows, excluded)
+}
+
+/// Fewest daily closes [`risk_metrics`] needs before its estimates mean much.
+pub const MIN_RELIABLE_DAYS: usize = 30;
+
+/// Return and risk metrics of one series, as printed by `pricr stats`.
+///
+/// Percentages are plain numbers (`2.5` for 2.5%). Fields needing more closes than the
+/// series has are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskMetrics {
+    pub symbol: String,
+    pub provider: String,
+    /// Daily closes the metrics are computed from.
+    pub days: usize,
+    pub annualized_volatility_pct: Option<f64>,
+    pub mean_daily_return_pct: Option<f64>,
+    pub best_day_pct: Option<f64>,
+    pub worst_day_pct: Option<f64>,
+    pub max_drawdown_pct: Option<f64>,
+    /// Annualized downside deviation against a zero target, in percent.
+    pub downside_deviation_pct: Option<f64>,
+}
+
+impl RiskMetrics {
+    /// Whether the series is too short for the estimates to be trusted.
+    pub fn is_unreliable(&self) -> bool {
+        self.days < MIN_RELIABLE_DAYS
+    }
+}
+
+/// Metrics from the daily closes `P_0..P_n` of `history` (last price of each UTC day).
+///
+/// With daily returns `r_i = P_i / P_(i-1) - 1` for `i = 1..n`:
+///
+/// - mean daily return: `m = Σ r_i / n`
+/// - annualized volatility: `sqrt(Σ (r_i - m)² / (n - 1)) * sqrt(N)`
+/// - downside deviation: `sqrt(Σ min(r_i, 0)² / n) * sqrt(N)`
+/// - best and worst day: the largest and smallest `r_i`
+/// - max drawdown: the deepest fall of a close below the highest close before it
+///
+/// `N` is the number of returns per year the series actually has, `n * 365.25 / span`
+/// with `span` the days between the first and last close, so a stock trading five days a
+/// week annualizes by about 261 and a coin trading every day by 365.25. Returns from a
+/// zero close are skipped. Volatility needs two returns, the others one.
+pub fn risk_metrics(history: &PriceHistory) -> RiskMetrics {
+    let closes = daily_closes(&history.points);
+    let prices: Vec<f64> = closes.values().copied().collect();
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter(|pair| pair[0].abs() > f64::EPSILON)
+        .map(|pair| pair[1] / pair[0] - 1.0)
+        .collect();
+    let span_days = match (closes.keys().next(), closes.keys().next_back()) {
+        (Some(first), Some(last)) => (*last - *first).num_days(),
+        _ => 0,
+    };
+
+    let mut metrics = RiskMetrics {
+        symbol: history.symbol.clone(),
+        provider: history.provider.clone(),
+        days: prices.len(),
+        annualized_volatility_pct: None,
+        mean_daily_return_pct: None,
+        best_day_pct: None,
+        worst_day_pct: None,
+        max_drawdown_pct: None,
+        downside_deviation_pct: None,
+    };
+    if returns.is_empty() || span_days <= 0 {
+        return metrics;
+    }
+
+    let n = returns.len() as f64;
+    let annualize = (n * 365.25 / span_days as f64).sqrt();
+    let mean = returns.iter().sum::<f64>() / n;
+    let downside = returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n;
+
+    metrics.mean_daily_return_pct = Some(mean * 100.0);
+    metrics.best_day_pct = returns.iter().copied().reduce(f64::max).map(|r| r * 100.0);
+    metrics.worst_day_pct = returns.iter().copied().reduce(f64::min).map(|r| r * 100.0);
+    metrics.max_drawdown_pct = Some(max_drawdown_pct(&prices));
+    metrics.downside_deviation_pct = Some(downside.sqrt() * annualize * 100.0);
+    if returns.len() > 1 {
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        metrics.annualized_volatility_pct = Some(variance.sqrt() * annualize * 100.0);
+    }
+    metrics
+}
+
+/// Fewest shared daily returns a symbol needs to enter the correlation matrix.
+pub const MIN_CORRELATION_OBSERVATIONS: usize = 10;
+
+/// Pearson correlations of daily log returns between every pair of symbols.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationMatrix {
+    /// Symbols in the matrix, in request order.
+    pub symbols: Vec<String>,
+    /// `values[i][j]` correlates `symbols[i]` with `symbols[j]`; `None` for a flat series.
+    pub values: Vec<Vec<Option<f64>>>,
+    /// Daily returns every symbol in the matrix shares.
+    pub observations: usize,
+    /// Symbols left out for sharing too few days with the others.
+    pub excluded: Vec<String>,
+}
+
+/// Correlation matrix of daily log returns `ln(P_t / P_(t-1))` across `histories`.
+///
+/// Series are aligned on the UTC days every included symbol has a close for, so each
+/// return spans the same two days for all of them. Symbols are added in request order;
+/// one that would leave fewer than `MIN_CORRELATION_OBSERVATIONS` shared returns, or has
+/// a non-positive close on a shared day, is excluded instead.
+pub fn correlation_matrix(histories: &[PriceHistory]) -> CorrelationMatrix {
+    let mut included: Vec<(&PriceHistory, BTreeMap<NaiveDate, f64>)> = Vec::new();
+    let mut shared_days: Option<Vec<NaiveDate>> = None;
+    let mut excluded = Vec::new();
+    for history in histories {
+        let closes = daily_closes(&history.points);
+        let days: Vec<NaiveDate> = match &shared_days {
+            Some(days) => days
+                .iter()
+                .filter(|day| closes.contains_key(day))
+                .copied()
+                .collect(),
+            None => closes.keys().copied().collect(),
+        };
+        let positive = days.iter().all(|day| closes[day] > 0.0)
+            && included
+                .iter()
+                .all(|(_, other)| days.iter().all(|day| other[day] > 0.0));
+        if days.len() <= MIN_CORRELATION_OBSERVATIONS || !positive {
+            excluded.push(history.symbol.clone());
+            continue;
+        }
+        shared_days = Some(days);
+        included.push((history, closes));
+    }
+
+    let days = shared_days.unwrap_or_default();
+    let returns: Vec<Vec<f64>> = included
+        .iter()
+        .map(|(_, closes)| {
+            days.windows(2)
+                .map(|pair| (closes[&pair[1]] / closes[&pair[0]]).ln())
+                .collect()
+        })
+        .collect();
+    let values = returns
+        .iter()
+        .map(|row| returns.iter().map(|col| pearson(row, col)).collect())
+        .collect();
+
+    CorrelationMatrix {
+        symbols: included
+            .iter()
+            .map(|(history, _)| history.symbol.clone())
+            .collect(),
+        values,
+        observations: days.len().saturating_sub(1),
+        excluded,
+    }
+}
+
+/// Last price of each UTC day.
+pub fn daily_closes(points: &[PricePoint]) -> BTreeMap<NaiveDate, f64> {
+    points
+        .iter()
+        .map(|p| (p.timestamp.date_naive(), p.price))
+        .collect()
+}
+
+/// Pearson correlation, or `None` for mismatched lengths, fewer than two values, or a flat input.
+pub fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    if xs.len() != ys.len() || xs.len() < 2 {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    let denom = (var_x * var_y).sqrt();
+    (denom > f64::EPSILON).then(|| cov / denom)
+}
+
+/// Percent change between consecutive prices; steps from a zero price are skipped.
+fn returns_pct(prices: impl Iterator<Item = f64>) -> Vec<f64> {
+    let prices: Vec<f64> = prices.collect();
+    prices
+        .windows(2)
+        .filter_map(|pair| percent_of(pair[1] - pair[0], pair[0]))
+        .collect()
+}
+
+fn percent_of(change: f64, base: f64) -> Option<f64> {
+    (base.abs() > f64::EPSILON).then(|| change / base * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(prices: &[f64]) -> Vec<PricePoint> {
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &price)| PricePoint {
+                timestamp: chrono::DateTime::from_timestamp(1_700_000_000 + i as i64 * 86_400, 0)
+                    .unwrap(),
+                price,
+                filled: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extremes_mark_the_first_of_equal_points() {
+        let points = daily(&[5.0, 9.0, 3.0, 9.0, 3.0]);
+        let (high, low) = extremes(&points).unwrap();
+
+        assert_eq!((high.value, high.index), (9.0, 1));
+        assert_eq!(high.timestamp, points[1].timestamp);
+        assert_eq!((low.value, low.index), (3.0, 2));
+        assert!(extremes(&[]).is_none());
+
+        let (high, low) = extremes(&daily(&[7.0])).unwrap();
+        assert_eq!((high.index, low.index), (0, 0));
+    }
+
+    #[test]
+    fn summarize_fixed_series() {
+        let stats = summarize(&daily(&[100.0, 120.0, 90.0, 110.0])).unwrap();
+
+        assert_eq!(stats.min, 90.0);
+        assert_eq!(stats.max, 120.0);
+        assert_eq!((stats.high.index, stats.low.index), (1, 2));
+        assert_eq!(stats.mean, 105.0);
+        assert_eq!(stats.start, 100.0);
+        assert_eq!(stats.end, 110.0);
+        assert_eq!(stats.change, Some(10.0));
+        assert!((stats.change_pct.unwrap() - 10.0).abs() < 1e-12);
+        assert!((stats.max_drawdown_pct.unwrap() + 25.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn volatility_annualizes_by_point_spacing() {
+        // Alternating +/-10% moves: log returns ln(1.1) and ln(1/1.1).
+        let points = daily(&[100.0, 110.0, 100.0, 110.0, 100.0]);
+        let r = 1.1_f64.ln();
+        // Mean is zero, so the sample variance is 4 r^2 / 3.
+        let expected = (4.0 * r * r / 3.0).sqrt() * 365.25_f64.sqrt() * 100.0;
+
+        let vol = annualized_volatility_pct(&points).unwrap();
+        assert!((vol - expected).abs() < 1e-9, "{vol} vs {expected}");
+    }
+
+    #[test]
+    fn short_series_have_no_change_or_volatility() {
+        let single = summarize(&daily(&[42.0])).unwrap();
+        assert_eq!(single.change, None);
+        assert_eq!(single.change_pct, None);
+        assert_eq!(single.max_drawdown_pct, None);
+        assert_eq!(single.volatility_pct, None);
+        assert_eq!(single.mean, 42.0);
+
+        assert_eq!(
+            annualized_volatility_pct(&daily(&[1.0, 2.0])),
+            None,
+            "one return has no sample deviation"
+        );
+        assert!(summarize(&[]).is_none());
+    }
+
+    #[test]
+    fn drawdown_is_zero_for_rising_series() {
+        assert_eq!(max_drawdown_pct(&[1.0, 2.0, 3.0]), 0.0);
+        assert_eq!(max_drawdown_pct(&[10.0, 5.0, 20.0, 15.0]), -50.0);
+    }
+
+    fn history(symbol: &str, start_day: i64, prices: &[f64]) -> PriceHistory {
+        let mut points = daily(prices);
+        for point in &mut points {
+            point.timestamp += chrono::Duration::days(start_day);
+        }
+        PriceHistory {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            currency: "USD".to_string(),
+            provider: "test".to_string(),
+            points,
+            events: Vec::new(),
+            granularity: None,
+        }
+    }
+
+    #[test]
+    fn compare_aligns_by_day_and_correlates_returns() {
+        let btc = history("BTC", 0, &[100.0, 110.0, 99.0, 108.9, 100.0]);
+        // Same moves, doubled prices: perfectly correlated.
+        let eth = history("ETH", 0, &[200.0, 220.0, 198.0, 217.8, 200.0]);
+        // Opposite moves, and starts a day late so the first BTC day is dropped.
+        let inv = history("INV", 1, &[50.0, 55.0, 50.0, 55.0]);
+
+        let (rows, excluded) = compare(&[btc, eth, inv]);
+        assert!(excluded.is_empty());
+        assert_eq!(rows.len(), 3);
+
+        assert!((rows[0].correlation.unwrap() - 1.0).abs() < 1e-12);
+        assert!((rows[1].correlation.unwrap() - 1.0).abs() < 1e-12);
+        assert!((rows[2].correlation.unwrap() + 1.0).abs() < 0.05);
+
+        assert!((rows[0].best_day_pct.unwrap() - 10.0).abs() < 1e-9);
+        assert!((rows[0].worst_day_pct.unwrap() + 10.0).abs() < 1e-9);
+        assert_eq!(rows[0].return_pct, Some(0.0));
+        assert!((rows[0].max_drawdown_pct + 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compare_excludes_series_with_too_little_overlap() {
+        let btc = history("BTC", 0, &[1.0, 2.0, 3.0, 4.0]);
+        let late = history("LATE", 2, &[5.0, 6.0, 7.0]);
+
+        let (rows, excluded) = compare(&[btc, late]);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(excluded, vec!["LATE".to_string()]);
+        assert!(
+            rows.iter()
+                .all(|row| row.correlation.is_some_and(f64::is_finite))
+        );
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("metric should be computed");
+        assert!((actual - expected).abs() < 1e-9, "{actual} vs {expected}");
+    }
+
+    #[test]
+    fn risk_metrics_match_hand_computed_daily_fixture() {
+        // Returns: +10%, -10%, 0%, +10% over four days, so N = 365.25.
+        let metrics = risk_metrics(&history("BTC", 0, &[100.0, 110.0, 99.0, 99.0, 108.9]));
+
+        assert_eq!(metrics.days, 5);
+        assert!(metrics.is_unreliable());
+        // m = 0.1 / 4 = 0.025.
+        assert_close(metrics.mean_daily_return_pct, 2.5);
+        // Squared deviations 0.075², 0.125², 0.025², 0.075² sum to 0.0275.
+        assert_close(
+            metrics.annualized_volatility_pct,
+            (0.0275_f64 / 3.0).sqrt() * 365.25_f64.sqrt() * 100.0,
+        );
+        // Only the -10% day counts: sqrt(0.01 / 4) = 0.05.
+        assert_close(
+            metrics.downside_deviation_pct,
+            0.05 * 365.25_f64.sqrt() * 100.0,
+        );
+        assert_close(metrics.best_day_pct, 10.0);
+        assert_close(metrics.worst_day_pct, -10.0);
+        assert_close(metrics.max_drawdown_pct, -10.0);
+    }
+
+    #[test]
+    fn risk_metrics_annualize_by_observed_trading_days() {
+        // Monday to Friday, then the next Monday: five returns over seven days.
+        let mut series = history("AAPL", 0, &[100.0, 102.0, 100.0, 101.0, 99.0, 100.0]);
+        series.points[5].timestamp += chrono::Duration::days(2);
+        let metrics = risk_metrics(&series);
+
+        let returns = [
+            0.02,
+            100.0 / 102.0 - 1.0,
+            0.01,
+            99.0 / 101.0 - 1.0,
+            1.0 / 99.0,
+        ];
+        let mean = returns.iter().sum::<f64>() / 5.0;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / 4.0;
+        let per_year = 5.0 * 365.25 / 7.0;
+        assert_close(
+            metrics.annualized_volatility_pct,
+            variance.sqrt() * f64::sqrt(per_year) * 100.0,
+        );
+        assert_close(metrics.mean_daily_return_pct, mean * 100.0);
+    }
+
+    #[test]
+    fn risk_metrics_need_enough_closes() {
+        let single = risk_metrics(&history("BTC", 0, &[42.0]));
+        assert_eq!(single.days, 1);
+        assert_eq!(single.mean_daily_return_pct, None);
+        assert_eq!(single.max_drawdown_pct, None);
+
+        let pair = risk_metrics(&history("BTC", 0, &[100.0, 90.0]));
+        assert_close(pair.worst_day_pct, -10.0);
+        assert_eq!(pair.annualized_volatility_pct, None);
+
+        let month: Vec<f64> = (0..MIN_RELIABLE_DAYS).map(|i| 100.0 + i as f64).collect();
+        assert!(!risk_metrics(&history("BTC", 0, &month)).is_unreliable());
+    }
+
+    #[test]
+    fn correlation_matrix_is_symmetric_over_shared_days() {
+        // Log returns: ln 2, ln 1/2, ... for A; B doubles A; C mirrors A.
+        let a: Vec<f64> = (0..12)
+            .map(|i| if i % 2 == 0 { 100.0 } else { 200.0 })
+            .collect();
+        let b: Vec<f64> = a.iter().map(|p| p * 2.0).collect();
+        let c: Vec<f64> = a.iter().map(|p| 20_000.0 / p).collect();
+        let mut d = a.clone();
+        d[3] = 150.0;
+        let matrix = correlation_matrix(&[
+            history("A", 0, &a),
+            history("B", 0, &b),
+            history("C", 0, &c),
+            history("D", 0, &d),
+        ]);
+
+        assert_eq!(matrix.symbols, vec!["A", "B", "C", "D"]);
+        assert_eq!(matrix.observations, 11);
+        assert!(matrix.excluded.is_empty());
+        for i in 0..4 {
+            assert!((matrix.values[i][i].unwrap() - 1.0).abs() < 1e-12);
+            for j in 0..4 {
+                assert_eq!(matrix.values[i][j], matrix.values[j][i]);
+            }
+        }
+        assert!((matrix.values[0][1].unwrap() - 1.0).abs() < 1e-12);
+        assert!((matrix.values[0][2].unwrap() + 1.0).abs() < 1e-12);
+
+        // D closes at 150 instead of 200 on day 3, so its returns into and out of
+        // that day are ln 1.5 and -ln 1.5 where A has ln 2 and -ln 2.
+        let ln2 = 2.0_f64.ln();
+        let xs: Vec<f64> = (0..11)
+            .map(|i| if i % 2 == 0 { ln2 } else { -ln2 })
+            .collect();
+        let mut ys = xs.clone();
+        ys[2] = 1.5_f64.ln();
+        ys[3] = -(1.5_f64.ln());
+        let expected = pearson(&xs, &ys).unwrap();
+        assert!((matrix.values[0][3].unwrap() - expected).abs() < 1e-12);
+        assert!(expected < 1.0);
+    }
+
+    #[test]
+    fn correlation_matrix_excludes_symbols_with_little_overlap() {
+        let long: Vec<f64> = (0..20).map(|i| 100.0 + (i % 3) as f64).collect();
+        // Starts 10 days late: only 10 shared days, 9 returns.
+        let late = history("LATE", 10, &long[..10]);
+        let short = history("SHORT", 0, &long[..5]);
+
+        let matrix = correlation_matrix(&[
+            history("BTC", 0, &long),
+            late,
+            history("ETH", 0, &long),
+            short,
+        ]);
+        assert_eq!(matrix.symbols, vec!["BTC", "ETH"]);
+        assert_eq!(matrix.excluded, vec!["LATE", "SHORT"]);
+        assert_eq!(matrix.observations, 19);
+    }
+
+    #[test]
+    fn pearson_rejects_flat_or_short_input() {
+        assert_eq!(pearson(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0]), None);
+        assert_eq!(pearson(&[1.0], &[1.0]), None);
+        assert_eq!(pearson(&[1.0, 2.0], &[1.0]), None);
+    }
+}
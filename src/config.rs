@@ -1,11 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
+use tracing::debug;
 
 use crate::error::{Error, Result};
+use crate::output::fmt::Locale;
+use crate::output::tz::DisplayTz;
+use crate::provider::coingecko::ApiTier;
+use crate::provider::{ChartRange, HistoryInterval, stooq};
 
 /// Default fiat currency for price display.
 pub const DEFAULT_CURRENCY: &str = "usd";
@@ -20,7 +25,32 @@ pub const CONFIG_FILE_NAME: &str = "pricr.toml";
 pub struct AppConfig {
     pub defaults: DefaultsConfig,
     pub coinmarketcap: CoinMarketCapConfig,
+    pub chart: ChartConfig,
+    pub display: DisplayConfig,
+    pub http: HttpConfig,
+    pub logging: LoggingConfig,
+    pub webhook: WebhookConfig,
+    /// Per-provider `[provider.<id>]` tables.
+    pub provider: ProviderSettings,
+    /// Named symbol groups, referenced as `@name`; members may name other groups.
+    pub groups: HashMap<String, Vec<String>>,
+    /// Older name for `[groups]`; a `[groups]` entry wins over one named the same here.
     pub watchlists: HashMap<String, Vec<String>>,
+    pub aliases: SymbolAliases,
+    /// Held amount per symbol, used by `--amount portfolio`.
+    pub portfolio: BTreeMap<String, f64>,
+}
+
+impl AppConfig {
+    /// `[watchlists]` and `[groups]` together, keyed by group name.
+    pub fn symbol_groups(&self) -> HashMap<String, Vec<String>> {
+        let mut groups = self.watchlists.clone();
+        for (name, members) in &self.groups {
+            groups.retain(|existing, _| !existing.eq_ignore_ascii_case(name));
+            groups.insert(name.clone(), members.clone());
+        }
+        groups
+    }
 }
 
 /// General defaults used when CLI flags are not provided.
@@ -29,6 +59,10 @@ pub struct AppConfig {
 pub struct DefaultsConfig {
     pub currency: Option<String>,
     pub provider_order: Option<Vec<String>>,
+    /// Symbols looked up when none are given on the command line.
+    pub symbols: Option<Vec<String>>,
+    /// Fixed decimal places for prices and amounts; adaptive when unset.
+    pub precision: Option<usize>,
 }
 
 /// CoinMarketCap provider-specific configuration.
@@ -38,6 +72,293 @@ pub struct CoinMarketCapConfig {
     pub api_key: Option<String>,
 }
 
+/// Smallest chart width (in terminal columns) that still renders readable axes.
+pub const MIN_CHART_WIDTH: u16 = 48;
+/// Smallest chart height (in terminal rows) that still renders readable axes.
+pub const MIN_CHART_HEIGHT: u16 = 12;
+
+/// Chart defaults; `--chart-width`, `--chart-height`, `--interval`, and `--sampling`
+/// override them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChartConfig {
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+    /// Window preset, spelled as for `--interval` (`1Y`, `YTD`, ...).
+    #[serde(deserialize_with = "chart_interval")]
+    pub interval: Option<ChartRange>,
+    /// Point density, spelled as for `--sampling` (`daily`, `weekly`, ...).
+    #[serde(deserialize_with = "chart_sampling")]
+    pub sampling: Option<HistoryInterval>,
+}
+
+impl ChartConfig {
+    /// Reject sizes the CLI flags would refuse.
+    fn validate(&self) -> std::result::Result<(), String> {
+        for (key, value, min) in [
+            ("width", self.width, MIN_CHART_WIDTH),
+            ("height", self.height, MIN_CHART_HEIGHT),
+        ] {
+            if let Some(value) = value
+                && value < min
+            {
+                return Err(format!(
+                    "invalid [chart] {key} {value}: expected at least {min}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn chart_interval<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<ChartRange>, D::Error> {
+    parse_key(deserializer, "[chart] interval")
+}
+
+fn chart_sampling<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<HistoryInterval>, D::Error> {
+    parse_key(deserializer, "[chart] sampling")
+}
+
+/// Read a string through the `FromStr` its CLI flag uses, naming `key` when the
+/// value is rejected.
+fn parse_key<'de, D, T>(deserializer: D, key: &str) -> std::result::Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: std::str::FromStr<Err = String>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(|err| {
+        serde::de::Error::custom(format!("invalid {} '{}': {}", key, value.trim(), err))
+    })
+}
+
+/// Number formatting for table output, from the `[display]` table.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// `en` (default), `de`, or `fr` separators and symbol placement.
+    pub locale: Option<Locale>,
+    pub group_digits: Option<bool>,
+    /// Show `$`/`€` symbols; `false` always shows the currency code.
+    pub currency_symbols: Option<bool>,
+    /// Bold percent changes whose magnitude exceeds this many percent.
+    pub highlight_change_pct: Option<f64>,
+    /// Time zone for displayed timestamps: `utc` (default), `local`, or an IANA name.
+    pub timezone: Option<DisplayTz>,
+    /// Quotes older than this many seconds show a yellow `--age` (default 900).
+    pub stale_after_secs: Option<u64>,
+    /// `false` hides provider credit lines such as `Data by CoinGecko`.
+    pub attribution: Option<bool>,
+}
+
+/// HTTP client settings shared by all providers.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Total per-request timeout in seconds.
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    /// Proxy URL for all provider requests; `HTTPS_PROXY` applies when unset.
+    pub proxy: Option<String>,
+    /// Maximum concurrent per-symbol requests to one provider.
+    pub max_concurrency: Option<usize>,
+    /// Replaces the default `pricr/<version>` `User-Agent`.
+    pub user_agent: Option<String>,
+    /// Contact URL appended to the `User-Agent` as `(+<url>)`.
+    pub contact: Option<String>,
+}
+
+/// Diagnostic log output; `--log-format` and `--log-file` take precedence.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: Option<LogFormat>,
+    /// Append logs to this file instead of stderr.
+    pub file: Option<PathBuf>,
+}
+
+/// Delivery settings for `--webhook`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Extra request headers, e.g. `Authorization = "Bearer ..."`.
+    pub headers: BTreeMap<String, String>,
+    /// Sign each payload with HMAC-SHA256 under this key.
+    pub secret: Option<String>,
+}
+
+/// Log line format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per event, including span timings.
+    Json,
+}
+
+/// Overrides for one provider from a `[provider.<id>]` table.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProviderConfig {
+    /// Replacement API root, e.g. a mirror or self-hosted proxy.
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// API plan for the key (`demo` or `pro`); only CoinGecko uses this.
+    pub tier: Option<ApiTier>,
+    /// Client-side request budget; `0` turns off the keyless CoinGecko default.
+    pub rate_limit_per_min: Option<u32>,
+    /// Fail instead of waiting longer than this for the rate limiter.
+    pub rate_limit_max_wait_secs: Option<u64>,
+    /// Exchange suffix for bare symbols (`us`, `de`, ...); only Stooq uses this.
+    pub default_market: Option<String>,
+    /// API credits the plan allows per calendar month, shown by `--quota`; only
+    /// CoinMarketCap uses this.
+    pub monthly_credits: Option<u64>,
+}
+
+/// All `[provider.<id>]` tables, keyed by provider id.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct ProviderSettings(BTreeMap<String, ProviderConfig>);
+
+impl ProviderSettings {
+    /// Settings for `provider_id`, matched case-insensitively.
+    pub fn get(&self, provider_id: &str) -> Option<&ProviderConfig> {
+        self.0
+            .iter()
+            .find(|(id, _)| id.eq_ignore_ascii_case(provider_id))
+            .map(|(_, cfg)| cfg)
+    }
+
+    /// Configured base URL for `provider_id`, or `default` when none is set.
+    pub fn base_url<'a>(&'a self, provider_id: &str, default: &'a str) -> &'a str {
+        self.get(provider_id)
+            .and_then(|cfg| cfg.base_url.as_deref())
+            .map(|url| url.trim_end_matches('/'))
+            .unwrap_or(default)
+    }
+
+    /// Configured API key for `provider_id`, if any.
+    pub fn api_key(&self, provider_id: &str) -> Option<&str> {
+        self.get(provider_id).and_then(|cfg| cfg.api_key.as_deref())
+    }
+
+    /// Configured default market for `provider_id`, if any.
+    pub fn default_market(&self, provider_id: &str) -> Option<&str> {
+        self.get(provider_id)
+            .and_then(|cfg| cfg.default_market.as_deref())
+    }
+
+    /// Configured monthly API credit allowance for `provider_id`, if any.
+    pub fn monthly_credits(&self, provider_id: &str) -> Option<u64> {
+        self.get(provider_id).and_then(|cfg| cfg.monthly_credits)
+    }
+
+    /// Set the API key for `provider_id`, replacing any configured one.
+    pub fn set_api_key(&mut self, provider_id: &str, api_key: String) {
+        self.entry_mut(provider_id).api_key = Some(api_key);
+    }
+
+    /// Set the base URL for `provider_id`, replacing any configured one.
+    pub fn set_base_url(&mut self, provider_id: &str, base_url: String) {
+        self.entry_mut(provider_id).base_url = Some(base_url);
+    }
+
+    /// Set the default market for `provider_id`, replacing any configured one.
+    pub fn set_default_market(&mut self, provider_id: &str, market: String) {
+        self.entry_mut(provider_id).default_market = Some(market);
+    }
+
+    fn entry_mut(&mut self, provider_id: &str) -> &mut ProviderConfig {
+        let key = self
+            .0
+            .keys()
+            .find(|id| id.eq_ignore_ascii_case(provider_id))
+            .cloned()
+            .unwrap_or_else(|| provider_id.to_string());
+        self.0.entry(key).or_default()
+    }
+
+    /// Reject base URLs that are not absolute `http(s)` URLs and unknown markets.
+    pub(crate) fn validate(&mut self) -> std::result::Result<(), String> {
+        for (id, cfg) in &mut self.0 {
+            if let Some(market) = cfg.default_market.as_deref() {
+                let market = stooq::parse_market(market)
+                    .map_err(|err| format!("invalid [provider.{id}] default_market: {err}"))?;
+                cfg.default_market = Some(market);
+            }
+            let Some(base_url) = cfg.base_url.as_deref() else {
+                continue;
+            };
+            let valid = reqwest::Url::parse(base_url)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+            if !valid {
+                return Err(format!(
+                    "invalid [provider.{id}] base_url '{base_url}': expected an http:// or https:// URL"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Provider-specific ids for user symbols, from the `[aliases]` table.
+///
+/// Example: `rndr = { coingecko = "render-token", cmc = "RNDR" }`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct SymbolAliases(BTreeMap<String, BTreeMap<String, String>>);
+
+impl SymbolAliases {
+    /// Look up the id a provider should use for `symbol`, if one is configured.
+    pub fn resolve(&self, provider_id: &str, symbol: &str) -> Option<&str> {
+        let symbol = symbol.trim();
+        self.0
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(symbol))
+            .and_then(|(_, ids)| {
+                ids.iter()
+                    .find(|(id, _)| id.eq_ignore_ascii_case(provider_id))
+                    .map(|(_, value)| value.as_str())
+            })
+    }
+
+    /// Drop entries for providers not in `known_ids`, logging each one at debug level.
+    pub fn retain_providers(&mut self, known_ids: &[&str]) {
+        for (alias, ids) in self.0.iter_mut() {
+            ids.retain(|provider_id, _| {
+                let known = known_ids
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(provider_id));
+                if !known {
+                    debug!(alias = %alias, provider = %provider_id, "ignoring alias for unknown provider");
+                }
+                known
+            });
+        }
+        self.0.retain(|_, ids| !ids.is_empty());
+    }
+
+    /// Iterate `(symbol, provider id, provider symbol)` entries in sorted order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        self.0.iter().flat_map(|(alias, ids)| {
+            ids.iter().map(move |(provider_id, value)| {
+                (alias.as_str(), provider_id.as_str(), value.as_str())
+            })
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 /// Resolve the configuration file path based on XDG conventions.
 pub fn config_path() -> Option<PathBuf> {
     if let Ok(xdg_config_home) = std::env::var("XDG_CONFIG_HOME")
@@ -64,7 +385,7 @@ pub fn load() -> Result<AppConfig> {
         }
     };
 
-    parse(&raw).map_err(|err| parse_config_error(&path, err))
+    parse_and_validate(&raw, &path)
 }
 
 /// Load config from an explicit path.
@@ -72,7 +393,22 @@ pub fn load() -> Result<AppConfig> {
 /// Unlike [`load`], this returns an error when the file is missing.
 pub fn load_from_path(path: &Path) -> Result<AppConfig> {
     let raw = fs::read_to_string(path).map_err(|err| read_config_error(path, err))?;
-    parse(&raw).map_err(|err| parse_config_error(path, err))
+    parse_and_validate(&raw, path)
+}
+
+fn parse_and_validate(raw: &str, path: &Path) -> Result<AppConfig> {
+    let mut cfg = parse(raw).map_err(|err| parse_config_error(path, err))?;
+    cfg.provider
+        .validate()
+        .and_then(|()| cfg.chart.validate())
+        .map_err(|message| {
+            Error::Config(format!(
+                "invalid config file '{}': {}",
+                path.display(),
+                message
+            ))
+        })?;
+    Ok(cfg)
 }
 
 fn parse(raw: &str) -> std::result::Result<AppConfig, toml::de::Error> {
@@ -104,8 +440,227 @@ mod tests {
         let cfg = parse("").unwrap();
         assert!(cfg.defaults.currency.is_none());
         assert!(cfg.defaults.provider_order.is_none());
+        assert!(cfg.defaults.symbols.is_none());
         assert!(cfg.coinmarketcap.api_key.is_none());
+        assert!(cfg.chart.width.is_none());
+        assert!(cfg.chart.height.is_none());
+        assert!(cfg.http.timeout_secs.is_none());
+        assert!(cfg.http.proxy.is_none());
+        assert!(cfg.provider.get("coingecko").is_none());
         assert!(cfg.watchlists.is_empty());
+        assert!(cfg.aliases.is_empty());
+    }
+
+    #[test]
+    fn parse_http_section() {
+        let cfg = parse(
+            r#"
+            [http]
+            timeout_secs = 5
+            connect_timeout_secs = 2
+            proxy = "http://proxy.internal:3128"
+            max_concurrency = 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(cfg.http.timeout_secs, Some(5));
+        assert_eq!(cfg.http.connect_timeout_secs, Some(2));
+        assert_eq!(
+            cfg.http.proxy.as_deref(),
+            Some("http://proxy.internal:3128")
+        );
+        assert_eq!(cfg.http.max_concurrency, Some(2));
+    }
+
+    #[test]
+    fn parse_aliases_and_resolve_case_insensitively() {
+        let cfg = parse(
+            r#"
+            [aliases]
+            rndr = { coingecko = "render-token", cmc = "RNDR" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            cfg.aliases.resolve("coingecko", "RNDR"),
+            Some("render-token")
+        );
+        assert_eq!(cfg.aliases.resolve("CMC", "rndr"), Some("RNDR"));
+        assert_eq!(cfg.aliases.resolve("yahoo", "rndr"), None);
+        assert_eq!(cfg.aliases.resolve("coingecko", "btc"), None);
+    }
+
+    #[test]
+    fn retain_providers_drops_unknown_provider_entries() {
+        let mut aliases = parse(
+            r#"
+            [aliases]
+            rndr = { coingecko = "render-token", kraken = "RNDR" }
+            foo = { binance = "FOO" }
+            "#,
+        )
+        .unwrap()
+        .aliases;
+
+        aliases.retain_providers(&["coingecko", "cmc"]);
+
+        let entries: Vec<_> = aliases.entries().collect();
+        assert_eq!(entries, vec![("rndr", "coingecko", "render-token")]);
+    }
+
+    #[test]
+    fn parse_provider_sections() {
+        let mut cfg = parse(
+            r#"
+            [provider.coingecko]
+            base_url = "https://cg-mirror.example.com/api/v3"
+
+            [provider.CMC]
+            api_key = "abc123"
+            "#,
+        )
+        .unwrap();
+
+        assert!(cfg.provider.validate().is_ok());
+        assert_eq!(
+            cfg.provider.base_url("coingecko", "https://default"),
+            "https://cg-mirror.example.com/api/v3"
+        );
+        assert_eq!(
+            cfg.provider.base_url("yahoo", "https://default"),
+            "https://default"
+        );
+        assert_eq!(cfg.provider.api_key("cmc"), Some("abc123"));
+    }
+
+    #[test]
+    fn parse_provider_tier_and_override_api_key() {
+        let mut settings = parse(
+            r#"
+            [provider.CoinGecko]
+            api_key = "CG-config"
+            tier = "pro"
+            "#,
+        )
+        .unwrap()
+        .provider;
+
+        assert_eq!(
+            settings.get("coingecko").and_then(|cfg| cfg.tier),
+            Some(ApiTier::Pro)
+        );
+
+        settings.set_api_key("coingecko", "CG-cli".to_string());
+        settings.set_api_key("cmc", "cmc-cli".to_string());
+        assert_eq!(settings.api_key("coingecko"), Some("CG-cli"));
+        assert_eq!(settings.api_key("cmc"), Some("cmc-cli"));
+        assert!(parse("[provider.coingecko]\ntier = \"gold\"\n").is_err());
+    }
+
+    #[test]
+    fn provider_base_url_without_scheme_is_rejected() {
+        for base_url in [
+            "cg-mirror.example.com/api",
+            "localhost:8080",
+            "ftp://mirror",
+        ] {
+            let mut cfg = parse(&format!(
+                "[provider.coingecko]\nbase_url = \"{base_url}\"\n"
+            ))
+            .unwrap();
+            let err = cfg.provider.validate().unwrap_err();
+            assert!(err.contains("[provider.coingecko]"), "{err}");
+        }
+    }
+
+    #[test]
+    fn provider_default_market_is_normalized_and_validated() {
+        let mut cfg = parse("[provider.stooq]\ndefault_market = \".DE\"\n").unwrap();
+        assert!(cfg.provider.validate().is_ok());
+        assert_eq!(cfg.provider.default_market("stooq"), Some("de"));
+
+        cfg.provider.set_default_market("stooq", "jp".to_string());
+        assert_eq!(cfg.provider.default_market("Stooq"), Some("jp"));
+
+        let mut cfg = parse("[provider.stooq]\ndefault_market = \"xx\"\n").unwrap();
+        let err = cfg.provider.validate().unwrap_err();
+        assert!(err.contains("default_market"), "{err}");
+        assert!(err.contains("us, uk, de"), "{err}");
+    }
+
+    #[test]
+    fn parse_display_section() {
+        let cfg = parse(
+            r#"
+            [display]
+            locale = "de"
+            group_digits = false
+            currency_symbols = false
+            highlight_change_pct = 5.0
+            timezone = "Asia/Tokyo"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(cfg.display.locale, Some(Locale::De));
+        assert_eq!(
+            cfg.display.timezone,
+            Some(DisplayTz::Named(chrono_tz::Asia::Tokyo))
+        );
+        assert_eq!(cfg.display.group_digits, Some(false));
+        assert_eq!(cfg.display.currency_symbols, Some(false));
+        assert_eq!(cfg.display.highlight_change_pct, Some(5.0));
+        assert!(parse("[display]\nlocale = \"xx\"\n").is_err());
+        assert!(parse("[display]\ntimezone = \"Nowhere/Land\"\n").is_err());
+    }
+
+    #[test]
+    fn parse_chart_dimensions() {
+        let cfg = parse(
+            r#"
+            [chart]
+            width = 120
+            height = 30
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(cfg.chart.width, Some(120));
+        assert_eq!(cfg.chart.height, Some(30));
+    }
+
+    #[test]
+    fn parse_chart_window_with_the_cli_spellings() {
+        let cfg = parse("[chart]\ninterval = \"1y\"\nsampling = \"Daily\"\n").unwrap();
+        assert_eq!(cfg.chart.interval, Some(ChartRange::OneYear));
+        assert_eq!(cfg.chart.sampling, Some(HistoryInterval::Daily));
+
+        let err = parse("[chart]\ninterval = \"2Y\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(
+                "invalid [chart] interval '2Y': expected one of 1D, 5D, 1M, 6M, YTD, 1Y, 5Y, ALL"
+            ),
+            "{err}"
+        );
+        let err = parse("[chart]\nsampling = \"minutely\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid [chart] sampling 'minutely': expected one of auto, hourly, 4h"),
+            "{err}"
+        );
+
+        let err = parse_and_validate("[chart]\nwidth = 10\n", Path::new("pricr.toml"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid [chart] width 10: expected at least 48"),
+            "{err}"
+        );
     }
 
     #[test]
@@ -127,11 +682,13 @@ mod tests {
             r#"
             [defaults]
             currency = "eur"
+            precision = 4
             "#,
         )
         .unwrap();
 
         assert_eq!(cfg.defaults.currency.as_deref(), Some("eur"));
+        assert_eq!(cfg.defaults.precision, Some(4));
         assert!(cfg.defaults.provider_order.is_none());
     }
 
@@ -155,6 +712,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_default_symbols() {
+        let cfg = parse(
+            r#"
+            [defaults]
+            symbols = ["btc", "eth", "@metals"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            cfg.defaults.symbols,
+            Some(vec![
+                "btc".to_string(),
+                "eth".to_string(),
+                "@metals".to_string()
+            ])
+        );
+    }
+
     #[test]
     fn parse_watchlists() {
         let cfg = parse(
@@ -179,4 +756,26 @@ mod tests {
             Some(&vec!["GC=F".to_string(), "SI=F".to_string()])
         );
     }
+
+    #[test]
+    fn groups_override_watchlists_of_the_same_name() {
+        let cfg = parse(
+            r#"
+            [groups]
+            defi = ["uni", "aave", "mkr"]
+            Metals = ["GC=F"]
+
+            [watchlists]
+            metals = ["GC=F", "SI=F"]
+            energy = ["CL=F"]
+            "#,
+        )
+        .unwrap();
+
+        let groups = cfg.symbol_groups();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["defi"], ["uni", "aave", "mkr"]);
+        assert_eq!(groups["Metals"], ["GC=F"]);
+        assert_eq!(groups["energy"], ["CL=F"]);
+    }
 }
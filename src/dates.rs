@@ -0,0 +1,200 @@
+//! Date arguments for `--start-date`, `--end-date`, and `--since`.
+//!
+//! Besides `YYYY-MM-DD` they take a whole month (`2024-03`, its first day),
+//! `today`, `yesterday`, and offsets back from an anchor day: `7d`, `3w`, `6m`,
+//! `2y`. Month and year offsets land on the same day of the month, clamped to
+//! the month's last day, so `1m` before March 31 is the end of February.
+
+use chrono::{Days, Months, NaiveDate};
+
+/// The forms every date argument accepts, for error messages.
+pub const ACCEPTED_FORMS: &str =
+    "YYYY-MM-DD, YYYY-MM, today, yesterday, or an offset like 7d, 3w, 6m, 2y";
+
+/// Largest offset accepted in any unit, so anchoring never leaves chrono's range.
+const MAX_OFFSET: u32 = 9_999;
+
+/// Unit of a relative offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// A parsed date argument: a calendar day, or an offset still waiting for its anchor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSpec {
+    Day(NaiveDate),
+    /// This many units before the anchor day.
+    Before(u32, DateUnit),
+}
+
+impl DateSpec {
+    /// The calendar day this stands for, counting offsets back from `anchor`.
+    pub fn resolve(self, anchor: NaiveDate) -> NaiveDate {
+        let date = match self {
+            Self::Day(date) => Some(date),
+            Self::Before(count, DateUnit::Day) => anchor.checked_sub_days(Days::new(count.into())),
+            Self::Before(count, DateUnit::Week) => {
+                anchor.checked_sub_days(Days::new(u64::from(count) * 7))
+            }
+            Self::Before(count, DateUnit::Month) => anchor.checked_sub_months(Months::new(count)),
+            Self::Before(count, DateUnit::Year) => {
+                anchor.checked_sub_months(Months::new(count * 12))
+            }
+        };
+        date.unwrap_or(NaiveDate::MIN)
+    }
+}
+
+/// Parse a date argument. `today` is the UTC day `yesterday` and `today` refer to,
+/// whatever anchor offsets are later resolved against.
+pub fn parse(raw: &str, today: NaiveDate) -> Result<DateSpec, String> {
+    let value = raw.trim();
+    let invalid = || format!("invalid date '{}', expected {}", value, ACCEPTED_FORMS);
+
+    match value.to_ascii_lowercase().as_str() {
+        "today" => return Ok(DateSpec::Day(today)),
+        "yesterday" => return Ok(DateSpec::Day(today - Days::new(1))),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(DateSpec::Day(date));
+    }
+    if let Some(date) = parse_month(value) {
+        return Ok(DateSpec::Day(date));
+    }
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (count, unit) = value.split_at(split);
+    let unit = match unit.to_ascii_lowercase().as_str() {
+        "d" => DateUnit::Day,
+        "w" => DateUnit::Week,
+        "m" => DateUnit::Month,
+        "y" => DateUnit::Year,
+        _ => return Err(invalid()),
+    };
+    let count: u32 = count.parse().map_err(|_| invalid())?;
+    if count > MAX_OFFSET {
+        return Err(format!(
+            "invalid date '{}', offsets go back at most {}{}",
+            value,
+            MAX_OFFSET,
+            &value[split..]
+        ));
+    }
+    Ok(DateSpec::Before(count, unit))
+}
+
+/// Parse a date argument and resolve offsets against `today`.
+pub fn parse_date(raw: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    parse(raw, today).map(|spec| spec.resolve(today))
+}
+
+/// `YYYY-MM` as the first day of that month.
+fn parse_month(value: &str) -> Option<NaiveDate> {
+    let (year, month) = value.split_once('-')?;
+    if year.len() != 4 || month.len() != 2 {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn resolved(raw: &str, anchor: NaiveDate) -> NaiveDate {
+        parse(raw, anchor).unwrap().resolve(anchor)
+    }
+
+    #[test]
+    fn calendar_days_months_and_named_days() {
+        let today = day(2026, 3, 15);
+        assert_eq!(resolved("2024-02-29", today), day(2024, 2, 29));
+        assert_eq!(resolved(" 2024-03 ", today), day(2024, 3, 1));
+        assert_eq!(resolved("today", today), today);
+        assert_eq!(resolved("Yesterday", today), day(2026, 3, 14));
+        assert_eq!(resolved("yesterday", day(2026, 1, 1)), day(2025, 12, 31));
+    }
+
+    #[test]
+    fn offsets_count_back_from_the_anchor() {
+        let anchor = day(2026, 3, 15);
+        assert_eq!(resolved("7d", anchor), day(2026, 3, 8));
+        assert_eq!(resolved("0d", anchor), anchor);
+        assert_eq!(resolved("3w", anchor), day(2026, 2, 22));
+        assert_eq!(resolved("6m", anchor), day(2025, 9, 15));
+        assert_eq!(resolved("2y", anchor), day(2024, 3, 15));
+        assert_eq!(resolved("6M", anchor), day(2025, 9, 15));
+        assert_eq!(resolved("10D", anchor), day(2026, 3, 5));
+    }
+
+    #[test]
+    fn month_and_year_offsets_clamp_to_the_month_end() {
+        assert_eq!(resolved("1m", day(2026, 3, 31)), day(2026, 2, 28));
+        assert_eq!(resolved("1m", day(2024, 3, 31)), day(2024, 2, 29));
+        assert_eq!(resolved("1m", day(2026, 5, 31)), day(2026, 4, 30));
+        assert_eq!(resolved("3m", day(2026, 5, 31)), day(2026, 2, 28));
+        assert_eq!(resolved("1y", day(2024, 2, 29)), day(2023, 2, 28));
+        assert_eq!(resolved("4y", day(2024, 2, 29)), day(2020, 2, 29));
+    }
+
+    #[test]
+    fn offsets_wait_for_their_anchor() {
+        let spec = parse("1w", day(2026, 3, 15)).unwrap();
+        assert_eq!(spec, DateSpec::Before(1, DateUnit::Week));
+        assert_eq!(spec.resolve(day(2025, 1, 3)), day(2024, 12, 27));
+        // Calendar days and named days ignore the anchor.
+        let yesterday = parse("yesterday", day(2026, 3, 15)).unwrap();
+        assert_eq!(yesterday.resolve(day(2025, 1, 3)), day(2026, 3, 14));
+        assert_eq!(
+            parse_date("2m", day(2026, 3, 15)).unwrap(),
+            day(2026, 1, 15)
+        );
+    }
+
+    #[test]
+    fn invalid_and_ambiguous_inputs_list_the_accepted_forms() {
+        let today = day(2026, 3, 15);
+        for raw in [
+            "",
+            "7",
+            "d",
+            "7x",
+            "-7d",
+            "7 d",
+            "1.5m",
+            "7dd",
+            "2024-13",
+            "2024-02-30",
+            "2024-3",
+            "24-03",
+            "03/15/2026",
+            "last week",
+        ] {
+            let err = parse(raw, today).unwrap_err();
+            assert_eq!(
+                err,
+                format!("invalid date '{}', expected {}", raw.trim(), ACCEPTED_FORMS),
+                "{raw:?}"
+            );
+        }
+        assert_eq!(
+            parse("10000y", today).unwrap_err(),
+            "invalid date '10000y', offsets go back at most 9999y"
+        );
+        assert_eq!(
+            parse("99999999999d", today).unwrap_err(),
+            format!("invalid date '99999999999d', expected {}", ACCEPTED_FORMS)
+        );
+    }
+}
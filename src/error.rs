@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
 /// Unified error type for the pricr application.
@@ -6,8 +8,20 @@ pub enum Error {
     #[error("HTTP request failed: {0}")]
     Http(#[from] reqwest::Error),
 
-    #[error("API error: {0}")]
-    Api(String),
+    /// A provider answered with an error. `status` is the HTTP status when there was one.
+    #[error("API error: {message}")]
+    Api {
+        provider: String,
+        status: Option<u16>,
+        message: String,
+    },
+
+    /// A provider answered `429 Too Many Requests`.
+    #[error("API error: {provider} returned 429 Too Many Requests{}", retry_after_suffix(.retry_after))]
+    RateLimited {
+        provider: String,
+        retry_after: Option<Duration>,
+    },
 
     #[error("Parse error: {0}")]
     Parse(String),
@@ -15,8 +29,288 @@ pub enum Error {
     #[error("Configuration error: {0}")]
     Config(String),
 
-    #[error("No results returned")]
-    NoResults,
+    /// Nothing came back. `provider` is empty when no single provider answered, and
+    /// `symbols` and `currency` are empty for lookups that are not per symbol.
+    #[error("{}", no_results_message(provider, symbols, currency))]
+    NoResults {
+        provider: String,
+        symbols: Vec<String>,
+        currency: String,
+    },
+
+    /// The lookup was cancelled, usually by Ctrl-C, before it finished.
+    #[error("interrupted")]
+    Interrupted,
+}
+
+fn no_results_message(provider: &str, symbols: &[String], currency: &str) -> String {
+    let mut message = String::from("No results");
+    if !provider.is_empty() {
+        message.push_str(&format!(" from {}", provider));
+    }
+    if !symbols.is_empty() {
+        message.push_str(&format!(" for [{}]", symbols.join(", ")));
+    }
+    if !currency.is_empty() {
+        message.push_str(&format!(" in {}", currency));
+    }
+    if message.len() == "No results".len() {
+        message.push_str(" returned");
+    }
+    message
+}
+
+fn retry_after_suffix(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(wait) => format!(" (retry after {}s)", wait.as_secs()),
+        None => String::new(),
+    }
+}
+
+impl Error {
+    /// Provider error without an HTTP status, e.g. an error message inside a 200 response.
+    pub fn api(provider: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Api {
+            provider: provider.into(),
+            status: None,
+            message: message.into(),
+        }
+    }
+
+    /// Provider error for a non-success HTTP `status`.
+    pub fn api_status(
+        provider: impl Into<String>,
+        status: reqwest::StatusCode,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::Api {
+            provider: provider.into(),
+            status: Some(status.as_u16()),
+            message: message.into(),
+        }
+    }
+
+    /// No results from `provider` for `symbols` quoted in `currency`, both uppercased.
+    pub fn no_results(provider: impl Into<String>, symbols: &[String], currency: &str) -> Self {
+        Self::NoResults {
+            provider: provider.into(),
+            symbols: symbols.iter().map(|s| s.trim().to_uppercase()).collect(),
+            currency: currency.to_uppercase(),
+        }
+    }
+
+    /// No results from `provider` for a lookup that is not per symbol, such as a search.
+    pub fn no_results_from(provider: impl Into<String>) -> Self {
+        Self::no_results(provider, &[], "")
+    }
+
+    /// Name of the provider that failed, for provider errors.
+    pub fn provider(&self) -> Option<&str> {
+        match self {
+            Self::Api { provider, .. } | Self::RateLimited { provider, .. } => Some(provider),
+            Self::NoResults { provider, .. } if !provider.is_empty() => Some(provider),
+            _ => None,
+        }
+    }
+
+    /// HTTP status behind a provider error, when known.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Api { status, .. } => *status,
+            Self::RateLimited { .. } => Some(429),
+            _ => None,
+        }
+    }
+
+    /// Process exit code for this failure class.
+    ///
+    /// `Config` (2), `Api` and `RateLimited` (3), `NoResults` (4), and `Parse` (5) get
+    /// distinct codes so scripts can tell them apart; transport failures keep the generic 1.
+    /// `Interrupted` exits with 130, as a shell reports a process stopped by `SIGINT`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Http(_) => 1,
+            Self::Config(_) => 2,
+            Self::Api { .. } | Self::RateLimited { .. } => 3,
+            Self::NoResults { .. } => 4,
+            Self::Parse(_) => 5,
+            Self::Interrupted => 130,
+        }
+    }
+
+    /// Short snake_case name of the failure class, used in structured error output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Http(_) => "http",
+            Self::Api { .. } => "api",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Parse(_) => "parse",
+            Self::Config(_) => "config",
+            Self::NoResults { .. } => "no_results",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    /// A next step for common provider failures, printed by the CLI below the error.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Self::RateLimited {
+                provider,
+                retry_after,
+            } => {
+                let alternative = if provider.eq_ignore_ascii_case("Yahoo Finance") {
+                    "coingecko"
+                } else {
+                    "yahoo"
+                };
+                Some(match retry_after {
+                    Some(wait) => format!(
+                        "try --provider {} or wait {}s",
+                        alternative,
+                        wait.as_secs().max(1)
+                    ),
+                    None => format!("try --provider {} or wait a minute", alternative),
+                })
+            }
+            Self::Api {
+                provider,
+                status: Some(401 | 403),
+                ..
+            } => match provider.as_str() {
+                "CoinMarketCap" => {
+                    Some("set COINMARKETCAP_API_KEY or pass --api-key with a valid key".into())
+                }
+                "CoinGecko" => Some(
+                    "check COINGECKO_API_KEY and the [provider.coingecko] tier (demo or pro)"
+                        .into(),
+                ),
+                _ => None,
+            },
+            Self::NoResults {
+                provider, symbols, ..
+            } if !symbols.is_empty() => Some(if provider.is_empty() {
+                "check symbols with --search".into()
+            } else {
+                let alternative = if provider.contains("CoinGecko") {
+                    "cmc"
+                } else {
+                    "coingecko"
+                };
+                format!(
+                    "check symbols with --search or try --provider {}",
+                    alternative
+                )
+            }),
+            _ => None,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_distinguishes_failure_classes() {
+        assert_eq!(Error::Config("bad flag".into()).exit_code(), 2);
+        assert_eq!(Error::api("CoinGecko", "500").exit_code(), 3);
+        assert_eq!(
+            Error::RateLimited {
+                provider: "CoinGecko".into(),
+                retry_after: None
+            }
+            .exit_code(),
+            3
+        );
+        assert_eq!(Error::no_results_from("Stooq").exit_code(), 4);
+        assert_eq!(Error::Parse("eof".into()).exit_code(), 5);
+        assert_eq!(Error::Interrupted.exit_code(), 130);
+    }
+
+    #[test]
+    fn kind_is_snake_case() {
+        assert_eq!(Error::no_results_from("Stooq").kind(), "no_results");
+        assert_eq!(Error::Config("x".into()).kind(), "config");
+        assert_eq!(
+            Error::RateLimited {
+                provider: "CoinGecko".into(),
+                retry_after: None
+            }
+            .kind(),
+            "rate_limited"
+        );
+    }
+
+    #[test]
+    fn display_keeps_api_message_format() {
+        let err = Error::api_status(
+            "CoinGecko",
+            reqwest::StatusCode::INTERNAL_SERVER_ERROR,
+            "CoinGecko returned 500 Internal Server Error: oops",
+        );
+        assert_eq!(
+            err.to_string(),
+            "API error: CoinGecko returned 500 Internal Server Error: oops"
+        );
+        assert_eq!(err.status(), Some(500));
+
+        let err = Error::RateLimited {
+            provider: "CoinGecko".into(),
+            retry_after: Some(Duration::from_secs(30)),
+        };
+        assert_eq!(
+            err.to_string(),
+            "API error: CoinGecko returned 429 Too Many Requests (retry after 30s)"
+        );
+    }
+
+    #[test]
+    fn hints_cover_auth_and_rate_limits() {
+        let cmc = Error::api_status(
+            "CoinMarketCap",
+            reqwest::StatusCode::UNAUTHORIZED,
+            "CoinMarketCap returned 401 Unauthorized",
+        );
+        assert!(cmc.hint().unwrap().contains("COINMARKETCAP_API_KEY"));
+
+        let limited = Error::RateLimited {
+            provider: "CoinGecko".into(),
+            retry_after: Some(Duration::from_secs(42)),
+        };
+        assert_eq!(
+            limited.hint().as_deref(),
+            Some("try --provider yahoo or wait 42s")
+        );
+
+        assert!(Error::api("Stooq", "boom").hint().is_none());
+    }
+
+    #[test]
+    fn no_results_names_provider_symbols_and_currency() {
+        let err = Error::no_results("CoinGecko", &["xyz".into(), "abc".into()], "eur");
+        assert_eq!(
+            err.to_string(),
+            "No results from CoinGecko for [XYZ, ABC] in EUR"
+        );
+        assert_eq!(err.provider(), Some("CoinGecko"));
+        assert_eq!(
+            err.hint().as_deref(),
+            Some("check symbols with --search or try --provider cmc")
+        );
+
+        let err = Error::no_results("", &["xyz".into()], "usd");
+        assert_eq!(err.to_string(), "No results for [XYZ] in USD");
+        assert_eq!(err.provider(), None);
+        assert_eq!(err.hint().as_deref(), Some("check symbols with --search"));
+
+        let err = Error::no_results_from("Yahoo Finance");
+        assert_eq!(err.to_string(), "No results from Yahoo Finance");
+        assert!(err.hint().is_none());
+        assert_eq!(
+            Error::no_results_from("").to_string(),
+            "No results returned"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! History requests one lookup needs, planned up front and run together.
+//!
+//! The sparkline column and `--since` both ask the primary provider for daily history.
+//! A [`HistoryPlan`] collects those requests, keeps one copy of identical ones, and
+//! serves a `--since` day from a sparkline series that already covers it, so each
+//! symbol costs at most one history call. [`HistoryPlan::run`] sends what is left
+//! concurrently, meant to be joined with the price request itself.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use futures::future::join_all;
+use tracing::{info, warn};
+
+use crate::error::Error;
+use crate::provider::{HistoryInterval, PriceHistory, PriceProvider};
+
+/// How much daily history a request asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryRange {
+    /// The last `n` days up to now, as for the sparkline column.
+    LastDays(u32),
+    /// One finished day, as for the `--since` close.
+    Day(NaiveDate),
+}
+
+/// One planned history request for a symbol in a currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryRequest {
+    pub symbol: String,
+    pub currency: String,
+    pub range: HistoryRange,
+}
+
+impl HistoryRequest {
+    fn same_series(&self, other: &HistoryRequest) -> bool {
+        self.symbol.eq_ignore_ascii_case(&other.symbol)
+            && self.currency.eq_ignore_ascii_case(&other.currency)
+    }
+}
+
+/// Handle for a request added to a [`HistoryPlan`], used to read its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ticket(usize);
+
+/// History requests for one lookup against one provider.
+#[derive(Debug, Clone)]
+pub struct HistoryPlan {
+    today: NaiveDate,
+    requests: Vec<HistoryRequest>,
+}
+
+impl HistoryPlan {
+    /// An empty plan; `today` decides which `LastDays` series cover a `Day`.
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            today,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Add a request, reusing an identical one already planned.
+    pub fn add(&mut self, symbol: &str, currency: &str, range: HistoryRange) -> Ticket {
+        let request = HistoryRequest {
+            symbol: symbol.to_string(),
+            currency: currency.to_string(),
+            range,
+        };
+        if let Some(idx) = self
+            .requests
+            .iter()
+            .position(|planned| planned.same_series(&request) && planned.range == range)
+        {
+            return Ticket(idx);
+        }
+        self.requests.push(request);
+        Ticket(self.requests.len() - 1)
+    }
+
+    /// Requests the plan actually sends: identical ones once, and no `Day` that a
+    /// planned `LastDays` series for the same symbol and currency covers.
+    pub fn calls(&self) -> Vec<&HistoryRequest> {
+        (0..self.requests.len())
+            .filter(|&idx| self.served_by(idx) == idx)
+            .map(|idx| &self.requests[idx])
+            .collect()
+    }
+
+    /// Index of the request whose response answers request `idx`.
+    fn served_by(&self, idx: usize) -> usize {
+        let request = &self.requests[idx];
+        let HistoryRange::Day(day) = request.range else {
+            return idx;
+        };
+        self.requests
+            .iter()
+            .position(|other| {
+                other.same_series(request)
+                    && matches!(other.range, HistoryRange::LastDays(days) if self.covers(days, day))
+            })
+            .unwrap_or(idx)
+    }
+
+    /// Whether a `LastDays(days)` series holds all of `day`; its first day may start
+    /// partway through.
+    fn covers(&self, days: u32, day: NaiveDate) -> bool {
+        day > self.today - Duration::days(i64::from(days)) && day < self.today
+    }
+
+    /// Send every call concurrently through `prov`.
+    ///
+    /// A failed call leaves its requests without a series: unsupported or unknown
+    /// symbols are logged at info level and other failures as warnings.
+    pub async fn run(&self, prov: &dyn PriceProvider) -> HistoryResults {
+        let served_by: Vec<usize> = (0..self.requests.len())
+            .map(|idx| self.served_by(idx))
+            .collect();
+        let calls: Vec<usize> = (0..self.requests.len())
+            .filter(|&idx| served_by[idx] == idx)
+            .collect();
+
+        let responses = join_all(calls.iter().map(|&idx| fetch(prov, &self.requests[idx]))).await;
+        let mut series = vec![None; self.requests.len()];
+        for (&idx, response) in calls.iter().zip(responses) {
+            let request = &self.requests[idx];
+            match response {
+                Ok(found) => {
+                    series[idx] = found.into_iter().find(|history| !history.points.is_empty());
+                }
+                // Cancelled along with the rest of the lookup.
+                Err(Error::Interrupted) => {}
+                Err(err @ Error::Config(_)) | Err(err @ Error::NoResults { .. }) => {
+                    info!(provider = prov.id(), symbol = %request.symbol, error = %err, "history unavailable");
+                }
+                Err(err) => {
+                    warn!(provider = prov.id(), symbol = %request.symbol, error = %err, "history fetch failed");
+                }
+            }
+        }
+
+        HistoryResults {
+            requests: self.requests.clone(),
+            served_by,
+            series,
+        }
+    }
+}
+
+async fn fetch(
+    prov: &dyn PriceProvider,
+    request: &HistoryRequest,
+) -> crate::error::Result<Vec<PriceHistory>> {
+    let symbols = std::slice::from_ref(&request.symbol);
+    match request.range {
+        HistoryRange::LastDays(days) => {
+            prov.get_price_history(symbols, &request.currency, days, HistoryInterval::Daily)
+                .await
+        }
+        HistoryRange::Day(day) => {
+            let (start, end) = day_bounds(day);
+            prov.get_price_history_window(
+                symbols,
+                &request.currency,
+                Some(start),
+                end - Duration::seconds(1),
+                HistoryInterval::Daily,
+            )
+            .await
+        }
+    }
+}
+
+/// Midnight UTC starting `day` and the one ending it.
+fn day_bounds(day: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = day.and_time(NaiveTime::MIN).and_utc();
+    (start, start + Duration::days(1))
+}
+
+/// Series returned for a [`HistoryPlan`], read back by [`Ticket`].
+#[derive(Debug, Clone)]
+pub struct HistoryResults {
+    requests: Vec<HistoryRequest>,
+    served_by: Vec<usize>,
+    series: Vec<Option<PriceHistory>>,
+}
+
+impl HistoryResults {
+    /// The series answering `ticket`, which may be a longer one it shares.
+    pub fn history(&self, ticket: Ticket) -> Option<&PriceHistory> {
+        self.series[self.served_by[ticket.0]].as_ref()
+    }
+
+    /// The last price on the day a `Day` request asked for, with the currency the
+    /// provider reported it in.
+    pub fn close(&self, ticket: Ticket) -> Option<(f64, &str)> {
+        let HistoryRange::Day(day) = self.requests[ticket.0].range else {
+            return None;
+        };
+        let (start, end) = day_bounds(day);
+        let history = self.history(ticket)?;
+        history
+            .points
+            .iter()
+            .rfind(|point| point.timestamp >= start && point.timestamp < end)
+            .map(|point| (point.price, history.currency.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(raw: &str) -> NaiveDate {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn plan_dedups_and_serves_covered_days_from_longer_series() {
+        let mut plan = HistoryPlan::new(day("2026-10-17"));
+        let spark = plan.add("btc", "USD", HistoryRange::LastDays(7));
+        assert_eq!(plan.add("BTC", "usd", HistoryRange::LastDays(7)), spark);
+        plan.add("btc", "USD", HistoryRange::Day(day("2026-10-14")));
+        plan.add("btc", "EUR", HistoryRange::Day(day("2026-10-14")));
+        // Outside the sparkline window: the first day may be partial, today unfinished.
+        plan.add("btc", "USD", HistoryRange::Day(day("2026-10-10")));
+
+        let calls: Vec<(&str, &str, HistoryRange)> = plan
+            .calls()
+            .into_iter()
+            .map(|call| (call.symbol.as_str(), call.currency.as_str(), call.range))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("btc", "USD", HistoryRange::LastDays(7)),
+                ("btc", "EUR", HistoryRange::Day(day("2026-10-14"))),
+                ("btc", "USD", HistoryRange::Day(day("2026-10-10"))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Provider API keys kept in the OS keyring (`keyring` feature).
+//!
+//! `pricr config set-key cmc` stores a key under the `pricr` service with the
+//! provider id as the account. Provider construction reads it after `--api-key`
+//! and the environment variables and before the config file, so the key never
+//! has to sit in plaintext TOML or `.env`. Setting [`DISABLE_VAR`] skips the
+//! keyring entirely.
+
+use keyring::Entry;
+use tracing::debug;
+
+use crate::error::{Error, Result};
+
+/// Keyring service the keys are stored under.
+pub const SERVICE: &str = "pricr";
+
+/// Environment variable that, when set, keeps provider construction away from the keyring.
+pub const DISABLE_VAR: &str = "PRICR_NO_KEYRING";
+
+/// How secret-service reports that no provider or D-Bus session exists; it has no
+/// dedicated `keyring` error kind.
+const NO_SECRET_SERVICE: &str = "no secret service";
+
+/// Providers that take an API key, with the environment variable that overrides a stored one.
+pub const KEYED_PROVIDERS: &[(&str, &str)] = &[
+    ("cmc", "COINMARKETCAP_API_KEY"),
+    ("coingecko", "COINGECKO_API_KEY"),
+];
+
+/// Parse a provider id for the `config` key commands, accepting only [`KEYED_PROVIDERS`].
+pub fn parse_provider(raw: &str) -> std::result::Result<String, String> {
+    let id = raw.trim().to_ascii_lowercase();
+    if KEYED_PROVIDERS.iter().any(|(known, _)| *known == id) {
+        Ok(id)
+    } else {
+        let known: Vec<&str> = KEYED_PROVIDERS.iter().map(|(id, _)| *id).collect();
+        Err(format!(
+            "unknown provider '{}' (API keys: {})",
+            raw.trim(),
+            known.join(", ")
+        ))
+    }
+}
+
+/// The keyring entry holding `provider_id`'s key.
+pub fn entry(provider_id: &str) -> Result<Entry> {
+    Entry::new(SERVICE, provider_id).map_err(keyring_error)
+}
+
+/// The key stored for `provider_id`, or `None` when there is none.
+///
+/// A machine without keyring storage, such as a headless box with no Secret
+/// Service, has no stored keys rather than a failing keyring.
+pub fn load(provider_id: &str) -> Result<Option<String>> {
+    if std::env::var_os(DISABLE_VAR).is_some() {
+        return Ok(None);
+    }
+    match entry(provider_id)?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) if storage_unavailable(&err) => {
+            debug!(provider = provider_id, error = %err, "no OS keyring storage");
+            Ok(None)
+        }
+        Err(err) => Err(keyring_error(err)),
+    }
+}
+
+/// Read the key in `entry`, or `None` when it holds none.
+pub fn read(entry: &Entry) -> Result<Option<String>> {
+    match entry.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(keyring_error(err)),
+    }
+}
+
+/// Store `key` in `entry`, trimmed, replacing any key already there.
+pub fn write(entry: &Entry, key: &str) -> Result<()> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(Error::Config("API key cannot be empty".into()));
+    }
+    entry.set_password(key).map_err(keyring_error)
+}
+
+/// Remove the key in `entry`; `false` when there was none.
+pub fn delete(entry: &Entry) -> Result<bool> {
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(err) => Err(keyring_error(err)),
+    }
+}
+
+/// `key` with only its first and last four characters visible, or fully hidden
+/// when it is too short for that to leave most of it out.
+pub fn mask(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Whether `err` only says this machine has no keyring to read from.
+fn storage_unavailable(err: &keyring::Error) -> bool {
+    match err {
+        keyring::Error::NoStorageAccess(_) => true,
+        keyring::Error::PlatformFailure(inner) => inner.to_string().contains(NO_SECRET_SERVICE),
+        _ => false,
+    }
+}
+
+fn keyring_error(err: keyring::Error) -> Error {
+    Error::Config(format!("OS keyring: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyring::mock::MockCredential;
+
+    fn mock_entry() -> Entry {
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        entry("cmc").unwrap()
+    }
+
+    #[test]
+    fn keys_round_trip_through_the_keyring() {
+        let entry = mock_entry();
+        assert_eq!(read(&entry).unwrap(), None);
+        write(&entry, "  cmc-secret-key \n").unwrap();
+        assert_eq!(read(&entry).unwrap().as_deref(), Some("cmc-secret-key"));
+        write(&entry, "cmc-rotated").unwrap();
+        assert_eq!(read(&entry).unwrap().as_deref(), Some("cmc-rotated"));
+        assert!(delete(&entry).unwrap());
+        assert!(!delete(&entry).unwrap());
+        assert_eq!(read(&entry).unwrap(), None);
+        assert!(write(&entry, "   ").is_err());
+    }
+
+    #[test]
+    fn keyring_failures_are_errors_not_missing_keys() {
+        let entry = mock_entry();
+        let mock: &MockCredential = entry.get_credential().downcast_ref().unwrap();
+        mock.set_error(keyring::Error::PlatformFailure("locked".into()));
+        let err = read(&entry).unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+        assert!(err.to_string().contains("OS keyring: "), "{err}");
+    }
+
+    #[test]
+    fn missing_keyring_storage_is_not_a_failure() {
+        let locked = keyring::Error::NoStorageAccess("locked".into());
+        assert!(storage_unavailable(&locked));
+        let headless = keyring::Error::PlatformFailure(
+            "no secret service provider or dbus session found".into(),
+        );
+        assert!(storage_unavailable(&headless));
+        let broken = keyring::Error::PlatformFailure("bad reply".into());
+        assert!(!storage_unavailable(&broken));
+    }
+
+    #[test]
+    fn masks_keep_only_the_ends_of_long_keys() {
+        assert_eq!(mask("b54bcf4d-1bca-4e8e-9a24-22ff2c3d462c"), "b54b…462c");
+        assert_eq!(mask("short"), "*****");
+        assert_eq!(mask("12345678"), "********");
+        assert_eq!(mask(""), "");
+    }
+
+    #[test]
+    fn only_providers_with_api_keys_parse() {
+        assert_eq!(parse_provider(" CMC ").unwrap(), "cmc");
+        assert_eq!(parse_provider("coingecko").unwrap(), "coingecko");
+        assert_eq!(
+            parse_provider("yahoo").unwrap_err(),
+            "unknown provider 'yahoo' (API keys: cmc, coingecko)"
+        );
+    }
+}
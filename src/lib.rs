@@ -1,5 +1,38 @@
+//! Cryptocurrency, stock, and fiat prices from several providers.
+//!
+//! Library callers configure every built-in provider at once with
+//! [`ProviderRegistry::builder`] and fetch through the [`PriceProvider`] trait:
+//!
+//! ```no_run
+//! # async fn demo() -> pricr::Result<()> {
+//! let registry = pricr::ProviderRegistry::builder().currency("usd").build()?;
+//! let prices = registry.get_prices("yahoo", &["AAPL".to_string()]).await?;
+//! println!("{}: {}", prices[0].symbol, prices[0].price);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod alerts;
 pub mod calc;
 pub mod config;
+pub mod dates;
 pub mod error;
+pub mod fetch;
+#[cfg(feature = "keyring")]
+pub mod keystore;
 pub mod output;
 pub mod provider;
+pub mod resolve;
+pub mod search;
+#[cfg(feature = "metrics")]
+pub mod server;
+pub mod state;
+pub mod timings;
+pub mod webhook;
+
+pub use config::{HttpConfig, ProviderSettings, SymbolAliases};
+pub use error::{Error, Result};
+pub use provider::{
+    CoinPrice, HistoryInterval, PriceHistory, PriceProvider, ProviderRegistry,
+    ProviderRegistryBuilder,
+};
@@ -1,98 +1,380 @@
-use chrono::{Datelike, NaiveDate};
+use chrono::NaiveDate;
 use clap::Parser;
-use pricr::{calc, config, error, output, provider};
+use futures::future::join_all;
+use pricr::{
+    alerts, calc, config, dates, error, fetch, output, provider, resolve, search, state, timings,
+    webhook,
+};
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 use crate::error::Result;
 
 const APP_VERSION: &str = env!("PRICR_VERSION");
 const MAX_CHART_FETCH_DAYS: u32 = 36_500;
+const CHART_FETCH_DAYS_BUFFER: i64 = 1;
+const SPARKLINE_DAYS: u32 = 7;
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 30;
+const SUGGESTION_LIMIT: usize = 3;
+/// Hidden first argument that lists completion candidates instead of running a lookup.
+const COMPLETE_SYMBOLS_COMMAND: &str = "__complete-symbols";
+const CACHE_COMMAND: &str = "cache";
+#[cfg(feature = "keyring")]
+const CONFIG_COMMAND: &str = "config";
+/// First arguments that stand for the flag of the same name, as in `pricr stats btc`.
+const SHORTHAND_COMMANDS: &[&str] = &["stats", "roi"];
+/// Positional argument that reads symbols from stdin.
+const STDIN_MARKER: &str = "-";
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  success
+  1  unexpected or network error
+  2  invalid flags or configuration
+  3  provider API error
+  4  no results for the requested symbols
+  5  malformed provider response
+  10 alert fired with --alert-once";
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
-enum SamplingArg {
-    Auto,
-    Hourly,
-    Daily,
+enum FillArg {
+    None,
+    Previous,
+    Interpolate,
+}
+
+impl From<FillArg> for calc::FillMethod {
+    fn from(value: FillArg) -> Self {
+        match value {
+            FillArg::None => Self::None,
+            FillArg::Previous => Self::Previous,
+            FillArg::Interpolate => Self::Interpolate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum ResampleMethodArg {
+    Last,
+    Mean,
+}
+
+impl From<ResampleMethodArg> for calc::ResampleMethod {
+    fn from(value: ResampleMethodArg) -> Self {
+        match value {
+            ResampleMethodArg::Last => Self::Last,
+            ResampleMethodArg::Mean => Self::Mean,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum FormatArg {
+    Table,
+    Json,
+    /// One compact JSON object per line
+    Ndjson,
+    Waybar,
+    /// GitHub-flavored markdown tables
+    Markdown,
+}
+
+/// Layout of `--json` output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum SchemaArg {
+    /// Bare arrays and objects, as before versioning
+    #[default]
+    Legacy,
+    /// The versioned {"schema": "cryptoprice/v1", "kind", "data", "meta"} envelope
+    V1,
+}
+
+#[cfg(feature = "png")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ThemeArg {
+    Light,
+    Dark,
+}
+
+#[cfg(feature = "png")]
+impl From<ThemeArg> for output::png::Theme {
+    fn from(value: ThemeArg) -> Self {
+        match value {
+            ThemeArg::Light => Self::Light,
+            ThemeArg::Dark => Self::Dark,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ChangeArg {
+    #[value(name = "24h")]
+    Day,
+    #[value(name = "7d")]
+    Week,
+    #[value(name = "30d")]
+    Month,
+}
+
+impl From<ChangeArg> for output::table::PriceColumn {
+    fn from(value: ChangeArg) -> Self {
+        match value {
+            ChangeArg::Day => Self::Change24h,
+            ChangeArg::Week => Self::Change7d,
+            ChangeArg::Month => Self::Change30d,
+        }
+    }
 }
 
-impl From<SamplingArg> for provider::HistoryInterval {
-    fn from(value: SamplingArg) -> Self {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ColumnArg {
+    Symbol,
+    Name,
+    Price,
+    #[value(name = "change-24h")]
+    Change24h,
+    #[value(name = "change-7d")]
+    Change7d,
+    #[value(name = "change-30d")]
+    Change30d,
+    Trend,
+    MarketCap,
+    Volume,
+    Supply,
+    Rank,
+    Provider,
+    PreMarket,
+    PostMarket,
+    #[value(name = "52w-range")]
+    Range52w,
+    Ath,
+    AthDate,
+    FromAth,
+    Atl,
+    Bid,
+    Ask,
+    Spread,
+    SincePrice,
+    SinceChange,
+    Delta,
+    Amount,
+    Value,
+    Age,
+}
+
+impl From<ColumnArg> for output::table::PriceColumn {
+    fn from(value: ColumnArg) -> Self {
         match value {
-            SamplingArg::Auto => Self::Auto,
-            SamplingArg::Hourly => Self::Hourly,
-            SamplingArg::Daily => Self::Daily,
+            ColumnArg::Symbol => Self::Symbol,
+            ColumnArg::Name => Self::Name,
+            ColumnArg::Price => Self::Price,
+            ColumnArg::Change24h => Self::Change24h,
+            ColumnArg::Change7d => Self::Change7d,
+            ColumnArg::Change30d => Self::Change30d,
+            ColumnArg::Trend => Self::Trend,
+            ColumnArg::MarketCap => Self::MarketCap,
+            ColumnArg::Volume => Self::Volume,
+            ColumnArg::Supply => Self::Supply,
+            ColumnArg::Rank => Self::Rank,
+            ColumnArg::Provider => Self::Provider,
+            ColumnArg::PreMarket => Self::PreMarket,
+            ColumnArg::PostMarket => Self::PostMarket,
+            ColumnArg::Range52w => Self::Range52w,
+            ColumnArg::Ath => Self::Ath,
+            ColumnArg::AthDate => Self::AthDate,
+            ColumnArg::FromAth => Self::FromAth,
+            ColumnArg::Atl => Self::Atl,
+            ColumnArg::Bid => Self::Bid,
+            ColumnArg::Ask => Self::Ask,
+            ColumnArg::Spread => Self::Spread,
+            ColumnArg::SincePrice => Self::SincePrice,
+            ColumnArg::SinceChange => Self::SinceChange,
+            ColumnArg::Delta => Self::Delta,
+            ColumnArg::Amount => Self::Amount,
+            ColumnArg::Value => Self::Value,
+            ColumnArg::Age => Self::Age,
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
-enum ChartRangeArg {
-    #[value(name = "1D")]
-    OneDay,
-    #[value(name = "5D")]
-    FiveDays,
-    #[value(name = "1M")]
-    OneMonth,
-    #[value(name = "6M")]
-    SixMonths,
-    #[value(name = "YTD")]
-    Ytd,
-    #[value(name = "1Y")]
-    OneYear,
-    #[value(name = "5Y")]
-    FiveYears,
-    #[value(name = "ALL")]
-    All,
-}
-
-impl ChartRangeArg {
-    fn label(self) -> &'static str {
-        match self {
-            Self::OneDay => "1D",
-            Self::FiveDays => "5D",
-            Self::OneMonth => "1M",
-            Self::SixMonths => "6M",
-            Self::Ytd => "YTD",
-            Self::OneYear => "1Y",
-            Self::FiveYears => "5Y",
-            Self::All => "ALL",
-        }
-    }
-
-    fn start_date(self, end_date: NaiveDate) -> Option<NaiveDate> {
-        match self {
-            Self::OneDay => Some(end_date - chrono::Duration::days(1)),
-            Self::FiveDays => Some(end_date - chrono::Duration::days(5)),
-            Self::OneMonth => end_date
-                .checked_sub_months(chrono::Months::new(1))
-                .or(Some(end_date - chrono::Duration::days(30))),
-            Self::SixMonths => end_date
-                .checked_sub_months(chrono::Months::new(6))
-                .or(Some(end_date - chrono::Duration::days(182))),
-            Self::Ytd => chrono::NaiveDate::from_ymd_opt(end_date.year(), 1, 1),
-            Self::OneYear => end_date
-                .checked_sub_months(chrono::Months::new(12))
-                .or(Some(end_date - chrono::Duration::days(365))),
-            Self::FiveYears => end_date
-                .checked_sub_months(chrono::Months::new(60))
-                .or(Some(end_date - chrono::Duration::days(365 * 5))),
-            Self::All => None,
+enum SortArg {
+    Symbol,
+    Price,
+    Change,
+    MarketCap,
+    Volume,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum ColorArg {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LogFormatArg {
+    Text,
+    Json,
+}
+
+impl From<LogFormatArg> for config::LogFormat {
+    fn from(value: LogFormatArg) -> Self {
+        match value {
+            LogFormatArg::Text => Self::Text,
+            LogFormatArg::Json => Self::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl From<CompletionShell> for clap_complete::Shell {
+    fn from(value: CompletionShell) -> Self {
+        match value {
+            CompletionShell::Bash => Self::Bash,
+            CompletionShell::Zsh => Self::Zsh,
+            CompletionShell::Fish => Self::Fish,
+        }
+    }
+}
+
+/// Accepts any provider id but advertises the built-in ones to help and completions.
+///
+/// Unknown ids are rejected later with the `--list-providers` hint.
+#[derive(Clone)]
+struct ProviderIdParser;
+
+impl clap::builder::TypedValueParser for ProviderIdParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> std::result::Result<String, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(
+            provider::PROVIDER_IDS
+                .iter()
+                .map(|id| clap::builder::PossibleValue::new(*id)),
+        ))
+    }
+}
+
+/// Value parser for a flag whose type has its own `FromStr`, shared with the config
+/// file, that still offers `names` as possible values in `--help` and the
+/// completion scripts.
+#[derive(Clone)]
+struct NamedValueParser<T> {
+    names: Vec<&'static str>,
+    parsed: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> NamedValueParser<T> {
+    fn new(names: impl IntoIterator<Item = &'static str>) -> Self {
+        Self {
+            names: names.into_iter().collect(),
+            parsed: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> clap::builder::TypedValueParser for NamedValueParser<T>
+where
+    T: std::str::FromStr<Err = String> + Clone + Send + Sync + 'static,
+{
+    type Value = T;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> std::result::Result<T, clap::Error> {
+        (|raw: &str| raw.parse::<T>()).parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(
+            self.names
+                .iter()
+                .map(|name| clap::builder::PossibleValue::new(*name)),
+        ))
+    }
+}
+
+impl From<ColorArg> for output::color::ColorMode {
+    fn from(value: ColorArg) -> Self {
+        match value {
+            ColorArg::Auto => Self::Auto,
+            ColorArg::Always => Self::Always,
+            ColorArg::Never => Self::Never,
         }
     }
 }
 
+impl From<SortArg> for output::sort::SortKey {
+    fn from(value: SortArg) -> Self {
+        match value {
+            SortArg::Symbol => Self::Symbol,
+            SortArg::Price => Self::Price,
+            SortArg::Change => Self::Change,
+            SortArg::MarketCap => Self::MarketCap,
+            SortArg::Volume => Self::Volume,
+        }
+    }
+}
+
+/// `--interval` and `--sampling`, falling back to `[chart]` and then to `1M` and `auto`.
+fn chart_window_defaults(
+    cli: &Cli,
+    chart: &config::ChartConfig,
+) -> (provider::ChartRange, provider::HistoryInterval) {
+    (
+        cli.interval.or(chart.interval).unwrap_or_default(),
+        cli.sampling.or(chart.sampling).unwrap_or_default(),
+    )
+}
+
+/// `--end-date`, with relative forms counted back from today (UTC).
 fn parse_chart_end_date(raw: &str) -> std::result::Result<NaiveDate, String> {
-    chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d")
-        .map_err(|_| "invalid end date, expected format YYYY-MM-DD".to_string())
+    dates::parse_date(raw, chrono::Utc::now().date_naive())
+}
+
+/// `--start-date`; relative forms wait for `--end-date` to anchor them.
+fn parse_chart_start_date(raw: &str) -> std::result::Result<dates::DateSpec, String> {
+    dates::parse(raw, chrono::Utc::now().date_naive())
+}
+
+/// `--since`, with relative forms counted back from today (UTC).
+fn parse_since_date(raw: &str) -> std::result::Result<NaiveDate, String> {
+    dates::parse_date(raw, chrono::Utc::now().date_naive())
 }
 
 fn format_chart_range_label(
     start_date: Option<NaiveDate>,
     end_date: NaiveDate,
-    fallback_interval: ChartRangeArg,
+    fallback_interval: provider::ChartRange,
 ) -> String {
     match start_date {
         Some(start) => format!(
@@ -130,97 +412,148 @@ fn resolve_search_query(cli: &Cli) -> Option<String> {
     None
 }
 
-fn resolve_watchlist<'a>(
-    watchlists: &'a HashMap<String, Vec<String>>,
-    name: &str,
-) -> Option<&'a Vec<String>> {
-    watchlists
+/// Split a watchlist file into symbols: whitespace/newline separated, `#` comments and blank lines ignored.
+fn parse_symbol_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split_once('#').map_or(line, |(before, _)| before))
+        .flat_map(str::split_whitespace)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Append `listed` symbols after the CLI ones, dropping case-insensitive repeats (first one wins).
+fn merge_symbol_sources(cli_symbols: &[String], listed: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    cli_symbols
         .iter()
-        .find(|(key, _)| key.eq_ignore_ascii_case(name))
-        .map(|(_, symbols)| symbols)
+        .chain(listed)
+        .filter(|symbol| seen.insert(symbol.to_ascii_lowercase()))
+        .cloned()
+        .collect()
+}
+
+/// Collect symbols from `--symbols-file` and, when `-` is among the positionals, from stdin.
+fn read_listed_symbols(cli: &Cli) -> Result<Vec<String>> {
+    let mut listed = Vec::new();
+    if let Some(path) = cli.symbols_file.as_deref() {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            error::Error::Config(format!(
+                "cannot read symbols file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        listed.extend(parse_symbol_list(&text));
+    }
+    if cli.symbols.iter().any(|symbol| symbol == STDIN_MARKER) {
+        let text = std::io::read_to_string(std::io::stdin())
+            .map_err(|e| error::Error::Config(format!("cannot read symbols from stdin: {}", e)))?;
+        listed.extend(parse_symbol_list(&text));
+    }
+    Ok(listed)
+}
+
+/// Pick the raw symbol tokens: CLI arguments win, `[defaults].symbols` fills in when none are given.
+fn select_symbol_tokens<'a>(
+    cli_symbols: &'a [String],
+    default_symbols: Option<&'a [String]>,
+) -> &'a [String] {
+    if cli_symbols.is_empty() {
+        default_symbols.unwrap_or_default()
+    } else {
+        cli_symbols
+    }
 }
 
+/// Replace each `@name` token with the members of that `[groups]` entry, expanding
+/// groups named inside groups. Names match case-insensitively.
 fn expand_symbol_tokens(
     raw_symbols: &[String],
-    watchlists: &HashMap<String, Vec<String>>,
+    groups: &HashMap<String, Vec<String>>,
 ) -> Result<Vec<String>> {
     let mut expanded = Vec::new();
+    expand_group_tokens(raw_symbols, groups, &mut Vec::new(), &mut expanded)?;
+    Ok(expanded)
+}
 
-    for token in raw_symbols {
-        if let Some(name) = token.strip_prefix('@') {
-            let trimmed_name = name.trim();
-            if trimmed_name.is_empty() {
-                return Err(error::Error::Config(
-                    "watchlist name cannot be empty after '@'".into(),
-                ));
-            }
-
-            let symbols = resolve_watchlist(watchlists, trimmed_name).ok_or_else(|| {
-                error::Error::Config(format!(
-                    "unknown watchlist '{}' -- define it under [watchlists] in config",
-                    trimmed_name
-                ))
-            })?;
-
-            let mut added = 0usize;
-            for symbol in symbols {
-                let trimmed_symbol = symbol.trim();
-                if trimmed_symbol.is_empty() {
-                    continue;
-                }
-                expanded.push(trimmed_symbol.to_string());
-                added += 1;
-            }
-
-            if added == 0 {
-                return Err(error::Error::Config(format!(
-                    "watchlist '{}' is empty -- add symbols under [watchlists].{}",
-                    trimmed_name, trimmed_name
-                )));
-            }
-
+fn expand_group_tokens(
+    tokens: &[String],
+    groups: &HashMap<String, Vec<String>>,
+    parents: &mut Vec<String>,
+    expanded: &mut Vec<String>,
+) -> Result<()> {
+    for token in tokens {
+        let Some(name) = token.trim().strip_prefix('@') else {
+            expanded.push(token.clone());
             continue;
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(error::Error::Config(
+                "group name cannot be empty after '@'".into(),
+            ));
+        }
+        let (name, members) = groups
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .ok_or_else(|| unknown_group(name, groups))?;
+        if parents.iter().any(|parent| parent == name) {
+            return Err(error::Error::Config(format!(
+                "group '{}' includes itself via @{} -> @{}",
+                name,
+                parents.join(" -> @"),
+                name
+            )));
         }
 
-        expanded.push(token.clone());
+        let members: Vec<String> = members
+            .iter()
+            .map(|member| member.trim().to_string())
+            .filter(|member| !member.is_empty())
+            .collect();
+        if members.is_empty() {
+            return Err(error::Error::Config(format!(
+                "group '{}' is empty -- add symbols under [groups].{}",
+                name, name
+            )));
+        }
+        parents.push(name.clone());
+        expand_group_tokens(&members, groups, parents, expanded)?;
+        parents.pop();
     }
-
-    Ok(expanded)
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct TickerMatchKey {
-    symbol: String,
-    name: String,
-    exchange: String,
-    asset_type: String,
+    Ok(())
 }
 
-fn ticker_match_key(candidate: &provider::TickerMatch) -> TickerMatchKey {
-    TickerMatchKey {
-        symbol: candidate.symbol.trim().to_uppercase(),
-        name: candidate.name.trim().to_lowercase(),
-        exchange: candidate.exchange.trim().to_lowercase(),
-        asset_type: candidate.asset_type.trim().to_lowercase(),
-    }
+fn unknown_group(name: &str, groups: &HashMap<String, Vec<String>>) -> error::Error {
+    let mut defined: Vec<String> = groups.keys().map(|name| format!("@{}", name)).collect();
+    defined.sort();
+    let hint = if defined.is_empty() {
+        "no groups are defined -- add them under [groups] in config".to_string()
+    } else {
+        format!("defined groups: {}", defined.join(", "))
+    };
+    error::Error::Config(format!("unknown group '@{}' -- {}", name, hint))
 }
 
-fn append_provider_name(existing: &mut String, provider_name: &str) {
-    if existing
-        .split(',')
-        .map(str::trim)
-        .any(|name| name.eq_ignore_ascii_case(provider_name))
-    {
-        return;
-    }
-
-    if existing.trim().is_empty() {
-        *existing = provider_name.to_string();
-        return;
+/// One alert per member for alerts on `@name`, dropping repeated conditions.
+fn expand_alert_groups(
+    conditions: &[alerts::AlertCondition],
+    groups: &HashMap<String, Vec<String>>,
+) -> Result<Vec<alerts::AlertCondition>> {
+    let mut expanded: Vec<alerts::AlertCondition> = Vec::new();
+    for condition in conditions {
+        let targets = expand_symbol_tokens(std::slice::from_ref(&condition.symbol), groups)?;
+        for symbol in targets {
+            let alert = alerts::AlertCondition {
+                symbol: symbol.to_uppercase(),
+                ..condition.clone()
+            };
+            if !expanded.contains(&alert) {
+                expanded.push(alert);
+            }
+        }
     }
-
-    existing.push_str(", ");
-    existing.push_str(provider_name);
+    Ok(expanded)
 }
 
 fn provider_ids_for_indices(
@@ -233,6 +566,70 @@ fn provider_ids_for_indices(
         .collect()
 }
 
+/// Result of `fetch` from the first provider in order that supports it.
+///
+/// Providers answering with a configuration error (no such endpoint, no API key) are
+/// skipped. With an explicit `--provider` only that one is asked, so its error is
+/// returned as-is.
+async fn fetch_from_first_supported<T>(
+    providers: &[Box<dyn provider::PriceProvider>],
+    provider_indices: &[usize],
+    explicit: bool,
+    capability: provider::Capability,
+    fetch: impl AsyncFn(&dyn provider::PriceProvider) -> Result<T>,
+) -> Result<T> {
+    let what = capability.describe();
+    let mut first_err = None;
+    for idx in capable_indices(providers, provider_indices, capability)? {
+        let prov = providers[idx].as_ref();
+        info!(provider = prov.id(), "fetching {}", what);
+        match fetch(prov).await {
+            Ok(value) => return Ok(value),
+            Err(err @ error::Error::Config(_)) if !explicit => {
+                info!(provider = prov.id(), error = %err, "skipping provider for {}", what);
+                first_err.get_or_insert(err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Err(first_err.unwrap_or_else(|| error::Error::no_results_from("")))
+}
+
+/// Fail before any request when `prov` does not implement `capability`.
+fn ensure_capability(
+    providers: &[Box<dyn provider::PriceProvider>],
+    prov: &dyn provider::PriceProvider,
+    capability: provider::Capability,
+) -> Result<()> {
+    if prov.capabilities().supports(capability) {
+        Ok(())
+    } else {
+        Err(provider::unsupported(providers, prov, capability))
+    }
+}
+
+/// The providers in `provider_indices` that implement `capability`, or an error naming
+/// the first one and the providers that would work.
+fn capable_indices(
+    providers: &[Box<dyn provider::PriceProvider>],
+    provider_indices: &[usize],
+    capability: provider::Capability,
+) -> Result<Vec<usize>> {
+    let capable: Vec<usize> = provider_indices
+        .iter()
+        .copied()
+        .filter(|&idx| providers[idx].capabilities().supports(capability))
+        .collect();
+    match (capable.is_empty(), provider_indices.first()) {
+        (true, Some(&first)) => Err(provider::unsupported(
+            providers,
+            providers[first].as_ref(),
+            capability,
+        )),
+        _ => Ok(capable),
+    }
+}
+
 fn resolve_provider_indices(
     providers: &[Box<dyn provider::PriceProvider>],
     explicit_provider: Option<&str>,
@@ -298,17 +695,19 @@ fn resolve_provider_indices(
 
 fn is_ignorable_search_error(err: &error::Error) -> bool {
     match err {
-        error::Error::NoResults => true,
-        error::Error::Config(message) => message
-            .to_ascii_lowercase()
-            .contains("does not support ticker search"),
+        error::Error::NoResults { .. } => true,
+        error::Error::Config(message) => {
+            let message = message.to_ascii_lowercase();
+            message.contains("does not support ticker search")
+                || message.contains("api key required for search")
+        }
         _ => false,
     }
 }
 
 fn is_ignorable_price_error(err: &error::Error) -> bool {
     match err {
-        error::Error::NoResults => true,
+        error::Error::NoResults { .. } => true,
         error::Error::Config(message) => {
             message.to_ascii_lowercase().contains("requires --api-key")
         }
@@ -316,39 +715,40 @@ fn is_ignorable_price_error(err: &error::Error) -> bool {
     }
 }
 
+/// Search each provider in `provider_indices`, one after another or all at once, and
+/// rank the merged matches against `query`.
+///
+/// Providers without search or results are skipped; the last other error is returned
+/// if nothing matched.
 async fn search_tickers_across_providers(
     providers: &[Box<dyn provider::PriceProvider>],
     provider_indices: &[usize],
     query: &str,
     limit: usize,
+    concurrent: bool,
 ) -> Result<Vec<provider::TickerMatch>> {
+    let pool = search::candidate_pool(limit);
+    let results = if concurrent {
+        futures::future::join_all(
+            provider_indices
+                .iter()
+                .map(|&idx| providers[idx].search_tickers(query, pool)),
+        )
+        .await
+    } else {
+        let mut results = Vec::with_capacity(provider_indices.len());
+        for &idx in provider_indices {
+            results.push(providers[idx].search_tickers(query, pool).await);
+        }
+        results
+    };
+
     let mut matches: Vec<provider::TickerMatch> = Vec::new();
-    let mut by_key: HashMap<TickerMatchKey, usize> = HashMap::new();
     let mut last_non_ignorable_error = None;
-
-    for provider_idx in provider_indices {
-        let prov = &providers[*provider_idx];
-        match prov.search_tickers(query, limit).await {
-            Ok(found) => {
-                for candidate in found {
-                    let key = ticker_match_key(&candidate);
-                    if let Some(existing_idx) = by_key.get(&key).copied() {
-                        append_provider_name(
-                            &mut matches[existing_idx].provider,
-                            &candidate.provider,
-                        );
-                        continue;
-                    }
-
-                    if matches.len() >= limit {
-                        continue;
-                    }
-
-                    let idx = matches.len();
-                    by_key.insert(key, idx);
-                    matches.push(candidate);
-                }
-            }
+    for (&idx, result) in provider_indices.iter().zip(results) {
+        let prov = &providers[idx];
+        match result {
+            Ok(found) => matches.extend(found),
             Err(err) if is_ignorable_search_error(&err) => {
                 info!(provider = prov.id(), error = %err, "skipping unsupported or empty search provider");
             }
@@ -363,11 +763,10 @@ async fn search_tickers_across_providers(
         if let Some(err) = last_non_ignorable_error {
             return Err(err);
         }
-        return Err(error::Error::NoResults);
+        return Err(error::Error::no_results_from(""));
     }
 
-    matches.truncate(limit);
-    Ok(matches)
+    Ok(search::rank(query, matches, limit))
 }
 
 async fn fetch_prices_with_provider_fallback(
@@ -375,7 +774,23 @@ async fn fetch_prices_with_provider_fallback(
     provider_indices: &[usize],
     symbols: &[String],
     currency: &str,
-) -> Result<Vec<provider::CoinPrice>> {
+) -> Result<provider::PriceBatch> {
+    fetch_prices_in_with_provider_fallback(
+        providers,
+        provider_indices,
+        symbols,
+        &[currency.to_string()],
+    )
+    .await
+}
+
+/// Provider fallback for one or more currencies; several go through `get_prices_in`.
+async fn fetch_prices_in_with_provider_fallback(
+    providers: &[Box<dyn provider::PriceProvider>],
+    provider_indices: &[usize],
+    symbols: &[String],
+    currencies: &[String],
+) -> Result<provider::PriceBatch> {
     let mut pending: Vec<(usize, String)> = symbols
         .iter()
         .enumerate()
@@ -383,6 +798,7 @@ async fn fetch_prices_with_provider_fallback(
         .collect();
     let mut resolved: Vec<Option<provider::CoinPrice>> = vec![None; symbols.len()];
     let mut last_non_ignorable_error = None;
+    let mut queried: Vec<&str> = Vec::new();
 
     for provider_idx in provider_indices {
         if pending.is_empty() {
@@ -393,24 +809,21 @@ async fn fetch_prices_with_provider_fallback(
             pending.iter().map(|(_, symbol)| symbol.clone()).collect();
         let prov = &providers[*provider_idx];
 
-        match prov.get_prices(&request_symbols, currency).await {
+        let found = match currencies {
+            [currency] => prov.get_prices(&request_symbols, currency).await,
+            _ => prov.get_prices_in(&request_symbols, currencies).await,
+        };
+        match found {
             Ok(found) => {
-                let mut found_by_symbol: HashMap<String, Vec<provider::CoinPrice>> = HashMap::new();
-                for price in found {
-                    found_by_symbol
-                        .entry(price.symbol.trim().to_uppercase())
-                        .or_default()
-                        .push(price);
-                }
-
+                queried.push(prov.name());
+                let mut found = found;
                 let mut next_pending = Vec::new();
                 for (original_idx, symbol) in pending {
-                    let key = symbol.trim().to_uppercase();
-                    let price = found_by_symbol
-                        .get_mut(&key)
-                        .and_then(|bucket| bucket.pop());
-                    if let Some(price) = price {
-                        resolved[original_idx] = Some(price);
+                    let position = found
+                        .iter()
+                        .rposition(|price| provider::symbol_answers(&symbol, &price.symbol));
+                    if let Some(position) = position {
+                        resolved[original_idx] = Some(found.remove(position));
                     } else {
                         next_pending.push((original_idx, symbol));
                     }
@@ -418,6 +831,9 @@ async fn fetch_prices_with_provider_fallback(
                 pending = next_pending;
             }
             Err(err) if is_ignorable_price_error(&err) => {
+                if matches!(err, error::Error::NoResults { .. }) {
+                    queried.push(prov.name());
+                }
                 info!(provider = prov.id(), error = %err, "skipping provider during price fallback");
             }
             Err(err) => {
@@ -432,58 +848,602 @@ async fn fetch_prices_with_provider_fallback(
         if let Some(err) = last_non_ignorable_error {
             return Err(err);
         }
-        return Err(error::Error::NoResults);
+        return Err(error::Error::no_results(
+            queried.join(", "),
+            symbols,
+            &currencies.join(","),
+        ));
+    }
+
+    Ok(provider::PriceBatch {
+        prices,
+        missing: pending
+            .into_iter()
+            .map(|(_, symbol)| symbol.trim().to_uppercase())
+            .collect(),
+    })
+}
+
+/// `--auto-provider` lookup: each asset class asks its preferred providers first and
+/// falls back to the others, and the rows are merged back into request order.
+async fn fetch_prices_by_asset_class(
+    providers: &[Box<dyn provider::PriceProvider>],
+    provider_indices: &[usize],
+    aliases: &config::SymbolAliases,
+    symbols: &[String],
+    currencies: &[String],
+) -> Result<provider::PriceBatch> {
+    let ids: Vec<&str> = provider_indices
+        .iter()
+        .map(|&idx| providers[idx].id())
+        .collect();
+    let groups = resolve::group_by_class(symbols, aliases);
+    let results = futures::future::join_all(groups.iter().map(|(class, members)| {
+        let order: Vec<usize> = resolve::route(*class, &ids)
+            .into_iter()
+            .map(|pos| provider_indices[pos])
+            .collect();
+        info!(
+            class = ?class,
+            symbols = ?members,
+            providers = ?provider_ids_for_indices(providers, &order),
+            "routing symbols by asset class"
+        );
+        async move {
+            fetch_prices_in_with_provider_fallback(providers, &order, members, currencies).await
+        }
+    }))
+    .await;
+
+    let mut found = Vec::new();
+    let mut no_results = None;
+    for result in results {
+        match result {
+            Ok(batch) => found.extend(batch.prices),
+            Err(err @ error::Error::NoResults { .. }) => {
+                no_results.get_or_insert(err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    if found.is_empty() {
+        return Err(no_results
+            .unwrap_or_else(|| error::Error::no_results("", symbols, &currencies.join(","))));
+    }
+
+    let mut prices = Vec::new();
+    let mut missing = Vec::new();
+    for symbol in symbols {
+        match found
+            .iter()
+            .position(|price| provider::symbol_answers(symbol, &price.symbol))
+        {
+            Some(position) => prices.push(found.remove(position)),
+            None => missing.push(symbol.trim().to_uppercase()),
+        }
+    }
+    Ok(provider::PriceBatch { prices, missing })
+}
+
+/// Closing prices for `--since` never change once the day is over.
+const SINCE_CACHE_TTL_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// A `--since` reference close, in the currency the provider reported it in.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct SinceClose {
+    price: f64,
+    currency: String,
+}
+
+/// Replace the sparklines of rows a fallback provider answered with that provider's
+/// own history, so each trend matches the price beside it.
+///
+/// The primary provider's sparklines are fetched alongside the prices; rows that
+/// came from another provider are only known afterwards and cost one more round.
+async fn refetch_fallback_sparklines(
+    sparklines: &mut Vec<provider::PriceHistory>,
+    prices: &[provider::CoinPrice],
+    providers: &[Box<dyn provider::PriceProvider>],
+    primary_idx: usize,
+    currency: &str,
+) {
+    let mut plans: Vec<(usize, fetch::HistoryPlan)> = Vec::new();
+    let mut tickets: Vec<(usize, &str, fetch::Ticket)> = Vec::new();
+    for price in prices {
+        let Some(idx) = provider::source_index(providers, price).filter(|&idx| {
+            idx != primary_idx
+                && providers[idx]
+                    .capabilities()
+                    .supports(provider::Capability::History)
+        }) else {
+            continue;
+        };
+        let pos = match plans.iter().position(|(planned, _)| *planned == idx) {
+            Some(pos) => pos,
+            None => {
+                plans.push((
+                    idx,
+                    fetch::HistoryPlan::new(chrono::Utc::now().date_naive()),
+                ));
+                plans.len() - 1
+            }
+        };
+        let ticket = plans[pos].1.add(
+            &price.symbol,
+            currency,
+            fetch::HistoryRange::LastDays(SPARKLINE_DAYS),
+        );
+        tickets.push((pos, price.symbol.as_str(), ticket));
+    }
+    let results = join_all(
+        plans
+            .iter()
+            .map(|(idx, plan)| plan.run(providers[*idx].as_ref())),
+    )
+    .await;
+    for (pos, symbol, ticket) in tickets {
+        sparklines.retain(|history| !history.symbol.eq_ignore_ascii_case(symbol));
+        if let Some(history) = results[pos].history(ticket) {
+            sparklines.push(history.clone());
+        }
+    }
+}
+
+/// `--since` closes already cached for `date`, keyed by upper-cased symbol and currency,
+/// and the quotes that still need a history request.
+async fn cached_since_closes<'a>(
+    cache_dir: Option<&Path>,
+    prov: &dyn provider::PriceProvider,
+    quotes: &'a [Quote],
+    date: NaiveDate,
+) -> (HashMap<(String, String), SinceClose>, Vec<&'a Quote>) {
+    let cached = join_all(quotes.iter().map(|quote| async move {
+        let key = since_cache_key(quote, date);
+        provider::cache::read_json::<SinceClose>(cache_dir, prov.id(), &key, SINCE_CACHE_TTL_SECS)
+            .await
+            .ok()
+    }))
+    .await;
+    let mut closes = HashMap::new();
+    let mut pending = Vec::new();
+    for (quote, close) in quotes.iter().zip(cached) {
+        match close {
+            Some(close) => {
+                closes.insert((quote.symbol.to_uppercase(), quote.currency.clone()), close);
+            }
+            None => pending.push(quote),
+        }
+    }
+    (closes, pending)
+}
+
+fn since_cache_key(quote: &Quote, date: NaiveDate) -> String {
+    format!(
+        "since:{}:{}:{}",
+        quote.symbol.to_uppercase(),
+        quote.currency,
+        date
+    )
+}
+
+/// Fill the `--delta` fields of each row from the prices the previous run remembered.
+fn apply_deltas(prices: &mut [provider::CoinPrice], baseline: &state::DeltaState) {
+    for price in prices.iter_mut() {
+        let delta = baseline.delta(price);
+        price.delta_pct = delta.map(|delta| delta.change_pct);
+        price.delta_since = delta.map(|delta| delta.since);
+    }
+}
+
+/// Fill the `--since` fields of each row and return the symbols left without a close.
+///
+/// A close in another currency is only usable when the row itself was converted from
+/// it, in which case the row's `fx_rate` applies.
+fn apply_since_closes(
+    prices: &mut [provider::CoinPrice],
+    closes: &HashMap<(String, String), SinceClose>,
+    date: NaiveDate,
+) -> Vec<String> {
+    let mut missing = Vec::new();
+    for price in prices.iter_mut() {
+        price.since_date = Some(date);
+        let close = closes
+            .iter()
+            .find(|((symbol, currency), _)| {
+                provider::symbol_answers(symbol, &price.symbol)
+                    && currency.eq_ignore_ascii_case(&price.currency)
+            })
+            .and_then(|(_, close)| {
+                if close.currency.eq_ignore_ascii_case(&price.currency) {
+                    Some(close.price)
+                } else if price.converted {
+                    price.fx_rate.map(|rate| close.price * rate)
+                } else {
+                    None
+                }
+            })
+            .filter(|close| *close > 0.0);
+        price.since_price = close;
+        price.since_change_pct = close.map(|close| (price.price - close) / close * 100.0);
+        if close.is_none() {
+            missing.push(price.symbol.to_uppercase());
+        }
     }
+    missing
+}
 
-    Ok(prices)
+/// Print CoinGecko "did you mean" hints to stderr for symbols that returned no price.
+async fn print_symbol_suggestions(
+    symbols: &[String],
+    client: &provider::http::HttpClient,
+    settings: &config::ProviderSettings,
+) {
+    let coingecko = provider::coingecko_from_settings(settings, client);
+    let futures = symbols
+        .iter()
+        .map(|symbol| coingecko.suggest_ids(symbol, SUGGESTION_LIMIT));
+
+    for (symbol, suggestions) in symbols.iter().zip(join_all(futures).await) {
+        if !suggestions.is_empty() {
+            eprintln!(
+                "hint: no price for '{}' -- did you mean: {}?",
+                symbol,
+                suggestions.join(", ")
+            );
+        }
+    }
 }
 
 #[derive(Parser)]
 #[command(
     name = "pricr",
     version = APP_VERSION,
-    about = "Fetch crypto and stock prices from your terminal"
+    about = "Fetch crypto and stock prices from your terminal",
+    after_help = EXIT_CODES_HELP,
+    group(clap::ArgGroup::new("history").args(["chart", "stats", "correlation", "roi"]).multiple(true)),
+    group(clap::ArgGroup::new("exports").args(["export", "export_html"]).multiple(true))
 )]
 struct Cli {
-    /// Asset symbols to look up (e.g. btc eth aapl msft) or watchlists via @name
+    /// Asset symbols to look up (e.g. btc eth aapl msft) or watchlists via @name; `-` reads stdin
     symbols: Vec<String>,
 
+    /// Read additional symbols from FILE (whitespace-separated, `#` starts a comment)
+    #[arg(long, value_name = "FILE")]
+    symbols_file: Option<PathBuf>,
+
     /// Output as JSON
     #[arg(long)]
     json: bool,
 
+    /// JSON layout: legacy bare arrays, or the versioned v1 envelope
+    #[arg(long, value_enum, value_name = "VERSION", default_value_t = SchemaArg::Legacy)]
+    schema: SchemaArg,
+
+    /// Print the JSON Schema of the --schema v1 envelope and exit
+    #[arg(long, exclusive = true)]
+    print_schema: bool,
+
+    /// On failure, also print {"error": {kind, message, exit_code}} to stdout
+    #[arg(long)]
+    json_errors: bool,
+
+    /// Output format (table, json, ndjson with one object per line, waybar status JSON, or markdown)
+    #[arg(long, value_enum, conflicts_with = "json")]
+    format: Option<FormatArg>,
+
+    /// When to color output; auto disables color for pipes and when NO_COLOR is set
+    #[arg(long, value_enum, default_value_t = ColorArg::Auto)]
+    color: ColorArg,
+
+    /// Plain-text line template, e.g. "{symbol} {price} {change_24h}" (also sets waybar text)
+    #[arg(long, conflicts_with = "json")]
+    template: Option<String>,
+
+    /// Print only each symbol's price (or --field), one per line; converted amounts in calc mode
+    #[arg(
+        long,
+        short,
+        conflicts_with_all = [
+            "json", "format", "template", "sort", "alerts", "watch", "history", "search", "top",
+            "trending", "exchanges", "check_providers", "global", "quota", "list_providers"
+        ]
+    )]
+    quiet: bool,
+
+    /// Price field --quiet prints instead of the price, e.g. change_24h or market_cap
+    #[arg(long, value_name = "FIELD", requires = "quiet", value_parser = output::quiet::parse_field)]
+    field: Option<String>,
+
+    /// Render output through a Handlebars template file, or a built-in: markdown-table, html-report
+    #[cfg(feature = "templates")]
+    #[arg(
+        long,
+        value_name = "FILE|NAME",
+        conflicts_with_all = [
+            "json", "format", "template", "stats", "correlation", "roi", "watch", "search",
+            "top", "trending", "exchanges", "check_providers", "quiet"
+        ]
+    )]
+    template_file: Option<String>,
+
+    /// Render output through an inline Handlebars template, e.g. "{{#each prices}}{{symbol}} {{/each}}"
+    #[cfg(feature = "templates")]
+    #[arg(
+        long,
+        value_name = "TEXT",
+        conflicts_with_all = [
+            "template_file", "json", "format", "template", "stats", "correlation", "roi", "watch",
+            "search", "top", "trending", "exchanges", "check_providers", "quiet"
+        ]
+    )]
+    template_str: Option<String>,
+
+    /// Also put the result on the clipboard: the price, the converted amount, or the --json output
+    #[cfg(feature = "clipboard")]
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "history", "watch", "search", "top", "trending", "exchanges", "check_providers"
+        ]
+    )]
+    copy: bool,
+
+    /// Clipboard text with --template placeholders, e.g. "{symbol} {price}" or "{to_amount} {to_symbol}"
+    #[cfg(feature = "clipboard")]
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        requires = "copy",
+        conflicts_with = "json"
+    )]
+    copy_format: Option<String>,
+
     /// Plot historical price charts
     #[arg(long)]
     chart: bool,
 
-    /// Chart interval preset (1D, 5D, 1M, 6M, YTD, 1Y, 5Y, ALL)
-    #[arg(long, value_enum, default_value_t = ChartRangeArg::OneMonth)]
-    interval: ChartRangeArg,
+    /// Print volatility, return, and drawdown statistics from daily history instead of a chart
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "columns", "sampling", "resample_method", "fill", "events", "exports"
+        ]
+    )]
+    stats: bool,
+
+    /// Print the correlation matrix of daily log returns across the symbols instead of a chart
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "stats", "columns", "sampling", "resample_method", "fill", "events", "exports"
+        ]
+    )]
+    correlation: bool,
+
+    /// Value a lump sum bought on --start-date at today's price (e.g. --amount 1000usd)
+    #[arg(
+        long,
+        requires_all = ["amount", "start_date"],
+        conflicts_with_all = [
+            "stats", "correlation", "interval", "end_date", "columns", "sampling",
+            "resample_method", "fill", "events", "exports"
+        ]
+    )]
+    roi: bool,
+
+    /// Add a 7-day sparkline column to the price table
+    #[arg(long, conflicts_with = "history")]
+    sparkline: bool,
+
+    /// Add pre-market, post-market, and 52-week range columns (equities only)
+    #[arg(long, conflicts_with = "history")]
+    extended: bool,
+
+    /// Held amounts to value (comma-separated: 0.5 per symbol in order, btc=0.5, or portfolio),
+    /// or the sum to invest with --roi (e.g. 1000usd)
+    #[arg(long, value_name = "AMOUNT", value_delimiter = ',', value_parser = parse_amount_arg)]
+    amount: Vec<AmountArg>,
+
+    /// Add all-time high, its date, and distance from it as columns (CoinGecko)
+    #[arg(long, conflicts_with = "history")]
+    ath: bool,
+
+    /// Add bid, ask, and bid/ask spread columns (providers with quote data, e.g. Yahoo)
+    #[arg(long, conflicts_with = "history")]
+    spread: bool,
+
+    /// Add a column with the age of each quote, yellow once older than [display].stale_after_secs
+    #[arg(long, conflicts_with = "history")]
+    age: bool,
+
+    /// Add the closing price on a past UTC date and the change since then (YYYY-MM-DD, 7d, yesterday, ...)
+    #[arg(long, value_name = "DATE", value_parser = parse_since_date, conflicts_with = "history")]
+    since: Option<NaiveDate>,
+
+    /// Add the change since the previous --delta run, remembered in $XDG_STATE_HOME/pricr
+    #[arg(long, conflicts_with = "history")]
+    delta: bool,
+
+    /// Forget the prices earlier --delta runs remembered, starting a new baseline
+    #[arg(long, requires = "delta")]
+    delta_reset: bool,
+
+    /// Percent-change columns in the price table (comma-separated: 24h, 7d, 30d)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [ChangeArg::Day]
+    )]
+    changes: Vec<ChangeArg>,
+
+    /// Exact price table columns, comma-separated (e.g. symbol,price,volume,rank)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        conflicts_with_all = ["changes", "sparkline", "extended", "ath", "spread", "age"]
+    )]
+    columns: Vec<ColumnArg>,
+
+    /// Sort price rows by a field (rows without a value go last)
+    #[arg(long, value_enum, conflicts_with_all = ["history", "search"])]
+    sort: Option<SortArg>,
+
+    /// Sort in descending order
+    #[arg(long, requires = "sort")]
+    desc: bool,
+
+    /// Chart interval preset [default: [chart] interval or 1M]
+    #[arg(
+        long,
+        value_name = "RANGE",
+        value_parser = NamedValueParser::<provider::ChartRange>::new(
+            provider::ChartRange::ALL.map(provider::ChartRange::label)
+        )
+    )]
+    interval: Option<provider::ChartRange>,
+
+    /// Sampling density for chart mode [default: [chart] sampling or auto]
+    #[arg(
+        long,
+        value_name = "SAMPLING",
+        value_parser = NamedValueParser::<provider::HistoryInterval>::new(
+            provider::HistoryInterval::ALL.map(provider::HistoryInterval::as_str)
+        )
+    )]
+    sampling: Option<provider::HistoryInterval>,
+
+    /// How 4h/weekly/monthly sampling collapses each bucket
+    #[arg(long, value_enum, default_value_t = ResampleMethodArg::Last, requires = "chart")]
+    resample_method: ResampleMethodArg,
 
-    /// Sampling density for chart mode
-    #[arg(long, value_enum, default_value_t = SamplingArg::Auto)]
-    sampling: SamplingArg,
+    /// Fill missing days in chart history: carry the previous price or interpolate
+    #[arg(long, value_enum, default_value_t = FillArg::None, requires = "chart")]
+    fill: FillArg,
 
-    /// End date for chart mode in UTC (YYYY-MM-DD)
-    #[arg(long, value_parser = parse_chart_end_date, requires = "chart")]
+    /// End date for chart and stats mode in UTC (YYYY-MM-DD, YYYY-MM, yesterday, or 7d, 3w, 6m, 2y back)
+    #[arg(long, value_parser = parse_chart_end_date, requires = "history")]
     end_date: Option<NaiveDate>,
 
-    /// Start date for chart mode in UTC (YYYY-MM-DD). Overrides --interval preset.
-    #[arg(long, value_parser = parse_chart_end_date, requires = "chart")]
-    start_date: Option<NaiveDate>,
+    /// Start date for chart and stats mode in UTC; offsets like 6m count back from --end-date. Overrides --interval preset.
+    #[arg(long, value_parser = parse_chart_start_date, requires = "history")]
+    start_date: Option<dates::DateSpec>,
+
+    /// Mark dividends and splits on stock charts (Yahoo Finance only)
+    #[arg(long, requires = "chart")]
+    events: bool,
+
+    /// Write the charted history to FILE (.csv or .json, chosen by extension)
+    #[arg(long, value_name = "FILE", requires = "chart")]
+    export: Option<PathBuf>,
+
+    /// Write a standalone HTML report with an SVG chart and stats per symbol to FILE
+    #[arg(long, value_name = "FILE", requires = "chart")]
+    export_html: Option<PathBuf>,
+
+    /// Render the charted history as a PNG image to FILE, one file per symbol unless --overlay
+    #[cfg(feature = "png")]
+    #[arg(long, value_name = "FILE", requires = "chart", group = "exports")]
+    export_png: Option<PathBuf>,
+
+    /// PNG image size as WIDTHxHEIGHT
+    #[cfg(feature = "png")]
+    #[arg(
+        long,
+        value_name = "WxH",
+        value_parser = output::png::parse_size,
+        requires = "export_png"
+    )]
+    size: Option<(u32, u32)>,
+
+    /// Draw every symbol into one PNG as percent change from its first point
+    #[cfg(feature = "png")]
+    #[arg(long, requires = "export_png")]
+    overlay: bool,
+
+    /// PNG color theme
+    #[cfg(feature = "png")]
+    #[arg(long, value_enum, default_value_t = ThemeArg::Light, requires = "export_png")]
+    theme: ThemeArg,
+
+    /// Overwrite an existing --export, --export-html, or --export-png file
+    #[arg(long, requires = "exports")]
+    force: bool,
+
+    /// Chart width in terminal columns (defaults to terminal width, or 80 when piped)
+    #[arg(long, value_parser = clap::value_parser!(u16).range(config::MIN_CHART_WIDTH as i64..))]
+    chart_width: Option<u16>,
+
+    /// Chart height in terminal rows (defaults to terminal height, or 20 when piped)
+    #[arg(long, value_parser = clap::value_parser!(u16).range(config::MIN_CHART_HEIGHT as i64..))]
+    chart_height: Option<u16>,
 
     /// Price provider to use
-    #[arg(long, short)]
+    #[arg(long, short, value_parser = ProviderIdParser)]
     provider: Option<String>,
 
+    /// Route each symbol to providers for its asset class (crypto, stocks, fiat)
+    #[arg(long, conflicts_with = "provider")]
+    auto_provider: bool,
+
     /// Fiat currency for prices
     #[arg(long, short)]
     currency: Option<String>,
 
-    /// API key for providers that require one
-    #[arg(long, env = "COINMARKETCAP_API_KEY")]
+    /// Show prices in several fiat currencies, one column each (comma-separated, at most 5)
+    #[arg(
+        long,
+        value_name = "CODES",
+        value_delimiter = ',',
+        conflicts_with_all = ["currency", "history", "columns"]
+    )]
+    currencies: Vec<String>,
+
+    /// Do not convert prices a provider quotes in another fiat currency
+    #[arg(long)]
+    no_convert: bool,
+
+    /// API key for CoinMarketCap, or for CoinGecko with --provider coingecko
+    #[arg(long)]
     api_key: Option<String>,
 
+    /// Exchange suffix Stooq appends to symbols without one (us, uk, de, jp, pl, hk, hu)
+    #[arg(long, value_name = "CODE", value_parser = provider::stooq::parse_market)]
+    market: Option<String>,
+
+    /// Per-request timeout in seconds (overrides [http] timeout_secs)
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Skip the on-disk response cache: always fetch, and do not store responses
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Answer from the response cache only and never touch the network
+    #[arg(long, conflicts_with_all = ["no_cache", "check_providers", "webhook"])]
+    offline: bool,
+
+    /// Decimal places for prices and amounts in tables (default: adaptive)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=output::fmt::MAX_PRECISION as i64))]
+    precision: Option<u8>,
+
+    /// Time zone for displayed timestamps: an IANA name (e.g. Asia/Tokyo), utc, or local
+    #[arg(long, value_name = "ZONE")]
+    tz: Option<output::tz::DisplayTz>,
+
+    /// Group thousands in table output (overrides [display] group_digits)
+    #[arg(long, overrides_with = "no_group_digits")]
+    group_digits: bool,
+
+    /// Do not group thousands in table output
+    #[arg(long, overrides_with = "group_digits")]
+    no_group_digits: bool,
+
+    /// Hide provider credit lines such as "Data by CoinGecko" (also [display] attribution)
+    #[arg(long)]
+    no_attribution: bool,
+
     /// Explicit config file path (overrides XDG lookup)
     #[arg(long)]
     config: Option<PathBuf>,
@@ -492,16 +1452,54 @@ struct Cli {
     #[arg(long)]
     list_providers: bool,
 
+    /// Show this month's CoinMarketCap API credit usage against [provider.cmc] monthly_credits
+    #[arg(long)]
+    quota: bool,
+
+    /// Probe each provider with a BTC/USD (or AAPL) lookup and report status and latency
+    #[arg(long, conflicts_with_all = ["history", "search", "watch"])]
+    check_providers: bool,
+
+    /// Print the effective [aliases] table from config
+    #[arg(long)]
+    show_aliases: bool,
+
     /// Search ticker symbols by keyword (provider-dependent)
     #[arg(
         long,
         short = 's',
-        conflicts_with = "chart",
+        conflicts_with = "history",
         conflicts_with = "symbols"
     )]
     search: Option<String>,
 
-    /// Max ticker search results
+    /// List the top N coins by market cap instead of looking up symbols (max 250)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..),
+        conflicts_with_all = ["history", "search", "symbols", "symbols_file"]
+    )]
+    top: Option<u16>,
+
+    /// Print total market cap, 24h volume, BTC/ETH dominance, and active coin count
+    /// (above the price table when symbols are given)
+    #[arg(long, conflicts_with_all = ["history", "search", "top"])]
+    global: bool,
+
+    /// List the most-searched coins on CoinGecko with their current price
+    #[arg(long, conflicts_with_all = ["history", "search", "top", "global", "symbols", "symbols_file"])]
+    trending: bool,
+
+    /// List the exchanges and pairs a coin trades on, highest volume first
+    #[arg(
+        long,
+        value_name = "SYMBOL",
+        conflicts_with_all = ["history", "search", "top", "global", "trending", "symbols", "symbols_file"]
+    )]
+    exchanges: Option<String>,
+
+    /// Max ticker search results (also limits --exchanges rows)
     #[arg(
         long,
         default_value_t = 10,
@@ -509,12 +1507,114 @@ struct Cli {
     )]
     search_limit: u8,
 
+    /// Search every provider with ticker search at once and merge their matches
+    #[arg(long, conflicts_with = "provider")]
+    search_all: bool,
+
+    /// Exit non-zero when any requested symbol has no price
+    #[arg(long, conflicts_with = "watch")]
+    strict: bool,
+
+    /// Keep refreshing prices every --watch-interval seconds
+    #[arg(long, conflicts_with_all = ["search", "end_date"])]
+    watch: bool,
+
+    /// Seconds between refreshes in watch mode
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = DEFAULT_WATCH_INTERVAL_SECS,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "watch"
+    )]
+    watch_interval: u64,
+
+    /// Alert condition, e.g. "btc>70000", "eth<=3000", or "btc%>5" for 24h change (repeatable)
+    #[arg(
+        long = "alert",
+        value_name = "EXPR",
+        value_parser = alerts::parse_alert,
+        conflicts_with_all = ["history", "search"]
+    )]
+    alerts: Vec<alerts::AlertCondition>,
+
+    /// Command run via `sh -c` when an alert fires; supports --template placeholders
+    #[arg(long, value_name = "CMD", requires = "alerts")]
+    alert_exec: Option<String>,
+
+    /// Exit with code 2 after the first triggered alert
+    #[arg(long, requires = "alerts")]
+    alert_once: bool,
+
+    /// POST every --watch refresh and triggered --alert as JSON to URL (see [webhook] in config)
+    #[arg(long, value_name = "URL", conflicts_with_all = ["history", "search"])]
+    webhook: Option<String>,
+
+    /// Serve Prometheus metrics for the requested symbols on ADDR (e.g. 0.0.0.0:9184)
+    #[cfg(feature = "metrics")]
+    #[arg(long, value_name = "ADDR", conflicts_with_all = ["history", "search", "json", "format", "template", "quiet", "watch", "alerts", "webhook"])]
+    serve_metrics: Option<std::net::SocketAddr>,
+
+    /// Minimum seconds between provider refreshes in metrics mode
+    #[cfg(feature = "metrics")]
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = pricr::server::DEFAULT_MIN_REFRESH.as_secs(),
+        requires = "serve_metrics"
+    )]
+    metrics_min_refresh: u64,
+
+    /// Save every provider response into DIR for later --replay
+    #[cfg(feature = "devtools")]
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["replay", "offline"])]
+    record: Option<std::path::PathBuf>,
+
+    /// Serve provider responses recorded with --record from DIR, without network access
+    #[cfg(feature = "devtools")]
+    #[arg(long, value_name = "DIR", conflicts_with = "offline")]
+    replay: Option<std::path::PathBuf>,
+
+    /// Print a shell completion script to stdout
+    #[arg(long, value_name = "SHELL", value_enum)]
+    completions: Option<CompletionShell>,
+
+    /// After the output, print where the time went (config, providers, each HTTP request, rendering) to stderr
+    #[arg(long, conflicts_with = "watch")]
+    timings: bool,
+
     /// Increase log verbosity (-v, -vv, -vvv)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
+
+    /// Log line format: human-readable text or one JSON object per event
+    #[arg(long, value_name = "FORMAT", value_enum)]
+    log_format: Option<LogFormatArg>,
+
+    /// Append logs to PATH instead of stderr
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+}
+
+/// `[logging]` from the config file with `--log-format`/`--log-file` applied on top.
+///
+/// Config errors are ignored here; `run` reports them once logging is up.
+fn logging_settings(cli: &Cli) -> config::LoggingConfig {
+    let loaded = match cli.config.as_deref() {
+        Some(path) => config::load_from_path(path),
+        None => config::load(),
+    };
+    let mut logging = loaded.map(|cfg| cfg.logging).unwrap_or_default();
+    if let Some(format) = cli.log_format {
+        logging.format = Some(format.into());
+    }
+    if let Some(path) = &cli.log_file {
+        logging.file = Some(path.clone());
+    }
+    logging
 }
 
-fn init_logging(verbose: u8) {
+fn init_logging(verbose: u8, logging: &config::LoggingConfig) -> Result<()> {
     let default_level = match verbose {
         0 => "warn",
         1 => "info",
@@ -525,17 +1625,42 @@ fn init_logging(verbose: u8) {
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
 
-    tracing_subscriber::fmt()
+    let writer = match &logging.file {
+        Some(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| {
+                    error::Error::Config(format!("cannot open log file {}: {}", path.display(), e))
+                })?;
+            BoxMakeWriter::new(std::sync::Mutex::new(file))
+        }
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let builder = tracing_subscriber::fmt()
         .with_env_filter(env_filter)
-        .with_writer(std::io::stderr)
-        .init();
+        .with_writer(writer)
+        .with_ansi(logging.file.is_none());
+
+    match logging.format.unwrap_or_default() {
+        config::LogFormat::Text => builder.init(),
+        // Span close events carry `time.busy`/`time.idle` for each instrumented provider call.
+        config::LogFormat::Json => builder
+            .json()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_current_span(true)
+            .init(),
+    }
+    Ok(())
 }
 
-fn compute_chart_fetch_days(start_date: Option<NaiveDate>) -> u32 {
+/// Days of history covering `start_date..=end_date`, with a day to spare for
+/// providers whose points fall just outside the edges. Always at least one day.
+fn compute_chart_fetch_days(start_date: Option<NaiveDate>, end_date: NaiveDate) -> u32 {
     match start_date {
         Some(start) => {
-            let today = chrono::Utc::now().date_naive();
-            let days = (today - start).num_days().max(1);
+            let days = ((end_date - start).num_days() + CHART_FETCH_DAYS_BUFFER).max(1);
             (days as u32).min(MAX_CHART_FETCH_DAYS)
         }
         None => MAX_CHART_FETCH_DAYS,
@@ -551,117 +1676,1597 @@ fn filter_histories_by_time_window(
         history.points.retain(|point| {
             point.timestamp <= end && start.map(|s| point.timestamp >= s).unwrap_or(true)
         });
+        history.events.retain(|event| {
+            event.timestamp <= end && start.map(|s| event.timestamp >= s).unwrap_or(true)
+        });
     }
 
     histories.retain(|history| !history.points.is_empty());
 }
 
-#[tokio::main]
-async fn main() {
-    // Load .env before CLI parsing so env-backed args (e.g. COINMARKETCAP_API_KEY) pick it up.
-    let _ = dotenvy::dotenv();
+/// Compare charted series against the first one; empty for a single series.
+///
+/// Series with too little overlap are left out with a warning on stderr.
+fn chart_comparison(histories: &[provider::PriceHistory]) -> Vec<calc::stats::Comparison> {
+    if histories.len() < 2 {
+        return Vec::new();
+    }
+    let (rows, excluded) = calc::stats::compare(histories);
+    if !excluded.is_empty() {
+        output::table::print_comparison_excluded_warning(&excluded, &histories[0].symbol);
+    }
+    rows
+}
 
-    let cli = Cli::parse();
-    init_logging(cli.verbose);
+/// Template context for charts: each series with its `stats`, the comparison, and the range.
+fn history_report_context(histories: &[provider::PriceHistory], range: &str) -> serde_json::Value {
+    serde_json::json!({
+        "histories": output::json::histories_with_stats(histories),
+        "comparison": chart_comparison(histories),
+        "range": range,
+    })
+}
 
-    if let Err(e) = run(cli).await {
-        error!(error = %e, "fatal error");
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+fn print_chart_comparison(histories: &[provider::PriceHistory]) {
+    let rows = chart_comparison(histories);
+    if !rows.is_empty() {
+        output::table::print_comparison_table(&rows, &histories[0].symbol);
     }
 }
 
-async fn run(cli: Cli) -> Result<()> {
-    let app_config = match cli.config.as_deref() {
-        Some(path) => config::load_from_path(path)?,
-        None => config::load()?,
+/// Fetch `symbols` over the `(start, end)` window, dropping points outside it.
+///
+/// Providers without window support fetch `fetch_days` back from today instead.
+async fn fetch_history_window(
+    prov: &dyn provider::PriceProvider,
+    symbols: &[String],
+    currency: &str,
+    (start, end): (
+        Option<chrono::DateTime<chrono::Utc>>,
+        chrono::DateTime<chrono::Utc>,
+    ),
+    interval: provider::HistoryInterval,
+    fetch_days: u32,
+) -> Result<Vec<provider::PriceHistory>> {
+    let mut histories = match prov
+        .get_price_history_window(symbols, currency, start, end, interval)
+        .await
+    {
+        Ok(histories) => histories,
+        Err(error::Error::Config(message))
+            if message.contains("does not support explicit chart date windows") =>
+        {
+            prov.get_price_history(symbols, currency, fetch_days, interval)
+                .await?
+        }
+        Err(other) => return Err(other),
     };
+    filter_histories_by_time_window(&mut histories, start, end);
+    if histories.is_empty() {
+        return Err(error::Error::no_results(prov.name(), symbols, currency));
+    }
+    Ok(histories)
+}
 
-    let search_query = resolve_search_query(&cli);
-
-    let merged_api_key = cli
-        .api_key
-        .or_else(|| app_config.coinmarketcap.api_key.clone());
-    let providers = provider::available_providers(merged_api_key);
-
-    let currency = cli
-        .currency
-        .or_else(|| app_config.defaults.currency.clone())
-        .unwrap_or_else(|| config::DEFAULT_CURRENCY.to_string());
-
-    if cli.list_providers {
-        println!("Available providers:");
-        for p in &providers {
-            println!("  {:12} {}", p.id(), p.name());
+/// Fetch what is newer than the last charted point and append it to `histories`.
+///
+/// Asks for a window starting at the oldest series' last timestamp; providers without
+/// window support refetch the whole history instead.
+async fn refresh_histories(
+    prov: &dyn provider::PriceProvider,
+    histories: &mut Vec<provider::PriceHistory>,
+    symbols: &[String],
+    currency: &str,
+    interval: provider::HistoryInterval,
+    fetch_days: u32,
+) -> Result<()> {
+    let since = histories
+        .iter()
+        .filter_map(|history| history.points.last())
+        .map(|point| point.timestamp)
+        .min();
+    let now = chrono::Utc::now();
+    match prov
+        .get_price_history_window(symbols, currency, since, now, interval)
+        .await
+    {
+        Ok(fresh) => {
+            for newer in fresh {
+                match histories
+                    .iter_mut()
+                    .find(|history| history.symbol.eq_ignore_ascii_case(&newer.symbol))
+                {
+                    Some(history) => history.append_newer(newer),
+                    None => histories.push(newer),
+                }
+            }
         }
-        return Ok(());
+        Err(error::Error::Config(message))
+            if message.contains("does not support explicit chart date windows") =>
+        {
+            *histories = prov
+                .get_price_history(symbols, currency, fetch_days, interval)
+                .await?;
+        }
+        Err(err) => return Err(err),
     }
+    Ok(())
+}
 
-    let provider_indices = resolve_provider_indices(
-        &providers,
-        cli.provider.as_deref(),
-        app_config.defaults.provider_order.as_deref(),
-    )?;
-    let primary_provider_idx = provider_indices[0];
-    let prov = &providers[primary_provider_idx];
-
-    if let Some(query) = search_query {
-        if query.is_empty() {
-            return Err(error::Error::Config(
-                "search mode requires a query -- usage: pricr --search apple".into(),
-            ));
+/// Apply `--fill` to each series and warn about gaps too long to fill completely.
+fn fill_history_gaps(histories: &mut [provider::PriceHistory], method: calc::FillMethod) {
+    if method == calc::FillMethod::None {
+        return;
+    }
+    let mut truncated = Vec::new();
+    for history in histories.iter_mut() {
+        let result = calc::fill_gaps(&history.points, method);
+        if result.truncated_gaps > 0 {
+            truncated.push(history.symbol.clone());
         }
+        history.points = result.points;
+    }
+    if !truncated.is_empty() {
+        output::table::print_fill_cap_warning(&truncated);
+    }
+}
 
-        let matches = if cli.provider.is_some() {
-            info!(provider = prov.id(), query = %query, limit = cli.search_limit, "searching tickers");
-            prov.search_tickers(&query, cli.search_limit as usize)
-                .await?
-        } else {
-            let ordered_ids = provider_ids_for_indices(&providers, &provider_indices);
-            info!(
-                providers = ?ordered_ids,
-                query = %query,
-                limit = cli.search_limit,
-                "searching tickers across providers"
-            );
-            search_tickers_across_providers(
-                &providers,
-                &provider_indices,
-                &query,
-                cli.search_limit as usize,
-            )
-            .await?
-        };
+/// Bucket each series for `--sampling 4h|weekly|monthly`; other samplings are left as fetched.
+fn resample_histories(
+    histories: &mut [provider::PriceHistory],
+    interval: provider::HistoryInterval,
+    method: calc::ResampleMethod,
+) {
+    for history in histories.iter_mut() {
+        history.points = calc::resample(&history.points, interval, method);
+    }
+}
 
-        if cli.json {
-            output::json::print_ticker_matches_json(&matches)?;
-        } else {
-            output::table::print_ticker_matches_table(&matches);
-        }
+#[tokio::main]
+async fn main() {
+    // Load .env before CLI parsing so env-backed settings (e.g. COINMARKETCAP_API_KEY) pick it up.
+    let _ = dotenvy::dotenv();
 
-        return Ok(());
+    if std::env::args().nth(1).as_deref() == Some(CACHE_COMMAND) {
+        // `cache` takes the place of the binary name so clap sees `ls`/`clear` as the subcommand.
+        let cache_cli = CacheCli::parse_from(std::env::args().skip(1));
+        if let Err(e) = run_cache_command(cache_cli) {
+            eprintln!("Error: {}", e);
+            std::process::exit(e.exit_code());
+        }
+        return;
     }
 
-    let symbols = expand_symbol_tokens(&cli.symbols, &app_config.watchlists)?;
-
-    if symbols.is_empty() {
-        return Err(error::Error::Config(
-            "no symbols provided -- usage: pricr btc eth".into(),
-        ));
+    #[cfg(feature = "keyring")]
+    if std::env::args().nth(1).as_deref() == Some(CONFIG_COMMAND) {
+        let config_cli = ConfigCli::parse_from(std::env::args().skip(1));
+        if let Err(e) = run_config_command(config_cli) {
+            eprintln!("Error: {}", e);
+            std::process::exit(e.exit_code());
+        }
+        return;
     }
 
-    let chart_end_date = cli
-        .end_date
-        .unwrap_or_else(|| chrono::Utc::now().date_naive());
-    if chart_end_date > chrono::Utc::now().date_naive() {
-        return Err(error::Error::Config(
-            "chart end date cannot be in the future".into(),
-        ));
+    if std::env::args().nth(1).as_deref() == Some(COMPLETE_SYMBOLS_COMMAND) {
+        // Runs on every <TAB>: stay offline and never fail loudly on a broken config.
+        let app_config = config::load().unwrap_or_default();
+        for symbol in completion_symbols(&app_config) {
+            println!("{}", symbol);
+        }
+        return;
     }
 
-    let chart_start_date = cli
-        .start_date
-        .or_else(|| cli.interval.start_date(chart_end_date));
+    let shorthand = std::env::args()
+        .nth(1)
+        .filter(|arg| SHORTHAND_COMMANDS.contains(&arg.as_str()));
+    let cli = if let Some(command) = shorthand {
+        // `pricr stats btc` is shorthand for `pricr --stats btc`.
+        Cli::parse_from(std::env::args().enumerate().map(|(idx, arg)| {
+            if idx == 1 {
+                format!("--{}", command)
+            } else {
+                arg
+            }
+        }))
+    } else {
+        Cli::parse()
+    };
+    if let Some(shell) = cli.completions {
+        // Nothing but the script may reach stdout so `eval "$(pricr --completions zsh)"` works.
+        print!("{}", completion_script(shell));
+        return;
+    }
+    if let Err(e) = init_logging(cli.verbose, &logging_settings(&cli)) {
+        eprintln!("Error: {}", e);
+        std::process::exit(e.exit_code());
+    }
+    let json_errors = cli.json_errors;
+    let json_output = cli.json || matches!(cli.format, Some(FormatArg::Json | FormatArg::Ndjson));
+    if cli.timings {
+        timings::enable();
+    }
+
+    // Ctrl-C cancels in-flight provider requests and drops the lookup at its next await,
+    // so nothing half-rendered reaches the terminal and guards such as the hidden cursor
+    // restore it on the way out.
+    let shutdown = CancellationToken::new();
+    tokio::spawn(cancel_on_interrupt(shutdown.clone()));
+    let result = tokio::select! {
+        biased;
+        _ = shutdown.cancelled() => Err(error::Error::Interrupted),
+        result = run(cli, shutdown.clone()) => result,
+    };
+    if let Err(e @ error::Error::Interrupted) = &result {
+        std::process::exit(e.exit_code());
+    }
+    // Price lookups in JSON mode already nested the timings in their output.
+    if let Some(timings) = timings::take() {
+        if json_output {
+            let _ = output::json::print_timings_json(&timings);
+        } else {
+            output::table::print_timings(&timings);
+        }
+    }
+    if let Err(e) = result {
+        error!(error = %e, "fatal error");
+        eprintln!("Error: {}", e);
+        if let Some(hint) = e.hint() {
+            eprintln!("hint: {}", hint);
+        }
+        if json_errors {
+            output::json::print_error_json(&e);
+        }
+        std::process::exit(e.exit_code());
+    }
+}
+
+/// Cancel `shutdown` on the first Ctrl-C.
+///
+/// A second Ctrl-C, or a lookup still busy outside an await a moment later, ends the
+/// process right away with the same exit code.
+async fn cancel_on_interrupt(shutdown: CancellationToken) {
+    if tokio::signal::ctrl_c().await.is_err() {
+        return;
+    }
+    shutdown.cancel();
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = tokio::time::sleep(std::time::Duration::from_secs(2)) => {}
+    }
+    std::process::exit(error::Error::Interrupted.exit_code());
+}
+
+/// `pricr cache ...`: inspect or purge the on-disk response cache.
+#[derive(Debug, Parser)]
+#[command(
+    name = "pricr cache",
+    bin_name = "pricr cache",
+    version = APP_VERSION,
+    about = "Inspect or purge the on-disk response cache"
+)]
+struct CacheCli {
+    #[command(subcommand)]
+    action: CacheAction,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum CacheAction {
+    /// List cached responses with provider, key, age, and size
+    Ls {
+        /// Print entries as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete cached responses (everything unless filtered)
+    Clear {
+        /// Only entries of this provider (e.g. coingecko, cmc)
+        #[arg(long)]
+        provider: Option<String>,
+        /// Only entries older than AGE (e.g. 30m, 12h, 1d, 2w)
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Option<chrono::Duration>,
+    },
+}
+
+/// Parse an age such as `90s`, `30m`, `12h`, `1d`, or `2w`.
+fn parse_age(value: &str) -> std::result::Result<chrono::Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+    let amount: i64 = digits
+        .parse()
+        .map_err(|_| format!("invalid age '{}' -- use e.g. 30m, 12h, 1d", value))?;
+    match unit.to_ascii_lowercase().as_str() {
+        "s" => Ok(chrono::Duration::seconds(amount)),
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(format!(
+            "invalid age unit in '{}' -- use s, m, h, d, or w",
+            value
+        )),
+    }
+}
+
+fn run_cache_command(cli: CacheCli) -> Result<()> {
+    let dir = provider::cache::cache_dir().ok_or_else(|| {
+        error::Error::Config("cannot locate the cache directory: HOME is not set".to_string())
+    })?;
+    match cli.action {
+        CacheAction::Ls { json } => {
+            let entries = provider::cache::list_entries(&dir);
+            if json {
+                output::json::print_cache_entries_json(&entries)?;
+            } else if entries.is_empty() {
+                println!("No cached responses in {}", dir.display());
+            } else {
+                output::table::print_cache_entries_table(&entries);
+            }
+        }
+        CacheAction::Clear {
+            provider,
+            older_than,
+        } => {
+            let summary = provider::cache::clear_entries(
+                &dir,
+                &provider::cache::ClearFilter {
+                    provider,
+                    older_than,
+                },
+            );
+            println!(
+                "Removed {} cached {} ({})",
+                summary.removed,
+                if summary.removed == 1 {
+                    "response"
+                } else {
+                    "responses"
+                },
+                output::fmt::format_bytes(summary.bytes)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `pricr config ...`: keep provider API keys in the OS keyring.
+#[cfg(feature = "keyring")]
+#[derive(Debug, Parser)]
+#[command(
+    name = "pricr config",
+    bin_name = "pricr config",
+    version = APP_VERSION,
+    about = "Store provider API keys in the OS keyring"
+)]
+struct ConfigCli {
+    #[command(subcommand)]
+    action: ConfigAction,
+}
+
+#[cfg(feature = "keyring")]
+#[derive(Debug, clap::Subcommand)]
+enum ConfigAction {
+    /// Prompt for a provider's API key (not echoed, or read from piped stdin) and store it
+    #[command(name = "set-key")]
+    Set {
+        /// Provider the key is for (cmc, coingecko)
+        #[arg(value_parser = pricr::keystore::parse_provider)]
+        provider: String,
+    },
+    /// Remove a provider's stored API key
+    #[command(name = "unset-key")]
+    Unset {
+        /// Provider the key is for (cmc, coingecko)
+        #[arg(value_parser = pricr::keystore::parse_provider)]
+        provider: String,
+    },
+    /// Print a provider's stored API key
+    #[command(name = "show-key")]
+    Show {
+        /// Provider the key is for (cmc, coingecko)
+        #[arg(value_parser = pricr::keystore::parse_provider)]
+        provider: String,
+        /// Show only the first and last four characters
+        #[arg(long)]
+        masked: bool,
+    },
+}
+
+#[cfg(feature = "keyring")]
+fn run_config_command(cli: ConfigCli) -> Result<()> {
+    use pricr::keystore;
+
+    match cli.action {
+        ConfigAction::Set { provider } => {
+            let key = if std::io::stdin().is_terminal() {
+                rpassword::prompt_password(format!("{} API key: ", provider))
+            } else {
+                let mut line = String::new();
+                std::io::stdin().read_line(&mut line).map(|_| line)
+            }
+            .map_err(|e| error::Error::Config(format!("cannot read the API key: {}", e)))?;
+            keystore::write(&keystore::entry(&provider)?, &key)?;
+            println!("Stored the {} API key in the OS keyring", provider);
+        }
+        ConfigAction::Unset { provider } => {
+            if keystore::delete(&keystore::entry(&provider)?)? {
+                println!("Removed the {} API key from the OS keyring", provider);
+            } else {
+                println!("No {} API key in the OS keyring", provider);
+            }
+        }
+        ConfigAction::Show { provider, masked } => {
+            let key = keystore::load(&provider)?.ok_or_else(|| {
+                error::Error::Config(format!(
+                    "no {} API key in the OS keyring -- store one with `pricr config set-key {}`",
+                    provider, provider
+                ))
+            })?;
+            if masked {
+                println!("{}", keystore::mask(&key));
+            } else {
+                println!("{}", key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Symbols offered by shell completion: config aliases and watchlists, built-in CoinGecko tickers, and fiat codes.
+fn completion_symbols(app_config: &config::AppConfig) -> Vec<String> {
+    let mut symbols: Vec<String> = app_config
+        .symbol_groups()
+        .keys()
+        .map(|name| format!("@{}", name))
+        .collect();
+    symbols.sort();
+
+    let builtin =
+        provider::coingecko::known_symbols().chain(calc::known_fiat_codes().iter().copied());
+    let known: std::collections::BTreeSet<String> = app_config
+        .aliases
+        .entries()
+        .map(|(alias, _, _)| alias.to_ascii_lowercase())
+        .chain(builtin.map(str::to_ascii_lowercase))
+        .collect();
+    symbols.extend(known);
+    symbols
+}
+
+/// Completion script for `shell`; zsh and fish also complete symbols via `pricr __complete-symbols`.
+fn completion_script(shell: CompletionShell) -> String {
+    let mut buf = Vec::new();
+    clap_complete::generate(
+        clap_complete::Shell::from(shell),
+        &mut <Cli as clap::CommandFactory>::command(),
+        "pricr",
+        &mut buf,
+    );
+    let mut script = String::from_utf8_lossy(&buf).into_owned();
+
+    match shell {
+        CompletionShell::Bash => {}
+        CompletionShell::Zsh => {
+            // Point the positional symbols spec at a helper defined before the compdef trailer.
+            if let Some(start) = script.find("'*::symbols -- ") {
+                let line_end = start + script[start..].find('\n').unwrap_or(script.len() - start);
+                let line = &script[start..line_end];
+                let patched = line.replacen(":_default'", ":_pricr_symbols'", 1);
+                script.replace_range(start..line_end, &patched);
+            }
+            let helper = format!(
+                "(( $+functions[_pricr_symbols] )) ||\n_pricr_symbols() {{\n    local -a symbols\n    symbols=(${{(f)\"$(pricr {} 2>/dev/null)\"}})\n    _describe -t symbols 'symbol' symbols\n}}\n\n",
+                COMPLETE_SYMBOLS_COMMAND
+            );
+            let trailer = script
+                .rfind("if [ \"$funcstack[1]\"")
+                .unwrap_or(script.len());
+            script.insert_str(trailer, &helper);
+        }
+        CompletionShell::Fish => {
+            script.push_str(&format!(
+                "complete -c pricr -f -a \"(pricr {} 2>/dev/null)\"\n",
+                COMPLETE_SYMBOLS_COMMAND
+            ));
+        }
+    }
+    script
+}
+
+fn ndjson_requested(cli: &Cli) -> bool {
+    cli.format == Some(FormatArg::Ndjson)
+}
+
+fn markdown_requested(cli: &Cli) -> bool {
+    cli.format == Some(FormatArg::Markdown)
+}
+
+/// `--format markdown` covers price lookups, conversions, search results, and charts.
+fn ensure_markdown_supported(cli: &Cli, mode: &str) -> Result<()> {
+    if markdown_requested(cli) {
+        return Err(error::Error::Config(format!(
+            "--format markdown is not available for {}",
+            mode
+        )));
+    }
+    Ok(())
+}
+
+/// `--schema v1` covers price lookups, top coins, charts, conversions, and search results.
+fn ensure_schema_supported(cli: &Cli, mode: &str) -> Result<()> {
+    if cli.schema == SchemaArg::V1 {
+        return Err(error::Error::Config(format!(
+            "--schema v1 is not available for {}",
+            mode
+        )));
+    }
+    Ok(())
+}
+
+fn status_output_requested(cli: &Cli) -> bool {
+    cli.format == Some(FormatArg::Waybar) || cli.template.is_some()
+}
+
+/// Fiat-to-fiat conversion rows for every target that has a rate, in request order.
+fn fiat_conversions(
+    fiat: &calc::FiatAmount,
+    targets: &[String],
+    rates: &provider::fiat::FiatRates,
+) -> Vec<calc::Conversion> {
+    targets
+        .iter()
+        .filter_map(|target| {
+            let upper = target.to_uppercase();
+            let fiat_rate = rates.rates.get(&upper)?;
+            Some(calc::Conversion {
+                from_amount: fiat.amount,
+                from_currency: fiat.currency.clone(),
+                to_symbol: upper.clone(),
+                to_name: calc::fiat_name(&upper).to_string(),
+                to_amount: fiat.amount * fiat_rate.rate,
+                rate: 1.0 / fiat_rate.rate,
+                provider: fiat_rate.provider.to_string(),
+                timestamp: chrono::Utc::now(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "templates")]
+type Report = output::template::Report;
+
+/// Stands in for the template report without the `templates` feature, where none can exist.
+#[cfg(not(feature = "templates"))]
+enum Report {}
+
+/// Parse `--template-file` or `--template-str`, so template errors surface before any fetch.
+#[cfg(feature = "templates")]
+fn load_report(cli: &Cli) -> Result<Option<Report>> {
+    if let Some(value) = cli.template_file.as_deref() {
+        return Report::from_name_or_path(value).map(Some);
+    }
+    cli.template_str.as_deref().map(Report::inline).transpose()
+}
+
+#[cfg(not(feature = "templates"))]
+fn load_report(_cli: &Cli) -> Result<Option<Report>> {
+    Ok(None)
+}
+
+#[cfg(feature = "templates")]
+fn print_report(report: &Report, context: &serde_json::Value) -> Result<()> {
+    print!("{}", report.render(context)?);
+    Ok(())
+}
+
+#[cfg(not(feature = "templates"))]
+fn print_report(report: &Report, _context: &serde_json::Value) -> Result<()> {
+    match *report {}
+}
+
+/// Write `--export-png` images and note each file on stderr.
+#[cfg(feature = "png")]
+fn export_png(cli: &Cli, histories: &[provider::PriceHistory], range_label: &str) -> Result<()> {
+    let Some(path) = cli.export_png.as_deref() else {
+        return Ok(());
+    };
+    let (width, height) = cli.size.unwrap_or(output::png::DEFAULT_SIZE);
+    let options = output::png::PngOptions {
+        width,
+        height,
+        theme: cli.theme.into(),
+        overlay: cli.overlay,
+    };
+    for written in output::png::write_charts(path, histories, range_label, &options, cli.force)? {
+        eprintln!("Wrote {}", written.display());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "png"))]
+fn export_png(_cli: &Cli, _histories: &[provider::PriceHistory], _range_label: &str) -> Result<()> {
+    Ok(())
+}
+
+/// Put the `--copy` text on the clipboard; `text` gets the `--copy-format` template.
+///
+/// A missing clipboard (e.g. on a headless machine) only warns.
+#[cfg(feature = "clipboard")]
+fn copy_result(cli: &Cli, text: impl FnOnce(Option<&str>) -> Result<Option<String>>) -> Result<()> {
+    if !cli.copy {
+        return Ok(());
+    }
+    if let Some(text) = text(cli.copy_format.as_deref())?
+        && let Err(err) = output::copy::set_clipboard(&text)
+    {
+        output::table::print_clipboard_warning(&err);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_result(
+    _cli: &Cli,
+    _text: impl FnOnce(Option<&str>) -> Result<Option<String>>,
+) -> Result<()> {
+    Ok(())
+}
+
+fn print_conversions(
+    cli: &Cli,
+    report: Option<&Report>,
+    conversions: &[calc::Conversion],
+    skipped: &[String],
+) -> Result<()> {
+    if let Some(report) = report {
+        print_report(
+            report,
+            &serde_json::json!({ "conversions": conversions, "skipped": skipped }),
+        )?;
+    } else if cli.quiet {
+        output::quiet::print_conversions(conversions);
+    } else if markdown_requested(cli) {
+        println!("{}", output::markdown::render_conversions(conversions));
+    } else if ndjson_requested(cli) {
+        output::json::print_ndjson(conversions)?;
+    } else if cli.json && cli.schema == SchemaArg::V1 {
+        output::json::print_envelope(&output::json::Envelope::conversions(conversions, skipped))?;
+    } else if cli.json {
+        output::json::print_conversions_json(conversions, skipped)?;
+    } else {
+        output::table::print_conversions_table(conversions);
+    }
+    if !skipped.is_empty() {
+        output::table::print_skipped_fiat_warning(skipped);
+    }
+    copy_result(cli, |format| {
+        if cli.json && cli.schema == SchemaArg::V1 {
+            output::json::envelope_json(&output::json::Envelope::conversions(conversions, skipped))
+                .map(Some)
+        } else if cli.json {
+            output::json::conversions_json(conversions, skipped).map(Some)
+        } else {
+            Ok(output::copy::conversion_text(conversions, format))
+        }
+    })
+}
+
+/// Table number style from `[display]`, `[defaults].precision`, and CLI overrides.
+fn number_style(cli: &Cli, app_config: &config::AppConfig) -> output::fmt::NumberStyle {
+    let display = &app_config.display;
+    let group_digits = if cli.group_digits {
+        true
+    } else if cli.no_group_digits {
+        false
+    } else {
+        display.group_digits.unwrap_or(true)
+    };
+
+    output::fmt::NumberStyle {
+        locale: display.locale.unwrap_or_default(),
+        group_digits,
+        currency_symbols: display.currency_symbols.unwrap_or(true),
+        precision: cli
+            .precision
+            .map(usize::from)
+            .or(app_config.defaults.precision),
+    }
+}
+
+/// Merge API keys into the `[provider.<id>]` settings.
+///
+/// Precedence per provider: `--api-key` (CoinGecko with `--provider coingecko`, otherwise
+/// CoinMarketCap), then `COINGECKO_API_KEY` / `COINMARKETCAP_API_KEY`, then the OS keyring
+/// with the `keyring` feature, then the config file, with the legacy `[coinmarketcap]`
+/// section last.
+fn resolve_provider_settings(
+    cli: &Cli,
+    app_config: &config::AppConfig,
+) -> config::ProviderSettings {
+    let mut settings = app_config.provider.clone();
+    if settings.api_key("cmc").is_none()
+        && let Some(key) = app_config.coinmarketcap.api_key.clone()
+    {
+        settings.set_api_key("cmc", key);
+    }
+    let flag_target = cli.api_key.as_ref().map(|_| match cli.provider.as_deref() {
+        Some(id) if id.eq_ignore_ascii_case("coingecko") => "coingecko",
+        _ => "cmc",
+    });
+    let keyring_target = (!cli.offline).then(|| primary_provider_id(cli, app_config));
+    for (provider_id, var) in [
+        ("cmc", "COINMARKETCAP_API_KEY"),
+        ("coingecko", "COINGECKO_API_KEY"),
+    ] {
+        if let Ok(key) = std::env::var(var)
+            && !key.trim().is_empty()
+        {
+            settings.set_api_key(provider_id, key);
+        } else if flag_target != Some(provider_id)
+            && keyring_target.as_deref() == Some(provider_id)
+            && let Some(key) = stored_api_key(provider_id)
+        {
+            settings.set_api_key(provider_id, key);
+        }
+    }
+    if let (Some(target), Some(key)) = (flag_target, cli.api_key.clone()) {
+        settings.set_api_key(target, key);
+    }
+    if let Some(market) = cli.market.clone() {
+        settings.set_default_market("stooq", market);
+    }
+    settings
+}
+
+/// Id of the provider a lookup starts with: `--provider`, else the first of
+/// `[defaults].provider_order`, else the first registered provider.
+fn primary_provider_id(cli: &Cli, app_config: &config::AppConfig) -> String {
+    cli.provider
+        .as_deref()
+        .or_else(|| {
+            app_config
+                .defaults
+                .provider_order
+                .as_deref()?
+                .iter()
+                .map(|id| id.trim())
+                .find(|id| !id.is_empty())
+        })
+        .unwrap_or(provider::PROVIDER_IDS[0])
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// The key `pricr config set-key` stored for `provider_id`.
+///
+/// A keyring that fails to read only warns, leaving the config file's key in place.
+#[cfg(feature = "keyring")]
+fn stored_api_key(provider_id: &str) -> Option<String> {
+    match pricr::keystore::load(provider_id) {
+        Ok(key) => key,
+        Err(err) => {
+            let reason = match err {
+                error::Error::Config(reason) => reason,
+                other => other.to_string(),
+            };
+            output::table::print_keyring_warning(provider_id, &reason);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn stored_api_key(_provider_id: &str) -> Option<String> {
+    None
+}
+
+fn ensure_lookup_only_flags(cli: &Cli, mode: &str) -> Result<()> {
+    if cli.watch {
+        return Err(error::Error::Config(format!(
+            "--watch is only available for price lookup and crypto charts, not {}",
+            mode
+        )));
+    }
+    ensure_watchable_flags(cli, mode)
+}
+
+/// Price-lookup-only flags other than `--watch`, which crypto charts support too.
+fn ensure_watchable_flags(cli: &Cli, mode: &str) -> Result<()> {
+    if status_output_requested(cli) {
+        return Err(error::Error::Config(format!(
+            "--format waybar and --template are only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if !cli.alerts.is_empty() {
+        return Err(error::Error::Config(format!(
+            "--alert is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if cli.webhook.is_some() {
+        return Err(error::Error::Config(format!(
+            "--webhook is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if !cli.amount.is_empty() && !cli.roi {
+        return Err(error::Error::Config(format!(
+            "--amount is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if cli.since.is_some() {
+        return Err(error::Error::Config(format!(
+            "--since is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if cli.delta {
+        return Err(error::Error::Config(format!(
+            "--delta is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if !cli.currencies.is_empty() {
+        return Err(error::Error::Config(format!(
+            "--currencies is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    if cli.auto_provider {
+        return Err(error::Error::Config(format!(
+            "--auto-provider is only available for price lookup, not {}",
+            mode
+        )));
+    }
+    Ok(())
+}
+
+/// Most `--currencies` codes one table shows side by side.
+const MAX_CURRENCIES: usize = 5;
+
+/// Validate `--currencies`: known fiat codes, uppercased, duplicates dropped.
+fn resolve_currencies(raw: &[String]) -> Result<Vec<String>> {
+    let mut currencies: Vec<String> = Vec::new();
+    for code in raw {
+        let code = code.trim().to_uppercase();
+        if code.is_empty() || currencies.contains(&code) {
+            continue;
+        }
+        if !calc::is_known_fiat(&code) {
+            return Err(error::Error::Config(format!(
+                "--currencies takes fiat currency codes, got '{}'",
+                code
+            )));
+        }
+        currencies.push(code);
+    }
+    if currencies.len() > MAX_CURRENCIES {
+        return Err(error::Error::Config(format!(
+            "--currencies takes at most {} currencies, got {} -- the table would not fit",
+            MAX_CURRENCIES,
+            currencies.len()
+        )));
+    }
+    Ok(currencies)
+}
+
+/// A positional symbol with the currency it is quoted in: `btc/eur`, or a bare symbol in
+/// the run's currency.
+#[derive(Debug, Clone, PartialEq)]
+struct Quote {
+    /// The argument as typed, used to report it when missing.
+    token: String,
+    symbol: String,
+    currency: String,
+}
+
+fn parse_quotes(symbols: &[String], currency: &str) -> Vec<Quote> {
+    symbols
+        .iter()
+        .map(|token| {
+            let (symbol, currency) = match calc::parse_pair(token) {
+                Some((symbol, fiat)) => (symbol.to_string(), fiat),
+                None => (token.clone(), currency.to_string()),
+            };
+            Quote {
+                token: token.clone(),
+                symbol,
+                currency,
+            }
+        })
+        .collect()
+}
+
+/// Symbols grouped by quote currency in first-seen order, one provider request per group.
+fn group_quotes(quotes: &[Quote]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for quote in quotes {
+        match groups
+            .iter_mut()
+            .find(|(currency, _)| currency.eq_ignore_ascii_case(&quote.currency))
+        {
+            Some((_, symbols)) => symbols.push(quote.symbol.clone()),
+            None => groups.push((quote.currency.clone(), vec![quote.symbol.clone()])),
+        }
+    }
+    groups
+}
+
+/// Merge per-currency batches back into input order; missing pairs are reported as typed.
+fn merge_quote_batches(
+    quotes: &[Quote],
+    batches: Vec<(String, provider::PriceBatch)>,
+) -> provider::PriceBatch {
+    let position = |symbol: &str, currency: &str| {
+        quotes
+            .iter()
+            .position(|quote| {
+                provider::symbol_answers(&quote.symbol, symbol)
+                    && quote.currency.eq_ignore_ascii_case(currency)
+            })
+            .unwrap_or(usize::MAX)
+    };
+    let mut prices: Vec<(usize, provider::CoinPrice)> = Vec::new();
+    let mut missing: Vec<(usize, String)> = Vec::new();
+    for (currency, batch) in batches {
+        prices.extend(
+            batch
+                .prices
+                .into_iter()
+                .map(|price| (position(&price.symbol, &currency), price)),
+        );
+        missing.extend(batch.missing.into_iter().map(|symbol| {
+            let at = position(&symbol, &currency);
+            let label = quotes
+                .get(at)
+                .map(|quote| quote.token.trim().to_uppercase())
+                .unwrap_or(symbol);
+            (at, label)
+        }));
+    }
+    prices.sort_by_key(|(at, _)| *at);
+    missing.sort_by_key(|(at, _)| *at);
+    provider::PriceBatch {
+        prices: prices.into_iter().map(|(_, price)| price).collect(),
+        missing: missing.into_iter().map(|(_, symbol)| symbol).collect(),
+    }
+}
+
+/// One `--amount` item.
+#[derive(Debug, Clone, PartialEq)]
+enum AmountArg {
+    /// A bare quantity, matched to the symbols in the order they were given.
+    Positional(f64),
+    /// `symbol=quantity`.
+    Symbol(String, f64),
+    /// The `[portfolio]` config table.
+    Portfolio,
+    /// A fiat sum such as `1000usd` or `$1000`, for `--roi`.
+    Fiat(f64, String),
+}
+
+fn parse_amount_arg(value: &str) -> std::result::Result<AmountArg, String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("portfolio") {
+        return Ok(AmountArg::Portfolio);
+    }
+    if let Some(fiat) = calc::parse_fiat_amount(value) {
+        return Ok(AmountArg::Fiat(fiat.amount, fiat.currency));
+    }
+    let parse_quantity = |quantity: &str| {
+        quantity
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|amount| amount.is_finite() && *amount >= 0.0)
+            .ok_or_else(|| {
+                format!(
+                    "invalid amount '{}' -- use a number, symbol=number, or portfolio",
+                    value
+                )
+            })
+    };
+    match value.split_once('=') {
+        Some((symbol, quantity)) if !symbol.trim().is_empty() => Ok(AmountArg::Symbol(
+            symbol.trim().to_string(),
+            parse_quantity(quantity)?,
+        )),
+        Some(_) => Err(format!("missing symbol in amount '{}'", value)),
+        None => parse_quantity(value).map(AmountArg::Positional),
+    }
+}
+
+/// Held amount per symbol from `--amount`; later entries for a symbol win.
+fn resolve_amounts(
+    args: &[AmountArg],
+    symbols: &[String],
+    portfolio: &std::collections::BTreeMap<String, f64>,
+) -> Result<Vec<(String, f64)>> {
+    let mut amounts: Vec<(String, f64)> = Vec::new();
+    let mut set = |symbol: &str, amount: f64| match amounts
+        .iter_mut()
+        .find(|(held, _)| held.eq_ignore_ascii_case(symbol))
+    {
+        Some(entry) => entry.1 = amount,
+        None => amounts.push((symbol.to_string(), amount)),
+    };
+    let mut positional = symbols.iter();
+    for arg in args {
+        match arg {
+            AmountArg::Positional(amount) => {
+                let Some(symbol) = positional.next() else {
+                    return Err(error::Error::Config(format!(
+                        "more --amount values than symbols ({} given)",
+                        symbols.len()
+                    )));
+                };
+                set(symbol, *amount);
+            }
+            AmountArg::Symbol(symbol, amount) => set(symbol, *amount),
+            AmountArg::Portfolio => {
+                if portfolio.is_empty() {
+                    return Err(error::Error::Config(
+                        "--amount portfolio needs a [portfolio] table in config, e.g. btc = 0.5"
+                            .into(),
+                    ));
+                }
+                for (symbol, amount) in portfolio {
+                    set(symbol, *amount);
+                }
+            }
+            AmountArg::Fiat(amount, currency) => {
+                return Err(error::Error::Config(format!(
+                    "a fiat amount like '{}{}' is only available with roi -- use quantities to value holdings",
+                    amount, currency
+                )));
+            }
+        }
+    }
+    Ok(amounts)
+}
+
+/// The sum `--roi` invests: one fiat amount, or a bare number in `currency`.
+fn resolve_roi_investment(args: &[AmountArg], currency: &str) -> Result<(f64, String)> {
+    let (amount, currency) = match args {
+        [AmountArg::Fiat(amount, code)] => (*amount, code.clone()),
+        [AmountArg::Positional(amount)] => (*amount, currency.to_uppercase()),
+        _ => {
+            return Err(error::Error::Config(
+                "roi takes one --amount, e.g. --amount 1000usd".into(),
+            ));
+        }
+    };
+    if amount <= 0.0 {
+        return Err(error::Error::Config(
+            "roi needs an --amount above zero".into(),
+        ));
+    }
+    Ok((amount, currency))
+}
+
+/// Days either side of the `--roi` date searched for the closest trading day.
+const ROI_SEARCH_DAYS: i64 = 7;
+
+/// The point `--roi` buys `symbol` at: the closest one to midnight UTC on `date`.
+///
+/// Fails naming the earliest available date when `date` precedes the asset's history.
+async fn fetch_roi_buy_point(
+    prov: &dyn provider::PriceProvider,
+    symbol: &str,
+    currency: &str,
+    date: NaiveDate,
+) -> Result<provider::PricePoint> {
+    let symbols = [symbol.to_string()];
+    let at = date.and_time(chrono::NaiveTime::MIN).and_utc();
+    let now = chrono::Utc::now();
+    let search = chrono::Duration::days(ROI_SEARCH_DAYS);
+    let window = (Some(at - search), (at + search).min(now));
+    let fetch_days = compute_chart_fetch_days(Some(date - search), now.date_naive());
+    let nearby = match fetch_history_window(
+        prov,
+        &symbols,
+        currency,
+        window,
+        provider::HistoryInterval::Daily,
+        fetch_days,
+    )
+    .await
+    {
+        Ok(histories) => histories.into_iter().next().map(|h| h.points),
+        Err(error::Error::NoResults { .. }) => None,
+        Err(other) => return Err(other),
+    }
+    .unwrap_or_default();
+
+    if !nearby.iter().any(|point| point.timestamp <= at) {
+        // Nothing on or before the date: is the date older than the whole history?
+        let earliest = fetch_history_window(
+            prov,
+            &symbols,
+            currency,
+            (None, now),
+            provider::HistoryInterval::Daily,
+            MAX_CHART_FETCH_DAYS,
+        )
+        .await?
+        .into_iter()
+        .filter_map(|history| history.points.first().map(|point| point.timestamp))
+        .min();
+        if let Some(earliest) = earliest
+            && earliest.date_naive() > date
+        {
+            return Err(error::Error::Config(format!(
+                "{} has no {} price history on {} -- the earliest available date is {}",
+                symbol.to_uppercase(),
+                prov.name(),
+                date,
+                earliest.date_naive()
+            )));
+        }
+    }
+    calc::closest_point(&nearby, at).cloned().ok_or_else(|| {
+        error::Error::Config(format!(
+            "no {} price for {} within {} days of {}",
+            prov.name(),
+            symbol.to_uppercase(),
+            ROI_SEARCH_DAYS,
+            date
+        ))
+    })
+}
+
+async fn run(mut cli: Cli, shutdown: CancellationToken) -> Result<()> {
+    if cli.print_schema {
+        println!("{}", output::json::envelope_schema_json()?);
+        return Ok(());
+    }
+    if matches!(cli.format, Some(FormatArg::Json | FormatArg::Ndjson)) {
+        cli.json = true;
+    }
+    if cli.schema == SchemaArg::V1 && (!cli.json || ndjson_requested(&cli)) {
+        return Err(error::Error::Config(
+            "--schema v1 applies to --json and --format json output".into(),
+        ));
+    }
+    output::color::apply(cli.color.into());
+    if status_output_requested(&cli) {
+        // Status bars render raw text; ANSI codes would leak into the tooltip table.
+        colored::control::set_override(false);
+    }
+
+    let config_started = std::time::Instant::now();
+    let app_config = match cli.config.as_deref() {
+        Some(path) => config::load_from_path(path)?,
+        None => config::load()?,
+    };
+    timings::record_phase("config", config_started);
+    let providers_started = std::time::Instant::now();
+
+    let search_query = resolve_search_query(&cli);
+
+    let provider_settings = resolve_provider_settings(&cli, &app_config);
+    output::fmt::set_style(number_style(&cli, &app_config));
+    output::color::set_highlight_threshold(app_config.display.highlight_change_pct);
+    output::color::set_stale_after(
+        app_config
+            .display
+            .stale_after_secs
+            .unwrap_or(output::color::DEFAULT_STALE_AFTER_SECS),
+    );
+    output::tz::set_timezone(cli.tz.or(app_config.display.timezone).unwrap_or_default());
+    let report = load_report(&cli)?;
+    let show_attribution = !cli.no_attribution && app_config.display.attribution != Some(false);
+    cli.alerts = expand_alert_groups(&cli.alerts, &app_config.symbol_groups())?;
+    let mut http_config = app_config.http.clone();
+    if let Some(timeout) = cli.timeout {
+        http_config.timeout_secs = Some(timeout);
+    }
+    if cli.no_cache {
+        provider::set_cache_enabled(false);
+    }
+    provider::cache::set_offline(cli.offline);
+    let http_client = provider::http::build_client(&http_config)?.with_cancellation(shutdown);
+    #[cfg(feature = "devtools")]
+    let http_client = {
+        use provider::fixtures::Fixtures;
+        let fixtures = match (&cli.record, &cli.replay) {
+            (Some(dir), _) => Some(Fixtures::record(dir)?),
+            (None, Some(dir)) => Some(Fixtures::replay(dir)?),
+            (None, None) => None,
+        };
+        match fixtures {
+            Some(fixtures) => {
+                // Cache hits would bypass both recording and replay.
+                provider::set_cache_enabled(false);
+                http_client.with_fixtures(fixtures)
+            }
+            None => http_client,
+        }
+    };
+    let webhook = match cli.webhook.as_deref() {
+        Some(_) if !cli.watch && cli.alerts.is_empty() => {
+            return Err(error::Error::Config(
+                "--webhook needs --watch or --alert -- it posts refreshes and triggered alerts"
+                    .into(),
+            ));
+        }
+        Some(url) => {
+            let client = http_client.client().cloned().ok_or_else(|| {
+                error::Error::Config("--webhook cannot be used with --offline".into())
+            })?;
+            Some(webhook::Webhook::new(client, url, &app_config.webhook)?)
+        }
+        None => None,
+    };
+    let mut providers = provider::available_providers(
+        &provider_settings,
+        &app_config.aliases,
+        &http_client,
+        state::credits_path().as_deref(),
+    );
+    if !cli.no_convert {
+        let rates = std::sync::Arc::new(provider::fiat::FiatRateSource::from_settings(
+            &provider_settings,
+            &http_client,
+        ));
+        providers = providers
+            .into_iter()
+            .map(|prov| provider::with_currency_fallback(prov, std::sync::Arc::clone(&rates)))
+            .collect();
+    }
+
+    let currencies = resolve_currencies(&cli.currencies)?;
+    let currency = currencies
+        .first()
+        .cloned()
+        .or_else(|| cli.currency.clone())
+        .or_else(|| app_config.defaults.currency.clone())
+        .unwrap_or_else(|| config::DEFAULT_CURRENCY.to_string());
+
+    if cli.list_providers {
+        let listing: Vec<provider::ProviderInfo> = providers
+            .iter()
+            .map(|p| provider::ProviderInfo::of(p.as_ref()))
+            .collect();
+        if cli.json {
+            output::json::print_provider_list_json(&listing)?;
+        } else {
+            output::table::print_provider_list_table(&listing);
+        }
+        return Ok(());
+    }
+
+    if cli.quota {
+        ensure_schema_supported(&cli, "--quota")?;
+        let path = state::credits_path().ok_or_else(|| {
+            error::Error::Config(
+                "--quota needs a state directory -- set XDG_STATE_HOME or HOME".into(),
+            )
+        })?;
+        let now = chrono::Utc::now();
+        let usage = state::QuotaUsage::new(
+            "cmc",
+            now,
+            state::CreditLedger::load(&path).used(now),
+            provider_settings.monthly_credits("cmc"),
+        );
+        if cli.json {
+            output::json::print_quota_json(&usage)?;
+        } else {
+            println!("{}", output::table::format_quota(&usage));
+        }
+        return Ok(());
+    }
+
+    if cli.check_providers {
+        ensure_markdown_supported(&cli, "provider checks")?;
+        ensure_schema_supported(&cli, "provider checks")?;
+        // Cached responses would hide a dead endpoint and skew latency.
+        provider::set_cache_enabled(false);
+        let report =
+            provider::health::check_providers(&providers, provider::health::DEFAULT_CHECK_TIMEOUT)
+                .await;
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(&report)?;
+        } else if cli.json {
+            output::json::print_provider_health_json(&report)?;
+        } else {
+            output::table::print_provider_health_table(&report);
+        }
+        return Ok(());
+    }
+
+    if cli.show_aliases {
+        let aliases = provider::effective_aliases(&providers, &app_config.aliases);
+        if aliases.is_empty() {
+            println!("No symbol aliases configured.");
+            return Ok(());
+        }
+
+        println!("Symbol aliases:");
+        for (symbol, provider_id, alias) in aliases.entries() {
+            println!("  {:12} {:12} {}", symbol, provider_id, alias);
+        }
+        return Ok(());
+    }
+
+    let provider_indices = resolve_provider_indices(
+        &providers,
+        cli.provider.as_deref(),
+        app_config.defaults.provider_order.as_deref(),
+    )?;
+    let primary_provider_idx = provider_indices[0];
+    let prov = &providers[primary_provider_idx];
+    timings::record_phase("providers", providers_started);
+
+    if cli.search_all && search_query.is_none() {
+        return Err(error::Error::Config(
+            "--search-all only applies to ticker search -- usage: pricr --search-all --search apple"
+                .into(),
+        ));
+    }
+
+    if let Some(query) = search_query {
+        ensure_lookup_only_flags(&cli, "ticker search")?;
+        if query.is_empty() {
+            return Err(error::Error::Config(
+                "search mode requires a query -- usage: pricr --search apple".into(),
+            ));
+        }
+
+        let limit = cli.search_limit as usize;
+        let matches = if cli.provider.is_some() {
+            ensure_capability(&providers, prov.as_ref(), provider::Capability::Search)?;
+            info!(provider = prov.id(), query = %query, limit = cli.search_limit, "searching tickers");
+            let found = prov
+                .search_tickers(&query, search::candidate_pool(limit))
+                .await?;
+            search::rank(&query, found, limit)
+        } else {
+            // --search-all asks every provider with search at once, not just the configured order.
+            let all_indices: Vec<usize> = (0..providers.len()).collect();
+            let search_indices = capable_indices(
+                &providers,
+                if cli.search_all {
+                    &all_indices
+                } else {
+                    &provider_indices
+                },
+                provider::Capability::Search,
+            )?;
+            let ordered_ids = provider_ids_for_indices(&providers, &search_indices);
+            info!(
+                providers = ?ordered_ids,
+                query = %query,
+                limit = cli.search_limit,
+                concurrent = cli.search_all,
+                "searching tickers across providers"
+            );
+            search_tickers_across_providers(
+                &providers,
+                &search_indices,
+                &query,
+                limit,
+                cli.search_all,
+            )
+            .await?
+        };
+
+        if markdown_requested(&cli) {
+            println!("{}", output::markdown::render_ticker_matches(&matches));
+        } else if ndjson_requested(&cli) {
+            output::json::print_ndjson(&matches)?;
+        } else if cli.json && cli.schema == SchemaArg::V1 {
+            output::json::print_envelope(&output::json::Envelope::search(
+                &matches,
+                cli.provider.as_deref(),
+            ))?;
+        } else if cli.json {
+            output::json::print_ticker_matches_json(&matches)?;
+        } else {
+            output::table::print_ticker_matches_table(&matches);
+        }
+
+        return Ok(());
+    }
+
+    if let Some(symbol) = cli.exchanges.as_deref() {
+        ensure_lookup_only_flags(&cli, "exchange listing")?;
+        ensure_markdown_supported(&cli, "exchange listing")?;
+        ensure_schema_supported(&cli, "exchange listing")?;
+        let markets = fetch_from_first_supported(
+            &providers,
+            &provider_indices,
+            cli.provider.is_some(),
+            provider::Capability::Markets,
+            async |prov| prov.get_markets(symbol, cli.search_limit as usize).await,
+        )
+        .await?;
+
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(&markets)?;
+        } else if cli.json {
+            output::json::print_market_tickers_json(&markets)?;
+        } else {
+            output::table::print_market_tickers_table(&markets);
+        }
+
+        return Ok(());
+    }
+
+    if cli.trending {
+        ensure_lookup_only_flags(&cli, "trending coins")?;
+        ensure_markdown_supported(&cli, "trending coins")?;
+        ensure_schema_supported(&cli, "trending coins")?;
+        let coins = fetch_from_first_supported(
+            &providers,
+            &provider_indices,
+            cli.provider.is_some(),
+            provider::Capability::Trending,
+            async |prov| prov.get_trending(&currency).await,
+        )
+        .await?;
+
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(&coins)?;
+        } else if cli.json {
+            output::json::print_trending_json(&coins)?;
+        } else {
+            output::table::print_trending_table(&coins);
+        }
+
+        return Ok(());
+    }
+
+    if let Some(limit) = cli.top {
+        ensure_lookup_only_flags(&cli, "top coins")?;
+        let mut prices = fetch_from_first_supported(
+            &providers,
+            &provider_indices,
+            cli.provider.is_some(),
+            provider::Capability::Top,
+            async |prov| prov.get_top_coins(limit as usize, &currency).await,
+        )
+        .await?;
+        if show_attribution {
+            provider::attribute(&mut prices, &providers);
+        }
+        if let Some(sort) = cli.sort {
+            output::sort::sort_prices(&mut prices, sort.into(), cli.desc);
+        }
+
+        let columns: Vec<output::table::PriceColumn> = if cli.columns.is_empty() {
+            output::table::TOP_COINS_COLUMNS.to_vec()
+        } else {
+            cli.columns.iter().map(|&column| column.into()).collect()
+        };
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(&prices)?;
+        } else if cli.json && cli.schema == SchemaArg::V1 {
+            let batch = provider::PriceBatch {
+                prices,
+                missing: Vec::new(),
+            };
+            output::json::print_envelope(&output::json::Envelope::prices(
+                &batch,
+                cli.provider.as_deref(),
+                Some(&currency),
+            ))?;
+        } else if cli.json {
+            output::json::print_json(&prices)?;
+        } else if markdown_requested(&cli) {
+            println!(
+                "{}",
+                output::markdown::render_prices(&prices, None, None, &columns)
+            );
+        } else {
+            output::table::print_table(&prices, None, None, &columns);
+        }
+
+        return Ok(());
+    }
+
+    let argv_symbols: Vec<String> = cli
+        .symbols
+        .iter()
+        .filter(|symbol| *symbol != STDIN_MARKER)
+        .cloned()
+        .collect();
+    let listed_symbols = read_listed_symbols(&cli)?;
+    // `3.5EUR` style conversions are only recognised when typed on the command line.
+    let calc_allowed = !argv_symbols.is_empty() || listed_symbols.is_empty();
+    let merged_symbols = merge_symbol_sources(&argv_symbols, &listed_symbols);
+
+    if cli.global && merged_symbols.is_empty() {
+        ensure_lookup_only_flags(&cli, "global metrics")?;
+        ensure_markdown_supported(&cli, "global metrics")?;
+        ensure_schema_supported(&cli, "global metrics")?;
+        let metrics = fetch_from_first_supported(
+            &providers,
+            &provider_indices,
+            cli.provider.is_some(),
+            provider::Capability::Global,
+            async |prov| prov.get_global_metrics(&currency).await,
+        )
+        .await?;
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(std::slice::from_ref(&metrics))?;
+        } else if cli.json {
+            output::json::print_global_json(&metrics)?;
+        } else {
+            output::table::print_global_table(&metrics);
+        }
+        return Ok(());
+    }
+    // `--amount portfolio` without symbols looks up the portfolio's symbols.
+    let portfolio_symbols: Vec<String> = if cli.amount.contains(&AmountArg::Portfolio) {
+        app_config.portfolio.keys().cloned().collect()
+    } else {
+        Vec::new()
+    };
+    let default_symbols = if portfolio_symbols.is_empty() {
+        app_config.defaults.symbols.as_deref()
+    } else {
+        Some(portfolio_symbols.as_slice())
+    };
+    let symbol_tokens = select_symbol_tokens(&merged_symbols, default_symbols);
+    let symbols = provider::normalize_symbols(&expand_symbol_tokens(
+        symbol_tokens,
+        &app_config.symbol_groups(),
+    )?);
+
+    if symbols.is_empty() {
+        return Err(error::Error::Config(
+            "no symbols provided -- usage: pricr btc eth, or set [defaults].symbols in config"
+                .into(),
+        ));
+    }
+
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = cli.serve_metrics {
+        let providers = std::sync::Arc::new(providers);
+        let fetch: pricr::server::PriceFetcher = Box::new(move || {
+            let providers = std::sync::Arc::clone(&providers);
+            let provider_indices = provider_indices.clone();
+            let symbols = symbols.clone();
+            let currency = currency.clone();
+            Box::pin(async move {
+                fetch_prices_with_provider_fallback(
+                    &providers,
+                    &provider_indices,
+                    &symbols,
+                    &currency,
+                )
+                .await
+                .map(|batch| batch.prices)
+            })
+        });
+        let exporter = pricr::server::MetricsExporter::new(
+            fetch,
+            std::time::Duration::from_secs(cli.metrics_min_refresh),
+        );
+        return pricr::server::serve(addr, exporter).await;
+    }
+
+    let chart_end_date = cli
+        .end_date
+        .unwrap_or_else(|| chrono::Utc::now().date_naive());
+    if chart_end_date > chrono::Utc::now().date_naive() {
+        return Err(error::Error::Config(
+            "chart end date cannot be in the future".into(),
+        ));
+    }
+
+    let (chart_range, sampling) = chart_window_defaults(&cli, &app_config.chart);
+    let chart_start_date = match cli.start_date {
+        Some(start) => Some(start.resolve(chart_end_date)),
+        None => chart_range.start_date(chart_end_date),
+    };
     if let Some(start) = chart_start_date
         && start > chart_end_date
     {
@@ -670,8 +3275,7 @@ async fn run(cli: Cli) -> Result<()> {
         ));
     }
 
-    let chart_range_label =
-        format_chart_range_label(chart_start_date, chart_end_date, cli.interval);
+    let chart_range_label = format_chart_range_label(chart_start_date, chart_end_date, chart_range);
     let chart_start_ts = chart_start_date
         .and_then(|d| d.and_hms_opt(0, 0, 0))
         .map(|dt| dt.and_utc());
@@ -679,9 +3283,175 @@ async fn run(cli: Cli) -> Result<()> {
         .and_hms_opt(23, 59, 59)
         .ok_or_else(|| error::Error::Config("invalid chart end date".into()))?
         .and_utc();
-    let chart_fetch_days = compute_chart_fetch_days(chart_start_date);
+    let chart_fetch_days = compute_chart_fetch_days(chart_start_date, chart_end_date);
+    // Providers without date windows count their `days` back from today.
+    let chart_days_to_today =
+        compute_chart_fetch_days(chart_start_date, chrono::Utc::now().date_naive());
+    provider::set_history_events(cli.events);
+    if let Some(path) = cli.export.as_deref() {
+        // Reject unknown extensions before spending any provider requests.
+        output::export::ExportFormat::from_path(path)?;
+    }
+    let chart_size = output::chart::ChartSize::resolve(
+        cli.chart_width.or(app_config.chart.width),
+        cli.chart_height.or(app_config.chart.height),
+    );
+
+    if cli.roi {
+        ensure_lookup_only_flags(&cli, "roi mode")?;
+        ensure_markdown_supported(&cli, "roi mode")?;
+        ensure_schema_supported(&cli, "roi mode")?;
+        ensure_capability(&providers, prov.as_ref(), provider::Capability::History)?;
+        let (invested, currency) = resolve_roi_investment(&cli.amount, &currency)?;
+        let date = cli
+            .start_date
+            .map(|start| start.resolve(chart_end_date))
+            .ok_or_else(|| error::Error::Config("roi needs --start-date".into()))?;
+        if let Some(pair) = symbols
+            .iter()
+            .find(|token| calc::parse_pair(token).is_some())
+        {
+            return Err(error::Error::Config(format!(
+                "pair syntax like '{}' is only available for price lookup -- put the currency in --amount",
+                pair
+            )));
+        }
+        info!(
+            provider = prov.id(),
+            symbols = ?symbols,
+            invested,
+            currency = %currency,
+            date = %date,
+            "valuing a past lump-sum buy"
+        );
+
+        let fetch_started = std::time::Instant::now();
+        let (bought, current) = tokio::join!(
+            futures::future::try_join_all(symbols.iter().map(|symbol| fetch_roi_buy_point(
+                prov.as_ref(),
+                symbol,
+                &currency,
+                date
+            )),),
+            prov.get_prices(&symbols, &currency)
+        );
+        let (bought, current) = (bought?, current?);
+        timings::record_phase("fetch", fetch_started);
+
+        let investments: Vec<calc::Investment> = symbols
+            .iter()
+            .zip(&bought)
+            .filter_map(|(symbol, point)| {
+                current
+                    .iter()
+                    .find(|price| provider::symbol_answers(symbol, &price.symbol))
+                    .map(|price| calc::lump_sum(invested, point, price))
+            })
+            .collect();
+        if investments.is_empty() {
+            return Err(error::Error::no_results(prov.name(), &symbols, &currency));
+        }
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(&investments)?;
+        } else if cli.json {
+            output::json::print_investments_json(&investments)?;
+        } else {
+            output::table::print_investments_table(&investments);
+        }
+        return Ok(());
+    }
+
+    if cli.stats || cli.correlation {
+        let mode = if cli.stats {
+            "stats mode"
+        } else {
+            "correlation mode"
+        };
+        ensure_lookup_only_flags(&cli, mode)?;
+        ensure_markdown_supported(&cli, mode)?;
+        ensure_schema_supported(&cli, mode)?;
+        ensure_capability(&providers, prov.as_ref(), provider::Capability::History)?;
+        if let Some(pair) = symbols
+            .iter()
+            .find(|token| calc::parse_pair(token).is_some())
+        {
+            return Err(error::Error::Config(format!(
+                "pair syntax like '{}' is only available for price lookup -- use --currency with {}",
+                pair, mode
+            )));
+        }
+        if cli.correlation && symbols.len() < 2 {
+            return Err(error::Error::Config(
+                "correlation mode needs at least two symbols -- usage: pricr --correlation btc eth"
+                    .into(),
+            ));
+        }
+        info!(
+            provider = prov.id(),
+            symbols = ?symbols,
+            currency = %currency,
+            range = %chart_range_label,
+            start_date = ?chart_start_date,
+            end_date = %chart_end_date,
+            mode,
+            "fetching daily history"
+        );
+
+        let fetch_started = std::time::Instant::now();
+        let histories = fetch_history_window(
+            prov.as_ref(),
+            &symbols,
+            &currency,
+            (chart_start_ts, chart_end_ts),
+            provider::HistoryInterval::Daily,
+            chart_days_to_today,
+        )
+        .await?;
+        timings::record_phase("fetch", fetch_started);
+
+        if cli.correlation {
+            let matrix = calc::stats::correlation_matrix(&histories);
+            if !matrix.excluded.is_empty() {
+                output::table::print_correlation_excluded_warning(&matrix.excluded);
+            }
+            if matrix.symbols.len() < 2 {
+                return Err(error::Error::Config(format!(
+                    "fewer than two symbols share {} daily returns in this window -- try a longer --interval",
+                    calc::stats::MIN_CORRELATION_OBSERVATIONS
+                )));
+            }
+            if ndjson_requested(&cli) {
+                output::json::print_ndjson(&[output::json::correlation_json(&matrix)])?;
+            } else if cli.json {
+                output::json::print_correlation_json(&matrix)?;
+            } else {
+                output::table::print_correlation_table(&matrix, &chart_range_label);
+            }
+            return Ok(());
+        }
+
+        let metrics: Vec<calc::stats::RiskMetrics> =
+            histories.iter().map(calc::stats::risk_metrics).collect();
+        let unreliable: Vec<String> = metrics
+            .iter()
+            .filter(|row| row.is_unreliable())
+            .map(|row| row.symbol.clone())
+            .collect();
+        if !unreliable.is_empty() {
+            output::table::print_short_history_warning(&unreliable);
+        }
+        if ndjson_requested(&cli) {
+            output::json::print_ndjson(&metrics)?;
+        } else if cli.json {
+            output::json::print_risk_json(&metrics)?;
+        } else {
+            output::table::print_risk_table(&metrics, &chart_range_label);
+        }
+        return Ok(());
+    }
 
     if cli.chart && calc::is_known_fiat(&symbols[0]) {
+        ensure_lookup_only_flags(&cli, "fiat chart mode")?;
         let base = symbols[0].to_uppercase();
         let targets: Vec<String> = symbols[1..].iter().map(|s| s.to_uppercase()).collect();
 
@@ -698,9 +3468,9 @@ async fn run(cli: Cli) -> Result<()> {
             ));
         }
 
-        if matches!(cli.sampling, SamplingArg::Hourly) {
+        if sampling.fetch_interval() == provider::HistoryInterval::Hourly {
             return Err(error::Error::Config(
-                "fiat chart mode supports daily history only -- use --sampling auto or --sampling daily"
+                "fiat chart mode supports daily history only -- use --sampling auto, daily, weekly, or monthly"
                     .into(),
             ));
         }
@@ -715,52 +3485,101 @@ async fn run(cli: Cli) -> Result<()> {
             "fetching fiat historical rates"
         );
 
-        let fiat_provider = provider::frankfurter::Frankfurter::new();
-        let mut histories = fiat_provider
-            .get_history(&base, &targets, chart_fetch_days)
-            .await?;
+        // Without --provider this settles on frankfurter, the built-in provider with rates.
+        let fetch_start = chart_end_date - chrono::Duration::days(chart_fetch_days.into());
+        let mut histories = fetch_from_first_supported(
+            &providers,
+            &provider_indices,
+            cli.provider.is_some(),
+            provider::Capability::FiatHistory,
+            async |prov| {
+                prov.get_fiat_history(&base, &targets, fetch_start, chart_end_date)
+                    .await
+            },
+        )
+        .await?;
         filter_histories_by_time_window(&mut histories, chart_start_ts, chart_end_ts);
         if histories.is_empty() {
-            return Err(error::Error::NoResults);
+            return Err(error::Error::no_results("", &targets, &base));
         }
-
-        if cli.json {
-            output::json::print_history_json(&histories)?;
-        } else {
-            output::table::print_history_charts(
+        fill_history_gaps(&mut histories, cli.fill.into());
+        resample_histories(&mut histories, sampling, cli.resample_method.into());
+        if let Some(path) = cli.export.as_deref() {
+            output::export::write_history(path, &histories, cli.force)?;
+        }
+        if let Some(path) = cli.export_html.as_deref() {
+            output::export::write_html(path, &histories, &chart_range_label, cli.force)?;
+        }
+        export_png(&cli, &histories, &chart_range_label)?;
+
+        if let Some(report) = &report {
+            print_report(
+                report,
+                &history_report_context(&histories, &chart_range_label),
+            )?;
+        } else if markdown_requested(&cli) {
+            println!("{}", output::markdown::render_history_stats(&histories));
+        } else if ndjson_requested(&cli) {
+            output::json::print_ndjson(&output::json::histories_with_stats(&histories))?;
+        } else if cli.json && cli.schema == SchemaArg::V1 {
+            output::json::print_envelope(&output::json::Envelope::history(
                 &histories,
-                &chart_range_label,
-                provider::HistoryInterval::Daily,
-            );
+                cli.provider.as_deref(),
+                None,
+            ))?;
+        } else if cli.json {
+            output::json::print_history_json(&histories, &chart_comparison(&histories))?;
+        } else {
+            let label = if sampling == sampling.fetch_interval() {
+                provider::HistoryInterval::Daily
+            } else {
+                sampling
+            };
+            output::table::print_history_charts(&histories, &chart_range_label, label, chart_size);
+            print_chart_comparison(&histories);
         }
 
         return Ok(());
     }
 
     // Calc mode: detect `<number><fiat>` as first positional arg.
-    if let Some(fiat) = calc::parse_fiat_amount(&symbols[0]) {
+    // A `de`/`fr` display locale opts into reading `1,5eur` as 1.5.
+    let decimal_comma = app_config
+        .display
+        .locale
+        .is_some_and(|locale| locale.decimal_comma());
+    if let Some(request) = calc::parse_invocation(&symbols, decimal_comma).filter(|_| calc_allowed)
+    {
+        ensure_lookup_only_flags(&cli, "conversion mode")?;
+        if cli.field.is_some() {
+            return Err(error::Error::Config(
+                "--field is only available for price lookup, not conversion mode".into(),
+            ));
+        }
         if cli.chart {
             return Err(error::Error::Config(
                 "chart mode is only available for direct symbol lookup".into(),
             ));
         }
-
-        let targets: Vec<String> = symbols[1..].to_vec();
-        if targets.is_empty() {
+        if !request.has_targets() {
             return Err(error::Error::Config(
                 "calc mode requires at least one target coin -- usage: pricr 3.5EUR xmr".into(),
             ));
         }
 
-        // Partition targets into fiat currencies and crypto symbols.
-        let (fiat_targets, crypto_targets): (Vec<String>, Vec<String>) =
-            targets.into_iter().partition(|t| calc::is_known_fiat(t));
-
+        let calc::CalcRequest {
+            sources,
+            fiat_targets,
+            crypto_targets,
+        } = &request;
+        let amounts: Vec<String> = sources
+            .iter()
+            .map(|source| format!("{}{}", source.amount, source.currency))
+            .collect();
         if cli.provider.is_some() {
             info!(
                 provider = prov.id(),
-                amount = fiat.amount,
-                currency = %fiat.currency,
+                sources = ?amounts,
                 fiat_targets = ?fiat_targets,
                 crypto_targets = ?crypto_targets,
                 "calc mode: fetching prices for conversion"
@@ -769,130 +3588,92 @@ async fn run(cli: Cli) -> Result<()> {
             let ordered_ids = provider_ids_for_indices(&providers, &provider_indices);
             info!(
                 providers = ?ordered_ids,
-                amount = fiat.amount,
-                currency = %fiat.currency,
+                sources = ?amounts,
                 fiat_targets = ?fiat_targets,
                 crypto_targets = ?crypto_targets,
                 "calc mode: fetching prices for conversion with provider fallback"
             );
         }
 
-        let mut conversions: Vec<calc::Conversion> = Vec::new();
-        let fiat_provider = provider::frankfurter::Frankfurter::new();
-
-        match (fiat_targets.is_empty(), crypto_targets.is_empty()) {
-            // Both fiat and crypto targets -- fetch concurrently.
-            (false, false) => {
-                let fiat_fut = fiat_provider.get_rates(&fiat.currency, &fiat_targets);
-                let crypto_fut = async {
-                    if cli.provider.is_some() {
-                        prov.get_prices(&crypto_targets, &fiat.currency).await
-                    } else {
-                        fetch_prices_with_provider_fallback(
-                            &providers,
-                            &provider_indices,
-                            &crypto_targets,
-                            &fiat.currency,
-                        )
-                        .await
-                    }
-                };
-
-                let (fiat_result, crypto_result) = tokio::join!(fiat_fut, crypto_fut);
-
-                let rates = fiat_result?;
-                for target in &fiat_targets {
-                    let upper = target.to_uppercase();
-                    if let Some(&rate) = rates.get(&upper) {
-                        conversions.push(calc::Conversion {
-                            from_amount: fiat.amount,
-                            from_currency: fiat.currency.clone(),
-                            to_symbol: upper.clone(),
-                            to_name: calc::fiat_name(&upper).to_string(),
-                            to_amount: fiat.amount * rate,
-                            rate: 1.0 / rate,
-                            provider: "Frankfurter/ECB".to_string(),
-                            timestamp: chrono::Utc::now(),
-                        });
-                    }
-                }
-
-                let prices = crypto_result?;
-                for p in &prices {
-                    conversions.push(calc::Conversion {
-                        from_amount: fiat.amount,
-                        from_currency: fiat.currency.clone(),
-                        to_symbol: p.symbol.clone(),
-                        to_name: p.name.clone(),
-                        to_amount: fiat.amount / p.price,
-                        rate: p.price,
-                        provider: p.provider.clone(),
-                        timestamp: chrono::Utc::now(),
-                    });
-                }
-            }
-            // Only fiat targets.
-            (false, true) => {
-                let rates = fiat_provider
-                    .get_rates(&fiat.currency, &fiat_targets)
-                    .await?;
-                for target in &fiat_targets {
-                    let upper = target.to_uppercase();
-                    if let Some(&rate) = rates.get(&upper) {
-                        conversions.push(calc::Conversion {
-                            from_amount: fiat.amount,
-                            from_currency: fiat.currency.clone(),
-                            to_symbol: upper.clone(),
-                            to_name: calc::fiat_name(&upper).to_string(),
-                            to_amount: fiat.amount * rate,
-                            rate: 1.0 / rate,
-                            provider: "Frankfurter/ECB".to_string(),
-                            timestamp: chrono::Utc::now(),
-                        });
-                    }
+        let fiat_source =
+            provider::fiat::FiatRateSource::from_settings(&provider_settings, &http_client);
+
+        // One rate lookup and one price lookup per source currency, however many
+        // amounts share it; fiat and crypto targets are fetched concurrently.
+        let mut quotes: HashMap<
+            String,
+            (Option<provider::fiat::FiatRates>, Vec<provider::CoinPrice>),
+        > = HashMap::new();
+        for currency in request.source_currencies() {
+            let fiat_fut = async {
+                if fiat_targets.is_empty() {
+                    return Ok(None);
                 }
-            }
-            // Only crypto targets (existing behavior).
-            (true, false) => {
-                let prices = if cli.provider.is_some() {
-                    prov.get_prices(&crypto_targets, &fiat.currency).await?
+                fiat_source
+                    .get_rates(&currency, fiat_targets)
+                    .await
+                    .map(Some)
+            };
+            let crypto_fut = async {
+                if crypto_targets.is_empty() {
+                    Ok(Vec::new())
+                } else if cli.provider.is_some() {
+                    prov.get_prices(crypto_targets, &currency).await
                 } else {
                     fetch_prices_with_provider_fallback(
                         &providers,
                         &provider_indices,
-                        &crypto_targets,
-                        &fiat.currency,
+                        crypto_targets,
+                        &currency,
                     )
-                    .await?
-                };
-                for p in &prices {
-                    conversions.push(calc::Conversion {
-                        from_amount: fiat.amount,
-                        from_currency: fiat.currency.clone(),
-                        to_symbol: p.symbol.clone(),
-                        to_name: p.name.clone(),
-                        to_amount: fiat.amount / p.price,
-                        rate: p.price,
-                        provider: p.provider.clone(),
-                        timestamp: chrono::Utc::now(),
-                    });
+                    .await
+                    .map(|batch| batch.prices)
                 }
-            }
-            // Both empty -- unreachable since we checked targets.is_empty() above.
-            (true, true) => unreachable!(),
+            };
+
+            let (fiat_result, crypto_result) = tokio::join!(fiat_fut, crypto_fut);
+            quotes.insert(currency, (fiat_result?, crypto_result?));
         }
 
-        if cli.json {
-            output::json::print_conversions_json(&conversions)?;
-        } else {
-            output::table::print_conversions_table(&conversions);
+        let mut conversions: Vec<calc::Conversion> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
+        for fiat in sources {
+            let (rates, prices) = &quotes[&fiat.currency];
+            if let Some(rates) = rates {
+                conversions.extend(fiat_conversions(fiat, fiat_targets, rates));
+                for code in &rates.skipped {
+                    if !skipped.contains(code) {
+                        skipped.push(code.clone());
+                    }
+                }
+            }
+            for p in prices {
+                conversions.push(calc::Conversion {
+                    from_amount: fiat.amount,
+                    from_currency: fiat.currency.clone(),
+                    to_symbol: p.symbol.clone(),
+                    to_name: p.name.clone(),
+                    to_amount: fiat.amount / p.price,
+                    rate: p.price,
+                    provider: p.provider.clone(),
+                    timestamp: chrono::Utc::now(),
+                });
+            }
         }
 
+        print_conversions(&cli, report.as_ref(), &conversions, &skipped)?;
+
         return Ok(());
     }
 
     // Calc mode (crypto source): detect `<number><crypto>` as first positional arg.
-    if let Some(crypto) = calc::parse_crypto_amount(&symbols[0]) {
+    if let Some(crypto) = calc::parse_crypto_amount(&symbols[0]).filter(|_| calc_allowed) {
+        ensure_lookup_only_flags(&cli, "conversion mode")?;
+        if cli.field.is_some() {
+            return Err(error::Error::Config(
+                "--field is only available for price lookup, not conversion mode".into(),
+            ));
+        }
         if cli.chart {
             return Err(error::Error::Config(
                 "chart mode is only available for direct symbol lookup".into(),
@@ -921,9 +3702,10 @@ async fn run(cli: Cli) -> Result<()> {
         );
 
         let mut conversions: Vec<calc::Conversion> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
 
         // For fiat targets: look up the source crypto price in each target fiat currency,
-        // then multiply. We use the first fiat target as the base and Frankfurter for cross-rates.
+        // then multiply. We use the first fiat target as the base and fiat rates for cross-rates.
         if !fiat_targets.is_empty() {
             let base_fiat = fiat_targets[0].to_uppercase();
             let prices = if cli.provider.is_some() {
@@ -937,6 +3719,7 @@ async fn run(cli: Cli) -> Result<()> {
                     &base_fiat,
                 )
                 .await?
+                .prices
             };
 
             if let Some(p) = prices.first() {
@@ -952,27 +3735,31 @@ async fn run(cli: Cli) -> Result<()> {
                     timestamp: chrono::Utc::now(),
                 });
 
-                // Cross-rate conversions for remaining fiat targets via Frankfurter.
+                // Cross-rate conversions for remaining fiat targets.
                 if fiat_targets.len() > 1 {
                     let other_fiats: Vec<String> =
                         fiat_targets[1..].iter().map(|s| s.to_uppercase()).collect();
-                    let fiat_provider = provider::frankfurter::Frankfurter::new();
-                    let rates = fiat_provider.get_rates(&base_fiat, &other_fiats).await?;
+                    let fiat_source = provider::fiat::FiatRateSource::from_settings(
+                        &provider_settings,
+                        &http_client,
+                    );
+                    let rates = fiat_source.get_rates(&base_fiat, &other_fiats).await?;
                     let base_value = crypto.amount * p.price;
                     for target in &other_fiats {
-                        if let Some(&rate) = rates.get(target) {
+                        if let Some(fiat_rate) = rates.rates.get(target) {
                             conversions.push(calc::Conversion {
                                 from_amount: crypto.amount,
                                 from_currency: crypto.symbol.clone(),
                                 to_symbol: target.clone(),
                                 to_name: calc::fiat_name(target).to_string(),
-                                to_amount: base_value * rate,
-                                rate: p.price * rate,
-                                provider: format!("{} + Frankfurter/ECB", p.provider),
+                                to_amount: base_value * fiat_rate.rate,
+                                rate: p.price * fiat_rate.rate,
+                                provider: format!("{} + {}", p.provider, fiat_rate.provider),
                                 timestamp: chrono::Utc::now(),
                             });
                         }
                     }
+                    skipped = rates.skipped;
                 }
             }
         }
@@ -991,6 +3778,7 @@ async fn run(cli: Cli) -> Result<()> {
                     "USD",
                 )
                 .await?
+                .prices
             };
 
             let source_price = prices
@@ -1018,93 +3806,606 @@ async fn run(cli: Cli) -> Result<()> {
                     }
                 }
             }
-        }
-
-        if cli.json {
-            output::json::print_conversions_json(&conversions)?;
-        } else {
-            output::table::print_conversions_table(&conversions);
-        }
-
-        return Ok(());
-    }
-
-    if cli.chart {
-        info!(
-            provider = prov.id(),
-            symbols = ?symbols,
-            currency = %currency,
-            range = %chart_range_label,
-            start_date = ?chart_start_date,
-            end_date = %chart_end_date,
-            fetch_days = chart_fetch_days,
-            "fetching historical prices"
-        );
+        }
+
+        print_conversions(&cli, report.as_ref(), &conversions, &skipped)?;
+
+        return Ok(());
+    }
+
+    if cli.chart {
+        ensure_watchable_flags(&cli, "chart mode")?;
+        ensure_capability(&providers, prov.as_ref(), provider::Capability::History)?;
+        if let Some(pair) = symbols
+            .iter()
+            .find(|token| calc::parse_pair(token).is_some())
+        {
+            return Err(error::Error::Config(format!(
+                "pair syntax like '{}' is only available for price lookup -- use --currency with --chart",
+                pair
+            )));
+        }
+        info!(
+            provider = prov.id(),
+            symbols = ?symbols,
+            currency = %currency,
+            range = %chart_range_label,
+            start_date = ?chart_start_date,
+            end_date = %chart_end_date,
+            fetch_days = chart_fetch_days,
+            "fetching historical prices"
+        );
+
+        let fetch_started = std::time::Instant::now();
+        let first_fetch_at = chrono::Utc::now();
+        let mut histories = fetch_history_window(
+            prov.as_ref(),
+            &symbols,
+            &currency,
+            (chart_start_ts, chart_end_ts),
+            sampling,
+            chart_days_to_today,
+        )
+        .await?;
+        timings::record_phase("fetch", fetch_started);
+
+        // Gap filling and resampling run on a copy so watch refreshes append to raw points.
+        let prepare = |raw: &[provider::PriceHistory]| {
+            let mut histories = raw.to_vec();
+            fill_history_gaps(&mut histories, cli.fill.into());
+            resample_histories(&mut histories, sampling, cli.resample_method.into());
+            histories
+        };
+        let render = |histories: &[provider::PriceHistory]| -> Result<()> {
+            if let Some(report) = &report {
+                print_report(
+                    report,
+                    &history_report_context(histories, &chart_range_label),
+                )?;
+            } else if markdown_requested(&cli) {
+                println!("{}", output::markdown::render_history_stats(histories));
+            } else if ndjson_requested(&cli) {
+                output::json::print_ndjson(&output::json::histories_with_stats(histories))?;
+            } else if cli.json && cli.schema == SchemaArg::V1 {
+                output::json::print_envelope(&output::json::Envelope::history(
+                    histories,
+                    Some(prov.id()),
+                    Some(&currency),
+                ))?;
+            } else if cli.json {
+                output::json::print_history_json(histories, &chart_comparison(histories))?;
+            } else {
+                output::table::print_history_charts(
+                    histories,
+                    &chart_range_label,
+                    sampling,
+                    chart_size,
+                );
+                print_chart_comparison(histories);
+            }
+            Ok(())
+        };
+
+        let prepared = prepare(&histories);
+        if let Some(path) = cli.export.as_deref() {
+            output::export::write_history(path, &prepared, cli.force)?;
+        }
+        if let Some(path) = cli.export_html.as_deref() {
+            output::export::write_html(path, &prepared, &chart_range_label, cli.force)?;
+        }
+        export_png(&cli, &prepared, &chart_range_label)?;
+        let render_started = std::time::Instant::now();
+        let redraw_in_place = cli.watch && !cli.json && std::io::stdout().is_terminal();
+        let _cursor = redraw_in_place.then(output::chart::HiddenCursor::hide);
+        if redraw_in_place {
+            print!("\x1b[2J\x1b[H");
+        }
+        render(&prepared)?;
+        timings::record_phase("render", render_started);
+        if !cli.watch {
+            return Ok(());
+        }
+
+        // The window keeps its length and slides with the clock. Ctrl-C ends the loop
+        // from `main`, dropping it at the sleep or refresh it is waiting on.
+        let window = chart_start_ts.map(|start| first_fetch_at - start);
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(cli.watch_interval)).await;
+            let refreshed = refresh_histories(
+                prov.as_ref(),
+                &mut histories,
+                &symbols,
+                &currency,
+                sampling,
+                chart_days_to_today,
+            )
+            .await;
+            if let Err(err) = refreshed {
+                warn!(error = %err, "chart refresh failed; retrying on next interval");
+                continue;
+            }
+            let now = chrono::Utc::now();
+            filter_histories_by_time_window(&mut histories, window.map(|window| now - window), now);
+            if redraw_in_place {
+                print!("\x1b[2J\x1b[H");
+            }
+            render(&prepare(&histories))?;
+        }
+    }
+
+    let redraw_in_place =
+        cli.watch && !cli.json && !status_output_requested(&cli) && std::io::stdout().is_terminal();
+    let mut alert_tracker = alerts::AlertTracker::default();
+
+    // `btc/eur` quotes one symbol in its own currency; the rest use `currency`.
+    let quotes = parse_quotes(&symbols, &currency);
+    let quote_groups = group_quotes(&quotes);
+    let symbols: Vec<String> = quotes.iter().map(|quote| quote.symbol.clone()).collect();
+    // A single code is just `--currency`, so the output keeps its usual shape.
+    let multi_currency = currencies.len() > 1;
+    if multi_currency && let Some(quote) = quotes.iter().find(|quote| quote.token != quote.symbol) {
+        return Err(error::Error::Config(format!(
+            "pair syntax like '{}' cannot be combined with --currencies",
+            quote.token
+        )));
+    }
+
+    let amounts = resolve_amounts(&cli.amount, &symbols, &app_config.portfolio)?;
+    if cli.schema == SchemaArg::V1 && (cli.global || !amounts.is_empty()) {
+        // v1 has no place for them yet; adding one is a schema change.
+        return Err(error::Error::Config(
+            "--schema v1 does not cover --global or held amounts -- use --schema legacy".into(),
+        ));
+    }
+    let table_columns: Vec<output::table::PriceColumn> = if cli.columns.is_empty() {
+        let change_columns: Vec<output::table::PriceColumn> =
+            cli.changes.iter().map(|&change| change.into()).collect();
+        let mut columns = output::table::price_columns(
+            &change_columns,
+            cli.sparkline,
+            cli.extended,
+            cli.ath,
+            cli.spread,
+        );
+        if multi_currency
+            && let Some(at) = columns
+                .iter()
+                .position(|&column| column == output::table::PriceColumn::Price)
+        {
+            columns.splice(
+                at..=at,
+                (0..currencies.len()).map(output::table::PriceColumn::PriceIn),
+            );
+        }
+        if cli.since.is_some() {
+            // The reference price reads best next to the current price and changes.
+            let at = columns
+                .iter()
+                .position(|&column| column == output::table::PriceColumn::MarketCap)
+                .unwrap_or(columns.len() - 1);
+            columns.splice(
+                at..at,
+                [
+                    output::table::PriceColumn::SincePrice,
+                    output::table::PriceColumn::SinceChange,
+                ],
+            );
+        }
+        if cli.delta {
+            let at = columns
+                .iter()
+                .position(|&column| column == output::table::PriceColumn::MarketCap)
+                .unwrap_or(columns.len() - 1);
+            columns.insert(at, output::table::PriceColumn::Delta);
+        }
+        if cli.age {
+            let at = columns.len() - 1;
+            columns.insert(at, output::table::PriceColumn::Age);
+        }
+        if !amounts.is_empty() {
+            // Holdings go just before the provider column.
+            let at = columns.len() - 1;
+            columns.splice(
+                at..at,
+                [
+                    output::table::PriceColumn::Amount,
+                    output::table::PriceColumn::Value,
+                ],
+            );
+        }
+        columns
+    } else {
+        cli.columns.iter().map(|&column| column.into()).collect()
+    };
+    let show_trend = table_columns.contains(&output::table::PriceColumn::Trend);
+    if let Some(date) = cli.since
+        && date >= chrono::Utc::now().date_naive()
+    {
+        return Err(error::Error::Config(
+            "--since must be a date before today".into(),
+        ));
+    }
+    // Every refresh compares with the previous run and then becomes the next run's baseline.
+    let mut delta = if cli.delta || table_columns.contains(&output::table::PriceColumn::Delta) {
+        let path = state::delta_path().ok_or_else(|| {
+            error::Error::Config(
+                "--delta needs a state directory -- set XDG_STATE_HOME or HOME".into(),
+            )
+        })?;
+        if cli.delta_reset {
+            state::reset(&path).map_err(|err| {
+                error::Error::Config(format!("cannot reset {}: {}", path.display(), err))
+            })?;
+        }
+        let baseline = state::DeltaState::load(&path);
+        Some((path, baseline.clone(), baseline))
+    } else {
+        None
+    };
+    // The reference closes are fixed: cached ones are reused, and a watch loop asks for
+    // the rest only with its first refresh.
+    let (mut since_closes, mut since_pending) = match cli.since {
+        Some(date) => {
+            cached_since_closes(http_client.cache_dir(), prov.as_ref(), &quotes, date).await
+        }
+        None => (HashMap::new(), Vec::new()),
+    };
+
+    let fetch_batch = async |symbols: &[String], currency: &str| {
+        if cli.provider.is_some() {
+            info!(
+                provider = prov.id(),
+                symbols = ?symbols,
+                currency = %currency,
+                "fetching prices"
+            );
+            prov.get_prices(symbols, currency)
+                .await
+                .map(|prices| provider::PriceBatch::from_requested(symbols, prices))
+        } else if cli.auto_provider {
+            fetch_prices_by_asset_class(
+                &providers,
+                &provider_indices,
+                &app_config.aliases,
+                symbols,
+                std::slice::from_ref(&currency.to_string()),
+            )
+            .await
+        } else {
+            let ordered_ids = provider_ids_for_indices(&providers, &provider_indices);
+            info!(
+                providers = ?ordered_ids,
+                symbols = ?symbols,
+                currency = %currency,
+                "fetching prices with provider fallback"
+            );
+            fetch_prices_with_provider_fallback(&providers, &provider_indices, symbols, currency)
+                .await
+        }
+    };
+
+    let fetch_in_currencies = async |symbols: &[String]| {
+        if cli.provider.is_some() {
+            info!(
+                provider = prov.id(),
+                symbols = ?symbols,
+                currencies = ?currencies,
+                "fetching prices"
+            );
+            prov.get_prices_in(symbols, &currencies)
+                .await
+                .map(|prices| provider::PriceBatch::from_requested(symbols, prices))
+        } else if cli.auto_provider {
+            fetch_prices_by_asset_class(
+                &providers,
+                &provider_indices,
+                &app_config.aliases,
+                symbols,
+                &currencies,
+            )
+            .await
+        } else {
+            fetch_prices_in_with_provider_fallback(
+                &providers,
+                &provider_indices,
+                symbols,
+                &currencies,
+            )
+            .await
+        }
+    };
+
+    loop {
+        let prices_fut = async {
+            if multi_currency {
+                return fetch_in_currencies(&symbols).await;
+            }
+            if let [(currency, symbols)] = quote_groups.as_slice()
+                && quotes.iter().all(|quote| quote.token == quote.symbol)
+            {
+                return fetch_batch(symbols, currency).await;
+            }
+            // Providers convert into one currency per request, so each currency is its
+            // own request; a currency with no results leaves its pairs missing.
+            let results = futures::future::join_all(
+                quote_groups
+                    .iter()
+                    .map(|(currency, symbols)| fetch_batch(symbols, currency)),
+            )
+            .await;
+            let mut batches = Vec::new();
+            let mut no_results = None;
+            for ((currency, symbols), result) in quote_groups.iter().zip(results) {
+                let batch = match result {
+                    Ok(batch) => batch,
+                    Err(err @ error::Error::NoResults { .. }) => {
+                        no_results.get_or_insert(err);
+                        provider::PriceBatch::from_requested(symbols, Vec::new())
+                    }
+                    Err(err) => return Err(err),
+                };
+                batches.push((currency.clone(), batch));
+            }
+            let batch = merge_quote_batches(&quotes, batches);
+            if batch.prices.is_empty() {
+                return Err(
+                    no_results.unwrap_or_else(|| error::Error::no_results("", &symbols, &currency))
+                );
+            }
+            Ok(batch)
+        };
+        // Sparkline and `--since` histories share one plan, so a since day inside the
+        // sparkline window costs no extra request. Sparklines are a table-only column,
+        // so JSON output skips them.
+        let show_sparklines = show_trend && !cli.json && !status_output_requested(&cli);
+        let mut history_plan = fetch::HistoryPlan::new(chrono::Utc::now().date_naive());
+        let sparkline_tickets: Vec<fetch::Ticket> = if show_sparklines {
+            symbols
+                .iter()
+                .map(|symbol| {
+                    history_plan.add(
+                        symbol,
+                        &currency,
+                        fetch::HistoryRange::LastDays(SPARKLINE_DAYS),
+                    )
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let since_tickets: Vec<(&Quote, fetch::Ticket)> = match cli.since {
+            Some(date) => since_pending
+                .iter()
+                .map(|&quote| {
+                    let ticket = history_plan.add(
+                        &quote.symbol,
+                        &quote.currency,
+                        fetch::HistoryRange::Day(date),
+                    );
+                    (quote, ticket)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let history_fut = history_plan.run(prov.as_ref());
+
+        // The summary is decoration on a lookup, so a failure only drops it.
+        let global_fut = async {
+            if !cli.global
+                || status_output_requested(&cli)
+                || ndjson_requested(&cli)
+                || markdown_requested(&cli)
+            {
+                return None;
+            }
+            fetch_from_first_supported(
+                &providers,
+                &provider_indices,
+                cli.provider.is_some(),
+                provider::Capability::Global,
+                async |prov| prov.get_global_metrics(&currency).await,
+            )
+            .await
+            .inspect_err(|err| warn!(error = %err, "global metrics unavailable"))
+            .ok()
+        };
 
-        let mut histories = match prov
-            .get_price_history_window(
-                &symbols,
+        let fetch_started = std::time::Instant::now();
+        let (prices, histories, global) = tokio::join!(prices_fut, history_fut, global_fut);
+        timings::record_phase("fetch", fetch_started);
+        let mut sparkline_histories: Option<Vec<provider::PriceHistory>> =
+            show_sparklines.then(|| {
+                sparkline_tickets
+                    .iter()
+                    .filter_map(|&ticket| histories.history(ticket).cloned())
+                    .collect()
+            });
+        if let (Some(sparklines), Ok(batch)) = (&mut sparkline_histories, &prices) {
+            refetch_fallback_sparklines(
+                sparklines,
+                &batch.prices,
+                &providers,
+                primary_provider_idx,
                 &currency,
-                chart_start_ts,
-                chart_end_ts,
-                cli.sampling.into(),
             )
-            .await
-        {
-            Ok(histories) => histories,
-            Err(error::Error::Config(message))
-                if message.contains("does not support explicit chart date windows") =>
+            .await;
+        }
+        if let Some(date) = cli.since {
+            for (quote, ticket) in since_tickets {
+                let Some((price, currency)) = histories.close(ticket) else {
+                    continue;
+                };
+                let close = SinceClose {
+                    price,
+                    currency: currency.to_string(),
+                };
+                provider::cache::write_json(
+                    http_client.cache_dir(),
+                    prov.id(),
+                    &since_cache_key(quote, date),
+                    &close,
+                )
+                .await;
+                since_closes.insert((quote.symbol.to_uppercase(), quote.currency.clone()), close);
+            }
+            since_pending.clear();
+        }
+        let mut batch = match prices {
+            Ok(batch) => batch,
+            // A transient provider failure should not end a long-running watch.
+            Err(err) if cli.watch => {
+                warn!(error = %err, "price refresh failed; retrying on next interval");
+                tokio::time::sleep(std::time::Duration::from_secs(cli.watch_interval)).await;
+                continue;
+            }
+            Err(err @ error::Error::NoResults { .. })
+                if provider_indices
+                    .iter()
+                    .any(|&idx| providers[idx].id() == "coingecko") =>
             {
-                prov.get_price_history(&symbols, &currency, chart_fetch_days, cli.sampling.into())
-                    .await?
+                print_symbol_suggestions(&symbols, &http_client, &provider_settings).await;
+                return Err(err);
             }
-            Err(other) => return Err(other),
+            Err(err) => return Err(err),
         };
-        filter_histories_by_time_window(&mut histories, chart_start_ts, chart_end_ts);
-        if histories.is_empty() {
-            return Err(error::Error::NoResults);
+        if show_attribution {
+            provider::attribute(&mut batch.prices, &providers);
         }
 
-        if cli.json {
-            output::json::print_history_json(&histories)?;
+        let since_missing = match cli.since {
+            Some(date) => apply_since_closes(&mut batch.prices, &since_closes, date),
+            None => Vec::new(),
+        };
+        if let Some((path, baseline, latest)) = &mut delta {
+            apply_deltas(&mut batch.prices, baseline);
+            latest.record(&batch.prices, chrono::Utc::now());
+            if let Err(err) = latest.save(path) {
+                warn!(path = %path.display(), error = %err, "failed to save --delta prices");
+            }
+        }
+
+        if let Some(sort) = cli.sort {
+            output::sort::sort_prices(&mut batch.prices, sort.into(), cli.desc);
+        }
+
+        if redraw_in_place {
+            print!("\x1b[2J\x1b[H");
+        }
+
+        let prices = &batch.prices;
+        let portfolio =
+            (!amounts.is_empty()).then(|| calc::value_positions(prices, &amounts, &currency));
+        let render_started = std::time::Instant::now();
+        if let Some(report) = &report {
+            print_report(
+                report,
+                &serde_json::json!({ "prices": prices, "portfolio": portfolio }),
+            )?;
+        } else if cli.format == Some(FormatArg::Waybar) {
+            output::status::print_waybar(prices, cli.template.as_deref())?;
+        } else if let Some(template) = cli.template.as_deref() {
+            output::status::print_template(prices, template);
+        } else if cli.quiet {
+            output::quiet::print_prices(
+                prices,
+                cli.field.as_deref().unwrap_or(output::quiet::DEFAULT_FIELD),
+            )?;
+        } else if markdown_requested(&cli) {
+            println!(
+                "{}",
+                output::markdown::render_prices(
+                    prices,
+                    sparkline_histories.as_deref(),
+                    portfolio.as_ref(),
+                    &table_columns,
+                )
+            );
+        } else if ndjson_requested(&cli) {
+            output::json::print_ndjson(prices)?;
+        } else if cli.json && cli.schema == SchemaArg::V1 {
+            output::json::print_envelope(&output::json::Envelope::prices(
+                &batch,
+                Some(prov.id()),
+                Some(&currency),
+            ))?;
+        } else if cli.json {
+            output::json::print_price_batch_json(
+                &batch,
+                global.as_ref(),
+                portfolio.as_ref(),
+                timings::take().as_ref(),
+            )?;
         } else {
-            output::table::print_history_charts(
-                &histories,
-                &chart_range_label,
-                cli.sampling.into(),
+            if let Some(global) = &global {
+                println!("{}", output::table::format_global_summary(global));
+            }
+            output::table::print_table(
+                prices,
+                sparkline_histories.as_deref(),
+                portfolio.as_ref(),
+                &table_columns,
             );
         }
+        timings::record_phase("render", render_started);
+        copy_result(&cli, |format| {
+            if cli.json && cli.schema == SchemaArg::V1 {
+                output::json::envelope_json(&output::json::Envelope::prices(
+                    &batch,
+                    Some(prov.id()),
+                    Some(&currency),
+                ))
+                .map(Some)
+            } else if cli.json {
+                output::json::price_batch_json(&batch, global.as_ref(), portfolio.as_ref(), None)
+                    .map(Some)
+            } else {
+                Ok(output::copy::price_text(prices, format))
+            }
+        })?;
+        if let Some(date) = cli.since
+            && !since_missing.is_empty()
+        {
+            output::table::print_since_warning(date, &since_missing);
+        }
+        if !batch.missing.is_empty() {
+            output::table::print_missing_warning(&batch.missing);
+            if cli.strict {
+                return Err(error::Error::no_results("", &batch.missing, &currency));
+            }
+        }
 
-        return Ok(());
-    }
-
-    let prices = if cli.provider.is_some() {
-        info!(
-            provider = prov.id(),
-            symbols = ?symbols,
-            currency = %currency,
-            "fetching prices"
-        );
-        prov.get_prices(&symbols, &currency).await?
-    } else {
-        let ordered_ids = provider_ids_for_indices(&providers, &provider_indices);
-        info!(
-            providers = ?ordered_ids,
-            symbols = ?symbols,
-            currency = %currency,
-            "fetching prices with provider fallback"
-        );
-        fetch_prices_with_provider_fallback(&providers, &provider_indices, &symbols, &currency)
-            .await?
-    };
+        if cli.watch
+            && let Some(webhook) = &webhook
+        {
+            webhook
+                .notify(&webhook::Event::Refresh {
+                    prices,
+                    missing: &batch.missing,
+                    portfolio: portfolio.as_ref(),
+                })
+                .await;
+        }
+        let fired = alert_tracker.check(&cli.alerts, prices);
+        for (condition, price) in &fired {
+            println!("{}", alerts::format_alert_line(condition, price));
+            if let Some(command) = cli.alert_exec.as_deref() {
+                alerts::spawn_exec(command, price);
+            }
+            if let Some(webhook) = &webhook {
+                webhook
+                    .notify(&webhook::Event::alert(condition, price))
+                    .await;
+            }
+        }
+        if cli.alert_once && !fired.is_empty() {
+            std::process::exit(alerts::ALERT_EXIT_CODE);
+        }
 
-    if cli.json {
-        output::json::print_json(&prices)?;
-    } else {
-        output::table::print_table(&prices);
+        if !cli.watch {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(cli.watch_interval)).await;
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
@@ -1125,16 +4426,92 @@ mod tests {
     }
 
     #[test]
-    fn append_provider_name_adds_unique_values() {
-        let mut provider = "Yahoo Finance".to_string();
-        append_provider_name(&mut provider, "Stooq");
-        append_provider_name(&mut provider, "stooq");
-        assert_eq!(provider, "Yahoo Finance, Stooq");
+    fn apply_since_closes_converts_only_converted_rows() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let row = |symbol: &str, price: f64, converted: bool| provider::CoinPrice {
+            symbol: symbol.to_string(),
+            price,
+            currency: "EUR".to_string(),
+            converted,
+            fx_rate: converted.then_some(0.5),
+            ..Default::default()
+        };
+        let mut prices = vec![
+            row("BTC", 60.0, false),
+            row("AAPL", 75.0, true),
+            row("TSLA", 10.0, false),
+            row("ETH", 5.0, false),
+        ];
+        let close = |price: f64, currency: &str| SinceClose {
+            price,
+            currency: currency.to_string(),
+        };
+        let closes = HashMap::from([
+            (("BTC".to_string(), "EUR".to_string()), close(40.0, "EUR")),
+            (("AAPL".to_string(), "EUR".to_string()), close(100.0, "USD")),
+            (("TSLA".to_string(), "EUR".to_string()), close(20.0, "USD")),
+        ]);
+
+        let missing = apply_since_closes(&mut prices, &closes, date);
+
+        assert_eq!(missing, vec!["TSLA".to_string(), "ETH".to_string()]);
+        assert!(prices.iter().all(|price| price.since_date == Some(date)));
+        assert_eq!(prices[0].since_price, Some(40.0));
+        assert_eq!(prices[0].since_change_pct, Some(50.0));
+        assert_eq!(prices[1].since_price, Some(50.0));
+        assert_eq!(prices[1].since_change_pct, Some(50.0));
+        assert_eq!(prices[2].since_price, None);
+        assert_eq!(prices[2].since_change_pct, None);
+    }
+
+    #[test]
+    fn chart_fetch_days_cover_the_window_not_the_time_since_it() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(
+            compute_chart_fetch_days(Some(date(2020, 1, 1)), date(2020, 12, 31)),
+            366
+        );
+        assert_eq!(
+            compute_chart_fetch_days(Some(date(2020, 6, 1)), date(2020, 6, 1)),
+            1
+        );
+        assert_eq!(
+            compute_chart_fetch_days(Some(date(2020, 6, 2)), date(2020, 6, 1)),
+            1
+        );
+        assert_eq!(
+            compute_chart_fetch_days(Some(date(1800, 1, 1)), date(2020, 6, 1)),
+            MAX_CHART_FETCH_DAYS
+        );
+        assert_eq!(
+            compute_chart_fetch_days(None, date(2020, 6, 1)),
+            MAX_CHART_FETCH_DAYS
+        );
+    }
+
+    #[test]
+    fn resolve_currencies_normalizes_and_caps_the_list() {
+        let codes = |raw: &str| raw.split(',').map(str::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            resolve_currencies(&codes("usd, EUR,usd,jpy")).unwrap(),
+            vec!["USD", "EUR", "JPY"]
+        );
+        assert!(resolve_currencies(&codes("usd,eur,jpy,gbp,chf")).is_ok());
+        let err = resolve_currencies(&codes("usd,eur,jpy,gbp,chf,cad")).unwrap_err();
+        assert!(err.to_string().contains("at most 5"), "{err}");
+        assert!(resolve_currencies(&codes("usd,btc")).is_err());
     }
 
     #[test]
     fn resolve_provider_indices_uses_configured_order_then_remaining() {
-        let providers = provider::available_providers(None);
+        let providers = provider::available_providers(
+            &config::ProviderSettings::default(),
+            &config::SymbolAliases::default(),
+            &provider::http::HttpClient::default(),
+            None,
+        );
         let configured = vec!["yahoo".to_string(), "coingecko".to_string()];
 
         let indices = resolve_provider_indices(&providers, None, Some(&configured)).unwrap();
@@ -1149,7 +4526,12 @@ mod tests {
 
     #[test]
     fn resolve_provider_indices_rejects_unknown_configured_provider() {
-        let providers = provider::available_providers(None);
+        let providers = provider::available_providers(
+            &config::ProviderSettings::default(),
+            &config::SymbolAliases::default(),
+            &provider::http::HttpClient::default(),
+            None,
+        );
         let configured = vec!["not-a-provider".to_string()];
 
         let err = resolve_provider_indices(&providers, None, Some(&configured)).unwrap_err();
@@ -1161,6 +4543,122 @@ mod tests {
         }
     }
 
+    #[test]
+    fn select_symbol_tokens_prefers_cli_over_config_defaults() {
+        let defaults = vec!["btc".to_string(), "eth".to_string()];
+        let cli = vec!["sol".to_string()];
+
+        assert_eq!(select_symbol_tokens(&cli, Some(&defaults)), ["sol"]);
+        assert_eq!(select_symbol_tokens(&[], Some(&defaults)), ["btc", "eth"]);
+        assert!(select_symbol_tokens(&[], None).is_empty());
+    }
+
+    #[test]
+    fn completion_scripts_cover_provider_values() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+        ] {
+            let script = completion_script(shell);
+            assert!(!script.is_empty(), "{:?} script is empty", shell);
+            assert!(
+                script.contains("--provider"),
+                "{:?} lacks --provider",
+                shell
+            );
+            for id in provider::PROVIDER_IDS {
+                assert!(script.contains(id), "{:?} lacks provider {}", shell, id);
+            }
+        }
+    }
+
+    #[test]
+    fn interval_and_sampling_offer_their_values() {
+        let cmd = <Cli as clap::CommandFactory>::command();
+        let values = |id: &str| -> Vec<String> {
+            cmd.get_arguments()
+                .find(|arg| arg.get_id() == id)
+                .unwrap()
+                .get_possible_values()
+                .iter()
+                .map(|value| value.get_name().to_string())
+                .collect()
+        };
+        assert_eq!(
+            values("interval"),
+            ["1D", "5D", "1M", "6M", "YTD", "1Y", "5Y", "ALL"]
+        );
+        assert_eq!(
+            values("sampling"),
+            ["auto", "hourly", "4h", "daily", "weekly", "monthly"]
+        );
+
+        let bash = completion_script(CompletionShell::Bash);
+        assert!(bash.contains("1D 5D 1M 6M YTD 1Y 5Y ALL"), "{bash}");
+        assert!(bash.contains("auto hourly 4h daily weekly monthly"));
+    }
+
+    #[test]
+    fn zsh_and_fish_scripts_complete_symbols_dynamically() {
+        let zsh = completion_script(CompletionShell::Zsh);
+        assert!(zsh.contains(":_pricr_symbols'"));
+        let helper = zsh.find("_pricr_symbols() {").unwrap();
+        assert!(helper < zsh.rfind("compdef _pricr pricr").unwrap());
+
+        let fish = completion_script(CompletionShell::Fish);
+        assert!(fish.contains("(pricr __complete-symbols 2>/dev/null)"));
+    }
+
+    #[test]
+    fn completion_symbols_merge_config_and_builtin_lists() {
+        let app_config: config::AppConfig = toml::from_str(
+            r#"
+                [watchlists]
+                majors = ["btc", "eth"]
+
+                [aliases]
+                RNDR = { coingecko = "render-token" }
+            "#,
+        )
+        .unwrap();
+        let symbols = completion_symbols(&app_config);
+
+        assert_eq!(symbols[0], "@majors");
+        for expected in ["rndr", "btc", "sol", "eur", "usd"] {
+            assert!(
+                symbols.iter().any(|s| s == expected),
+                "missing {}",
+                expected
+            );
+        }
+        assert_eq!(
+            symbols.iter().filter(|s| s.as_str() == "btc").count(),
+            1,
+            "symbols are deduplicated"
+        );
+    }
+
+    #[test]
+    fn parse_symbol_list_skips_comments_and_blank_lines() {
+        let text = "# crypto\nbtc eth\n\n  sol  # layer 1\n\taapl\n#msft\n";
+        assert_eq!(parse_symbol_list(text), ["btc", "eth", "sol", "aapl"]);
+        assert!(parse_symbol_list("\n# only comments\n").is_empty());
+    }
+
+    #[test]
+    fn merge_symbol_sources_dedups_case_insensitively_in_order() {
+        let cli = vec!["btc".to_string(), "ETH".to_string()];
+        let listed = vec![
+            "eth".to_string(),
+            "sol".to_string(),
+            "BTC".to_string(),
+            "Sol".to_string(),
+        ];
+        assert_eq!(merge_symbol_sources(&cli, &listed), ["btc", "ETH", "sol"]);
+        assert_eq!(merge_symbol_sources(&[], &listed), ["eth", "sol", "BTC"]);
+    }
+
     #[test]
     fn expand_symbol_tokens_resolves_watchlists() {
         let raw = vec!["@commodities".to_string(), "btc".to_string()];
@@ -1184,9 +4682,469 @@ mod tests {
 
         match err {
             error::Error::Config(message) => {
-                assert!(message.contains("unknown watchlist"));
+                assert_eq!(
+                    message,
+                    "unknown group '@unknown' -- defined groups: @commodities, @metals"
+                );
             }
             other => panic!("unexpected error: {other}"),
         }
+        let err = expand_symbol_tokens(&raw, &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("no groups are defined"), "{err}");
+    }
+
+    #[test]
+    fn nested_groups_expand_and_dedup_against_explicit_symbols() {
+        let mut groups = watchlists_for_tests();
+        groups.insert(
+            "defi".to_string(),
+            vec!["uni".to_string(), "aave".to_string(), "mkr".to_string()],
+        );
+        groups.insert(
+            "majors".to_string(),
+            vec!["btc".to_string(), "eth".to_string(), "sol".to_string()],
+        );
+        groups.insert(
+            "everything".to_string(),
+            vec![
+                "@majors".to_string(),
+                "@DeFi".to_string(),
+                "BTC".to_string(),
+            ],
+        );
+        let raw: Vec<String> = ["@everything", "@defi", "@majors", "eth", "link"]
+            .iter()
+            .map(|token| token.to_string())
+            .collect();
+
+        let symbols = provider::normalize_symbols(&expand_symbol_tokens(&raw, &groups).unwrap());
+
+        assert_eq!(symbols, ["btc", "eth", "sol", "uni", "aave", "mkr", "link"]);
+    }
+
+    #[test]
+    fn groups_that_include_themselves_are_rejected() {
+        let groups = HashMap::from([
+            ("a".to_string(), vec!["btc".to_string(), "@b".to_string()]),
+            ("b".to_string(), vec!["@A".to_string()]),
+        ]);
+
+        let err = expand_symbol_tokens(&["@a".to_string()], &groups).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: group 'a' includes itself via @a -> @b -> @a"
+        );
+    }
+
+    #[test]
+    fn alerts_on_groups_target_each_member_once() {
+        let cli = Cli::try_parse_from([
+            "pricr",
+            "--watch",
+            "--alert",
+            "@Majors%>5",
+            "--alert",
+            "eth%>5",
+            "--alert",
+            "btc<60000",
+            "@majors",
+        ])
+        .unwrap();
+        let groups = HashMap::from([(
+            "majors".to_string(),
+            vec!["btc".to_string(), "eth".to_string()],
+        )]);
+
+        let alerts = expand_alert_groups(&cli.alerts, &groups).unwrap();
+
+        let rendered: Vec<String> = alerts.iter().map(ToString::to_string).collect();
+        assert_eq!(rendered, ["BTC%>5", "ETH%>5", "BTC<60000"]);
+        assert!(expand_alert_groups(&cli.alerts, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn global_flag_combines_with_symbols_but_not_chart() {
+        assert!(Cli::try_parse_from(["pricr", "--global"]).unwrap().global);
+        assert!(Cli::try_parse_from(["pricr", "--global", "btc", "eth"]).is_ok());
+        assert!(Cli::try_parse_from(["pricr", "--global", "--chart", "btc"]).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--global", "--top", "10"]).is_err());
+    }
+
+    #[test]
+    fn chart_flags_override_chart_config() {
+        let chart = config::ChartConfig {
+            interval: Some(provider::ChartRange::OneYear),
+            sampling: Some(provider::HistoryInterval::Daily),
+            ..Default::default()
+        };
+        let cli = Cli::try_parse_from(["pricr", "--chart", "btc"]).unwrap();
+        assert_eq!(
+            chart_window_defaults(&cli, &chart),
+            (
+                provider::ChartRange::OneYear,
+                provider::HistoryInterval::Daily
+            )
+        );
+        assert_eq!(
+            chart_window_defaults(&cli, &config::ChartConfig::default()),
+            (
+                provider::ChartRange::OneMonth,
+                provider::HistoryInterval::Auto
+            )
+        );
+
+        let cli = Cli::try_parse_from([
+            "pricr",
+            "--chart",
+            "--interval",
+            "ytd",
+            "--sampling",
+            "4H",
+            "btc",
+        ])
+        .unwrap();
+        assert_eq!(
+            chart_window_defaults(&cli, &chart),
+            (
+                provider::ChartRange::Ytd,
+                provider::HistoryInterval::FourHour
+            )
+        );
+        let err = Cli::try_parse_from(["pricr", "--chart", "--interval", "2Y", "btc"])
+            .err()
+            .expect("unknown preset should be rejected")
+            .to_string();
+        assert!(
+            err.contains("expected one of 1D, 5D, 1M, 6M, YTD, 1Y, 5Y, ALL"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn stats_and_correlation_share_chart_dates_but_not_table_columns() {
+        let cli = Cli::try_parse_from([
+            "pricr",
+            "--stats",
+            "--interval",
+            "1Y",
+            "--start-date",
+            "2025-01-01",
+            "btc",
+        ])
+        .unwrap();
+        assert!(cli.stats && !cli.chart);
+        assert!(Cli::try_parse_from(["pricr", "--chart", "--stats", "btc"]).is_ok());
+        assert!(Cli::try_parse_from(["pricr", "--start-date", "2025-01-01", "btc"]).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--stats", "--sparkline", "btc"]).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--stats", "--global"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "pricr",
+                "--correlation",
+                "--end-date",
+                "2025-06-01",
+                "btc",
+                "eth"
+            ])
+            .is_ok()
+        );
+        assert!(Cli::try_parse_from(["pricr", "--correlation", "--stats", "btc", "eth"]).is_err());
+    }
+
+    #[test]
+    fn primary_provider_follows_flag_then_configured_order() {
+        let mut app_config = config::AppConfig::default();
+        let cli = Cli::try_parse_from(["pricr", "btc"]).unwrap();
+        assert_eq!(primary_provider_id(&cli, &app_config), "coingecko");
+
+        app_config.defaults.provider_order = Some(vec![" ".into(), "CMC".into()]);
+        assert_eq!(primary_provider_id(&cli, &app_config), "cmc");
+
+        let cli = Cli::try_parse_from(["pricr", "--provider", "Yahoo", "btc"]).unwrap();
+        assert_eq!(primary_provider_id(&cli, &app_config), "yahoo");
+    }
+
+    #[test]
+    fn market_flag_accepts_known_suffixes_only() {
+        let cli = Cli::try_parse_from(["pricr", "--market", "DE", "sap"]).unwrap();
+        assert_eq!(cli.market.as_deref(), Some("de"));
+        let settings = resolve_provider_settings(&cli, &config::AppConfig::default());
+        assert_eq!(settings.default_market("stooq"), Some("de"));
+
+        let err = match Cli::try_parse_from(["pricr", "--market", "fr", "sap"]) {
+            Ok(_) => panic!("unknown market should be rejected"),
+            Err(err) => err.to_string(),
+        };
+        assert!(err.contains("unknown market 'fr'"), "{err}");
+        assert!(err.contains("us, uk, de, jp, pl, hk, hu"), "{err}");
+    }
+
+    #[test]
+    fn amount_flag_maps_positional_and_named_amounts() {
+        let cli = Cli::try_parse_from(["pricr", "--amount", "0.5", "--amount", "eth=3", "btc"])
+            .expect("--amount should parse");
+        assert_eq!(
+            cli.amount,
+            vec![
+                AmountArg::Positional(0.5),
+                AmountArg::Symbol("eth".into(), 3.0)
+            ]
+        );
+        assert!(Cli::try_parse_from(["pricr", "--amount", "btc=lots", "btc"]).is_err());
+
+        let symbols = vec!["btc".to_string(), "eth".to_string()];
+        let portfolio = std::collections::BTreeMap::from([("sol".to_string(), 10.0)]);
+        let args = [
+            AmountArg::Portfolio,
+            AmountArg::Positional(0.5),
+            AmountArg::Symbol("SOL".into(), 12.0),
+        ];
+        assert_eq!(
+            resolve_amounts(&args, &symbols, &portfolio).unwrap(),
+            vec![("sol".to_string(), 12.0), ("btc".to_string(), 0.5)]
+        );
+        let too_many = vec![AmountArg::Positional(1.0); 3];
+        assert!(resolve_amounts(&too_many, &symbols, &portfolio).is_err());
+        assert!(resolve_amounts(&[AmountArg::Portfolio], &symbols, &Default::default()).is_err());
+    }
+
+    #[test]
+    fn roi_takes_one_fiat_or_bare_amount() {
+        let cli = Cli::try_parse_from([
+            "pricr",
+            "--roi",
+            "--amount",
+            "1000usd",
+            "--start-date",
+            "2021-05-01",
+            "btc",
+        ])
+        .unwrap();
+        assert_eq!(cli.amount, vec![AmountArg::Fiat(1000.0, "USD".into())]);
+        assert_eq!(
+            resolve_roi_investment(&cli.amount, "eur").unwrap(),
+            (1000.0, "USD".to_string())
+        );
+        assert_eq!(
+            resolve_roi_investment(&[AmountArg::Positional(250.0)], "eur").unwrap(),
+            (250.0, "EUR".to_string())
+        );
+        assert!(resolve_roi_investment(&[AmountArg::Portfolio], "usd").is_err());
+        assert!(resolve_roi_investment(&[AmountArg::Positional(0.0)], "usd").is_err());
+        assert!(resolve_amounts(&cli.amount, &["btc".to_string()], &Default::default()).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--roi", "--amount", "1000usd", "btc"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "pricr",
+                "--roi",
+                "--amount",
+                "1",
+                "--start-date",
+                "2021-05-01",
+                "--end-date",
+                "2022-05-01",
+                "btc",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn pairs_group_by_currency_and_merge_in_input_order() {
+        let tokens: Vec<String> = ["btc/eur", "eth", "sol/EUR", "BRK/B", "doge/gbp"]
+            .iter()
+            .map(|token| token.to_string())
+            .collect();
+        let quotes = parse_quotes(&tokens, "usd");
+        assert_eq!(quotes[3].symbol, "BRK/B");
+        let groups = group_quotes(&quotes);
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    "EUR".to_string(),
+                    vec!["btc".to_string(), "sol".to_string()]
+                ),
+                (
+                    "usd".to_string(),
+                    vec!["eth".to_string(), "BRK/B".to_string()]
+                ),
+                ("GBP".to_string(), vec!["doge".to_string()]),
+            ]
+        );
+
+        let price = |symbol: &str, currency: &str| provider::CoinPrice {
+            symbol: symbol.to_string(),
+            currency: currency.to_string(),
+            ..Default::default()
+        };
+        let batches = vec![
+            (
+                "EUR".to_string(),
+                provider::PriceBatch {
+                    prices: vec![price("SOL", "EUR"), price("BTC", "EUR")],
+                    missing: Vec::new(),
+                },
+            ),
+            (
+                "usd".to_string(),
+                provider::PriceBatch {
+                    prices: vec![price("BRK/B", "USD"), price("ETH", "USD")],
+                    missing: Vec::new(),
+                },
+            ),
+            (
+                "GBP".to_string(),
+                provider::PriceBatch {
+                    prices: Vec::new(),
+                    missing: vec!["DOGE".to_string()],
+                },
+            ),
+        ];
+        let merged = merge_quote_batches(&quotes, batches);
+        let order: Vec<&str> = merged.prices.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(order, vec!["BTC", "ETH", "SOL", "BRK/B"]);
+        assert_eq!(merged.missing, vec!["DOGE/GBP"]);
+    }
+
+    #[test]
+    fn watch_combines_with_chart_but_not_end_date() {
+        let cli = Cli::try_parse_from(["pricr", "--chart", "--watch", "btc"]).unwrap();
+        assert!(cli.chart && cli.watch);
+        assert!(
+            Cli::try_parse_from([
+                "pricr",
+                "--chart",
+                "--watch",
+                "--end-date",
+                "2024-01-31",
+                "btc"
+            ])
+            .is_err()
+        );
+        assert!(ensure_watchable_flags(&cli, "chart mode").is_ok());
+        assert!(ensure_lookup_only_flags(&cli, "fiat chart mode").is_err());
+    }
+
+    #[test]
+    fn exchanges_flag_takes_one_symbol() {
+        let cli = Cli::try_parse_from(["pricr", "--exchanges", "btc", "--search-limit", "5"])
+            .expect("--exchanges should parse");
+        assert_eq!(cli.exchanges.as_deref(), Some("btc"));
+        assert!(Cli::try_parse_from(["pricr", "--exchanges", "btc", "eth"]).is_err());
+    }
+
+    #[test]
+    fn trending_flag_stands_alone() {
+        assert!(
+            Cli::try_parse_from(["pricr", "--trending"])
+                .unwrap()
+                .trending
+        );
+        assert!(Cli::try_parse_from(["pricr", "--trending", "-c", "eur", "--json"]).is_ok());
+        assert!(Cli::try_parse_from(["pricr", "--trending", "btc"]).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--trending", "--top", "5"]).is_err());
+    }
+
+    #[test]
+    fn top_flag_takes_a_count_without_symbols() {
+        let cli = Cli::try_parse_from(["pricr", "--top", "20", "--sort", "change"])
+            .expect("--top should parse");
+        assert_eq!(cli.top, Some(20));
+        assert!(Cli::try_parse_from(["pricr", "--top", "20", "btc"]).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--top", "0"]).is_err());
+        assert!(Cli::try_parse_from(["pricr", "--top", "5", "--chart"]).is_err());
+    }
+
+    #[test]
+    fn columns_flag_parses_known_names_and_rejects_unknown() {
+        let cli = Cli::try_parse_from(["pricr", "--columns", "symbol,volume,rank", "btc"])
+            .expect("known columns should parse");
+        assert_eq!(
+            cli.columns,
+            vec![ColumnArg::Symbol, ColumnArg::Volume, ColumnArg::Rank]
+        );
+
+        assert!(Cli::try_parse_from(["pricr", "--columns", "symbol,bogus", "btc"]).is_err());
+    }
+
+    #[test]
+    fn cache_command_parses_clear_filters() {
+        let cli = CacheCli::try_parse_from([
+            "cache",
+            "clear",
+            "--provider",
+            "coingecko",
+            "--older-than",
+            "1d",
+        ])
+        .unwrap();
+        match cli.action {
+            CacheAction::Clear {
+                provider,
+                older_than,
+            } => {
+                assert_eq!(provider.as_deref(), Some("coingecko"));
+                assert_eq!(older_than, Some(chrono::Duration::days(1)));
+            }
+            other => panic!("unexpected action {other:?}"),
+        }
+        assert!(CacheCli::try_parse_from(["cache", "clear", "--older-than", "1y"]).is_err());
+        assert!(CacheCli::try_parse_from(["cache", "ls", "--json"]).is_ok());
+        assert_eq!(parse_age("90s"), Ok(chrono::Duration::seconds(90)));
+        assert_eq!(parse_age("2w"), Ok(chrono::Duration::weeks(2)));
+        assert!(parse_age("d").is_err());
+    }
+
+    #[cfg(feature = "keyring")]
+    #[test]
+    fn config_command_parses_key_actions() {
+        let cli = ConfigCli::try_parse_from(["config", "show-key", "CMC", "--masked"]).unwrap();
+        match cli.action {
+            ConfigAction::Show { provider, masked } => {
+                assert_eq!(provider, "cmc");
+                assert!(masked);
+            }
+            other => panic!("unexpected action {other:?}"),
+        }
+        assert!(ConfigCli::try_parse_from(["config", "set-key", "coingecko"]).is_ok());
+        assert!(ConfigCli::try_parse_from(["config", "unset-key", "yahoo"]).is_err());
+        assert!(ConfigCli::try_parse_from(["config", "set-key"]).is_err());
+    }
+
+    #[test]
+    fn log_flags_override_logging_config() {
+        let dir = std::env::temp_dir().join(format!("pricr-logging-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("pricr.toml");
+        std::fs::write(
+            &config_path,
+            "[logging]\nformat = \"json\"\nfile = \"/var/log/pricr.log\"\n",
+        )
+        .unwrap();
+        let config_arg = config_path.to_str().unwrap();
+
+        let cli = Cli::try_parse_from(["pricr", "--config", config_arg, "btc"]).unwrap();
+        let logging = logging_settings(&cli);
+        assert_eq!(logging.format, Some(config::LogFormat::Json));
+        assert_eq!(logging.file, Some(PathBuf::from("/var/log/pricr.log")));
+
+        let cli = Cli::try_parse_from([
+            "pricr",
+            "--config",
+            config_arg,
+            "--log-format",
+            "text",
+            "--log-file",
+            "pricr.log",
+            "btc",
+        ])
+        .unwrap();
+        let logging = logging_settings(&cli);
+        assert_eq!(logging.format, Some(config::LogFormat::Text));
+        assert_eq!(logging.file, Some(PathBuf::from("pricr.log")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
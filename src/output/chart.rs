@@ -1,13 +1,85 @@
+use std::io::IsTerminal;
+
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::symbols;
 use ratatui::text::Line;
 use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Widget};
 
-use crate::provider::PriceHistory;
+use crate::calc::stats;
+use crate::config::{MIN_CHART_HEIGHT, MIN_CHART_WIDTH};
+use crate::output::{fmt, tz};
+use crate::provider::{PriceHistory, PricePoint};
+
+/// Glyph marking the window's highest point on a chart.
+pub const HIGH_MARKER: char = '▲';
+/// Glyph marking the window's lowest point on a chart.
+pub const LOW_MARKER: char = '▼';
+
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARKLINE_MAX_WIDTH: usize = 14;
+
+const PIPED_WIDTH: u16 = 80;
+const PIPED_HEIGHT: u16 = 20;
+const MAX_AUTO_HEIGHT: u16 = 32;
+/// Lines printed around each chart (title, high/low callout, stats, provider footer, spacing).
+const CHART_CHROME_LINES: u16 = 9;
+/// Longest window, in days, whose time axis is labeled with full dates rather than months.
+const DATE_LABEL_MAX_DAYS: i64 = 400;
+
+/// Chart dimensions in terminal cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartSize {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl ChartSize {
+    /// Resolve chart dimensions from explicit overrides, auto-detecting the rest.
+    ///
+    /// When stdout is not a terminal the fallback is a fixed 80x20 so piped
+    /// output stays stable across environments.
+    pub fn resolve(width: Option<u16>, height: Option<u16>) -> Self {
+        let terminal = if std::io::stdout().is_terminal() {
+            ratatui::crossterm::terminal::size().ok()
+        } else {
+            None
+        };
+        Self::from_parts(width, height, terminal)
+    }
+
+    fn from_parts(width: Option<u16>, height: Option<u16>, terminal: Option<(u16, u16)>) -> Self {
+        let (auto_width, auto_height) = match terminal {
+            Some((cols, rows)) if cols > 0 && rows > 0 => (
+                cols,
+                rows.saturating_sub(CHART_CHROME_LINES).min(MAX_AUTO_HEIGHT),
+            ),
+            _ => (PIPED_WIDTH, PIPED_HEIGHT),
+        };
+
+        Self {
+            width: width.unwrap_or(auto_width).max(MIN_CHART_WIDTH),
+            height: height.unwrap_or(auto_height).max(MIN_CHART_HEIGHT),
+        }
+    }
+}
+
+/// Hides the terminal cursor while a watched chart redraws and shows it again on drop,
+/// including when the watch ends with Ctrl-C or an error.
+pub struct HiddenCursor(());
+
+impl HiddenCursor {
+    pub fn hide() -> Self {
+        let _ = ratatui::crossterm::execute!(std::io::stdout(), ratatui::crossterm::cursor::Hide);
+        Self(())
+    }
+}
 
-const MIN_WIDTH: u16 = 48;
-const MIN_HEIGHT: u16 = 12;
+impl Drop for HiddenCursor {
+    fn drop(&mut self) {
+        let _ = ratatui::crossterm::execute!(std::io::stdout(), ratatui::crossterm::cursor::Show);
+    }
+}
 
 /// Render a static terminal chart for a coin price history series.
 pub fn render_history_chart(history: &PriceHistory, width: u16, height: u16) -> String {
@@ -15,7 +87,12 @@ pub fn render_history_chart(history: &PriceHistory, width: u16, height: u16) ->
         return String::new();
     }
 
-    let area = Rect::new(0, 0, width.max(MIN_WIDTH), height.max(MIN_HEIGHT));
+    let area = Rect::new(
+        0,
+        0,
+        width.max(MIN_CHART_WIDTH),
+        height.max(MIN_CHART_HEIGHT),
+    );
     let points: Vec<(f64, f64)> = history
         .points
         .iter()
@@ -26,15 +103,16 @@ pub fn render_history_chart(history: &PriceHistory, width: u16, height: u16) ->
     let x_max = points.len().saturating_sub(1) as f64;
     let (y_min, y_max) = y_bounds(&points);
 
+    let label_format = axis_label_format(&history.points);
     let first_label = history
         .points
         .first()
-        .map(|p| p.timestamp.format("%Y-%m-%d").to_string())
+        .map(|p| tz::format_timestamp(p.timestamp, label_format))
         .unwrap_or_default();
     let last_label = history
         .points
         .last()
-        .map(|p| p.timestamp.format("%Y-%m-%d").to_string())
+        .map(|p| tz::format_timestamp(p.timestamp, label_format))
         .unwrap_or_default();
 
     let dataset = Dataset::default()
@@ -43,7 +121,21 @@ pub fn render_history_chart(history: &PriceHistory, width: u16, height: u16) ->
         .marker(symbols::Marker::Dot)
         .data(&points);
 
-    let chart = Chart::new(vec![dataset])
+    let event_points = event_markers(history, &points);
+    let mut datasets = vec![dataset];
+    if !event_points.is_empty() {
+        datasets.push(
+            Dataset::default()
+                .graph_type(GraphType::Scatter)
+                .marker(symbols::Marker::Block)
+                .data(&event_points),
+        );
+    }
+
+    // The title already names the series, and the legend's corner is where the
+    // high of a rising series lands.
+    let chart = Chart::new(datasets)
+        .legend_position(None)
         .block(
             Block::default()
                 .title(format!("{} Price History", history.symbol))
@@ -57,20 +149,143 @@ pub fn render_history_chart(history: &PriceHistory, width: u16, height: u16) ->
         )
         .y_axis(
             Axis::default()
-                .title(Line::from(history.currency.clone()))
+                .title(Line::from(fmt::currency_axis_title(&history.currency)))
                 .bounds([y_min, y_max])
-                .labels(vec![
-                    Line::from(format_price_label(y_min)),
-                    Line::from(format_price_label(y_max)),
-                ]),
+                .labels(
+                    fmt::format_axis_labels(&[y_min, (y_min + y_max) / 2.0, y_max])
+                        .into_iter()
+                        .map(Line::from)
+                        .collect::<Vec<_>>(),
+                ),
         );
 
     let mut buffer = Buffer::empty(area);
     chart.render(area, &mut buffer);
+    if let Some((high, low)) = stats::extremes(&history.points) {
+        let bounds = ([0.0, x_max.max(1.0)], [y_min, y_max]);
+        for (extreme, marker) in [(low, LOW_MARKER), (high, HIGH_MARKER)] {
+            let data = (extreme.index as f64, extreme.value);
+            if let Some((x, y)) = data_cell(&buffer, area, bounds, data) {
+                buffer[(x, y)].set_char(marker);
+            }
+        }
+    }
     buffer_to_string(&buffer, area)
 }
 
-fn y_bounds(points: &[(f64, f64)]) -> (f64, f64) {
+/// Cell a data point was plotted in, found from where the chart drew its axes.
+///
+/// The plot sits right of the y axis and above the x axis, whose corner `└` is
+/// the only one inside the block; points map onto it the way ratatui's canvas
+/// maps them for [`symbols::Marker::Dot`].
+fn data_cell(
+    buffer: &Buffer,
+    area: Rect,
+    (x_bounds, y_bounds): ([f64; 2], [f64; 2]),
+    (x, y): (f64, f64),
+) -> Option<(u16, u16)> {
+    let inner = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(2),
+    );
+    let (corner_x, corner_y) = (inner.top()..inner.bottom())
+        .flat_map(|row| (inner.left()..inner.right()).map(move |col| (col, row)))
+        .find(|&cell| buffer[cell].symbol() == symbols::line::BOTTOM_LEFT)?;
+
+    let left = corner_x + 1;
+    let width = inner.right().checked_sub(left).filter(|&w| w > 0)?;
+    let height = corner_y.checked_sub(inner.top()).filter(|&h| h > 0)?;
+    let col = (x - x_bounds[0]) * f64::from(width - 1) / (x_bounds[1] - x_bounds[0]);
+    let row = (y_bounds[1] - y) * f64::from(height - 1) / (y_bounds[1] - y_bounds[0]);
+    Some((left + col as u16, inner.top() + row as u16))
+}
+
+/// Render a compact unicode sparkline for a price series.
+///
+/// Long series are downsampled to at most 14 cells; flat series render at mid height.
+pub fn render_sparkline(points: &[PricePoint]) -> String {
+    let prices: Vec<f64> = points
+        .iter()
+        .map(|p| p.price)
+        .filter(|price| price.is_finite())
+        .collect();
+    if prices.is_empty() {
+        return String::new();
+    }
+
+    let sampled: Vec<f64> = if prices.len() > SPARKLINE_MAX_WIDTH {
+        let step = (prices.len() - 1) as f64 / (SPARKLINE_MAX_WIDTH - 1) as f64;
+        (0..SPARKLINE_MAX_WIDTH)
+            .map(|i| prices[((i as f64) * step).round() as usize])
+            .collect()
+    } else {
+        prices
+    };
+
+    let min = sampled.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = sampled.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let span = max - min;
+    let top = SPARKLINE_LEVELS.len() - 1;
+
+    sampled
+        .iter()
+        .map(|price| {
+            if span <= f64::EPSILON {
+                return SPARKLINE_LEVELS[top / 2];
+            }
+            let level = (((price - min) / span) * top as f64).round() as usize;
+            SPARKLINE_LEVELS[level.min(top)]
+        })
+        .collect()
+}
+
+/// Time-axis label format for the window `points` cover: times of day for a window of
+/// a day or less, date and time for longer intraday windows, dates up to about a year,
+/// and months beyond that or for monthly series.
+pub fn axis_label_format(points: &[PricePoint]) -> &'static str {
+    let gaps = || {
+        points
+            .windows(2)
+            .map(|pair| pair[1].timestamp - pair[0].timestamp)
+    };
+    let window = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => last.timestamp - first.timestamp,
+        _ => chrono::Duration::zero(),
+    };
+    if gaps().any(|gap| gap < chrono::Duration::days(1)) {
+        if window <= chrono::Duration::days(1) {
+            "%H:%M"
+        } else {
+            "%m-%d %H:%M"
+        }
+    } else if window > chrono::Duration::days(DATE_LABEL_MAX_DAYS)
+        || (points.len() > 1 && gaps().all(|gap| gap >= chrono::Duration::days(28)))
+    {
+        "%Y-%m"
+    } else {
+        "%Y-%m-%d"
+    }
+}
+
+/// Place each history event on the first plotted point at or after it.
+fn event_markers(history: &PriceHistory, points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    history
+        .events
+        .iter()
+        .filter_map(|event| {
+            let idx = history
+                .points
+                .iter()
+                .position(|p| p.timestamp >= event.timestamp)?;
+            points.get(idx).copied()
+        })
+        .collect()
+}
+
+/// Price axis bounds: the data range padded by 8%, or widened around a flat series.
+pub fn y_bounds(points: &[(f64, f64)]) -> (f64, f64) {
     let min = points.iter().map(|(_, y)| *y).fold(f64::INFINITY, f64::min);
     let max = points
         .iter()
@@ -91,16 +306,6 @@ fn y_bounds(points: &[(f64, f64)]) -> (f64, f64) {
     }
 }
 
-fn format_price_label(value: f64) -> String {
-    if value.abs() >= 1_000.0 {
-        format!("{value:.0}")
-    } else if value.abs() >= 1.0 {
-        format!("{value:.2}")
-    } else {
-        format!("{value:.4}")
-    }
-}
-
 fn buffer_to_string(buffer: &Buffer, area: Rect) -> String {
     let mut lines = Vec::with_capacity(area.height as usize);
     for y in area.y..area.y + area.height {
@@ -136,13 +341,17 @@ mod tests {
                     timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(1_700_000_000, 0)
                         .expect("valid timestamp"),
                     price: 40000.0,
+                    filled: false,
                 },
                 PricePoint {
                     timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(1_700_086_400, 0)
                         .expect("valid timestamp"),
                     price: 42000.0,
+                    filled: false,
                 },
             ],
+            events: Vec::new(),
+            granularity: None,
         };
 
         let rendered = render_history_chart(&history, 60, 14);
@@ -150,4 +359,298 @@ mod tests {
         assert!(rendered.lines().count() >= 10);
         assert!(rendered.contains("BTC Price History"));
     }
+
+    #[test]
+    fn render_history_chart_respects_requested_width() {
+        let history = PriceHistory {
+            symbol: "ETH".to_string(),
+            name: "Ethereum".to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            points: (0..24)
+                .map(|i| PricePoint {
+                    timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                        1_700_000_000 + i * 3_600,
+                        0,
+                    )
+                    .expect("valid timestamp"),
+                    price: 3_000.0 + (i as f64) * 5.0,
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        };
+
+        let rendered = render_history_chart(&history, 64, 16);
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines.len(), 16);
+        assert_eq!(lines[0].chars().count(), 64);
+        assert!(lines.iter().all(|line| line.chars().count() <= 64));
+    }
+
+    #[test]
+    fn render_sparkline_maps_prices_to_levels() {
+        let points: Vec<PricePoint> = [1.0, 2.0, 3.0, 4.0, 5.0]
+            .iter()
+            .enumerate()
+            .map(|(i, price)| PricePoint {
+                timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                    1_700_000_000 + (i as i64) * 86_400,
+                    0,
+                )
+                .expect("valid timestamp"),
+                price: *price,
+                filled: false,
+            })
+            .collect();
+
+        let spark = render_sparkline(&points);
+        assert_eq!(spark.chars().count(), 5);
+        assert!(spark.starts_with('▁'));
+        assert!(spark.ends_with('█'));
+    }
+
+    #[test]
+    fn render_sparkline_downsamples_and_handles_flat_series() {
+        let points: Vec<PricePoint> = (0..100)
+            .map(|i| PricePoint {
+                timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                    1_700_000_000 + i * 3_600,
+                    0,
+                )
+                .expect("valid timestamp"),
+                price: 10.0,
+                filled: false,
+            })
+            .collect();
+
+        let spark = render_sparkline(&points);
+        assert_eq!(spark.chars().count(), SPARKLINE_MAX_WIDTH);
+        assert!(spark.chars().all(|c| c == '▄'));
+        assert!(render_sparkline(&[]).is_empty());
+    }
+
+    #[test]
+    fn chart_size_falls_back_to_fixed_size_when_not_a_terminal() {
+        let size = ChartSize::from_parts(None, None, None);
+        assert_eq!(
+            size,
+            ChartSize {
+                width: 80,
+                height: 20
+            }
+        );
+    }
+
+    #[test]
+    fn chart_size_uses_terminal_and_overrides() {
+        let size = ChartSize::from_parts(None, None, Some((140, 50)));
+        assert_eq!(size.width, 140);
+        assert_eq!(size.height, MAX_AUTO_HEIGHT);
+
+        let size = ChartSize::from_parts(Some(100), Some(24), Some((140, 50)));
+        assert_eq!(
+            size,
+            ChartSize {
+                width: 100,
+                height: 24
+            }
+        );
+    }
+
+    #[test]
+    fn chart_size_enforces_minimums() {
+        let size = ChartSize::from_parts(Some(10), Some(2), Some((30, 10)));
+        assert_eq!(
+            size,
+            ChartSize {
+                width: MIN_CHART_WIDTH,
+                height: MIN_CHART_HEIGHT
+            }
+        );
+    }
+
+    #[test]
+    fn axis_label_granularity_follows_the_window() {
+        let series = |step: i64, count: i64| -> Vec<PricePoint> {
+            (0..count)
+                .map(|i| PricePoint {
+                    timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                        1_700_000_000 + i * step,
+                        0,
+                    )
+                    .expect("valid timestamp"),
+                    price: 1.0,
+                    filled: false,
+                })
+                .collect()
+        };
+
+        assert_eq!(axis_label_format(&series(3_600, 25)), "%H:%M");
+        assert_eq!(axis_label_format(&series(3_600, 5 * 24)), "%m-%d %H:%M");
+        assert_eq!(axis_label_format(&series(86_400, 30)), "%Y-%m-%d");
+        assert_eq!(axis_label_format(&series(86_400, 365)), "%Y-%m-%d");
+        assert_eq!(axis_label_format(&series(86_400, 5 * 365)), "%Y-%m");
+        assert_eq!(axis_label_format(&series(30 * 86_400, 6)), "%Y-%m");
+    }
+
+    /// Rendered y-axis labels and title for a flat-ish series around `base`.
+    fn y_axis_text(base: f64, currency: &str) -> String {
+        let history = PriceHistory {
+            symbol: "X".to_string(),
+            name: "X".to_string(),
+            currency: currency.to_string(),
+            provider: "CoinGecko".to_string(),
+            points: (0..10)
+                .map(|i| PricePoint {
+                    timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                        1_700_000_000 + i * 86_400,
+                        0,
+                    )
+                    .expect("valid timestamp"),
+                    price: base * (1.0 + i as f64 / 100.0),
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        };
+        render_history_chart(&history, 60, 14)
+    }
+
+    #[test]
+    fn y_axis_snapshot_for_a_large_price() {
+        assert_eq!(
+            y_axis_text(67_231.55, "usd"),
+            [
+                "┌X Price History───────────────────────────────────────────┐",
+                "│73.8k    │USD ($)                                      ••▲│",
+                "│         │                                       ••••••   │",
+                "│         │                                  •••••         │",
+                "│         │                             •••••              │",
+                "│         │                  •••••••••••                   │",
+                "│70.3k    │             •••••                              │",
+                "│         │        •••••                                   │",
+                "│         │   •••••                                        │",
+                "│         │▼••                                             │",
+                "│66.7k    │                                            Time│",
+                "│         └────────────────────────────────────────────────│",
+                "│2023-11-14                                      2023-11-23│",
+                "└──────────────────────────────────────────────────────────┘",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn y_axis_labels_are_compact_at_every_magnitude() {
+        let cases = [
+            (1_050_000.0, "eur", ["1.04M", "1.10M", "1.15M"]),
+            (150.0, "gbp", ["149", "157", "165"]),
+            (0.00001234, "usd", ["0.0000123", "0.0000129", "0.0000135"]),
+        ];
+        for (base, currency, labels) in cases {
+            let rendered = y_axis_text(base, currency);
+            let axis: Vec<&str> = rendered
+                .lines()
+                .filter_map(|line| line.strip_prefix('│')?.split('│').next())
+                .map(str::trim)
+                .filter(|label| !label.is_empty())
+                .collect();
+            let mut expected = labels.to_vec();
+            expected.reverse();
+            assert_eq!(axis[..3], expected[..], "{rendered}");
+            let title = fmt::currency_axis_title(currency);
+            assert_eq!(rendered.matches(title.as_str()).count(), 1, "{rendered}");
+        }
+    }
+
+    #[test]
+    fn render_history_chart_marks_first_high_and_low() {
+        let prices = [100.0, 120.0, 90.0, 120.0, 110.0, 90.0, 105.0];
+        let history = PriceHistory {
+            symbol: "X".to_string(),
+            name: "X".to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            points: prices
+                .iter()
+                .enumerate()
+                .map(|(i, &price)| PricePoint {
+                    timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                        1_700_000_000 + i as i64 * 86_400,
+                        0,
+                    )
+                    .expect("valid timestamp"),
+                    price,
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        };
+
+        let rendered = render_history_chart(&history, 60, 14);
+        let find = |marker: char| -> Vec<(usize, usize)> {
+            rendered
+                .lines()
+                .enumerate()
+                .flat_map(|(row, line)| {
+                    line.chars()
+                        .enumerate()
+                        .filter(move |&(_, c)| c == marker)
+                        .map(move |(col, _)| (col, row))
+                })
+                .collect()
+        };
+        let (high, low) = (find(HIGH_MARKER), find(LOW_MARKER));
+        assert_eq!(high.len(), 1, "{rendered}");
+        assert_eq!(low.len(), 1, "{rendered}");
+
+        // The plot spans columns 11..=58 and rows 1..=10 of the y range 87.6..=122.4,
+        // so the first 120 (x = 1 of 6) and the first 90 (x = 2 of 6) land here.
+        assert_eq!(high[0], (18, 1), "{rendered}");
+        assert_eq!(low[0], (26, 9), "{rendered}");
+    }
+
+    #[test]
+    fn render_history_chart_marks_events() {
+        let points: Vec<PricePoint> = (0..10)
+            .map(|i| PricePoint {
+                timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(
+                    1_700_000_000 + i * 86_400,
+                    0,
+                )
+                .expect("valid timestamp"),
+                price: 100.0 + i as f64,
+                filled: false,
+            })
+            .collect();
+        let mut history = PriceHistory {
+            symbol: "AAPL".to_string(),
+            name: "Apple Inc.".to_string(),
+            currency: "USD".to_string(),
+            provider: "Yahoo Finance".to_string(),
+            points,
+            events: Vec::new(),
+            granularity: None,
+        };
+        assert!(!render_history_chart(&history, 60, 14).contains('█'));
+
+        history.events.push(crate::provider::HistoryEvent {
+            timestamp: history.points[4].timestamp - chrono::Duration::hours(6),
+            kind: crate::provider::HistoryEventKind::Dividend,
+            value: 0.24,
+        });
+        let plotted: Vec<(f64, f64)> = history
+            .points
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| (idx as f64, p.price))
+            .collect();
+        assert_eq!(event_markers(&history, &plotted), vec![(4.0, 104.0)]);
+        assert!(render_history_chart(&history, 60, 14).contains('█'));
+    }
 }
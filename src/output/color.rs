@@ -0,0 +1,156 @@
+use std::io::IsTerminal;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use colored::{ColoredString, Colorize};
+
+static HIGHLIGHT_CHANGE_PCT: RwLock<Option<f64>> = RwLock::new(None);
+static STALE_AFTER_SECS: AtomicU64 = AtomicU64::new(DEFAULT_STALE_AFTER_SECS);
+
+/// Quote age past which `--age` turns yellow, unless `[display].stale_after_secs` is set.
+pub const DEFAULT_STALE_AFTER_SECS: u64 = 15 * 60;
+
+/// When to emit ANSI colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color only when stdout is a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Decide whether to color output for `mode`.
+///
+/// `NO_COLOR` only affects `Auto`; an explicit `--color always` still wins.
+pub fn should_colorize(mode: ColorMode, no_color: bool, stdout_is_tty: bool) -> bool {
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => !no_color && stdout_is_tty,
+    }
+}
+
+/// Apply `mode` to all colored output of this process, checking `NO_COLOR` and stdout.
+pub fn apply(mode: ColorMode) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    colored::control::set_override(should_colorize(
+        mode,
+        no_color,
+        std::io::stdout().is_terminal(),
+    ));
+}
+
+/// Bold percent changes whose magnitude exceeds `threshold`; `None` disables highlighting.
+pub fn set_highlight_threshold(threshold: Option<f64>) {
+    *HIGHLIGHT_CHANGE_PCT
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = threshold;
+}
+
+/// Color `text` for a percent `change`: green when positive, red when negative, and bold
+/// past the highlight threshold.
+pub fn paint_change(text: String, change: f64) -> ColoredString {
+    let threshold = *HIGHLIGHT_CHANGE_PCT
+        .read()
+        .unwrap_or_else(|e| e.into_inner());
+    paint_change_with(text, change, threshold)
+}
+
+fn paint_change_with(text: String, change: f64, threshold: Option<f64>) -> ColoredString {
+    let painted = if change >= 0.0 {
+        text.green()
+    } else {
+        text.red()
+    };
+    match threshold {
+        Some(threshold) if change.abs() > threshold => painted.bold(),
+        _ => painted,
+    }
+}
+
+/// Paint quote ages yellow once they exceed `secs`.
+pub fn set_stale_after(secs: u64) {
+    STALE_AFTER_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// Color `text` for a quote `age`: yellow past the staleness threshold, plain otherwise.
+pub fn paint_age(text: String, age: chrono::Duration) -> ColoredString {
+    paint_age_with(text, age, STALE_AFTER_SECS.load(Ordering::Relaxed))
+}
+
+fn paint_age_with(text: String, age: chrono::Duration, stale_after_secs: u64) -> ColoredString {
+    if age.num_seconds() > i64::try_from(stale_after_secs).unwrap_or(i64::MAX) {
+        text.yellow()
+    } else {
+        text.normal()
+    }
+}
+
+/// Correlation from which a matrix cell turns green.
+pub const HIGH_CORRELATION: f64 = 0.5;
+
+/// Color `text` for a `correlation`: green from `HIGH_CORRELATION` up, red below zero.
+pub fn paint_correlation(text: String, correlation: f64) -> ColoredString {
+    if correlation >= HIGH_CORRELATION {
+        text.green()
+    } else if correlation < 0.0 {
+        text.red()
+    } else {
+        text.normal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_mode_follows_no_color_and_tty() {
+        assert!(should_colorize(ColorMode::Auto, false, true));
+        assert!(!should_colorize(ColorMode::Auto, true, true));
+        assert!(!should_colorize(ColorMode::Auto, false, false));
+        assert!(should_colorize(ColorMode::Always, true, false));
+        assert!(!should_colorize(ColorMode::Never, false, true));
+    }
+
+    #[test]
+    fn paint_change_emits_ansi_only_when_enabled() {
+        colored::control::set_override(true);
+        let up = paint_change_with("+1.00%".into(), 1.0, Some(5.0)).to_string();
+        let big_down = paint_change_with("-7.00%".into(), -7.0, Some(5.0)).to_string();
+        colored::control::set_override(false);
+        let plain = paint_change_with("-7.00%".into(), -7.0, Some(5.0)).to_string();
+        colored::control::unset_override();
+
+        assert!(up.contains("\x1b[32m"), "{up:?}");
+        assert!(!up.contains("\x1b[1"), "{up:?}");
+        assert!(big_down.contains("31m"), "{big_down:?}");
+        assert!(big_down.contains("\x1b[1;"), "{big_down:?}");
+        assert_eq!(plain, "-7.00%");
+    }
+
+    #[test]
+    fn paint_age_turns_yellow_past_threshold() {
+        colored::control::set_override(true);
+        let fresh = paint_age_with("2m ago".into(), chrono::Duration::minutes(2), 900).to_string();
+        let stale = paint_age_with("3h ago".into(), chrono::Duration::hours(3), 900).to_string();
+        colored::control::unset_override();
+
+        assert_eq!(fresh, "2m ago");
+        assert!(stale.contains("\x1b[33m"), "{stale:?}");
+    }
+
+    #[test]
+    fn paint_correlation_marks_high_and_negative_values() {
+        colored::control::set_override(true);
+        let high = paint_correlation("0.82".into(), 0.82).to_string();
+        let weak = paint_correlation("0.10".into(), 0.10).to_string();
+        let negative = paint_correlation("-0.40".into(), -0.40).to_string();
+        colored::control::unset_override();
+
+        assert!(high.contains("\x1b[32m"), "{high:?}");
+        assert_eq!(weak, "0.10");
+        assert!(negative.contains("\x1b[31m"), "{negative:?}");
+    }
+}
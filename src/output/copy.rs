@@ -0,0 +1,125 @@
+//! Clipboard text for `--copy`.
+//!
+//! `--copy-format` uses the `{placeholder}` syntax of `--template`. Price lookups
+//! accept the same placeholders as the status-bar output; conversions take their
+//! `--json` field names.
+
+use crate::calc::Conversion;
+use crate::output::status;
+use crate::provider::CoinPrice;
+
+/// What a price lookup copies without `--copy-format`: the bare price.
+pub const DEFAULT_PRICE_FORMAT: &str = "{price_raw}";
+/// What a conversion copies without `--copy-format`: the converted amount.
+pub const DEFAULT_CONVERSION_FORMAT: &str = "{to_amount}";
+
+/// One line per price, or `None` when there is nothing to copy.
+pub fn price_text(prices: &[CoinPrice], format: Option<&str>) -> Option<String> {
+    let format = format.unwrap_or(DEFAULT_PRICE_FORMAT);
+    lines(
+        prices
+            .iter()
+            .map(|price| status::render_template(format, price)),
+    )
+}
+
+/// One line per conversion, or `None` when there is nothing to copy.
+///
+/// Placeholders: `from_amount`, `from_currency`, `to_amount`, `to_symbol`,
+/// `to_name`, `rate`, `provider`.
+pub fn conversion_text(conversions: &[Conversion], format: Option<&str>) -> Option<String> {
+    let format = format.unwrap_or(DEFAULT_CONVERSION_FORMAT);
+    lines(conversions.iter().map(|conversion| {
+        status::render_placeholders(format, |key| conversion_value(key, conversion))
+    }))
+}
+
+fn conversion_value(key: &str, conversion: &Conversion) -> Option<String> {
+    let value = match key {
+        "from_amount" => conversion.from_amount.to_string(),
+        "from_currency" => conversion.from_currency.clone(),
+        "to_amount" => conversion.to_amount.to_string(),
+        "to_symbol" => conversion.to_symbol.clone(),
+        "to_name" => conversion.to_name.clone(),
+        "rate" => conversion.rate.to_string(),
+        "provider" => conversion.provider.clone(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn lines(lines: impl Iterator<Item = String>) -> Option<String> {
+    let lines: Vec<String> = lines.collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Put `text` on the system clipboard.
+#[cfg(feature = "clipboard")]
+pub fn set_clipboard(text: &str) -> std::result::Result<(), arboard::Error> {
+    arboard::Clipboard::new()?.set_text(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc() -> CoinPrice {
+        CoinPrice {
+            symbol: "BTC".into(),
+            name: "Bitcoin".into(),
+            price: 67_234.5,
+            change_24h: Some(1.5),
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            ..Default::default()
+        }
+    }
+
+    fn conversion() -> Conversion {
+        Conversion {
+            from_amount: 100.0,
+            from_currency: "USD".into(),
+            to_symbol: "BTC".into(),
+            to_name: "Bitcoin".into(),
+            to_amount: 0.0015,
+            rate: 66_000.0,
+            provider: "CoinGecko".into(),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn prices_copy_the_bare_price_by_default() {
+        assert_eq!(price_text(&[btc()], None).as_deref(), Some("67234.5"));
+        assert_eq!(
+            price_text(&[btc()], Some("{symbol}: {price} ({change_24h})")).as_deref(),
+            Some("BTC: $67.2k (+1.50%)")
+        );
+        let eth = CoinPrice {
+            symbol: "ETH".into(),
+            price: 3_456.78,
+            ..btc()
+        };
+        assert_eq!(
+            price_text(&[btc(), eth], Some("{symbol} {price_raw}")).as_deref(),
+            Some("BTC 67234.5\nETH 3456.78")
+        );
+        assert_eq!(price_text(&[], None), None);
+    }
+
+    #[test]
+    fn conversions_copy_the_converted_amount_by_default() {
+        assert_eq!(
+            conversion_text(&[conversion()], None).as_deref(),
+            Some("0.0015")
+        );
+        assert_eq!(
+            conversion_text(
+                &[conversion()],
+                Some("{from_amount} {from_currency} = {to_amount} {to_symbol} {unknown}")
+            )
+            .as_deref(),
+            Some("100 USD = 0.0015 BTC {unknown}")
+        );
+    }
+}
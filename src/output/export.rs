@@ -0,0 +1,329 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use crate::calc::stats;
+use crate::error::{Error, Result};
+use crate::output::fmt::{self, format_price};
+use crate::output::svg::{self, escape_xml};
+use crate::output::{json, table, tz};
+use crate::provider::PriceHistory;
+
+/// On-disk format for `--export`, picked from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `symbol,timestamp,price` rows, one per point.
+    Csv,
+    /// The series array `--chart --json` prints, with per-series stats.
+    Json,
+}
+
+impl ExportFormat {
+    /// Infer the format from `path`'s extension (case-insensitive).
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match ext.as_deref() {
+            Some("csv") => Ok(Self::Csv),
+            Some("json") => Ok(Self::Json),
+            Some("parquet") => Err(Error::Config(
+                "Parquet export is not supported in this build -- use a .csv or .json file".into(),
+            )),
+            _ => Err(Error::Config(format!(
+                "cannot infer export format from '{}' -- use a .csv or .json extension",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// Write `histories` to `path` in the format implied by its extension.
+///
+/// An existing file is only replaced when `force` is set.
+pub fn write_history(path: &Path, histories: &[PriceHistory], force: bool) -> Result<()> {
+    let contents = match ExportFormat::from_path(path)? {
+        ExportFormat::Csv => history_csv(histories),
+        ExportFormat::Json => {
+            let mut json = serde_json::to_string_pretty(&json::histories_with_stats(histories))
+                .map_err(|e| Error::Parse(format!("JSON serialize: {}", e)))?;
+            json.push('\n');
+            json
+        }
+    };
+    write_new(path, &contents, force)
+}
+
+/// Write a standalone HTML report for `--export-html`: one SVG chart and stats block
+/// per series, with the provider, range, currency, and generation time.
+///
+/// An existing file is only replaced when `force` is set.
+pub fn write_html(
+    path: &Path,
+    histories: &[PriceHistory],
+    range_label: &str,
+    force: bool,
+) -> Result<()> {
+    write_new(path, &history_html(histories, range_label), force)
+}
+
+fn write_new(path: &Path, contents: &str, force: bool) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    let mut file = options.open(path).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+            Error::Config(format!(
+                "{} already exists -- pass --force to overwrite it",
+                path.display()
+            ))
+        } else {
+            Error::Config(format!("cannot write {}: {}", path.display(), e))
+        }
+    })?;
+    file.write_all(contents.as_bytes())
+        .map_err(|e| Error::Config(format!("cannot write {}: {}", path.display(), e)))
+}
+
+/// Render the `--export-html` document; series without points are left out.
+pub fn history_html(histories: &[PriceHistory], range_label: &str) -> String {
+    let generated_at = tz::format_timestamp(chrono::Utc::now(), "%Y-%m-%d %H:%M:%S %Z");
+    let mut html = format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>pricr chart {range}</title>\n\
+         <style>\n\
+         body {{ font-family: system-ui, sans-serif; margin: 2rem; color: #222; }}\n\
+         section {{ margin-bottom: 2.5rem; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ padding: 0.2rem 0.8rem; text-align: left; }}\n\
+         td {{ text-align: right; font-variant-numeric: tabular-nums; }}\n\
+         .meta {{ color: #666; }}\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <h1>Price history</h1>\n\
+         <p class=\"meta\">Range {range} &middot; generated {generated}</p>\n",
+        range = escape_xml(range_label),
+        generated = escape_xml(&generated_at),
+    );
+    for history in histories {
+        let Some(summary) = stats::summarize(&history.points) else {
+            continue;
+        };
+        let currency = &history.currency;
+        let percent_or_na = |value: Option<f64>| {
+            value
+                .map(|v| format!("{}%", fmt::format_fixed(v, 2)))
+                .unwrap_or_else(|| "n/a".to_string())
+        };
+        let rows = [
+            ("Start", format_price(summary.start, currency)),
+            ("End", format_price(summary.end, currency)),
+            (
+                "Change",
+                summary
+                    .change_pct
+                    .map(table::format_percent)
+                    .unwrap_or_else(|| "n/a".to_string()),
+            ),
+            ("Low", format_price(summary.min, currency)),
+            ("High", format_price(summary.max, currency)),
+            ("Mean", format_price(summary.mean, currency)),
+            ("Max drawdown", percent_or_na(summary.max_drawdown_pct)),
+            (
+                "Volatility (annualized)",
+                percent_or_na(summary.volatility_pct),
+            ),
+        ];
+        html.push_str(&format!(
+            "<section>\n<h2>{} &middot; {}</h2>\n\
+             <p class=\"meta\">{} &middot; {} &middot; {} points</p>\n",
+            escape_xml(&history.symbol),
+            escape_xml(&history.name),
+            escape_xml(&history.provider),
+            escape_xml(currency),
+            history.points.len()
+        ));
+        html.push_str(&svg::render_history_svg(history));
+        html.push_str("<table>\n");
+        for (label, value) in rows {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                label,
+                escape_xml(&value)
+            ));
+        }
+        html.push_str("</table>\n</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render histories as CSV with a header row and RFC 3339 UTC timestamps.
+pub fn history_csv(histories: &[PriceHistory]) -> String {
+    let mut out = String::from("symbol,timestamp,price\n");
+    for history in histories {
+        let symbol = csv_field(&history.symbol);
+        for point in &history.points {
+            out.push_str(&format!(
+                "{},{},{}\n",
+                symbol,
+                point
+                    .timestamp
+                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                point.price
+            ));
+        }
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::PricePoint;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn sample() -> Vec<PriceHistory> {
+        let point = |day: u32, price: f64| PricePoint {
+            timestamp: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+            price,
+            filled: false,
+        };
+        vec![
+            PriceHistory {
+                symbol: "BTC".into(),
+                name: "Bitcoin".into(),
+                currency: "USD".into(),
+                provider: "CoinGecko".into(),
+                points: vec![point(1, 61234.5), point(2, 62000.0)],
+                events: Vec::new(),
+                granularity: None,
+            },
+            PriceHistory {
+                symbol: "ETH".into(),
+                name: "Ethereum".into(),
+                currency: "USD".into(),
+                provider: "CoinGecko".into(),
+                points: vec![point(1, 3401.25)],
+                events: Vec::new(),
+                granularity: None,
+            },
+        ]
+    }
+
+    fn read_csv(text: &str) -> Vec<(String, DateTime<Utc>, f64)> {
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("symbol,timestamp,price"));
+        lines
+            .map(|line| {
+                let fields: Vec<&str> = line.split(',').collect();
+                assert_eq!(fields.len(), 3, "unexpected row: {}", line);
+                (
+                    fields[0].to_string(),
+                    DateTime::parse_from_rfc3339(fields[1])
+                        .unwrap()
+                        .with_timezone(&Utc),
+                    fields[2].parse().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("pricr-export-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn csv_round_trips_all_points() {
+        let histories = sample();
+        let path = temp_path("roundtrip.csv");
+        let _ = std::fs::remove_file(&path);
+
+        write_history(&path, &histories, false).unwrap();
+        let rows = read_csv(&std::fs::read_to_string(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let expected: Vec<_> = histories
+            .iter()
+            .flat_map(|h| {
+                h.points
+                    .iter()
+                    .map(|p| (h.symbol.clone(), p.timestamp, p.price))
+            })
+            .collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn existing_file_requires_force() {
+        let path = temp_path("force.json");
+        std::fs::write(&path, "old").unwrap();
+
+        let err = write_history(&path, &sample(), false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+
+        write_history(&path, &sample(), true).unwrap();
+        let parsed: Vec<PriceHistory> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].points.len(), 2);
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.CSV")).unwrap(),
+            ExportFormat::Csv
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("dir/out.json")).unwrap(),
+            ExportFormat::Json
+        );
+        assert!(ExportFormat::from_path(Path::new("out.parquet")).is_err());
+        assert!(ExportFormat::from_path(Path::new("out")).is_err());
+    }
+
+    #[test]
+    fn html_report_has_one_chart_per_series_and_respects_force() {
+        let path = temp_path("report.html");
+        std::fs::write(&path, "old").unwrap();
+        assert!(write_html(&path, &sample(), "2024-03-01..2024-03-02", false).is_err());
+
+        write_html(&path, &sample(), "2024-03-01..2024-03-02", true).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script"));
+        assert!(html.contains("Range 2024-03-01..2024-03-02"));
+        assert_eq!(html.matches("<svg ").count(), 2);
+        assert!(html.contains("<h2>BTC &middot; Bitcoin</h2>"));
+        assert!(html.contains("<tr><th>Change</th><td>+1.25%</td></tr>"));
+        assert!(html.contains("CoinGecko &middot; USD &middot; 2 points"));
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_commas() {
+        assert_eq!(csv_field("BRK,B"), "\"BRK,B\"");
+        assert_eq!(csv_field("BTC"), "BTC");
+    }
+}
@@ -0,0 +1,533 @@
+use std::sync::RwLock;
+
+use serde::Deserialize;
+
+/// Largest `--precision` accepted; an f64 carries about 17 significant digits.
+pub const MAX_PRECISION: usize = 18;
+/// Significant digits kept for values below 0.01, so tiny-cap prices don't print as zero.
+const SMALL_VALUE_SIGNIFICANT_DIGITS: i32 = 10;
+/// Significant digits chart axis labels keep at least.
+const AXIS_SIGNIFICANT_DIGITS: i32 = 3;
+
+static STYLE: RwLock<NumberStyle> = RwLock::new(NumberStyle::DEFAULT);
+
+/// Number conventions for table output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// `$1,234.56`
+    #[default]
+    En,
+    /// `1.234,56 €`
+    De,
+    /// `1 234,56 €` with a narrow no-break space between groups.
+    Fr,
+}
+
+impl Locale {
+    fn group_separator(self) -> &'static str {
+        match self {
+            Self::En => ",",
+            Self::De => ".",
+            Self::Fr => "\u{202f}",
+        }
+    }
+
+    fn decimal_separator(self) -> char {
+        match self {
+            Self::En => '.',
+            Self::De | Self::Fr => ',',
+        }
+    }
+
+    /// Whether amounts use `,` as the decimal separator.
+    pub fn decimal_comma(self) -> bool {
+        self.decimal_separator() == ','
+    }
+
+    /// Whether the currency symbol follows the amount.
+    fn symbol_after(self) -> bool {
+        !matches!(self, Self::En)
+    }
+}
+
+/// How prices and amounts are rendered in tables, status text, and chart labels.
+///
+/// JSON output never goes through this; it always carries plain numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberStyle {
+    pub locale: Locale,
+    /// Insert thousands separators.
+    pub group_digits: bool,
+    /// Use `$`/`€`/... where known; otherwise (or when `false`) show the currency code.
+    pub currency_symbols: bool,
+    /// Fixed decimals for prices and amounts; adaptive when `None`.
+    pub precision: Option<usize>,
+}
+
+impl NumberStyle {
+    pub const DEFAULT: Self = Self {
+        locale: Locale::En,
+        group_digits: true,
+        currency_symbols: true,
+        precision: None,
+    };
+
+    /// Format a price with its currency (e.g. `$67,231.55` or `67.231,55 €`).
+    pub fn format_price(&self, value: f64, currency: &str) -> String {
+        self.with_currency(&self.format_price_value(value), currency)
+    }
+
+    /// Format a price without a currency (e.g. `67,231.55`).
+    pub fn format_price_value(&self, value: f64) -> String {
+        self.localize(&self.fixed(value, price_decimals), self.group_digits)
+    }
+
+    /// Format a coin amount without its symbol (e.g. `0.00148`).
+    pub fn format_amount(&self, value: f64) -> String {
+        self.localize(&self.fixed(value, amount_decimals), self.group_digits)
+    }
+
+    /// Unit and decimals for an axis whose labels reach `largest` in magnitude and are
+    /// `step` apart.
+    ///
+    /// From 1000 upward labels are scaled to `k`/`M`/`B`/`T` by `largest`; they keep three
+    /// significant digits, or more when that is needed to tell labels `step` apart.
+    /// `--precision` fixes the decimals of unscaled labels.
+    pub fn axis_scale(&self, largest: f64, step: f64) -> AxisScale {
+        let largest = largest.abs();
+        let (divisor, suffix) = match largest {
+            v if v >= 1e12 => (1e12, "T"),
+            v if v >= 1e9 => (1e9, "B"),
+            v if v >= 1e6 => (1e6, "M"),
+            v if v >= 1e3 => (1e3, "k"),
+            _ => (1.0, ""),
+        };
+        let decimals = match self.precision {
+            Some(p) if suffix.is_empty() => p,
+            _ => {
+                let magnitude = |v: f64| v.log10().floor() as i32;
+                let significant = if largest > 0.0 {
+                    AXIS_SIGNIFICANT_DIGITS - 1 - magnitude(largest / divisor)
+                } else {
+                    0
+                };
+                let step = step.abs() / divisor;
+                let resolving = if step > 0.0 && step.is_finite() {
+                    -magnitude(step)
+                } else {
+                    0
+                };
+                significant.max(resolving).clamp(0, MAX_PRECISION as i32) as usize
+            }
+        };
+        AxisScale {
+            divisor,
+            suffix,
+            decimals,
+        }
+    }
+
+    /// Format one axis label under `scale`, never grouped (e.g. `67.2k`, `0.0000123`).
+    pub fn format_axis_value(&self, value: f64, scale: AxisScale) -> String {
+        let decimals = scale.decimals;
+        let plain = format!("{:.decimals$}", value / scale.divisor);
+        format!("{}{}", self.localize(&plain, false), scale.suffix)
+    }
+
+    /// Format ascending axis `values` with one scale, so they share a unit and
+    /// neighbouring labels differ.
+    pub fn format_axis_labels(&self, values: &[f64]) -> Vec<String> {
+        let largest = values.iter().fold(0.0_f64, |max, v| max.max(v.abs()));
+        let step = values
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .filter(|step| *step > 0.0)
+            .fold(f64::INFINITY, f64::min);
+        let scale = self.axis_scale(largest, step);
+        values
+            .iter()
+            .map(|&value| self.format_axis_value(value, scale))
+            .collect()
+    }
+
+    /// Axis title for prices in `currency`: the code, with its symbol when one is shown
+    /// (e.g. `USD ($)`).
+    pub fn currency_axis_title(&self, currency: &str) -> String {
+        let code = currency.to_uppercase();
+        match currency_symbol(&code) {
+            Some(symbol) if self.currency_symbols && symbol != code => {
+                format!("{} ({})", code, symbol)
+            }
+            _ => code,
+        }
+    }
+
+    /// Format `value` with exactly `decimals` places, ignoring `precision` (e.g. percentages).
+    pub fn format_fixed(&self, value: f64, decimals: usize) -> String {
+        self.localize(&format!("{value:.decimals$}"), self.group_digits)
+    }
+
+    /// Format a large value with a K/M/B/T suffix (e.g. `28.50B`).
+    pub fn format_compact(&self, value: f64) -> String {
+        let (scaled, suffix) = if value >= 1_000_000_000_000.0 {
+            (value / 1_000_000_000_000.0, "T")
+        } else if value >= 1_000_000_000.0 {
+            (value / 1_000_000_000.0, "B")
+        } else if value >= 1_000_000.0 {
+            (value / 1_000_000.0, "M")
+        } else if value >= 1_000.0 {
+            (value / 1_000.0, "K")
+        } else {
+            (value, "")
+        };
+        format!(
+            "{}{}",
+            self.localize(&format!("{scaled:.2}"), false),
+            suffix
+        )
+    }
+
+    /// Attach the currency symbol or code to an already formatted amount.
+    pub fn with_currency(&self, formatted: &str, currency: &str) -> String {
+        let code = currency.to_uppercase();
+        let unit = match currency_symbol(&code) {
+            Some(symbol) if self.currency_symbols => symbol,
+            _ => code.as_str(),
+        };
+        // Letter units (codes, `CHF`) always get a space; `$`-style symbols hug the number.
+        let spaced = unit.chars().last().is_some_and(char::is_alphabetic);
+        match (self.locale.symbol_after(), spaced) {
+            (true, _) => format!("{formatted} {unit}"),
+            (false, true) => format!("{unit} {formatted}"),
+            (false, false) => format!("{unit}{formatted}"),
+        }
+    }
+
+    /// Plain `-1234.56`-style digits under the configured or adaptive precision.
+    fn fixed(&self, value: f64, decimals: fn(f64) -> usize) -> String {
+        match self.precision {
+            Some(p) => format!("{value:.p$}"),
+            None => trim_small(value, format!("{value:.*}", decimals(value))),
+        }
+    }
+
+    /// Apply locale separators (and optionally grouping) to plain `-1234.56` digits.
+    fn localize(&self, plain: &str, group: bool) -> String {
+        let (sign, unsigned) = match plain.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", plain),
+        };
+        let (whole, fraction) = match unsigned.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut out = String::from(sign);
+        for (i, ch) in whole.chars().enumerate() {
+            if group && i > 0 && (whole.len() - i) % 3 == 0 {
+                out.push_str(self.locale.group_separator());
+            }
+            out.push(ch);
+        }
+        if let Some(fraction) = fraction {
+            out.push(self.locale.decimal_separator());
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+/// Unit suffix and decimals shared by the labels on one chart axis, from
+/// [`NumberStyle::axis_scale`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisScale {
+    divisor: f64,
+    suffix: &'static str,
+    decimals: usize,
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Set the number style used by table and status output for this process.
+pub fn set_style(style: NumberStyle) {
+    let style = NumberStyle {
+        precision: style.precision.map(|p| p.min(MAX_PRECISION)),
+        ..style
+    };
+    *STYLE.write().unwrap_or_else(|e| e.into_inner()) = style;
+}
+
+/// The number style currently in effect.
+pub fn style() -> NumberStyle {
+    *STYLE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Adaptive decimals for a price: 2 from 1 upward, 4 down to 0.01, then 10 significant digits.
+pub fn price_decimals(value: f64) -> usize {
+    let abs = value.abs();
+    if abs >= 1.0 || abs == 0.0 {
+        2
+    } else if abs >= 0.01 {
+        4
+    } else {
+        significant_decimals(abs)
+    }
+}
+
+/// Adaptive decimals for a coin amount: 2 from 1000 upward, 6 from 0.01, then 10
+/// significant digits.
+pub fn amount_decimals(value: f64) -> usize {
+    let abs = value.abs();
+    if abs >= 1_000.0 || abs == 0.0 {
+        2
+    } else if abs >= 0.01 {
+        6
+    } else {
+        significant_decimals(abs)
+    }
+}
+
+fn significant_decimals(abs: f64) -> usize {
+    let magnitude = abs.log10().floor() as i32;
+    (SMALL_VALUE_SIGNIFICANT_DIGITS - 1 - magnitude).clamp(0, MAX_PRECISION as i32) as usize
+}
+
+/// Drop trailing zeros from significant-digit output for values below 0.01.
+fn trim_small(value: f64, formatted: String) -> String {
+    if value == 0.0 || value.abs() >= 0.01 || !formatted.contains('.') {
+        return formatted;
+    }
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// [`NumberStyle::format_price`] with the current style.
+pub fn format_price(value: f64, currency: &str) -> String {
+    style().format_price(value, currency)
+}
+
+/// [`NumberStyle::format_price_value`] with the current style.
+pub fn format_price_value(value: f64) -> String {
+    style().format_price_value(value)
+}
+
+/// [`NumberStyle::format_amount`] with the current style.
+pub fn format_amount(value: f64) -> String {
+    style().format_amount(value)
+}
+
+/// [`NumberStyle::axis_scale`] with the current style.
+pub fn axis_scale(largest: f64, step: f64) -> AxisScale {
+    style().axis_scale(largest, step)
+}
+
+/// [`NumberStyle::format_axis_value`] with the current style.
+pub fn format_axis_value(value: f64, scale: AxisScale) -> String {
+    style().format_axis_value(value, scale)
+}
+
+/// [`NumberStyle::format_axis_labels`] with the current style.
+pub fn format_axis_labels(values: &[f64]) -> Vec<String> {
+    style().format_axis_labels(values)
+}
+
+/// [`NumberStyle::currency_axis_title`] with the current style.
+pub fn currency_axis_title(currency: &str) -> String {
+    style().currency_axis_title(currency)
+}
+
+/// [`NumberStyle::format_fixed`] with the current style.
+pub fn format_fixed(value: f64, decimals: usize) -> String {
+    style().format_fixed(value, decimals)
+}
+
+/// [`NumberStyle::format_compact`] with the current style.
+pub fn format_compact(value: f64) -> String {
+    style().format_compact(value)
+}
+
+/// [`NumberStyle::with_currency`] with the current style.
+pub fn with_currency(formatted: &str, currency: &str) -> String {
+    style().with_currency(formatted, currency)
+}
+
+/// Symbol for a currency code, if it has a well-known one.
+pub fn currency_symbol(currency: &str) -> Option<&'static str> {
+    let symbol = match currency.to_uppercase().as_str() {
+        "USD" => "$",
+        "EUR" => "\u{20ac}",
+        "GBP" => "\u{00a3}",
+        "JPY" | "CNY" => "\u{00a5}",
+        "CAD" => "CA$",
+        "AUD" => "A$",
+        "CHF" => "CHF",
+        "BTC" => "\u{20bf}",
+        _ => return None,
+    };
+    Some(symbol)
+}
+
+/// Largest whole unit of an age, e.g. `45s`, `12m`, `3h`, `2d`.
+pub fn format_age(age: chrono::Duration) -> String {
+    let secs = age.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+/// Byte count in binary units, e.g. `512 B`, `1.5 KiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_locale(locale: Locale) -> NumberStyle {
+        NumberStyle {
+            locale,
+            ..NumberStyle::DEFAULT
+        }
+    }
+
+    #[test]
+    fn adaptive_price_precision_by_magnitude() {
+        let style = NumberStyle::DEFAULT;
+        assert_eq!(style.format_price_value(67231.5512), "67,231.55");
+        assert_eq!(style.format_price_value(1.5), "1.50");
+        assert_eq!(style.format_price_value(0.0523), "0.0523");
+        assert_eq!(
+            style.format_price_value(0.000_008_912_345),
+            "0.000008912345"
+        );
+        assert_eq!(style.format_price_value(6.2e-8), "0.000000062");
+        assert_eq!(style.format_price_value(0.0), "0.00");
+        assert_eq!(style.format_price_value(-1_234.5), "-1,234.50");
+    }
+
+    #[test]
+    fn adaptive_amount_precision_keeps_small_coin_amounts() {
+        let style = NumberStyle::DEFAULT;
+        assert_eq!(style.format_amount(1234.5678), "1,234.57");
+        assert_eq!(style.format_amount(1.234567), "1.234567");
+        assert_eq!(style.format_amount(0.00148), "0.00148");
+        assert_eq!(style.format_amount(2_500_000.0), "2,500,000.00");
+    }
+
+    #[test]
+    fn explicit_precision_overrides_adaptive_decimals() {
+        let style = |precision| NumberStyle {
+            precision: Some(precision),
+            ..NumberStyle::DEFAULT
+        };
+        assert_eq!(style(10).format_price_value(6.2e-8), "0.0000000620");
+        assert_eq!(style(0).format_price_value(67231.5512), "67,232");
+        assert_eq!(style(3).format_amount(0.5), "0.500");
+    }
+
+    #[test]
+    fn en_locale_prefixes_symbols_and_falls_back_to_code() {
+        let style = with_locale(Locale::En);
+        assert_eq!(style.format_price(67231.5512, "usd"), "$67,231.55");
+        assert_eq!(style.format_price(1_500.0, "gbp"), "\u{00a3}1,500.00");
+        assert_eq!(style.format_price(2.0, "chf"), "CHF 2.00");
+        assert_eq!(style.format_price(1_530.5, "ngn"), "NGN 1,530.50");
+        assert_eq!(style.format_compact(28_500_000_000.0), "28.50B");
+    }
+
+    #[test]
+    fn de_locale_swaps_separators_and_suffixes_symbol() {
+        let style = with_locale(Locale::De);
+        assert_eq!(style.format_price(67231.5512, "eur"), "67.231,55 \u{20ac}");
+        assert_eq!(style.format_price(150_000.0, "jpy"), "150.000,00 \u{00a5}");
+        assert_eq!(style.format_compact(1_050_000.0), "1,05M");
+        assert_eq!(
+            style.format_axis_labels(&[67_231.5, 67_890.0]),
+            vec!["67,2k", "67,9k"]
+        );
+    }
+
+    #[test]
+    fn axis_labels_share_a_unit_and_resolve_their_step() {
+        let style = NumberStyle::DEFAULT;
+        let labels = |values: &[f64]| style.format_axis_labels(values);
+        assert_eq!(
+            labels(&[61_850.2, 64_540.9, 67_231.55]),
+            ["61.9k", "64.5k", "67.2k"]
+        );
+        assert_eq!(
+            labels(&[67_210.0, 67_230.0, 67_250.0]),
+            ["67.21k", "67.23k", "67.25k"]
+        );
+        assert_eq!(labels(&[980_000.0, 1_050_000.0]), ["0.98M", "1.05M"]);
+        assert_eq!(labels(&[2.5e12, 3.1e12]), ["2.50T", "3.10T"]);
+        assert_eq!(labels(&[175.3, 205.2]), ["175", "205"]);
+        assert_eq!(labels(&[0.998, 1.002]), ["0.998", "1.002"]);
+        assert_eq!(
+            labels(&[0.00001234, 0.00001301]),
+            ["0.0000123", "0.0000130"]
+        );
+        assert_eq!(labels(&[-12.5, 0.0, 12.5]), ["-12.5", "0.0", "12.5"]);
+
+        let fixed = NumberStyle {
+            precision: Some(4),
+            ..NumberStyle::DEFAULT
+        };
+        assert_eq!(fixed.format_axis_labels(&[175.3]), ["175.3000"]);
+        assert_eq!(fixed.format_axis_labels(&[67_231.55]), ["67.2k"]);
+    }
+
+    #[test]
+    fn currency_axis_title_names_the_code_once() {
+        let style = NumberStyle::DEFAULT;
+        assert_eq!(style.currency_axis_title("usd"), "USD ($)");
+        assert_eq!(style.currency_axis_title("CHF"), "CHF");
+        assert_eq!(style.currency_axis_title("ngn"), "NGN");
+        let codes = NumberStyle {
+            currency_symbols: false,
+            ..NumberStyle::DEFAULT
+        };
+        assert_eq!(codes.currency_axis_title("eur"), "EUR");
+    }
+
+    #[test]
+    fn fr_locale_groups_with_narrow_space() {
+        let style = with_locale(Locale::Fr);
+        assert_eq!(
+            style.format_price(1_234_567.891, "eur"),
+            "1\u{202f}234\u{202f}567,89 \u{20ac}"
+        );
+    }
+
+    #[test]
+    fn grouping_and_symbols_can_be_disabled() {
+        let style = NumberStyle {
+            group_digits: false,
+            currency_symbols: false,
+            ..NumberStyle::DEFAULT
+        };
+        assert_eq!(style.format_price(67231.5512, "usd"), "USD 67231.55");
+        assert_eq!(style.format_fixed(-1_234.5, 2), "-1234.50");
+    }
+}
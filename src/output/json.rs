@@ -1,6 +1,20 @@
-use crate::calc::Conversion;
+use std::io::Write;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::calc::stats::{self, Comparison, CorrelationMatrix, HistoryStats, RiskMetrics};
+use crate::calc::{Conversion, Investment, PortfolioValue};
 use crate::error::Result;
-use crate::provider::{CoinPrice, PriceHistory, TickerMatch};
+use crate::provider::cache::CacheEntry;
+use crate::provider::health::ProviderHealth;
+use crate::provider::{
+    CoinPrice, GlobalMetrics, MarketTicker, PriceBatch, PriceHistory, ProviderInfo, TickerMatch,
+    TrendingCoin,
+};
+use crate::state::QuotaUsage;
+use crate::timings::Timings;
 
 /// Print prices as formatted JSON to stdout.
 pub fn print_json(prices: &[CoinPrice]) -> Result<()> {
@@ -10,22 +24,381 @@ pub fn print_json(prices: &[CoinPrice]) -> Result<()> {
     Ok(())
 }
 
+/// Print items as newline-delimited JSON to stdout, one compact object per line.
+///
+/// Each line is flushed as soon as it is written so pipeline consumers see it promptly.
+pub fn print_ndjson<T: Serialize>(items: &[T]) -> Result<()> {
+    let stdout = std::io::stdout();
+    write_ndjson(&mut stdout.lock(), items)
+}
+
+fn write_ndjson<T: Serialize>(out: &mut impl Write, items: &[T]) -> Result<()> {
+    for item in items {
+        let line = serde_json::to_string(item)
+            .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+        writeln!(out, "{}", line)
+            .and_then(|_| out.flush())
+            .map_err(|e| crate::error::Error::Parse(format!("NDJSON write: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Lookup result with the `--global` market summary attached.
+#[derive(Serialize)]
+struct PriceEnvelope<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    global: Option<&'a GlobalMetrics>,
+    prices: &'a [CoinPrice],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    missing: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    portfolio: Option<&'a PortfolioValue>,
+    #[serde(rename = "_timings", skip_serializing_if = "Option::is_none")]
+    timings: Option<&'a Timings>,
+}
+
+/// Print a lookup result as formatted JSON to stdout.
+///
+/// Complete lookups keep the plain price array; when symbols are missing the output
+/// becomes `{"prices": [...], "missing": [...]}`, with `global` it gains a leading
+/// `"global": {...}`, with `portfolio` a `"portfolio": {...}` valuation, and with
+/// `timings` a trailing `"_timings": {...}`.
+pub fn print_price_batch_json(
+    batch: &PriceBatch,
+    global: Option<&GlobalMetrics>,
+    portfolio: Option<&PortfolioValue>,
+    timings: Option<&Timings>,
+) -> Result<()> {
+    println!("{}", price_batch_json(batch, global, portfolio, timings)?);
+    Ok(())
+}
+
+/// The text `print_price_batch_json` prints.
+pub fn price_batch_json(
+    batch: &PriceBatch,
+    global: Option<&GlobalMetrics>,
+    portfolio: Option<&PortfolioValue>,
+    timings: Option<&Timings>,
+) -> Result<String> {
+    if global.is_none() && portfolio.is_none() && timings.is_none() && batch.missing.is_empty() {
+        return serde_json::to_string_pretty(&batch.prices)
+            .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)));
+    }
+    serde_json::to_string_pretty(&PriceEnvelope {
+        global,
+        prices: &batch.prices,
+        missing: &batch.missing,
+        portfolio,
+        timings,
+    })
+    .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))
+}
+
+/// `schema` value of the versioned JSON envelope.
+pub const SCHEMA_V1: &str = "cryptoprice/v1";
+
+/// The `--schema v1` document: `{"schema", "kind", "data", "meta"}`.
+///
+/// Field names here only change with a new schema version; `--schema legacy` keeps
+/// the older bare arrays.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "cryptoprice/v1")]
+pub struct Envelope {
+    /// Always `cryptoprice/v1`.
+    #[schemars(extend("const" = "cryptoprice/v1"))]
+    pub schema: String,
+    #[serde(flatten)]
+    pub data: EnvelopeData,
+    pub meta: EnvelopeMeta,
+}
+
+/// The rows of an envelope, tagged by `kind`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum EnvelopeData {
+    Prices(Vec<CoinPrice>),
+    History(Vec<HistorySeries>),
+    Conversions(Vec<Conversion>),
+    Search(Vec<TickerMatch>),
+}
+
+/// A history series with its summary statistics.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HistorySeries {
+    #[serde(flatten)]
+    pub history: PriceHistory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<HistoryStats>,
+}
+
+/// What an envelope was produced from.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnvelopeMeta {
+    /// Id of the provider the lookup was pinned to or started with; `null` when it
+    /// tried providers in turn. Each row names the provider that answered it.
+    pub provider: Option<String>,
+    /// Requested quote currency, lower-cased; `null` where the lookup has none.
+    pub currency: Option<String>,
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
+    pub generated_at: DateTime<Utc>,
+    /// Requested symbols, or fiat targets of a conversion, that had no result.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+impl Envelope {
+    fn new(data: EnvelopeData, provider: Option<&str>, currency: Option<&str>) -> Self {
+        Self {
+            schema: SCHEMA_V1.to_string(),
+            data,
+            meta: EnvelopeMeta {
+                provider: provider.map(str::to_string),
+                currency: currency.map(str::to_lowercase),
+                generated_at: Utc::now(),
+                missing: Vec::new(),
+            },
+        }
+    }
+
+    /// Prices of a lookup, with its unresolved symbols under `meta.missing`.
+    pub fn prices(batch: &PriceBatch, provider: Option<&str>, currency: Option<&str>) -> Self {
+        let mut envelope = Self::new(
+            EnvelopeData::Prices(batch.prices.clone()),
+            provider,
+            currency,
+        );
+        envelope.meta.missing = batch.missing.clone();
+        envelope
+    }
+
+    /// History series, each with its `calc::stats` summary.
+    pub fn history(
+        histories: &[PriceHistory],
+        provider: Option<&str>,
+        currency: Option<&str>,
+    ) -> Self {
+        let series = histories
+            .iter()
+            .map(|history| HistorySeries {
+                history: history.clone(),
+                stats: stats::summarize(&history.points),
+            })
+            .collect();
+        Self::new(EnvelopeData::History(series), provider, currency)
+    }
+
+    /// Conversions, with fiat targets that had no rate under `meta.missing`.
+    pub fn conversions(conversions: &[Conversion], skipped: &[String]) -> Self {
+        let mut envelope = Self::new(EnvelopeData::Conversions(conversions.to_vec()), None, None);
+        envelope.meta.missing = skipped.to_vec();
+        envelope
+    }
+
+    /// Ticker search matches.
+    pub fn search(matches: &[TickerMatch], provider: Option<&str>) -> Self {
+        Self::new(EnvelopeData::Search(matches.to_vec()), provider, None)
+    }
+}
+
+/// Print `envelope` as formatted JSON to stdout.
+pub fn print_envelope(envelope: &Envelope) -> Result<()> {
+    println!("{}", envelope_json(envelope)?);
+    Ok(())
+}
+
+/// The text [`print_envelope`] prints.
+pub fn envelope_json(envelope: &Envelope) -> Result<String> {
+    serde_json::to_string_pretty(envelope)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))
+}
+
+/// JSON Schema document describing [`Envelope`], as printed by `--print-schema`.
+pub fn envelope_schema_json() -> Result<String> {
+    serde_json::to_string_pretty(&schemars::schema_for!(Envelope))
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))
+}
+
+/// Print `{"_timings": {...}}` on one line to stderr, keeping stdout parseable.
+pub fn print_timings_json(timings: &Timings) -> Result<()> {
+    #[derive(Serialize)]
+    struct TimingsOnly<'a> {
+        #[serde(rename = "_timings")]
+        timings: &'a Timings,
+    }
+
+    let output = serde_json::to_string(&TimingsOnly { timings })
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    eprintln!("{}", output);
+    Ok(())
+}
+
+/// Print global market metrics as a formatted JSON object to stdout.
+pub fn print_global_json(metrics: &GlobalMetrics) -> Result<()> {
+    let output = serde_json::to_string_pretty(metrics)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print a fatal error as a single-line `{"error": {...}}` object to stdout.
+pub fn print_error_json(err: &crate::error::Error) {
+    println!("{}", error_json(err));
+}
+
+/// `{"error": {...}}` body; provider fields are only present for provider errors.
+fn error_json(err: &crate::error::Error) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "kind": err.kind(),
+        "message": err.to_string(),
+        "exit_code": err.exit_code(),
+    });
+    if let Some(provider) = err.provider() {
+        body["provider"] = provider.into();
+    }
+    if let Some(status) = err.status() {
+        body["status"] = status.into();
+    }
+    if let crate::error::Error::RateLimited {
+        retry_after: Some(wait),
+        ..
+    } = err
+    {
+        body["retry_after_secs"] = wait.as_secs().into();
+    }
+    if let crate::error::Error::NoResults {
+        symbols, currency, ..
+    } = err
+    {
+        if !symbols.is_empty() {
+            body["symbols"] = symbols.clone().into();
+        }
+        if !currency.is_empty() {
+            body["currency"] = currency.clone().into();
+        }
+    }
+    if let Some(hint) = err.hint() {
+        body["hint"] = hint.into();
+    }
+    serde_json::json!({ "error": body })
+}
+
 /// Print fiat-to-crypto conversions as formatted JSON to stdout.
-pub fn print_conversions_json(conversions: &[Conversion]) -> Result<()> {
-    let output = serde_json::to_string_pretty(conversions)
+///
+/// When some fiat targets had no rate the output becomes
+/// `{"conversions": [...], "skipped": [...]}`.
+pub fn print_conversions_json(conversions: &[Conversion], skipped: &[String]) -> Result<()> {
+    println!("{}", conversions_json(conversions, skipped)?);
+    Ok(())
+}
+
+/// The text `print_conversions_json` prints.
+pub fn conversions_json(conversions: &[Conversion], skipped: &[String]) -> Result<String> {
+    if skipped.is_empty() {
+        serde_json::to_string_pretty(conversions)
+    } else {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "conversions": conversions,
+            "skipped": skipped,
+        }))
+    }
+    .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))
+}
+
+/// A history series with its summary statistics, as printed by `--chart --json`.
+#[derive(Debug, Serialize)]
+pub struct HistoryJson<'a> {
+    #[serde(flatten)]
+    pub history: &'a PriceHistory,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<HistoryStats>,
+}
+
+/// Pair each series with its `calc::stats` summary.
+pub fn histories_with_stats(histories: &[PriceHistory]) -> Vec<HistoryJson<'_>> {
+    histories
+        .iter()
+        .map(|history| HistoryJson {
+            history,
+            stats: stats::summarize(&history.points),
+        })
+        .collect()
+}
+
+/// Print historical prices, with a `stats` object per series, as formatted JSON to stdout.
+///
+/// With more than one series the output becomes `{"histories": [...], "comparison": [...]}`.
+pub fn print_history_json(histories: &[PriceHistory], comparison: &[Comparison]) -> Result<()> {
+    let output = serde_json::to_string_pretty(&history_json(histories, comparison)?)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+fn history_json(
+    histories: &[PriceHistory],
+    comparison: &[Comparison],
+) -> Result<serde_json::Value> {
+    let series = serde_json::to_value(histories_with_stats(histories))
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    if histories.len() < 2 {
+        return Ok(series);
+    }
+    Ok(serde_json::json!({
+        "histories": series,
+        "comparison": comparison,
+    }))
+}
+
+/// Print `pricr stats` metrics as a formatted JSON array to stdout.
+pub fn print_risk_json(metrics: &[RiskMetrics]) -> Result<()> {
+    let output = serde_json::to_string_pretty(metrics)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print `pricr roi` results as a formatted JSON array to stdout.
+pub fn print_investments_json(investments: &[Investment]) -> Result<()> {
+    let output = serde_json::to_string_pretty(investments)
         .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
     println!("{}", output);
     Ok(())
 }
 
-/// Print historical prices as formatted JSON to stdout.
-pub fn print_history_json(histories: &[PriceHistory]) -> Result<()> {
-    let output = serde_json::to_string_pretty(histories)
+/// Print the correlation matrix as formatted JSON to stdout.
+///
+/// Shape: `{"correlation": {"BTC": {"BTC": 1.0, "ETH": 0.82}, ...}, "observations": 29,
+/// "excluded": []}`, with `null` for a pair involving a flat series.
+pub fn print_correlation_json(matrix: &CorrelationMatrix) -> Result<()> {
+    let output = serde_json::to_string_pretty(&correlation_json(matrix))
         .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
     println!("{}", output);
     Ok(())
 }
 
+/// The object [`print_correlation_json`] prints; `--ndjson` prints it on one line.
+pub fn correlation_json(matrix: &CorrelationMatrix) -> serde_json::Value {
+    let nested: serde_json::Map<String, serde_json::Value> = matrix
+        .symbols
+        .iter()
+        .zip(&matrix.values)
+        .map(|(symbol, row)| {
+            let pairs: serde_json::Map<String, serde_json::Value> = matrix
+                .symbols
+                .iter()
+                .zip(row)
+                .map(|(other, value)| (other.clone(), serde_json::json!(value)))
+                .collect();
+            (symbol.clone(), serde_json::Value::Object(pairs))
+        })
+        .collect();
+    serde_json::json!({
+        "correlation": nested,
+        "observations": matrix.observations,
+        "excluded": matrix.excluded,
+    })
+}
+
 /// Print ticker search matches as formatted JSON to stdout.
 pub fn print_ticker_matches_json(matches: &[TickerMatch]) -> Result<()> {
     let output = serde_json::to_string_pretty(matches)
@@ -33,3 +406,332 @@ pub fn print_ticker_matches_json(matches: &[TickerMatch]) -> Result<()> {
     println!("{}", output);
     Ok(())
 }
+
+/// Print trending coins, including provider ids, as formatted JSON to stdout.
+pub fn print_trending_json(coins: &[TrendingCoin]) -> Result<()> {
+    let output = serde_json::to_string_pretty(coins)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print exchange markets as formatted JSON to stdout.
+pub fn print_market_tickers_json(markets: &[MarketTicker]) -> Result<()> {
+    let output = serde_json::to_string_pretty(markets)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print cached responses as formatted JSON to stdout.
+pub fn print_cache_entries_json(entries: &[CacheEntry]) -> Result<()> {
+    let output = serde_json::to_string_pretty(entries)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print provider health check results as formatted JSON to stdout.
+pub fn print_provider_health_json(report: &[ProviderHealth]) -> Result<()> {
+    let output = serde_json::to_string_pretty(report)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print `--list-providers` entries with their capabilities as formatted JSON to stdout.
+pub fn print_provider_list_json(providers: &[ProviderInfo]) -> Result<()> {
+    let output = serde_json::to_string_pretty(providers)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print monthly API credit usage as formatted JSON to stdout.
+pub fn print_quota_json(usage: &QuotaUsage) -> Result<()> {
+    let output = serde_json::to_string_pretty(usage)
+        .map_err(|e| crate::error::Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{PricePoint, TickerMatch};
+
+    #[test]
+    fn error_json_includes_provider_details() {
+        let err = crate::error::Error::RateLimited {
+            provider: "CoinGecko".into(),
+            retry_after: Some(std::time::Duration::from_secs(20)),
+        };
+        let value = error_json(&err);
+        assert_eq!(value["error"]["kind"], "rate_limited");
+        assert_eq!(value["error"]["provider"], "CoinGecko");
+        assert_eq!(value["error"]["status"], 429);
+        assert_eq!(value["error"]["retry_after_secs"], 20);
+        assert_eq!(value["error"]["hint"], "try --provider yahoo or wait 20s");
+
+        let value = error_json(&crate::error::Error::no_results_from(""));
+        assert!(value["error"].get("provider").is_none());
+        assert!(value["error"].get("hint").is_none());
+
+        let value = error_json(&crate::error::Error::no_results(
+            "CoinGecko",
+            &["xyz".into()],
+            "eur",
+        ));
+        assert_eq!(value["error"]["provider"], "CoinGecko");
+        assert_eq!(value["error"]["symbols"], serde_json::json!(["XYZ"]));
+        assert_eq!(value["error"]["currency"], "EUR");
+        assert_eq!(
+            value["error"]["message"],
+            "No results from CoinGecko for [XYZ] in EUR"
+        );
+    }
+
+    fn ndjson_matches_array<T: Serialize>(items: &[T]) {
+        let mut out = Vec::new();
+        write_ndjson(&mut out, items).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let array: serde_json::Value = serde_json::to_value(items).unwrap();
+
+        assert_eq!(text.lines().count(), items.len());
+        assert_eq!(serde_json::Value::Array(lines), array);
+    }
+
+    #[test]
+    fn ndjson_lines_match_array_output_for_prices() {
+        let prices = vec![
+            CoinPrice {
+                symbol: "BTC".to_string(),
+                name: "Bitcoin".to_string(),
+                price: 67_000.0,
+                change_24h: Some(1.5),
+                currency: "USD".to_string(),
+                provider: "CoinGecko".to_string(),
+                ..Default::default()
+            },
+            CoinPrice {
+                symbol: "ETH".to_string(),
+                price: 3_000.0,
+                ..Default::default()
+            },
+        ];
+        ndjson_matches_array(&prices);
+    }
+
+    #[test]
+    fn ndjson_keeps_history_points_inline() {
+        let history = vec![PriceHistory {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            points: vec![PricePoint {
+                timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                price: 40_000.0,
+                filled: false,
+            }],
+            events: Vec::new(),
+            granularity: None,
+        }];
+        ndjson_matches_array(&history);
+
+        let mut out = Vec::new();
+        write_ndjson(&mut out, &history).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(!text.trim_end().contains('\n'));
+        assert!(text.contains("\"points\":[{"));
+
+        let with_stats = serde_json::to_value(histories_with_stats(&history)).unwrap();
+        assert_eq!(with_stats[0]["symbol"], "BTC");
+        assert_eq!(with_stats[0]["stats"]["mean"], 40_000.0);
+        assert!(with_stats[0]["stats"]["change_pct"].is_null());
+        assert_eq!(
+            with_stats[0]["stats"]["high"],
+            serde_json::json!({"value": 40_000.0, "timestamp": "2023-11-14T22:13:20Z"})
+        );
+        assert_eq!(
+            with_stats[0]["stats"]["low"],
+            with_stats[0]["stats"]["high"]
+        );
+        assert!(history_json(&history, &[]).unwrap().is_array());
+
+        let mut pair = history.clone();
+        pair.push(PriceHistory {
+            symbol: "ETH".to_string(),
+            ..history[0].clone()
+        });
+        let (comparison, _) = stats::compare(&pair);
+        let envelope = history_json(&pair, &comparison).unwrap();
+        assert_eq!(envelope["histories"].as_array().unwrap().len(), 2);
+        assert!(envelope["comparison"].is_array());
+    }
+
+    #[test]
+    fn correlation_json_nests_values_by_symbol_pair() {
+        let matrix = CorrelationMatrix {
+            symbols: vec!["BTC".to_string(), "ETH".to_string()],
+            values: vec![vec![Some(1.0), Some(0.82)], vec![Some(0.82), None]],
+            observations: 29,
+            excluded: vec!["AAPL".to_string()],
+        };
+        let json = correlation_json(&matrix);
+
+        assert_eq!(json["correlation"]["BTC"]["ETH"], 0.82);
+        assert_eq!(json["correlation"]["ETH"]["BTC"], 0.82);
+        assert_eq!(json["correlation"]["BTC"]["BTC"], 1.0);
+        assert!(json["correlation"]["ETH"]["ETH"].is_null());
+        assert_eq!(json["observations"], 29);
+        assert_eq!(json["excluded"], serde_json::json!(["AAPL"]));
+    }
+
+    #[test]
+    fn ndjson_lines_match_array_output_for_conversions_and_matches() {
+        let conversions = vec![Conversion {
+            from_amount: 100.0,
+            from_currency: "USD".to_string(),
+            to_symbol: "BTC".to_string(),
+            to_name: "Bitcoin".to_string(),
+            to_amount: 0.0015,
+            rate: 66_666.0,
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }];
+        ndjson_matches_array(&conversions);
+
+        let matches = vec![TickerMatch {
+            symbol: "AAPL".to_string(),
+            name: "Apple Inc.".to_string(),
+            exchange: "NASDAQ".to_string(),
+            asset_type: "equity".to_string(),
+            provider: "Yahoo Finance".to_string(),
+        }];
+        ndjson_matches_array(&matches);
+    }
+
+    fn round_trip(envelope: &Envelope) -> serde_json::Value {
+        let text = envelope_json(envelope).unwrap();
+        let keys: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("  \""))
+            .filter_map(|line| line.split('"').next())
+            .collect();
+        assert_eq!(keys, ["schema", "kind", "data", "meta"]);
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["schema"], SCHEMA_V1);
+
+        let parsed: Envelope = serde_json::from_str(&text).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+        value
+    }
+
+    #[test]
+    fn v1_envelopes_round_trip_every_kind() {
+        let batch = PriceBatch {
+            prices: vec![CoinPrice {
+                symbol: "BTC".to_string(),
+                name: "Bitcoin".to_string(),
+                price: 67_000.0,
+                prices: vec![
+                    ("USD".to_string(), Some(67_000.0)),
+                    ("EUR".to_string(), None),
+                ],
+                currency: "USD".to_string(),
+                provider: "CoinGecko".to_string(),
+                timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                ..Default::default()
+            }],
+            missing: vec!["XYZ".to_string()],
+        };
+        let value = round_trip(&Envelope::prices(&batch, Some("coingecko"), Some("USD")));
+        assert_eq!(value["kind"], "prices");
+        assert_eq!(
+            value["data"][0]["prices"],
+            serde_json::json!({"USD": 67_000.0, "EUR": null})
+        );
+        assert_eq!(value["meta"]["provider"], "coingecko");
+        assert_eq!(value["meta"]["currency"], "usd");
+        assert_eq!(value["meta"]["missing"], serde_json::json!(["XYZ"]));
+
+        let history = PriceHistory {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            points: vec![
+                PricePoint {
+                    timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                    price: 40_000.0,
+                    filled: false,
+                },
+                PricePoint {
+                    timestamp: chrono::DateTime::from_timestamp(1_700_086_400, 0).unwrap(),
+                    price: 42_000.0,
+                    filled: true,
+                },
+            ],
+            events: Vec::new(),
+            granularity: None,
+        };
+        let value = round_trip(&Envelope::history(
+            std::slice::from_ref(&history),
+            None,
+            Some("usd"),
+        ));
+        assert_eq!(value["kind"], "history");
+        assert_eq!(value["data"][0]["points"][1]["filled"], true);
+        assert_eq!(value["data"][0]["stats"]["change_pct"], 5.0);
+        assert!(value["meta"]["provider"].is_null());
+
+        let conversion = Conversion {
+            from_amount: 100.0,
+            from_currency: "USD".to_string(),
+            to_symbol: "BTC".to_string(),
+            to_name: "Bitcoin".to_string(),
+            to_amount: 0.0015,
+            rate: 66_666.0,
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        };
+        let value = round_trip(&Envelope::conversions(&[conversion], &[]));
+        assert_eq!(value["kind"], "conversions");
+        assert!(value["meta"].get("missing").is_none());
+
+        let matches = vec![TickerMatch {
+            symbol: "AAPL".to_string(),
+            name: "Apple Inc.".to_string(),
+            exchange: "NASDAQ".to_string(),
+            asset_type: "equity".to_string(),
+            provider: "Yahoo Finance".to_string(),
+        }];
+        let value = round_trip(&Envelope::search(&matches, Some("yahoo")));
+        assert_eq!(value["kind"], "search");
+        assert_eq!(value["data"][0]["symbol"], "AAPL");
+    }
+
+    #[test]
+    fn v1_schema_pins_the_version_and_lists_every_kind() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&envelope_schema_json().unwrap()).unwrap();
+        assert_eq!(schema["properties"]["schema"]["const"], SCHEMA_V1);
+        let kinds: Vec<&str> = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["kind"]["const"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["prices", "history", "conversions", "search"]);
+        assert_eq!(
+            schema["$defs"]["CoinPrice"]["properties"]["prices"]["type"],
+            "object"
+        );
+    }
+}
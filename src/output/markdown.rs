@@ -0,0 +1,307 @@
+//! GitHub-flavored markdown tables for `--format markdown`.
+//!
+//! Cells reuse the terminal table's formatting with the styling stripped, and
+//! numeric columns are right-aligned with `---:`.
+
+use crate::calc::stats::summarize;
+use crate::calc::{Conversion, PortfolioValue};
+use crate::output::fmt::{self, format_price};
+use crate::output::table::{self, PriceColumn};
+use crate::provider::{CoinPrice, PriceHistory, TickerMatch};
+
+/// Render prices with the same columns as the terminal table.
+///
+/// With `portfolio`, a bold total line follows the table.
+pub fn render_prices(
+    prices: &[CoinPrice],
+    histories: Option<&[PriceHistory]>,
+    portfolio: Option<&PortfolioValue>,
+    columns: &[PriceColumn],
+) -> String {
+    let header: Vec<(String, bool)> = columns
+        .iter()
+        .map(|column| (column.label(prices), column.is_numeric()))
+        .collect();
+    let rows = prices.iter().map(|p| {
+        columns
+            .iter()
+            .map(|&column| table::price_cell(column, p, histories, portfolio))
+            .collect()
+    });
+    let mut out = render(&header, rows);
+    if let Some(portfolio) = portfolio {
+        out.push_str(&format!(
+            "\n\n**Total:** {}",
+            format_price(portfolio.total, &portfolio.currency)
+        ));
+        if !portfolio.excluded.is_empty() {
+            out.push_str(&format!(
+                " (excludes {}, not quoted in {})",
+                portfolio.excluded.join(", "),
+                portfolio.currency
+            ));
+        }
+    }
+    out
+}
+
+/// Render conversions as amount, result, rate, and provider columns.
+pub fn render_conversions(conversions: &[Conversion]) -> String {
+    let header = [
+        ("Amount".to_string(), true),
+        ("Result".to_string(), true),
+        ("Rate".to_string(), true),
+        ("Provider".to_string(), false),
+    ];
+    render(
+        &header,
+        conversions
+            .iter()
+            .map(|c| table::conversion_cells(c).to_vec()),
+    )
+}
+
+/// Render ticker search matches.
+pub fn render_ticker_matches(matches: &[TickerMatch]) -> String {
+    let header = ["Symbol", "Name", "Exchange", "Type", "Provider"].map(|h| (h.to_string(), false));
+    render(
+        &header,
+        matches.iter().map(|m| {
+            vec![
+                m.symbol.clone(),
+                m.name.clone(),
+                m.exchange.clone(),
+                m.asset_type.clone(),
+                m.provider.clone(),
+            ]
+        }),
+    )
+}
+
+/// Render one stats row per history series instead of its points.
+///
+/// Series without points are left out.
+pub fn render_history_stats(histories: &[PriceHistory]) -> String {
+    let header = [
+        ("Symbol", false),
+        ("Start", true),
+        ("End", true),
+        ("Change", true),
+        ("Low", true),
+        ("High", true),
+        ("Mean", true),
+        ("Max Drawdown", true),
+        ("Volatility", true),
+    ]
+    .map(|(h, numeric)| (h.to_string(), numeric));
+    let percent_or_na = |value: Option<f64>| {
+        value
+            .map(|v| format!("{}%", fmt::format_fixed(v, 2)))
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+    render(
+        &header,
+        histories.iter().filter_map(|history| {
+            let stats = summarize(&history.points)?;
+            let currency = &history.currency;
+            Some(vec![
+                history.symbol.clone(),
+                format_price(stats.start, currency),
+                format_price(stats.end, currency),
+                stats
+                    .change_pct
+                    .map(table::format_percent)
+                    .unwrap_or_else(|| "n/a".to_string()),
+                format_price(stats.min, currency),
+                format_price(stats.max, currency),
+                format_price(stats.mean, currency),
+                percent_or_na(stats.max_drawdown_pct),
+                percent_or_na(stats.volatility_pct),
+            ])
+        }),
+    )
+}
+
+/// Header row, alignment row, then one line per row.
+fn render(header: &[(String, bool)], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut lines = vec![
+        line(header.iter().map(|(label, _)| escape(label))),
+        line(
+            header
+                .iter()
+                .map(|&(_, numeric)| if numeric { "---:" } else { "---" }.to_string()),
+        ),
+    ];
+    lines.extend(rows.map(|row| line(row.iter().map(|cell| escape(&strip_ansi(cell))))));
+    lines.join("\n")
+}
+
+fn line(cells: impl Iterator<Item = String>) -> String {
+    format!("| {} |", cells.collect::<Vec<_>>().join(" | "))
+}
+
+/// Escape pipes, which would split the cell, and fold line breaks into spaces.
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Drop ANSI escape sequences left by the terminal table's styling.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::Position;
+    use crate::provider::PricePoint;
+    use chrono::TimeZone;
+
+    fn btc() -> CoinPrice {
+        CoinPrice {
+            symbol: "BTC".into(),
+            name: "Bitcoin".into(),
+            price: 67_234.5,
+            change_24h: Some(1.5),
+            market_cap: Some(1_320_000_000_000.0),
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prices_use_table_columns_and_right_align_numbers() {
+        let eth = CoinPrice {
+            symbol: "ETH".into(),
+            name: "Ether | Classic".into(),
+            price: 3_456.78,
+            change_24h: Some(-0.5),
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            ..Default::default()
+        };
+        let out = render_prices(&[btc(), eth], None, None, &table::DEFAULT_PRICE_COLUMNS);
+        assert_eq!(
+            out,
+            "| Symbol | Name | Price | 24h Change | Market Cap | Provider |\n\
+             | --- | --- | ---: | ---: | ---: | --- |\n\
+             | BTC | Bitcoin | $67,234.50 | +1.50% | $1.32T | CoinGecko |\n\
+             | ETH | Ether \\| Classic | $3,456.78 | -0.50% | - | CoinGecko |"
+        );
+    }
+
+    #[test]
+    fn portfolio_total_follows_the_table() {
+        let portfolio = PortfolioValue {
+            currency: "USD".into(),
+            positions: vec![Position {
+                symbol: "BTC".into(),
+                amount: 0.5,
+                value: Some(33_617.25),
+            }],
+            total: 33_617.25,
+            excluded: Vec::new(),
+        };
+        let columns = [PriceColumn::Symbol, PriceColumn::Amount, PriceColumn::Value];
+        let out = render_prices(&[btc()], None, Some(&portfolio), &columns);
+        assert_eq!(
+            out,
+            "| Symbol | Amount | Value |\n\
+             | --- | ---: | ---: |\n\
+             | BTC | 0.500000 | $33,617.25 |\n\
+             \n\
+             **Total:** $33,617.25"
+        );
+    }
+
+    #[test]
+    fn conversions_and_search_results() {
+        let conversion = Conversion {
+            from_amount: 100.0,
+            from_currency: "USD".into(),
+            to_symbol: "BTC".into(),
+            to_name: "Bitcoin".into(),
+            to_amount: 0.0015,
+            rate: 66_000.0,
+            provider: "CoinGecko".into(),
+            timestamp: chrono::Utc::now(),
+        };
+        assert_eq!(
+            render_conversions(&[conversion]),
+            "| Amount | Result | Rate | Provider |\n\
+             | ---: | ---: | ---: | --- |\n\
+             | $100.00 | 0.0015 BTC | 1 BTC = $66,000.00 | CoinGecko |"
+        );
+
+        let matches = [TickerMatch {
+            symbol: "AAPL".into(),
+            name: "Apple Inc.".into(),
+            exchange: "NASDAQ".into(),
+            asset_type: "Equity".into(),
+            provider: "Yahoo Finance".into(),
+        }];
+        assert_eq!(
+            render_ticker_matches(&matches),
+            "| Symbol | Name | Exchange | Type | Provider |\n\
+             | --- | --- | --- | --- | --- |\n\
+             | AAPL | Apple Inc. | NASDAQ | Equity | Yahoo Finance |"
+        );
+    }
+
+    #[test]
+    fn history_prints_one_stats_row_per_series() {
+        let day = |d: u32| chrono::Utc.with_ymd_and_hms(2025, 6, d, 0, 0, 0).unwrap();
+        let history = PriceHistory {
+            symbol: "BTC".into(),
+            name: "Bitcoin".into(),
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            points: [(1, 100.0), (2, 120.0), (3, 90.0), (4, 110.0)]
+                .into_iter()
+                .map(|(d, price)| PricePoint {
+                    timestamp: day(d),
+                    price,
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        };
+        let out = render_history_stats(&[history]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3, "{out}");
+        assert_eq!(
+            lines[0],
+            "| Symbol | Start | End | Change | Low | High | Mean | Max Drawdown | Volatility |"
+        );
+        assert_eq!(
+            lines[1],
+            "| --- | ---: | ---: | ---: | ---: | ---: | ---: | ---: | ---: |"
+        );
+        assert!(
+            lines[2].starts_with(
+                "| BTC | $100.00 | $110.00 | +10.00% | $90.00 | $120.00 | $105.00 | -25.00% | "
+            ),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn strip_ansi_removes_color_codes() {
+        assert_eq!(strip_ansi("\x1b[1;32m+1.50%\x1b[0m"), "+1.50%");
+    }
+}
@@ -1,3 +1,17 @@
 pub mod chart;
+pub mod color;
+pub mod copy;
+pub mod export;
+pub mod fmt;
 pub mod json;
+pub mod markdown;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod quiet;
+pub mod sort;
+pub mod status;
+pub mod svg;
 pub mod table;
+#[cfg(feature = "templates")]
+pub mod template;
+pub mod tz;
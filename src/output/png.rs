@@ -0,0 +1,407 @@
+//! PNG chart export for `--export-png`, drawn with plotters' bitmap backend.
+//!
+//! Each series gets its own file unless `--overlay` puts them on one chart as
+//! percent change from their first point. Axis labels use the terminal chart's
+//! date formats and the shared number formatting.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use plotters::prelude::*;
+
+use crate::error::{Error, Result};
+use crate::output::chart::{self, axis_label_format};
+use crate::output::{fmt, table, tz};
+use crate::provider::{PriceHistory, PricePoint};
+
+/// Image size used when `--size` is not given.
+pub const DEFAULT_SIZE: (u32, u32) = (1200, 600);
+/// Smallest image that still fits the axis labels.
+const MIN_SIZE: (u32, u32) = (320, 200);
+const FONT: &str = "sans-serif";
+/// Price levels labeled on the y axis.
+const Y_LABELS: usize = 6;
+
+/// Color scheme for `--theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+struct Palette {
+    background: RGBColor,
+    foreground: RGBColor,
+    grid: RGBColor,
+    series: [RGBColor; 6],
+}
+
+impl Theme {
+    fn palette(self) -> Palette {
+        match self {
+            Self::Light => Palette {
+                background: WHITE,
+                foreground: RGBColor(34, 34, 34),
+                grid: RGBColor(229, 229, 229),
+                series: [
+                    RGBColor(31, 111, 235),
+                    RGBColor(207, 34, 46),
+                    RGBColor(26, 127, 55),
+                    RGBColor(154, 103, 0),
+                    RGBColor(130, 80, 223),
+                    RGBColor(27, 124, 131),
+                ],
+            },
+            Self::Dark => Palette {
+                background: RGBColor(13, 17, 23),
+                foreground: RGBColor(230, 237, 243),
+                grid: RGBColor(48, 54, 61),
+                series: [
+                    RGBColor(88, 166, 255),
+                    RGBColor(255, 123, 114),
+                    RGBColor(63, 185, 80),
+                    RGBColor(210, 153, 34),
+                    RGBColor(188, 140, 255),
+                    RGBColor(57, 197, 207),
+                ],
+            },
+        }
+    }
+}
+
+/// How `--export-png` draws its charts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngOptions {
+    pub width: u32,
+    pub height: u32,
+    pub theme: Theme,
+    /// One combined chart of percent change instead of one file per series.
+    pub overlay: bool,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_SIZE.0,
+            height: DEFAULT_SIZE.1,
+            theme: Theme::default(),
+            overlay: false,
+        }
+    }
+}
+
+/// Parse `--size WIDTHxHEIGHT`, e.g. `1200x600`.
+pub fn parse_size(value: &str) -> std::result::Result<(u32, u32), String> {
+    let invalid = || {
+        format!(
+            "invalid size '{}' -- expected WIDTHxHEIGHT, e.g. 1200x600",
+            value
+        )
+    };
+    let (width, height) = value.trim().split_once(['x', 'X']).ok_or_else(invalid)?;
+    let width: u32 = width.trim().parse().map_err(|_| invalid())?;
+    let height: u32 = height.trim().parse().map_err(|_| invalid())?;
+    if width < MIN_SIZE.0 || height < MIN_SIZE.1 {
+        return Err(format!(
+            "size {}x{} is too small -- the minimum is {}x{}",
+            width, height, MIN_SIZE.0, MIN_SIZE.1
+        ));
+    }
+    Ok((width, height))
+}
+
+/// Files `write_charts` creates: `path` itself for one series or an overlay,
+/// otherwise `path` with a `-<symbol>` suffix per series, e.g. `chart-btc.png`.
+pub fn output_paths(path: &Path, symbols: &[&str], overlay: bool) -> Vec<PathBuf> {
+    if overlay || symbols.len() <= 1 {
+        return vec![path.to_path_buf()];
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    symbols
+        .iter()
+        .map(|symbol| {
+            let symbol: String = symbol
+                .to_lowercase()
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+            path.with_file_name(format!("{}-{}{}", stem, symbol, extension))
+        })
+        .collect()
+}
+
+/// Draw `histories` into PNG files and return their paths.
+///
+/// Series without points are skipped. Existing files are only replaced when
+/// `force` is set, and nothing is written if any of them would be refused.
+pub fn write_charts(
+    path: &Path,
+    histories: &[PriceHistory],
+    range_label: &str,
+    options: &PngOptions,
+    force: bool,
+) -> Result<Vec<PathBuf>> {
+    let histories: Vec<&PriceHistory> = histories
+        .iter()
+        .filter(|history| !history.points.is_empty())
+        .collect();
+    let symbols: Vec<&str> = histories.iter().map(|h| h.symbol.as_str()).collect();
+    let paths = output_paths(path, &symbols, options.overlay);
+    if !force && let Some(existing) = paths.iter().find(|path| path.exists()) {
+        return Err(Error::Config(format!(
+            "{} already exists -- pass --force to overwrite it",
+            existing.display()
+        )));
+    }
+
+    if options.overlay || histories.len() <= 1 {
+        let title = if options.overlay && histories.len() > 1 {
+            format!("Change since start ({})", range_label)
+        } else {
+            format!(
+                "{} Price History ({})",
+                histories.first().map_or("", |h| h.symbol.as_str()),
+                range_label
+            )
+        };
+        draw(&paths[0], &histories, &title, options)?;
+    } else {
+        for (history, path) in histories.iter().zip(&paths) {
+            let title = format!("{} Price History ({})", history.symbol, range_label);
+            draw(path, std::slice::from_ref(history), &title, options)?;
+        }
+    }
+    Ok(paths)
+}
+
+/// Draw one chart; several series are rebased to percent change from their first point.
+fn draw(path: &Path, histories: &[&PriceHistory], title: &str, options: &PngOptions) -> Result<()> {
+    let normalized = histories.len() > 1;
+    let series: Vec<Vec<(i64, f64)>> = histories
+        .iter()
+        .map(|history| {
+            let base = history.points.first().map_or(0.0, |p| p.price);
+            history
+                .points
+                .iter()
+                .map(|p| {
+                    let value = if normalized && base != 0.0 {
+                        (p.price / base - 1.0) * 100.0
+                    } else {
+                        p.price
+                    };
+                    (p.timestamp.timestamp(), value)
+                })
+                .collect()
+        })
+        .collect();
+
+    let all: Vec<(f64, f64)> = series
+        .iter()
+        .flatten()
+        .map(|&(x, y)| (x as f64, y))
+        .collect();
+    let (y_min, y_max) = chart::y_bounds(&all);
+    let x_min = series.iter().flatten().map(|p| p.0).min().unwrap_or(0);
+    let mut x_max = series.iter().flatten().map(|p| p.0).max().unwrap_or(0);
+    if x_max <= x_min {
+        x_max = x_min + 86_400;
+    }
+    let points: Vec<PricePoint> = histories
+        .iter()
+        .flat_map(|history| history.points.iter().cloned())
+        .collect();
+    let label_format = axis_label_format(&points);
+    let currency = histories.first().map_or("", |h| h.currency.as_str());
+
+    let palette = options.theme.palette();
+    let failed = |e: &dyn std::fmt::Display| {
+        Error::Config(format!("cannot write {}: {}", path.display(), e))
+    };
+
+    let root = BitMapBackend::new(path, (options.width, options.height)).into_drawing_area();
+    root.fill(&palette.background).map_err(|e| failed(&e))?;
+    let mut plot = ChartBuilder::on(&root)
+        .caption(title, (FONT, 22).into_font().color(&palette.foreground))
+        .margin(16)
+        .x_label_area_size(36)
+        .y_label_area_size(90)
+        .build_cartesian_2d(x_min..x_max, y_min..y_max)
+        .map_err(|e| failed(&e))?;
+
+    let x_label = |x: &i64| {
+        DateTime::<Utc>::from_timestamp(*x, 0)
+            .map(|ts| tz::format_timestamp(ts, label_format))
+            .unwrap_or_default()
+    };
+    let y_scale = fmt::axis_scale(
+        y_min.abs().max(y_max.abs()),
+        (y_max - y_min) / Y_LABELS as f64,
+    );
+    let y_label = |y: &f64| {
+        if normalized {
+            table::format_percent(*y)
+        } else {
+            fmt::format_axis_value(*y, y_scale)
+        }
+    };
+    plot.configure_mesh()
+        .x_labels(5)
+        .y_labels(Y_LABELS)
+        .x_label_formatter(&x_label)
+        .y_label_formatter(&y_label)
+        .y_desc(if normalized {
+            "%".to_string()
+        } else {
+            fmt::currency_axis_title(currency)
+        })
+        .axis_style(palette.foreground)
+        .label_style((FONT, 14).into_font().color(&palette.foreground))
+        .bold_line_style(palette.grid)
+        .light_line_style(palette.background)
+        .draw()
+        .map_err(|e| failed(&e))?;
+
+    for (idx, (history, points)) in histories.iter().zip(series).enumerate() {
+        let color = palette.series[idx % palette.series.len()];
+        plot.draw_series(LineSeries::new(points, color.stroke_width(2)))
+            .map_err(|e| failed(&e))?
+            .label(history.symbol.as_str())
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+    }
+    if normalized {
+        plot.configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(palette.background)
+            .border_style(palette.grid)
+            .label_font((FONT, 14).into_font().color(&palette.foreground))
+            .draw()
+            .map_err(|e| failed(&e))?;
+    }
+
+    root.present().map_err(|e| failed(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn history(symbol: &str, prices: &[f64]) -> PriceHistory {
+        PriceHistory {
+            symbol: symbol.into(),
+            name: symbol.into(),
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            points: prices
+                .iter()
+                .enumerate()
+                .map(|(day, &price)| PricePoint {
+                    timestamp: Utc
+                        .with_ymd_and_hms(2025, 6, 1 + day as u32, 0, 0, 0)
+                        .unwrap(),
+                    price,
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        }
+    }
+
+    /// Width and height from the IHDR chunk, after checking the PNG signature.
+    fn png_dimensions(path: &Path) -> (u32, u32) {
+        let bytes = std::fs::read(path).unwrap();
+        assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n", "not a PNG");
+        assert_eq!(&bytes[12..16], b"IHDR");
+        let be =
+            |range: std::ops::Range<usize>| u32::from_be_bytes(bytes[range].try_into().unwrap());
+        (be(16..20), be(20..24))
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("pricr-png-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn renders_a_png_of_the_requested_size() {
+        let path = temp_path("single.png");
+        let _ = std::fs::remove_file(&path);
+        let options = PngOptions {
+            width: 640,
+            height: 320,
+            theme: Theme::Dark,
+            overlay: false,
+        };
+
+        let written = write_charts(
+            &path,
+            &[history("BTC", &[100.0, 104.0, 98.0, 110.0])],
+            "2025-06-01..2025-06-04",
+            &options,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(written, vec![path.clone()]);
+        assert_eq!(png_dimensions(&path), (640, 320));
+        assert!(write_charts(&path, &[history("BTC", &[1.0])], "", &options, false).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn several_series_get_one_file_each_unless_overlaid() {
+        let histories = [
+            history("BTC", &[100.0, 110.0]),
+            history("^GSPC", &[50.0, 45.0]),
+        ];
+        let base = temp_path("multi.png");
+        let paths = output_paths(&base, &["BTC", "^GSPC"], false);
+        assert_eq!(
+            paths
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<_>>(),
+            vec![
+                format!("pricr-png-{}-multi-btc.png", std::process::id()),
+                format!("pricr-png-{}-multi-_gspc.png", std::process::id()),
+            ]
+        );
+        assert_eq!(
+            output_paths(&base, &["BTC", "^GSPC"], true),
+            vec![base.clone()]
+        );
+
+        let overlay = PngOptions {
+            overlay: true,
+            ..PngOptions::default()
+        };
+        write_charts(&base, &histories, "range", &overlay, true).unwrap();
+        assert_eq!(png_dimensions(&base), DEFAULT_SIZE);
+        std::fs::remove_file(&base).unwrap();
+    }
+
+    #[test]
+    fn size_parses_width_by_height() {
+        assert_eq!(parse_size("1200x600"), Ok((1200, 600)));
+        assert_eq!(parse_size("800X400"), Ok((800, 400)));
+        assert!(parse_size("1200").is_err());
+        assert!(parse_size("10x10").unwrap_err().contains("too small"));
+    }
+}
@@ -0,0 +1,197 @@
+//! `--quiet` output: one bare value per line, for shell prompts and scripts.
+
+use serde_json::Value;
+
+use crate::calc::Conversion;
+use crate::error::{Error, Result};
+use crate::provider::CoinPrice;
+
+/// Field printed when `--field` is not given.
+pub const DEFAULT_FIELD: &str = "price";
+
+/// `CoinPrice` fields `--field` accepts, named as in `--json` output.
+///
+/// Every serialized field holding a single value; `prices` and `extended` are
+/// nested and left out.
+pub const FIELDS: &[&str] = &[
+    "symbol",
+    "name",
+    "price",
+    "change_24h",
+    "change_7d",
+    "change_30d",
+    "market_cap",
+    "volume_24h",
+    "circulating_supply",
+    "rank",
+    "ath",
+    "ath_date",
+    "atl",
+    "atl_date",
+    "bid",
+    "ask",
+    "since_date",
+    "since_price",
+    "since_change_pct",
+    "delta_pct",
+    "delta_since",
+    "currency",
+    "provider",
+    "attribution",
+    "timestamp",
+    "quote_time_estimated",
+    "converted",
+    "fx_rate",
+];
+
+/// Boolean [`FIELDS`] that `--json` only writes when set.
+const FLAGS: &[&str] = &["quote_time_estimated", "converted"];
+
+/// Parse a `--field` value, accepting only names from [`FIELDS`].
+pub fn parse_field(raw: &str) -> std::result::Result<String, String> {
+    let field = raw.trim().to_lowercase();
+    if FIELDS.contains(&field.as_str()) {
+        Ok(field)
+    } else {
+        Err(format!(
+            "unknown field '{}' (supported: {})",
+            raw.trim(),
+            FIELDS.join(", ")
+        ))
+    }
+}
+
+/// One line per price with the value of `field`, in the order given.
+///
+/// Numbers print in full without grouping or currency symbols, timestamps as in
+/// `--json`, and fields without a value as empty lines so rows stay aligned with
+/// the symbols.
+pub fn render_prices(prices: &[CoinPrice], field: &str) -> Result<String> {
+    let lines = prices
+        .iter()
+        .map(|price| {
+            let row = serde_json::to_value(price)
+                .map_err(|e| Error::Parse(format!("JSON serialize: {}", e)))?;
+            Ok(match row.get(field) {
+                Some(value) => bare_value(value),
+                // Flags are left out of the JSON row while unset.
+                None if FLAGS.contains(&field) => "false".to_string(),
+                None => String::new(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(lines.join("\n"))
+}
+
+/// Print [`render_prices`] to stdout.
+pub fn print_prices(prices: &[CoinPrice], field: &str) -> Result<()> {
+    if !prices.is_empty() {
+        println!("{}", render_prices(prices, field)?);
+    }
+    Ok(())
+}
+
+/// One line per conversion with the converted amount.
+pub fn render_conversions(conversions: &[Conversion]) -> String {
+    conversions
+        .iter()
+        .map(|c| c.to_amount.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print [`render_conversions`] to stdout.
+pub fn print_conversions(conversions: &[Conversion]) {
+    if !conversions.is_empty() {
+        println!("{}", render_conversions(conversions));
+    }
+}
+
+fn bare_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        // `f64` display never switches to exponent notation, unlike JSON numbers.
+        Value::Number(number) => match number.as_f64() {
+            Some(n) if number.is_f64() => n.to_string(),
+            _ => number.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn btc() -> CoinPrice {
+        CoinPrice {
+            symbol: "BTC".into(),
+            name: "Bitcoin".into(),
+            price: 67_231.55,
+            change_24h: Some(-1.25),
+            rank: Some(1),
+            currency: "usd".into(),
+            provider: "coingecko".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prices_print_the_bare_value_of_the_field() {
+        let eth = CoinPrice {
+            symbol: "ETH".into(),
+            price: 0.00000001,
+            change_24h: None,
+            rank: None,
+            ..btc()
+        };
+        let prices = [btc(), eth];
+        assert_eq!(
+            render_prices(&prices, "price").unwrap(),
+            "67231.55\n0.00000001"
+        );
+        assert_eq!(render_prices(&prices, "change_24h").unwrap(), "-1.25\n");
+        assert_eq!(render_prices(&prices, "rank").unwrap(), "1\n");
+        assert_eq!(render_prices(&prices, "symbol").unwrap(), "BTC\nETH");
+        assert_eq!(render_prices(&prices, "converted").unwrap(), "false\nfalse");
+    }
+
+    #[test]
+    fn conversions_print_the_converted_amount() {
+        let conversion = Conversion {
+            from_amount: 100.0,
+            from_currency: "USD".into(),
+            to_symbol: "BTC".into(),
+            to_name: "Bitcoin".into(),
+            to_amount: 0.0015,
+            rate: 66_000.0,
+            provider: "coingecko".into(),
+            timestamp: chrono::Utc::now(),
+        };
+        assert_eq!(render_conversions(&[conversion]), "0.0015");
+    }
+
+    #[test]
+    fn fields_match_the_scalar_price_fields() {
+        assert_eq!(parse_field(" Change_24h ").unwrap(), "change_24h");
+        assert!(
+            parse_field("prices")
+                .unwrap_err()
+                .contains("unknown field 'prices'")
+        );
+
+        let schema = serde_json::to_value(schemars::schema_for!(CoinPrice)).unwrap();
+        let mut properties: Vec<&str> = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !["prices", "extended"].contains(name))
+            .collect();
+        let mut fields = FIELDS.to_vec();
+        properties.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(fields, properties);
+    }
+}
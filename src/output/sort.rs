@@ -0,0 +1,112 @@
+use std::cmp::Ordering;
+
+use crate::provider::CoinPrice;
+
+/// Field used to order price rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Symbol,
+    Price,
+    /// 24h change in percent.
+    Change,
+    MarketCap,
+    /// 24h volume.
+    Volume,
+}
+
+/// Sort prices in place by `key`, ascending unless `descending` is set.
+///
+/// Rows without a value for `key` always go last, whatever the direction, and
+/// ties fall back to ascending symbol order so output is deterministic.
+pub fn sort_prices(prices: &mut [CoinPrice], key: SortKey, descending: bool) {
+    prices.sort_by(|a, b| {
+        let primary = match key {
+            SortKey::Symbol => {
+                let ord = compare_symbols(a, b);
+                if descending { ord.reverse() } else { ord }
+            }
+            SortKey::Price => compare_values(Some(a.price), Some(b.price), descending),
+            SortKey::Change => compare_values(a.change_24h, b.change_24h, descending),
+            SortKey::MarketCap => compare_values(a.market_cap, b.market_cap, descending),
+            SortKey::Volume => compare_values(a.volume_24h, b.volume_24h, descending),
+        };
+        primary.then_with(|| compare_symbols(a, b))
+    });
+}
+
+fn compare_symbols(a: &CoinPrice, b: &CoinPrice) -> Ordering {
+    a.symbol
+        .to_ascii_uppercase()
+        .cmp(&b.symbol.to_ascii_uppercase())
+}
+
+fn compare_values(a: Option<f64>, b: Option<f64>, descending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ord = a.total_cmp(&b);
+            if descending { ord.reverse() } else { ord }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(symbol: &str, value: f64, change_24h: Option<f64>) -> CoinPrice {
+        CoinPrice {
+            symbol: symbol.to_string(),
+            price: value,
+            change_24h,
+            ..Default::default()
+        }
+    }
+
+    fn symbols(prices: &[CoinPrice]) -> Vec<&str> {
+        prices.iter().map(|p| p.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn missing_values_sort_last_in_both_directions() {
+        let mut prices = vec![
+            price("AAA", 1.0, None),
+            price("BBB", 1.0, Some(5.0)),
+            price("CCC", 1.0, Some(-2.0)),
+        ];
+
+        sort_prices(&mut prices, SortKey::Change, false);
+        assert_eq!(symbols(&prices), vec!["CCC", "BBB", "AAA"]);
+
+        sort_prices(&mut prices, SortKey::Change, true);
+        assert_eq!(symbols(&prices), vec!["BBB", "CCC", "AAA"]);
+    }
+
+    #[test]
+    fn ties_fall_back_to_symbol_order() {
+        let mut prices = vec![
+            price("ETH", 10.0, None),
+            price("btc", 10.0, None),
+            price("SOL", 5.0, None),
+        ];
+
+        sort_prices(&mut prices, SortKey::Price, true);
+        assert_eq!(symbols(&prices), vec!["btc", "ETH", "SOL"]);
+
+        sort_prices(&mut prices, SortKey::MarketCap, false);
+        assert_eq!(symbols(&prices), vec!["btc", "ETH", "SOL"]);
+    }
+
+    #[test]
+    fn symbol_sort_respects_direction() {
+        let mut prices = vec![price("ETH", 1.0, None), price("BTC", 2.0, None)];
+
+        sort_prices(&mut prices, SortKey::Symbol, false);
+        assert_eq!(symbols(&prices), vec!["BTC", "ETH"]);
+
+        sort_prices(&mut prices, SortKey::Symbol, true);
+        assert_eq!(symbols(&prices), vec!["ETH", "BTC"]);
+    }
+}
@@ -0,0 +1,244 @@
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::output::{fmt, table};
+use crate::provider::CoinPrice;
+
+/// Single-line status object understood by Waybar's `custom` module.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct WaybarStatus {
+    pub text: String,
+    pub tooltip: String,
+    pub class: String,
+}
+
+/// Build a Waybar status from the first price, with the full table as tooltip.
+///
+/// When `template` is set it replaces the default `BTC $67.2k ▲1.3%` text.
+pub fn waybar_status(prices: &[CoinPrice], template: Option<&str>) -> Option<WaybarStatus> {
+    let first = prices.first()?;
+
+    let text = match template {
+        Some(template) => render_template(template, first),
+        None => default_status_text(first),
+    };
+    let class = match first.change_24h {
+        Some(change) if change < 0.0 => "down",
+        Some(_) => "up",
+        None => "neutral",
+    };
+
+    Some(WaybarStatus {
+        text,
+        tooltip: table::render_table(prices, None, None, &table::DEFAULT_PRICE_COLUMNS),
+        class: class.to_string(),
+    })
+}
+
+/// Print a Waybar status object as one line of JSON to stdout.
+pub fn print_waybar(prices: &[CoinPrice], template: Option<&str>) -> Result<()> {
+    let status = waybar_status(prices, template).ok_or_else(|| Error::no_results_from(""))?;
+    let output = serde_json::to_string(&status)
+        .map_err(|e| Error::Parse(format!("JSON serialize: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
+
+/// Print one templated line per price to stdout.
+pub fn print_template(prices: &[CoinPrice], template: &str) {
+    for price in prices {
+        println!("{}", render_template(template, price));
+    }
+}
+
+/// Substitute `{placeholder}` fields in `template` with values from `price`.
+///
+/// Supported placeholders: `symbol`, `name`, `price`, `price_raw`, `change_24h`,
+/// `change_7d`, `change_30d`, `arrow`, `market_cap`, `currency`, `provider`. Fields without a value render
+/// as empty strings; unknown placeholders are left untouched.
+pub fn render_template(template: &str, price: &CoinPrice) -> String {
+    render_placeholders(template, |key| placeholder_value(key, price))
+}
+
+/// Substitute `{placeholder}` fields in `template` using `lookup`.
+///
+/// Keys `lookup` does not know (`None`) are left untouched, as is an unclosed `{`.
+pub fn render_placeholders(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after_open = &rest[open + 1..];
+
+        let Some(close) = after_open.find('}') else {
+            out.push_str(&rest[open..]);
+            return out;
+        };
+
+        let key = &after_open[..close];
+        match lookup(key) {
+            Some(value) => out.push_str(&value),
+            None => {
+                out.push('{');
+                out.push_str(key);
+                out.push('}');
+            }
+        }
+        rest = &after_open[close + 1..];
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Format a price compactly for status bars (e.g. `$67.2k`, `€1.05M`).
+pub fn format_compact_price(price: f64, currency: &str) -> String {
+    let sym = fmt::currency_symbol(currency).unwrap_or("");
+    let abs = price.abs();
+    if abs >= 1_000_000_000.0 {
+        format!("{}{:.2}B", sym, price / 1_000_000_000.0)
+    } else if abs >= 1_000_000.0 {
+        format!("{}{:.2}M", sym, price / 1_000_000.0)
+    } else if abs >= 1_000.0 {
+        format!("{}{:.1}k", sym, price / 1_000.0)
+    } else {
+        format!("{}{}", sym, fmt::format_price_value(price))
+    }
+}
+
+fn default_status_text(price: &CoinPrice) -> String {
+    let mut text = format!(
+        "{} {}",
+        price.symbol,
+        format_compact_price(price.price, &price.currency)
+    );
+    if let Some(change) = price.change_24h {
+        text.push_str(&format!(" {}{:.1}%", change_arrow(change), change.abs()));
+    }
+    text
+}
+
+fn change_arrow(change: f64) -> &'static str {
+    if change < 0.0 { "▼" } else { "▲" }
+}
+
+fn placeholder_value(key: &str, price: &CoinPrice) -> Option<String> {
+    let value = match key {
+        "symbol" => price.symbol.clone(),
+        "name" => price.name.clone(),
+        "price" => format_compact_price(price.price, &price.currency),
+        "price_raw" => price.price.to_string(),
+        "change_24h" => price
+            .change_24h
+            .map(|c| format!("{:+.2}%", c))
+            .unwrap_or_default(),
+        "change_7d" => price
+            .change_7d
+            .map(|c| format!("{:+.2}%", c))
+            .unwrap_or_default(),
+        "change_30d" => price
+            .change_30d
+            .map(|c| format!("{:+.2}%", c))
+            .unwrap_or_default(),
+        "arrow" => price
+            .change_24h
+            .map(|c| change_arrow(c).to_string())
+            .unwrap_or_default(),
+        "market_cap" => price
+            .market_cap
+            .map(|cap| format_compact_price(cap, &price.currency))
+            .unwrap_or_default(),
+        "currency" => price.currency.clone(),
+        "provider" => price.provider.clone(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(change_24h: Option<f64>, market_cap: Option<f64>) -> CoinPrice {
+        CoinPrice {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            price: 67_231.55,
+            change_24h,
+            change_7d: None,
+            change_30d: None,
+            market_cap,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(1_700_000_000, 0)
+                .expect("valid timestamp"),
+            quote_time_estimated: false,
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        }
+    }
+
+    #[test]
+    fn render_template_substitutes_known_fields() {
+        let rendered = render_template(
+            "{symbol} {price} {change_24h} via {provider}",
+            &price(Some(1.3), None),
+        );
+        assert_eq!(rendered, "BTC $67.2k +1.30% via CoinGecko");
+    }
+
+    #[test]
+    fn render_template_renders_missing_fields_as_empty() {
+        let rendered = render_template(
+            "{symbol}|{change_24h}|{arrow}|{market_cap}",
+            &price(None, None),
+        );
+        assert_eq!(rendered, "BTC|||");
+    }
+
+    #[test]
+    fn render_template_keeps_unknown_and_unterminated_placeholders() {
+        let rendered = render_template("{symbol} {volume} {price", &price(None, None));
+        assert_eq!(rendered, "BTC {volume} {price");
+    }
+
+    #[test]
+    fn format_compact_price_uses_suffixes() {
+        assert_eq!(format_compact_price(67_231.55, "usd"), "$67.2k");
+        assert_eq!(format_compact_price(1_050_000.0, "eur"), "\u{20ac}1.05M");
+        assert_eq!(format_compact_price(2.5e12, "usd"), "$2500.00B");
+        assert_eq!(format_compact_price(0.5, "xyz"), "0.5000");
+    }
+
+    #[test]
+    fn waybar_status_uses_first_price_and_direction_class() {
+        let prices = vec![price(Some(-2.04), Some(1.3e12))];
+        let status = waybar_status(&prices, None).expect("status");
+
+        assert_eq!(status.text, "BTC $67.2k ▼2.0%");
+        assert_eq!(status.class, "down");
+        assert!(status.tooltip.contains("Bitcoin"));
+
+        let templated = waybar_status(&prices, Some("{symbol}:{price}")).expect("status");
+        assert_eq!(templated.text, "BTC:$67.2k");
+        assert!(waybar_status(&[], None).is_none());
+    }
+}
@@ -0,0 +1,218 @@
+//! Standalone SVG line charts for `--export-html`.
+//!
+//! The plot uses the terminal chart's scaling: points are spaced by index, the
+//! price axis comes from [`chart::y_bounds`], and axis labels use the same date
+//! and number formats.
+
+use crate::output::chart::{self, axis_label_format};
+use crate::output::{fmt, tz};
+use crate::provider::PriceHistory;
+
+/// Width of the SVG viewport in pixels.
+pub const WIDTH: f64 = 720.0;
+/// Height of the SVG viewport in pixels.
+pub const HEIGHT: f64 = 320.0;
+
+const MARGIN_LEFT: f64 = 80.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+/// Labeled price levels, including the bottom and top of the axis.
+const Y_TICKS: usize = 5;
+
+/// Render `history` as an SVG line chart; empty for a series without points.
+pub fn render_history_svg(history: &PriceHistory) -> String {
+    if history.points.is_empty() {
+        return String::new();
+    }
+
+    let points: Vec<(f64, f64)> = history
+        .points
+        .iter()
+        .enumerate()
+        .map(|(idx, p)| (idx as f64, p.price))
+        .collect();
+    let x_max = (points.len().saturating_sub(1) as f64).max(1.0);
+    let (y_min, y_max) = chart::y_bounds(&points);
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let x_at = |x: f64| MARGIN_LEFT + x / x_max * plot_width;
+    let y_at = |y: f64| MARGIN_TOP + (y_max - y) / (y_max - y_min) * plot_height;
+    let bottom = MARGIN_TOP + plot_height;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {WIDTH} {HEIGHT}\" \
+         width=\"{WIDTH}\" height=\"{HEIGHT}\" role=\"img\">\n\
+         <title>{} Price History</title>\n",
+        escape_xml(&history.symbol)
+    );
+
+    let ticks: Vec<f64> = (0..Y_TICKS)
+        .map(|tick| y_min + (y_max - y_min) * tick as f64 / (Y_TICKS - 1) as f64)
+        .collect();
+    svg.push_str(&format!(
+        "<text class=\"unit\" x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"end\" font-size=\"11\">{}</text>\n",
+        MARGIN_LEFT - 6.0,
+        MARGIN_TOP - 8.0,
+        escape_xml(&fmt::currency_axis_title(&history.currency))
+    ));
+    for (value, label) in ticks.iter().zip(fmt::format_axis_labels(&ticks)) {
+        let y = y_at(*value);
+        svg.push_str(&format!(
+            "<line class=\"grid\" x1=\"{MARGIN_LEFT}\" y1=\"{y:.2}\" x2=\"{:.2}\" y2=\"{y:.2}\" \
+             stroke=\"#e5e5e5\"/>\n\
+             <text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"end\" font-size=\"11\">{}</text>\n",
+            WIDTH - MARGIN_RIGHT,
+            MARGIN_LEFT - 6.0,
+            y + 4.0,
+            escape_xml(&label)
+        ));
+    }
+
+    let label_format = axis_label_format(&history.points);
+    let last = history.points.len() - 1;
+    let mut x_ticks = vec![0, last / 2, last];
+    x_ticks.dedup();
+    for idx in x_ticks {
+        let anchor = match idx {
+            0 => "start",
+            _ if idx == last => "end",
+            _ => "middle",
+        };
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"{anchor}\" font-size=\"11\">{}</text>\n",
+            x_at(idx as f64),
+            bottom + 18.0,
+            escape_xml(&tz::format_timestamp(
+                history.points[idx].timestamp,
+                label_format
+            ))
+        ));
+    }
+
+    svg.push_str(&format!(
+        "<line class=\"axis\" x1=\"{MARGIN_LEFT}\" y1=\"{MARGIN_TOP}\" x2=\"{MARGIN_LEFT}\" \
+         y2=\"{bottom:.2}\" stroke=\"#999\"/>\n\
+         <line class=\"axis\" x1=\"{MARGIN_LEFT}\" y1=\"{bottom:.2}\" x2=\"{:.2}\" y2=\"{bottom:.2}\" \
+         stroke=\"#999\"/>\n",
+        WIDTH - MARGIN_RIGHT
+    ));
+
+    let polyline: Vec<String> = points
+        .iter()
+        .map(|&(x, y)| format!("{:.2},{:.2}", x_at(x), y_at(y)))
+        .collect();
+    svg.push_str(&format!(
+        "<polyline fill=\"none\" stroke=\"#1f6feb\" stroke-width=\"1.5\" points=\"{}\"/>\n",
+        polyline.join(" ")
+    ));
+
+    for event in &history.events {
+        if let Some(idx) = history
+            .points
+            .iter()
+            .position(|p| p.timestamp >= event.timestamp)
+        {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\" fill=\"#cf222e\"/>\n",
+                x_at(points[idx].0),
+                y_at(points[idx].1)
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape text for XML and HTML content and attribute values.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::PricePoint;
+    use chrono::{TimeZone, Utc};
+
+    fn history(symbol: &str, prices: &[f64]) -> PriceHistory {
+        PriceHistory {
+            symbol: symbol.into(),
+            name: symbol.into(),
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            points: prices
+                .iter()
+                .enumerate()
+                .map(|(day, &price)| PricePoint {
+                    timestamp: Utc
+                        .with_ymd_and_hms(2025, 6, 1 + day as u32, 0, 0, 0)
+                        .unwrap(),
+                    price,
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        }
+    }
+
+    #[test]
+    fn svg_parses_and_plots_every_point() {
+        let svg = render_history_svg(&history("A&B", &[100.0, 110.0, 95.0, 120.0, 118.0]));
+        let doc = roxmltree::Document::parse(&svg).expect("valid XML");
+        let root = doc.root_element();
+        assert_eq!(root.tag_name().name(), "svg");
+
+        let polyline = root
+            .descendants()
+            .find(|node| node.has_tag_name("polyline"))
+            .unwrap();
+        let coords: Vec<(f64, f64)> = polyline
+            .attribute("points")
+            .unwrap()
+            .split(' ')
+            .map(|pair| {
+                let (x, y) = pair.split_once(',').unwrap();
+                (x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(coords.len(), 5);
+        assert_eq!(coords[0].0, MARGIN_LEFT);
+        assert_eq!(coords[4].0, WIDTH - MARGIN_RIGHT);
+        // Higher prices sit higher up, i.e. at smaller y.
+        assert!(coords[3].1 < coords[2].1);
+
+        let title = root
+            .descendants()
+            .find(|node| node.has_tag_name("title"))
+            .unwrap();
+        assert_eq!(title.text(), Some("A&B Price History"));
+        let labels: Vec<&str> = root
+            .descendants()
+            .filter(|node| node.has_tag_name("text"))
+            .filter_map(|node| node.text())
+            .collect();
+        assert!(labels.contains(&"2025-06-01"), "{labels:?}");
+        assert!(labels.contains(&"2025-06-05"), "{labels:?}");
+    }
+
+    #[test]
+    fn single_point_and_empty_series() {
+        let svg = render_history_svg(&history("BTC", &[42.0]));
+        let doc = roxmltree::Document::parse(&svg).expect("valid XML");
+        let polyline = doc
+            .descendants()
+            .find(|node| node.has_tag_name("polyline"))
+            .unwrap();
+        assert_eq!(polyline.attribute("points").unwrap().split(' ').count(), 1);
+
+        assert_eq!(render_history_svg(&history("BTC", &[])), "");
+    }
+}
@@ -1,54 +1,410 @@
 use colored::Colorize;
+use tabled::builder::Builder;
 use tabled::settings::Style;
 use tabled::{Table, Tabled};
 
-use crate::calc::{self, Conversion};
-use crate::output::chart;
-use crate::provider::{CoinPrice, HistoryInterval, PriceHistory, TickerMatch};
+use crate::calc::stats::{self, Comparison, CorrelationMatrix, HistoryStats, RiskMetrics};
+use crate::calc::{self, Conversion, Investment, PortfolioValue};
+use crate::output::chart::{self, ChartSize};
+use crate::output::color;
+use crate::output::fmt::{self, format_price};
+use crate::output::tz;
+use crate::provider::cache::CacheEntry;
+use crate::provider::health::{HealthStatus, ProviderHealth};
+use crate::provider::{
+    Capability, CoinPrice, GlobalMetrics, HistoryEvent, HistoryEventKind, HistoryInterval,
+    MarketTicker, PriceHistory, ProviderInfo, TickerMatch, TrendingCoin,
+};
+use crate::state::QuotaUsage;
+use crate::timings::Timings;
 
-#[derive(Tabled)]
-struct PriceRow {
-    #[tabled(rename = "Symbol")]
-    symbol: String,
-    #[tabled(rename = "Name")]
-    name: String,
-    #[tabled(rename = "Price")]
-    price: String,
-    #[tabled(rename = "24h Change")]
-    change_24h: String,
-    #[tabled(rename = "Market Cap")]
-    market_cap: String,
-    #[tabled(rename = "Provider")]
-    provider: String,
+/// A column of the price table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceColumn {
+    Symbol,
+    Name,
+    Price,
+    /// Price in the n-th `--currencies` code, headed by the code.
+    PriceIn(usize),
+    Change24h,
+    Change7d,
+    Change30d,
+    /// Sparkline of the recent history; `n/a` when no history matches.
+    Trend,
+    MarketCap,
+    Volume,
+    /// Circulating supply in coin units.
+    Supply,
+    /// Market cap rank.
+    Rank,
+    Provider,
+    /// Equity pre-market price.
+    PreMarket,
+    /// Equity post-market (after-hours) price.
+    PostMarket,
+    /// 52-week low to high.
+    Range52w,
+    /// All-time high price.
+    Ath,
+    /// Date the all-time high was set.
+    AthDate,
+    /// Percent drawdown from the all-time high.
+    FromAth,
+    /// All-time low price.
+    Atl,
+    /// Best bid.
+    Bid,
+    /// Best ask.
+    Ask,
+    /// Bid/ask spread as a percentage of the mid price.
+    Spread,
+    /// Closing price on the `--since` date.
+    SincePrice,
+    /// Percent change since the `--since` date.
+    SinceChange,
+    /// Percent change since the previous `--delta` run, e.g. `+1.20% since 14:05`.
+    Delta,
+    /// Held amount from `--amount`.
+    Amount,
+    /// Held amount times price.
+    Value,
+    /// Time since the quote was taken, e.g. `3h ago`.
+    Age,
+}
+
+impl PriceColumn {
+    fn header(self) -> &'static str {
+        match self {
+            Self::Symbol => "Symbol",
+            Self::Name => "Name",
+            Self::Price | Self::PriceIn(_) => "Price",
+            Self::Change24h => "24h Change",
+            Self::Change7d => "7d Change",
+            Self::Change30d => "30d Change",
+            Self::Trend => "7d Trend",
+            Self::MarketCap => "Market Cap",
+            Self::Volume => "24h Volume",
+            Self::Supply => "Supply",
+            Self::Rank => "Rank",
+            Self::Provider => "Provider",
+            Self::PreMarket => "Pre-Market",
+            Self::PostMarket => "Post-Market",
+            Self::Range52w => "52W Range",
+            Self::Ath => "ATH",
+            Self::AthDate => "ATH Date",
+            Self::FromAth => "From ATH",
+            Self::Atl => "ATL",
+            Self::Bid => "Bid",
+            Self::Ask => "Ask",
+            Self::Spread => "Spread%",
+            Self::SincePrice => "Price on",
+            Self::SinceChange => "Change since",
+            Self::Delta => "Since Last Run",
+            Self::Amount => "Amount",
+            Self::Value => "Value",
+            Self::Age => "Age",
+        }
+    }
+
+    /// Whether the column holds numbers, which markdown output right-aligns.
+    pub fn is_numeric(self) -> bool {
+        !matches!(
+            self,
+            Self::Symbol | Self::Name | Self::Trend | Self::Provider | Self::AthDate | Self::Age
+        )
+    }
+
+    /// Header text; the `--since` columns name the reference date of `prices`.
+    pub fn label(self, prices: &[CoinPrice]) -> String {
+        match (self, prices.iter().find_map(|p| p.since_date)) {
+            (Self::SincePrice, Some(date)) => format!("Price on {}", date),
+            (Self::SinceChange, Some(date)) => format!("Change since {} %", date),
+            (Self::PriceIn(slot), _) => prices.iter().find_map(|p| p.prices.get(slot)).map_or_else(
+                || self.header().to_string(),
+                |(currency, _)| currency.clone(),
+            ),
+            _ => self.header().to_string(),
+        }
+    }
+}
+
+/// Columns shown by default: symbol, name, price, 24h change, market cap, provider.
+pub const DEFAULT_PRICE_COLUMNS: [PriceColumn; 6] = [
+    PriceColumn::Symbol,
+    PriceColumn::Name,
+    PriceColumn::Price,
+    PriceColumn::Change24h,
+    PriceColumn::MarketCap,
+    PriceColumn::Provider,
+];
+
+/// Columns for the `--top` market overview.
+pub const TOP_COINS_COLUMNS: [PriceColumn; 6] = [
+    PriceColumn::Rank,
+    PriceColumn::Symbol,
+    PriceColumn::Name,
+    PriceColumn::Price,
+    PriceColumn::Change24h,
+    PriceColumn::MarketCap,
+];
+
+/// Default layout with `changes` in place of the 24h change column, plus optional
+/// trend, extended-quote, all-time-high, and bid/ask columns after them.
+pub fn price_columns(
+    changes: &[PriceColumn],
+    trend: bool,
+    extended: bool,
+    ath: bool,
+    spread: bool,
+) -> Vec<PriceColumn> {
+    let mut columns = vec![PriceColumn::Symbol, PriceColumn::Name, PriceColumn::Price];
+    columns.extend_from_slice(changes);
+    if trend {
+        columns.push(PriceColumn::Trend);
+    }
+    if extended {
+        columns.extend([
+            PriceColumn::PreMarket,
+            PriceColumn::PostMarket,
+            PriceColumn::Range52w,
+        ]);
+    }
+    if ath {
+        columns.extend([PriceColumn::Ath, PriceColumn::AthDate, PriceColumn::FromAth]);
+    }
+    if spread {
+        columns.extend([PriceColumn::Bid, PriceColumn::Ask, PriceColumn::Spread]);
+    }
+    columns.extend([PriceColumn::MarketCap, PriceColumn::Provider]);
+    columns
 }
 
 /// Print prices as a styled table to stdout.
-pub fn print_table(prices: &[CoinPrice]) {
-    let rows: Vec<PriceRow> = prices
-        .iter()
-        .map(|p| {
-            let change_str = match p.change_24h {
-                Some(c) if c >= 0.0 => format!("+{:.2}%", c).green().to_string(),
-                Some(c) => format!("{:.2}%", c).red().to_string(),
-                None => "-".dimmed().to_string(),
-            };
+///
+/// `histories` feeds the trend column; symbols without a matching history show `n/a`.
+/// `portfolio` feeds the amount and value columns and adds a total line below the table.
+pub fn print_table(
+    prices: &[CoinPrice],
+    histories: Option<&[PriceHistory]>,
+    portfolio: Option<&PortfolioValue>,
+    columns: &[PriceColumn],
+) {
+    println!("{}", render_table(prices, histories, portfolio, columns));
+    if let Some(portfolio) = portfolio {
+        println!("{}", format_portfolio_total(portfolio));
+    }
+    if let Some(attribution) = format_attribution(prices) {
+        println!("{}", attribution);
+    }
+}
 
-            PriceRow {
-                symbol: p.symbol.clone().bold().to_string(),
-                name: p.name.clone(),
-                price: format_price(p.price, &p.currency),
-                change_24h: change_str,
-                market_cap: match p.market_cap {
-                    Some(cap) => format_market_cap(cap, &p.currency),
-                    None => "-".to_string(),
-                },
-                provider: p.provider.clone().dimmed().to_string(),
+/// `Data by CoinGecko · Data by CoinMarketCap`, one credit per provider that asks for
+/// one; `None` when no row carries an attribution.
+pub fn format_attribution(prices: &[CoinPrice]) -> Option<String> {
+    let lines = crate::provider::attributions(prices);
+    (!lines.is_empty()).then(|| lines.join(" \u{b7} ").dimmed().to_string())
+}
+
+/// `Total: $39,000.00`, noting rows left out because they are quoted in another currency.
+pub fn format_portfolio_total(portfolio: &PortfolioValue) -> String {
+    let mut line = format!(
+        "{} {}",
+        "Total:".bold(),
+        format_price(portfolio.total, &portfolio.currency).bold()
+    );
+    if !portfolio.excluded.is_empty() {
+        line.push_str(
+            &format!(
+                " (excludes {}, not quoted in {})",
+                portfolio.excluded.join(", "),
+                portfolio.currency
+            )
+            .dimmed()
+            .to_string(),
+        );
+    }
+    line
+}
+
+/// Render prices as a styled table string with the given columns.
+pub fn render_table(
+    prices: &[CoinPrice],
+    histories: Option<&[PriceHistory]>,
+    portfolio: Option<&PortfolioValue>,
+    columns: &[PriceColumn],
+) -> String {
+    let mut builder = Builder::default();
+    builder.push_record(columns.iter().map(|c| c.label(prices)));
+
+    for p in prices {
+        builder.push_record(
+            columns
+                .iter()
+                .map(|&column| price_cell(column, p, histories, portfolio)),
+        );
+    }
+
+    builder.build().with(Style::rounded()).to_string()
+}
+
+/// One styled cell of the price table.
+pub fn price_cell(
+    column: PriceColumn,
+    p: &CoinPrice,
+    histories: Option<&[PriceHistory]>,
+    portfolio: Option<&PortfolioValue>,
+) -> String {
+    let extended = p.extended.as_ref();
+    match column {
+        PriceColumn::Symbol => p.symbol.clone().bold().to_string(),
+        PriceColumn::Name => p.name.clone(),
+        PriceColumn::Price => format_price(p.price, &p.currency),
+        PriceColumn::PriceIn(slot) => match p.prices.get(slot) {
+            Some((currency, price)) => format_quote(*price, currency),
+            None => NO_QUOTE.dimmed().to_string(),
+        },
+        PriceColumn::Change24h => format_change(p.change_24h),
+        PriceColumn::Change7d => format_change(p.change_7d),
+        PriceColumn::Change30d => format_change(p.change_30d),
+        PriceColumn::Trend => histories
+            .unwrap_or_default()
+            .iter()
+            .find(|h| h.symbol.eq_ignore_ascii_case(&p.symbol))
+            .map(|h| chart::render_sparkline(&h.points))
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "n/a".dimmed().to_string()),
+        PriceColumn::MarketCap => match p.market_cap {
+            Some(cap) => format_market_cap(cap, &p.currency),
+            None => "-".to_string(),
+        },
+        PriceColumn::Volume => match p.volume_24h {
+            Some(volume) => format_market_cap(volume, &p.currency),
+            None => "-".to_string(),
+        },
+        PriceColumn::Supply => match p.circulating_supply {
+            Some(supply) => fmt::format_compact(supply),
+            None => "-".to_string(),
+        },
+        PriceColumn::Rank => match p.rank {
+            Some(rank) => format!("#{}", rank),
+            None => "-".to_string(),
+        },
+        PriceColumn::Provider => p.provider.clone().dimmed().to_string(),
+        PriceColumn::PreMarket => {
+            format_optional_price(extended.and_then(|e| e.pre_market_price), &p.currency)
+        }
+        PriceColumn::PostMarket => {
+            format_optional_price(extended.and_then(|e| e.post_market_price), &p.currency)
+        }
+        PriceColumn::Range52w => {
+            match extended.and_then(|e| e.fifty_two_week_low.zip(e.fifty_two_week_high)) {
+                Some((low, high)) => format!(
+                    "{} - {}",
+                    format_price(low, &p.currency),
+                    format_price(high, &p.currency)
+                ),
+                None => String::new(),
             }
-        })
-        .collect();
+        }
+        PriceColumn::Ath => format_optional_price(p.ath, &p.currency),
+        PriceColumn::AthDate => p
+            .ath_date
+            .map(|date| tz::format_timestamp(date, "%Y-%m-%d"))
+            .unwrap_or_default(),
+        PriceColumn::FromAth => match p.ath.filter(|&ath| ath > 0.0) {
+            Some(ath) => format_change(Some((p.price - ath) / ath * 100.0)),
+            None => String::new(),
+        },
+        PriceColumn::Atl => format_optional_price(p.atl, &p.currency),
+        PriceColumn::Bid => format_quote(p.bid, &p.currency),
+        PriceColumn::Ask => format_quote(p.ask, &p.currency),
+        PriceColumn::Spread => match spread_percent(p.bid, p.ask) {
+            Some(spread) => format!("{}%", fmt::format_fixed(spread, 3)),
+            None => NO_QUOTE.dimmed().to_string(),
+        },
+        PriceColumn::SincePrice => format_optional_price(p.since_price, &p.currency),
+        PriceColumn::SinceChange => match p.since_price {
+            Some(_) => format_change(p.since_change_pct),
+            None => String::new(),
+        },
+        PriceColumn::Delta => match (p.delta_pct, p.delta_since) {
+            (Some(pct), Some(since)) => format_delta(pct, since, chrono::Utc::now()),
+            _ => NO_QUOTE.dimmed().to_string(),
+        },
+        PriceColumn::Amount => portfolio
+            .and_then(|portfolio| portfolio.position(&p.symbol))
+            .map(|position| fmt::format_amount(position.amount))
+            .unwrap_or_default(),
+        PriceColumn::Value => format_optional_price(
+            portfolio
+                .and_then(|portfolio| portfolio.position(&p.symbol))
+                .and_then(|position| position.value),
+            &p.currency,
+        ),
+        PriceColumn::Age => format_quote_age(p, chrono::Utc::now()),
+    }
+}
 
-    let table = Table::new(rows).with(Style::rounded()).to_string();
-    println!("{}", table);
+/// `3h ago`, yellow when stale; `n/a` when the provider gave no quote time.
+fn format_quote_age(price: &CoinPrice, now: chrono::DateTime<chrono::Utc>) -> String {
+    if price.quote_time_estimated {
+        return "n/a".dimmed().to_string();
+    }
+    let age = now - price.timestamp;
+    color::paint_age(format!("{} ago", fmt::format_age(age)), age).to_string()
+}
+
+/// `+1.20% since 14:05`; the date is added once the previous run was on another day.
+fn format_delta(
+    change_pct: f64,
+    since: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let same_day = tz::format_timestamp(since, "%Y-%m-%d") == tz::format_timestamp(now, "%Y-%m-%d");
+    let format = if same_day { "%H:%M" } else { "%Y-%m-%d %H:%M" };
+    format!(
+        "{} since {}",
+        format_change(Some(change_pct)),
+        tz::format_timestamp(since, format)
+    )
+}
+
+/// Shown in bid/ask columns for providers without order-book data, and in the
+/// `--delta` column before there is a previous run.
+const NO_QUOTE: &str = "\u{2014}";
+
+fn format_quote(price: Option<f64>, currency: &str) -> String {
+    match price {
+        Some(price) => format_price(price, currency),
+        None => NO_QUOTE.dimmed().to_string(),
+    }
+}
+
+/// `(ask - bid) / mid` in percent; `None` unless both sides are quoted and not crossed.
+fn spread_percent(bid: Option<f64>, ask: Option<f64>) -> Option<f64> {
+    let (bid, ask) = bid.zip(ask)?;
+    let mid = (bid + ask) / 2.0;
+    (mid > 0.0 && ask >= bid).then(|| (ask - bid) / mid * 100.0)
+}
+
+/// Blank when missing, so crypto rows leave equity-only columns empty.
+fn format_optional_price(price: Option<f64>, currency: &str) -> String {
+    price
+        .map(|value| format_price(value, currency))
+        .unwrap_or_default()
+}
+
+fn format_change(change: Option<f64>) -> String {
+    match change {
+        Some(c) => color::paint_change(format_percent(c), c).to_string(),
+        None => "-".dimmed().to_string(),
+    }
+}
+
+/// Signed percentage with two decimals, e.g. `+1.50%`.
+pub fn format_percent(change: f64) -> String {
+    let sign = if change >= 0.0 { "+" } else { "" };
+    format!("{}{}%", sign, fmt::format_fixed(change, 2))
 }
 
 #[derive(Tabled)]
@@ -65,71 +421,84 @@ struct ConversionRow {
     provider: String,
 }
 
+/// Print a yellow warning to stderr listing symbols that returned no price.
+pub fn print_missing_warning(missing: &[String]) {
+    eprintln!(
+        "{}",
+        format!("warning: no price found for {}", missing.join(", ")).yellow()
+    );
+}
+
+/// Print a yellow warning to stderr listing symbols without a `--since` price.
+pub fn print_since_warning(date: chrono::NaiveDate, symbols: &[String]) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: no price on {} for {} -- the provider may not serve history",
+            date,
+            symbols.join(", ")
+        )
+        .yellow()
+    );
+}
+
+/// Print a yellow warning to stderr when `--copy` found no usable clipboard.
+pub fn print_clipboard_warning(reason: &dyn std::fmt::Display) {
+    eprintln!(
+        "{}",
+        format!("warning: could not copy to the clipboard: {}", reason).yellow()
+    );
+}
+
+/// Print a yellow warning to stderr when the OS keyring could not be read for a provider's key.
+pub fn print_keyring_warning(provider_id: &str, reason: &dyn std::fmt::Display) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: could not read the {} API key from the OS keyring, using other sources: {}",
+            provider_id, reason
+        )
+        .yellow()
+    );
+}
+
+/// Print a yellow warning to stderr listing fiat targets no rate source covers.
+pub fn print_skipped_fiat_warning(skipped: &[String]) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: no exchange rate available for {}",
+            skipped.join(", ")
+        )
+        .yellow()
+    );
+}
+
+/// Print a yellow warning to stderr listing series with gaps `--fill` only partly filled.
+pub fn print_fill_cap_warning(symbols: &[String]) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: gaps longer than {} days were only partly filled for {}",
+            calc::MAX_FILLED_DAYS,
+            symbols.join(", ")
+        )
+        .yellow()
+    );
+}
+
 /// Print fiat-to-crypto conversions as a styled table to stdout.
 pub fn print_conversions_table(conversions: &[Conversion]) {
     let rows: Vec<ConversionRow> = conversions
         .iter()
         .map(|c| {
-            let from_is_fiat = calc::is_known_fiat(&c.from_currency);
-            let to_is_fiat = calc::is_known_fiat(&c.to_symbol);
-
-            let amount = if from_is_fiat {
-                let from_sym = currency_symbol(&c.from_currency);
-                format!("{}{}", from_sym, format_with_commas(c.from_amount, 2))
-            } else {
-                format_crypto_amount(c.from_amount, &c.from_currency)
-            };
-
-            let result = if to_is_fiat {
-                let to_sym = currency_symbol(&c.to_symbol);
-                format!("{}{}", to_sym, format_with_commas(c.to_amount, 2))
-            } else {
-                format_crypto_amount(c.to_amount, &c.to_symbol)
-            };
-
-            let rate = if from_is_fiat && !to_is_fiat {
-                // fiat->crypto: "1 XMR = €294.52"
-                let from_sym = currency_symbol(&c.from_currency);
-                format!(
-                    "1 {} = {}{}",
-                    c.to_symbol.to_uppercase(),
-                    from_sym,
-                    format_with_commas(c.rate, 2)
-                )
-            } else if !from_is_fiat && to_is_fiat {
-                // crypto->fiat: "1 XMR = €294.52"
-                let to_sym = currency_symbol(&c.to_symbol);
-                format!(
-                    "1 {} = {}{}",
-                    c.from_currency.to_uppercase(),
-                    to_sym,
-                    format_with_commas(c.rate, 2)
-                )
-            } else if from_is_fiat && to_is_fiat {
-                // fiat->fiat: "1 EUR = $1.08"
-                let from_sym = currency_symbol(&c.from_currency);
-                format!(
-                    "1 {} = {}{}",
-                    c.to_symbol.to_uppercase(),
-                    from_sym,
-                    format_with_commas(c.rate, 2)
-                )
-            } else {
-                // crypto->crypto: "1 BTC = 15.23 ETH"
-                format!(
-                    "1 {} = {} {}",
-                    c.from_currency.to_uppercase(),
-                    format_with_commas(c.rate, 6),
-                    c.to_symbol.to_uppercase()
-                )
-            };
-
+            let [amount, result, rate, provider] = conversion_cells(c);
             ConversionRow {
                 amount,
                 arrow: "->".to_string(),
                 result,
                 rate,
-                provider: c.provider.clone().dimmed().to_string(),
+                provider,
             }
         })
         .collect();
@@ -138,32 +507,85 @@ pub fn print_conversions_table(conversions: &[Conversion]) {
     println!("{}", table);
 }
 
+/// Amount, result, rate, and provider cells of a conversion row.
+pub fn conversion_cells(c: &Conversion) -> [String; 4] {
+    let from_is_fiat = calc::is_known_fiat(&c.from_currency);
+    let to_is_fiat = calc::is_known_fiat(&c.to_symbol);
+
+    let amount = if from_is_fiat {
+        format_price(c.from_amount, &c.from_currency)
+    } else {
+        format_crypto_amount(c.from_amount, &c.from_currency)
+    };
+
+    let result = if to_is_fiat {
+        format_price(c.to_amount, &c.to_symbol)
+    } else {
+        format_crypto_amount(c.to_amount, &c.to_symbol)
+    };
+
+    let rate = if from_is_fiat && !to_is_fiat {
+        // fiat->crypto: "1 XMR = €294.52"
+        format!(
+            "1 {} = {}",
+            c.to_symbol.to_uppercase(),
+            format_price(c.rate, &c.from_currency)
+        )
+    } else if !from_is_fiat && to_is_fiat {
+        // crypto->fiat: "1 XMR = €294.52"
+        format!(
+            "1 {} = {}",
+            c.from_currency.to_uppercase(),
+            format_price(c.rate, &c.to_symbol)
+        )
+    } else if from_is_fiat && to_is_fiat {
+        // fiat->fiat: "1 EUR = $1.08"
+        format!(
+            "1 {} = {}",
+            c.to_symbol.to_uppercase(),
+            format_price(c.rate, &c.from_currency)
+        )
+    } else {
+        // crypto->crypto: "1 BTC = 15.23 ETH"
+        format!(
+            "1 {} = {}",
+            c.from_currency.to_uppercase(),
+            format_crypto_amount(c.rate, &c.to_symbol)
+        )
+    };
+
+    [
+        amount,
+        result,
+        rate,
+        c.provider.clone().dimmed().to_string(),
+    ]
+}
+
+/// Note for a series the provider could only deliver at a coarser granularity.
+fn granularity_notice(history: &PriceHistory) -> Option<String> {
+    history.granularity.map(|granularity| {
+        format!(
+            "only {} data available for this range",
+            granularity.as_str()
+        )
+    })
+}
+
 /// Print ASCII charts for historical price series.
 pub fn print_history_charts(
     histories: &[PriceHistory],
     range_label: &str,
     sampling: HistoryInterval,
+    size: ChartSize,
 ) {
     for history in histories {
         if history.points.is_empty() {
             continue;
         }
 
-        let prices: Vec<f64> = history.points.iter().map(|p| p.price).collect();
-        let start = prices[0];
-        let end = prices[prices.len() - 1];
-        let low = prices.iter().copied().fold(f64::INFINITY, f64::min);
-        let high = prices.iter().copied().fold(f64::NEG_INFINITY, f64::max);
-        let change_pct = if start.abs() > f64::EPSILON {
-            ((end - start) / start) * 100.0
-        } else {
-            0.0
-        };
-
-        let trend = if change_pct >= 0.0 {
-            format!("+{change_pct:.2}%").green().to_string()
-        } else {
-            format!("{change_pct:.2}%").red().to_string()
+        let Some(stats) = stats::summarize(&history.points) else {
+            continue;
         };
 
         println!(
@@ -174,18 +596,21 @@ pub fn print_history_charts(
             range_label
         );
         println!("Sampling: {}", sampling.as_str());
+        if let Some(notice) = granularity_notice(history) {
+            println!("{}", notice.yellow());
+        }
         println!(
-            "Start: {}  End: {}  Change: {}",
-            format_price(start, &history.currency),
-            format_price(end, &history.currency),
-            trend
-        );
-        println!(
-            "Low:   {}  High: {}",
-            format_price(low, &history.currency),
-            format_price(high, &history.currency)
+            "{}",
+            chart::render_history_chart(history, size.width, size.height)
         );
-        println!("{}", chart::render_history_chart(history, 96, 18));
+        println!("{}", render_extremes_callout(&stats, history));
+        println!("{}", render_history_stats(&stats, &history.currency));
+        if !history.events.is_empty() {
+            println!("Events (█):");
+            for event in &history.events {
+                println!("  {}", format_history_event(event, &history.currency));
+            }
+        }
         println!("Provider: {}", history.provider.dimmed());
         println!();
     }
@@ -222,72 +647,1343 @@ pub fn print_ticker_matches_table(matches: &[TickerMatch]) {
     println!("{}", table);
 }
 
-fn format_crypto_amount(amount: f64, symbol: &str) -> String {
-    let upper = symbol.to_uppercase();
-    if amount >= 0.0001 {
-        format!("{:.6} {}", amount, upper)
-    } else {
-        format!("{:.10} {}", amount, upper)
+#[derive(Tabled)]
+struct TrendingRow {
+    #[tabled(rename = "#")]
+    position: usize,
+    #[tabled(rename = "Symbol")]
+    symbol: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Rank")]
+    rank: String,
+    #[tabled(rename = "Price")]
+    price: String,
+    #[tabled(rename = "24h Change")]
+    change_24h: String,
+}
+
+/// Print trending coins, most searched first, as a styled table to stdout.
+pub fn print_trending_table(coins: &[TrendingCoin]) {
+    println!("{}", render_trending_table(coins));
+}
+
+fn render_trending_table(coins: &[TrendingCoin]) -> String {
+    let rows: Vec<TrendingRow> = coins
+        .iter()
+        .enumerate()
+        .map(|(i, coin)| TrendingRow {
+            position: i + 1,
+            symbol: coin.symbol.clone().bold().to_string(),
+            name: coin.name.clone(),
+            rank: coin
+                .market_cap_rank
+                .map(|rank| format!("#{}", rank))
+                .unwrap_or_else(|| "-".to_string()),
+            price: coin
+                .price
+                .map(|price| format_price(price, &coin.currency))
+                .unwrap_or_else(|| "-".to_string()),
+            change_24h: format_change(coin.change_24h),
+        })
+        .collect();
+
+    Table::new(rows).with(Style::rounded()).to_string()
+}
+
+#[derive(Tabled)]
+struct MarketTickerRow {
+    #[tabled(rename = "Exchange")]
+    exchange: String,
+    #[tabled(rename = "Pair")]
+    pair: String,
+    #[tabled(rename = "Last Price")]
+    last_price: String,
+    #[tabled(rename = "24h Volume")]
+    volume: String,
+    #[tabled(rename = "Trust")]
+    trust_score: String,
+}
+
+/// Print the markets a coin trades on as a styled table to stdout.
+pub fn print_market_tickers_table(markets: &[MarketTicker]) {
+    let rows: Vec<MarketTickerRow> = markets
+        .iter()
+        .map(|m| MarketTickerRow {
+            exchange: m.exchange.clone().bold().to_string(),
+            pair: m.pair.clone(),
+            last_price: m
+                .last_price
+                .map(fmt::format_price_value)
+                .unwrap_or_else(|| "-".to_string()),
+            volume: m
+                .volume_usd
+                .map(|volume| format_market_cap(volume, "USD"))
+                .unwrap_or_else(|| "-".to_string()),
+            trust_score: match m.trust_score.as_deref() {
+                Some("green") => "green".green().to_string(),
+                Some("yellow") => "yellow".yellow().to_string(),
+                Some("red") => "red".red().to_string(),
+                Some(other) => other.to_string(),
+                None => "-".dimmed().to_string(),
+            },
+        })
+        .collect();
+
+    let table = Table::new(rows).with(Style::rounded()).to_string();
+    println!("{}", table);
+}
+
+/// Print the `--timings` breakdown to stderr: phases, then one row per HTTP request.
+pub fn print_timings(timings: &Timings) {
+    eprintln!("{}", render_timings(timings));
+}
+
+fn render_timings(timings: &Timings) -> String {
+    let mut phases = Builder::default();
+    phases.push_record(["Phase", "Time"]);
+    for phase in &timings.phases {
+        phases.push_record([phase.name.clone(), format_millis(phase.ms)]);
     }
+    phases.push_record([
+        "total".bold().to_string(),
+        format_millis(timings.total_ms).bold().to_string(),
+    ]);
+    let mut rendered = phases.build().with(Style::rounded()).to_string();
+
+    if !timings.requests.is_empty() {
+        let mut requests = Builder::default();
+        requests.push_record(["Provider", "Status", "TTFB", "Body", "URL"]);
+        for request in &timings.requests {
+            requests.push_record([
+                request.provider.clone(),
+                request.status.to_string(),
+                format_millis(request.ttfb_ms),
+                request
+                    .body_ms
+                    .map(format_millis)
+                    .unwrap_or_else(|| "-".to_string()),
+                request.url.clone().dimmed().to_string(),
+            ]);
+        }
+        rendered.push('\n');
+        rendered.push_str(&requests.build().with(Style::rounded()).to_string());
+    }
+    rendered
 }
 
-fn format_price(price: f64, currency: &str) -> String {
-    let sym = currency_symbol(currency);
-    if price >= 1.0 {
-        format!("{}{}", sym, format_with_commas(price, 2))
-    } else if price >= 0.01 {
-        format!("{}{:.4}", sym, price)
-    } else {
-        format!("{}{:.8}", sym, price)
+fn format_millis(ms: f64) -> String {
+    format!("{:.1} ms", ms)
+}
+
+#[derive(Tabled)]
+struct CacheEntryRow {
+    #[tabled(rename = "Provider")]
+    provider: String,
+    #[tabled(rename = "Key")]
+    key: String,
+    #[tabled(rename = "Age")]
+    age: String,
+    #[tabled(rename = "Size")]
+    size: String,
+}
+
+/// Print cached responses as a styled table to stdout, with a total line below.
+pub fn print_cache_entries_table(entries: &[CacheEntry]) {
+    println!(
+        "{}",
+        render_cache_entries_table(entries, chrono::Utc::now())
+    );
+}
+
+fn render_cache_entries_table(
+    entries: &[CacheEntry],
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let rows: Vec<CacheEntryRow> = entries
+        .iter()
+        .map(|entry| CacheEntryRow {
+            provider: entry.provider.clone(),
+            key: if entry.valid {
+                entry.key.clone()
+            } else {
+                format!("{} {}", entry.key, "(corrupt)".red())
+            },
+            age: entry
+                .fetched_at
+                .map(|at| fmt::format_age(now - at))
+                .unwrap_or_else(|| "-".to_string()),
+            size: fmt::format_bytes(entry.size_bytes),
+        })
+        .collect();
+    let total: u64 = entries.iter().map(|entry| entry.size_bytes).sum();
+    format!(
+        "{}\n{}",
+        Table::new(rows).with(Style::rounded()),
+        format!(
+            "{} {}, {}",
+            entries.len(),
+            if entries.len() == 1 {
+                "entry"
+            } else {
+                "entries"
+            },
+            fmt::format_bytes(total)
+        )
+        .dimmed()
+    )
+}
+
+#[derive(Tabled)]
+struct ProviderHealthRow {
+    #[tabled(rename = "Provider")]
+    provider: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Latency")]
+    latency: String,
+    #[tabled(rename = "Detail")]
+    detail: String,
+}
+
+/// Print provider health check results as a styled table to stdout.
+pub fn print_provider_health_table(report: &[ProviderHealth]) {
+    let rows: Vec<ProviderHealthRow> = report
+        .iter()
+        .map(|h| {
+            let status = h.status.to_string();
+            let status = match h.status {
+                HealthStatus::Ok => status.green().to_string(),
+                HealthStatus::NoApiKey | HealthStatus::RateLimited => status.yellow().to_string(),
+                _ => status.red().to_string(),
+            };
+            ProviderHealthRow {
+                provider: h.provider.clone().bold().to_string(),
+                status,
+                latency: h
+                    .latency_ms
+                    .map(|ms| format!("{} ms", ms))
+                    .unwrap_or_else(|| "-".to_string()),
+                detail: h.detail.clone().unwrap_or_default().dimmed().to_string(),
+            }
+        })
+        .collect();
+
+    let table = Table::new(rows).with(Style::rounded()).to_string();
+    println!("{}", table);
+}
+
+/// Print the `--list-providers` table with one column per [`Capability`].
+pub fn print_provider_list_table(providers: &[ProviderInfo]) {
+    println!("{}", render_provider_list_table(providers));
+}
+
+fn render_provider_list_table(providers: &[ProviderInfo]) -> String {
+    let mut builder = Builder::default();
+    builder.push_record(
+        ["Provider", "Name"]
+            .into_iter()
+            .chain(Capability::ALL.iter().map(|capability| capability.label())),
+    );
+    for info in providers {
+        let marks = Capability::ALL.iter().map(|&capability| {
+            if info.capabilities.supports(capability) {
+                "yes".green().to_string()
+            } else {
+                "-".dimmed().to_string()
+            }
+        });
+        builder.push_record(
+            [info.id.clone().bold().to_string(), info.name.clone()]
+                .into_iter()
+                .chain(marks),
+        );
+    }
+    builder.build().with(Style::rounded()).to_string()
+}
+
+/// Print the multi-asset comparison printed after the charts.
+pub fn print_comparison_table(rows: &[Comparison], reference: &str) {
+    println!("{}", render_comparison_table(rows, reference));
+}
+
+fn render_comparison_table(rows: &[Comparison], reference: &str) -> String {
+    let mut builder = Builder::default();
+    builder.push_record([
+        "Symbol".to_string(),
+        "Return".to_string(),
+        "Best Day".to_string(),
+        "Worst Day".to_string(),
+        "Max Drawdown".to_string(),
+        format!("Corr vs {}", reference),
+    ]);
+    for row in rows {
+        builder.push_record([
+            row.symbol.clone().bold().to_string(),
+            format_change(row.return_pct),
+            format_change(row.best_day_pct),
+            format_change(row.worst_day_pct),
+            format!("{}%", fmt::format_fixed(row.max_drawdown_pct, 2)),
+            row.correlation
+                .map(|c| fmt::format_fixed(c, 2))
+                .unwrap_or_else(|| "n/a".to_string()),
+        ]);
     }
+    builder.build().with(Style::rounded()).to_string()
 }
 
-fn format_with_commas(value: f64, decimals: usize) -> String {
-    let formatted = format!("{value:.decimals$}");
-    let parts: Vec<&str> = formatted.split('.').collect();
-    let whole = parts[0];
+/// Print `pricr stats` metrics, one row per symbol, under a line naming the window.
+pub fn print_risk_table(metrics: &[RiskMetrics], range_label: &str) {
+    println!("Daily return statistics ({})", range_label);
+    println!("{}", render_risk_table(metrics));
+}
 
-    let mut result = String::new();
-    for (i, ch) in whole.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            result.push(',');
-        }
-        result.push(ch);
+fn render_risk_table(metrics: &[RiskMetrics]) -> String {
+    let pct = |value: Option<f64>| {
+        value
+            .map(|v| format!("{}%", fmt::format_fixed(v, 2)))
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+    let mut builder = Builder::default();
+    builder.push_record([
+        "Symbol",
+        "Days",
+        "Volatility (ann.)",
+        "Mean Day",
+        "Best Day",
+        "Worst Day",
+        "Max Drawdown",
+        "Downside Dev.",
+    ]);
+    for row in metrics {
+        builder.push_record([
+            row.symbol.clone().bold().to_string(),
+            row.days.to_string(),
+            pct(row.annualized_volatility_pct),
+            format_change(row.mean_daily_return_pct),
+            format_change(row.best_day_pct),
+            format_change(row.worst_day_pct),
+            pct(row.max_drawdown_pct),
+            pct(row.downside_deviation_pct),
+        ]);
+    }
+    builder.build().with(Style::rounded()).to_string()
+}
+
+/// Print a yellow warning to stderr listing series too short for reliable `pricr stats`.
+pub fn print_short_history_warning(symbols: &[String]) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: fewer than {} daily closes for {}; the estimates are unreliable",
+            stats::MIN_RELIABLE_DAYS,
+            symbols.join(", ")
+        )
+        .yellow()
+    );
+}
+
+/// Print `pricr roi` results, one row per symbol.
+pub fn print_investments_table(investments: &[Investment]) {
+    println!("{}", render_investments_table(investments));
+}
+
+fn render_investments_table(investments: &[Investment]) -> String {
+    let mut builder = Builder::default();
+    builder.push_record([
+        "Symbol",
+        "Invested",
+        "Bought",
+        "Buy Price",
+        "Units",
+        "Price",
+        "Value",
+        "Gain",
+        "Return",
+        "CAGR",
+    ]);
+    for row in investments {
+        let gain = format_price(row.gain.abs(), &row.currency);
+        let gain = if row.gain < 0.0 {
+            format!("-{}", gain)
+        } else {
+            format!("+{}", gain)
+        };
+        builder.push_record([
+            row.symbol.clone().bold().to_string(),
+            format_price(row.invested, &row.currency),
+            tz::format_timestamp(row.bought_at, "%Y-%m-%d"),
+            format_price(row.buy_price, &row.currency),
+            fmt::format_amount(row.units),
+            format_price(row.current_price, &row.currency),
+            format_price(row.value, &row.currency).bold().to_string(),
+            color::paint_change(gain, row.gain).to_string(),
+            format_change(Some(row.gain_pct)),
+            format_change(row.cagr_pct),
+        ]);
+    }
+    builder.build().with(Style::rounded()).to_string()
+}
+
+/// Print the correlation matrix under a line naming the window and observation count.
+pub fn print_correlation_table(matrix: &CorrelationMatrix, range_label: &str) {
+    println!(
+        "Correlation of daily log returns ({}, {} days)",
+        range_label, matrix.observations
+    );
+    println!("{}", render_correlation_table(matrix));
+}
+
+fn render_correlation_table(matrix: &CorrelationMatrix) -> String {
+    let mut builder = Builder::default();
+    builder.push_record(std::iter::once(String::new()).chain(matrix.symbols.iter().cloned()));
+    for (symbol, row) in matrix.symbols.iter().zip(&matrix.values) {
+        let cells = row.iter().map(|value| match value {
+            Some(value) => {
+                color::paint_correlation(fmt::format_fixed(*value, 2), *value).to_string()
+            }
+            None => "n/a".dimmed().to_string(),
+        });
+        builder.push_record(std::iter::once(symbol.clone().bold().to_string()).chain(cells));
     }
-    let whole_formatted: String = result.chars().rev().collect();
+    builder.build().with(Style::rounded()).to_string()
+}
+
+/// Print a yellow warning to stderr listing symbols left out of the correlation matrix.
+pub fn print_correlation_excluded_warning(excluded: &[String]) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: fewer than {} daily returns shared with the other symbols; left out of the correlation matrix: {}",
+            stats::MIN_CORRELATION_OBSERVATIONS,
+            excluded.join(", ")
+        )
+        .yellow()
+    );
+}
 
-    if parts.len() > 1 {
-        format!("{}.{}", whole_formatted, parts[1])
+/// Share of the monthly credits past which `--quota` turns yellow.
+pub const QUOTA_WARN_PCT: f64 = 80.0;
+
+/// `CoinMarketCap credits 2026-10: 8,500 / 10,000 (85.0%)`, yellow past
+/// [`QUOTA_WARN_PCT`] and red once the allowance is spent.
+pub fn format_quota(usage: &QuotaUsage) -> String {
+    let used = fmt::format_fixed(usage.credits_used as f64, 0);
+    let head = format!("CoinMarketCap credits {}:", usage.month);
+    let (Some(limit), Some(pct)) = (usage.monthly_credits, usage.used_pct) else {
+        return format!(
+            "{} {} {}",
+            head.bold(),
+            used,
+            "(set [provider.cmc] monthly_credits to compare with your plan)".dimmed()
+        );
+    };
+    let detail = format!(
+        "{} / {} ({:.1}%)",
+        used,
+        fmt::format_fixed(limit as f64, 0),
+        pct
+    );
+    let detail = if pct >= 100.0 {
+        detail.red().to_string()
+    } else if pct > QUOTA_WARN_PCT {
+        detail.yellow().to_string()
     } else {
-        whole_formatted
+        detail
+    };
+    format!("{} {}", head.bold(), detail)
+}
+
+/// Print global market metrics as a two-column table to stdout.
+pub fn print_global_table(metrics: &GlobalMetrics) {
+    println!("{}", render_global_table(metrics));
+}
+
+fn render_global_table(metrics: &GlobalMetrics) -> String {
+    let money = |value: Option<f64>| {
+        value
+            .map(|v| format_market_cap(v, &metrics.currency))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let mut builder = Builder::default();
+    builder.push_record(["Metric", "Value"]);
+    builder.push_record([
+        "Total Market Cap".to_string(),
+        money(metrics.total_market_cap),
+    ]);
+    builder.push_record(["24h Volume".to_string(), money(metrics.total_volume_24h)]);
+    builder.push_record([
+        "BTC Dominance".to_string(),
+        format_dominance(metrics.btc_dominance),
+    ]);
+    builder.push_record([
+        "ETH Dominance".to_string(),
+        format_dominance(metrics.eth_dominance),
+    ]);
+    builder.push_record([
+        "Active Coins".to_string(),
+        metrics
+            .active_coins
+            .map(|count| fmt::format_fixed(count as f64, 0))
+            .unwrap_or_else(|| "-".to_string()),
+    ]);
+    builder.push_record(["Provider".to_string(), metrics.provider.clone()]);
+    builder.build().with(Style::rounded()).to_string()
+}
+
+/// One-line market summary printed above the price table, e.g.
+/// `Market cap $2.45T · 24h vol $98.10B · BTC 52.3% · ETH 16.9% · 14,203 coins`.
+pub fn format_global_summary(metrics: &GlobalMetrics) -> String {
+    let mut parts = Vec::new();
+    if let Some(cap) = metrics.total_market_cap {
+        parts.push(format!(
+            "Market cap {}",
+            format_market_cap(cap, &metrics.currency)
+        ));
+    }
+    if let Some(volume) = metrics.total_volume_24h {
+        parts.push(format!(
+            "24h vol {}",
+            format_market_cap(volume, &metrics.currency)
+        ));
+    }
+    if metrics.btc_dominance.is_some() {
+        parts.push(format!("BTC {}", format_dominance(metrics.btc_dominance)));
+    }
+    if metrics.eth_dominance.is_some() {
+        parts.push(format!("ETH {}", format_dominance(metrics.eth_dominance)));
+    }
+    if let Some(count) = metrics.active_coins {
+        parts.push(format!("{} coins", fmt::format_fixed(count as f64, 0)));
     }
+    parts.join(" \u{b7} ")
 }
 
-fn format_market_cap(cap: f64, currency: &str) -> String {
-    let sym = currency_symbol(currency);
-    if cap >= 1_000_000_000_000.0 {
-        format!("{}{:.2}T", sym, cap / 1_000_000_000_000.0)
-    } else if cap >= 1_000_000_000.0 {
-        format!("{}{:.2}B", sym, cap / 1_000_000_000.0)
-    } else if cap >= 1_000_000.0 {
-        format!("{}{:.2}M", sym, cap / 1_000_000.0)
-    } else if cap >= 1_000.0 {
-        format!("{}{:.2}K", sym, cap / 1_000.0)
+fn format_dominance(pct: Option<f64>) -> String {
+    pct.map(|pct| format!("{}%", fmt::format_fixed(pct, 1)))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Print a yellow warning to stderr listing series left out of the comparison table.
+pub fn print_comparison_excluded_warning(excluded: &[String], reference: &str) {
+    eprintln!(
+        "{}",
+        format!(
+            "warning: fewer than {} days overlap with {}; left out of the comparison: {}",
+            stats::MIN_COMPARISON_DAYS,
+            reference,
+            excluded.join(", ")
+        )
+        .yellow()
+    );
+}
+
+/// Stats block printed under a chart; changes and volatility read `n/a` for short series.
+fn render_history_stats(stats: &HistoryStats, currency: &str) -> String {
+    let change = match (stats.change, stats.change_pct) {
+        (Some(change), Some(pct)) => format!(
+            "{} ({})",
+            format_signed_price(change, currency),
+            color::paint_change(format_percent(pct), pct)
+        ),
+        (Some(change), None) => format_signed_price(change, currency),
+        _ => "n/a".to_string(),
+    };
+    let percent_or_na = |value: Option<f64>| {
+        value
+            .map(|v| format!("{}%", fmt::format_fixed(v, 2)))
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+
+    [
+        format!(
+            "Start: {}  End: {}  Change: {}",
+            format_price(stats.start, currency),
+            format_price(stats.end, currency),
+            change
+        ),
+        format!(
+            "Low:   {}  High: {}  Mean: {}",
+            format_price(stats.low.value, currency),
+            format_price(stats.high.value, currency),
+            format_price(stats.mean, currency)
+        ),
+        format!(
+            "Max drawdown: {}  Volatility: {} (annualized)",
+            percent_or_na(stats.max_drawdown_pct),
+            percent_or_na(stats.volatility_pct)
+        ),
+    ]
+    .join("\n")
+}
+
+fn format_signed_price(value: f64, currency: &str) -> String {
+    let sign = if value < 0.0 { "-" } else { "+" };
+    format!("{}{}", sign, format_price(value.abs(), currency))
+}
+
+/// Callout for the chart's markers, e.g. `▲ High $73,810.00 on 2024-03-14 · ▼ Low ...`.
+///
+/// Intraday series also show the time of day.
+fn render_extremes_callout(stats: &HistoryStats, history: &PriceHistory) -> String {
+    let date_format = if chart::axis_label_format(&history.points).contains("%H") {
+        "%Y-%m-%d %H:%M"
     } else {
-        format!("{}{:.2}", sym, cap)
+        "%Y-%m-%d"
+    };
+    let describe = |extreme: &stats::Extreme| {
+        format!(
+            "{} on {}",
+            format_price(extreme.value, &history.currency),
+            tz::format_timestamp(extreme.timestamp, date_format)
+        )
+    };
+    format!(
+        "{} High {} · {} Low {}",
+        chart::HIGH_MARKER,
+        describe(&stats.high),
+        chart::LOW_MARKER,
+        describe(&stats.low)
+    )
+}
+
+/// One footnote line under a chart, e.g. `2020-08-31  Split 4:1`.
+fn format_history_event(event: &HistoryEvent, currency: &str) -> String {
+    let date = tz::format_timestamp(event.timestamp, "%Y-%m-%d");
+    match event.kind {
+        HistoryEventKind::Dividend => {
+            format!("{}  Dividend {}", date, format_price(event.value, currency))
+        }
+        HistoryEventKind::Split if event.value >= 1.0 => {
+            format!("{}  Split {}:1", date, trim_ratio(event.value))
+        }
+        HistoryEventKind::Split => format!("{}  Split 1:{}", date, trim_ratio(1.0 / event.value)),
     }
 }
 
-fn currency_symbol(currency: &str) -> &str {
-    match currency.to_uppercase().as_str() {
-        "USD" => "$",
-        "EUR" => "\u{20ac}",
-        "GBP" => "\u{00a3}",
-        "JPY" | "CNY" => "\u{00a5}",
-        "CAD" => "CA$",
-        "AUD" => "A$",
-        "CHF" => "CHF ",
-        "BTC" => "\u{20bf}",
-        _ => "",
+fn trim_ratio(value: f64) -> String {
+    ((value * 1000.0).round() / 1000.0).to_string()
+}
+
+fn format_crypto_amount(amount: f64, symbol: &str) -> String {
+    format!("{} {}", fmt::format_amount(amount), symbol.to_uppercase())
+}
+
+fn format_market_cap(cap: f64, currency: &str) -> String {
+    fmt::with_currency(&fmt::format_compact(cap), currency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_columns_places_changes_and_trend_before_market_cap() {
+        let columns = price_columns(
+            &[PriceColumn::Change24h, PriceColumn::Change7d],
+            true,
+            false,
+            false,
+            false,
+        );
+        assert_eq!(
+            columns,
+            vec![
+                PriceColumn::Symbol,
+                PriceColumn::Name,
+                PriceColumn::Price,
+                PriceColumn::Change24h,
+                PriceColumn::Change7d,
+                PriceColumn::Trend,
+                PriceColumn::MarketCap,
+                PriceColumn::Provider,
+            ]
+        );
+        assert_eq!(
+            price_columns(&[PriceColumn::Change24h], false, false, false, false),
+            DEFAULT_PRICE_COLUMNS.to_vec()
+        );
+    }
+
+    #[test]
+    fn render_table_shows_selected_change_columns() {
+        let price = CoinPrice {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            price: 67_000.0,
+            change_24h: Some(1.0),
+            change_7d: Some(-4.5),
+            change_30d: None,
+            market_cap: None,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::Utc::now(),
+            quote_time_estimated: false,
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        };
+        let rendered = render_table(
+            &[price],
+            None,
+            None,
+            &price_columns(
+                &[PriceColumn::Change7d, PriceColumn::Change30d],
+                false,
+                false,
+                false,
+                false,
+            ),
+        );
+
+        assert!(rendered.contains("7d Change"));
+        assert!(rendered.contains("30d Change"));
+        assert!(!rendered.contains("24h Change"));
+        assert!(rendered.contains("-4.50%"));
+    }
+
+    #[test]
+    fn render_table_shows_holdings_and_total() {
+        let prices = vec![
+            CoinPrice {
+                symbol: "BTC".to_string(),
+                price: 60_000.0,
+                currency: "USD".to_string(),
+                provider: "CoinGecko".to_string(),
+                ..Default::default()
+            },
+            CoinPrice {
+                symbol: "ETH".to_string(),
+                price: 3_000.0,
+                currency: "USD".to_string(),
+                provider: "CoinGecko".to_string(),
+                ..Default::default()
+            },
+        ];
+        let portfolio = crate::calc::value_positions(
+            &prices,
+            &[("btc".to_string(), 0.5), ("eth".to_string(), 3.0)],
+            "usd",
+        );
+        let rendered = render_table(
+            &prices,
+            None,
+            Some(&portfolio),
+            &[
+                PriceColumn::Symbol,
+                PriceColumn::Price,
+                PriceColumn::Amount,
+                PriceColumn::Value,
+            ],
+        );
+
+        assert!(rendered.contains("Amount"));
+        assert!(rendered.contains("$30,000.00"));
+        assert!(rendered.contains("$9,000.00"));
+        assert!(format_portfolio_total(&portfolio).contains("$39,000.00"));
+    }
+
+    #[test]
+    fn render_table_formats_volume_supply_and_rank() {
+        let price = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 67_000.0,
+            volume_24h: Some(28_500_000_000.0),
+            circulating_supply: Some(19_750_000.0),
+            rank: Some(1),
+            currency: "USD".to_string(),
+            ..Default::default()
+        };
+        let rendered = render_table(
+            &[price],
+            None,
+            None,
+            &[
+                PriceColumn::Symbol,
+                PriceColumn::Volume,
+                PriceColumn::Supply,
+                PriceColumn::Rank,
+            ],
+        );
+
+        assert!(rendered.contains("$28.50B"));
+        assert!(rendered.contains("19.75M"));
+        assert!(rendered.contains("#1"));
+        assert!(!rendered.contains("Market Cap"));
+    }
+
+    #[test]
+    fn quote_age_reads_ago_and_skips_estimated_times() {
+        let now = chrono::Utc::now();
+        let quoted = CoinPrice {
+            timestamp: now - chrono::Duration::minutes(125),
+            ..Default::default()
+        };
+        let estimated = CoinPrice {
+            timestamp: now,
+            quote_time_estimated: true,
+            ..Default::default()
+        };
+
+        colored::control::set_override(false);
+        assert_eq!(format_quote_age(&quoted, now), "2h ago");
+        assert_eq!(format_quote_age(&estimated, now), "n/a");
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn extended_columns_fill_equities_and_leave_crypto_blank() {
+        let equity = CoinPrice {
+            symbol: "AAPL".to_string(),
+            price: 190.0,
+            currency: "USD".to_string(),
+            extended: Some(crate::provider::ExtendedQuote {
+                post_market_price: Some(191.25),
+                fifty_two_week_low: Some(164.08),
+                fifty_two_week_high: Some(199.62),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let crypto = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 67_000.0,
+            currency: "USD".to_string(),
+            ..Default::default()
+        };
+        let columns = price_columns(&[PriceColumn::Change24h], false, true, false, false);
+        assert_eq!(
+            &columns[columns.len() - 5..],
+            &[
+                PriceColumn::PreMarket,
+                PriceColumn::PostMarket,
+                PriceColumn::Range52w,
+                PriceColumn::MarketCap,
+                PriceColumn::Provider,
+            ]
+        );
+
+        let rendered = render_table(&[equity, crypto], None, None, &columns);
+        assert!(rendered.contains("Post-Market"));
+        assert!(rendered.contains("$191.25"));
+        assert!(rendered.contains("$164.08 - $199.62"));
+        let btc_row = rendered.lines().find(|line| line.contains("BTC")).unwrap();
+        assert!(!btc_row.contains(" - $"));
+    }
+
+    #[test]
+    fn timings_list_phases_total_and_requests() {
+        use crate::timings::{PhaseTiming, RequestTiming};
+
+        let timings = Timings {
+            phases: vec![
+                PhaseTiming {
+                    name: "config".to_string(),
+                    ms: 1.25,
+                },
+                PhaseTiming {
+                    name: "fetch".to_string(),
+                    ms: 412.0,
+                },
+            ],
+            requests: vec![RequestTiming {
+                provider: "CoinGecko".to_string(),
+                url: "https://api.coingecko.com/api/v3/coins/markets".to_string(),
+                status: 200,
+                ttfb_ms: 380.1,
+                body_ms: None,
+            }],
+            total_ms: 415.5,
+        };
+        let rendered = render_timings(&timings);
+        assert!(rendered.contains("config"));
+        assert!(rendered.contains("412.0 ms"));
+        assert!(rendered.contains("415.5 ms"));
+        let request_row = rendered
+            .lines()
+            .find(|line| line.contains("CoinGecko"))
+            .unwrap();
+        assert!(request_row.contains("200"));
+        assert!(request_row.contains("380.1 ms"));
+        assert!(request_row.contains("/coins/markets"));
+    }
+
+    #[test]
+    fn cache_table_shows_age_size_and_corrupt_entries() {
+        let now = chrono::Utc::now();
+        let entry = |key: &str, age_secs, size_bytes, valid| CacheEntry {
+            provider: "coingecko".to_string(),
+            key: key.to_string(),
+            path: std::path::PathBuf::from(format!("{key}.json")),
+            size_bytes,
+            fetched_at: Some(now - chrono::Duration::seconds(age_secs)),
+            valid,
+        };
+        let rendered = render_cache_entries_table(
+            &[
+                entry("markets:usd:bitcoin", 90, 2048, true),
+                entry("0badc0de", 3 * 86_400, 12, false),
+            ],
+            now,
+        );
+        assert!(rendered.contains("markets:usd:bitcoin"));
+        assert!(rendered.contains("1m"));
+        assert!(rendered.contains("2.0 KiB"));
+        assert!(rendered.contains("0badc0de"));
+        assert!(rendered.contains("(corrupt)"));
+        assert!(rendered.contains("3d"));
+        assert!(rendered.contains("2 entries, 2.0 KiB"));
+    }
+
+    #[test]
+    fn delta_column_shows_change_since_the_previous_run() {
+        let ts = |raw: &str| {
+            chrono::DateTime::parse_from_rfc3339(raw)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let now = ts("2024-03-14T18:30:00Z");
+        colored::control::set_override(false);
+        assert_eq!(
+            format_delta(1.2, ts("2024-03-14T14:05:00Z"), now),
+            "+1.20% since 14:05"
+        );
+        assert_eq!(
+            format_delta(-0.5, ts("2024-03-13T09:00:00Z"), now),
+            "-0.50% since 2024-03-13 09:00"
+        );
+
+        let first_run = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 54_000.0,
+            currency: "USD".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            price_cell(PriceColumn::Delta, &first_run, None, None),
+            NO_QUOTE
+        );
+        colored::control::unset_override();
+    }
+
+    #[test]
+    fn ath_columns_show_date_and_drawdown() {
+        let btc = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 54_000.0,
+            currency: "USD".to_string(),
+            ath: Some(72_000.0),
+            ath_date: Some(
+                chrono::DateTime::parse_from_rfc3339("2024-03-14T07:10:36Z")
+                    .unwrap()
+                    .with_timezone(&chrono::Utc),
+            ),
+            ..Default::default()
+        };
+        let aapl = CoinPrice {
+            symbol: "AAPL".to_string(),
+            price: 190.0,
+            currency: "USD".to_string(),
+            ..Default::default()
+        };
+        let columns = price_columns(&[PriceColumn::Change24h], false, false, true, false);
+        assert_eq!(
+            &columns[columns.len() - 5..],
+            &[
+                PriceColumn::Ath,
+                PriceColumn::AthDate,
+                PriceColumn::FromAth,
+                PriceColumn::MarketCap,
+                PriceColumn::Provider,
+            ]
+        );
+
+        let rendered = render_table(&[btc, aapl], None, None, &columns);
+        assert!(rendered.contains("From ATH"));
+        let btc_row = rendered.lines().find(|line| line.contains("BTC")).unwrap();
+        assert!(btc_row.contains("$72,000.00"));
+        assert!(btc_row.contains("2024-03-14"));
+        assert!(btc_row.contains("-25.00%"));
+        let aapl_row = rendered.lines().find(|line| line.contains("AAPL")).unwrap();
+        assert!(!aapl_row.contains('%'));
+    }
+
+    #[test]
+    fn spread_columns_show_quotes_or_dash() {
+        let aapl = CoinPrice {
+            symbol: "AAPL".to_string(),
+            price: 100.0,
+            currency: "USD".to_string(),
+            bid: Some(99.95),
+            ask: Some(100.05),
+            ..Default::default()
+        };
+        let btc = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 60_000.0,
+            currency: "USD".to_string(),
+            ..Default::default()
+        };
+        let columns = price_columns(&[PriceColumn::Change24h], false, false, false, true);
+        assert!(columns.ends_with(&[
+            PriceColumn::Bid,
+            PriceColumn::Ask,
+            PriceColumn::Spread,
+            PriceColumn::MarketCap,
+            PriceColumn::Provider,
+        ]));
+
+        let rendered = render_table(&[aapl, btc], None, None, &columns);
+        assert!(rendered.contains("Spread%"));
+        let aapl_row = rendered.lines().find(|line| line.contains("AAPL")).unwrap();
+        assert!(aapl_row.contains("$99.95"));
+        assert!(aapl_row.contains("$100.05"));
+        assert!(aapl_row.contains("0.100%"));
+        let btc_row = rendered.lines().find(|line| line.contains("BTC")).unwrap();
+        assert_eq!(btc_row.matches('\u{2014}').count(), 3);
+        assert_eq!(spread_percent(Some(101.0), Some(100.0)), None);
+    }
+
+    #[test]
+    fn since_columns_name_the_date_and_blank_missing_closes() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let btc = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 60_000.0,
+            currency: "USD".to_string(),
+            since_date: Some(date),
+            since_price: Some(40_000.0),
+            since_change_pct: Some(50.0),
+            ..Default::default()
+        };
+        let aapl = CoinPrice {
+            symbol: "AAPL".to_string(),
+            price: 100.0,
+            currency: "USD".to_string(),
+            since_date: Some(date),
+            ..Default::default()
+        };
+        let columns = [
+            PriceColumn::Symbol,
+            PriceColumn::SincePrice,
+            PriceColumn::SinceChange,
+        ];
+
+        let rendered = render_table(&[btc, aapl], None, None, &columns);
+        assert!(rendered.contains("Price on 2024-01-15"));
+        assert!(rendered.contains("Change since 2024-01-15 %"));
+        let btc_row = rendered.lines().find(|line| line.contains("BTC")).unwrap();
+        assert!(btc_row.contains("$40,000.00"), "{btc_row}");
+        assert!(btc_row.contains("+50.00%"), "{btc_row}");
+        let aapl_row = rendered.lines().find(|line| line.contains("AAPL")).unwrap();
+        assert!(!aapl_row.contains('%'), "{aapl_row}");
+    }
+
+    #[test]
+    fn currency_columns_are_headed_by_their_code() {
+        let btc = CoinPrice {
+            symbol: "BTC".to_string(),
+            price: 60_000.0,
+            currency: "USD".to_string(),
+            prices: vec![
+                ("USD".to_string(), Some(60_000.0)),
+                ("EUR".to_string(), None),
+            ],
+            ..Default::default()
+        };
+        let columns = [
+            PriceColumn::Symbol,
+            PriceColumn::PriceIn(0),
+            PriceColumn::PriceIn(1),
+        ];
+
+        let rendered = render_table(&[btc], None, None, &columns);
+        let header = rendered.lines().nth(1).unwrap();
+        assert!(header.contains("USD") && header.contains("EUR"), "{header}");
+        assert!(!header.contains("Price"), "{header}");
+        let row = rendered.lines().find(|line| line.contains("BTC")).unwrap();
+        assert!(row.contains("$60,000.00"), "{row}");
+        assert!(row.contains('\u{2014}'), "{row}");
+    }
+
+    #[test]
+    fn granularity_notice_names_the_delivered_granularity() {
+        let mut history = PriceHistory {
+            symbol: "AAPL".to_string(),
+            name: "Apple".to_string(),
+            currency: "USD".to_string(),
+            provider: "Stooq".to_string(),
+            points: Vec::new(),
+            events: Vec::new(),
+            granularity: None,
+        };
+        assert_eq!(granularity_notice(&history), None);
+
+        history.granularity = Some(crate::provider::ResolvedInterval::Daily);
+        assert_eq!(
+            granularity_notice(&history).as_deref(),
+            Some("only daily data available for this range")
+        );
+    }
+
+    #[test]
+    fn history_events_format_dividends_and_splits() {
+        let at = chrono::DateTime::parse_from_rfc3339("2020-08-31T13:30:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let event = |kind, value| HistoryEvent {
+            timestamp: at,
+            kind,
+            value,
+        };
+
+        assert_eq!(
+            format_history_event(&event(HistoryEventKind::Split, 4.0), "USD"),
+            "2020-08-31  Split 4:1"
+        );
+        assert_eq!(
+            format_history_event(&event(HistoryEventKind::Split, 0.1), "USD"),
+            "2020-08-31  Split 1:10"
+        );
+        assert_eq!(
+            format_history_event(&event(HistoryEventKind::Dividend, 0.24), "USD"),
+            "2020-08-31  Dividend $0.2400"
+        );
+    }
+
+    #[test]
+    fn history_stats_block_prints_na_for_single_point() {
+        let point = |secs: i64, price: f64| crate::provider::PricePoint {
+            timestamp: chrono::DateTime::from_timestamp(secs, 0).unwrap(),
+            price,
+            filled: false,
+        };
+
+        let single = stats::summarize(&[point(1_700_000_000, 50.0)]).unwrap();
+        let rendered = render_history_stats(&single, "USD");
+        assert!(rendered.contains("Change: n/a"));
+        assert!(rendered.contains("Max drawdown: n/a  Volatility: n/a"));
+
+        let series = stats::summarize(&[
+            point(1_700_000_000, 100.0),
+            point(1_700_086_400, 80.0),
+            point(1_700_172_800, 90.0),
+        ])
+        .unwrap();
+        let rendered = render_history_stats(&series, "USD");
+        assert!(rendered.contains("Change: -$10.00 ("), "{rendered}");
+        assert!(rendered.contains("-10.00%"));
+        assert!(rendered.contains("Mean: $90.00"));
+        assert!(rendered.contains("Max drawdown: -20.00%"));
+    }
+
+    #[test]
+    fn extremes_callout_names_dates_and_times() {
+        let point = |secs: i64, price: f64| crate::provider::PricePoint {
+            timestamp: chrono::DateTime::from_timestamp(secs, 0).unwrap(),
+            price,
+            filled: false,
+        };
+        let mut history = PriceHistory {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            points: vec![
+                point(1_710_374_400, 73_810.0),
+                point(1_710_460_800, 70_000.0),
+                point(1_722_816_000, 56_420.0),
+            ],
+            events: Vec::new(),
+            granularity: None,
+        };
+
+        let stats = stats::summarize(&history.points).unwrap();
+        assert_eq!(
+            render_extremes_callout(&stats, &history),
+            "▲ High $73,810.00 on 2024-03-14 · ▼ Low $56,420.00 on 2024-08-05"
+        );
+
+        history.points = vec![point(1_710_374_400, 10.0), point(1_710_378_000, 12.0)];
+        let stats = stats::summarize(&history.points).unwrap();
+        assert_eq!(
+            render_extremes_callout(&stats, &history),
+            "▲ High $12.00 on 2024-03-14 01:00 · ▼ Low $10.00 on 2024-03-14 00:00"
+        );
+    }
+
+    #[test]
+    fn comparison_table_names_reference_symbol() {
+        let rows = [Comparison {
+            symbol: "ETH".to_string(),
+            return_pct: Some(12.5),
+            best_day_pct: Some(4.0),
+            worst_day_pct: None,
+            max_drawdown_pct: -8.25,
+            correlation: Some(0.8123),
+        }];
+        let rendered = render_comparison_table(&rows, "BTC");
+
+        assert!(rendered.contains("Corr vs BTC"));
+        assert!(rendered.contains("+12.50%"));
+        assert!(rendered.contains("-8.25%"));
+        assert!(rendered.contains("0.81"));
+    }
+
+    #[test]
+    fn risk_table_formats_percentages_and_missing_metrics() {
+        let rows = [RiskMetrics {
+            symbol: "BTC".to_string(),
+            provider: "CoinGecko".to_string(),
+            days: 2,
+            annualized_volatility_pct: None,
+            mean_daily_return_pct: Some(-10.0),
+            best_day_pct: Some(-10.0),
+            worst_day_pct: Some(-10.0),
+            max_drawdown_pct: Some(-10.0),
+            downside_deviation_pct: Some(191.1234),
+        }];
+        let rendered = render_risk_table(&rows);
+
+        assert!(rendered.contains("Volatility (ann.)"));
+        assert!(rendered.contains("n/a"));
+        assert!(rendered.contains("-10.00%"));
+        assert!(rendered.contains("191.12%"));
+    }
+
+    #[test]
+    fn investments_table_signs_gains_and_losses() {
+        let investment = |symbol: &str, value: f64| Investment {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            invested: 1000.0,
+            bought_at: chrono::DateTime::from_timestamp(1_619_827_200, 0).unwrap(),
+            buy_price: 50_000.0,
+            units: 0.02,
+            current_price: value * 50.0,
+            value,
+            gain: value - 1000.0,
+            gain_pct: (value - 1000.0) / 10.0,
+            cagr_pct: None,
+        };
+        let rendered =
+            render_investments_table(&[investment("BTC", 1500.0), investment("ETH", 800.0)]);
+
+        assert!(rendered.contains("2021-05-01"), "{rendered}");
+        assert!(rendered.contains("+$500.00"), "{rendered}");
+        assert!(rendered.contains("-$200.00"), "{rendered}");
+        assert!(rendered.contains("+50.00%"), "{rendered}");
+        assert!(rendered.contains("-20.00%"), "{rendered}");
+    }
+
+    #[test]
+    fn correlation_table_is_labelled_on_both_axes() {
+        let matrix = CorrelationMatrix {
+            symbols: vec!["BTC".to_string(), "ETH".to_string()],
+            values: vec![vec![Some(1.0), Some(-0.25)], vec![Some(-0.25), None]],
+            observations: 29,
+            excluded: Vec::new(),
+        };
+        let rendered = render_correlation_table(&matrix);
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert!(
+            lines[1].contains("BTC") && lines[1].contains("ETH"),
+            "{rendered}"
+        );
+        assert!(
+            lines[3].contains("BTC") && lines[3].contains("1.00"),
+            "{rendered}"
+        );
+        assert!(
+            lines[4].contains("ETH") && lines[4].contains("n/a"),
+            "{rendered}"
+        );
+        assert!(rendered.contains("-0.25"));
+    }
+
+    #[test]
+    fn global_summary_skips_missing_fields() {
+        let mut metrics = GlobalMetrics {
+            total_market_cap: Some(2.45e12),
+            total_volume_24h: Some(9.81e10),
+            btc_dominance: Some(52.31),
+            eth_dominance: Some(16.92),
+            active_coins: Some(14203),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::Utc::now(),
+        };
+        assert_eq!(
+            format_global_summary(&metrics),
+            "Market cap $2.45T \u{b7} 24h vol $98.10B \u{b7} BTC 52.3% \u{b7} ETH 16.9% \u{b7} 14,203 coins"
+        );
+
+        metrics.total_volume_24h = None;
+        metrics.eth_dominance = None;
+        assert_eq!(
+            format_global_summary(&metrics),
+            "Market cap $2.45T \u{b7} BTC 52.3% \u{b7} 14,203 coins"
+        );
+        assert!(render_global_table(&metrics).contains("ETH Dominance"));
+    }
+
+    #[test]
+    fn trending_table_numbers_rows_and_marks_missing_quotes() {
+        let coins = [TrendingCoin {
+            id: "render-token".to_string(),
+            symbol: "RNDR".to_string(),
+            name: "Render".to_string(),
+            market_cap_rank: Some(40),
+            price: None,
+            change_24h: None,
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+        }];
+        let rendered = render_trending_table(&coins);
+
+        assert!(rendered.contains("RNDR"));
+        assert!(rendered.contains("#40"));
+        assert!(rendered.contains("\u{2502} 1 \u{2502}"), "{rendered}");
+        assert!(!rendered.contains("render-token"));
+    }
+
+    #[test]
+    fn quota_line_warns_past_eighty_percent() {
+        colored::control::set_override(true);
+        let at = chrono::DateTime::parse_from_rfc3339("2026-10-17T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let line = format_quota(&QuotaUsage::new("cmc", at, 8_500, Some(10_000)));
+        assert!(line.contains("2026-10"), "{line}");
+        assert!(
+            line.contains(&"8,500 / 10,000 (85.0%)".yellow().to_string()),
+            "{line}"
+        );
+
+        let line = format_quota(&QuotaUsage::new("cmc", at, 1_200, Some(10_000)));
+        assert!(line.ends_with("1,200 / 10,000 (12.0%)"), "{line}");
+
+        let line = format_quota(&QuotaUsage::new("cmc", at, 42, None));
+        assert!(line.contains("monthly_credits"), "{line}");
+        colored::control::unset_override();
     }
 }
@@ -0,0 +1,321 @@
+//! Handlebars reports for `--template-file` and `--template-str`.
+//!
+//! Each output mode hands the template one context object: price lookups expose
+//! `prices` (and `portfolio` with `--amount`), conversions expose `conversions`
+//! and `skipped`, and charts expose `histories`, `comparison` and `range`.
+//! [`Report::render`] adds `generated_at` to all of them. Templates render in strict mode, so a
+//! misspelled field is an error rather than an empty string.
+
+use std::path::Path;
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderError,
+    RenderErrorReason, TemplateError,
+};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::output::{fmt, table, tz};
+
+/// Templates shipped with pricr, selectable by name.
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "markdown-table",
+        include_str!("templates/markdown-table.hbs"),
+    ),
+    ("html-report", include_str!("templates/html-report.hbs")),
+];
+
+/// A parsed template ready to render contexts.
+pub struct Report {
+    registry: Handlebars<'static>,
+    name: String,
+}
+
+impl Report {
+    /// Load a built-in template by name, or a template file from `value`.
+    ///
+    /// Built-ins and files ending in `.html`/`.htm` escape HTML in `{{field}}`
+    /// output; everything else renders values as they are.
+    pub fn from_name_or_path(value: &str) -> Result<Self> {
+        if let Some((name, source)) = BUILTIN_TEMPLATES.iter().find(|(name, _)| *name == value) {
+            return Self::parse(name, source, *name == "html-report");
+        }
+        let path = Path::new(value);
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!(
+                "cannot read template {}: {} (built-in templates: {})",
+                path.display(),
+                e,
+                builtin_names()
+            ))
+        })?;
+        let html = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+        Self::parse(value, &source, html)
+    }
+
+    /// Parse an inline template from `--template-str`.
+    pub fn inline(source: &str) -> Result<Self> {
+        Self::parse("--template-str", source, false)
+    }
+
+    fn parse(name: &str, source: &str, escape_html: bool) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        registry.set_strict_mode(true);
+        if !escape_html {
+            registry.register_escape_fn(handlebars::no_escape);
+        }
+        registry.register_helper("money", Box::new(money_helper));
+        registry.register_helper("amount", Box::new(amount_helper));
+        registry.register_helper("percent", Box::new(percent_helper));
+        registry.register_helper("compact", Box::new(compact_helper));
+        registry.register_helper("trend", Box::new(trend_helper));
+        registry
+            .register_template_string(name, source)
+            .map_err(|e| template_error(name, &e))?;
+        Ok(Self {
+            registry,
+            name: name.to_string(),
+        })
+    }
+
+    /// Render `context` through the template, adding `generated_at` to it.
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let mut context = context.clone();
+        if let Value::Object(fields) = &mut context {
+            fields.insert("generated_at".into(), generated_at().into());
+        }
+        self.registry
+            .render(&self.name, &context)
+            .map_err(|e| render_error(&self.name, &e))
+    }
+}
+
+fn builtin_names() -> String {
+    BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn generated_at() -> String {
+    tz::format_timestamp(chrono::Utc::now(), "%Y-%m-%d %H:%M:%S %Z")
+}
+
+fn template_error(name: &str, err: &TemplateError) -> Error {
+    let reason = err.reason().to_string();
+    Error::Config(match err.pos() {
+        Some((line, column)) => {
+            format!("template '{name}' line {line}, column {column}: {reason}")
+        }
+        None => format!("template '{name}': {reason}"),
+    })
+}
+
+fn render_error(name: &str, err: &RenderError) -> Error {
+    let reason = match err.reason() {
+        RenderErrorReason::MissingVariable(Some(path)) => format!("missing field `{path}`"),
+        reason => reason.to_string(),
+    };
+    let name = err.template_name.as_deref().unwrap_or(name);
+    Error::Config(match (err.line_no, err.column_no) {
+        (Some(line), Some(column)) => {
+            format!("template '{name}' line {line}, column {column}: {reason}")
+        }
+        _ => format!("template '{name}': {reason}"),
+    })
+}
+
+/// Numeric parameter `idx`; `None` for `null`, an error for a missing field.
+fn number_param(helper: &Helper<'_>, idx: usize) -> std::result::Result<Option<f64>, RenderError> {
+    let param = helper
+        .param(idx)
+        .ok_or(RenderErrorReason::ParamNotFoundForIndex("number", idx))?;
+    if param.is_value_missing() {
+        return Err(RenderError::strict_error(param.relative_path()));
+    }
+    match param.value() {
+        Value::Null => Ok(None),
+        value => value
+            .as_f64()
+            .map(Some)
+            .ok_or_else(|| RenderErrorReason::InvalidParamType("a number or null").into()),
+    }
+}
+
+/// `{{money price currency}}`: a price with its currency symbol, as in the table.
+fn money_helper(
+    helper: &Helper<'_>,
+    _: &Handlebars<'_>,
+    _: &Context,
+    _: &mut RenderContext<'_, '_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let currency = helper
+        .param(1)
+        .and_then(|param| param.value().as_str())
+        .unwrap_or("");
+    let text = number_param(helper, 0)?.map_or_else(
+        || "-".to_string(),
+        |value| fmt::format_price(value, currency),
+    );
+    out.write(&text)?;
+    Ok(())
+}
+
+/// `{{amount to_amount}}`: a quantity with the table's decimals.
+fn amount_helper(
+    helper: &Helper<'_>,
+    _: &Handlebars<'_>,
+    _: &Context,
+    _: &mut RenderContext<'_, '_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let text = number_param(helper, 0)?.map_or_else(|| "-".to_string(), fmt::format_amount);
+    out.write(&text)?;
+    Ok(())
+}
+
+/// `{{percent change_24h}}`: a signed percentage such as `+1.50%`.
+fn percent_helper(
+    helper: &Helper<'_>,
+    _: &Handlebars<'_>,
+    _: &Context,
+    _: &mut RenderContext<'_, '_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let text = number_param(helper, 0)?.map_or_else(|| "-".to_string(), table::format_percent);
+    out.write(&text)?;
+    Ok(())
+}
+
+/// `{{compact market_cap}}`: a large number shortened to `1.2B`.
+fn compact_helper(
+    helper: &Helper<'_>,
+    _: &Handlebars<'_>,
+    _: &Context,
+    _: &mut RenderContext<'_, '_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let text = number_param(helper, 0)?.map_or_else(|| "-".to_string(), fmt::format_compact);
+    out.write(&text)?;
+    Ok(())
+}
+
+/// `{{trend change_24h}}`: `up`, `down`, or nothing, for CSS classes.
+fn trend_helper(
+    helper: &Helper<'_>,
+    _: &Handlebars<'_>,
+    _: &Context,
+    _: &mut RenderContext<'_, '_>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let class = match number_param(helper, 0)? {
+        Some(change) if change < 0.0 => "down",
+        Some(_) => "up",
+        None => "",
+    };
+    out.write(class)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::CoinPrice;
+    use serde_json::json;
+
+    fn sample_prices() -> Vec<CoinPrice> {
+        vec![
+            CoinPrice {
+                symbol: "BTC".into(),
+                name: "Bitcoin".into(),
+                price: 67_234.5,
+                change_24h: Some(1.5),
+                change_7d: Some(-3.25),
+                market_cap: Some(1_320_000_000_000.0),
+                currency: "USD".into(),
+                provider: "CoinGecko".into(),
+                ..Default::default()
+            },
+            CoinPrice {
+                symbol: "ETH".into(),
+                name: "Ethereum <ERC>".into(),
+                price: 3_456.78,
+                change_24h: Some(-0.5),
+                currency: "USD".into(),
+                provider: "CoinGecko".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn markdown_table_renders_one_row_per_price() {
+        let report = Report::from_name_or_path("markdown-table").unwrap();
+        let rendered = report
+            .render(&json!({ "prices": sample_prices() }))
+            .unwrap();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "| Symbol | Name | Price | 24h | 7d | Market Cap | Provider |",
+                "| --- | --- | ---: | ---: | ---: | ---: | --- |",
+                "| BTC | Bitcoin | $67,234.50 | +1.50% | -3.25% | 1.32T | CoinGecko |",
+                "| ETH | Ethereum <ERC> | $3,456.78 | -0.50% | - | - | CoinGecko |",
+            ]
+        );
+    }
+
+    #[test]
+    fn html_report_escapes_names_and_marks_trends() {
+        let report = Report::from_name_or_path("html-report").unwrap();
+        let rendered = report
+            .render(&json!({ "prices": sample_prices() }))
+            .unwrap();
+        assert!(rendered.starts_with("<!DOCTYPE html>"), "{rendered}");
+        assert!(
+            rendered.contains(
+                "<tr><td>BTC</td><td>Bitcoin</td><td class=\"num\">$67,234.50</td>\
+                 <td class=\"num up\">+1.50%</td><td class=\"num down\">-3.25%</td>\
+                 <td class=\"num\">1.32T</td><td>CoinGecko</td></tr>"
+            ),
+            "{rendered}"
+        );
+        assert!(
+            rendered.contains("<td>Ethereum &lt;ERC&gt;</td>"),
+            "{rendered}"
+        );
+        assert!(!rendered.contains("<thead><tr><th>Amount"), "{rendered}");
+    }
+
+    #[test]
+    fn syntax_errors_report_line_and_column() {
+        let err = Report::inline("{{#each prices}}\n{{symbol}\n{{/each}}")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            err.contains("template '--template-str' line 2, column"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_fields_report_line_and_column() {
+        let report = Report::inline("{{#each prices}}\n  {{symbol}} {{prise}}\n{{/each}}").unwrap();
+        let err = report
+            .render(&json!({ "prices": sample_prices() }))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("template '--template-str' line 2, column 14: missing field `prise`"),
+            "{err}"
+        );
+    }
+}
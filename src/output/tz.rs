@@ -0,0 +1,141 @@
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serializer;
+
+use crate::error::{Error, Result};
+
+static DISPLAY_TZ: RwLock<DisplayTz> = RwLock::new(DisplayTz::Utc);
+
+/// Time zone used to display timestamps. Data is always fetched and filtered in UTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayTz {
+    #[default]
+    Utc,
+    /// The system time zone.
+    Local,
+    Named(chrono_tz::Tz),
+}
+
+impl FromStr for DisplayTz {
+    type Err = Error;
+
+    /// Accepts `utc`, `local`, or an IANA name such as `Asia/Tokyo`.
+    fn from_str(value: &str) -> Result<Self> {
+        let trimmed = value.trim();
+        if trimmed.eq_ignore_ascii_case("utc") {
+            return Ok(Self::Utc);
+        }
+        if trimmed.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        chrono_tz::Tz::from_str_insensitive(trimmed)
+            .map(Self::Named)
+            .map_err(|_| {
+                Error::Config(format!(
+                    "unknown time zone '{}' -- use an IANA name like Europe/Paris, utc, or local",
+                    trimmed
+                ))
+            })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DisplayTz {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Set the display time zone for this process.
+pub fn set_timezone(tz: DisplayTz) {
+    *DISPLAY_TZ.write().unwrap_or_else(|e| e.into_inner()) = tz;
+}
+
+/// The display time zone currently in effect.
+pub fn timezone() -> DisplayTz {
+    *DISPLAY_TZ.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Format `ts` with a `strftime` pattern in the display time zone.
+pub fn format_timestamp(ts: DateTime<Utc>, pattern: &str) -> String {
+    format_in(timezone(), ts, pattern)
+}
+
+fn format_in(tz: DisplayTz, ts: DateTime<Utc>, pattern: &str) -> String {
+    match tz {
+        DisplayTz::Utc => ts.format(pattern).to_string(),
+        DisplayTz::Local => ts.with_timezone(&chrono::Local).format(pattern).to_string(),
+        DisplayTz::Named(zone) => ts.with_timezone(&zone).format(pattern).to_string(),
+    }
+}
+
+fn rfc3339_in(tz: DisplayTz, ts: DateTime<Utc>) -> String {
+    match tz {
+        DisplayTz::Utc => ts.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        DisplayTz::Local => ts
+            .with_timezone(&chrono::Local)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, false),
+        DisplayTz::Named(zone) => ts
+            .with_timezone(&zone)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, false),
+    }
+}
+
+/// Serde `serialize_with` for timestamps: RFC 3339 with the display zone's offset applied.
+///
+/// UTC output is unchanged from chrono's default (`...Z`).
+pub fn serialize_timestamp<S: Serializer>(
+    ts: &DateTime<Utc>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match timezone() {
+        DisplayTz::Utc => serde::Serialize::serialize(ts, serializer),
+        tz => serializer.serialize_str(&rfc3339_in(tz, *ts)),
+    }
+}
+
+/// [`serialize_timestamp`] for optional timestamps; `None` stays `null`.
+pub fn serialize_optional_timestamp<S: Serializer>(
+    ts: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match ts {
+        Some(ts) => serialize_timestamp(ts, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parses_utc_local_and_iana_names() {
+        assert_eq!("UTC".parse::<DisplayTz>().unwrap(), DisplayTz::Utc);
+        assert_eq!("local".parse::<DisplayTz>().unwrap(), DisplayTz::Local);
+        assert_eq!(
+            "asia/tokyo".parse::<DisplayTz>().unwrap(),
+            DisplayTz::Named(chrono_tz::Asia::Tokyo)
+        );
+        assert!("Mars/Olympus".parse::<DisplayTz>().is_err());
+    }
+
+    #[test]
+    fn labels_follow_dst_offset_change() {
+        let tz = DisplayTz::Named(chrono_tz::America::New_York);
+        // US clocks sprang forward at 2024-03-10 07:00 UTC.
+        let before = Utc.with_ymd_and_hms(2024, 3, 10, 6, 0, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 3, 10, 8, 0, 0).unwrap();
+
+        assert_eq!(format_in(tz, before, "%Y-%m-%d %H:%M"), "2024-03-10 01:00");
+        assert_eq!(format_in(tz, after, "%Y-%m-%d %H:%M"), "2024-03-10 04:00");
+        assert_eq!(rfc3339_in(tz, before), "2024-03-10T01:00:00-05:00");
+        assert_eq!(rfc3339_in(tz, after), "2024-03-10T04:00:00-04:00");
+        assert_eq!(rfc3339_in(DisplayTz::Utc, after), "2024-03-10T08:00:00Z");
+    }
+}
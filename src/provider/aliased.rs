@@ -0,0 +1,248 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tracing::debug;
+
+use super::{
+    Capabilities, CoinPrice, GlobalMetrics, HistoryInterval, MarketTicker, PriceHistory,
+    PriceProvider, TickerMatch, TrendingCoin,
+};
+use crate::config::SymbolAliases;
+use crate::error::Result;
+
+/// Wraps a provider so configured `[aliases]` are applied before its own symbol resolution.
+///
+/// Results are reported under the symbol the user typed, so provider fallback and
+/// output keep matching on the original input.
+pub(crate) struct AliasedProvider {
+    inner: Box<dyn PriceProvider>,
+    aliases: SymbolAliases,
+}
+
+impl AliasedProvider {
+    pub(crate) fn new(inner: Box<dyn PriceProvider>, aliases: SymbolAliases) -> Self {
+        Self { inner, aliases }
+    }
+
+    /// Translate symbols to provider ids, returning a map back to the user symbols.
+    fn translate(&self, symbols: &[String]) -> (Vec<String>, HashMap<String, String>) {
+        let mut originals = HashMap::new();
+        let translated = symbols
+            .iter()
+            .map(|symbol| match self.aliases.resolve(self.inner.id(), symbol) {
+                Some(alias) => {
+                    debug!(provider = self.inner.id(), symbol = %symbol, alias = %alias, "using configured alias");
+                    originals.insert(alias.to_uppercase(), symbol.to_uppercase());
+                    alias.to_string()
+                }
+                None => symbol.clone(),
+            })
+            .collect();
+        (translated, originals)
+    }
+
+    fn restore_histories(histories: &mut [PriceHistory], originals: &HashMap<String, String>) {
+        for history in histories {
+            if let Some(original) = originals.get(&history.symbol.to_uppercase()) {
+                history.symbol = original.clone();
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for AliasedProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn attribution(&self) -> Option<&str> {
+        self.inner.attribution()
+    }
+
+    async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
+        let (translated, originals) = self.translate(symbols);
+        let mut prices = self.inner.get_prices(&translated, currency).await?;
+        for price in &mut prices {
+            if let Some(original) = originals.get(&price.symbol.to_uppercase()) {
+                price.symbol = original.clone();
+            }
+        }
+        Ok(prices)
+    }
+
+    async fn get_prices_in(
+        &self,
+        symbols: &[String],
+        currencies: &[String],
+    ) -> Result<Vec<CoinPrice>> {
+        let (translated, originals) = self.translate(symbols);
+        let mut prices = self.inner.get_prices_in(&translated, currencies).await?;
+        for price in &mut prices {
+            if let Some(original) = originals.get(&price.symbol.to_uppercase()) {
+                price.symbol = original.clone();
+            }
+        }
+        Ok(prices)
+    }
+
+    async fn get_price_history(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        days: u32,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        let (translated, originals) = self.translate(symbols);
+        let mut histories = self
+            .inner
+            .get_price_history(&translated, currency, days, interval)
+            .await?;
+        Self::restore_histories(&mut histories, &originals);
+        Ok(histories)
+    }
+
+    async fn get_price_history_window(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        let (translated, originals) = self.translate(symbols);
+        let mut histories = self
+            .inner
+            .get_price_history_window(&translated, currency, start, end, interval)
+            .await?;
+        Self::restore_histories(&mut histories, &originals);
+        Ok(histories)
+    }
+
+    async fn get_fiat_history(
+        &self,
+        base: &str,
+        targets: &[String],
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+    ) -> Result<Vec<PriceHistory>> {
+        self.inner.get_fiat_history(base, targets, start, end).await
+    }
+
+    async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
+        self.inner.search_tickers(query, limit).await
+    }
+
+    async fn get_top_coins(&self, limit: usize, currency: &str) -> Result<Vec<CoinPrice>> {
+        self.inner.get_top_coins(limit, currency).await
+    }
+
+    async fn get_global_metrics(&self, currency: &str) -> Result<GlobalMetrics> {
+        self.inner.get_global_metrics(currency).await
+    }
+
+    async fn get_trending(&self, currency: &str) -> Result<Vec<TrendingCoin>> {
+        self.inner.get_trending(currency).await
+    }
+
+    async fn get_markets(&self, symbol: &str, limit: usize) -> Result<Vec<MarketTicker>> {
+        let (translated, _) = self.translate(std::slice::from_ref(&symbol.to_string()));
+        self.inner.get_markets(&translated[0], limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use std::sync::{Arc, Mutex};
+
+    struct RecordingProvider {
+        requested: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl PriceProvider for RecordingProvider {
+        fn name(&self) -> &str {
+            "CoinGecko"
+        }
+
+        fn id(&self) -> &str {
+            "coingecko"
+        }
+
+        async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
+            self.requested
+                .lock()
+                .unwrap()
+                .extend(symbols.iter().cloned());
+            if symbols.is_empty() {
+                return Err(Error::no_results(self.name(), symbols, currency));
+            }
+            Ok(symbols
+                .iter()
+                .map(|symbol| CoinPrice {
+                    symbol: symbol.to_uppercase(),
+                    name: symbol.clone(),
+                    price: 1.0,
+                    change_24h: None,
+                    change_7d: None,
+                    change_30d: None,
+                    market_cap: None,
+                    volume_24h: None,
+                    circulating_supply: None,
+                    rank: None,
+                    currency: currency.to_uppercase(),
+                    provider: "CoinGecko".to_string(),
+                    timestamp: chrono::Utc::now(),
+                    quote_time_estimated: false,
+                    converted: false,
+                    fx_rate: None,
+                    ath: None,
+                    ath_date: None,
+                    atl: None,
+                    atl_date: None,
+                    bid: None,
+                    ask: None,
+                    since_date: None,
+                    since_price: None,
+                    since_change_pct: None,
+                    delta_pct: None,
+                    delta_since: None,
+                    attribution: None,
+                    prices: Vec::new(),
+                    extended: None,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn get_prices_uses_alias_and_reports_user_symbol() {
+        let aliases: SymbolAliases =
+            toml::from_str(r#"rndr = { coingecko = "render-token", cmc = "RNDR" }"#).unwrap();
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let provider = AliasedProvider::new(
+            Box::new(RecordingProvider {
+                requested: Arc::clone(&requested),
+            }),
+            aliases,
+        );
+
+        let prices = provider
+            .get_prices(&["rndr".to_string(), "btc".to_string()], "usd")
+            .await
+            .unwrap();
+
+        let symbols: Vec<&str> = prices.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["RNDR", "BTC"]);
+        assert_eq!(*requested.lock().unwrap(), vec!["render-token", "btc"]);
+    }
+}
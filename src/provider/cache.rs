@@ -1,33 +1,227 @@
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
-use tracing::debug;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, warn};
+
+use crate::error::Error;
+
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// A lookup the cache could not answer, kept so an offline request can say which
+/// entry it needed.
+#[derive(Debug)]
+pub struct CacheMiss {
+    provider: String,
+    key: String,
+    /// Age of the cached copy that was too old, if there was one.
+    age: Option<chrono::Duration>,
+    /// Oldest copy the lookup accepts, when it has a fixed TTL.
+    max_age: Option<chrono::Duration>,
+}
+
+impl CacheMiss {
+    /// Clear the caller to fetch, or fail with the error naming this entry and how
+    /// fresh it would have had to be when offline.
+    pub fn ensure_online(self) -> Result<(), Error> {
+        if !is_offline() {
+            return Ok(());
+        }
+        let detail = match (self.age, self.max_age) {
+            (Some(age), Some(max_age)) => format!(
+                " (cached copy is {} old; it must be under {})",
+                format_age(age),
+                format_age(max_age)
+            ),
+            (Some(age), None) => {
+                format!(" (cached copy from {} ago has expired)", format_age(age))
+            }
+            (None, Some(max_age)) => format!(" (needs a copy under {} old)", format_age(max_age)),
+            (None, None) => String::new(),
+        };
+        Err(Error::Config(format!(
+            "offline: no cached data for {}/{}{}",
+            provider_dir(&self.provider),
+            self.key,
+            detail
+        )))
+    }
+}
 
 #[derive(Debug, Serialize, serde::Deserialize)]
 struct CacheEnvelope<T> {
     fetched_at_unix: i64,
+    /// Request key the entry was stored under, shown by `pricr cache ls`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
     value: T,
 }
 
-pub async fn read_json<T: DeserializeOwned>(provider: &str, key: &str, ttl_secs: i64) -> Option<T> {
-    let path = cache_path(provider, key)?;
-    let raw = tokio::fs::read_to_string(&path).await.ok()?;
-    let envelope: CacheEnvelope<T> = serde_json::from_str(&raw).ok()?;
+/// Envelope fields needed to list an entry, without decoding the payload.
+#[derive(Debug, serde::Deserialize)]
+struct EnvelopeHeader {
+    fetched_at_unix: i64,
+    #[serde(default)]
+    key: Option<String>,
+}
+
+/// One file in the response cache.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    /// Cache directory of the provider, e.g. `coingecko`.
+    pub provider: String,
+    /// Request key; the file's hash for entries that predate recorded keys or are corrupt.
+    pub key: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Fetch time from the envelope, or the file's modification time when it cannot be read.
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// `false` when the file is not a valid cache envelope.
+    pub valid: bool,
+}
+
+/// Which entries [`clear_entries`] removes; unset filters match everything.
+#[derive(Debug, Clone, Default)]
+pub struct ClearFilter {
+    /// Provider id (`cmc`) or cache directory name (`coinmarketcap`).
+    pub provider: Option<String>,
+    /// Only entries fetched longer ago than this.
+    pub older_than: Option<chrono::Duration>,
+}
+
+impl ClearFilter {
+    fn matches(&self, entry: &CacheEntry, now: DateTime<Utc>) -> bool {
+        if let Some(provider) = &self.provider
+            && !entry.provider.eq_ignore_ascii_case(&provider_dir(provider))
+        {
+            return false;
+        }
+        match self.older_than {
+            // Entries of unknown age are only removed without an age filter.
+            Some(age) => entry.fetched_at.is_some_and(|at| now - at > age),
+            None => true,
+        }
+    }
+}
+
+/// Entries removed by [`clear_entries`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClearSummary {
+    pub removed: usize,
+    pub bytes: u64,
+}
+
+pub fn set_enabled(enabled: bool) {
+    CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn is_enabled() -> bool {
+    CACHE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Serve provider requests from the cache only; a [`CacheMiss`] fails its
+/// [`ensure_online`](CacheMiss::ensure_online) instead of reaching the network.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
 
-    let age_secs = chrono::Utc::now().timestamp() - envelope.fetched_at_unix;
-    if age_secs < 0 || age_secs > ttl_secs {
-        return None;
+/// The error for an offline request that skipped the cache entirely.
+pub fn offline_error(provider: &str) -> Error {
+    Error::Config(format!(
+        "offline: no cached data for {} -- this request is never cached",
+        provider
+    ))
+}
+
+fn format_age(age: chrono::Duration) -> String {
+    crate::output::fmt::format_age(age)
+}
+
+/// Read `provider`'s entry for `key` under `dir` if it is at most `ttl_secs` old.
+///
+/// `dir` is the client's [`cache_dir`](super::http::HttpClient::cache_dir); `None`
+/// always misses.
+pub async fn read_json<T: DeserializeOwned>(
+    dir: Option<&Path>,
+    provider: &str,
+    key: &str,
+    ttl_secs: i64,
+) -> Result<T, CacheMiss> {
+    let max_age = chrono::Duration::seconds(ttl_secs);
+    lookup(dir, provider, key, Some(max_age), |_, fetched_at| {
+        let age = Utc::now() - fetched_at;
+        age >= chrono::Duration::zero() && age <= max_age
+    })
+    .await
+}
+
+/// Read a cached value that `fresh` accepts given the value and when it was fetched.
+///
+/// For payloads whose expiry depends on their content, like exchange rates that are
+/// only republished once per business day.
+pub async fn read_json_if<T: DeserializeOwned>(
+    dir: Option<&Path>,
+    provider: &str,
+    key: &str,
+    fresh: impl FnOnce(&T, DateTime<Utc>) -> bool,
+) -> Result<T, CacheMiss> {
+    lookup(dir, provider, key, None, fresh).await
+}
+
+async fn lookup<T: DeserializeOwned>(
+    dir: Option<&Path>,
+    provider: &str,
+    key: &str,
+    max_age: Option<chrono::Duration>,
+    fresh: impl FnOnce(&T, DateTime<Utc>) -> bool,
+) -> Result<T, CacheMiss> {
+    let mut age = None;
+    if is_enabled()
+        && let Some((value, fetched_at)) = read_envelope::<T>(dir, provider, key).await
+    {
+        if fresh(&value, fetched_at) {
+            return Ok(value);
+        }
+        age = Some(Utc::now() - fetched_at);
     }
+    Err(CacheMiss {
+        provider: provider.to_string(),
+        key: key.to_string(),
+        age,
+        max_age,
+    })
+}
 
-    Some(envelope.value)
+async fn read_envelope<T: DeserializeOwned>(
+    dir: Option<&Path>,
+    provider: &str,
+    key: &str,
+) -> Option<(T, DateTime<Utc>)> {
+    let path = cache_path(dir?, provider, key);
+    let raw = tokio::fs::read_to_string(&path).await.ok()?;
+    let envelope: CacheEnvelope<T> = serde_json::from_str(&raw).ok()?;
+    let fetched_at = DateTime::from_timestamp(envelope.fetched_at_unix, 0)?;
+    Some((envelope.value, fetched_at))
 }
 
-pub async fn write_json<T: Serialize>(provider: &str, key: &str, value: &T) {
-    let Some(path) = cache_path(provider, key) else {
+/// Store `value` as `provider`'s entry for `key` under `dir`; `None` stores nothing.
+pub async fn write_json<T: Serialize>(dir: Option<&Path>, provider: &str, key: &str, value: &T) {
+    if !is_enabled() {
+        return;
+    }
+
+    let Some(dir) = dir else {
         return;
     };
+    let path = cache_path(dir, provider, key);
 
     let Some(parent) = path.parent() else {
         return;
@@ -40,6 +234,7 @@ pub async fn write_json<T: Serialize>(provider: &str, key: &str, value: &T) {
 
     let envelope = CacheEnvelope {
         fetched_at_unix: chrono::Utc::now().timestamp(),
+        key: Some(key.to_string()),
         value,
     };
 
@@ -56,11 +251,123 @@ pub async fn write_json<T: Serialize>(provider: &str, key: &str, value: &T) {
     }
 }
 
-fn cache_path(provider: &str, key: &str) -> Option<PathBuf> {
-    let root = cache_root()?;
+fn cache_path(dir: &Path, provider: &str, key: &str) -> PathBuf {
     let provider_dir = sanitize_component(provider);
     let file = format!("{}.json", hash_key(key));
-    Some(root.join("pricr").join(provider_dir).join(file))
+    dir.join(provider_dir).join(file)
+}
+
+/// Default directory for cached responses: `$XDG_CACHE_HOME/pricr` or `~/.cache/pricr`.
+pub fn cache_dir() -> Option<PathBuf> {
+    Some(cache_root()?.join("pricr"))
+}
+
+/// Cache directory name for a provider id; most providers cache under their own id.
+fn provider_dir(provider: &str) -> String {
+    match provider.to_ascii_lowercase().as_str() {
+        "cmc" => "coinmarketcap".to_string(),
+        "exchangerate-api" => "open-er-api".to_string(),
+        other => sanitize_component(other),
+    }
+}
+
+/// Every entry under `dir`, sorted by provider and key.
+///
+/// A missing directory is an empty cache. Unreadable directories are skipped with a
+/// warning, and files that are not valid envelopes are listed with `valid: false`.
+pub fn list_entries(dir: &Path) -> Vec<CacheEntry> {
+    let mut entries = Vec::new();
+    for provider_path in read_dir_logged(dir) {
+        if !provider_path.is_dir() {
+            continue;
+        }
+        let provider = provider_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        for path in read_dir_logged(&provider_path) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            if let Some(entry) = read_entry(&provider, path) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.provider.cmp(&b.provider).then_with(|| a.key.cmp(&b.key)));
+    entries
+}
+
+/// Delete the entries under `dir` matching `filter`.
+pub fn clear_entries(dir: &Path, filter: &ClearFilter) -> ClearSummary {
+    let now = Utc::now();
+    let mut summary = ClearSummary::default();
+    for entry in list_entries(dir) {
+        if !filter.matches(&entry, now) {
+            continue;
+        }
+        match std::fs::remove_file(&entry.path) {
+            Ok(()) => {
+                summary.removed += 1;
+                summary.bytes += entry.size_bytes;
+            }
+            Err(err) => {
+                warn!(path = %entry.path.display(), error = %err, "failed to remove cache entry");
+            }
+        }
+    }
+    summary
+}
+
+fn read_dir_logged(dir: &Path) -> Vec<PathBuf> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            warn!(path = %dir.display(), error = %err, "skipping unreadable cache directory");
+            Vec::new()
+        }
+    }
+}
+
+fn read_entry(provider: &str, path: PathBuf) -> Option<CacheEntry> {
+    let metadata = match std::fs::metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            warn!(path = %path.display(), error = %err, "skipping unreadable cache entry");
+            return None;
+        }
+    };
+    let hash = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let header = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<EnvelopeHeader>(&raw).ok());
+    let (key, fetched_at, valid) = match header {
+        Some(header) => (
+            header.key.unwrap_or(hash),
+            DateTime::from_timestamp(header.fetched_at_unix, 0),
+            true,
+        ),
+        None => (
+            hash,
+            metadata.modified().ok().map(DateTime::<Utc>::from),
+            false,
+        ),
+    };
+    Some(CacheEntry {
+        provider: provider.to_string(),
+        key,
+        path,
+        size_bytes: metadata.len(),
+        fetched_at,
+        valid,
+    })
 }
 
 fn cache_root() -> Option<PathBuf> {
@@ -91,3 +398,98 @@ fn hash_key(key: &str) -> String {
     key.hash(&mut hasher);
     format!("{:016x}", hasher.finish())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_entry(dir: &Path, provider: &str, key: &str, age_secs: i64) {
+        let provider_dir = dir.join(provider);
+        std::fs::create_dir_all(&provider_dir).unwrap();
+        let envelope = CacheEnvelope {
+            fetched_at_unix: Utc::now().timestamp() - age_secs,
+            key: Some(key.to_string()),
+            value: "{}",
+        };
+        std::fs::write(
+            provider_dir.join(format!("{}.json", hash_key(key))),
+            serde_json::to_string(&envelope).unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn temp_cache(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pricr-cache-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn lists_entries_and_flags_corrupt_files() {
+        let dir = temp_cache("list");
+        write_entry(&dir, "coingecko", "markets:usd:bitcoin", 60);
+        write_entry(&dir, "coinmarketcap", "map:*", 7200);
+        std::fs::write(dir.join("coingecko").join("0badc0de.json"), "{not json").unwrap();
+        std::fs::write(dir.join("coingecko").join("notes.txt"), "ignored").unwrap();
+        std::fs::write(dir.join("stray.json"), "{}").unwrap();
+
+        let entries = list_entries(&dir);
+        let keys: Vec<(&str, &str, bool)> = entries
+            .iter()
+            .map(|e| (e.provider.as_str(), e.key.as_str(), e.valid))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("coingecko", "0badc0de", false),
+                ("coingecko", "markets:usd:bitcoin", true),
+                ("coinmarketcap", "map:*", true),
+            ]
+        );
+        assert!(entries.iter().all(|e| e.fetched_at.is_some()));
+        assert!(entries[1].size_bytes > 0);
+
+        assert!(list_entries(&dir.join("missing")).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clear_filters_by_provider_and_age() {
+        let dir = temp_cache("clear");
+        write_entry(&dir, "coingecko", "fresh", 60);
+        write_entry(&dir, "coingecko", "stale", 3 * 86_400);
+        write_entry(&dir, "coinmarketcap", "stale", 3 * 86_400);
+
+        let summary = clear_entries(
+            &dir,
+            &ClearFilter {
+                provider: Some("coingecko".into()),
+                older_than: Some(chrono::Duration::days(1)),
+            },
+        );
+        assert_eq!(summary.removed, 1);
+        let left: Vec<(String, String)> = list_entries(&dir)
+            .into_iter()
+            .map(|e| (e.provider, e.key))
+            .collect();
+        assert_eq!(
+            left,
+            vec![
+                ("coingecko".to_string(), "fresh".to_string()),
+                ("coinmarketcap".to_string(), "stale".to_string()),
+            ]
+        );
+
+        let summary = clear_entries(
+            &dir,
+            &ClearFilter {
+                provider: Some("cmc".into()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(summary.removed, 1);
+        assert_eq!(clear_entries(&dir, &ClearFilter::default()).removed, 1);
+        assert!(list_entries(&dir).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
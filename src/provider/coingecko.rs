@@ -1,76 +1,166 @@
 use async_trait::async_trait;
-use futures::future::join_all;
-use reqwest::Client;
+use reqwest::RequestBuilder;
 use serde::Deserialize;
 use std::collections::HashMap;
-use tracing::{debug, trace};
+use tokio::sync::RwLock;
+use tracing::{debug, trace, warn};
 
 use super::cache;
-use super::{CoinPrice, HistoryInterval, PriceHistory, PricePoint, PriceProvider};
+use super::http::HttpClient;
+use super::{
+    Capabilities, CoinPrice, GlobalMetrics, HistoryInterval, MAX_TOP_COINS, MarketTicker,
+    PriceHistory, PricePoint, PriceProvider, ResolvedInterval, TickerMatch, TrendingCoin,
+    fetch_all, fetch_chunks,
+};
 use crate::error::{Error, Result};
 
-const BASE_URL: &str = "https://api.coingecko.com/api/v3";
+pub const BASE_URL: &str = "https://api.coingecko.com/api/v3";
+/// API root for paid plans; keyed requests on the pro tier go here.
+pub const PRO_BASE_URL: &str = "https://pro-api.coingecko.com/api/v3";
+/// Client-side requests per minute when no API key is configured.
+pub const KEYLESS_RATE_LIMIT_PER_MIN: u32 = 10;
 const PRICE_CACHE_TTL_SECS: i64 = 30;
+/// Coin ids per `coins/markets` or `simple/price` request; longer lists are split so
+/// URLs stay short and `coins/markets` stays within one page.
+pub const MAX_IDS_PER_REQUEST: usize = 50;
+/// Credit CoinGecko's free API terms ask for wherever its data is shown.
+pub const ATTRIBUTION: &str = "Data by CoinGecko";
 const HOURLY_HISTORY_CACHE_TTL_SECS: i64 = 60 * 60;
 const DAILY_HISTORY_CACHE_TTL_SECS: i64 = 12 * 60 * 60;
+/// Windows that ended in the past no longer change.
+const PAST_HISTORY_CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+/// `days` sent for a window with no start, i.e. all available history.
+const ALL_HISTORY_DAYS: u32 = 36_500;
+const SEARCH_CACHE_TTL_SECS: i64 = 10 * 60;
+const SUPPORTED_CURRENCIES_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+/// Quote currencies accepted without fetching `/simple/supported_vs_currencies`.
+const COMMON_VS_CURRENCIES: &[&str] = &[
+    "usd", "eur", "gbp", "jpy", "cny", "cad", "aud", "chf", "krw", "inr", "brl", "btc", "eth",
+];
 
-/// CoinGecko price provider -- free public API, no key required.
+/// CoinGecko API plan a key belongs to.
+///
+/// Demo and pro keys share the same `CG-` prefix, so the tier cannot be inferred from
+/// the key itself; it defaults to demo unless configured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTier {
+    #[default]
+    Demo,
+    Pro,
+}
+
+impl ApiTier {
+    /// Request header that carries the key for this tier.
+    pub fn header_name(self) -> &'static str {
+        match self {
+            Self::Demo => "x-cg-demo-api-key",
+            Self::Pro => "x-cg-pro-api-key",
+        }
+    }
+}
+
+/// Common tickers (plus long-name aliases) mapped to CoinGecko API id and display name.
+const KNOWN_COINS: &[(&[&str], &str, &str)] = &[
+    (&["btc", "bitcoin"], "bitcoin", "Bitcoin"),
+    (&["eth", "ethereum"], "ethereum", "Ethereum"),
+    (&["usdt", "tether"], "tether", "Tether"),
+    (&["bnb"], "binancecoin", "BNB"),
+    (&["sol", "solana"], "solana", "Solana"),
+    (&["xrp", "ripple"], "ripple", "XRP"),
+    (&["usdc"], "usd-coin", "USDC"),
+    (&["ada", "cardano"], "cardano", "Cardano"),
+    (&["doge", "dogecoin"], "dogecoin", "Dogecoin"),
+    (&["dot", "polkadot"], "polkadot", "Polkadot"),
+    (&["matic", "polygon"], "matic-network", "Polygon"),
+    (&["ltc", "litecoin"], "litecoin", "Litecoin"),
+    (&["avax", "avalanche"], "avalanche-2", "Avalanche"),
+    (&["link", "chainlink"], "chainlink", "Chainlink"),
+    (&["atom", "cosmos"], "cosmos", "Cosmos"),
+    (&["uni", "uniswap"], "uniswap", "Uniswap"),
+    (&["xlm", "stellar"], "stellar", "Stellar"),
+    (&["shib"], "shiba-inu", "Shiba Inu"),
+    (&["trx", "tron"], "tron", "TRON"),
+    (&["ton"], "the-open-network", "Toncoin"),
+    (&["pepe"], "pepe", "Pepe"),
+    (&["near"], "near", "NEAR"),
+    (&["apt", "aptos"], "aptos", "Aptos"),
+    (&["arb", "arbitrum"], "arbitrum", "Arbitrum"),
+    (&["op", "optimism"], "optimism", "Optimism"),
+    (&["sui"], "sui", "Sui"),
+];
+
+/// CoinGecko price provider -- free public API, optional demo/pro API key.
 pub struct CoinGecko {
-    client: Client,
+    client: HttpClient,
     base_url: String,
+    api_key: Option<(String, ApiTier)>,
+    supported_currencies: RwLock<Option<Vec<String>>>,
 }
 
 impl CoinGecko {
     /// Create a CoinGecko provider using the default production API URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built; [`try_new`](Self::try_new) returns
+    /// the error instead.
     pub fn new() -> Self {
         Self::with_base_url(BASE_URL)
     }
 
+    /// Create a CoinGecko provider using the default production API URL, failing
+    /// instead of panicking when the HTTP client cannot be built.
+    pub fn try_new() -> Result<Self> {
+        Ok(Self::with_client(HttpClient::try_default()?, BASE_URL))
+    }
+
     /// Create a CoinGecko provider with a custom base URL.
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
-        let client = Client::builder()
-            .user_agent("pricr/0.1.0")
-            .build()
-            .expect("failed to build HTTP client");
+        Self::with_client(HttpClient::default(), base_url)
+    }
+
+    /// Create a CoinGecko provider that sends requests through `client`.
+    pub fn with_client(client: HttpClient, base_url: impl Into<String>) -> Self {
         Self {
             client,
             base_url: base_url.into(),
+            api_key: None,
+            supported_currencies: RwLock::new(None),
+        }
+    }
+
+    /// Send `api_key` with every request using the header for `tier`.
+    ///
+    /// The pro tier also switches the default public API URL to [`PRO_BASE_URL`];
+    /// custom base URLs are kept as-is.
+    pub fn with_api_key(mut self, api_key: impl Into<String>, tier: ApiTier) -> Self {
+        if tier == ApiTier::Pro && self.base_url == BASE_URL {
+            self.base_url = PRO_BASE_URL.to_string();
         }
+        self.api_key = Some((api_key.into(), tier));
+        self
+    }
+
+    /// GET request with the API key header attached when one is configured.
+    fn get(&self, url: &str) -> Result<RequestBuilder> {
+        let request = self.client.get(self.name(), url)?;
+        Ok(match &self.api_key {
+            Some((key, tier)) => request.header(tier.header_name(), key),
+            None => request,
+        })
     }
 
     /// Map common ticker symbols to (CoinGecko API id, display name).
     fn resolve(symbol: &str) -> (String, String) {
         let lower = symbol.to_lowercase();
-        let (id, name) = match lower.as_str() {
-            "btc" | "bitcoin" => ("bitcoin", "Bitcoin"),
-            "eth" | "ethereum" => ("ethereum", "Ethereum"),
-            "usdt" | "tether" => ("tether", "Tether"),
-            "bnb" => ("binancecoin", "BNB"),
-            "sol" | "solana" => ("solana", "Solana"),
-            "xrp" | "ripple" => ("ripple", "XRP"),
-            "usdc" => ("usd-coin", "USDC"),
-            "ada" | "cardano" => ("cardano", "Cardano"),
-            "doge" | "dogecoin" => ("dogecoin", "Dogecoin"),
-            "dot" | "polkadot" => ("polkadot", "Polkadot"),
-            "matic" | "polygon" => ("matic-network", "Polygon"),
-            "ltc" | "litecoin" => ("litecoin", "Litecoin"),
-            "avax" | "avalanche" => ("avalanche-2", "Avalanche"),
-            "link" | "chainlink" => ("chainlink", "Chainlink"),
-            "atom" | "cosmos" => ("cosmos", "Cosmos"),
-            "uni" | "uniswap" => ("uniswap", "Uniswap"),
-            "xlm" | "stellar" => ("stellar", "Stellar"),
-            "shib" => ("shiba-inu", "Shiba Inu"),
-            "trx" | "tron" => ("tron", "TRON"),
-            "ton" => ("the-open-network", "Toncoin"),
-            "pepe" => ("pepe", "Pepe"),
-            "near" => ("near", "NEAR"),
-            "apt" | "aptos" => ("aptos", "Aptos"),
-            "arb" | "arbitrum" => ("arbitrum", "Arbitrum"),
-            "op" | "optimism" => ("optimism", "Optimism"),
-            "sui" => ("sui", "Sui"),
-            _ => return (lower.clone(), capitalize(&lower)),
-        };
-        (id.to_string(), name.to_string())
+        match KNOWN_COINS
+            .iter()
+            .find(|(aliases, _, _)| aliases.contains(&lower.as_str()))
+        {
+            Some((_, id, name)) => (id.to_string(), name.to_string()),
+            None => (lower.clone(), capitalize(&lower)),
+        }
     }
 }
 
@@ -80,15 +170,197 @@ impl Default for CoinGecko {
     }
 }
 
-/// CoinGecko `/simple/price` response shape.
-/// Example: `{ "bitcoin": { "usd": 50000, "usd_24h_change": 2.5, "usd_market_cap": 9.5e11 } }`
-type SimplePrice = HashMap<String, HashMap<String, f64>>;
+/// Tickers the provider resolves without a network lookup.
+pub fn known_symbols() -> impl Iterator<Item = &'static str> {
+    KNOWN_COINS.iter().map(|(aliases, _, _)| aliases[0])
+}
+
+/// Ticker of a known coin named by its ticker or a long-name alias, e.g. `bitcoin` -> `btc`.
+pub fn known_ticker(symbol: &str) -> Option<&'static str> {
+    let lower = symbol.trim().to_lowercase();
+    KNOWN_COINS
+        .iter()
+        .find(|(aliases, _, _)| aliases.contains(&lower.as_str()))
+        .map(|(aliases, _, _)| aliases[0])
+}
+
+/// One entry of the CoinGecko `/coins/markets` response.
+#[derive(Debug, Deserialize)]
+struct MarketEntry {
+    id: String,
+    #[serde(default)]
+    symbol: String,
+    name: String,
+    current_price: Option<f64>,
+    market_cap: Option<f64>,
+    market_cap_rank: Option<u32>,
+    total_volume: Option<f64>,
+    circulating_supply: Option<f64>,
+    price_change_percentage_24h: Option<f64>,
+    price_change_percentage_24h_in_currency: Option<f64>,
+    price_change_percentage_7d_in_currency: Option<f64>,
+    price_change_percentage_30d_in_currency: Option<f64>,
+    #[serde(default)]
+    ath: Option<f64>,
+    #[serde(default)]
+    ath_date: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    atl: Option<f64>,
+    #[serde(default)]
+    atl_date: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    last_updated: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl MarketEntry {
+    fn to_price(&self, symbol: String, currency: &str, provider: &str) -> CoinPrice {
+        CoinPrice {
+            symbol,
+            name: self.name.clone(),
+            price: self.current_price.unwrap_or(0.0),
+            change_24h: self
+                .price_change_percentage_24h_in_currency
+                .or(self.price_change_percentage_24h),
+            change_7d: self.price_change_percentage_7d_in_currency,
+            change_30d: self.price_change_percentage_30d_in_currency,
+            market_cap: self.market_cap,
+            volume_24h: self.total_volume,
+            circulating_supply: self.circulating_supply,
+            rank: self.market_cap_rank,
+            currency: currency.to_uppercase(),
+            provider: provider.to_string(),
+            timestamp: self.last_updated.unwrap_or_else(chrono::Utc::now),
+            quote_time_estimated: self.last_updated.is_none(),
+            converted: false,
+            fx_rate: None,
+            ath: self.ath,
+            ath_date: self.ath_date,
+            atl: self.atl,
+            atl_date: self.atl_date,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        }
+    }
+}
+
+/// CoinGecko `/global` response.
+#[derive(Debug, Deserialize)]
+struct GlobalResponse {
+    data: GlobalData,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlobalData {
+    active_cryptocurrencies: Option<u64>,
+    #[serde(default)]
+    total_market_cap: HashMap<String, f64>,
+    #[serde(default)]
+    total_volume: HashMap<String, f64>,
+    #[serde(default)]
+    market_cap_percentage: HashMap<String, f64>,
+    updated_at: Option<i64>,
+}
+
+/// CoinGecko `/search/trending` response; only the `coins` section is used.
+#[derive(Debug, Deserialize)]
+struct TrendingResponse {
+    #[serde(default)]
+    coins: Vec<TrendingEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendingEntry {
+    item: TrendingItem,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendingItem {
+    id: String,
+    symbol: String,
+    name: String,
+    market_cap_rank: Option<u32>,
+}
+
+/// CoinGecko `/coins/{id}/tickers` response.
+#[derive(Debug, Deserialize)]
+struct TickersResponse {
+    #[serde(default)]
+    tickers: Vec<TickerEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TickerEntry {
+    base: String,
+    target: String,
+    market: TickerMarket,
+    last: Option<f64>,
+    #[serde(default)]
+    converted_volume: HashMap<String, f64>,
+    trust_score: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TickerMarket {
+    name: String,
+}
+
+/// Highest-volume markets first, keeping one row per exchange and pair.
+fn top_markets(tickers: Vec<TickerEntry>, limit: usize, provider: &str) -> Vec<MarketTicker> {
+    let mut markets: Vec<MarketTicker> = tickers
+        .into_iter()
+        .map(|ticker| MarketTicker {
+            exchange: ticker.market.name,
+            pair: format!(
+                "{}/{}",
+                ticker.base.to_uppercase(),
+                ticker.target.to_uppercase()
+            ),
+            last_price: ticker.last,
+            volume_usd: ticker.converted_volume.get("usd").copied(),
+            trust_score: ticker.trust_score,
+            provider: provider.to_string(),
+        })
+        .collect();
+    markets.sort_by(|a, b| {
+        b.volume_usd
+            .unwrap_or(0.0)
+            .total_cmp(&a.volume_usd.unwrap_or(0.0))
+    });
+
+    let mut seen = std::collections::HashSet::new();
+    markets.retain(|market| seen.insert((market.exchange.clone(), market.pair.clone())));
+    markets.truncate(limit);
+    markets
+}
 
 #[derive(Debug, Deserialize)]
 struct MarketChartResponse {
     prices: Vec<[f64; 2]>,
 }
 
+/// CoinGecko `/search` response; only the `coins` section is used.
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    coins: Vec<SearchCoin>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchCoin {
+    id: String,
+    symbol: String,
+    name: String,
+    market_cap_rank: Option<u32>,
+}
+
 #[async_trait]
 impl PriceProvider for CoinGecko {
     fn name(&self) -> &str {
@@ -99,165 +371,731 @@ impl PriceProvider for CoinGecko {
         "coingecko"
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            history: true,
+            history_window: true,
+            search: true,
+            top: true,
+            global: true,
+            trending: true,
+            markets: true,
+            ..Capabilities::PRICES_ONLY
+        }
+    }
+
+    fn attribution(&self) -> Option<&str> {
+        Some(ATTRIBUTION)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
+        self.ensure_currency(currency).await?;
+        let symbols = &super::normalize_symbols(symbols);
         let resolved: Vec<(String, String)> = symbols.iter().map(|s| Self::resolve(s)).collect();
-        let ids_param: String = resolved
+        // `btc` and `bitcoin` resolve to the same id; request it once.
+        let mut ids: Vec<&str> = Vec::new();
+        for (id, _) in &resolved {
+            if !ids.contains(&id.as_str()) {
+                ids.push(id);
+            }
+        }
+        let cur = currency.to_lowercase();
+
+        let data = fetch_chunks(
+            self.name(),
+            &ids,
+            MAX_IDS_PER_REQUEST,
+            self.client.max_concurrency(),
+            |chunk| self.fetch_price_chunk(chunk, &cur),
+        )
+        .await?;
+
+        let mut results = Vec::new();
+        for (i, (cg_id, _)) in resolved.iter().enumerate() {
+            if let Some(entry) = data.iter().find(|entry| entry.id == *cg_id) {
+                results.push(entry.to_price(symbols[i].to_uppercase(), &cur, self.name()));
+            }
+        }
+
+        if results.is_empty() {
+            return Err(Error::no_results(self.name(), symbols, currency));
+        }
+
+        Ok(results)
+    }
+
+    /// The first currency comes from `coins/markets` for the full row; the others share
+    /// one `simple/price` request, which takes several `vs_currencies` at once.
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_prices_in(
+        &self,
+        symbols: &[String],
+        currencies: &[String],
+    ) -> Result<Vec<CoinPrice>> {
+        let Some(first) = currencies.first() else {
+            return Err(Error::no_results(self.name(), symbols, ""));
+        };
+        for currency in &currencies[1..] {
+            self.ensure_currency(currency).await?;
+        }
+        let mut rows = self.get_prices(symbols, first).await?;
+
+        let mut ids: Vec<String> = Vec::new();
+        for row in &rows {
+            let (id, _) = Self::resolve(&row.symbol);
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        let rest: Vec<String> = currencies[1..].iter().map(|c| c.to_lowercase()).collect();
+        let vs_param = rest.join(",");
+
+        // The rows already hold the first currency, so a failure here only leaves gaps.
+        let quotes: HashMap<String, HashMap<String, f64>> = if rest.is_empty() {
+            HashMap::new()
+        } else {
+            fetch_chunks(
+                self.name(),
+                &ids,
+                MAX_IDS_PER_REQUEST,
+                self.client.max_concurrency(),
+                |chunk| self.fetch_simple_price_chunk(chunk, &vs_param),
+            )
+            .await
+            .inspect_err(|err| warn!(error = %err, "CoinGecko multi-currency lookup failed"))
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+        };
+
+        for row in &mut rows {
+            let (id, _) = Self::resolve(&row.symbol);
+            let by_currency = quotes.get(&id);
+            row.prices = std::iter::once((first.to_uppercase(), Some(row.price)))
+                .chain(rest.iter().map(|currency| {
+                    (
+                        currency.to_uppercase(),
+                        by_currency.and_then(|quote| quote.get(currency)).copied(),
+                    )
+                }))
+                .collect();
+        }
+
+        Ok(rows)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_price_history(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        days: u32,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        self.fetch_histories(symbols, currency, ChartSpan::Days(days), interval)
+            .await
+    }
+
+    /// `market_chart` counts `days` back from now, so windows reaching the last day use
+    /// it and windows that ended earlier use `market_chart/range` instead.
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_price_history_window(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        let now = chrono::Utc::now();
+        let span = if end >= now - chrono::Duration::days(1) {
+            ChartSpan::Days(start.map_or(ALL_HISTORY_DAYS, |start| {
+                (now.date_naive() - start.date_naive()).num_days().max(1) as u32
+            }))
+        } else {
+            ChartSpan::Range {
+                from: start.map_or(0, |start| start.timestamp()),
+                to: end.timestamp(),
+            }
+        };
+        self.fetch_histories(symbols, currency, span, interval)
+            .await
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_top_coins(&self, limit: usize, currency: &str) -> Result<Vec<CoinPrice>> {
+        self.ensure_currency(currency).await?;
+        let limit = limit.clamp(1, MAX_TOP_COINS);
+        let cur = currency.to_lowercase();
+        let url = format!(
+            "{}/coins/markets?vs_currency={}&order=market_cap_desc&per_page={}&page=1&price_change_percentage=24h,7d,30d",
+            self.base_url, cur, limit
+        );
+        let cache_key = format!("top_markets:{}:{}:{}", self.base_url, limit, cur);
+
+        debug!(url = %url, limit, "fetching top coins from CoinGecko");
+
+        let results: Vec<CoinPrice> = self
+            .fetch_markets(&url, &cache_key)
+            .await?
+            .iter()
+            .map(|entry| entry.to_price(entry.symbol.trim().to_uppercase(), &cur, self.name()))
+            .collect();
+
+        if results.is_empty() {
+            return Err(Error::no_results(self.name(), &[], currency));
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_global_metrics(&self, currency: &str) -> Result<GlobalMetrics> {
+        let cur = currency.to_lowercase();
+        let url = format!("{}/global", self.base_url);
+        let cache_key = format!("global:{}", self.base_url);
+
+        debug!(url = %url, "fetching global metrics from CoinGecko");
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coingecko",
+            &cache_key,
+            PRICE_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!("using cached CoinGecko global metrics");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self.client.send(self.name(), self.get(&url)?).await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinGecko global response");
+                trace!(body = %body, "CoinGecko global response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinGecko returned {}: {}", status, body),
+                    ));
+                }
+
+                cache::write_json(self.client.cache_dir(), "coingecko", &cache_key, &body).await;
+                body
+            }
+        };
+
+        let payload: GlobalResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CoinGecko global JSON: {}", e)))?;
+        let data = payload.data;
+        // Totals are keyed by every supported currency; an unknown one leaves them empty.
+        Ok(GlobalMetrics {
+            total_market_cap: data.total_market_cap.get(&cur).copied(),
+            total_volume_24h: data.total_volume.get(&cur).copied(),
+            btc_dominance: data.market_cap_percentage.get("btc").copied(),
+            eth_dominance: data.market_cap_percentage.get("eth").copied(),
+            active_coins: data.active_cryptocurrencies,
+            currency: cur.to_uppercase(),
+            provider: self.name().to_string(),
+            timestamp: data
+                .updated_at
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                .unwrap_or_else(chrono::Utc::now),
+        })
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_trending(&self, currency: &str) -> Result<Vec<TrendingCoin>> {
+        let cur = currency.to_lowercase();
+        let url = format!("{}/search/trending", self.base_url);
+        let cache_key = format!("trending:{}", self.base_url);
+
+        debug!(url = %url, "fetching trending coins from CoinGecko");
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coingecko",
+            &cache_key,
+            SEARCH_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!("using cached CoinGecko trending coins");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self.client.send(self.name(), self.get(&url)?).await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinGecko trending response");
+                trace!(body = %body, "CoinGecko trending response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinGecko returned {} for trending: {}", status, body),
+                    ));
+                }
+
+                cache::write_json(self.client.cache_dir(), "coingecko", &cache_key, &body).await;
+                body
+            }
+        };
+
+        let payload: TrendingResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CoinGecko trending JSON: {}", e)))?;
+        let mut coins: Vec<TrendingCoin> = payload
+            .coins
+            .into_iter()
+            .map(|entry| TrendingCoin {
+                id: entry.item.id,
+                symbol: entry.item.symbol.trim().to_uppercase(),
+                name: entry.item.name,
+                market_cap_rank: entry.item.market_cap_rank,
+                price: None,
+                change_24h: None,
+                currency: cur.to_uppercase(),
+                provider: self.name().to_string(),
+            })
+            .collect();
+        if coins.is_empty() {
+            return Err(Error::no_results(self.name(), &[], currency));
+        }
+
+        // One batched markets call prices the whole list; without it the list still stands.
+        let ids_param = coins
             .iter()
-            .map(|(id, _)| id.as_str())
+            .map(|coin| coin.id.as_str())
             .collect::<Vec<_>>()
             .join(",");
-        let cur = currency.to_lowercase();
+        let url = format!(
+            "{}/coins/markets?vs_currency={}&ids={}&price_change_percentage=24h,7d,30d",
+            self.base_url, cur, ids_param
+        );
+        let cache_key = format!("coins_markets:{}:{}:{}", self.base_url, ids_param, cur);
+        match self.fetch_markets(&url, &cache_key).await {
+            Ok(markets) => {
+                for coin in &mut coins {
+                    if let Some(entry) = markets.iter().find(|entry| entry.id == coin.id) {
+                        coin.price = entry.current_price;
+                        coin.change_24h = entry
+                            .price_change_percentage_24h_in_currency
+                            .or(entry.price_change_percentage_24h);
+                    }
+                }
+            }
+            Err(err) => {
+                debug!(error = %err, "pricing trending coins failed");
+            }
+        }
+
+        Ok(coins)
+    }
 
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_markets(&self, symbol: &str, limit: usize) -> Result<Vec<MarketTicker>> {
+        let (cg_id, _) = Self::resolve(symbol.trim());
         let url = format!(
-            "{}/simple/price?ids={}&vs_currencies={}&include_24hr_change=true&include_market_cap=true",
-            self.base_url, ids_param, cur
+            "{}/coins/{}/tickers?order=volume_desc",
+            self.base_url, cg_id
         );
-        let cache_key = format!("simple_price:{}:{}:{}", self.base_url, ids_param, cur);
+        let cache_key = format!("tickers:{}:{}", self.base_url, cg_id);
 
-        debug!(url = %url, "fetching prices from CoinGecko");
+        debug!(url = %url, symbol = %symbol, "fetching exchange markets from CoinGecko");
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("coingecko", &cache_key, PRICE_CACHE_TTL_SECS).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coingecko",
+            &cache_key,
+            SEARCH_CACHE_TTL_SECS,
+        )
+        .await
         {
-            debug!(ids = %ids_param, currency = %cur, "using cached CoinGecko prices");
-            cached_body
-        } else {
-            let resp = self.client.get(&url).send().await?;
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(status = %status, body_len = body.len(), "CoinGecko response");
-            trace!(body = %body, "CoinGecko response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "CoinGecko returned {}: {}",
-                    status, body
-                )));
+            Ok(cached_body) => {
+                debug!(symbol = %symbol, "using cached CoinGecko tickers");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self.client.send(self.name(), self.get(&url)?).await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinGecko tickers response");
+                trace!(body = %body, symbol = %symbol, "CoinGecko tickers response body");
 
-            cache::write_json("coingecko", &cache_key, &body).await;
-            body
+                if status == reqwest::StatusCode::NOT_FOUND {
+                    return Err(Error::no_results(self.name(), &[symbol.to_string()], ""));
+                }
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinGecko returned {} for tickers: {}", status, body),
+                    ));
+                }
+
+                cache::write_json(self.client.cache_dir(), "coingecko", &cache_key, &body).await;
+                body
+            }
         };
 
-        let data: SimplePrice = serde_json::from_str(&body)
-            .map_err(|e| Error::Parse(format!("CoinGecko JSON: {}", e)))?;
+        let payload: TickersResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CoinGecko tickers JSON: {}", e)))?;
+        let markets = top_markets(payload.tickers, limit, self.name());
+        if markets.is_empty() {
+            return Err(Error::no_results(self.name(), &[symbol.to_string()], ""));
+        }
 
-        let change_key = format!("{}_24h_change", cur);
-        let cap_key = format!("{}_market_cap", cur);
+        Ok(markets)
+    }
 
-        let mut results = Vec::new();
-        for (i, (cg_id, display_name)) in resolved.iter().enumerate() {
-            if let Some(coin_data) = data.get(cg_id.as_str()) {
-                let price = coin_data.get(&cur).copied().unwrap_or(0.0);
-                results.push(CoinPrice {
-                    symbol: symbols[i].to_uppercase(),
-                    name: display_name.clone(),
-                    price,
-                    change_24h: coin_data.get(&change_key).copied(),
-                    market_cap: coin_data.get(&cap_key).copied(),
-                    currency: cur.to_uppercase(),
-                    provider: self.name().to_string(),
-                    timestamp: chrono::Utc::now(),
-                });
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
+        let coins = self.search_coins(query).await?;
+
+        let matches = coins
+            .into_iter()
+            .take(limit)
+            .map(|coin| TickerMatch {
+                symbol: coin.symbol.trim().to_uppercase(),
+                name: coin.name,
+                exchange: coin
+                    .market_cap_rank
+                    .map(|rank| format!("#{}", rank))
+                    .unwrap_or_default(),
+                asset_type: "crypto".to_string(),
+                provider: self.name().to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            return Err(Error::no_results_from(self.name()));
+        }
+
+        Ok(matches)
+    }
+}
+
+impl CoinGecko {
+    /// Suggest CoinGecko ids for an unresolved symbol, formatted as `render-token (RNDR)`.
+    ///
+    /// Search failures are logged and yield no suggestions.
+    pub async fn suggest_ids(&self, symbol: &str, limit: usize) -> Vec<String> {
+        match self.search_coins(symbol).await {
+            Ok(coins) => coins
+                .into_iter()
+                .take(limit)
+                .map(|coin| format!("{} ({})", coin.id, coin.symbol.to_uppercase()))
+                .collect(),
+            Err(err) => {
+                debug!(symbol = %symbol, error = %err, "CoinGecko suggestion search failed");
+                Vec::new()
             }
         }
+    }
 
-        if results.is_empty() {
-            return Err(Error::NoResults);
+    /// GET a `/coins/markets` URL, caching the raw body under `cache_key`.
+    /// `coins/markets` rows for one chunk of ids.
+    async fn fetch_price_chunk(&self, ids: &[&str], cur: &str) -> Result<Vec<MarketEntry>> {
+        let ids_param = ids.join(",");
+        let url = format!(
+            "{}/coins/markets?vs_currency={}&ids={}&price_change_percentage=24h,7d,30d",
+            self.base_url, cur, ids_param
+        );
+        let cache_key = format!("coins_markets:{}:{}:{}", self.base_url, ids_param, cur);
+
+        debug!(url = %url, "fetching prices from CoinGecko");
+
+        self.fetch_markets(&url, &cache_key).await
+    }
+
+    /// `simple/price` quotes for one chunk of ids, as `(id, quote per currency)` pairs.
+    async fn fetch_simple_price_chunk(
+        &self,
+        ids: &[String],
+        vs_param: &str,
+    ) -> Result<Vec<(String, HashMap<String, f64>)>> {
+        let ids_param = ids.join(",");
+        let url = format!(
+            "{}/simple/price?ids={}&vs_currencies={}",
+            self.base_url, ids_param, vs_param
+        );
+        let cache_key = format!("simple_price:{}:{}:{}", self.base_url, ids_param, vs_param);
+
+        debug!(url = %url, "fetching extra currencies from CoinGecko");
+
+        let quotes: HashMap<String, HashMap<String, f64>> = self
+            .fetch_cached(&url, &cache_key, PRICE_CACHE_TTL_SECS)
+            .await?;
+        Ok(quotes.into_iter().collect())
+    }
+
+    async fn fetch_markets(&self, url: &str, cache_key: &str) -> Result<Vec<MarketEntry>> {
+        self.fetch_cached(url, cache_key, PRICE_CACHE_TTL_SECS)
+            .await
+    }
+
+    /// Fail with a suggestion when CoinGecko does not list `currency` as a vs_currency.
+    ///
+    /// The list is fetched at most once per provider and cached for a day; when it
+    /// cannot be fetched the request goes ahead unchecked.
+    async fn ensure_currency(&self, currency: &str) -> Result<()> {
+        let cur = currency.trim().to_lowercase();
+        if COMMON_VS_CURRENCIES.contains(&cur.as_str()) {
+            return Ok(());
+        }
+        if let Some(supported) = self.supported_currencies.read().await.as_ref() {
+            return super::check_currency(self.id(), &cur, supported);
         }
 
-        Ok(results)
+        let mut guard = self.supported_currencies.write().await;
+        if guard.is_none() {
+            let url = format!("{}/simple/supported_vs_currencies", self.base_url);
+            let cache_key = format!("supported_vs_currencies:{}", self.base_url);
+            match self
+                .fetch_cached::<Vec<String>>(&url, &cache_key, SUPPORTED_CURRENCIES_CACHE_TTL_SECS)
+                .await
+            {
+                Ok(supported) => *guard = Some(supported),
+                Err(err) => {
+                    debug!(error = %err, "CoinGecko supported currencies unavailable; skipping check");
+                }
+            }
+        }
+        match guard.as_ref() {
+            Some(supported) => super::check_currency(self.id(), &cur, supported),
+            None => Ok(()),
+        }
     }
 
-    async fn get_price_history(
+    async fn fetch_cached<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        cache_key: &str,
+        ttl_secs: i64,
+    ) -> Result<T> {
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coingecko",
+            cache_key,
+            ttl_secs,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(cache_key = %cache_key, "using cached CoinGecko response");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self.client.send(self.name(), self.get(url)?).await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinGecko response");
+                trace!(body = %body, "CoinGecko response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinGecko returned {}: {}", status, body),
+                    ));
+                }
+
+                cache::write_json(self.client.cache_dir(), "coingecko", cache_key, &body).await;
+                body
+            }
+        };
+
+        serde_json::from_str(&body).map_err(|e| Error::Parse(format!("CoinGecko JSON: {}", e)))
+    }
+
+    async fn search_coins(&self, query: &str) -> Result<Vec<SearchCoin>> {
+        let trimmed = query.trim();
+        if trimmed.is_empty() {
+            return Err(Error::Config("ticker search query cannot be empty".into()));
+        }
+
+        let url = format!("{}/search", self.base_url);
+        let query_lower = trimmed.to_lowercase();
+        let cache_key = format!("search:{}:{}", self.base_url, query_lower);
+
+        debug!(url = %url, query = %trimmed, "searching coins on CoinGecko");
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coingecko",
+            &cache_key,
+            SEARCH_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(query = %trimmed, "using cached CoinGecko search response");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self.get(&url)?.query(&[("query", trimmed)]);
+                let resp = self.client.send(self.name(), request).await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinGecko search response");
+                trace!(body = %body, query = %trimmed, "CoinGecko search response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinGecko returned {} for search: {}", status, body),
+                    ));
+                }
+
+                cache::write_json(self.client.cache_dir(), "coingecko", &cache_key, &body).await;
+                body
+            }
+        };
+
+        let payload: SearchResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CoinGecko search JSON: {}", e)))?;
+
+        Ok(payload.coins)
+    }
+
+    async fn fetch_histories(
         &self,
         symbols: &[String],
         currency: &str,
-        days: u32,
+        span: ChartSpan,
         interval: HistoryInterval,
     ) -> Result<Vec<PriceHistory>> {
+        self.ensure_currency(currency).await?;
         let cur = currency.to_lowercase();
-        let futures = symbols
-            .iter()
-            .map(|symbol| self.fetch_history_for_symbol(symbol, &cur, days, interval));
+        let fetched = fetch_all(symbols, self.client.max_concurrency(), |symbol| {
+            self.fetch_history_for_symbol(symbol, &cur, span, interval)
+        })
+        .await;
 
         let mut histories = Vec::new();
-        for result in join_all(futures).await {
+        for result in fetched {
             histories.push(result?);
         }
 
         if histories.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(self.name(), symbols, currency));
         }
 
         Ok(histories)
     }
-}
 
-impl CoinGecko {
     async fn fetch_history_for_symbol(
         &self,
         symbol: &str,
         currency: &str,
-        days: u32,
+        span: ChartSpan,
         interval: HistoryInterval,
     ) -> Result<PriceHistory> {
         let (cg_id, display_name) = Self::resolve(symbol);
-        let interval_param = match interval {
-            HistoryInterval::Auto => String::new(),
-            HistoryInterval::Hourly => "&interval=hourly".to_string(),
-            HistoryInterval::Daily => "&interval=daily".to_string(),
+        let range_days = match span {
+            ChartSpan::Days(days) => days,
+            ChartSpan::Range { from, to } => ((to - from) / 86_400).max(1) as u32,
+        };
+        let resolved = interval.resolve(range_days);
+        // Without an interval CoinGecko returns hourly points for windows up to 90 days.
+        let interval_param = match (interval.fetch_interval(), resolved) {
+            (HistoryInterval::Hourly, _) => "&interval=hourly",
+            (_, ResolvedInterval::Hourly) => "",
+            (_, ResolvedInterval::Daily) => "&interval=daily",
+        };
+        let (url, cache_key, cache_ttl) = match span {
+            ChartSpan::Days(days) => (
+                format!(
+                    "{}/coins/{}/market_chart?vs_currency={}&days={}{}",
+                    self.base_url, cg_id, currency, days, interval_param
+                ),
+                format!(
+                    "market_chart:{}:{}:{}:{}:{}",
+                    self.base_url,
+                    cg_id,
+                    currency,
+                    days,
+                    interval.as_str()
+                ),
+                history_cache_ttl(resolved),
+            ),
+            // The range endpoint picks its own granularity from the window length.
+            ChartSpan::Range { from, to } => (
+                format!(
+                    "{}/coins/{}/market_chart/range?vs_currency={}&from={}&to={}",
+                    self.base_url, cg_id, currency, from, to
+                ),
+                format!(
+                    "market_chart_range:{}:{}:{}:{}:{}",
+                    self.base_url, cg_id, currency, from, to
+                ),
+                PAST_HISTORY_CACHE_TTL_SECS,
+            ),
         };
-        let url = format!(
-            "{}/coins/{}/market_chart?vs_currency={}&days={}{}",
-            self.base_url, cg_id, currency, days, interval_param
-        );
-        let cache_key = format!(
-            "market_chart:{}:{}:{}:{}:{}",
-            self.base_url,
-            cg_id,
-            currency,
-            days,
-            interval.as_str()
-        );
-        let cache_ttl = history_cache_ttl(interval, days);
 
         debug!(
             url = %url,
             symbol = %symbol,
-            days,
+            span = ?span,
             interval = interval.as_str(),
             "fetching chart data from CoinGecko"
         );
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("coingecko", &cache_key, cache_ttl).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coingecko",
+            &cache_key,
+            cache_ttl,
+        )
+        .await
         {
-            debug!(symbol = %symbol, currency = %currency, "using cached CoinGecko chart data");
-            cached_body
-        } else {
-            let resp = self.client.get(&url).send().await?;
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(
-                status = %status,
-                body_len = body.len(),
-                symbol = %symbol,
-                "CoinGecko chart response"
-            );
-            trace!(body = %body, symbol = %symbol, "CoinGecko chart response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "CoinGecko returned {} for chart data: {}",
-                    status, body
-                )));
+            Ok(cached_body) => {
+                debug!(symbol = %symbol, currency = %currency, "using cached CoinGecko chart data");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self.client.send(self.name(), self.get(&url)?).await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(
+                    status = %status,
+                    body_len = body.len(),
+                    symbol = %symbol,
+                    "CoinGecko chart response"
+                );
+                trace!(body = %body, symbol = %symbol, "CoinGecko chart response body");
 
-            cache::write_json("coingecko", &cache_key, &body).await;
-            body
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinGecko returned {} for chart data: {}", status, body),
+                    ));
+                }
+
+                cache::write_json(self.client.cache_dir(), "coingecko", &cache_key, &body).await;
+                body
+            }
         };
 
         let payload: MarketChartResponse = serde_json::from_str(&body)
@@ -273,12 +1111,20 @@ impl CoinGecko {
             }
 
             if let Some(timestamp) = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ts_ms) {
-                points.push(PricePoint { timestamp, price });
+                points.push(PricePoint {
+                    timestamp,
+                    price,
+                    filled: false,
+                });
             }
         }
 
         if points.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(
+                self.name(),
+                &[symbol.to_string()],
+                currency,
+            ));
         }
 
         Ok(PriceHistory {
@@ -287,6 +1133,8 @@ impl CoinGecko {
             currency: currency.to_uppercase(),
             provider: self.name().to_string(),
             points,
+            events: Vec::new(),
+            granularity: None,
         })
     }
 }
@@ -302,16 +1150,18 @@ fn capitalize(s: &str) -> String {
     }
 }
 
-fn history_cache_ttl(interval: HistoryInterval, days: u32) -> i64 {
-    match interval {
-        HistoryInterval::Daily => DAILY_HISTORY_CACHE_TTL_SECS,
-        HistoryInterval::Hourly => HOURLY_HISTORY_CACHE_TTL_SECS,
-        HistoryInterval::Auto => {
-            if days > 30 {
-                DAILY_HISTORY_CACHE_TTL_SECS
-            } else {
-                HOURLY_HISTORY_CACHE_TTL_SECS
-            }
-        }
+/// The stretch of history one `market_chart` request covers.
+#[derive(Debug, Clone, Copy)]
+enum ChartSpan {
+    /// The last `days` days up to now.
+    Days(u32),
+    /// Unix seconds `from..=to`.
+    Range { from: i64, to: i64 },
+}
+
+fn history_cache_ttl(resolved: ResolvedInterval) -> i64 {
+    match resolved {
+        ResolvedInterval::Hourly => HOURLY_HISTORY_CACHE_TTL_SECS,
+        ResolvedInterval::Daily => DAILY_HISTORY_CACHE_TTL_SECS,
     }
 }
@@ -1,74 +1,136 @@
 use async_trait::async_trait;
-use futures::future::join_all;
-use reqwest::Client;
 use serde::Deserialize;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 use super::cache;
-use super::{CoinPrice, HistoryInterval, PriceHistory, PricePoint, PriceProvider};
+use super::http::HttpClient;
+use super::{
+    Capabilities, CoinPrice, GlobalMetrics, HistoryInterval, MAX_TOP_COINS, PriceHistory,
+    PricePoint, PriceProvider, TickerMatch, fetch_all, fetch_chunks,
+};
 use crate::error::{Error, Result};
+use crate::state;
 
-const BASE_URL: &str = "https://pro-api.coinmarketcap.com/v1";
+pub const BASE_URL: &str = "https://pro-api.coinmarketcap.com/v1";
 const WEB_CHART_BASE_URL: &str = "https://api.coinmarketcap.com/data-api/v3.3";
 const COIN_SUMMARIES_URL: &str = "https://s3.coinmarketcap.com/whitepaper/summaries/coins.json";
 const CATALOG_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
 const DAILY_CHART_CACHE_TTL_SECS: i64 = 12 * 60 * 60;
 const PRICE_CACHE_TTL_SECS: i64 = 30;
+/// Symbols per `quotes/latest` request; longer lists are split into several requests.
+pub const MAX_SYMBOLS_PER_REQUEST: usize = 100;
+/// Credit CoinMarketCap's API terms ask for when its data is displayed.
+pub const ATTRIBUTION: &str = "Data by CoinMarketCap";
 const HOURLY_CHART_CACHE_TTL_SECS: i64 = 60 * 60;
+/// Queries up to this length made of letters and digits are sent as a `symbol` filter first.
+const TICKER_QUERY_MAX_LEN: usize = 5;
 
 /// CoinMarketCap price provider -- requires an API key.
 pub struct CoinMarketCap {
-    client: Client,
+    client: HttpClient,
     api_key: Option<String>,
     base_url: String,
     chart_base_url: String,
     coin_summaries_url: String,
     coin_catalog: RwLock<Option<HashMap<String, (u64, String)>>>,
+    credit_log: Option<std::path::PathBuf>,
 }
 
 impl CoinMarketCap {
     /// Create a CoinMarketCap provider using the default production API URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built; [`try_new`](Self::try_new) returns
+    /// the error instead.
     pub fn new(api_key: String) -> Self {
-        Self::with_optional_key(
+        Self::with_client(HttpClient::default(), Some(api_key), BASE_URL)
+    }
+
+    /// Create a CoinMarketCap provider using the default production API URL, failing
+    /// instead of panicking when the HTTP client cannot be built.
+    pub fn try_new(api_key: String) -> Result<Self> {
+        Ok(Self::with_client(
+            HttpClient::try_default()?,
             Some(api_key),
             BASE_URL,
-            WEB_CHART_BASE_URL,
-            COIN_SUMMARIES_URL,
-        )
+        ))
     }
 
     /// Create a CoinMarketCap provider without an API key.
     pub fn without_key() -> Self {
-        Self::with_optional_key(None, BASE_URL, WEB_CHART_BASE_URL, COIN_SUMMARIES_URL)
+        Self::with_client(HttpClient::default(), None, BASE_URL)
     }
 
     /// Create a CoinMarketCap provider with a custom base URL.
     pub fn with_base_url(api_key: String, base_url: impl Into<String>) -> Self {
-        let base_url = base_url.into();
-        let chart_base_url = derive_chart_base_url(&base_url);
-        let coin_summaries_url = derive_coin_summaries_url(&chart_base_url);
-        Self::with_optional_key(Some(api_key), base_url, chart_base_url, coin_summaries_url)
+        Self::with_client(HttpClient::default(), Some(api_key), base_url)
     }
 
-    fn with_optional_key(
+    /// Create a CoinMarketCap provider that sends requests through `client`.
+    ///
+    /// Chart and coin catalog URLs are derived from `base_url` unless it is the
+    /// production API URL.
+    pub fn with_client(
+        client: HttpClient,
         api_key: Option<String>,
         base_url: impl Into<String>,
-        chart_base_url: impl Into<String>,
-        coin_summaries_url: impl Into<String>,
     ) -> Self {
-        let client = Client::builder()
-            .user_agent("pricr/0.1.0")
-            .build()
-            .expect("failed to build HTTP client");
+        let base_url = base_url.into();
+        let (chart_base_url, coin_summaries_url) = if base_url == BASE_URL {
+            (
+                WEB_CHART_BASE_URL.to_string(),
+                COIN_SUMMARIES_URL.to_string(),
+            )
+        } else {
+            let chart_base_url = derive_chart_base_url(&base_url);
+            let coin_summaries_url = derive_coin_summaries_url(&chart_base_url);
+            (chart_base_url, coin_summaries_url)
+        };
+
         Self {
             client,
             api_key,
-            base_url: base_url.into(),
-            chart_base_url: chart_base_url.into(),
-            coin_summaries_url: coin_summaries_url.into(),
+            base_url,
+            chart_base_url,
+            coin_summaries_url,
             coin_catalog: RwLock::new(None),
+            credit_log: None,
+        }
+    }
+
+    /// Add the credits each API response reports to the monthly ledger at `path`.
+    pub fn with_credit_log(mut self, path: std::path::PathBuf) -> Self {
+        self.credit_log = Some(path);
+        self
+    }
+
+    /// Record the `status.credit_count` of a fresh API response; cached responses cost
+    /// nothing and never reach here.
+    async fn record_credits(&self, body: &str) {
+        #[derive(Deserialize)]
+        struct CreditResponse {
+            status: Option<CmcStatus>,
+        }
+
+        let Some(credits) = serde_json::from_str::<CreditResponse>(body)
+            .ok()
+            .and_then(|raw| raw.status?.credit_count)
+            .filter(|&credits| credits > 0)
+        else {
+            return;
+        };
+        let Some(path) = self.credit_log.clone() else {
+            debug!(credits, "CoinMarketCap credits used");
+            return;
+        };
+        let now = chrono::Utc::now();
+        match tokio::task::spawn_blocking(move || state::add_credits(&path, credits, now)).await {
+            Ok(Ok(month_total)) => debug!(credits, month_total, "CoinMarketCap credits used"),
+            Ok(Err(err)) => warn!(error = %err, "failed to record CoinMarketCap credits"),
+            Err(err) => warn!(error = %err, "failed to record CoinMarketCap credits"),
         }
     }
 
@@ -106,14 +168,60 @@ impl CoinMarketCap {
 struct CmcCoin {
     name: String,
     symbol: String,
+    cmc_rank: Option<u32>,
+    circulating_supply: Option<f64>,
     quote: HashMap<String, CmcQuote>,
 }
 
+impl CmcCoin {
+    /// The coin quoted in `convert`, if the response carried that quote.
+    fn to_price(&self, convert: &str, provider: &str) -> Option<CoinPrice> {
+        let quote = self.quote.get(convert)?;
+        Some(CoinPrice {
+            symbol: self.symbol.clone(),
+            name: self.name.clone(),
+            price: quote.price.unwrap_or(0.0),
+            change_24h: quote.percent_change_24h,
+            change_7d: quote.percent_change_7d,
+            change_30d: quote.percent_change_30d,
+            market_cap: quote.market_cap,
+            volume_24h: quote.volume_24h,
+            circulating_supply: self.circulating_supply,
+            rank: self.cmc_rank,
+            currency: convert.to_string(),
+            provider: provider.to_string(),
+            timestamp: quote.last_updated.unwrap_or_else(chrono::Utc::now),
+            quote_time_estimated: quote.last_updated.is_none(),
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct CmcQuote {
     price: Option<f64>,
     percent_change_24h: Option<f64>,
+    percent_change_7d: Option<f64>,
+    percent_change_30d: Option<f64>,
     market_cap: Option<f64>,
+    volume_24h: Option<f64>,
+    #[serde(default)]
+    last_updated: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -122,9 +230,41 @@ struct CmcRawResponse {
     status: Option<CmcStatus>,
 }
 
+/// `/cryptocurrency/listings/latest` response.
+#[derive(Debug, Deserialize)]
+struct CmcListingsResponse {
+    #[serde(default)]
+    data: Vec<CmcCoin>,
+    status: Option<CmcStatus>,
+}
+
+/// `/global-metrics/quotes/latest` response.
+#[derive(Debug, Deserialize)]
+struct CmcGlobalResponse {
+    data: Option<CmcGlobalData>,
+    status: Option<CmcStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CmcGlobalData {
+    active_cryptocurrencies: Option<u64>,
+    btc_dominance: Option<f64>,
+    eth_dominance: Option<f64>,
+    #[serde(default)]
+    quote: HashMap<String, CmcGlobalQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CmcGlobalQuote {
+    total_market_cap: Option<f64>,
+    total_volume_24h: Option<f64>,
+}
+
 #[derive(Debug, Deserialize)]
 struct CmcStatus {
     error_message: Option<String>,
+    /// API credits the call used.
+    credit_count: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -158,6 +298,23 @@ struct CmcCoinSummary {
     id: u64,
 }
 
+#[derive(Debug, Deserialize)]
+struct CmcMapResponse {
+    #[serde(default)]
+    data: Vec<CmcMapEntry>,
+    status: Option<CmcStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CmcMapEntry {
+    name: String,
+    symbol: String,
+    rank: Option<u32>,
+    /// Set instead of `symbol` for precious metals in the fiat map.
+    #[serde(default)]
+    code: Option<String>,
+}
+
 struct WebChartRequest<'a> {
     symbol_upper: &'a str,
     display_name: &'a str,
@@ -179,12 +336,400 @@ impl PriceProvider for CoinMarketCap {
         "cmc"
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            history: true,
+            search: true,
+            top: true,
+            global: true,
+            ..Capabilities::PRICES_ONLY
+        }
+    }
+
+    fn attribution(&self) -> Option<&str> {
+        Some(ATTRIBUTION)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
         let api_key = self.required_api_key()?;
-        let symbols_upper: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
-        let symbols_joined = symbols_upper.join(",");
+        let symbols_upper: Vec<String> = super::normalize_symbols(symbols)
+            .iter()
+            .map(|s| s.to_uppercase())
+            .collect();
         let convert = currency.to_uppercase();
+        self.ensure_convert(api_key, &convert).await?;
+
+        let results = fetch_chunks(
+            self.name(),
+            &symbols_upper,
+            MAX_SYMBOLS_PER_REQUEST,
+            self.client.max_concurrency(),
+            |chunk| self.fetch_quote_chunk(api_key, chunk, &convert),
+        )
+        .await?;
+
+        if results.is_empty() {
+            return Err(Error::no_results(self.name(), &symbols_upper, &convert));
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_top_coins(&self, limit: usize, currency: &str) -> Result<Vec<CoinPrice>> {
+        let api_key = self.required_api_key()?;
+        let limit = limit.clamp(1, MAX_TOP_COINS);
+        let convert = currency.to_uppercase();
+        self.ensure_convert(api_key, &convert).await?;
 
+        let url = format!(
+            "{}/cryptocurrency/listings/latest?start=1&limit={}&convert={}",
+            self.base_url, limit, convert
+        );
+        let cache_key = format!("listings_latest:{}:{}:{}", self.base_url, limit, convert);
+
+        debug!(url = %url, limit, "fetching top coins from CoinMarketCap");
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &cache_key,
+            PRICE_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(limit, currency = %convert, "using cached CoinMarketCap listings");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self
+                    .client
+                    .get(self.name(), &url)?
+                    .header("X-CMC_PRO_API_KEY", api_key);
+                let resp = self.client.send(self.name(), request).await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinMarketCap listings response");
+                trace!(body = %body, "CoinMarketCap listings response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinMarketCap returned {}: {}", status, body),
+                    ));
+                }
+
+                self.record_credits(&body).await;
+                cache::write_json(self.client.cache_dir(), "coinmarketcap", &cache_key, &body)
+                    .await;
+                body
+            }
+        };
+
+        let raw: CmcListingsResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CMC listings JSON: {}", e)))?;
+
+        if let Some(ref st) = raw.status
+            && let Some(ref msg) = st.error_message
+            && !msg.is_empty()
+        {
+            return Err(Error::api(self.name(), format!("CoinMarketCap: {}", msg)));
+        }
+
+        let results: Vec<CoinPrice> = raw
+            .data
+            .iter()
+            .filter_map(|coin| coin.to_price(&convert, self.name()))
+            .collect();
+
+        if results.is_empty() {
+            return Err(Error::no_results(self.name(), &[], &convert));
+        }
+
+        Ok(results)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_global_metrics(&self, currency: &str) -> Result<GlobalMetrics> {
+        let api_key = self.required_api_key()?;
+        let convert = currency.to_uppercase();
+        self.ensure_convert(api_key, &convert).await?;
+
+        let url = format!(
+            "{}/global-metrics/quotes/latest?convert={}",
+            self.base_url, convert
+        );
+        let cache_key = format!("global_metrics:{}:{}", self.base_url, convert);
+
+        debug!(url = %url, "fetching global metrics from CoinMarketCap");
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &cache_key,
+            PRICE_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(currency = %convert, "using cached CoinMarketCap global metrics");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self
+                    .client
+                    .get(self.name(), &url)?
+                    .header("X-CMC_PRO_API_KEY", api_key);
+                let resp = self.client.send(self.name(), request).await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinMarketCap global response");
+                trace!(body = %body, "CoinMarketCap global response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinMarketCap returned {}: {}", status, body),
+                    ));
+                }
+
+                self.record_credits(&body).await;
+                cache::write_json(self.client.cache_dir(), "coinmarketcap", &cache_key, &body)
+                    .await;
+                body
+            }
+        };
+
+        let raw: CmcGlobalResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CMC global JSON: {}", e)))?;
+
+        if let Some(ref st) = raw.status
+            && let Some(ref msg) = st.error_message
+            && !msg.is_empty()
+        {
+            return Err(Error::api(self.name(), format!("CoinMarketCap: {}", msg)));
+        }
+
+        let data = raw
+            .data
+            .ok_or_else(|| Error::no_results(self.name(), &[], &convert))?;
+        let quote = data.quote.get(&convert);
+        Ok(GlobalMetrics {
+            total_market_cap: quote.and_then(|q| q.total_market_cap),
+            total_volume_24h: quote.and_then(|q| q.total_volume_24h),
+            btc_dominance: data.btc_dominance,
+            eth_dominance: data.eth_dominance,
+            active_coins: data.active_cryptocurrencies,
+            currency: convert,
+            provider: self.name().to_string(),
+            timestamp: chrono::Utc::now(),
+        })
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_price_history(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        days: u32,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        let convert = currency.to_uppercase();
+        let interval_param = interval.resolve(days).as_str();
+
+        let fetched = fetch_all(symbols, self.client.max_concurrency(), |symbol| {
+            self.fetch_history_for_symbol(symbol, &convert, days, interval_param)
+        })
+        .await;
+
+        let mut histories = Vec::new();
+        for result in fetched {
+            histories.push(result?);
+        }
+
+        if histories.is_empty() {
+            return Err(Error::no_results(self.name(), symbols, &convert));
+        }
+
+        Ok(histories)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
+        let trimmed = query.trim();
+        if trimmed.is_empty() {
+            return Err(Error::Config("ticker search query cannot be empty".into()));
+        }
+        let api_key = self.api_key.as_deref().ok_or_else(|| {
+            Error::Config(
+                "CoinMarketCap API key required for search -- pass --api-key or set COINMARKETCAP_API_KEY"
+                    .into(),
+            )
+        })?;
+
+        let query_upper = trimmed.to_uppercase();
+        let looks_like_ticker = trimmed.len() <= TICKER_QUERY_MAX_LEN
+            && trimmed.chars().all(|c| c.is_ascii_alphanumeric());
+
+        let mut entries = Vec::new();
+        if looks_like_ticker {
+            match self.fetch_map(api_key, Some(&query_upper)).await {
+                Ok(found) => entries = found,
+                // CMC rejects unknown symbols with 400; fall back to the full listing below.
+                Err(Error::Api { message, .. }) => {
+                    debug!(query = %trimmed, error = %message, "CoinMarketCap symbol filter failed");
+                }
+                Err(other) => return Err(other),
+            }
+        }
+        if entries.is_empty() {
+            entries = self.fetch_map(api_key, None).await?;
+        }
+
+        let query_lower = trimmed.to_lowercase();
+        let mut matches: Vec<CmcMapEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                entry.symbol.eq_ignore_ascii_case(trimmed)
+                    || entry.symbol.to_lowercase().contains(&query_lower)
+                    || entry.name.to_lowercase().contains(&query_lower)
+            })
+            .collect();
+        matches.sort_by_key(|entry| entry.rank.unwrap_or(u32::MAX));
+
+        let matches = matches
+            .into_iter()
+            .take(limit)
+            .map(|entry| TickerMatch {
+                symbol: entry.symbol.to_uppercase(),
+                name: entry.name,
+                exchange: entry
+                    .rank
+                    .map(|rank| format!("#{}", rank))
+                    .unwrap_or_default(),
+                asset_type: "crypto".to_string(),
+                provider: self.name().to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            return Err(Error::no_results_from(self.name()));
+        }
+
+        Ok(matches)
+    }
+}
+
+impl CoinMarketCap {
+    /// Fetch active entries from `/cryptocurrency/map`, optionally filtered by symbol.
+    async fn fetch_map(&self, api_key: &str, symbol: Option<&str>) -> Result<Vec<CmcMapEntry>> {
+        let mut query = vec![("listing_status", "active")];
+        if let Some(symbol) = symbol {
+            query.push(("symbol", symbol));
+        }
+        let cache_key = format!("map:{}:{}", self.base_url, symbol.unwrap_or("*"));
+        self.fetch_map_endpoint(api_key, "cryptocurrency/map", &query, &cache_key)
+            .await
+    }
+
+    /// Fetch the fiat currencies and precious metals `convert` accepts from `/fiat/map`.
+    async fn fetch_fiat_map(&self, api_key: &str) -> Result<Vec<CmcMapEntry>> {
+        let cache_key = format!("fiat_map:{}", self.base_url);
+        self.fetch_map_endpoint(
+            api_key,
+            "fiat/map",
+            &[("include_metals", "true")],
+            &cache_key,
+        )
+        .await
+    }
+
+    async fn fetch_map_endpoint(
+        &self,
+        api_key: &str,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        cache_key: &str,
+    ) -> Result<Vec<CmcMapEntry>> {
+        let url = format!("{}/{}", self.base_url, endpoint);
+
+        debug!(url = %url, query = ?query, "fetching CoinMarketCap map");
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            cache_key,
+            CATALOG_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(cache_key = %cache_key, "using cached CoinMarketCap map");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self
+                    .client
+                    .get(self.name(), &url)?
+                    .query(query)
+                    .header("X-CMC_PRO_API_KEY", api_key);
+                let resp = self.client.send(self.name(), request).await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinMarketCap map response");
+                trace!(body = %body, "CoinMarketCap map response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!(
+                            "CoinMarketCap returned {} for {}: {}",
+                            status, endpoint, body
+                        ),
+                    ));
+                }
+
+                self.record_credits(&body).await;
+                cache::write_json(self.client.cache_dir(), "coinmarketcap", cache_key, &body).await;
+                body
+            }
+        };
+
+        let raw: CmcMapResponse = serde_json::from_str(&body)
+            .map_err(|e| Error::Parse(format!("CMC map JSON: {}", e)))?;
+
+        if let Some(ref st) = raw.status
+            && let Some(ref msg) = st.error_message
+            && !msg.is_empty()
+        {
+            return Err(Error::api(self.name(), format!("CoinMarketCap: {}", msg)));
+        }
+
+        Ok(raw.data)
+    }
+
+    /// `quotes/latest` prices for one chunk of upper-case symbols, in chunk order.
+    async fn fetch_quote_chunk(
+        &self,
+        api_key: &str,
+        symbols_upper: &[String],
+        convert: &str,
+    ) -> Result<Vec<CoinPrice>> {
+        let symbols_joined = symbols_upper.join(",");
         let url = format!(
             "{}/cryptocurrency/quotes/latest?symbol={}&convert={}",
             self.base_url, symbols_joined, convert
@@ -196,34 +741,45 @@ impl PriceProvider for CoinMarketCap {
 
         debug!(url = %url, "fetching prices from CoinMarketCap");
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("coinmarketcap", &cache_key, PRICE_CACHE_TTL_SECS).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &cache_key,
+            PRICE_CACHE_TTL_SECS,
+        )
+        .await
         {
-            debug!(symbols = %symbols_joined, currency = %convert, "using cached CoinMarketCap quotes");
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&url)
-                .header("X-CMC_PRO_API_KEY", api_key)
-                .send()
-                .await?;
-
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(status = %status, body_len = body.len(), "CoinMarketCap response");
-            trace!(body = %body, "CoinMarketCap response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "CoinMarketCap returned {}: {}",
-                    status, body
-                )));
+            Ok(cached_body) => {
+                debug!(symbols = %symbols_joined, currency = %convert, "using cached CoinMarketCap quotes");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self
+                    .client
+                    .get(self.name(), &url)?
+                    .header("X-CMC_PRO_API_KEY", api_key);
+                let resp = self.client.send(self.name(), request).await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(status = %status, body_len = body.len(), "CoinMarketCap response");
+                trace!(body = %body, "CoinMarketCap response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinMarketCap returned {}: {}", status, body),
+                    ));
+                }
 
-            cache::write_json("coinmarketcap", &cache_key, &body).await;
-            body
+                self.record_credits(&body).await;
+                cache::write_json(self.client.cache_dir(), "coinmarketcap", &cache_key, &body)
+                    .await;
+                body
+            }
         };
 
         let raw: CmcRawResponse =
@@ -233,11 +789,11 @@ impl PriceProvider for CoinMarketCap {
             && let Some(ref msg) = st.error_message
             && !msg.is_empty()
         {
-            return Err(Error::Api(format!("CoinMarketCap: {}", msg)));
+            return Err(Error::api(self.name(), format!("CoinMarketCap: {}", msg)));
         }
 
         let mut results = Vec::new();
-        for sym in &symbols_upper {
+        for sym in symbols_upper {
             if let Some(val) = raw.data.get(sym.as_str()) {
                 // CMC may return a single coin object or an array for duplicate symbols.
                 let coin: CmcCoin = if val.is_array() {
@@ -252,66 +808,47 @@ impl PriceProvider for CoinMarketCap {
                         .map_err(|e| Error::Parse(format!("CMC coin: {}", e)))?
                 };
 
-                if let Some(quote) = coin.quote.get(&convert) {
-                    results.push(CoinPrice {
-                        symbol: coin.symbol.clone(),
-                        name: coin.name.clone(),
-                        price: quote.price.unwrap_or(0.0),
-                        change_24h: quote.percent_change_24h,
-                        market_cap: quote.market_cap,
-                        currency: convert.clone(),
-                        provider: self.name().to_string(),
-                        timestamp: chrono::Utc::now(),
-                    });
+                if let Some(price) = coin.to_price(convert, self.name()) {
+                    results.push(price);
                 }
             }
         }
 
-        if results.is_empty() {
-            return Err(Error::NoResults);
-        }
-
         Ok(results)
     }
 
-    async fn get_price_history(
-        &self,
-        symbols: &[String],
-        currency: &str,
-        days: u32,
-        interval: HistoryInterval,
-    ) -> Result<Vec<PriceHistory>> {
-        let convert = currency.to_uppercase();
-        let interval_param = match interval {
-            HistoryInterval::Auto => {
-                if days <= 30 {
-                    "hourly"
-                } else {
-                    "daily"
-                }
+    /// Fail with a suggestion when `convert` is neither a CoinMarketCap fiat code nor a
+    /// listed coin.
+    ///
+    /// Known fiat codes pass without a lookup; when the fiat map cannot be fetched the
+    /// request goes ahead unchecked.
+    async fn ensure_convert(&self, api_key: &str, convert: &str) -> Result<()> {
+        if crate::calc::is_known_fiat(convert) {
+            return Ok(());
+        }
+        let fiat: Vec<String> = match self.fetch_fiat_map(api_key).await {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|entry| entry.code.unwrap_or(entry.symbol).to_uppercase())
+                .filter(|code| !code.is_empty())
+                .collect(),
+            Err(err) => {
+                debug!(error = %err, "CoinMarketCap fiat map unavailable; skipping check");
+                return Ok(());
             }
-            HistoryInterval::Hourly => "hourly",
-            HistoryInterval::Daily => "daily",
         };
-
-        let futures = symbols
-            .iter()
-            .map(|symbol| self.fetch_history_for_symbol(symbol, &convert, days, interval_param));
-
-        let mut histories = Vec::new();
-        for result in join_all(futures).await {
-            histories.push(result?);
+        if fiat.iter().any(|code| code == convert) {
+            return Ok(());
         }
-
-        if histories.is_empty() {
-            return Err(Error::NoResults);
+        // `convert` also accepts coins, as in `--currency btc`.
+        if let Ok(coins) = self.fetch_map(api_key, Some(convert)).await
+            && !coins.is_empty()
+        {
+            return Ok(());
         }
-
-        Ok(histories)
+        super::check_currency(self.id(), convert, &fiat)
     }
-}
 
-impl CoinMarketCap {
     async fn fetch_history_for_symbol(
         &self,
         symbol: &str,
@@ -399,22 +936,35 @@ impl CoinMarketCap {
     async fn fetch_coin_catalog(&self) -> Result<HashMap<String, (u64, String)>> {
         let catalog_cache_key = self.coin_catalog_cache_key();
 
-        if let Some(cached_body) =
-            cache::read_json::<String>("coinmarketcap", &catalog_cache_key, CATALOG_CACHE_TTL_SECS)
-                .await
+        match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &catalog_cache_key,
+            CATALOG_CACHE_TTL_SECS,
+        )
+        .await
         {
-            debug!("using cached CoinMarketCap coin catalog");
+            Ok(cached_body) => {
+                debug!("using cached CoinMarketCap coin catalog");
 
-            if let Ok(catalog) = parse_coin_catalog(&cached_body) {
-                return Ok(catalog);
-            }
+                if let Ok(catalog) = parse_coin_catalog(&cached_body) {
+                    return Ok(catalog);
+                }
 
-            debug!("cached CoinMarketCap coin catalog is invalid; refetching");
+                debug!("cached CoinMarketCap coin catalog is invalid; refetching");
+            }
+            Err(miss) => miss.ensure_online()?,
         }
 
-        let resp = self.client.get(&self.coin_summaries_url).send().await?;
+        let resp = self
+            .client
+            .send(
+                self.name(),
+                self.client.get(self.name(), &self.coin_summaries_url)?,
+            )
+            .await?;
         let status = resp.status();
-        let body = resp.text().await?;
+        let body = self.client.text(self.name(), resp).await?;
 
         debug!(
             url = %self.coin_summaries_url,
@@ -424,13 +974,20 @@ impl CoinMarketCap {
         );
 
         if !status.is_success() {
-            return Err(Error::Api(format!(
-                "CoinMarketCap coin catalog returned {}: {}",
-                status, body
-            )));
+            return Err(Error::api_status(
+                self.name(),
+                status,
+                format!("CoinMarketCap coin catalog returned {}: {}", status, body),
+            ));
         }
 
-        cache::write_json("coinmarketcap", &catalog_cache_key, &body).await;
+        cache::write_json(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &catalog_cache_key,
+            &body,
+        )
+        .await;
 
         parse_coin_catalog(&body)
     }
@@ -453,15 +1010,30 @@ impl CoinMarketCap {
         let cache_key = self.chart_cache_key(req.coin_id, req.convert_id, req.interval, req.range);
         let cache_ttl = chart_ttl(req.interval);
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("coinmarketcap", &cache_key, cache_ttl).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &cache_key,
+            cache_ttl,
+        )
+        .await
         {
-            debug!(symbol = %req.symbol_upper, interval = req.interval, "using cached CoinMarketCap web chart response");
-            cached_body
-        } else {
-            let fetched = self.fetch_web_chart_body(&url, req.symbol_upper).await?;
-            cache::write_json("coinmarketcap", &cache_key, &fetched).await;
-            fetched
+            Ok(cached_body) => {
+                debug!(symbol = %req.symbol_upper, interval = req.interval, "using cached CoinMarketCap web chart response");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let fetched = self.fetch_web_chart_body(&url, req.symbol_upper).await?;
+                cache::write_json(
+                    self.client.cache_dir(),
+                    "coinmarketcap",
+                    &cache_key,
+                    &fetched,
+                )
+                .await;
+                fetched
+            }
         };
 
         let raw: CmcWebChartResponse = serde_json::from_str(&body)
@@ -484,7 +1056,11 @@ impl CoinMarketCap {
                 continue;
             };
 
-            points.push(PricePoint { timestamp, price });
+            points.push(PricePoint {
+                timestamp,
+                price,
+                filled: false,
+            });
         }
 
         points.sort_by_key(|p| p.timestamp);
@@ -492,7 +1068,11 @@ impl CoinMarketCap {
         trim_points_to_days(&mut points, req.days);
 
         if points.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(
+                self.name(),
+                &[req.symbol_upper.to_string()],
+                req.convert,
+            ));
         }
 
         Ok(PriceHistory {
@@ -501,20 +1081,21 @@ impl CoinMarketCap {
             currency: req.convert.to_uppercase(),
             provider: "CoinMarketCap".to_string(),
             points,
+            events: Vec::new(),
+            granularity: None,
         })
     }
 
     async fn fetch_web_chart_body(&self, url: &str, symbol_upper: &str) -> Result<String> {
-        let resp = self
+        let request = self
             .client
-            .get(url)
+            .get(self.name(), url)?
             .header("accept", "application/json, text/plain, */*")
-            .header("platform", "web")
-            .send()
-            .await?;
+            .header("platform", "web");
+        let resp = self.client.send(self.name(), request).await?;
 
         let status = resp.status();
-        let body = resp.text().await?;
+        let body = self.client.text(self.name(), resp).await?;
 
         debug!(
             status = %status,
@@ -525,10 +1106,11 @@ impl CoinMarketCap {
         trace!(body = %body, symbol = %symbol_upper, "CoinMarketCap web chart response body");
 
         if !status.is_success() {
-            return Err(Error::Api(format!(
-                "CoinMarketCap web chart returned {}: {}",
-                status, body
-            )));
+            return Err(Error::api_status(
+                self.name(),
+                status,
+                format!("CoinMarketCap web chart returned {}: {}", status, body),
+            ));
         }
 
         Ok(body)
@@ -568,39 +1150,50 @@ impl CoinMarketCap {
             "fetching chart data from CoinMarketCap"
         );
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("coinmarketcap", &cache_key, history_ttl).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "coinmarketcap",
+            &cache_key,
+            history_ttl,
+        )
+        .await
         {
-            debug!(symbol = %symbol_upper, currency = %convert, "using cached CoinMarketCap pro history");
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&url)
-                .header("X-CMC_PRO_API_KEY", api_key)
-                .send()
-                .await?;
-
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(
-                status = %status,
-                body_len = body.len(),
-                symbol = %symbol_upper,
-                "CoinMarketCap chart response"
-            );
-            trace!(body = %body, symbol = %symbol_upper, "CoinMarketCap chart response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "CoinMarketCap returned {} for chart data: {}",
-                    status, body
-                )));
+            Ok(cached_body) => {
+                debug!(symbol = %symbol_upper, currency = %convert, "using cached CoinMarketCap pro history");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self
+                    .client
+                    .get(self.name(), &url)?
+                    .header("X-CMC_PRO_API_KEY", api_key);
+                let resp = self.client.send(self.name(), request).await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(
+                    status = %status,
+                    body_len = body.len(),
+                    symbol = %symbol_upper,
+                    "CoinMarketCap chart response"
+                );
+                trace!(body = %body, symbol = %symbol_upper, "CoinMarketCap chart response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("CoinMarketCap returned {} for chart data: {}", status, body),
+                    ));
+                }
 
-            cache::write_json("coinmarketcap", &cache_key, &body).await;
-            body
+                self.record_credits(&body).await;
+                cache::write_json(self.client.cache_dir(), "coinmarketcap", &cache_key, &body)
+                    .await;
+                body
+            }
         };
 
         let raw: CmcHistoryRawResponse = serde_json::from_str(&body)
@@ -610,7 +1203,7 @@ impl CoinMarketCap {
             && let Some(ref msg) = st.error_message
             && !msg.is_empty()
         {
-            return Err(Error::Api(format!("CoinMarketCap: {}", msg)));
+            return Err(Error::api(self.name(), format!("CoinMarketCap: {}", msg)));
         }
 
         parse_history_data(raw.data, symbol_upper, convert)
@@ -784,13 +1377,21 @@ fn parse_history_data(
             continue;
         }
 
-        points.push(PricePoint { timestamp, price });
+        points.push(PricePoint {
+            timestamp,
+            price,
+            filled: false,
+        });
     }
 
     points.sort_by_key(|p| p.timestamp);
 
     if points.is_empty() {
-        return Err(Error::NoResults);
+        return Err(Error::no_results(
+            "CoinMarketCap",
+            &[symbol_upper.to_string()],
+            convert,
+        ));
     }
 
     Ok(PriceHistory {
@@ -799,6 +1400,8 @@ fn parse_history_data(
         currency: convert.to_uppercase(),
         provider: "CoinMarketCap".to_string(),
         points,
+        events: Vec::new(),
+        granularity: None,
     })
 }
 
@@ -0,0 +1,280 @@
+use async_trait::async_trait;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use super::fiat::{FiatRate, FiatRateSource};
+use super::{
+    Capabilities, CoinPrice, GlobalMetrics, HistoryInterval, MarketTicker, PriceHistory,
+    PriceProvider, TickerMatch, TrendingCoin, frankfurter,
+};
+use crate::calc;
+use crate::error::{Error, Result};
+
+/// Currency every provider can quote in; used when the requested one is rejected.
+const FALLBACK_CURRENCY: &str = "USD";
+
+/// Wraps a provider so rows quoted in another fiat currency are converted to the requested one.
+///
+/// Providers that report the listing currency (Yahoo, Stooq) are converted row by row;
+/// providers that reject the requested currency outright are retried in USD first.
+/// Rows that cannot be converted are returned unchanged.
+pub(crate) struct CurrencyFallback {
+    inner: Box<dyn PriceProvider>,
+    rates: Arc<FiatRateSource>,
+}
+
+impl CurrencyFallback {
+    pub(crate) fn new(inner: Box<dyn PriceProvider>, rates: Arc<FiatRateSource>) -> Self {
+        Self { inner, rates }
+    }
+
+    async fn convert_prices(&self, prices: &mut [CoinPrice], target: &str) {
+        let sources: BTreeSet<String> = prices
+            .iter()
+            .filter(|price| !price.currency.eq_ignore_ascii_case(target))
+            // Exact-case match only: Yahoo reports London listings in pence as `GBp`.
+            .filter(|price| {
+                calc::is_known_fiat(&price.currency)
+                    && price.currency == price.currency.to_uppercase()
+            })
+            .map(|price| price.currency.clone())
+            .collect();
+
+        for source in sources {
+            let rate = match self.rates.get_rates(&source, &[target.to_string()]).await {
+                Ok(rates) => rates.rates.get(target).copied(),
+                Err(err) => {
+                    warn!(provider = self.inner.id(), from = %source, to = %target, error = %err, "currency conversion failed");
+                    None
+                }
+            };
+            let Some(rate) = rate else {
+                continue;
+            };
+            debug!(provider = self.inner.id(), from = %source, to = %target, rate = rate.rate, "converting quote currency");
+            for price in prices.iter_mut().filter(|price| price.currency == source) {
+                apply_rate(price, target, rate);
+            }
+        }
+    }
+
+    /// Fill empty `--currencies` slots by converting each row's own quote.
+    async fn fill_currency_gaps(&self, prices: &mut [CoinPrice]) {
+        let sources: BTreeSet<String> = prices
+            .iter()
+            .filter(|price| price.prices.iter().any(|(_, slot)| slot.is_none()))
+            .filter(|price| {
+                calc::is_known_fiat(&price.currency)
+                    && price.currency == price.currency.to_uppercase()
+            })
+            .map(|price| price.currency.clone())
+            .collect();
+
+        for source in sources {
+            let targets: BTreeSet<String> = prices
+                .iter()
+                .filter(|price| price.currency == source)
+                .flat_map(|price| &price.prices)
+                .filter(|(currency, slot)| slot.is_none() && calc::is_known_fiat(currency))
+                .map(|(currency, _)| currency.clone())
+                .collect();
+            if targets.is_empty() {
+                continue;
+            }
+            let targets: Vec<String> = targets.into_iter().collect();
+            let rates = match self.rates.get_rates(&source, &targets).await {
+                Ok(rates) => rates.rates,
+                Err(err) => {
+                    warn!(provider = self.inner.id(), from = %source, error = %err, "currency conversion failed");
+                    continue;
+                }
+            };
+            for price in prices.iter_mut().filter(|price| price.currency == source) {
+                let quote = price.price;
+                for (currency, slot) in &mut price.prices {
+                    if slot.is_none() {
+                        *slot = rates.get(currency.as_str()).map(|rate| quote * rate.rate);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn apply_rate(price: &mut CoinPrice, target: &str, rate: FiatRate) {
+    price.price *= rate.rate;
+    price.market_cap = price.market_cap.map(|cap| cap * rate.rate);
+    price.volume_24h = price.volume_24h.map(|volume| volume * rate.rate);
+    price.ath = price.ath.map(|ath| ath * rate.rate);
+    price.atl = price.atl.map(|atl| atl * rate.rate);
+    price.bid = price.bid.map(|bid| bid * rate.rate);
+    price.ask = price.ask.map(|ask| ask * rate.rate);
+    if let Some(extended) = price.extended.as_mut() {
+        for value in [
+            &mut extended.pre_market_price,
+            &mut extended.post_market_price,
+            &mut extended.fifty_two_week_high,
+            &mut extended.fifty_two_week_low,
+        ] {
+            *value = value.map(|v| v * rate.rate);
+        }
+    }
+    price.currency = target.to_string();
+    price.converted = true;
+    price.fx_rate = Some(rate.rate);
+    let source = if rate.provider == frankfurter::PROVIDER_NAME {
+        "ECB"
+    } else {
+        rate.provider
+    };
+    price.provider = format!("{}\u{2192}{}", price.provider, source);
+}
+
+#[async_trait]
+impl PriceProvider for CurrencyFallback {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn attribution(&self) -> Option<&str> {
+        self.inner.attribution()
+    }
+
+    async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
+        let target = currency.to_uppercase();
+        if !calc::is_known_fiat(&target) {
+            return self.inner.get_prices(symbols, currency).await;
+        }
+
+        let mut prices = match self.inner.get_prices(symbols, currency).await {
+            Ok(prices) => prices,
+            Err(err @ (Error::Api { .. } | Error::Config(_))) if target != FALLBACK_CURRENCY => {
+                debug!(provider = self.inner.id(), currency = %target, error = %err, "retrying in USD for conversion");
+                match self.inner.get_prices(symbols, FALLBACK_CURRENCY).await {
+                    Ok(prices) => prices,
+                    Err(_) => return Err(err),
+                }
+            }
+            Err(err) => return Err(err),
+        };
+
+        self.convert_prices(&mut prices, &target).await;
+        Ok(prices)
+    }
+
+    /// Currencies the inner provider left empty, such as all but the listing currency
+    /// for Yahoo, are filled by converting each row's own quote.
+    async fn get_prices_in(
+        &self,
+        symbols: &[String],
+        currencies: &[String],
+    ) -> Result<Vec<CoinPrice>> {
+        let mut prices = self.inner.get_prices_in(symbols, currencies).await?;
+        self.fill_currency_gaps(&mut prices).await;
+        if let Some(first) = currencies.first() {
+            let target = first.to_uppercase();
+            if calc::is_known_fiat(&target) {
+                self.convert_prices(&mut prices, &target).await;
+            }
+        }
+        Ok(prices)
+    }
+
+    async fn get_price_history(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        days: u32,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        self.inner
+            .get_price_history(symbols, currency, days, interval)
+            .await
+    }
+
+    async fn get_price_history_window(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        self.inner
+            .get_price_history_window(symbols, currency, start, end, interval)
+            .await
+    }
+
+    async fn get_fiat_history(
+        &self,
+        base: &str,
+        targets: &[String],
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+    ) -> Result<Vec<PriceHistory>> {
+        self.inner.get_fiat_history(base, targets, start, end).await
+    }
+
+    async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
+        self.inner.search_tickers(query, limit).await
+    }
+
+    async fn get_top_coins(&self, limit: usize, currency: &str) -> Result<Vec<CoinPrice>> {
+        self.inner.get_top_coins(limit, currency).await
+    }
+
+    async fn get_global_metrics(&self, currency: &str) -> Result<GlobalMetrics> {
+        self.inner.get_global_metrics(currency).await
+    }
+
+    async fn get_trending(&self, currency: &str) -> Result<Vec<TrendingCoin>> {
+        self.inner.get_trending(currency).await
+    }
+
+    async fn get_markets(&self, symbol: &str, limit: usize) -> Result<Vec<MarketTicker>> {
+        self.inner.get_markets(symbol, limit).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_rate_scales_money_fields_and_marks_row() {
+        let mut price = CoinPrice {
+            symbol: "AAPL".into(),
+            price: 200.0,
+            change_24h: Some(1.5),
+            market_cap: Some(1000.0),
+            currency: "USD".into(),
+            provider: "Yahoo Finance".into(),
+            ..Default::default()
+        };
+        apply_rate(
+            &mut price,
+            "EUR",
+            FiatRate {
+                rate: 0.5,
+                provider: frankfurter::PROVIDER_NAME,
+            },
+        );
+
+        assert_eq!(price.price, 100.0);
+        assert_eq!(price.market_cap, Some(500.0));
+        assert_eq!(price.change_24h, Some(1.5));
+        assert_eq!(price.currency, "EUR");
+        assert!(price.converted);
+        assert_eq!(price.fx_rate, Some(0.5));
+        assert_eq!(price.provider, "Yahoo Finance\u{2192}ECB");
+    }
+}
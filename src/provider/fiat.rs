@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use tracing::{debug, warn};
+
+use super::frankfurter::{self, Frankfurter};
+use super::http::HttpClient;
+use super::open_er_api::{self, OpenErApi};
+use crate::config::ProviderSettings;
+use crate::error::Result;
+
+/// One fiat exchange rate and the source that published it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiatRate {
+    /// Expressed as "1 source = rate target".
+    pub rate: f64,
+    pub provider: &'static str,
+}
+
+/// Rates found for a set of fiat targets plus the targets no source could price.
+#[derive(Debug, Clone, Default)]
+pub struct FiatRates {
+    pub rates: HashMap<String, FiatRate>,
+    /// Uppercased target codes without a rate, in request order.
+    pub skipped: Vec<String>,
+}
+
+/// Fiat rate lookup used by conversion mode.
+///
+/// Pairs the ECB publishes go to Frankfurter; every other known fiat code is priced
+/// through ExchangeRate-API. A failing fallback only skips its targets.
+pub struct FiatRateSource {
+    ecb: Frankfurter,
+    fallback: OpenErApi,
+}
+
+impl FiatRateSource {
+    pub fn new(ecb: Frankfurter, fallback: OpenErApi) -> Self {
+        Self { ecb, fallback }
+    }
+
+    /// Build both sources on `client`, honoring `[provider.<id>]` base URL and rate limit
+    /// overrides.
+    pub fn from_settings(settings: &ProviderSettings, client: &HttpClient) -> Self {
+        Self::new(
+            Frankfurter::with_client(
+                super::rate_limited_client(settings, "frankfurter", None, client),
+                settings.base_url("frankfurter", frankfurter::BASE_URL),
+            ),
+            OpenErApi::with_client(
+                super::rate_limited_client(settings, "exchangerate-api", None, client),
+                settings.base_url("exchangerate-api", open_er_api::BASE_URL),
+            ),
+        )
+    }
+
+    /// Fetch "1 `from` = rate target" for every code in `to`.
+    pub async fn get_rates(&self, from: &str, to: &[String]) -> Result<FiatRates> {
+        let from = from.to_uppercase();
+        let mut targets: Vec<String> = Vec::new();
+        for code in to.iter().map(|code| code.to_uppercase()) {
+            if !targets.contains(&code) {
+                targets.push(code);
+            }
+        }
+
+        let from_on_ecb = frankfurter::is_ecb_currency(&from);
+        let (ecb_targets, other_targets): (Vec<String>, Vec<String>) = targets
+            .iter()
+            .cloned()
+            .partition(|code| from_on_ecb && frankfurter::is_ecb_currency(code));
+        if !other_targets.is_empty() {
+            debug!(from = %from, targets = ?other_targets, "pair not published by the ECB, using ExchangeRate-API");
+        }
+
+        let ecb_fut = async {
+            if ecb_targets.is_empty() {
+                Ok(HashMap::new())
+            } else {
+                self.ecb.get_rates(&from, &ecb_targets).await
+            }
+        };
+        let fallback_fut = async {
+            if other_targets.is_empty() {
+                Ok(HashMap::new())
+            } else {
+                self.fallback.get_rates(&from, &other_targets).await
+            }
+        };
+        let (ecb_result, fallback_result) = tokio::join!(ecb_fut, fallback_fut);
+
+        let mut rates: HashMap<String, FiatRate> = HashMap::new();
+        for (code, rate) in ecb_result? {
+            rates.insert(
+                code,
+                FiatRate {
+                    rate,
+                    provider: frankfurter::PROVIDER_NAME,
+                },
+            );
+        }
+        match fallback_result {
+            Ok(fallback_rates) => {
+                for (code, rate) in fallback_rates {
+                    rates.insert(
+                        code,
+                        FiatRate {
+                            rate,
+                            provider: open_er_api::PROVIDER_NAME,
+                        },
+                    );
+                }
+            }
+            Err(err) => {
+                warn!(from = %from, targets = ?other_targets, error = %err, "fallback fiat rates failed");
+            }
+        }
+
+        let skipped = targets
+            .into_iter()
+            .filter(|code| !rates.contains_key(code))
+            .collect();
+
+        Ok(FiatRates { rates, skipped })
+    }
+}
@@ -0,0 +1,233 @@
+//! Record/replay of provider HTTP responses (`--record DIR`, `--replay DIR`).
+//!
+//! Each response is stored as `<key>.json` in the fixture directory, where the key is a
+//! hash of the request method and full URL. Replay serves those files without touching
+//! the network.
+
+use std::path::{Path, PathBuf};
+
+use reqwest::{Method, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::{Error, Result};
+
+/// Whether fixtures are being written or served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    Record,
+    Replay,
+}
+
+/// A fixture directory and what to do with it.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    mode: FixtureMode,
+    dir: PathBuf,
+}
+
+/// One recorded response.
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    body: String,
+}
+
+impl Fixtures {
+    /// Record every response into `dir`, creating it if needed.
+    pub fn record(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            Error::Config(format!(
+                "cannot create fixture directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self {
+            mode: FixtureMode::Record,
+            dir,
+        })
+    }
+
+    /// Serve responses recorded in `dir`, which must exist.
+    pub fn replay(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(Error::Config(format!(
+                "fixture directory {} does not exist",
+                dir.display()
+            )));
+        }
+        Ok(Self {
+            mode: FixtureMode::Replay,
+            dir,
+        })
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// File a response to `method url` is stored in.
+    pub fn path(&self, method: &Method, url: &Url) -> PathBuf {
+        self.dir.join(format!("{}.json", fixture_key(method, url)))
+    }
+
+    /// The recorded response for `method url`, or an error naming the missing fixture.
+    pub(crate) fn load(&self, provider: &str, method: &Method, url: &Url) -> Result<Response> {
+        let path = self.path(method, url);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return Err(Error::api(
+                provider,
+                format!(
+                    "no recorded response for {} {} (expected fixture {})",
+                    method,
+                    url,
+                    path.display()
+                ),
+            ));
+        };
+        let fixture: Fixture = serde_json::from_str(&text)
+            .map_err(|e| Error::Parse(format!("invalid fixture {}: {}", path.display(), e)))?;
+        debug!(provider, %method, %url, path = %path.display(), "replaying recorded response");
+        let status = StatusCode::from_u16(fixture.status).map_err(|e| {
+            Error::Parse(format!(
+                "invalid status in fixture {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(build_response(
+            status,
+            fixture.content_type.as_deref(),
+            fixture.body.into_bytes(),
+        ))
+    }
+
+    /// Write `body` as the response to `method url`.
+    pub(crate) fn save(
+        &self,
+        method: &Method,
+        url: &Url,
+        status: StatusCode,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<()> {
+        let path = self.path(method, url);
+        let fixture = Fixture {
+            method: method.to_string(),
+            url: url.to_string(),
+            status: status.as_u16(),
+            content_type: content_type.map(str::to_string),
+            body: String::from_utf8_lossy(body).into_owned(),
+        };
+        let json = serde_json::to_string_pretty(&fixture)
+            .map_err(|e| Error::Parse(format!("failed to encode fixture: {}", e)))?;
+        std::fs::write(&path, json).map_err(|e| {
+            Error::Config(format!("cannot write fixture {}: {}", path.display(), e))
+        })?;
+        debug!(%method, %url, path = %path.display(), "recorded response");
+        Ok(())
+    }
+}
+
+/// Rebuild a `reqwest` response from recorded parts.
+pub(crate) fn build_response(
+    status: StatusCode,
+    content_type: Option<&str>,
+    body: Vec<u8>,
+) -> Response {
+    let mut builder = http::Response::builder().status(status);
+    if let Some(content_type) = content_type {
+        builder = builder.header(reqwest::header::CONTENT_TYPE, content_type);
+    }
+    let response = builder
+        .body(body)
+        .expect("status and content type were already valid");
+    Response::from(response)
+}
+
+/// Hex FNV-1a hash of `METHOD url`. Unlike `DefaultHasher` its output is stable across
+/// Rust releases, so fixture directories can be shared.
+fn fixture_key(method: &Method, url: &Url) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("{} {}", method, url).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("pricr-fixtures-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn key_depends_on_method_and_full_url() {
+        let url = Url::parse("https://api.example.com/v1/price?ids=bitcoin").unwrap();
+        let other = Url::parse("https://api.example.com/v1/price?ids=ethereum").unwrap();
+        assert_eq!(fixture_key(&Method::GET, &url), "1a7bae5ddeab33db");
+        assert_ne!(
+            fixture_key(&Method::GET, &url),
+            fixture_key(&Method::GET, &other)
+        );
+        assert_ne!(
+            fixture_key(&Method::GET, &url),
+            fixture_key(&Method::POST, &url)
+        );
+    }
+
+    #[tokio::test]
+    async fn saved_response_replays_status_and_body() {
+        let dir = temp_dir("roundtrip");
+        let url = Url::parse("https://api.example.com/quote?s=aapl").unwrap();
+        let recorder = Fixtures::record(&dir).unwrap();
+        recorder
+            .save(
+                &Method::GET,
+                &url,
+                StatusCode::NOT_FOUND,
+                Some("text/plain"),
+                b"no such symbol",
+            )
+            .unwrap();
+
+        let replay = Fixtures::replay(&dir).unwrap();
+        let response = replay.load("Yahoo Finance", &Method::GET, &url).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.text().await.unwrap(), "no such symbol");
+
+        let missing = Url::parse("https://api.example.com/quote?s=msft").unwrap();
+        let err = replay
+            .load("Yahoo Finance", &Method::GET, &missing)
+            .unwrap_err();
+        let expected = replay.path(&Method::GET, &missing);
+        assert!(
+            err.to_string().contains(&expected.display().to_string()),
+            "{err}"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn replay_requires_existing_directory() {
+        assert!(matches!(
+            Fixtures::replay(temp_dir("missing")),
+            Err(Error::Config(_))
+        ));
+    }
+}
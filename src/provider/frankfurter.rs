@@ -1,33 +1,68 @@
 use std::collections::HashMap;
 
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::cache;
-use super::{PriceHistory, PricePoint};
+use super::http::HttpClient;
+use super::{
+    Capabilities, CoinPrice, HistoryInterval, PriceHistory, PricePoint, PriceProvider, TickerMatch,
+    check_currency, note_daily_only,
+};
 use crate::calc;
 use crate::error::{Error, Result};
 
-const BASE_URL: &str = "https://api.frankfurter.dev/v1";
-const LATEST_RATES_CACHE_TTL_SECS: i64 = 10 * 60;
+pub const BASE_URL: &str = "https://api.frankfurter.dev/v1";
+pub const PROVIDER_NAME: &str = "Frankfurter/ECB";
+/// Currencies in the ECB reference rate set; other known fiat codes are not served.
+const ECB_CURRENCIES: &[&str] = &[
+    "AUD", "BGN", "BRL", "CAD", "CHF", "CNY", "CZK", "DKK", "EUR", "GBP", "HKD", "HUF", "IDR",
+    "ILS", "INR", "ISK", "JPY", "KRW", "MXN", "MYR", "NOK", "NZD", "PHP", "PLN", "RON", "SEK",
+    "SGD", "THB", "TRY", "USD", "ZAR",
+];
+/// Longest time latest rates are served from cache, whatever their reference date.
+const LATEST_RATES_MAX_AGE_SECS: i64 = 6 * 60 * 60;
+/// How often to look again when the cached rates are older than the last publication,
+/// e.g. on ECB holidays or when publication runs late.
+const LATEST_RATES_RECHECK_SECS: i64 = 60 * 60;
+/// Earliest UTC hour of the daily ECB publication (~16:00 CET, 14:00 UTC in summer).
+const ECB_PUBLICATION_HOUR_UTC: u32 = 14;
 const HISTORY_CACHE_TTL_SECS: i64 = 12 * 60 * 60;
+const CURRENCIES_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
 
 /// Frankfurter forex provider backed by ECB reference rates.
 pub struct Frankfurter {
-    client: reqwest::Client,
+    client: HttpClient,
     base_url: String,
 }
 
 impl Frankfurter {
     /// Create a Frankfurter provider using the default production API URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built; [`try_new`](Self::try_new) returns
+    /// the error instead.
     pub fn new() -> Self {
         Self::with_base_url(BASE_URL)
     }
 
+    /// Create a Frankfurter provider using the default production API URL, failing
+    /// instead of panicking when the HTTP client cannot be built.
+    pub fn try_new() -> Result<Self> {
+        Ok(Self::with_client(HttpClient::try_default()?, BASE_URL))
+    }
+
     /// Create a Frankfurter provider with a custom base URL.
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self::with_client(HttpClient::default(), base_url)
+    }
+
+    /// Create a Frankfurter provider that sends requests through `client`.
+    pub fn with_client(client: HttpClient, base_url: impl Into<String>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client,
             base_url: base_url.into(),
         }
     }
@@ -36,6 +71,7 @@ impl Frankfurter {
     ///
     /// Returns a map of target currency code to rate where each value is
     /// expressed as "1 source = rate target".
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     pub async fn get_rates(&self, from: &str, to: &[String]) -> Result<HashMap<String, f64>> {
         let from_upper = from.to_uppercase();
         let to_param = to.join(",").to_uppercase();
@@ -47,22 +83,42 @@ impl Frankfurter {
 
         debug!(url = %url, "fetching forex rates from Frankfurter");
 
-        let body: FrankfurterResponse = if let Some(cached) =
-            cache::read_json("frankfurter", &cache_key, LATEST_RATES_CACHE_TTL_SECS).await
+        let body: FrankfurterResponse = match cache::read_json_if(
+            self.client.cache_dir(),
+            "frankfurter",
+            &cache_key,
+            |cached: &FrankfurterResponse, fetched_at| {
+                chrono::Utc::now() < rates_expire_at(cached.date, fetched_at)
+            },
+        )
+        .await
         {
-            debug!(from = %from_upper, to = %to_param, "using cached Frankfurter rates");
-            cached
-        } else {
-            let resp = self.client.get(&url).send().await?.error_for_status()?;
-            let fetched: FrankfurterResponse = resp.json().await?;
-            cache::write_json("frankfurter", &cache_key, &fetched).await;
-            fetched
+            Ok(cached) => {
+                debug!(from = %from_upper, to = %to_param, date = ?cached.date, cached = true, "using cached Frankfurter rates");
+                cached
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self
+                    .client
+                    .send(PROVIDER_NAME, self.client.get(PROVIDER_NAME, &url)?)
+                    .await?
+                    .error_for_status()?;
+                let fetched: FrankfurterResponse = resp
+                    .json()
+                    .await
+                    .map_err(|e| self.client.request_error(PROVIDER_NAME, e))?;
+                debug!(from = %from_upper, to = %to_param, date = ?fetched.date, cached = false, "fetched Frankfurter rates");
+                cache::write_json(self.client.cache_dir(), "frankfurter", &cache_key, &fetched)
+                    .await;
+                fetched
+            }
         };
 
         debug!(rates = ?body.rates, "received forex rates");
 
         if body.rates.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(PROVIDER_NAME, to, &from_upper));
         }
 
         Ok(body.rates)
@@ -77,13 +133,25 @@ impl Frankfurter {
         from: &str,
         to: &[String],
         days: u32,
+    ) -> Result<Vec<PriceHistory>> {
+        let end = chrono::Utc::now().date_naive();
+        self.get_history_range(from, to, end - chrono::Duration::days(days as i64), end)
+            .await
+    }
+
+    /// Fetch historical forex rates published between `start` and `end`, inclusive.
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    pub async fn get_history_range(
+        &self,
+        from: &str,
+        to: &[String],
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
     ) -> Result<Vec<PriceHistory>> {
         let from_upper = from.to_uppercase();
         let to_upper: Vec<String> = to.iter().map(|s| s.to_uppercase()).collect();
         let to_param = to_upper.join(",");
 
-        let end = chrono::Utc::now().date_naive();
-        let start = end - chrono::Duration::days(days as i64);
         let url = format!(
             "{}/{}..{}?from={}&to={}",
             self.base_url,
@@ -93,26 +161,43 @@ impl Frankfurter {
             to_param
         );
         let cache_key = format!(
-            "history:{}:{}:{}:{}",
-            self.base_url, from_upper, to_param, days
+            "history:{}:{}:{}:{}..{}",
+            self.base_url, from_upper, to_param, start, end
         );
 
         debug!(url = %url, "fetching historical forex rates from Frankfurter");
 
-        let body: FrankfurterHistoryResponse = if let Some(cached) =
-            cache::read_json("frankfurter", &cache_key, HISTORY_CACHE_TTL_SECS).await
+        let body: FrankfurterHistoryResponse = match cache::read_json(
+            self.client.cache_dir(),
+            "frankfurter",
+            &cache_key,
+            HISTORY_CACHE_TTL_SECS,
+        )
+        .await
         {
-            debug!(from = %from_upper, to = %to_param, days, "using cached Frankfurter history");
-            cached
-        } else {
-            let resp = self.client.get(&url).send().await?.error_for_status()?;
-            let fetched: FrankfurterHistoryResponse = resp.json().await?;
-            cache::write_json("frankfurter", &cache_key, &fetched).await;
-            fetched
+            Ok(cached) => {
+                debug!(from = %from_upper, to = %to_param, %start, %end, "using cached Frankfurter history");
+                cached
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self
+                    .client
+                    .send(PROVIDER_NAME, self.client.get(PROVIDER_NAME, &url)?)
+                    .await?
+                    .error_for_status()?;
+                let fetched: FrankfurterHistoryResponse = resp
+                    .json()
+                    .await
+                    .map_err(|e| self.client.request_error(PROVIDER_NAME, e))?;
+                cache::write_json(self.client.cache_dir(), "frankfurter", &cache_key, &fetched)
+                    .await;
+                fetched
+            }
         };
 
         if body.rates.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(PROVIDER_NAME, &to_upper, &from_upper));
         }
 
         let mut histories = Vec::new();
@@ -135,6 +220,7 @@ impl Frankfurter {
                 points.push(PricePoint {
                     timestamp: naive_dt.and_utc(),
                     price: rate,
+                    filled: false,
                 });
             }
 
@@ -148,17 +234,78 @@ impl Frankfurter {
                 symbol: target.clone(),
                 name: calc::fiat_name(&target).to_string(),
                 currency: from_upper.clone(),
-                provider: "Frankfurter/ECB".to_string(),
+                provider: PROVIDER_NAME.to_string(),
                 points,
+                events: Vec::new(),
+                granularity: None,
             });
         }
 
         if histories.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(PROVIDER_NAME, to, &from_upper));
         }
 
         Ok(histories)
     }
+
+    /// Uppercased `currency`, checked against the codes Frankfurter publishes.
+    ///
+    /// ECB reference currencies pass without a lookup; anything else is checked against
+    /// `/currencies`, cached for a day, or against the ECB set when that cannot be fetched.
+    async fn quote_currency(&self, currency: &str) -> Result<String> {
+        let quote = currency.trim().to_uppercase();
+        if is_ecb_currency(&quote) {
+            return Ok(quote);
+        }
+        let supported = match self.fetch_currencies().await {
+            Ok(codes) => codes,
+            Err(err) => {
+                debug!(error = %err, "Frankfurter currency list unavailable; using the ECB set");
+                ECB_CURRENCIES.iter().map(|code| code.to_string()).collect()
+            }
+        };
+        check_currency(self.id(), &quote, &supported)?;
+        Ok(quote)
+    }
+
+    async fn fetch_currencies(&self) -> Result<Vec<String>> {
+        let url = format!("{}/currencies", self.base_url);
+        let cache_key = format!("currencies:{}", self.base_url);
+
+        let names: HashMap<String, String> = match cache::read_json(
+            self.client.cache_dir(),
+            "frankfurter",
+            &cache_key,
+            CURRENCIES_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached) => {
+                debug!("using cached Frankfurter currency list");
+                cached
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                debug!(url = %url, "fetching currency list from Frankfurter");
+                let resp = self
+                    .client
+                    .send(PROVIDER_NAME, self.client.get(PROVIDER_NAME, &url)?)
+                    .await?
+                    .error_for_status()?;
+                let fetched: HashMap<String, String> = resp
+                    .json()
+                    .await
+                    .map_err(|e| self.client.request_error(PROVIDER_NAME, e))?;
+                cache::write_json(self.client.cache_dir(), "frankfurter", &cache_key, &fetched)
+                    .await;
+                fetched
+            }
+        };
+
+        let mut codes: Vec<String> = names.into_keys().map(|code| code.to_uppercase()).collect();
+        codes.sort();
+        Ok(codes)
+    }
 }
 
 impl Default for Frankfurter {
@@ -167,9 +314,195 @@ impl Default for Frankfurter {
     }
 }
 
+/// Frankfurter as a regular provider: each fiat symbol is "priced" in the quote currency,
+/// so `eur` in `usd` is how many US dollars one euro buys.
+#[async_trait]
+impl PriceProvider for Frankfurter {
+    fn name(&self) -> &str {
+        PROVIDER_NAME
+    }
+
+    fn id(&self) -> &str {
+        "frankfurter"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            history: true,
+            fiat_history: true,
+            search: true,
+            ..Capabilities::PRICES_ONLY
+        }
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
+        let quote = self.quote_currency(currency).await?;
+        let targets = fiat_targets(symbols, &quote);
+        let rates = if targets.is_empty() {
+            HashMap::new()
+        } else {
+            self.get_rates(&quote, &targets).await?
+        };
+
+        let now = chrono::Utc::now();
+        let prices: Vec<CoinPrice> = symbols
+            .iter()
+            .filter_map(|symbol| {
+                let code = symbol.trim().to_uppercase();
+                let price = if code == quote {
+                    1.0
+                } else {
+                    let rate = rates.get(&code).copied().filter(|rate| *rate > 0.0)?;
+                    1.0 / rate
+                };
+                Some(CoinPrice {
+                    name: calc::fiat_name(&code).to_string(),
+                    symbol: code,
+                    price,
+                    currency: quote.clone(),
+                    provider: PROVIDER_NAME.to_string(),
+                    timestamp: now,
+                    quote_time_estimated: true,
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        if prices.is_empty() {
+            return Err(Error::no_results(PROVIDER_NAME, symbols, &quote));
+        }
+
+        Ok(prices)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_price_history(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        days: u32,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        if interval.fetch_interval() == HistoryInterval::Hourly {
+            return Err(Error::Config(
+                "provider 'frankfurter' only publishes daily rates".into(),
+            ));
+        }
+
+        let quote = self.quote_currency(currency).await?;
+        let targets = fiat_targets(symbols, &quote);
+        if targets.is_empty() {
+            return Err(Error::no_results(PROVIDER_NAME, symbols, &quote));
+        }
+
+        let mut histories = self.get_history(&quote, &targets, days).await?;
+        for history in &mut histories {
+            history.points.retain(|point| point.price > 0.0);
+            for point in &mut history.points {
+                point.price = 1.0 / point.price;
+            }
+        }
+        histories.retain(|history| !history.points.is_empty());
+
+        if histories.is_empty() {
+            return Err(Error::no_results(PROVIDER_NAME, symbols, &quote));
+        }
+
+        note_daily_only(&mut histories, interval, days);
+        Ok(histories)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_fiat_history(
+        &self,
+        base: &str,
+        targets: &[String],
+        start: chrono::NaiveDate,
+        end: chrono::NaiveDate,
+    ) -> Result<Vec<PriceHistory>> {
+        let base = self.quote_currency(base).await?;
+        self.get_history_range(&base, targets, start, end).await
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        Ok(ECB_CURRENCIES
+            .iter()
+            .filter(|code| {
+                code.to_lowercase().contains(&query)
+                    || calc::fiat_name(code).to_lowercase().contains(&query)
+            })
+            .take(limit)
+            .map(|code| TickerMatch {
+                symbol: code.to_string(),
+                name: calc::fiat_name(code).to_string(),
+                exchange: "ECB".to_string(),
+                asset_type: "currency".to_string(),
+                provider: PROVIDER_NAME.to_string(),
+            })
+            .collect())
+    }
+}
+
+/// Returns `true` when the ECB publishes reference rates for `code` (case-insensitive).
+pub fn is_ecb_currency(code: &str) -> bool {
+    ECB_CURRENCIES.contains(&code.trim().to_uppercase().as_str())
+}
+
+/// Uppercase `currency`, rejecting anything the ECB does not publish.
+/// ECB currency codes among `symbols`, uppercased and deduplicated, excluding `quote`.
+fn fiat_targets(symbols: &[String], quote: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for symbol in symbols {
+        let code = symbol.trim().to_uppercase();
+        if code != quote && is_ecb_currency(&code) && !targets.contains(&code) {
+            targets.push(code);
+        }
+    }
+    targets
+}
+
+/// When latest rates for ECB reference date `date`, fetched at `fetched_at`, stop being
+/// served from cache: at the next publication, capped at [`LATEST_RATES_MAX_AGE_SECS`].
+fn rates_expire_at(
+    date: Option<chrono::NaiveDate>,
+    fetched_at: chrono::DateTime<chrono::Utc>,
+) -> chrono::DateTime<chrono::Utc> {
+    let max_age = fetched_at + chrono::Duration::seconds(LATEST_RATES_MAX_AGE_SECS);
+    let Some(date) = date else {
+        return max_age;
+    };
+    next_publication(date)
+        .max(fetched_at + chrono::Duration::seconds(LATEST_RATES_RECHECK_SECS))
+        .min(max_age)
+}
+
+/// Publication time of the reference rates following those for `date`: the next
+/// weekday at [`ECB_PUBLICATION_HOUR_UTC`]. TARGET holidays are not modelled.
+fn next_publication(date: chrono::NaiveDate) -> chrono::DateTime<chrono::Utc> {
+    use chrono::Datelike;
+
+    let mut next = date.succ_opt().unwrap_or(date);
+    while matches!(next.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun) {
+        next = next.succ_opt().unwrap_or(next);
+    }
+    next.and_hms_opt(ECB_PUBLICATION_HOUR_UTC, 0, 0)
+        .expect("publication hour is a valid time")
+        .and_utc()
+}
+
 /// Response shape from `GET /latest` on the Frankfurter API.
 #[derive(Debug, Serialize, Deserialize)]
 struct FrankfurterResponse {
+    /// ECB reference date of the rates; absent in entries cached by older versions.
+    #[serde(default)]
+    date: Option<chrono::NaiveDate>,
     rates: HashMap<String, f64>,
 }
 
@@ -190,6 +523,48 @@ mod tests {
         assert_eq!(resp.rates.len(), 2);
         assert!((resp.rates["EUR"] - 0.84983).abs() < 1e-6);
         assert!((resp.rates["GBP"] - 0.74174).abs() < 1e-6);
+        assert_eq!(resp.date, chrono::NaiveDate::from_ymd_opt(2026, 2, 20));
+    }
+
+    #[test]
+    fn cached_rates_expire_at_next_publication_or_max_age() {
+        use chrono::TimeZone;
+        let utc = |d, h, m| chrono::Utc.with_ymd_and_hms(2026, 2, d, h, m, 0).unwrap();
+        // Friday 2026-02-20 rates.
+        let friday = chrono::NaiveDate::from_ymd_opt(2026, 2, 20);
+
+        // Fetched Friday evening: capped at six hours.
+        assert_eq!(rates_expire_at(friday, utc(20, 18, 0)), utc(21, 0, 0));
+        // Fetched Monday morning: Monday's publication comes first.
+        assert_eq!(rates_expire_at(friday, utc(23, 10, 0)), utc(23, 14, 0));
+        // Monday's publication is overdue: check again in an hour.
+        assert_eq!(rates_expire_at(friday, utc(23, 15, 30)), utc(23, 16, 30));
+        // No reference date: only the age cap applies.
+        assert_eq!(rates_expire_at(None, utc(23, 10, 0)), utc(23, 16, 0));
+    }
+
+    #[tokio::test]
+    async fn search_matches_codes_and_names() {
+        let provider = Frankfurter::new();
+
+        let by_code = provider.search_tickers("chf", 5).await.unwrap();
+        assert!(is_ecb_currency("chf"));
+        assert!(!is_ecb_currency("NGN"));
+        assert_eq!(by_code.len(), 1);
+        assert_eq!(by_code[0].name, "Swiss Franc");
+
+        let by_name = provider.search_tickers("peso", 10).await.unwrap();
+        assert!(by_name.iter().any(|m| m.symbol == "MXN"));
+        assert!(by_name.iter().all(|m| m.name.contains("Peso")));
+    }
+
+    #[test]
+    fn fiat_targets_skip_quote_and_non_fiat_symbols() {
+        let symbols: Vec<String> = ["eur", "btc", "USD", "gbp", "EUR", "ngn"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(fiat_targets(&symbols, "USD"), vec!["EUR", "GBP"]);
     }
 
     #[test]
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use serde::Serialize;
+use tracing::debug;
+
+use super::PriceProvider;
+use crate::error::Error;
+
+/// Per-provider timeout for `--check-providers` probes.
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a provider health probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// The provider needs an API key and none is configured.
+    NoApiKey,
+    AuthError,
+    RateLimited,
+    /// Connection failure or timeout.
+    Unreachable,
+    /// Any other failure, such as a malformed response.
+    Error,
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Ok => "ok",
+            Self::NoApiKey => "no API key configured",
+            Self::AuthError => "auth error",
+            Self::RateLimited => "rate limited",
+            Self::Unreachable => "unreachable",
+            Self::Error => "error",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Result of probing one provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub name: String,
+    pub status: HealthStatus,
+    /// Round-trip time of the probe; absent when no request was sent.
+    pub latency_ms: Option<u64>,
+    pub detail: Option<String>,
+}
+
+/// Probe every provider concurrently with a known-good USD lookup.
+///
+/// Each probe is bounded by `timeout`, so one dead endpoint cannot stall the report.
+pub async fn check_providers(
+    providers: &[Box<dyn PriceProvider>],
+    timeout: Duration,
+) -> Vec<ProviderHealth> {
+    join_all(
+        providers
+            .iter()
+            .map(|provider| check_provider(provider.as_ref(), timeout)),
+    )
+    .await
+}
+
+async fn check_provider(provider: &dyn PriceProvider, timeout: Duration) -> ProviderHealth {
+    let symbols = vec![probe_symbol(provider.id()).to_string()];
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(timeout, provider.get_prices(&symbols, "usd")).await;
+    let elapsed = started.elapsed();
+
+    let (status, detail) = match outcome {
+        Ok(Ok(_)) => (HealthStatus::Ok, None),
+        Ok(Err(err)) => classify_error(&err),
+        Err(_) => (
+            HealthStatus::Unreachable,
+            Some(format!("timed out after {}s", timeout.as_secs_f64())),
+        ),
+    };
+    debug!(provider = provider.id(), status = %status, elapsed_ms = elapsed.as_millis() as u64, "provider health probe finished");
+
+    ProviderHealth {
+        provider: provider.id().to_string(),
+        name: provider.name().to_string(),
+        status,
+        latency_ms: (status != HealthStatus::NoApiKey).then_some(elapsed.as_millis() as u64),
+        detail,
+    }
+}
+
+/// Symbol each provider is expected to always quote.
+fn probe_symbol(provider_id: &str) -> &'static str {
+    match provider_id {
+        "yahoo" | "stooq" => "aapl",
+        "frankfurter" => "eur",
+        _ => "btc",
+    }
+}
+
+fn classify_error(err: &Error) -> (HealthStatus, Option<String>) {
+    let status = match err {
+        Error::Config(msg) if msg.to_ascii_lowercase().contains("api-key") => {
+            return (HealthStatus::NoApiKey, None);
+        }
+        Error::Http(_) => HealthStatus::Unreachable,
+        Error::RateLimited { .. } => HealthStatus::RateLimited,
+        Error::Api {
+            status: Some(401 | 403),
+            ..
+        } => HealthStatus::AuthError,
+        _ => HealthStatus::Error,
+    };
+    (status, Some(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::provider::CoinPrice;
+    use async_trait::async_trait;
+
+    struct StubProvider {
+        id: &'static str,
+        delay: Duration,
+        result: fn() -> Result<Vec<CoinPrice>>,
+    }
+
+    #[async_trait]
+    impl PriceProvider for StubProvider {
+        fn name(&self) -> &str {
+            self.id
+        }
+
+        fn id(&self) -> &str {
+            self.id
+        }
+
+        async fn get_prices(&self, _symbols: &[String], _currency: &str) -> Result<Vec<CoinPrice>> {
+            tokio::time::sleep(self.delay).await;
+            (self.result)()
+        }
+    }
+
+    #[test]
+    fn classify_error_maps_provider_failures() {
+        let no_key = Error::Config(
+            "CoinMarketCap price lookup requires --api-key or COINMARKETCAP_API_KEY".into(),
+        );
+        assert_eq!(classify_error(&no_key), (HealthStatus::NoApiKey, None));
+        assert_eq!(
+            classify_error(&Error::RateLimited {
+                provider: "CoinGecko".into(),
+                retry_after: None,
+            })
+            .0,
+            HealthStatus::RateLimited
+        );
+        assert_eq!(
+            classify_error(&Error::api_status(
+                "CoinMarketCap",
+                reqwest::StatusCode::UNAUTHORIZED,
+                "CoinMarketCap returned 401 Unauthorized: bad key"
+            ))
+            .0,
+            HealthStatus::AuthError
+        );
+        assert_eq!(
+            classify_error(&Error::Parse("Yahoo JSON: eof".into())).0,
+            HealthStatus::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn check_providers_times_out_slow_providers_without_blocking_others() {
+        let providers: Vec<Box<dyn PriceProvider>> = vec![
+            Box::new(StubProvider {
+                id: "slow",
+                delay: Duration::from_secs(60),
+                result: || Ok(Vec::new()),
+            }),
+            Box::new(StubProvider {
+                id: "fast",
+                delay: Duration::ZERO,
+                result: || Ok(Vec::new()),
+            }),
+        ];
+
+        let started = Instant::now();
+        let report = check_providers(&providers, Duration::from_millis(50)).await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(report[0].status, HealthStatus::Unreachable);
+        assert!(report[0].detail.as_deref().unwrap().contains("timed out"));
+        assert_eq!(report[1].status, HealthStatus::Ok);
+        assert!(report[1].latency_ms.is_some());
+    }
+}
@@ -0,0 +1,529 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, IntoUrl, Proxy, RequestBuilder, Response, StatusCode};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use super::cache;
+use crate::config::HttpConfig;
+use crate::error::{Error, Result};
+use crate::timings;
+
+/// Total request timeout used when `[http] timeout_secs` is not set.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Connect timeout used when `[http] connect_timeout_secs` is not set.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Per-provider request fan-out used when `[http] max_concurrency` is not set.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Longest a request waits for the rate limiter when `rate_limit_max_wait_secs` is not set.
+pub const DEFAULT_RATE_LIMIT_MAX_WAIT: Duration = Duration::from_secs(10);
+
+/// Default `User-Agent` product token, carrying the build version.
+pub const DEFAULT_USER_AGENT: &str = concat!("pricr/", env!("PRICR_VERSION"));
+
+/// Client-side request budget for one provider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained requests per minute.
+    pub per_min: u32,
+    /// Requests that may go out back to back before spacing kicks in.
+    pub burst: u32,
+    /// Fail with [`Error::RateLimited`] instead of waiting longer than this.
+    pub max_wait: Duration,
+}
+
+impl RateLimit {
+    /// `per_min` requests per minute, all of which may be spent at once.
+    pub fn per_minute(per_min: u32) -> Self {
+        Self {
+            per_min,
+            burst: per_min,
+            max_wait: DEFAULT_RATE_LIMIT_MAX_WAIT,
+        }
+    }
+}
+
+/// Token bucket shared by every clone of a rate-limited client.
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    /// Available tokens and when they were last refilled; tokens go negative for
+    /// requests that reserved a future slot and are sleeping until it.
+    state: Mutex<(f64, tokio::time::Instant)>,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new((f64::from(limit.burst.max(1)), tokio::time::Instant::now())),
+        }
+    }
+
+    /// Take a token, sleeping until one is available or failing when that is too far off.
+    async fn acquire(&self, provider: &str) -> Result<()> {
+        let per_sec = f64::from(self.limit.per_min.max(1)) / 60.0;
+        let wait = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let (tokens, updated) = &mut *state;
+            let now = tokio::time::Instant::now();
+            let refilled = now.duration_since(*updated).as_secs_f64() * per_sec;
+            *tokens = (*tokens + refilled).min(f64::from(self.limit.burst.max(1)));
+            *updated = now;
+
+            let wait = Duration::from_secs_f64(((1.0 - *tokens) / per_sec).max(0.0));
+            if wait > self.limit.max_wait {
+                return Err(Error::RateLimited {
+                    provider: provider.to_string(),
+                    retry_after: Some(wait),
+                });
+            }
+            *tokens -= 1.0;
+            wait
+        };
+        if !wait.is_zero() {
+            debug!(
+                provider,
+                wait_ms = wait.as_millis() as u64,
+                "waiting for client-side rate limit"
+            );
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+}
+
+/// HTTP client shared by providers, remembering its timeout for error messages.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    /// `None` offline, where nothing is sent and no connection pool is built.
+    client: Option<Client>,
+    /// Settings the client was built from, for [`with_cookie_store`](Self::with_cookie_store).
+    config: Arc<HttpConfig>,
+    timeout: Duration,
+    max_concurrency: usize,
+    limiter: Option<Arc<TokenBucket>>,
+    cancel: Option<CancellationToken>,
+    /// Where providers using this client cache responses; `None` caches nothing.
+    cache_dir: Option<PathBuf>,
+    #[cfg(feature = "devtools")]
+    fixtures: Option<Arc<super::fixtures::Fixtures>>,
+}
+
+impl HttpClient {
+    /// Total per-request timeout this client was built with.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Maximum number of per-symbol requests a provider should run at once.
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Directory the providers using this client read and write cached responses
+    /// under; [`cache::cache_dir`] unless [`with_cache_dir`](Self::with_cache_dir)
+    /// moved it.
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_deref()
+    }
+
+    /// A client sharing this one's connection pool whose providers cache responses
+    /// under `dir`.
+    pub fn with_cache_dir(&self, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: Some(dir.into()),
+            ..self.clone()
+        }
+    }
+
+    /// A client with its own connection pool and cookie store, for a provider whose
+    /// session lives in cookies. Other providers' requests never carry those cookies.
+    pub fn with_cookie_store(&self) -> Result<Self> {
+        let client = match &self.client {
+            Some(_) => Some(reqwest_client(&self.config, true)?),
+            None => None,
+        };
+        Ok(Self {
+            client,
+            ..self.clone()
+        })
+    }
+
+    /// A client sharing this one's connection pool whose requests draw from a new
+    /// token bucket. Clones of the returned client share that bucket.
+    pub fn with_rate_limit(&self, limit: RateLimit) -> Self {
+        Self {
+            limiter: Some(Arc::new(TokenBucket::new(limit))),
+            ..self.clone()
+        }
+    }
+
+    /// A client sharing this one's connection pool whose requests, including rate
+    /// limiter waits, fail with [`Error::Interrupted`] once `token` is cancelled.
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        Self {
+            cancel: Some(token),
+            ..self.clone()
+        }
+    }
+
+    /// The underlying connection pool, or `None` offline.
+    pub fn client(&self) -> Option<&Client> {
+        self.client.as_ref()
+    }
+
+    /// Start a `GET` request to `url`, or fail with [`cache::offline_error`] when offline,
+    /// where the client has no connection pool to build it on.
+    pub fn get(&self, provider: &str, url: impl IntoUrl) -> Result<RequestBuilder> {
+        match &self.client {
+            Some(client) => Ok(client.get(url)),
+            None => Err(cache::offline_error(provider)),
+        }
+    }
+
+    /// Run `fut` unless the client's cancellation token fires first, in which case the
+    /// in-flight request is dropped.
+    async fn cancellable<T>(&self, fut: impl Future<Output = Result<T>>) -> Result<T> {
+        let Some(token) = &self.cancel else {
+            return fut.await;
+        };
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(Error::Interrupted),
+            result = fut => result,
+        }
+    }
+
+    /// A client sharing this one's connection pool that records responses to, or
+    /// replays them from, `fixtures`.
+    #[cfg(feature = "devtools")]
+    pub fn with_fixtures(&self, fixtures: super::fixtures::Fixtures) -> Self {
+        Self {
+            fixtures: Some(Arc::new(fixtures)),
+            ..self.clone()
+        }
+    }
+
+    /// Send `request`, reporting a timeout as an API error naming `provider`.
+    ///
+    /// Rate-limited clients first wait for a token, or fail with [`Error::RateLimited`]
+    /// when the wait would exceed the limit's `max_wait`. A `429 Too Many Requests`
+    /// answer also becomes [`Error::RateLimited`], with the `Retry-After` header when
+    /// the provider sent one. A cancelled client fails with [`Error::Interrupted`].
+    pub async fn send(&self, provider: &str, request: RequestBuilder) -> Result<Response> {
+        let response = self.cancellable(self.execute(provider, request)).await?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(Error::RateLimited {
+                provider: provider.to_string(),
+                retry_after: response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_retry_after),
+            });
+        }
+        Ok(response)
+    }
+
+    async fn execute(&self, provider: &str, request: RequestBuilder) -> Result<Response> {
+        if cache::is_offline() {
+            return Err(cache::offline_error(provider));
+        }
+        #[cfg(feature = "devtools")]
+        if let Some(fixtures) = &self.fixtures {
+            return self
+                .execute_with_fixtures(fixtures, provider, request)
+                .await;
+        }
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(provider).await?;
+        }
+        let started = std::time::Instant::now();
+        let response = request
+            .send()
+            .await
+            .map_err(|err| self.request_error(provider, err))?;
+        let elapsed = started.elapsed();
+        debug!(
+            provider,
+            status = response.status().as_u16(),
+            latency_ms = elapsed.as_millis() as u64,
+            "provider responded"
+        );
+        timings::record_request(
+            provider,
+            response.url(),
+            response.status().as_u16(),
+            elapsed,
+        );
+        Ok(response)
+    }
+
+    /// Replayed requests skip the network and rate limiter; recorded ones are saved
+    /// before their body is handed back.
+    #[cfg(feature = "devtools")]
+    async fn execute_with_fixtures(
+        &self,
+        fixtures: &super::fixtures::Fixtures,
+        provider: &str,
+        request: RequestBuilder,
+    ) -> Result<Response> {
+        use super::fixtures::{self, FixtureMode};
+
+        let (client, request) = request.build_split();
+        let request = request.map_err(|err| self.request_error(provider, err))?;
+        let method = request.method().clone();
+        let url = request.url().clone();
+        if fixtures.mode() == FixtureMode::Replay {
+            return fixtures.load(provider, &method, &url);
+        }
+
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire(provider).await?;
+        }
+        let response = client
+            .execute(request)
+            .await
+            .map_err(|err| self.request_error(provider, err))?;
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| self.request_error(provider, err))?;
+        fixtures.save(&method, &url, status, content_type.as_deref(), &body)?;
+        Ok(fixtures::build_response(
+            status,
+            content_type.as_deref(),
+            body.to_vec(),
+        ))
+    }
+
+    /// Read a response body, with the same timeout reporting as [`HttpClient::send`].
+    pub async fn text(&self, provider: &str, response: Response) -> Result<String> {
+        let url = response.url().clone();
+        let started = std::time::Instant::now();
+        let body = self
+            .cancellable(async {
+                response
+                    .text()
+                    .await
+                    .map_err(|err| self.request_error(provider, err))
+            })
+            .await?;
+        timings::record_body(&url, started.elapsed());
+        Ok(body)
+    }
+
+    /// Convert a transport error, turning timeouts into `Error::Api`.
+    pub fn request_error(&self, provider: &str, err: reqwest::Error) -> Error {
+        if err.is_timeout() {
+            return Error::api(
+                provider,
+                format!(
+                    "request to {} timed out after {}s",
+                    provider,
+                    self.timeout.as_secs()
+                ),
+            );
+        }
+        Error::Http(err)
+    }
+}
+
+impl HttpClient {
+    /// A client with default `[http]` settings, or the error building it.
+    pub fn try_default() -> Result<Self> {
+        build_client(&HttpConfig::default())
+    }
+}
+
+impl Default for HttpClient {
+    /// # Panics
+    ///
+    /// Panics if the client cannot be built; use [`HttpClient::try_default`] to get the
+    /// error instead.
+    fn default() -> Self {
+        Self::try_default().expect("failed to build HTTP client")
+    }
+}
+
+/// Parse a `Retry-After` value: delay seconds or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// The `User-Agent` sent to providers: `[http] user_agent` or [`DEFAULT_USER_AGENT`],
+/// followed by `(+<contact>)` when a contact URL is configured.
+pub fn user_agent(cfg: &HttpConfig) -> String {
+    let product = cfg
+        .user_agent
+        .as_deref()
+        .map(str::trim)
+        .filter(|ua| !ua.is_empty())
+        .unwrap_or(DEFAULT_USER_AGENT);
+    match cfg.contact.as_deref().map(str::trim) {
+        Some(contact) if !contact.is_empty() => format!("{} (+{})", product, contact),
+        _ => product.to_string(),
+    }
+}
+
+/// Build the HTTP client used by providers from `[http]` settings.
+///
+/// Without an explicit `proxy`, the standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`
+/// environment variables are honored.
+pub fn build_client(cfg: &HttpConfig) -> Result<HttpClient> {
+    // Offline nothing is sent: skip the connection pool, proxy discovery, and root
+    // certificates altogether.
+    let client = if cache::is_offline() {
+        None
+    } else {
+        Some(reqwest_client(cfg, false)?)
+    };
+    Ok(HttpClient {
+        client,
+        config: Arc::new(cfg.clone()),
+        timeout: request_timeout(cfg),
+        max_concurrency: cfg
+            .max_concurrency
+            .unwrap_or(DEFAULT_MAX_CONCURRENCY)
+            .max(1),
+        limiter: None,
+        cancel: None,
+        cache_dir: cache::cache_dir(),
+        #[cfg(feature = "devtools")]
+        fixtures: None,
+    })
+}
+
+fn request_timeout(cfg: &HttpConfig) -> Duration {
+    Duration::from_secs(cfg.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+}
+
+fn reqwest_client(cfg: &HttpConfig, cookies: bool) -> Result<Client> {
+    let connect_timeout = Duration::from_secs(
+        cfg.connect_timeout_secs
+            .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+    );
+    let mut builder = Client::builder()
+        .user_agent(user_agent(cfg))
+        .timeout(request_timeout(cfg))
+        .connect_timeout(connect_timeout)
+        .cookie_store(cookies);
+    if let Some(proxy) = cfg.proxy.as_deref() {
+        debug!(proxy = %proxy, "routing provider requests through configured proxy");
+        let proxy = Proxy::all(proxy)
+            .map_err(|e| Error::Config(format!("invalid [http] proxy '{}': {}", proxy, e)))?;
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| Error::Config(format!("failed to build HTTP client: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_accepts_seconds_and_dates() {
+        assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn build_client_applies_configured_timeout() {
+        let client = build_client(&HttpConfig {
+            timeout_secs: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(client.timeout(), Duration::from_secs(7));
+        assert_eq!(
+            HttpClient::default().timeout(),
+            Duration::from_secs(DEFAULT_TIMEOUT_SECS)
+        );
+    }
+
+    #[test]
+    fn user_agent_uses_override_and_contact() {
+        assert_eq!(
+            user_agent(&HttpConfig::default()),
+            format!("pricr/{}", env!("PRICR_VERSION"))
+        );
+        assert_eq!(
+            user_agent(&HttpConfig {
+                user_agent: Some("my-dashboard/2.0".into()),
+                contact: Some("https://example.com/bot".into()),
+                ..Default::default()
+            }),
+            "my-dashboard/2.0 (+https://example.com/bot)"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn token_bucket_spaces_requests_after_burst() {
+        let bucket = TokenBucket::new(RateLimit {
+            per_min: 60,
+            burst: 2,
+            max_wait: Duration::from_secs(5),
+        });
+        let start = tokio::time::Instant::now();
+        for _ in 0..4 {
+            bucket.acquire("CoinGecko").await.unwrap();
+        }
+        // Two immediate requests, then one per second.
+        assert_eq!(start.elapsed().as_secs(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn token_bucket_fails_fast_past_max_wait() {
+        let bucket = TokenBucket::new(RateLimit {
+            per_min: 1,
+            burst: 1,
+            max_wait: Duration::from_secs(5),
+        });
+        bucket.acquire("CoinGecko").await.unwrap();
+        let err = bucket.acquire("CoinGecko").await.unwrap_err();
+        match err {
+            Error::RateLimited {
+                provider,
+                retry_after: Some(wait),
+            } => {
+                assert_eq!(provider, "CoinGecko");
+                assert_eq!(wait.as_secs(), 60);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn build_client_rejects_invalid_proxy() {
+        let err = build_client(&HttpConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(err, Error::Config(ref msg) if msg.contains("invalid [http] proxy")));
+    }
+}
@@ -0,0 +1,230 @@
+//! Index and commodity symbols shared by the stock providers.
+//!
+//! Stooq and Yahoo spell the same instrument differently (`^spx` vs `^GSPC`,
+//! `xauusd` vs `GC=F`). Users can type either spelling, a friendly alias such as
+//! `gold`, or an index code behind `^`, `.` or `index:`; [`lookup`] maps all of
+//! them onto one [`MarketSymbol`] that knows each provider's ticker.
+
+/// A well-known index or commodity with its provider tickers.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Instrument {
+    /// Friendly name shown in the `name` field, e.g. `S&P 500`.
+    pub name: &'static str,
+    /// Stooq ticker, e.g. `^spx` or `xauusd`.
+    pub stooq: &'static str,
+    /// Yahoo Finance ticker, e.g. `^GSPC` or `GC=F`.
+    pub yahoo: &'static str,
+    /// Quote currency; `None` for indices, which are quoted in points.
+    pub currency: Option<&'static str>,
+    /// Extra spellings; index aliases only match behind an index prefix.
+    aliases: &'static [&'static str],
+}
+
+impl Instrument {
+    fn is_index(&self) -> bool {
+        self.stooq.starts_with('^')
+    }
+
+    fn matches(&self, code: &str) -> bool {
+        let bare = |ticker: &str| ticker.trim_start_matches('^').eq_ignore_ascii_case(code);
+        bare(self.stooq) || bare(self.yahoo) || self.aliases.contains(&code)
+    }
+}
+
+const INSTRUMENTS: &[Instrument] = &[
+    Instrument {
+        name: "S&P 500",
+        stooq: "^spx",
+        yahoo: "^GSPC",
+        currency: None,
+        aliases: &["sp500"],
+    },
+    Instrument {
+        name: "Dow Jones Industrial Average",
+        stooq: "^dji",
+        yahoo: "^DJI",
+        currency: None,
+        aliases: &["dow"],
+    },
+    Instrument {
+        name: "Nasdaq Composite",
+        stooq: "^ndq",
+        yahoo: "^IXIC",
+        currency: None,
+        aliases: &["nasdaq"],
+    },
+    Instrument {
+        name: "Nasdaq 100",
+        stooq: "^ndx",
+        yahoo: "^NDX",
+        currency: None,
+        aliases: &[],
+    },
+    Instrument {
+        name: "DAX",
+        stooq: "^dax",
+        yahoo: "^GDAXI",
+        currency: None,
+        aliases: &[],
+    },
+    Instrument {
+        name: "FTSE 100",
+        stooq: "^ukx",
+        yahoo: "^FTSE",
+        currency: None,
+        aliases: &[],
+    },
+    Instrument {
+        name: "Nikkei 225",
+        stooq: "^nkx",
+        yahoo: "^N225",
+        currency: None,
+        aliases: &["nikkei"],
+    },
+    Instrument {
+        name: "Gold",
+        stooq: "xauusd",
+        yahoo: "GC=F",
+        currency: Some("USD"),
+        aliases: &["gold", "xau"],
+    },
+    Instrument {
+        name: "Silver",
+        stooq: "xagusd",
+        yahoo: "SI=F",
+        currency: Some("USD"),
+        aliases: &["silver", "xag"],
+    },
+    Instrument {
+        name: "Crude Oil (WTI)",
+        stooq: "cl.f",
+        yahoo: "CL=F",
+        currency: Some("USD"),
+        aliases: &["oil", "wti"],
+    },
+    Instrument {
+        name: "Natural Gas",
+        stooq: "ng.f",
+        yahoo: "NG=F",
+        currency: Some("USD"),
+        aliases: &["natgas"],
+    },
+];
+
+/// An index or commodity recognised in user input.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MarketSymbol {
+    /// An entry of the built-in table.
+    Known(&'static Instrument),
+    /// Any other index, by its lowercase code without the prefix.
+    Index(String),
+}
+
+impl MarketSymbol {
+    /// Ticker to send to Stooq.
+    pub fn stooq_ticker(&self) -> String {
+        match self {
+            Self::Known(instrument) => instrument.stooq.to_string(),
+            Self::Index(code) => format!("^{}", code),
+        }
+    }
+
+    /// Ticker to send to Yahoo Finance.
+    pub fn yahoo_ticker(&self) -> String {
+        match self {
+            Self::Known(instrument) => instrument.yahoo.to_string(),
+            Self::Index(code) => format!("^{}", code.to_uppercase()),
+        }
+    }
+
+    /// Friendly name from the table, if the instrument is in it.
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Known(instrument) => Some(instrument.name),
+            Self::Index(_) => None,
+        }
+    }
+
+    /// Currency the instrument is quoted in, if it has one.
+    pub fn currency(&self) -> Option<&'static str> {
+        match self {
+            Self::Known(instrument) => instrument.currency,
+            Self::Index(_) => None,
+        }
+    }
+}
+
+/// Recognise `symbol` as an index or commodity.
+///
+/// Commodities match their aliases and provider tickers as typed (`gold`,
+/// `xauusd`, `cl.f`, `GC=F`). Indices need a `^`, `.` or `index:` prefix so
+/// that tickers such as `dow` still reach the stock lookup.
+pub fn lookup(symbol: &str) -> Option<MarketSymbol> {
+    let symbol = symbol.trim().to_lowercase();
+    if let Some(instrument) = INSTRUMENTS
+        .iter()
+        .find(|instrument| !instrument.is_index() && instrument.matches(&symbol))
+    {
+        return Some(MarketSymbol::Known(instrument));
+    }
+
+    let code = ["index:", "^", "."]
+        .iter()
+        .find_map(|prefix| symbol.strip_prefix(prefix))?;
+    if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(
+        INSTRUMENTS
+            .iter()
+            .find(|instrument| instrument.is_index() && instrument.matches(code))
+            .map(MarketSymbol::Known)
+            .unwrap_or_else(|| MarketSymbol::Index(code.to_string())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_prefixes_map_to_provider_tickers() {
+        for input in ["^spx", ".SPX", "index:spx", "^GSPC", "index:sp500"] {
+            let symbol = lookup(input).unwrap_or_else(|| panic!("{input} should resolve"));
+            assert_eq!(symbol.stooq_ticker(), "^spx", "{input}");
+            assert_eq!(symbol.yahoo_ticker(), "^GSPC", "{input}");
+            assert_eq!(symbol.name(), Some("S&P 500"), "{input}");
+            assert_eq!(symbol.currency(), None, "{input}");
+        }
+
+        let other = lookup("^hsi").unwrap();
+        assert_eq!(other, MarketSymbol::Index("hsi".to_string()));
+        assert_eq!(other.stooq_ticker(), "^hsi");
+        assert_eq!(other.yahoo_ticker(), "^HSI");
+        assert_eq!(other.name(), None);
+    }
+
+    #[test]
+    fn commodities_match_aliases_and_both_tickers() {
+        for input in ["gold", "XAU", "xauusd", "GC=F"] {
+            let symbol = lookup(input).unwrap_or_else(|| panic!("{input} should resolve"));
+            assert_eq!(symbol.stooq_ticker(), "xauusd", "{input}");
+            assert_eq!(symbol.yahoo_ticker(), "GC=F", "{input}");
+            assert_eq!(symbol.name(), Some("Gold"), "{input}");
+            assert_eq!(symbol.currency(), Some("USD"), "{input}");
+        }
+        let oil = lookup("oil").unwrap();
+        assert_eq!(oil.stooq_ticker(), "cl.f");
+        assert_eq!(oil.yahoo_ticker(), "CL=F");
+        assert_eq!(lookup("cl.f"), Some(oil));
+    }
+
+    #[test]
+    fn plain_tickers_are_left_alone() {
+        for input in [
+            "aapl", "dow", "spx", "sap.de", "cl", "^", "index:", "^a.b", "btc",
+        ] {
+            assert_eq!(lookup(input), None, "{input}");
+        }
+    }
+}
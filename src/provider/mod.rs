@@ -1,37 +1,239 @@
-mod cache;
+mod aliased;
+pub mod cache;
 pub mod coingecko;
 pub mod coinmarketcap;
+mod converted;
+pub mod fiat;
+#[cfg(feature = "devtools")]
+pub mod fixtures;
 pub mod frankfurter;
+pub mod health;
+pub mod http;
+pub mod instruments;
+pub mod open_er_api;
 pub mod stooq;
 pub mod yahoo;
 
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
 
+use crate::config::{self, ProviderSettings, SymbolAliases};
 use crate::error::{Error, Result};
 
+static HISTORY_EVENTS: AtomicBool = AtomicBool::new(false);
+
 /// A single coin's price data returned by a provider.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct CoinPrice {
     pub symbol: String,
     pub name: String,
     pub price: f64,
     pub change_24h: Option<f64>,
+    #[serde(default)]
+    pub change_7d: Option<f64>,
+    #[serde(default)]
+    pub change_30d: Option<f64>,
     pub market_cap: Option<f64>,
+    #[serde(default)]
+    pub volume_24h: Option<f64>,
+    #[serde(default)]
+    pub circulating_supply: Option<f64>,
+    /// Market cap rank as reported by the provider.
+    #[serde(default)]
+    pub rank: Option<u32>,
+    /// All-time high price; always serialized, `null` when the provider has none.
+    #[serde(default)]
+    pub ath: Option<f64>,
+    #[serde(
+        default,
+        serialize_with = "crate::output::tz::serialize_optional_timestamp"
+    )]
+    pub ath_date: Option<chrono::DateTime<chrono::Utc>>,
+    /// All-time low price.
+    #[serde(default)]
+    pub atl: Option<f64>,
+    #[serde(
+        default,
+        serialize_with = "crate::output::tz::serialize_optional_timestamp"
+    )]
+    pub atl_date: Option<chrono::DateTime<chrono::Utc>>,
+    /// Best bid from the provider's order book or quote; always serialized.
+    #[serde(default)]
+    pub bid: Option<f64>,
+    /// Best ask.
+    #[serde(default)]
+    pub ask: Option<f64>,
+    /// Reference date from `--since`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_date: Option<chrono::NaiveDate>,
+    /// Closing price on `since_date`, in `currency`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_price: Option<f64>,
+    /// Percent change from `since_price` to `price`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_change_pct: Option<f64>,
+    /// Percent change from the price the previous `--delta` run saw.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_pct: Option<f64>,
+    /// When the previous `--delta` run saw its price.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::output::tz::serialize_optional_timestamp"
+    )]
+    pub delta_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Price in each `--currencies` code, in the order requested; `None` where the
+    /// provider had no quote. Empty for single-currency lookups.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "currency_prices"
+    )]
+    #[schemars(with = "std::collections::BTreeMap<String, Option<f64>>")]
+    pub prices: Vec<(String, Option<f64>)>,
     pub currency: String,
     pub provider: String,
+    /// Credit line the provider asks for, such as `Data by CoinGecko`; set by [`attribute`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// When the provider says the quote was taken, or the fetch time when it does not say.
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Set when `timestamp` is the fetch time because the provider gave no quote time.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quote_time_estimated: bool,
+    /// Set when the provider quoted another currency and the row was converted to `currency`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub converted: bool,
+    /// Rate applied by the conversion: 1 unit of the provider's currency = `fx_rate` `currency`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fx_rate: Option<f64>,
+    /// Equity-only quote details; `None` for crypto and providers without them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended: Option<ExtendedQuote>,
+}
+
+/// `CoinPrice::prices` as a JSON object keyed by currency, keeping the requested order.
+mod currency_prices {
+    use serde::de::{MapAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        prices: &[(String, Option<f64>)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(prices.iter().map(|(currency, price)| (currency, price)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(String, Option<f64>)>, D::Error> {
+        struct PricesVisitor;
+
+        impl<'de> Visitor<'de> for PricesVisitor {
+            type Value = Vec<(String, Option<f64>)>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a map of currency codes to prices")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut prices = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    prices.push(entry);
+                }
+                Ok(prices)
+            }
+        }
+
+        deserializer.deserialize_map(PricesVisitor)
+    }
+}
+
+/// Extended-hours prices, 52-week range, and share volume reported for equities.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ExtendedQuote {
+    #[serde(default)]
+    pub pre_market_price: Option<f64>,
+    #[serde(default)]
+    pub post_market_price: Option<f64>,
+    #[serde(default)]
+    pub fifty_two_week_high: Option<f64>,
+    #[serde(default)]
+    pub fifty_two_week_low: Option<f64>,
+    /// Shares traded in the regular session (not a currency amount).
+    #[serde(default)]
+    pub regular_market_volume: Option<f64>,
+}
+
+/// Prices found for a lookup plus the requested symbols that did not resolve.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriceBatch {
+    pub prices: Vec<CoinPrice>,
+    pub missing: Vec<String>,
+}
+
+impl PriceBatch {
+    /// Build a batch by matching returned prices against the requested symbols.
+    ///
+    /// Symbols are matched with [`symbol_answers`]; missing entries are reported uppercased.
+    pub fn from_requested(symbols: &[String], prices: Vec<CoinPrice>) -> Self {
+        let missing = symbols
+            .iter()
+            .filter(|symbol| {
+                !prices
+                    .iter()
+                    .any(|price| symbol_answers(symbol, &price.symbol))
+            })
+            .map(|symbol| symbol.trim().to_uppercase())
+            .collect();
+
+        Self { prices, missing }
+    }
+}
+
+/// Whether a row reported as `reported` answers a request for `requested`: the same
+/// symbol ignoring case, that symbol plus a market suffix the provider added, as
+/// Stooq reports `aapl` as `AAPL.US`, or another spelling of the same index or
+/// commodity, as Yahoo reports `gold` as `GC=F`.
+pub fn symbol_answers(requested: &str, reported: &str) -> bool {
+    let requested = requested.trim();
+    let reported = reported.trim();
+    if reported.eq_ignore_ascii_case(requested) {
+        return true;
+    }
+    if let Some(instrument) = instruments::lookup(requested) {
+        return instruments::lookup(reported).is_some_and(|other| other == instrument);
+    }
+    if requested.contains('.') {
+        return false;
+    }
+    reported
+        .rsplit_once('.')
+        .is_some_and(|(base, _)| base.eq_ignore_ascii_case(requested))
 }
 
 /// A single historical price point for a coin.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PricePoint {
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub price: f64,
+    /// Synthetic point added by gap filling rather than reported by the provider.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub filled: bool,
 }
 
 /// A single ticker search match returned by a provider.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TickerMatch {
     pub symbol: String,
     pub name: String,
@@ -40,33 +242,422 @@ pub struct TickerMatch {
     pub provider: String,
 }
 
+/// Market-wide totals from a provider's global endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalMetrics {
+    pub total_market_cap: Option<f64>,
+    pub total_volume_24h: Option<f64>,
+    /// Bitcoin share of total market cap, in percent.
+    pub btc_dominance: Option<f64>,
+    /// Ether share of total market cap, in percent.
+    pub eth_dominance: Option<f64>,
+    pub active_coins: Option<u64>,
+    pub currency: String,
+    pub provider: String,
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// A coin from a provider's trending list, with its current quote when one was found.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingCoin {
+    /// Provider id, e.g. the CoinGecko id to pass back for chart lookups.
+    pub id: String,
+    pub symbol: String,
+    pub name: String,
+    pub market_cap_rank: Option<u32>,
+    pub price: Option<f64>,
+    pub change_24h: Option<f64>,
+    pub currency: String,
+    pub provider: String,
+}
+
+/// One market (exchange and trading pair) a coin trades on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTicker {
+    pub exchange: String,
+    /// Trading pair as `BASE/TARGET`, e.g. `BTC/USDT`.
+    pub pair: String,
+    /// Last trade price in the pair's target currency.
+    pub last_price: Option<f64>,
+    /// 24h volume converted to USD, so markets with different targets compare.
+    pub volume_usd: Option<f64>,
+    /// Provider trust rating such as `green`, `yellow`, or `red`.
+    pub trust_score: Option<String>,
+    pub provider: String,
+}
+
 /// Sampling interval used when fetching historical chart data.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub enum HistoryInterval {
+    #[default]
     Auto,
     Hourly,
     Daily,
+    /// Fetched hourly; bucket with [`crate::calc::resample`].
+    FourHour,
+    /// Fetched daily; bucket with [`crate::calc::resample`].
+    Weekly,
+    /// Fetched daily; bucket with [`crate::calc::resample`].
+    Monthly,
 }
 
 impl HistoryInterval {
+    /// Every interval, in the order `--sampling` lists them.
+    pub const ALL: [Self; 6] = [
+        Self::Auto,
+        Self::Hourly,
+        Self::FourHour,
+        Self::Daily,
+        Self::Weekly,
+        Self::Monthly,
+    ];
+
     /// Render interval as the CLI-facing lowercase string.
     pub fn as_str(self) -> &'static str {
         match self {
             Self::Auto => "auto",
             Self::Hourly => "hourly",
             Self::Daily => "daily",
+            Self::FourHour => "4h",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// Granularity providers fetch for this interval; resampled intervals map to their source.
+    pub fn fetch_interval(self) -> Self {
+        match self {
+            Self::FourHour => Self::Hourly,
+            Self::Weekly | Self::Monthly => Self::Daily,
+            other => other,
+        }
+    }
+
+    /// Granularity to fetch for a window `range_days` long.
+    ///
+    /// `Auto` picks hourly points up to [`AUTO_HOURLY_MAX_DAYS`] and daily beyond, so short
+    /// ranges still draw a line; explicit intervals keep their fetch granularity.
+    pub fn resolve(self, range_days: u32) -> ResolvedInterval {
+        match self.fetch_interval() {
+            Self::Hourly => ResolvedInterval::Hourly,
+            Self::Auto if range_days <= AUTO_HOURLY_MAX_DAYS => ResolvedInterval::Hourly,
+            _ => ResolvedInterval::Daily,
         }
     }
 }
 
+impl std::str::FromStr for HistoryInterval {
+    type Err = String;
+
+    /// Accepts the `--sampling` names (`auto`, `hourly`, `4h`, `daily`, `weekly`,
+    /// `monthly`) in any case; `[chart] sampling` goes through here too.
+    fn from_str(value: &str) -> std::result::Result<Self, String> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|interval| interval.as_str().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|interval| interval.as_str()).collect();
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Longest window `--sampling auto` fetches hourly.
+pub const AUTO_HOURLY_MAX_DAYS: u32 = 7;
+
+/// Granularity a history request is fetched at once `Auto` is settled for its range.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolvedInterval {
+    Hourly,
+    Daily,
+}
+
+impl ResolvedInterval {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hourly => "hourly",
+            Self::Daily => "daily",
+        }
+    }
+}
+
+/// Whole days between `start` and `end`, at least one; an open start counts as unbounded.
+pub fn range_days(
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: chrono::DateTime<chrono::Utc>,
+) -> u32 {
+    start.map_or(u32::MAX, |start| {
+        u32::try_from((end - start).num_days().max(1)).unwrap_or(u32::MAX)
+    })
+}
+
+/// Chart window preset ending at the chart's end date, as `--interval` and
+/// `[chart] interval` name it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChartRange {
+    OneDay,
+    FiveDays,
+    #[default]
+    OneMonth,
+    SixMonths,
+    Ytd,
+    OneYear,
+    FiveYears,
+    All,
+}
+
+impl ChartRange {
+    /// Every preset, shortest first.
+    pub const ALL: [Self; 8] = [
+        Self::OneDay,
+        Self::FiveDays,
+        Self::OneMonth,
+        Self::SixMonths,
+        Self::Ytd,
+        Self::OneYear,
+        Self::FiveYears,
+        Self::All,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OneDay => "1D",
+            Self::FiveDays => "5D",
+            Self::OneMonth => "1M",
+            Self::SixMonths => "6M",
+            Self::Ytd => "YTD",
+            Self::OneYear => "1Y",
+            Self::FiveYears => "5Y",
+            Self::All => "ALL",
+        }
+    }
+
+    /// First day of the window ending on `end_date`; `None` for `ALL`.
+    pub fn start_date(self, end_date: chrono::NaiveDate) -> Option<chrono::NaiveDate> {
+        use chrono::Datelike;
+
+        match self {
+            Self::OneDay => Some(end_date - chrono::Duration::days(1)),
+            Self::FiveDays => Some(end_date - chrono::Duration::days(5)),
+            Self::OneMonth => end_date
+                .checked_sub_months(chrono::Months::new(1))
+                .or(Some(end_date - chrono::Duration::days(30))),
+            Self::SixMonths => end_date
+                .checked_sub_months(chrono::Months::new(6))
+                .or(Some(end_date - chrono::Duration::days(182))),
+            Self::Ytd => chrono::NaiveDate::from_ymd_opt(end_date.year(), 1, 1),
+            Self::OneYear => end_date
+                .checked_sub_months(chrono::Months::new(12))
+                .or(Some(end_date - chrono::Duration::days(365))),
+            Self::FiveYears => end_date
+                .checked_sub_months(chrono::Months::new(60))
+                .or(Some(end_date - chrono::Duration::days(365 * 5))),
+            Self::All => None,
+        }
+    }
+}
+
+impl std::str::FromStr for ChartRange {
+    type Err = String;
+
+    /// Accepts the preset labels (`1D`, `5D`, `1M`, `6M`, `YTD`, `1Y`, `5Y`, `ALL`) in
+    /// any case; `[chart] interval` goes through here too.
+    fn from_str(value: &str) -> std::result::Result<Self, String> {
+        let value = value.trim();
+        Self::ALL
+            .into_iter()
+            .find(|range| range.label().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                let labels: Vec<&str> = Self::ALL.iter().map(|range| range.label()).collect();
+                format!("expected one of {}", labels.join(", "))
+            })
+    }
+}
+
 /// Historical price series for one coin.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PriceHistory {
     pub symbol: String,
     pub name: String,
     pub currency: String,
     pub provider: String,
     pub points: Vec<PricePoint>,
+    /// Corporate actions inside the window; only filled when events are requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<HistoryEvent>,
+    /// Set when the provider could only deliver this coarser granularity than the
+    /// resolved interval asked for, e.g. daily closes for a one-day auto chart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<ResolvedInterval>,
+}
+
+impl PriceHistory {
+    /// Append the points of `newer` that are not older than the last point, as fetched by
+    /// an incremental refresh. A point at the last timestamp replaces it, since the latest
+    /// candle may still be moving.
+    pub fn append_newer(&mut self, newer: PriceHistory) {
+        for point in newer.points {
+            match self.points.last_mut() {
+                Some(last) if point.timestamp < last.timestamp => {}
+                Some(last) if point.timestamp == last.timestamp => *last = point,
+                _ => self.points.push(point),
+            }
+        }
+        for event in newer.events {
+            if !self
+                .events
+                .iter()
+                .any(|known| known.timestamp == event.timestamp && known.kind == event.kind)
+            {
+                self.events.push(event);
+            }
+        }
+    }
+}
+
+/// Kind of corporate action attached to a price history.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryEventKind {
+    Dividend,
+    Split,
+}
+
+/// A dividend or split on a history timeline.
+///
+/// `value` is the per-share amount for dividends, in the history's currency,
+/// and the new-to-old share ratio for splits (`4.0` for a 4:1 split).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct HistoryEvent {
+    #[serde(serialize_with = "crate::output::tz::serialize_timestamp")]
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub kind: HistoryEventKind,
+    pub value: f64,
+}
+
+/// A kind of request a provider can answer, one per optional [`PriceProvider`] method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Prices,
+    History,
+    HistoryWindow,
+    FiatHistory,
+    Search,
+    Top,
+    Global,
+    Trending,
+    Markets,
+}
+
+impl Capability {
+    pub const ALL: [Self; 9] = [
+        Self::Prices,
+        Self::History,
+        Self::HistoryWindow,
+        Self::FiatHistory,
+        Self::Search,
+        Self::Top,
+        Self::Global,
+        Self::Trending,
+        Self::Markets,
+    ];
+
+    /// Column header in `--list-providers`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Prices => "Prices",
+            Self::History => "History",
+            Self::HistoryWindow => "Window",
+            Self::FiatHistory => "Fiat",
+            Self::Search => "Search",
+            Self::Top => "Top",
+            Self::Global => "Global",
+            Self::Trending => "Trending",
+            Self::Markets => "Markets",
+        }
+    }
+
+    /// What the capability covers, as used in logs and errors.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Prices => "price lookups",
+            Self::History => "chart history",
+            Self::HistoryWindow => "explicit chart date windows",
+            Self::FiatHistory => "fiat history",
+            Self::Search => "ticker search",
+            Self::Top => "top coins",
+            Self::Global => "global metrics",
+            Self::Trending => "trending coins",
+            Self::Markets => "exchange markets",
+        }
+    }
+}
+
+/// What a provider implements, as reported by [`PriceProvider::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct Capabilities {
+    pub prices: bool,
+    /// Price history counted back from today (`--chart`, `--stats`, `--since`).
+    pub history: bool,
+    /// History between explicit start and end dates.
+    pub history_window: bool,
+    /// Daily exchange rates between fiat currencies.
+    pub fiat_history: bool,
+    pub search: bool,
+    pub top: bool,
+    pub global: bool,
+    pub trending: bool,
+    pub markets: bool,
+}
+
+impl Capabilities {
+    /// What every provider supports.
+    pub const PRICES_ONLY: Self = Self {
+        prices: true,
+        history: false,
+        history_window: false,
+        fiat_history: false,
+        search: false,
+        top: false,
+        global: false,
+        trending: false,
+        markets: false,
+    };
+
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Prices => self.prices,
+            Capability::History => self.history,
+            Capability::HistoryWindow => self.history_window,
+            Capability::FiatHistory => self.fiat_history,
+            Capability::Search => self.search,
+            Capability::Top => self.top,
+            Capability::Global => self.global,
+            Capability::Trending => self.trending,
+            Capability::Markets => self.markets,
+        }
+    }
+}
+
+/// A provider's entry in `--list-providers`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+    pub capabilities: Capabilities,
+}
+
+impl ProviderInfo {
+    pub fn of(provider: &dyn PriceProvider) -> Self {
+        Self {
+            id: provider.id().to_string(),
+            name: provider.name().to_string(),
+            capabilities: provider.capabilities(),
+        }
+    }
 }
 
 /// Trait implemented by all price data providers.
@@ -78,9 +669,40 @@ pub trait PriceProvider: Send + Sync {
     /// Short identifier used in CLI flags.
     fn id(&self) -> &str;
 
+    /// Which optional methods this provider implements.
+    ///
+    /// Defaults to prices only; providers override it alongside the methods they add.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::PRICES_ONLY
+    }
+
+    /// Credit line the provider's terms ask to show next to its data, if any.
+    fn attribution(&self) -> Option<&str> {
+        None
+    }
+
     /// Fetch prices for the given coin symbols in the specified fiat currency.
     async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>>;
 
+    /// Fetch prices in several currencies at once, one row per symbol.
+    ///
+    /// Each row is quoted in `currencies[0]` and its `prices` lists every currency in
+    /// order. The default asks [`get_prices`](Self::get_prices) once per currency,
+    /// concurrently; providers with a multi-currency endpoint override it.
+    async fn get_prices_in(
+        &self,
+        symbols: &[String],
+        currencies: &[String],
+    ) -> Result<Vec<CoinPrice>> {
+        let batches = futures::future::join_all(
+            currencies
+                .iter()
+                .map(|currency| self.get_prices(symbols, currency)),
+        )
+        .await;
+        merge_currency_batches(currencies, batches)
+    }
+
     /// Fetch price history for the given coin symbols.
     ///
     /// Providers that do not support historical data may return a configuration error.
@@ -114,6 +736,23 @@ pub trait PriceProvider: Send + Sync {
         )))
     }
 
+    /// Fetch daily exchange rates from `base` into each of `targets` between `start` and
+    /// `end`, one series per target where each point is "1 base = rate target".
+    ///
+    /// Providers without fiat rate history return a configuration error.
+    async fn get_fiat_history(
+        &self,
+        _base: &str,
+        _targets: &[String],
+        _start: chrono::NaiveDate,
+        _end: chrono::NaiveDate,
+    ) -> Result<Vec<PriceHistory>> {
+        Err(Error::Config(format!(
+            "provider '{}' does not chart fiat exchange rates -- try --provider frankfurter",
+            self.id()
+        )))
+    }
+
     /// Search provider instruments by symbol/name query.
     ///
     /// Providers that do not support search may return a configuration error.
@@ -123,23 +762,683 @@ pub trait PriceProvider: Send + Sync {
             self.id()
         )))
     }
+
+    /// Fetch the `limit` largest coins by market cap, ranked first to last.
+    ///
+    /// Implementations cap `limit` at [`MAX_TOP_COINS`]. Providers without a listings
+    /// endpoint return a configuration error.
+    async fn get_top_coins(&self, _limit: usize, _currency: &str) -> Result<Vec<CoinPrice>> {
+        Err(Error::Config(format!(
+            "provider '{}' does not list top coins -- try --provider coingecko",
+            self.id()
+        )))
+    }
+
+    /// Fetch market-wide totals quoted in `currency`.
+    ///
+    /// Providers without a global endpoint return a configuration error.
+    async fn get_global_metrics(&self, _currency: &str) -> Result<GlobalMetrics> {
+        Err(Error::Config(format!(
+            "provider '{}' does not report global market metrics -- try --provider coingecko",
+            self.id()
+        )))
+    }
+
+    /// Fetch the provider's most-searched coins, most popular first, priced in `currency`.
+    ///
+    /// Providers without a trending endpoint return a configuration error.
+    async fn get_trending(&self, _currency: &str) -> Result<Vec<TrendingCoin>> {
+        Err(Error::Config(format!(
+            "provider '{}' does not list trending coins -- try --provider coingecko",
+            self.id()
+        )))
+    }
+
+    /// Fetch up to `limit` markets `symbol` trades on, highest volume first, with one
+    /// row per exchange and pair.
+    ///
+    /// Providers without exchange data return a configuration error.
+    async fn get_markets(&self, _symbol: &str, _limit: usize) -> Result<Vec<MarketTicker>> {
+        Err(Error::Config(format!(
+            "provider '{}' does not list exchange markets -- try --provider coingecko",
+            self.id()
+        )))
+    }
+}
+
+/// Combine one `get_prices` result per currency into multi-currency rows.
+///
+/// A row's base is its quote in the first currency that has one. A quote only fills
+/// a currency's slot when it is actually in that currency, so a provider that answers
+/// in its listing currency leaves the other slots empty for conversion. Currencies
+/// without results are skipped; the first other error, or else the first currency's
+/// no-results error, is returned if nothing resolved.
+pub fn merge_currency_batches(
+    currencies: &[String],
+    batches: Vec<Result<Vec<CoinPrice>>>,
+) -> Result<Vec<CoinPrice>> {
+    let mut rows: Vec<CoinPrice> = Vec::new();
+    let mut first_err = None;
+    let mut no_results = None;
+    let mut quoted = Vec::new();
+    for (slot, result) in batches.into_iter().enumerate() {
+        match result {
+            Ok(prices) => {
+                for price in prices {
+                    quoted.push((
+                        slot,
+                        price.symbol.to_uppercase(),
+                        price.currency.clone(),
+                        price.price,
+                    ));
+                    if !rows
+                        .iter()
+                        .any(|row| row.symbol.eq_ignore_ascii_case(&price.symbol))
+                    {
+                        rows.push(price);
+                    }
+                }
+            }
+            Err(err @ Error::NoResults { .. }) => {
+                no_results.get_or_insert(err);
+            }
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+    if rows.is_empty() {
+        return Err(first_err
+            .or(no_results)
+            .unwrap_or_else(|| Error::no_results_from("")));
+    }
+
+    for row in &mut rows {
+        row.prices = currencies
+            .iter()
+            .enumerate()
+            .map(|(slot, currency)| {
+                let price = quoted
+                    .iter()
+                    .find(|(quoted_slot, symbol, quoted_currency, _)| {
+                        *quoted_slot == slot
+                            && row.symbol.eq_ignore_ascii_case(symbol)
+                            && quoted_currency.eq_ignore_ascii_case(currency)
+                    })
+                    .map(|(_, _, _, price)| *price);
+                (currency.to_uppercase(), price)
+            })
+            .collect();
+    }
+    Ok(rows)
+}
+
+/// Largest `--top` listing a provider is asked for.
+pub const MAX_TOP_COINS: usize = 250;
+
+/// Ids of every provider built by [`available_providers`], in registration order.
+pub const PROVIDER_IDS: &[&str] = &["coingecko", "stooq", "yahoo", "cmc", "frankfurter"];
+
+/// Flag `histories` from a daily-only provider when `interval` resolved to hourly for
+/// `range_days`, so the chart can say why it has so few points.
+pub(crate) fn note_daily_only(
+    histories: &mut [PriceHistory],
+    interval: HistoryInterval,
+    range_days: u32,
+) {
+    if interval.resolve(range_days) == ResolvedInterval::Hourly {
+        for history in histories {
+            history.granularity = Some(ResolvedInterval::Daily);
+        }
+    }
+}
+
+/// Reject `currency` unless `supported` lists it, suggesting the closest listed code.
+pub(crate) fn check_currency(provider: &str, currency: &str, supported: &[String]) -> Result<()> {
+    if supported
+        .iter()
+        .any(|code| code.eq_ignore_ascii_case(currency))
+    {
+        return Ok(());
+    }
+    let code = currency.to_uppercase();
+    let message = match crate::calc::closest_match(&code, supported.iter().map(String::as_str)) {
+        Some(closest) => format!(
+            "currency '{}' not supported by {}; did you mean {}?",
+            code,
+            provider,
+            closest.to_uppercase()
+        ),
+        None => format!("currency '{}' not supported by {}", code, provider),
+    };
+    Err(Error::Config(message))
+}
+
+/// Every built-in provider, configured once and shared by library callers.
+///
+/// Build one with [`ProviderRegistry::builder`]:
+///
+/// ```
+/// use pricr::ProviderRegistry;
+///
+/// let registry = ProviderRegistry::builder()
+///     .currency("EUR")
+///     .base_url("coingecko", "http://localhost:8080/api/v3")
+///     .build()?;
+/// assert_eq!(registry.currency(), "eur");
+/// assert!(registry.get("coingecko").is_some());
+/// # Ok::<(), pricr::Error>(())
+/// ```
+///
+/// Prices then come from any provider by id, in the registry's currency:
+///
+/// ```no_run
+/// # async fn demo() -> pricr::Result<()> {
+/// use pricr::ProviderRegistry;
+///
+/// let registry = ProviderRegistry::builder()
+///     .api_key("cmc", std::env::var("CMC_API_KEY").unwrap_or_default())
+///     .timeout(std::time::Duration::from_secs(5))
+///     .build()?;
+/// let symbols = ["btc".to_string(), "eth".to_string()];
+/// for price in registry.get_prices("coingecko", &symbols).await? {
+///     println!("{} {:.2} {}", price.symbol, price.price, price.currency);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn PriceProvider>>,
+    currency: String,
+}
+
+impl ProviderRegistry {
+    /// Start configuring a registry with default settings and [`config::DEFAULT_CURRENCY`].
+    pub fn builder() -> ProviderRegistryBuilder {
+        ProviderRegistryBuilder::default()
+    }
+
+    /// Providers built from `settings` and `aliases`, sharing `client`. CoinMarketCap
+    /// adds the credits it uses to the ledger at `credit_log`, if any.
+    fn with_client(
+        settings: &ProviderSettings,
+        aliases: &SymbolAliases,
+        client: &http::HttpClient,
+        credit_log: Option<&std::path::Path>,
+        currency: String,
+    ) -> Self {
+        let providers: Vec<Box<dyn PriceProvider>> = vec![
+            Box::new(coingecko_from_settings(settings, client)),
+            Box::new(
+                stooq::Stooq::with_client(
+                    rate_limited_client(settings, "stooq", None, client),
+                    settings.base_url("stooq", stooq::BASE_URL),
+                )
+                .with_market(
+                    settings
+                        .default_market("stooq")
+                        .unwrap_or(stooq::DEFAULT_MARKET),
+                ),
+            ),
+            Box::new(yahoo::YahooFinance::with_client(
+                rate_limited_client(settings, "yahoo", None, client),
+                settings.base_url("yahoo", yahoo::BASE_URL),
+            )),
+            Box::new(cmc_from_settings(settings, client, credit_log)),
+            Box::new(frankfurter::Frankfurter::with_client(
+                rate_limited_client(settings, "frankfurter", None, client),
+                settings.base_url("frankfurter", frankfurter::BASE_URL),
+            )),
+        ];
+
+        let aliases = effective_aliases(&providers, aliases);
+        let providers = if aliases.is_empty() {
+            providers
+        } else {
+            providers
+                .into_iter()
+                .map(|inner| {
+                    Box::new(aliased::AliasedProvider::new(inner, aliases.clone()))
+                        as Box<dyn PriceProvider>
+                })
+                .collect()
+        };
+        Self {
+            providers,
+            currency,
+        }
+    }
+
+    /// Lower-cased currency prices are quoted in by [`get_prices`](Self::get_prices).
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Every provider, in [`PROVIDER_IDS`] order.
+    pub fn providers(&self) -> &[Box<dyn PriceProvider>] {
+        &self.providers
+    }
+
+    /// The provider with id `provider_id`, matched case-insensitively.
+    pub fn get(&self, provider_id: &str) -> Option<&dyn PriceProvider> {
+        self.providers
+            .iter()
+            .find(|prov| prov.id().eq_ignore_ascii_case(provider_id))
+            .map(|prov| prov.as_ref())
+    }
+
+    /// Fetch `symbols` from `provider_id` in the registry's currency.
+    ///
+    /// An unknown provider id is a configuration error.
+    pub async fn get_prices(
+        &self,
+        provider_id: &str,
+        symbols: &[String],
+    ) -> Result<Vec<CoinPrice>> {
+        let prov = self.get(provider_id).ok_or_else(|| {
+            Error::Config(format!(
+                "unknown provider '{}' -- expected one of: {}",
+                provider_id,
+                PROVIDER_IDS.join(", ")
+            ))
+        })?;
+        prov.get_prices(symbols, &self.currency).await
+    }
+
+    /// Take the providers out of the registry.
+    pub fn into_providers(self) -> Vec<Box<dyn PriceProvider>> {
+        self.providers
+    }
+}
+
+/// Settings for a [`ProviderRegistry`]; nothing is checked until [`build`](Self::build).
+#[derive(Debug, Clone, Default)]
+pub struct ProviderRegistryBuilder {
+    currency: Option<String>,
+    settings: ProviderSettings,
+    aliases: SymbolAliases,
+    http: config::HttpConfig,
+    cache_dir: Option<std::path::PathBuf>,
+    credit_log: Option<std::path::PathBuf>,
+    cancel: Option<tokio_util::sync::CancellationToken>,
+}
+
+impl ProviderRegistryBuilder {
+    /// Currency [`ProviderRegistry::get_prices`] quotes in, such as `usd` or `EUR`.
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    /// Start from `[provider.<id>]` tables, as loaded by [`config::load`]; later
+    /// [`api_key`](Self::api_key) and [`base_url`](Self::base_url) calls override them.
+    pub fn settings(mut self, settings: ProviderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// API key for `provider_id`, such as `coingecko` or `cmc`.
+    pub fn api_key(mut self, provider_id: &str, api_key: impl Into<String>) -> Self {
+        self.settings.set_api_key(provider_id, api_key.into());
+        self
+    }
+
+    /// Send `provider_id`'s requests to `base_url` instead of its production API.
+    pub fn base_url(mut self, provider_id: &str, base_url: impl Into<String>) -> Self {
+        self.settings.set_base_url(provider_id, base_url.into());
+        self
+    }
+
+    /// Provider-specific ids for user symbols, as in the `[aliases]` table.
+    pub fn aliases(mut self, aliases: SymbolAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// HTTP client settings, as in the `[http]` table.
+    pub fn http(mut self, http: config::HttpConfig) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Total per-request timeout, rounded down to whole seconds and at least one.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout_secs = Some(timeout.as_secs().max(1));
+        self
+    }
+
+    /// Cache this registry's responses under `dir` instead of `$XDG_CACHE_HOME/pricr`.
+    /// Other registries keep their own cache directory.
+    pub fn cache_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Add the API credits CoinMarketCap reports to the monthly ledger at `path`, as
+    /// read by `pricr --quota`. Off unless set.
+    pub fn credit_log(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.credit_log = Some(path.into());
+        self
+    }
+
+    /// Abort every provider's in-flight requests with [`Error::Interrupted`] once
+    /// `token` is cancelled, for example from a Ctrl-C handler.
+    pub fn cancellation(mut self, token: tokio_util::sync::CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Build the HTTP client and every provider.
+    ///
+    /// An empty currency, a base URL that is not an `http(s)` URL, or an HTTP client
+    /// that cannot be built (such as an invalid proxy) is a configuration error.
+    pub fn build(self) -> Result<ProviderRegistry> {
+        let currency = self
+            .currency
+            .as_deref()
+            .unwrap_or(config::DEFAULT_CURRENCY)
+            .trim()
+            .to_lowercase();
+        if currency.is_empty() {
+            return Err(Error::Config("currency must not be empty".into()));
+        }
+        let mut settings = self.settings;
+        settings.validate().map_err(Error::Config)?;
+        let mut client = http::build_client(&self.http)?;
+        if let Some(token) = self.cancel {
+            client = client.with_cancellation(token);
+        }
+        if let Some(dir) = self.cache_dir {
+            client = client.with_cache_dir(dir);
+        }
+        Ok(ProviderRegistry::with_client(
+            &settings,
+            &self.aliases,
+            &client,
+            self.credit_log.as_deref(),
+            currency,
+        ))
+    }
 }
 
 /// Build the list of available providers based on configuration.
-pub fn available_providers(api_key: Option<String>) -> Vec<Box<dyn PriceProvider>> {
-    let cmc_key = api_key.or_else(|| std::env::var("COINMARKETCAP_API_KEY").ok());
+///
+/// All providers share `client`, so one invocation sets up a single connection pool
+/// and TLS config. Base URLs and API keys come from `[provider.<id>]` settings.
+/// Providers with entries in `aliases` resolve those symbols through the alias table first.
+/// CoinMarketCap logs the credits it uses to `credit_log` when one is given.
+pub fn available_providers(
+    settings: &ProviderSettings,
+    aliases: &SymbolAliases,
+    client: &http::HttpClient,
+    credit_log: Option<&std::path::Path>,
+) -> Vec<Box<dyn PriceProvider>> {
+    ProviderRegistry::with_client(
+        settings,
+        aliases,
+        client,
+        credit_log,
+        config::DEFAULT_CURRENCY.to_string(),
+    )
+    .into_providers()
+}
 
-    let mut providers: Vec<Box<dyn PriceProvider>> = vec![
-        Box::new(coingecko::CoinGecko::new()),
-        Box::new(stooq::Stooq::new()),
-        Box::new(yahoo::YahooFinance::new()),
-    ];
-    match cmc_key {
-        Some(key) => providers.push(Box::new(coinmarketcap::CoinMarketCap::new(key))),
-        None => providers.push(Box::new(coinmarketcap::CoinMarketCap::without_key())),
+/// CoinMarketCap with its key and base URL from settings, logging the API credits it
+/// uses to `credit_log` when set.
+fn cmc_from_settings(
+    settings: &ProviderSettings,
+    client: &http::HttpClient,
+    credit_log: Option<&std::path::Path>,
+) -> coinmarketcap::CoinMarketCap {
+    let cmc = coinmarketcap::CoinMarketCap::with_client(
+        rate_limited_client(settings, "cmc", None, client),
+        settings.api_key("cmc").map(str::to_string),
+        settings.base_url("cmc", coinmarketcap::BASE_URL),
+    );
+    match credit_log {
+        Some(path) => cmc.with_credit_log(path.to_path_buf()),
+        None => cmc,
     }
+}
+
+/// Wrap `prov` so prices quoted in a different fiat currency are converted through `rates`.
+///
+/// Converted rows carry `converted = true`, the applied `fx_rate`, and a provider label
+/// such as `Yahoo Finance→ECB`. History and search calls pass through unchanged.
+pub fn with_currency_fallback(
+    prov: Box<dyn PriceProvider>,
+    rates: Arc<fiat::FiatRateSource>,
+) -> Box<dyn PriceProvider> {
+    Box::new(converted::CurrencyFallback::new(prov, rates))
+}
+
+/// Fill in each row's `attribution` from the provider that quoted it.
+///
+/// Rows are matched by provider name; a converted row such as `CoinGecko→ECB` credits
+/// the provider before the arrow.
+pub fn attribute(prices: &mut [CoinPrice], providers: &[Box<dyn PriceProvider>]) {
+    for price in prices {
+        price.attribution = source_index(providers, price)
+            .and_then(|idx| providers[idx].attribution())
+            .map(str::to_string);
+    }
+}
+
+/// Index of the provider that answered `price`, read from its `provider` name and
+/// ignoring any `→` conversion note.
+pub fn source_index(providers: &[Box<dyn PriceProvider>], price: &CoinPrice) -> Option<usize> {
+    let source = price.provider.split('\u{2192}').next().unwrap_or_default();
+    providers.iter().position(|prov| prov.name() == source)
+}
+
+/// Each distinct `attribution` among `prices`, in first-seen order.
+pub fn attributions(prices: &[CoinPrice]) -> Vec<&str> {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in prices
+        .iter()
+        .filter_map(|price| price.attribution.as_deref())
+    {
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// Client for `provider_id` limited to its `rate_limit_per_min`, or `default_per_min`
+/// when none is configured. A limit of `0` means unlimited.
+pub fn rate_limited_client(
+    settings: &ProviderSettings,
+    provider_id: &str,
+    default_per_min: Option<u32>,
+    client: &http::HttpClient,
+) -> http::HttpClient {
+    let cfg = settings.get(provider_id);
+    let per_min = cfg
+        .and_then(|cfg| cfg.rate_limit_per_min)
+        .or(default_per_min)
+        .filter(|&per_min| per_min > 0);
+    let Some(per_min) = per_min else {
+        return client.clone();
+    };
+    let mut limit = http::RateLimit::per_minute(per_min);
+    if let Some(secs) = cfg.and_then(|cfg| cfg.rate_limit_max_wait_secs) {
+        limit.max_wait = Duration::from_secs(secs);
+    }
+    debug!(
+        provider = provider_id,
+        per_min, "client-side rate limit enabled"
+    );
+    client.with_rate_limit(limit)
+}
 
+/// Build the CoinGecko provider with its `[provider.coingecko]` base URL, key, tier, and
+/// rate limit. Keyless access defaults to [`coingecko::KEYLESS_RATE_LIMIT_PER_MIN`].
+pub fn coingecko_from_settings(
+    settings: &ProviderSettings,
+    client: &http::HttpClient,
+) -> coingecko::CoinGecko {
+    let default_limit = settings
+        .api_key("coingecko")
+        .is_none()
+        .then_some(coingecko::KEYLESS_RATE_LIMIT_PER_MIN);
+    let provider = coingecko::CoinGecko::with_client(
+        rate_limited_client(settings, "coingecko", default_limit, client),
+        settings.base_url("coingecko", coingecko::BASE_URL),
+    );
+    match settings.get("coingecko") {
+        Some(config::ProviderConfig {
+            api_key: Some(key),
+            tier,
+            ..
+        }) => provider.with_api_key(key.clone(), tier.unwrap_or_default()),
+        _ => provider,
+    }
+}
+
+/// Alias table restricted to the given providers; entries for other ids are dropped.
+pub fn effective_aliases(
+    providers: &[Box<dyn PriceProvider>],
+    aliases: &SymbolAliases,
+) -> SymbolAliases {
+    let known_ids: Vec<&str> = providers.iter().map(|p| p.id()).collect();
+    let mut aliases = aliases.clone();
+    aliases.retain_providers(&known_ids);
+    aliases
+}
+
+/// Run `fetch` for every item with at most `concurrency` requests in flight.
+///
+/// Results are returned in input order, and a failing item does not cancel the others.
+pub async fn fetch_all<I, F, Fut>(items: I, concurrency: usize, mut fetch: F) -> Vec<Fut::Output>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future,
+{
+    let mut results: Vec<(usize, Fut::Output)> = stream::iter(items.into_iter().enumerate())
+        .map(|(idx, item)| {
+            let fut = fetch(item);
+            async move { (idx, fut.await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, output)| output).collect()
+}
+
+/// Split `items` into requests of at most `chunk_size` and run them through [`fetch_all`],
+/// merging the rows in input order.
+///
+/// A failed chunk is logged and leaves its items out, so they show up as missing; the
+/// call only fails when every chunk does, with the first chunk's error.
+pub async fn fetch_chunks<'a, T, R, F, Fut>(
+    provider: &str,
+    items: &'a [T],
+    chunk_size: usize,
+    concurrency: usize,
+    fetch: F,
+) -> Result<Vec<R>>
+where
+    T: std::fmt::Display,
+    F: FnMut(&'a [T]) -> Fut,
+    Fut: Future<Output = Result<Vec<R>>>,
+{
+    let chunks: Vec<&'a [T]> = items.chunks(chunk_size.max(1)).collect();
+    let results = fetch_all(chunks.clone(), concurrency, fetch).await;
+
+    let mut rows = Vec::new();
+    let mut first_error = None;
+    for (chunk, result) in chunks.iter().zip(results) {
+        match result {
+            Ok(chunk_rows) => rows.extend(chunk_rows),
+            Err(err) => {
+                if chunks.len() > 1 {
+                    let names: Vec<String> = chunk.iter().map(ToString::to_string).collect();
+                    warn!(
+                        provider,
+                        items = %names.join(","),
+                        error = %err,
+                        "chunked request failed; continuing with the other chunks"
+                    );
+                }
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) if rows.is_empty() => Err(err),
+        _ => Ok(rows),
+    }
+}
+
+/// Trim symbols and drop blanks and case-insensitive repeats, keeping first-seen order.
+///
+/// Each dropped repeat is logged once at info level.
+pub fn normalize_symbols(symbols: &[String]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut normalized = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let trimmed = symbol.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if seen.insert(trimmed.to_uppercase()) {
+            normalized.push(trimmed.to_string());
+        } else {
+            info!(symbol = %trimmed, "dropping duplicate symbol");
+        }
+    }
+    normalized
+}
+
+/// Ask providers that support it to attach dividend and split events to histories.
+pub fn set_history_events(enabled: bool) {
+    HISTORY_EVENTS.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn history_events_enabled() -> bool {
+    HISTORY_EVENTS.load(Ordering::Relaxed)
+}
+
+/// Enable or disable the shared on-disk response cache for this process.
+pub fn set_cache_enabled(enabled: bool) {
+    cache::set_enabled(enabled);
+}
+
+/// Ids of the providers that support `capability`, in registration order.
+pub fn providers_supporting(
+    providers: &[Box<dyn PriceProvider>],
+    capability: Capability,
+) -> Vec<&str> {
     providers
+        .iter()
+        .filter(|p| p.capabilities().supports(capability))
+        .map(|p| p.id())
+        .collect()
+}
+
+/// Configuration error for asking `provider` for something it does not implement,
+/// suggesting the providers that do.
+pub fn unsupported(
+    providers: &[Box<dyn PriceProvider>],
+    provider: &dyn PriceProvider,
+    capability: Capability,
+) -> Error {
+    let mut message = format!(
+        "provider '{}' does not support {}",
+        provider.id(),
+        capability.describe()
+    );
+    match providers_supporting(providers, capability).as_slice() {
+        [] => {}
+        [only] => message.push_str(&format!(" -- try {}", only)),
+        [rest @ .., last] => {
+            message.push_str(&format!(" -- try {} or {}", rest.join(", "), last));
+        }
+    }
+    Error::Config(message)
 }
 
 /// Look up a provider index by its short id.
@@ -148,3 +1447,411 @@ pub fn get_provider(providers: &[Box<dyn PriceProvider>], id: &str) -> Option<us
         .iter()
         .position(|p| p.id().eq_ignore_ascii_case(id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_ids_match_available_providers() {
+        let client = http::build_client(&config::HttpConfig::default()).unwrap();
+        let providers = available_providers(
+            &ProviderSettings::default(),
+            &SymbolAliases::default(),
+            &client,
+            None,
+        );
+        let ids: Vec<&str> = providers.iter().map(|p| p.id()).collect();
+        assert_eq!(ids, PROVIDER_IDS);
+    }
+
+    /// A capability a provider does not claim must fall through to the trait default.
+    async fn falls_back_to_default(provider: &dyn PriceProvider, capability: Capability) -> bool {
+        let now = chrono::Utc::now();
+        let today = now.date_naive();
+        let usd = "usd";
+        let err = match capability {
+            Capability::Prices => return false,
+            Capability::History => provider
+                .get_price_history(&[], usd, 1, HistoryInterval::Daily)
+                .await
+                .err(),
+            Capability::HistoryWindow => provider
+                .get_price_history_window(&[], usd, None, now, HistoryInterval::Daily)
+                .await
+                .err(),
+            Capability::FiatHistory => provider
+                .get_fiat_history(usd, &[], today, today)
+                .await
+                .err(),
+            Capability::Search => provider.search_tickers("btc", 1).await.err(),
+            Capability::Top => provider.get_top_coins(1, usd).await.err(),
+            Capability::Global => provider.get_global_metrics(usd).await.err(),
+            Capability::Trending => provider.get_trending(usd).await.err(),
+            Capability::Markets => provider.get_markets("btc", 1).await.err(),
+        };
+        matches!(err, Some(Error::Config(message)) if message.contains(&format!("provider '{}' does not", provider.id())))
+    }
+
+    #[tokio::test]
+    async fn unclaimed_capabilities_use_the_trait_defaults() {
+        let client = http::build_client(&config::HttpConfig::default()).unwrap();
+        let providers = available_providers(
+            &ProviderSettings::default(),
+            &SymbolAliases::default(),
+            &client,
+            None,
+        );
+        for provider in &providers {
+            let capabilities = provider.capabilities();
+            assert!(capabilities.prices, "{}", provider.id());
+            for capability in Capability::ALL {
+                if !capabilities.supports(capability) {
+                    assert!(
+                        falls_back_to_default(provider.as_ref(), capability).await,
+                        "{} claims no {:?} but overrides it",
+                        provider.id(),
+                        capability
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_suggests_the_providers_that_have_it() {
+        let client = http::build_client(&config::HttpConfig::default()).unwrap();
+        let providers = available_providers(
+            &ProviderSettings::default(),
+            &SymbolAliases::default(),
+            &client,
+            None,
+        );
+        let stooq = providers[get_provider(&providers, "stooq").unwrap()].as_ref();
+        assert_eq!(
+            unsupported(&providers, stooq, Capability::Top).to_string(),
+            "Configuration error: provider 'stooq' does not support top coins -- try coingecko or cmc"
+        );
+        assert_eq!(
+            unsupported(&providers, stooq, Capability::FiatHistory).to_string(),
+            "Configuration error: provider 'stooq' does not support fiat history -- try frankfurter"
+        );
+        assert_eq!(
+            providers_supporting(&providers, Capability::Search),
+            PROVIDER_IDS
+        );
+    }
+
+    #[test]
+    fn chart_presets_and_samplings_parse_their_own_labels() {
+        for range in ChartRange::ALL {
+            assert_eq!(range.label().parse(), Ok(range));
+            assert_eq!(range.label().to_lowercase().parse(), Ok(range));
+        }
+        for interval in HistoryInterval::ALL {
+            assert_eq!(interval.as_str().parse(), Ok(interval));
+            assert_eq!(interval.as_str().to_uppercase().parse(), Ok(interval));
+        }
+        assert_eq!(" 1y ".parse(), Ok(ChartRange::OneYear));
+        assert!("2Y".parse::<ChartRange>().is_err());
+        assert_eq!(
+            "minutely".parse::<HistoryInterval>(),
+            Err("expected one of auto, hourly, 4h, daily, weekly, monthly".to_string())
+        );
+    }
+
+    #[test]
+    fn normalize_symbols_trims_and_dedups_in_order() {
+        let symbols: Vec<String> = [" btc", "BTC", "eth ", "", "Btc", "ETH", "sol"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(normalize_symbols(&symbols), ["btc", "eth", "sol"]);
+    }
+
+    fn price(symbol: &str) -> CoinPrice {
+        CoinPrice {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            price: 1.0,
+            change_24h: None,
+            change_7d: None,
+            change_30d: None,
+            market_cap: None,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::Utc::now(),
+            quote_time_estimated: false,
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        }
+    }
+
+    #[test]
+    fn symbol_answers_accepts_added_market_suffixes_only() {
+        assert!(symbol_answers("aapl", "AAPL"));
+        assert!(symbol_answers("aapl", "AAPL.US"));
+        assert!(symbol_answers("sap.de", "SAP.DE"));
+        assert!(!symbol_answers("sap.de", "SAP.US"));
+        assert!(!symbol_answers("aap", "AAPL.US"));
+        assert!(!symbol_answers("aapl", "AAPL-USD"));
+        assert!(symbol_answers("gold", "GC=F"));
+        assert!(symbol_answers("index:spx", "^SPX"));
+        assert!(symbol_answers(".spx", "^GSPC"));
+        assert!(!symbol_answers("^spx", "^DJI"));
+    }
+
+    #[test]
+    fn check_currency_suggests_the_closest_supported_code() {
+        let supported: Vec<String> = ["usd", "eur", "btc"].map(String::from).to_vec();
+        assert!(check_currency("coingecko", "EUR", &supported).is_ok());
+        match check_currency("coingecko", "usdd", &supported) {
+            Err(Error::Config(message)) => assert_eq!(
+                message,
+                "currency 'USDD' not supported by coingecko; did you mean USD?"
+            ),
+            other => panic!("expected config error, got {:?}", other),
+        }
+        match check_currency("coingecko", "xyz", &supported) {
+            Err(Error::Config(message)) => {
+                assert_eq!(message, "currency 'XYZ' not supported by coingecko")
+            }
+            other => panic!("expected config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn merge_currency_batches_fills_slots_by_quoted_currency() {
+        let quote = |symbol: &str, price: f64, currency: &str| CoinPrice {
+            symbol: symbol.to_string(),
+            price,
+            currency: currency.to_string(),
+            ..Default::default()
+        };
+        let currencies = vec!["USD".to_string(), "EUR".to_string(), "JPY".to_string()];
+        let batches = vec![
+            Ok(vec![
+                quote("BTC", 60000.0, "USD"),
+                quote("AAPL", 200.0, "USD"),
+            ]),
+            // A listing-currency provider answers in USD for every request.
+            Ok(vec![
+                quote("BTC", 55000.0, "EUR"),
+                quote("AAPL", 200.0, "USD"),
+            ]),
+            Err(Error::no_results("Yahoo Finance", &[], "JPY")),
+        ];
+
+        let rows = merge_currency_batches(&currencies, batches).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].currency, "USD");
+        assert_eq!(
+            rows[0].prices,
+            vec![
+                ("USD".to_string(), Some(60000.0)),
+                ("EUR".to_string(), Some(55000.0)),
+                ("JPY".to_string(), None),
+            ]
+        );
+        assert_eq!(rows[1].prices[1], ("EUR".to_string(), None));
+
+        let failed = merge_currency_batches(
+            &currencies,
+            vec![
+                Err(Error::no_results("Stooq", &[], "USD")),
+                Err(Error::Config("no key".into())),
+            ],
+        );
+        assert!(matches!(failed, Err(Error::Config(_))));
+
+        let empty = merge_currency_batches(
+            &currencies,
+            vec![Err(Error::no_results("Stooq", &[], "USD"))],
+        );
+        assert!(matches!(empty, Err(Error::NoResults { ref provider, .. }) if provider == "Stooq"));
+    }
+
+    #[test]
+    fn resolve_picks_hourly_only_for_short_auto_ranges() {
+        use HistoryInterval::*;
+        let cases = [
+            (Auto, 1, ResolvedInterval::Hourly),
+            (Auto, AUTO_HOURLY_MAX_DAYS, ResolvedInterval::Hourly),
+            (Auto, AUTO_HOURLY_MAX_DAYS + 1, ResolvedInterval::Daily),
+            (Auto, u32::MAX, ResolvedInterval::Daily),
+            (Hourly, 365, ResolvedInterval::Hourly),
+            (FourHour, 365, ResolvedInterval::Hourly),
+            (Daily, 1, ResolvedInterval::Daily),
+            (Weekly, 1, ResolvedInterval::Daily),
+            (Monthly, 1, ResolvedInterval::Daily),
+        ];
+        for (interval, days, expected) in cases {
+            assert_eq!(
+                interval.resolve(days),
+                expected,
+                "{interval:?} over {days} days"
+            );
+        }
+
+        let end = chrono::Utc::now();
+        assert_eq!(range_days(Some(end - chrono::Duration::hours(3)), end), 1);
+        assert_eq!(range_days(Some(end - chrono::Duration::days(30)), end), 30);
+        assert_eq!(range_days(None, end), u32::MAX);
+    }
+
+    #[test]
+    fn append_newer_skips_old_points_and_replaces_the_last() {
+        let at = |minute| {
+            chrono::DateTime::parse_from_rfc3339(&format!("2026-03-02T10:{minute:02}:00Z"))
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let series = |points: &[(u32, f64)]| PriceHistory {
+            symbol: "BTC".to_string(),
+            name: "Bitcoin".to_string(),
+            currency: "USD".to_string(),
+            provider: "CoinGecko".to_string(),
+            points: points
+                .iter()
+                .map(|&(minute, price)| PricePoint {
+                    timestamp: at(minute),
+                    price,
+                    filled: false,
+                })
+                .collect(),
+            events: Vec::new(),
+            granularity: None,
+        };
+
+        let mut history = series(&[(0, 1.0), (5, 2.0)]);
+        history.append_newer(series(&[(0, 9.0), (5, 2.5), (10, 3.0)]));
+
+        let prices: Vec<f64> = history.points.iter().map(|p| p.price).collect();
+        assert_eq!(prices, vec![1.0, 2.5, 3.0]);
+    }
+
+    #[test]
+    fn price_batch_reports_unresolved_symbols() {
+        let symbols = vec!["btc".to_string(), "typoxyz".to_string(), "Eth".to_string()];
+        let batch = PriceBatch::from_requested(&symbols, vec![price("BTC"), price("ETH")]);
+
+        assert_eq!(batch.prices.len(), 2);
+        assert_eq!(batch.missing, vec!["TYPOXYZ"]);
+    }
+
+    #[tokio::test]
+    async fn fetch_all_keeps_input_order_and_bounds_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let delays = [40_u64, 5, 20, 1, 10];
+
+        let results = fetch_all(delays, 2, |delay| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if delay == 20 {
+                    Err(Error::no_results_from("test"))
+                } else {
+                    Ok(delay)
+                }
+            }
+        })
+        .await;
+
+        let values: Vec<Option<u64>> = results.iter().map(|r| r.as_ref().ok().copied()).collect();
+        assert_eq!(values, vec![Some(40), Some(5), None, Some(1), Some(10)]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn fetch_chunks_merges_in_order_and_skips_failed_chunks() {
+        let items: Vec<u32> = (1..=7).collect();
+        let chunk = |fail: u32| {
+            move |chunk: &[u32]| {
+                let chunk = chunk.to_vec();
+                async move {
+                    if chunk.contains(&fail) {
+                        Err(Error::no_results_from("test"))
+                    } else {
+                        Ok(chunk.iter().map(|n| n * 10).collect::<Vec<_>>())
+                    }
+                }
+            }
+        };
+
+        let all = fetch_chunks("test", &items, 3, 2, chunk(0)).await.unwrap();
+        assert_eq!(all, vec![10, 20, 30, 40, 50, 60, 70]);
+
+        let partial = fetch_chunks("test", &items, 3, 2, chunk(5)).await.unwrap();
+        assert_eq!(partial, vec![10, 20, 30, 70]);
+
+        assert!(
+            fetch_chunks("test", &items[..2], 3, 2, chunk(1))
+                .await
+                .is_err()
+        );
+        assert!(
+            fetch_chunks("test", &[] as &[u32], 3, 2, chunk(0))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn attribute_credits_each_providing_source_once() {
+        let providers: Vec<Box<dyn PriceProvider>> = vec![
+            Box::new(coingecko::CoinGecko::new()),
+            Box::new(yahoo::YahooFinance::new()),
+            Box::new(coinmarketcap::CoinMarketCap::new("key".into())),
+        ];
+        let mut prices = vec![
+            price("BTC"),
+            CoinPrice {
+                provider: "Yahoo Finance".into(),
+                ..price("AAPL")
+            },
+            CoinPrice {
+                provider: "CoinMarketCap\u{2192}ECB".into(),
+                ..price("SOL")
+            },
+            price("ETH"),
+        ];
+
+        attribute(&mut prices, &providers);
+
+        assert_eq!(
+            prices[0].attribution.as_deref(),
+            Some(coingecko::ATTRIBUTION)
+        );
+        assert_eq!(prices[1].attribution, None);
+        assert_eq!(
+            attributions(&prices),
+            vec![coingecko::ATTRIBUTION, coinmarketcap::ATTRIBUTION]
+        );
+    }
+}
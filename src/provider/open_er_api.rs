@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::cache;
+use super::http::HttpClient;
+use crate::error::{Error, Result};
+
+pub const BASE_URL: &str = "https://open.er-api.com/v6";
+pub const PROVIDER_NAME: &str = "ExchangeRate-API";
+/// The open endpoint refreshes once a day, so an hour is plenty.
+const LATEST_RATES_CACHE_TTL_SECS: i64 = 60 * 60;
+
+/// Keyless ExchangeRate-API endpoint, used for fiat codes the ECB does not publish.
+pub struct OpenErApi {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl OpenErApi {
+    /// Create an ExchangeRate-API source that sends requests through `client`.
+    pub fn with_client(client: HttpClient, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Fetch rates for `to` expressed as "1 source = rate target".
+    ///
+    /// Targets the API does not know are left out of the returned map.
+    #[tracing::instrument(skip(self), fields(provider = "exchangerate-api"))]
+    pub async fn get_rates(&self, from: &str, to: &[String]) -> Result<HashMap<String, f64>> {
+        let from_upper = from.to_uppercase();
+        let url = format!("{}/latest/{}", self.base_url, from_upper);
+        let cache_key = format!("latest:{}:{}", self.base_url, from_upper);
+
+        debug!(url = %url, "fetching forex rates from ExchangeRate-API");
+
+        let body: OpenErResponse = match cache::read_json(
+            self.client.cache_dir(),
+            "open-er-api",
+            &cache_key,
+            LATEST_RATES_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached) => {
+                debug!(from = %from_upper, "using cached ExchangeRate-API rates");
+                cached
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self
+                    .client
+                    .send(PROVIDER_NAME, self.client.get(PROVIDER_NAME, &url)?)
+                    .await?;
+                let status = resp.status();
+                let text = self.client.text(PROVIDER_NAME, resp).await?;
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        PROVIDER_NAME,
+                        status,
+                        format!("ExchangeRate-API returned {}: {}", status, text),
+                    ));
+                }
+
+                let fetched: OpenErResponse = serde_json::from_str(&text)
+                    .map_err(|e| Error::Parse(format!("ExchangeRate-API response: {}", e)))?;
+                if fetched.result != "success" {
+                    return Err(Error::api(
+                        PROVIDER_NAME,
+                        format!(
+                            "ExchangeRate-API error for {}: {}",
+                            from_upper,
+                            fetched.error_type.as_deref().unwrap_or("unknown error")
+                        ),
+                    ));
+                }
+                cache::write_json(self.client.cache_dir(), "open-er-api", &cache_key, &fetched)
+                    .await;
+                fetched
+            }
+        };
+
+        let rates: HashMap<String, f64> = to
+            .iter()
+            .map(|code| code.to_uppercase())
+            .filter_map(|code| body.rates.get(&code).map(|rate| (code, *rate)))
+            .collect();
+
+        if rates.is_empty() {
+            return Err(Error::no_results(PROVIDER_NAME, to, &from_upper));
+        }
+
+        Ok(rates)
+    }
+}
+
+/// Response shape from `GET /latest/{code}`.
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenErResponse {
+    result: String,
+    #[serde(rename = "error-type", default)]
+    error_type: Option<String>,
+    #[serde(default)]
+    rates: HashMap<String, f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_er_response_parsing() {
+        let json = r#"{"result":"success","base_code":"USD","rates":{"USD":1,"NGN":1530.5}}"#;
+        let resp: OpenErResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.result, "success");
+        assert!((resp.rates["NGN"] - 1530.5).abs() < 1e-9);
+
+        let json = r#"{"result":"error","error-type":"unsupported-code"}"#;
+        let resp: OpenErResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(resp.error_type.as_deref(), Some("unsupported-code"));
+        assert!(resp.rates.is_empty());
+    }
+}
@@ -1,32 +1,87 @@
 use async_trait::async_trait;
-use futures::future::join_all;
-use reqwest::Client;
 use serde::Deserialize;
 use tracing::{debug, trace};
 
 use super::cache;
-use super::{CoinPrice, HistoryInterval, PriceHistory, PricePoint, PriceProvider, TickerMatch};
+use super::http::HttpClient;
+use super::instruments;
+use super::{
+    Capabilities, CoinPrice, HistoryInterval, PriceHistory, PricePoint, PriceProvider, TickerMatch,
+    fetch_all, note_daily_only, range_days,
+};
 use crate::error::{Error, Result};
 
-const BASE_URL: &str = "https://stooq.com";
+pub const BASE_URL: &str = "https://stooq.com";
 const SEARCH_BASE_URL: &str = "https://query2.finance.yahoo.com";
 const HISTORY_CACHE_TTL_SECS: i64 = 12 * 60 * 60;
 const PRICE_CACHE_TTL_SECS: i64 = 30;
 const SEARCH_CACHE_TTL_SECS: i64 = 10 * 60;
 
+/// Market used for symbols without an exchange suffix.
+pub const DEFAULT_MARKET: &str = "us";
+
+/// Exchange suffixes Stooq understands, with the currency its quotes are in.
+///
+/// `uk` has no entry because London listings are quoted in pence.
+pub const MARKETS: &[(&str, Option<&str>)] = &[
+    ("us", Some("USD")),
+    ("uk", None),
+    ("de", Some("EUR")),
+    ("jp", Some("JPY")),
+    ("pl", Some("PLN")),
+    ("hk", Some("HKD")),
+    ("hu", Some("HUF")),
+];
+
+/// Parse a market code such as `de` or `.DE` into its canonical lowercase form.
+pub fn parse_market(raw: &str) -> std::result::Result<String, String> {
+    let code = raw.trim().trim_start_matches('.').to_lowercase();
+    if is_market(&code) {
+        Ok(code)
+    } else {
+        let supported: Vec<&str> = MARKETS.iter().map(|(code, _)| *code).collect();
+        Err(format!(
+            "unknown market '{}' (supported: {})",
+            raw.trim(),
+            supported.join(", ")
+        ))
+    }
+}
+
+/// Whether `code` is one of the supported [`MARKETS`].
+pub fn is_market(code: &str) -> bool {
+    MARKETS.iter().any(|(known, _)| *known == code)
+}
+
 /// Stooq price provider for stock and ETF symbols.
 pub struct Stooq {
-    client: Client,
+    client: HttpClient,
     base_url: String,
     search_base_url: String,
+    market: String,
 }
 
 impl Stooq {
     /// Create a Stooq provider using the default production API URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built; [`try_new`](Self::try_new) returns
+    /// the error instead.
     pub fn new() -> Self {
         Self::with_base_urls(BASE_URL, SEARCH_BASE_URL)
     }
 
+    /// Create a Stooq provider using the default production API URL, failing
+    /// instead of panicking when the HTTP client cannot be built.
+    pub fn try_new() -> Result<Self> {
+        Ok(Self::with_client_and_urls(
+            HttpClient::try_default()?,
+            BASE_URL,
+            SEARCH_BASE_URL,
+        ))
+    }
+
     /// Create a Stooq provider with a custom base URL.
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
         Self::with_base_urls(base_url, SEARCH_BASE_URL)
@@ -34,16 +89,32 @@ impl Stooq {
 
     /// Create a Stooq provider with custom quote/history and search base URLs.
     pub fn with_base_urls(base_url: impl Into<String>, search_base_url: impl Into<String>) -> Self {
-        let client = Client::builder()
-            .user_agent("pricr/0.1.0")
-            .build()
-            .expect("failed to build HTTP client");
+        Self::with_client_and_urls(HttpClient::default(), base_url, search_base_url)
+    }
+
+    /// Create a Stooq provider that sends requests through `client`.
+    pub fn with_client(client: HttpClient, base_url: impl Into<String>) -> Self {
+        Self::with_client_and_urls(client, base_url, SEARCH_BASE_URL)
+    }
+
+    fn with_client_and_urls(
+        client: HttpClient,
+        base_url: impl Into<String>,
+        search_base_url: impl Into<String>,
+    ) -> Self {
         Self {
             client,
             base_url: base_url.into(),
             search_base_url: search_base_url.into(),
+            market: DEFAULT_MARKET.to_string(),
         }
     }
+
+    /// Append `market`'s suffix to symbols that do not name an exchange.
+    pub fn with_market(mut self, market: impl Into<String>) -> Self {
+        self.market = market.into();
+        self
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -78,31 +149,50 @@ impl PriceProvider for Stooq {
         "stooq"
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            history: true,
+            history_window: true,
+            search: true,
+            ..Capabilities::PRICES_ONLY
+        }
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
         let requested_currency = currency.to_uppercase();
         let requested: Vec<(String, String)> = symbols
             .iter()
-            .map(|symbol| (symbol.to_uppercase(), normalize_symbol(symbol)))
+            .map(|symbol| {
+                let normalized = normalize_symbol(symbol, &self.market);
+                (normalized.to_uppercase(), normalized)
+            })
             .collect();
 
         let mut results = Vec::new();
-        let futures = requested.iter().map(|(display_symbol, normalized)| {
-            self.fetch_quote_for_symbol(display_symbol, normalized, &requested_currency)
-        });
-
-        for result in join_all(futures).await {
+        let fetched = fetch_all(
+            &requested,
+            self.client.max_concurrency(),
+            |(display_symbol, normalized)| {
+                self.fetch_quote_for_symbol(display_symbol, normalized, &requested_currency)
+            },
+        )
+        .await;
+
+        for result in fetched {
             if let Some(price) = result? {
                 results.push(price);
             }
         }
 
         if results.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(self.name(), symbols, &requested_currency));
         }
 
         Ok(results)
     }
 
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_price_history(
         &self,
         symbols: &[String],
@@ -110,29 +200,66 @@ impl PriceProvider for Stooq {
         days: u32,
         interval: HistoryInterval,
     ) -> Result<Vec<PriceHistory>> {
-        if matches!(interval, HistoryInterval::Hourly) {
+        if interval.fetch_interval() == HistoryInterval::Hourly {
             return Err(Error::Config(
                 "provider 'stooq' supports daily history only".into(),
             ));
         }
 
         let requested_currency = currency.to_uppercase();
-        let futures = symbols
-            .iter()
-            .map(|symbol| self.fetch_history_for_symbol(symbol, &requested_currency, days));
+        let fetched = fetch_all(symbols, self.client.max_concurrency(), |symbol| {
+            self.fetch_history_for_symbol(symbol, &requested_currency, days)
+        })
+        .await;
 
         let mut histories = Vec::new();
-        for result in join_all(futures).await {
+        for result in fetched {
             histories.push(result?);
         }
 
         if histories.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(self.name(), symbols, currency));
         }
 
+        note_daily_only(&mut histories, interval, days);
         Ok(histories)
     }
 
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
+    async fn get_price_history_window(
+        &self,
+        symbols: &[String],
+        currency: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+        interval: HistoryInterval,
+    ) -> Result<Vec<PriceHistory>> {
+        if interval.fetch_interval() == HistoryInterval::Hourly {
+            return Err(Error::Config(
+                "provider 'stooq' supports daily history only".into(),
+            ));
+        }
+
+        let requested_currency = currency.to_uppercase();
+        let fetched = fetch_all(symbols, self.client.max_concurrency(), |symbol| {
+            self.fetch_history_window_for_symbol(symbol, &requested_currency, start, end)
+        })
+        .await;
+
+        let mut histories = Vec::new();
+        for result in fetched {
+            histories.push(result?);
+        }
+
+        if histories.is_empty() {
+            return Err(Error::no_results(self.name(), symbols, currency));
+        }
+
+        note_daily_only(&mut histories, interval, range_days(start, end));
+        Ok(histories)
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
         let trimmed = query.trim();
         if trimmed.is_empty() {
@@ -149,38 +276,44 @@ impl PriceProvider for Stooq {
 
         debug!(query = %trimmed, limit, "searching tickers via Yahoo Finance search API");
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("stooq", &cache_key, SEARCH_CACHE_TTL_SECS).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "stooq",
+            &cache_key,
+            SEARCH_CACHE_TTL_SECS,
+        )
+        .await
         {
-            debug!(query = %trimmed, limit, "using cached ticker search response");
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&endpoint)
-                .query(&[
+            Ok(cached_body) => {
+                debug!(query = %trimmed, limit, "using cached ticker search response");
+                cached_body
+            }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self.client.get(self.name(), &endpoint)?.query(&[
                     ("q", trimmed),
                     ("quotesCount", limit_string.as_str()),
                     ("newsCount", "0"),
-                ])
-                .send()
-                .await?;
+                ]);
+                let resp = self.client.send(self.name(), request).await?;
 
-            let status = resp.status();
-            let body = resp.text().await?;
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
 
-            debug!(status = %status, body_len = body.len(), "ticker search response");
-            trace!(body = %body, query = %trimmed, "ticker search response body");
+                debug!(status = %status, body_len = body.len(), "ticker search response");
+                trace!(body = %body, query = %trimmed, "ticker search response body");
 
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "ticker search returned {}: {}",
-                    status, body
-                )));
-            }
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("ticker search returned {}: {}", status, body),
+                    ));
+                }
 
-            cache::write_json("stooq", &cache_key, &body).await;
-            body
+                cache::write_json(self.client.cache_dir(), "stooq", &cache_key, &body).await;
+                body
+            }
         };
 
         let raw: YahooSearchResponse = serde_json::from_str(&body)
@@ -212,7 +345,7 @@ impl PriceProvider for Stooq {
             .collect::<Vec<_>>();
 
         if matches.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results_from(self.name()));
         }
 
         Ok(matches)
@@ -231,36 +364,48 @@ impl Stooq {
 
         debug!(symbol = %normalized, "fetching quote from Stooq");
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("stooq", &cache_key, PRICE_CACHE_TTL_SECS).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "stooq",
+            &cache_key,
+            PRICE_CACHE_TTL_SECS,
+        )
+        .await
         {
-            debug!(symbol = %normalized, "using cached Stooq quote response");
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&endpoint)
-                .query(&[("s", normalized), ("i", "d")])
-                .send()
-                .await?;
-
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(
-                status = %status,
-                symbol = %normalized,
-                body_len = body.len(),
-                "Stooq quote response"
-            );
-            trace!(body = %body, symbol = %normalized, "Stooq quote response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!("Stooq returned {}: {}", status, body)));
+            Ok(cached_body) => {
+                debug!(symbol = %normalized, "using cached Stooq quote response");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let request = self
+                    .client
+                    .get(self.name(), &endpoint)?
+                    .query(&[("s", normalized), ("i", "d")]);
+                let resp = self.client.send(self.name(), request).await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(
+                    status = %status,
+                    symbol = %normalized,
+                    body_len = body.len(),
+                    "Stooq quote response"
+                );
+                trace!(body = %body, symbol = %normalized, "Stooq quote response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("Stooq returned {}: {}", status, body),
+                    ));
+                }
 
-            cache::write_json("stooq", &cache_key, &body).await;
-            body
+                cache::write_json(self.client.cache_dir(), "stooq", &cache_key, &body).await;
+                body
+            }
         };
 
         let key = normalized.to_uppercase();
@@ -275,16 +420,38 @@ impl Stooq {
 
         Ok(Some(CoinPrice {
             symbol: display_symbol.to_string(),
-            name: display_symbol.to_string(),
+            name: display_name(normalized),
             price: row.close,
             change_24h: row
                 .open
                 .and_then(|open| percent_change(open, row.close))
                 .filter(|v| v.is_finite()),
+            change_7d: None,
+            change_30d: None,
             market_cap: None,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
             currency: currency_for_symbol(normalized, requested_currency),
             provider: self.name().to_string(),
-            timestamp: chrono::Utc::now(),
+            timestamp: row.quoted_at.unwrap_or_else(chrono::Utc::now),
+            quote_time_estimated: row.quoted_at.is_none(),
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
         }))
     }
 
@@ -294,102 +461,190 @@ impl Stooq {
         requested_currency: &str,
         days: u32,
     ) -> Result<PriceHistory> {
-        let display_symbol = symbol.to_uppercase();
-        let normalized = normalize_symbol(symbol);
-        let endpoint = format!("{}/q/d/l/", self.base_url);
-        let cache_key = format!("history:{}:{}:{}", self.base_url, normalized, days);
-
+        let normalized = normalize_symbol(symbol, &self.market);
         debug!(
             symbol = %normalized,
             days,
             "fetching chart data from Stooq"
         );
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("stooq", &cache_key, HISTORY_CACHE_TTL_SECS).await
-        {
-            debug!(symbol = %normalized, "using cached Stooq history response");
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&endpoint)
-                .query(&[("s", normalized.as_str()), ("i", "d")])
-                .send()
-                .await?;
-
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(
-                status = %status,
-                symbol = %normalized,
-                body_len = body.len(),
-                "Stooq history response"
-            );
-            trace!(body = %body, symbol = %normalized, "Stooq history response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "Stooq returned {} for chart data: {}",
-                    status, body
-                )));
-            }
+        let mut points = self.fetch_history_points(&normalized, None).await?;
+        trim_points_to_days(&mut points, days);
+        self.history_from_points(symbol, &normalized, requested_currency, points)
+    }
 
-            cache::write_json("stooq", &cache_key, &body).await;
-            body
-        };
+    async fn fetch_history_window_for_symbol(
+        &self,
+        symbol: &str,
+        requested_currency: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> Result<PriceHistory> {
+        let normalized = normalize_symbol(symbol, &self.market);
+        debug!(
+            symbol = %normalized,
+            start = ?start,
+            end = %end,
+            "fetching chart window from Stooq"
+        );
 
-        let mut points = Vec::new();
-        for line in body.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with("Date,") {
-                continue;
-            }
+        let range = start.map(|start| (start.date_naive(), end.date_naive()));
+        let mut points = self.fetch_history_points(&normalized, range).await?;
+        points.retain(|p| p.timestamp <= end && start.is_none_or(|s| p.timestamp >= s));
+        self.history_from_points(symbol, &normalized, requested_currency, points)
+    }
 
-            let cols: Vec<&str> = trimmed.split(',').collect();
-            if cols.len() < 5 {
-                continue;
+    /// Download and parse the daily CSV history, optionally limited to a date range.
+    async fn fetch_history_points(
+        &self,
+        normalized: &str,
+        range: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
+    ) -> Result<Vec<PricePoint>> {
+        let endpoint = format!("{}/q/d/l/", self.base_url);
+        let mut query = vec![("s", normalized.to_string()), ("i", "d".to_string())];
+        if let Some((from, to)) = range {
+            query.push(("d1", from.format("%Y%m%d").to_string()));
+            query.push(("d2", to.format("%Y%m%d").to_string()));
+        }
+        let range_key = range
+            .map(|(from, to)| format!("{}-{}", from, to))
+            .unwrap_or_else(|| "all".to_string());
+        let cache_key = format!("history:{}:{}:{}", self.base_url, normalized, range_key);
+
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "stooq",
+            &cache_key,
+            HISTORY_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(symbol = %normalized, "using cached Stooq history response");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let resp = self
+                    .client
+                    .send(
+                        self.name(),
+                        self.client.get(self.name(), &endpoint)?.query(&query),
+                    )
+                    .await?;
+
+                let status = resp.status();
+                let body = self.client.text(self.name(), resp).await?;
+
+                debug!(
+                    status = %status,
+                    symbol = %normalized,
+                    body_len = body.len(),
+                    "Stooq history response"
+                );
+                trace!(body = %body, symbol = %normalized, "Stooq history response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("Stooq returned {} for chart data: {}", status, body),
+                    ));
+                }
 
-            let Ok(date) = chrono::NaiveDate::parse_from_str(cols[0].trim(), "%Y-%m-%d") else {
-                continue;
-            };
-            let Some(close) = parse_decimal(cols[4]) else {
-                continue;
-            };
-
-            let Some(naive_dt) = date.and_hms_opt(0, 0, 0) else {
-                continue;
-            };
-
-            points.push(PricePoint {
-                timestamp: naive_dt.and_utc(),
-                price: close,
-            });
-        }
+                cache::write_json(self.client.cache_dir(), "stooq", &cache_key, &body).await;
+                body
+            }
+        };
 
-        points.sort_by_key(|p| p.timestamp);
-        trim_points_to_days(&mut points, days);
+        parse_history_csv(&body)
+    }
 
+    fn history_from_points(
+        &self,
+        symbol: &str,
+        normalized: &str,
+        requested_currency: &str,
+        points: Vec<PricePoint>,
+    ) -> Result<PriceHistory> {
         if points.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(
+                self.name(),
+                &[symbol.to_string()],
+                requested_currency,
+            ));
         }
 
         Ok(PriceHistory {
-            symbol: display_symbol.clone(),
-            name: display_symbol,
-            currency: currency_for_symbol(&normalized, requested_currency),
+            symbol: normalized.to_uppercase(),
+            name: display_name(normalized),
+            currency: currency_for_symbol(normalized, requested_currency),
             provider: self.name().to_string(),
             points,
+            events: Vec::new(),
+            granularity: None,
         })
     }
 }
 
+/// Parse Stooq's `Date,Open,High,Low,Close,Volume` CSV into points sorted by time.
+///
+/// An empty body or Stooq's `No data` marker yields no points; anything else that
+/// does not look like the expected CSV is a parse error.
+fn parse_history_csv(body: &str) -> Result<Vec<PricePoint>> {
+    let mut lines = body
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    if header.eq_ignore_ascii_case("no data") {
+        return Ok(Vec::new());
+    }
+    if !header.starts_with("Date,") {
+        return Err(Error::Parse(format!(
+            "Stooq CSV: unexpected header '{}'",
+            header
+        )));
+    }
+
+    let mut points = Vec::new();
+    for (idx, line) in lines {
+        let cols: Vec<&str> = line.split(',').collect();
+        let parsed = (cols.len() >= 5)
+            .then(|| {
+                let date = chrono::NaiveDate::parse_from_str(cols[0].trim(), "%Y-%m-%d").ok()?;
+                let close = parse_decimal(cols[4])?;
+                Some((date.and_hms_opt(0, 0, 0)?.and_utc(), close))
+            })
+            .flatten();
+
+        let Some((timestamp, price)) = parsed else {
+            return Err(Error::Parse(format!(
+                "Stooq CSV: malformed row {}: '{}'",
+                idx + 1,
+                line
+            )));
+        };
+        points.push(PricePoint {
+            timestamp,
+            price,
+            filled: false,
+        });
+    }
+
+    points.sort_by_key(|p| p.timestamp);
+    Ok(points)
+}
+
 struct QuoteRow {
     symbol: String,
     open: Option<f64>,
     close: f64,
+    quoted_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 fn parse_quote_row(line: &str) -> Option<QuoteRow> {
@@ -410,9 +665,20 @@ fn parse_quote_row(line: &str) -> Option<QuoteRow> {
         symbol,
         open,
         close,
+        quoted_at: parse_quote_time(cols[1], cols[2]),
     })
 }
 
+/// Stooq's `YYYYMMDD` date and `HHMMSS` time columns, which are Warsaw local time.
+fn parse_quote_time(date: &str, time: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let raw = format!("{}{:0>6}", date.trim(), time.trim());
+    let local = chrono::NaiveDateTime::parse_from_str(&raw, "%Y%m%d%H%M%S").ok()?;
+    local
+        .and_local_timezone(chrono_tz::Europe::Warsaw)
+        .earliest()
+        .map(|at| at.with_timezone(&chrono::Utc))
+}
+
 fn parse_decimal(value: &str) -> Option<f64> {
     let parsed = value.trim().parse::<f64>().ok()?;
     if parsed.is_finite() {
@@ -430,21 +696,40 @@ fn percent_change(open: f64, close: f64) -> Option<f64> {
     Some(((close - open) / open) * 100.0)
 }
 
-fn normalize_symbol(symbol: &str) -> String {
+/// Stooq ticker for `symbol`: index and commodity names map to Stooq's own
+/// tickers (`^spx`, `xauusd`), other symbols are lowercased and get the `market`
+/// suffix unless they already have one.
+fn normalize_symbol(symbol: &str, market: &str) -> String {
+    if let Some(instrument) = instruments::lookup(symbol) {
+        return instrument.stooq_ticker();
+    }
     let trimmed = symbol.trim().to_lowercase();
     if trimmed.contains('.') {
         trimmed
     } else {
-        format!("{}.us", trimmed)
+        format!("{}.{}", trimmed, market)
     }
 }
 
+/// Friendly name for indices and commodities, otherwise the uppercased ticker.
+fn display_name(normalized_symbol: &str) -> String {
+    instruments::lookup(normalized_symbol)
+        .and_then(|instrument| instrument.name())
+        .map(str::to_string)
+        .unwrap_or_else(|| normalized_symbol.to_uppercase())
+}
+
+/// Currency of a suffixed symbol's quotes, or `fallback` when the market is unknown.
 fn currency_for_symbol(normalized_symbol: &str, fallback: &str) -> String {
-    if normalized_symbol.ends_with(".us") {
-        "USD".to_string()
-    } else {
-        fallback.to_string()
+    if let Some(instrument) = instruments::lookup(normalized_symbol) {
+        return instrument.currency().unwrap_or(fallback).to_string();
     }
+    normalized_symbol
+        .rsplit_once('.')
+        .and_then(|(_, suffix)| MARKETS.iter().find(|(code, _)| *code == suffix))
+        .and_then(|(_, currency)| *currency)
+        .map(str::to_string)
+        .unwrap_or_else(|| fallback.to_string())
 }
 
 fn trim_points_to_days(points: &mut Vec<PricePoint>, days: u32) {
@@ -1,41 +1,85 @@
 use async_trait::async_trait;
-use futures::future::join_all;
-use reqwest::Client;
+use reqwest::{RequestBuilder, StatusCode};
 use serde::Deserialize;
-use tracing::{debug, trace};
+use std::collections::HashMap;
+use tracing::{debug, trace, warn};
 
 use super::cache;
-use super::{CoinPrice, HistoryInterval, PriceHistory, PricePoint, PriceProvider, TickerMatch};
+use super::coingecko;
+use super::http::HttpClient;
+use super::instruments;
+use super::{
+    Capabilities, CoinPrice, ExtendedQuote, HistoryEvent, HistoryEventKind, HistoryInterval,
+    PriceHistory, PricePoint, PriceProvider, ResolvedInterval, TickerMatch, fetch_all,
+    history_events_enabled, range_days,
+};
 use crate::error::{Error, Result};
 
-const BASE_URL: &str = "https://query2.finance.yahoo.com";
+pub const BASE_URL: &str = "https://query2.finance.yahoo.com";
+/// Yahoo Finance web pages, which hand out the session cookies and crumb.
+pub const PAGE_URL: &str = "https://finance.yahoo.com";
+/// Quote page visited to bootstrap a session; any ticker works.
+const BOOTSTRAP_TICKER: &str = "AAPL";
 const QUOTE_CACHE_TTL_SECS: i64 = 30;
 const SEARCH_CACHE_TTL_SECS: i64 = 10 * 60;
 const HOURLY_HISTORY_CACHE_TTL_SECS: i64 = 60 * 60;
 const DAILY_HISTORY_CACHE_TTL_SECS: i64 = 12 * 60 * 60;
+/// Quote currency every Yahoo crypto pair exists in.
+const CRYPTO_FALLBACK_CURRENCY: &str = "USD";
 
 /// Yahoo Finance provider for stocks/ETFs and ticker discovery.
 pub struct YahooFinance {
-    client: Client,
+    client: HttpClient,
     base_url: String,
+    /// Where the cookie and crumb bootstrap finds its quote page.
+    page_url: String,
+    /// Crumb from the last bootstrap, sent with every request from then on.
+    crumb: tokio::sync::Mutex<Option<String>>,
 }
 
 impl YahooFinance {
     /// Create a Yahoo Finance provider using the default production API URL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be built; [`try_new`](Self::try_new) returns
+    /// the error instead.
     pub fn new() -> Self {
         Self::with_base_url(BASE_URL)
     }
 
+    /// Create a Yahoo Finance provider using the default production API URL, failing
+    /// instead of panicking when the HTTP client cannot be built.
+    pub fn try_new() -> Result<Self> {
+        Ok(Self::with_client(HttpClient::try_default()?, BASE_URL))
+    }
+
     /// Create a Yahoo Finance provider with a custom base URL.
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
-        let client = Client::builder()
-            .user_agent("pricr/0.1.0")
-            .build()
-            .expect("failed to build HTTP client");
+        Self::with_client(HttpClient::default(), base_url)
+    }
 
+    /// Create a Yahoo Finance provider that sends requests through `client`.
+    ///
+    /// Requests go through a copy of `client` with its own cookie store, which carries
+    /// the session from the consent bootstrap. A custom base URL also serves the quote
+    /// page of that bootstrap.
+    pub fn with_client(client: HttpClient, base_url: impl Into<String>) -> Self {
+        let client = client.with_cookie_store().unwrap_or_else(|err| {
+            warn!(error = %err, "no cookie store for Yahoo; consent bootstrap may fail");
+            client
+        });
+        let base_url = base_url.into();
+        let page_url = if base_url == BASE_URL {
+            PAGE_URL.to_string()
+        } else {
+            base_url.clone()
+        };
         Self {
             client,
-            base_url: base_url.into(),
+            base_url,
+            page_url,
+            crumb: tokio::sync::Mutex::new(None),
         }
     }
 }
@@ -62,6 +106,65 @@ struct YahooChartResult {
     meta: YahooChartMeta,
     timestamp: Option<Vec<i64>>,
     indicators: YahooChartIndicators,
+    #[serde(default)]
+    events: Option<YahooChartEvents>,
+}
+
+/// Corporate actions keyed by their unix timestamp, sent when `events=div,splits` is requested.
+#[derive(Debug, Default, Deserialize)]
+struct YahooChartEvents {
+    #[serde(default)]
+    dividends: HashMap<String, YahooDividend>,
+    #[serde(default)]
+    splits: HashMap<String, YahooSplit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooDividend {
+    amount: f64,
+    date: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooSplit {
+    date: i64,
+    numerator: f64,
+    denominator: f64,
+}
+
+impl YahooChartEvents {
+    /// Events inside `[start, end]`, oldest first. Splits with a zero denominator are dropped.
+    fn into_history_events(
+        self,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<HistoryEvent> {
+        let dividends = self
+            .dividends
+            .into_values()
+            .map(|d| (d.date, HistoryEventKind::Dividend, d.amount));
+        let splits = self
+            .splits
+            .into_values()
+            .filter(|s| s.denominator.abs() > f64::EPSILON)
+            .map(|s| (s.date, HistoryEventKind::Split, s.numerator / s.denominator));
+
+        let mut events: Vec<HistoryEvent> = dividends
+            .chain(splits)
+            .filter(|(_, _, value)| value.is_finite())
+            .filter_map(|(ts, kind, value)| {
+                let timestamp = chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)?;
+                let in_window = timestamp <= end && start.is_none_or(|s| timestamp >= s);
+                in_window.then_some(HistoryEvent {
+                    timestamp,
+                    kind,
+                    value,
+                })
+            })
+            .collect();
+        events.sort_by_key(|event| event.timestamp);
+        events
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -73,8 +176,44 @@ struct YahooChartMeta {
     long_name: Option<String>,
     #[serde(rename = "regularMarketPrice")]
     regular_market_price: Option<f64>,
+    /// Unix time of `regularMarketPrice`.
+    #[serde(rename = "regularMarketTime")]
+    regular_market_time: Option<i64>,
     #[serde(rename = "chartPreviousClose")]
     chart_previous_close: Option<f64>,
+    #[serde(rename = "preMarketPrice")]
+    pre_market_price: Option<f64>,
+    #[serde(rename = "postMarketPrice")]
+    post_market_price: Option<f64>,
+    #[serde(rename = "fiftyTwoWeekHigh")]
+    fifty_two_week_high: Option<f64>,
+    #[serde(rename = "fiftyTwoWeekLow")]
+    fifty_two_week_low: Option<f64>,
+    #[serde(rename = "regularMarketVolume")]
+    regular_market_volume: Option<f64>,
+    bid: Option<f64>,
+    ask: Option<f64>,
+}
+
+impl YahooChartMeta {
+    /// Extended quote fields, or `None` when Yahoo sent none of them.
+    fn extended_quote(&self) -> Option<ExtendedQuote> {
+        let finite = |value: Option<f64>| value.filter(|v| v.is_finite());
+        let quote = ExtendedQuote {
+            pre_market_price: finite(self.pre_market_price),
+            post_market_price: finite(self.post_market_price),
+            fifty_two_week_high: finite(self.fifty_two_week_high),
+            fifty_two_week_low: finite(self.fifty_two_week_low),
+            regular_market_volume: finite(self.regular_market_volume),
+        };
+        (quote != ExtendedQuote::default()).then_some(quote)
+    }
+
+    /// Bid and ask; Yahoo sends `0` outside trading hours, which counts as missing.
+    fn bid_ask(&self) -> (Option<f64>, Option<f64>) {
+        let quoted = |value: Option<f64>| value.filter(|v| v.is_finite() && *v > 0.0);
+        (quoted(self.bid), quoted(self.ask))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -102,8 +241,11 @@ struct YahooSearchQuote {
     symbol: String,
     shortname: Option<String>,
     longname: Option<String>,
+    exchange: Option<String>,
     #[serde(rename = "exchDisp")]
     exch_disp: Option<String>,
+    #[serde(rename = "quoteType")]
+    quote_type: Option<String>,
     #[serde(rename = "typeDisp")]
     type_disp: Option<String>,
 }
@@ -118,25 +260,37 @@ impl PriceProvider for YahooFinance {
         "yahoo"
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            history: true,
+            history_window: true,
+            search: true,
+            ..Capabilities::PRICES_ONLY
+        }
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_prices(&self, symbols: &[String], currency: &str) -> Result<Vec<CoinPrice>> {
         let requested_currency = currency.to_uppercase();
-        let futures = symbols
-            .iter()
-            .map(|symbol| self.fetch_latest_quote_for_symbol(symbol, &requested_currency));
+        let fetched = fetch_all(symbols, self.client.max_concurrency(), |symbol| {
+            self.fetch_latest_quote(symbol, &requested_currency)
+        })
+        .await;
         let mut results = Vec::new();
-        for result in join_all(futures).await {
+        for result in fetched {
             if let Some(price) = result? {
                 results.push(price);
             }
         }
 
         if results.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(self.name(), symbols, &requested_currency));
         }
 
         Ok(results)
     }
 
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_price_history(
         &self,
         symbols: &[String],
@@ -150,6 +304,7 @@ impl PriceProvider for YahooFinance {
             .await
     }
 
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn get_price_history_window(
         &self,
         symbols: &[String],
@@ -159,22 +314,24 @@ impl PriceProvider for YahooFinance {
         interval: HistoryInterval,
     ) -> Result<Vec<PriceHistory>> {
         let requested_currency = currency.to_uppercase();
-        let futures = symbols.iter().map(|symbol| {
-            self.fetch_history_for_symbol(symbol, &requested_currency, start, end, interval)
-        });
+        let fetched = fetch_all(symbols, self.client.max_concurrency(), |symbol| {
+            self.fetch_history(symbol, &requested_currency, start, end, interval)
+        })
+        .await;
 
         let mut histories = Vec::new();
-        for result in join_all(futures).await {
+        for result in fetched {
             histories.push(result?);
         }
 
         if histories.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(self.name(), symbols, &requested_currency));
         }
 
         Ok(histories)
     }
 
+    #[tracing::instrument(skip(self), fields(provider = self.id()))]
     async fn search_tickers(&self, query: &str, limit: usize) -> Result<Vec<TickerMatch>> {
         let trimmed = query.trim();
         if trimmed.is_empty() {
@@ -185,33 +342,46 @@ impl PriceProvider for YahooFinance {
         let limit_string = limit.to_string();
         let cache_key = format!("search:{}:{}:{}", self.base_url, trimmed, limit_string);
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("yahoo", &cache_key, SEARCH_CACHE_TTL_SECS).await
-        {
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&endpoint)
-                .query(&[
-                    ("q", trimmed),
-                    ("quotesCount", limit_string.as_str()),
-                    ("newsCount", "0"),
-                ])
-                .send()
-                .await?;
+        debug!(query = %trimmed, limit, "searching tickers via Yahoo Finance");
 
-            let status = resp.status();
-            let body = resp.text().await?;
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "Yahoo Finance search returned {}: {}",
-                    status, body
-                )));
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "yahoo",
+            &cache_key,
+            SEARCH_CACHE_TTL_SECS,
+        )
+        .await
+        {
+            Ok(cached_body) => {
+                debug!(query = %trimmed, limit, "using cached Yahoo search response");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let (status, body) = self
+                    .fetch_body(|| {
+                        Ok(self.client.get(self.name(), &endpoint)?.query(&[
+                            ("q", trimmed),
+                            ("quotesCount", limit_string.as_str()),
+                            ("newsCount", "0"),
+                        ]))
+                    })
+                    .await?;
+
+                debug!(status = %status, body_len = body.len(), "Yahoo search response");
+                trace!(body = %body, query = %trimmed, "Yahoo search response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("Yahoo Finance search returned {}: {}", status, body),
+                    ));
+                }
 
-            cache::write_json("yahoo", &cache_key, &body).await;
-            body
+                cache::write_json(self.client.cache_dir(), "yahoo", &cache_key, &body).await;
+                body
+            }
         };
 
         let payload: YahooSearchResponse = serde_json::from_str(&body)
@@ -226,11 +396,18 @@ impl PriceProvider for YahooFinance {
                     return None;
                 }
 
+                // Crypto pairs such as BTC-USD are kept: they are also how this provider charts crypto.
                 Some(TickerMatch {
                     symbol: symbol.clone(),
                     name: quote.longname.or(quote.shortname).unwrap_or(symbol),
-                    exchange: quote.exch_disp.unwrap_or_else(|| "Unknown".to_string()),
-                    asset_type: quote.type_disp.unwrap_or_else(|| "Unknown".to_string()),
+                    exchange: quote
+                        .exch_disp
+                        .or(quote.exchange)
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                    asset_type: quote
+                        .quote_type
+                        .or(quote.type_disp)
+                        .unwrap_or_else(|| "Unknown".to_string()),
                     provider: self.name().to_string(),
                 })
             })
@@ -238,7 +415,7 @@ impl PriceProvider for YahooFinance {
             .collect::<Vec<_>>();
 
         if matches.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results_from(self.name()));
         }
 
         Ok(matches)
@@ -246,6 +423,218 @@ impl PriceProvider for YahooFinance {
 }
 
 impl YahooFinance {
+    /// Send the request `build` makes and read the answer.
+    ///
+    /// When Yahoo answers with a consent page or asks for a crumb, the session is
+    /// bootstrapped once and the request retried with the new crumb.
+    async fn fetch_body(
+        &self,
+        build: impl Fn() -> Result<RequestBuilder>,
+    ) -> Result<(StatusCode, String)> {
+        let crumb = self.crumb.lock().await.clone();
+        if let Some(answer) = self.send_with_crumb(&build, crumb.as_deref()).await? {
+            return Ok(answer);
+        }
+        debug!("Yahoo asked for consent or a crumb; bootstrapping the session");
+        let crumb = self.refresh_crumb(crumb.as_deref()).await?;
+        self.send_with_crumb(&build, Some(&crumb))
+            .await?
+            .ok_or_else(|| self.bootstrap_failed("Yahoo still asks for consent with the new crumb"))
+    }
+
+    /// `None` when the answer is a consent page or a crumb error instead of data.
+    async fn send_with_crumb(
+        &self,
+        build: &impl Fn() -> Result<RequestBuilder>,
+        crumb: Option<&str>,
+    ) -> Result<Option<(StatusCode, String)>> {
+        let mut request = build()?;
+        if let Some(crumb) = crumb {
+            request = request.query(&[("crumb", crumb)]);
+        }
+        let resp = self.client.send(self.name(), request).await?;
+        let status = resp.status();
+        let consent_page = is_consent_url(resp.url());
+        let body = self.client.text(self.name(), resp).await?;
+        if consent_page || asks_for_crumb(status, &body) {
+            trace!(status = %status, body = %body, "Yahoo consent or crumb response");
+            return Ok(None);
+        }
+        Ok(Some((status, body)))
+    }
+
+    /// A fresh crumb, unless another request already replaced `stale` while this
+    /// one waited for the lock.
+    async fn refresh_crumb(&self, stale: Option<&str>) -> Result<String> {
+        let mut cached = self.crumb.lock().await;
+        if let Some(crumb) = cached.as_deref()
+            && Some(crumb) != stale
+        {
+            return Ok(crumb.to_string());
+        }
+        let crumb = self.bootstrap_crumb().await.map_err(|err| match err {
+            Error::Interrupted | Error::RateLimited { .. } => err,
+            Error::Api { message, .. } => self.bootstrap_failed(message),
+            other => self.bootstrap_failed(other),
+        })?;
+        debug!("Yahoo session bootstrapped");
+        *cached = Some(crumb.clone());
+        Ok(crumb)
+    }
+
+    /// Visit a quote page so Yahoo sets its session cookies, following any consent
+    /// redirect, and read the crumb from the page or else from `getcrumb`.
+    async fn bootstrap_crumb(&self) -> Result<String> {
+        let page = format!("{}/quote/{}", self.page_url, BOOTSTRAP_TICKER);
+        let resp = self
+            .client
+            .send(self.name(), self.client.get(self.name(), &page)?)
+            .await?;
+        let status = resp.status();
+        let body = self.client.text(self.name(), resp).await?;
+        if status.is_success()
+            && let Some(crumb) = page_crumb(&body)
+        {
+            return Ok(crumb);
+        }
+        debug!(status = %status, "no crumb in the Yahoo quote page; asking getcrumb");
+
+        let endpoint = format!("{}/v1/test/getcrumb", self.base_url);
+        let resp = self
+            .client
+            .send(self.name(), self.client.get(self.name(), &endpoint)?)
+            .await?;
+        let status = resp.status();
+        let body = self.client.text(self.name(), resp).await?;
+        if !status.is_success() {
+            return Err(Error::api(
+                self.name(),
+                format!("getcrumb returned {}", status),
+            ));
+        }
+        let crumb = body.trim();
+        if !is_crumb(crumb) {
+            return Err(Error::api(
+                self.name(),
+                "no crumb in the quote page or the getcrumb response",
+            ));
+        }
+        Ok(crumb.to_string())
+    }
+
+    fn bootstrap_failed(&self, reason: impl std::fmt::Display) -> Error {
+        Error::api(
+            self.name(),
+            format!("Yahoo requires consent/crumb; bootstrap failed: {}", reason),
+        )
+    }
+
+    /// Latest quote for `symbol`. Indices and commodities are asked for by their
+    /// Yahoo ticker (`^GSPC`, `GC=F`). Bare crypto tickers are asked for as the
+    /// `TICKER-CURRENCY` pair, then as `TICKER-USD` when Yahoo has no such pair;
+    /// the row keeps the ticker as given.
+    async fn fetch_latest_quote(
+        &self,
+        symbol: &str,
+        requested_currency: &str,
+    ) -> Result<Option<CoinPrice>> {
+        if let Some(instrument) = instruments::lookup(symbol) {
+            let found = self
+                .fetch_latest_quote_for_symbol(&instrument.yahoo_ticker(), requested_currency)
+                .await?;
+            return Ok(found.map(|mut price| {
+                if let Some(name) = instrument.name() {
+                    price.name = name.to_string();
+                }
+                price
+            }));
+        }
+        let Some(ticker) = crypto_ticker(symbol) else {
+            return self
+                .fetch_latest_quote_for_symbol(symbol, requested_currency)
+                .await;
+        };
+        let pair = format!("{}-{}", ticker, requested_currency);
+        let mut found = self
+            .fetch_latest_quote_for_symbol(&pair, requested_currency)
+            .await;
+        if requested_currency != CRYPTO_FALLBACK_CURRENCY
+            && matches!(
+                found,
+                Ok(None)
+                    | Err(Error::Api {
+                        status: Some(404),
+                        ..
+                    })
+            )
+        {
+            debug!(pair = %pair, "Yahoo has no such crypto pair; falling back to USD");
+            let pair = format!("{}-{}", ticker, CRYPTO_FALLBACK_CURRENCY);
+            found = self
+                .fetch_latest_quote_for_symbol(&pair, CRYPTO_FALLBACK_CURRENCY)
+                .await;
+        }
+        Ok(found?.map(|mut price| {
+            price.symbol = symbol.trim().to_uppercase();
+            price
+        }))
+    }
+
+    /// Chart history for `symbol`, mapping indices, commodities and bare crypto
+    /// tickers like [`Self::fetch_latest_quote`].
+    async fn fetch_history(
+        &self,
+        symbol: &str,
+        requested_currency: &str,
+        start: Option<chrono::DateTime<chrono::Utc>>,
+        end: chrono::DateTime<chrono::Utc>,
+        interval: HistoryInterval,
+    ) -> Result<PriceHistory> {
+        if let Some(instrument) = instruments::lookup(symbol) {
+            let mut history = self
+                .fetch_history_for_symbol(
+                    &instrument.yahoo_ticker(),
+                    requested_currency,
+                    start,
+                    end,
+                    interval,
+                )
+                .await?;
+            if let Some(name) = instrument.name() {
+                history.name = name.to_string();
+            }
+            return Ok(history);
+        }
+        let Some(ticker) = crypto_ticker(symbol) else {
+            return self
+                .fetch_history_for_symbol(symbol, requested_currency, start, end, interval)
+                .await;
+        };
+        let pair = format!("{}-{}", ticker, requested_currency);
+        let mut found = self
+            .fetch_history_for_symbol(&pair, requested_currency, start, end, interval)
+            .await;
+        if requested_currency != CRYPTO_FALLBACK_CURRENCY
+            && matches!(
+                found,
+                Err(Error::NoResults { .. }
+                    | Error::Api {
+                        status: Some(404),
+                        ..
+                    })
+            )
+        {
+            debug!(pair = %pair, "Yahoo has no such crypto pair; falling back to USD");
+            let pair = format!("{}-{}", ticker, CRYPTO_FALLBACK_CURRENCY);
+            found = self
+                .fetch_history_for_symbol(&pair, CRYPTO_FALLBACK_CURRENCY, start, end, interval)
+                .await;
+        }
+        let mut history = found?;
+        history.symbol = symbol.trim().to_uppercase();
+        Ok(history)
+    }
+
     async fn fetch_latest_quote_for_symbol(
         &self,
         symbol: &str,
@@ -257,29 +646,36 @@ impl YahooFinance {
 
         debug!(symbol = %symbol_upper, "fetching latest quote from Yahoo Finance chart endpoint");
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("yahoo", &cache_key, QUOTE_CACHE_TTL_SECS).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "yahoo",
+            &cache_key,
+            QUOTE_CACHE_TTL_SECS,
+        )
+        .await
         {
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&endpoint)
-                .query(&[("range", "5d"), ("interval", "1d")])
-                .send()
-                .await?;
+            Ok(cached_body) => cached_body,
+            Err(miss) => {
+                miss.ensure_online()?;
+                let (status, body) = self
+                    .fetch_body(|| {
+                        Ok(self
+                            .client
+                            .get(self.name(), &endpoint)?
+                            .query(&[("range", "5d"), ("interval", "1d")]))
+                    })
+                    .await?;
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("Yahoo Finance returned {} for quote data: {}", status, body),
+                    ));
+                }
 
-            let status = resp.status();
-            let body = resp.text().await?;
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "Yahoo Finance returned {} for quote data: {}",
-                    status, body
-                )));
+                cache::write_json(self.client.cache_dir(), "yahoo", &cache_key, &body).await;
+                body
             }
-
-            cache::write_json("yahoo", &cache_key, &body).await;
-            body
         };
 
         let payload: YahooChartEnvelope = serde_json::from_str(&body)
@@ -289,7 +685,10 @@ impl YahooFinance {
             && let Some(description) = api_error.description
             && !description.is_empty()
         {
-            return Err(Error::Api(format!("Yahoo Finance: {}", description)));
+            return Err(Error::api(
+                self.name(),
+                format!("Yahoo Finance: {}", description),
+            ));
         }
 
         let chart = payload
@@ -339,6 +738,12 @@ impl YahooFinance {
             })
             .filter(|value| value.is_finite());
 
+        let extended = chart.meta.extended_quote();
+        let (bid, ask) = chart.meta.bid_ask();
+        let quote_time = chart
+            .meta
+            .regular_market_time
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0));
         let quote_currency = chart
             .meta
             .currency
@@ -355,10 +760,32 @@ impl YahooFinance {
             name,
             price,
             change_24h,
+            change_7d: None,
+            change_30d: None,
             market_cap: None,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
             currency: quote_currency,
             provider: self.name().to_string(),
-            timestamp: chrono::Utc::now(),
+            timestamp: quote_time.unwrap_or_else(chrono::Utc::now),
+            quote_time_estimated: quote_time.is_none(),
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid,
+            ask,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended,
         }))
     }
 
@@ -377,9 +804,15 @@ impl YahooFinance {
         let period2 = (end + chrono::Duration::seconds(1))
             .timestamp()
             .max(period1 + 1);
+        let with_events = history_events_enabled();
         let cache_key = format!(
-            "chart:{}:{}:{}:{}:{}",
-            self.base_url, symbol_upper, period1, period2, interval_param
+            "chart:{}:{}:{}:{}:{}{}",
+            self.base_url,
+            symbol_upper,
+            period1,
+            period2,
+            interval_param,
+            if with_events { ":events" } else { "" }
         );
         let cache_ttl = if interval_param == "1h" {
             HOURLY_HISTORY_CACHE_TTL_SECS
@@ -395,43 +828,54 @@ impl YahooFinance {
             "fetching chart data from Yahoo Finance"
         );
 
-        let body = if let Some(cached_body) =
-            cache::read_json::<String>("yahoo", &cache_key, cache_ttl).await
+        let body = match cache::read_json::<String>(
+            self.client.cache_dir(),
+            "yahoo",
+            &cache_key,
+            cache_ttl,
+        )
+        .await
         {
-            debug!(symbol = %symbol_upper, "using cached Yahoo chart response");
-            cached_body
-        } else {
-            let resp = self
-                .client
-                .get(&endpoint)
-                .query(&[
-                    ("period1", period1.to_string()),
-                    ("period2", period2.to_string()),
-                    ("interval", interval_param.to_string()),
-                ])
-                .send()
-                .await?;
-
-            let status = resp.status();
-            let body = resp.text().await?;
-
-            debug!(
-                status = %status,
-                symbol = %symbol_upper,
-                body_len = body.len(),
-                "Yahoo chart response"
-            );
-            trace!(body = %body, symbol = %symbol_upper, "Yahoo chart response body");
-
-            if !status.is_success() {
-                return Err(Error::Api(format!(
-                    "Yahoo Finance returned {} for chart data: {}",
-                    status, body
-                )));
+            Ok(cached_body) => {
+                debug!(symbol = %symbol_upper, "using cached Yahoo chart response");
+                cached_body
             }
+            Err(miss) => {
+                miss.ensure_online()?;
+                let (status, body) = self
+                    .fetch_body(|| {
+                        let request = self.client.get(self.name(), &endpoint)?.query(&[
+                            ("period1", period1.to_string()),
+                            ("period2", period2.to_string()),
+                            ("interval", interval_param.to_string()),
+                        ]);
+                        Ok(if with_events {
+                            request.query(&[("events", "div,splits")])
+                        } else {
+                            request
+                        })
+                    })
+                    .await?;
+
+                debug!(
+                    status = %status,
+                    symbol = %symbol_upper,
+                    body_len = body.len(),
+                    "Yahoo chart response"
+                );
+                trace!(body = %body, symbol = %symbol_upper, "Yahoo chart response body");
+
+                if !status.is_success() {
+                    return Err(Error::api_status(
+                        self.name(),
+                        status,
+                        format!("Yahoo Finance returned {} for chart data: {}", status, body),
+                    ));
+                }
 
-            cache::write_json("yahoo", &cache_key, &body).await;
-            body
+                cache::write_json(self.client.cache_dir(), "yahoo", &cache_key, &body).await;
+                body
+            }
         };
 
         let payload: YahooChartEnvelope = serde_json::from_str(&body)
@@ -441,15 +885,32 @@ impl YahooFinance {
             && let Some(description) = api_error.description
             && !description.is_empty()
         {
-            return Err(Error::Api(format!("Yahoo Finance: {}", description)));
+            return Err(Error::api(
+                self.name(),
+                format!("Yahoo Finance: {}", description),
+            ));
         }
 
         let chart = payload
             .chart
             .result
             .and_then(|mut values| values.drain(..).next())
-            .ok_or(Error::NoResults)?;
-
+            .ok_or_else(|| {
+                Error::no_results(
+                    self.name(),
+                    std::slice::from_ref(&symbol_upper),
+                    requested_currency,
+                )
+            })?;
+
+        let events = if with_events {
+            chart
+                .events
+                .unwrap_or_default()
+                .into_history_events(start, end)
+        } else {
+            Vec::new()
+        };
         let timestamps = chart.timestamp.unwrap_or_default();
         let closes = chart
             .indicators
@@ -481,12 +942,20 @@ impl YahooFinance {
                 continue;
             }
 
-            points.push(PricePoint { timestamp, price });
+            points.push(PricePoint {
+                timestamp,
+                price,
+                filled: false,
+            });
         }
 
         points.sort_by_key(|point| point.timestamp);
         if points.is_empty() {
-            return Err(Error::NoResults);
+            return Err(Error::no_results(
+                self.name(),
+                &[symbol_upper],
+                requested_currency,
+            ));
         }
 
         let currency = chart
@@ -506,10 +975,58 @@ impl YahooFinance {
             currency,
             provider: self.name().to_string(),
             points,
+            events,
+            granularity: None,
         })
     }
 }
 
+/// Uppercased ticker when `symbol` is a known coin without a Yahoo suffix or
+/// exchange marker, e.g. `btc` but not `BTC-EUR` or `BTC.L`.
+fn crypto_ticker(symbol: &str) -> Option<String> {
+    let symbol = symbol.trim();
+    if symbol.contains(['-', '.', '=', '^']) {
+        return None;
+    }
+    coingecko::known_ticker(symbol).map(str::to_uppercase)
+}
+
+/// Whether a response ended on Yahoo's consent or privacy gate after redirects.
+fn is_consent_url(url: &reqwest::Url) -> bool {
+    let host = url.host_str().unwrap_or_default();
+    host.starts_with("consent.") || host.starts_with("guce.") || url.path().contains("/consent")
+}
+
+/// Whether Yahoo answered without data because the session lacks a cookie or crumb:
+/// an `Invalid Crumb`/`Invalid Cookie` rejection, or an HTML page where JSON belongs.
+fn asks_for_crumb(status: StatusCode, body: &str) -> bool {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            let body = body.to_ascii_lowercase();
+            body.contains("crumb") || body.contains("cookie")
+        }
+        status if status.is_success() => body.trim_start().starts_with('<'),
+        _ => false,
+    }
+}
+
+/// The crumb a quote page embeds in its data, as `"crumb":"..."`.
+fn page_crumb(html: &str) -> Option<String> {
+    const KEY: &str = "\"crumb\":\"";
+    let start = html.find(KEY)? + KEY.len();
+    let end = html[start..].find('"')?;
+    let crumb = html[start..start + end].replace("\\u002F", "/");
+    is_crumb(&crumb).then_some(crumb)
+}
+
+fn is_crumb(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 64
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '<' | '>' | '{' | '}' | '"'))
+}
+
 fn percent_change(previous: f64, current: f64) -> Option<f64> {
     if !previous.is_finite() || previous.abs() <= f64::EPSILON {
         return None;
@@ -523,12 +1040,8 @@ fn chart_interval(
     start: Option<chrono::DateTime<chrono::Utc>>,
     end: chrono::DateTime<chrono::Utc>,
 ) -> &'static str {
-    match interval {
-        HistoryInterval::Daily => "1d",
-        HistoryInterval::Hourly => "1h",
-        HistoryInterval::Auto => {
-            let days = start.map(|s| (end - s).num_days().max(1)).unwrap_or(366);
-            if days <= 5 { "1h" } else { "1d" }
-        }
+    match interval.resolve(range_days(start, end)) {
+        ResolvedInterval::Hourly => "1h",
+        ResolvedInterval::Daily => "1d",
     }
 }
@@ -0,0 +1,156 @@
+//! Asset-class heuristics for `--auto-provider`.
+//!
+//! Each symbol is classified as crypto, equity (stocks, ETFs, indices,
+//! commodities) or fiat, and each class has providers that answer it best.
+
+use crate::calc;
+use crate::config::SymbolAliases;
+use crate::provider::coingecko;
+
+/// What kind of asset a symbol most likely names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetClass {
+    Crypto,
+    Equity,
+    Fiat,
+}
+
+const CRYPTO_PROVIDERS: &[&str] = &["coingecko", "cmc"];
+const EQUITY_PROVIDERS: &[&str] = &["yahoo", "stooq"];
+const FIAT_PROVIDERS: &[&str] = &["frankfurter"];
+
+impl AssetClass {
+    /// Provider ids that serve this class, best first.
+    pub fn preferred_providers(self) -> &'static [&'static str] {
+        match self {
+            Self::Crypto => CRYPTO_PROVIDERS,
+            Self::Equity => EQUITY_PROVIDERS,
+            Self::Fiat => FIAT_PROVIDERS,
+        }
+    }
+
+    fn from_provider(provider_id: &str) -> Option<Self> {
+        [Self::Crypto, Self::Equity, Self::Fiat]
+            .into_iter()
+            .find(|class| {
+                class
+                    .preferred_providers()
+                    .iter()
+                    .any(|id| id.eq_ignore_ascii_case(provider_id))
+            })
+    }
+}
+
+/// Classify `symbol`.
+///
+/// An `[aliases]` entry decides first: a symbol aliased only for equity providers
+/// (`link = { yahoo = "LINK" }`) is an equity even though `link` is also a coin.
+/// Otherwise known coins win over fiat codes and stock-market symbols, and
+/// anything unrecognised is treated as an equity ticker.
+pub fn classify(symbol: &str, aliases: &SymbolAliases) -> AssetClass {
+    if let Some(class) = aliased_class(symbol, aliases) {
+        return class;
+    }
+    if coingecko::known_ticker(symbol).is_some() {
+        AssetClass::Crypto
+    } else if calc::is_known_fiat(symbol.trim()) {
+        AssetClass::Fiat
+    } else {
+        AssetClass::Equity
+    }
+}
+
+/// The class of the providers `symbol` has aliases for, preferring crypto.
+fn aliased_class(symbol: &str, aliases: &SymbolAliases) -> Option<AssetClass> {
+    let mut classes = crate::provider::PROVIDER_IDS
+        .iter()
+        .filter(|id| aliases.resolve(id, symbol).is_some())
+        .filter_map(|id| AssetClass::from_provider(id));
+    let first = classes.next()?;
+    Some(
+        if first == AssetClass::Crypto || classes.any(|c| c == AssetClass::Crypto) {
+            AssetClass::Crypto
+        } else {
+            first
+        },
+    )
+}
+
+/// Split `symbols` by class, keeping request order within each class and
+/// ordering classes by their first symbol.
+pub fn group_by_class(
+    symbols: &[String],
+    aliases: &SymbolAliases,
+) -> Vec<(AssetClass, Vec<String>)> {
+    let mut groups: Vec<(AssetClass, Vec<String>)> = Vec::new();
+    for symbol in symbols {
+        let class = classify(symbol, aliases);
+        match groups.iter_mut().find(|(c, _)| *c == class) {
+            Some((_, members)) => members.push(symbol.clone()),
+            None => groups.push((class, vec![symbol.clone()])),
+        }
+    }
+    groups
+}
+
+/// Positions into `provider_ids` to try for `class`: its preferred providers
+/// first, then every other provider as a fallback, each in the given order.
+pub fn route(class: AssetClass, provider_ids: &[&str]) -> Vec<usize> {
+    let preferred = class.preferred_providers();
+    let is_preferred = |id: &str| preferred.iter().any(|p| p.eq_ignore_ascii_case(id));
+    let (mut first, rest): (Vec<usize>, Vec<usize>) =
+        (0..provider_ids.len()).partition(|&idx| is_preferred(provider_ids[idx]));
+    first.extend(rest);
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_prefers_crypto_then_fiat_then_equity() {
+        let none = SymbolAliases::default();
+        assert_eq!(classify("btc", &none), AssetClass::Crypto);
+        assert_eq!(classify("LINK", &none), AssetClass::Crypto);
+        assert_eq!(classify("eur", &none), AssetClass::Fiat);
+        assert_eq!(classify("aapl", &none), AssetClass::Equity);
+        assert_eq!(classify("^spx", &none), AssetClass::Equity);
+        assert_eq!(classify("sap.de", &none), AssetClass::Equity);
+    }
+
+    #[test]
+    fn aliases_override_the_crypto_preference() {
+        let equity: SymbolAliases = toml::from_str(r#"link = { yahoo = "LINK" }"#).unwrap();
+        assert_eq!(classify("link", &equity), AssetClass::Equity);
+
+        let both: SymbolAliases =
+            toml::from_str(r#"link = { yahoo = "LINK", coingecko = "chainlink" }"#).unwrap();
+        assert_eq!(classify("link", &both), AssetClass::Crypto);
+
+        let coin: SymbolAliases = toml::from_str(r#"acme = { cmc = "ACME" }"#).unwrap();
+        assert_eq!(classify("acme", &coin), AssetClass::Crypto);
+    }
+
+    #[test]
+    fn groups_keep_request_order_and_routes_put_preferred_first() {
+        let symbols: Vec<String> = ["btc", "aapl", "eur", "eth", "msft"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let groups = group_by_class(&symbols, &SymbolAliases::default());
+        assert_eq!(
+            groups,
+            vec![
+                (AssetClass::Crypto, vec!["btc".into(), "eth".into()]),
+                (AssetClass::Equity, vec!["aapl".into(), "msft".into()]),
+                (AssetClass::Fiat, vec!["eur".into()]),
+            ]
+        );
+
+        let ids = ["coingecko", "stooq", "yahoo", "cmc", "frankfurter"];
+        assert_eq!(route(AssetClass::Crypto, &ids), vec![0, 3, 1, 2, 4]);
+        assert_eq!(route(AssetClass::Equity, &ids), vec![1, 2, 0, 3, 4]);
+        assert_eq!(route(AssetClass::Fiat, &ids), vec![4, 0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,268 @@
+//! Client-side ranking of ticker search results.
+//!
+//! Provider search endpoints return matches in their own order, which often puts
+//! the obvious answer below the limit (`appl` on Stooq can list Apple Hospitality
+//! first). Results are asked for with a wider [`candidate_pool`], merged across
+//! providers, and then ranked against the query: an exact symbol first, then
+//! symbol prefixes, then names that start with or contain the query, and finally
+//! trigram similarity for typos.
+
+use std::collections::HashSet;
+
+use crate::provider::TickerMatch;
+
+/// How many more candidates than the requested limit to ask each provider for.
+const CANDIDATE_FACTOR: usize = 3;
+
+/// Largest candidate pool asked of a single provider.
+const MAX_CANDIDATES: usize = 50;
+
+/// Number of results to request from a provider so ranking has room to reorder.
+pub fn candidate_pool(limit: usize) -> usize {
+    (limit * CANDIDATE_FACTOR).clamp(limit, MAX_CANDIDATES.max(limit))
+}
+
+/// Relevance of `candidate` for `query`, higher first.
+///
+/// Tiers never overlap, so a weaker kind of match never outranks a stronger
+/// one: exact symbol (1000), symbol prefix (up to 800), name or name-word prefix
+/// (up to 600), name substring (up to 500), symbol substring (up to 400), then
+/// trigram similarity of the closer of symbol and name (up to 300). Within a
+/// tier, the less text left over around the query, the higher.
+pub fn score(query: &str, candidate: &TickerMatch) -> f64 {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return 0.0;
+    }
+    let symbol = candidate.symbol.trim().to_lowercase();
+    let name = candidate.name.trim().to_lowercase();
+    let tier = |top: f64, text: &str| top - leftover(text, &query);
+
+    if symbol == query {
+        1000.0
+    } else if symbol.starts_with(&query) {
+        tier(800.0, &symbol)
+    } else if name
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(&query))
+    {
+        tier(600.0, &name)
+    } else if name.contains(&query) {
+        tier(500.0, &name)
+    } else if symbol.contains(&query) {
+        tier(400.0, &symbol)
+    } else {
+        300.0 * similarity(&query, &symbol).max(similarity(&query, &name))
+    }
+}
+
+/// Characters of `text` outside the matched `query`, capped below a tier's width.
+fn leftover(text: &str, query: &str) -> f64 {
+    (text.chars().count() - query.chars().count()).min(99) as f64
+}
+
+/// Merge rows for the same symbol on the same exchange, rank them against
+/// `query`, and keep the best `limit`.
+///
+/// Merged rows keep the first row's details and list every provider that
+/// returned it. Equal scores keep their incoming order, so provider order and
+/// each provider's own ranking still break ties.
+pub fn rank(query: &str, matches: Vec<TickerMatch>, limit: usize) -> Vec<TickerMatch> {
+    let mut ranked: Vec<(f64, TickerMatch)> = dedup(matches)
+        .into_iter()
+        .map(|candidate| (score(query, &candidate), candidate))
+        .collect();
+    ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Drop repeated symbol and exchange pairs, compared case-insensitively,
+/// folding their providers into the first row.
+pub fn dedup(matches: Vec<TickerMatch>) -> Vec<TickerMatch> {
+    let mut unique: Vec<TickerMatch> = Vec::new();
+    for candidate in matches {
+        match unique
+            .iter_mut()
+            .find(|kept| same_listing(kept, &candidate))
+        {
+            Some(kept) => append_provider_name(&mut kept.provider, &candidate.provider),
+            None => unique.push(candidate),
+        }
+    }
+    unique
+}
+
+fn same_listing(a: &TickerMatch, b: &TickerMatch) -> bool {
+    a.symbol.trim().eq_ignore_ascii_case(b.symbol.trim())
+        && a.exchange.trim().eq_ignore_ascii_case(b.exchange.trim())
+}
+
+/// Add `provider_name` to a comma-separated provider list unless already present.
+pub fn append_provider_name(existing: &mut String, provider_name: &str) {
+    if existing
+        .split(',')
+        .map(str::trim)
+        .any(|name| name.eq_ignore_ascii_case(provider_name))
+    {
+        return;
+    }
+
+    if existing.trim().is_empty() {
+        *existing = provider_name.to_string();
+        return;
+    }
+
+    existing.push_str(", ");
+    existing.push_str(provider_name);
+}
+
+/// Jaccard similarity of the two strings' trigram sets, from 0 to 1.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a = trigrams(a);
+    let b = trigrams(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(&b).count();
+    shared as f64 / (a.len() + b.len() - shared) as f64
+}
+
+/// Trigrams of each word, padded so short words and word starts still count.
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let mut grams = HashSet::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+        let padded: Vec<char> = "  ".chars().chain(word.chars()).chain([' ']).collect();
+        for window in padded.windows(3) {
+            grams.insert([window[0], window[1], window[2]]);
+        }
+    }
+    grams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(symbol: &str, name: &str, exchange: &str, provider: &str) -> TickerMatch {
+        TickerMatch {
+            symbol: symbol.into(),
+            name: name.into(),
+            exchange: exchange.into(),
+            asset_type: "Equity".into(),
+            provider: provider.into(),
+        }
+    }
+
+    fn symbols(matches: &[TickerMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.symbol.as_str()).collect()
+    }
+
+    #[test]
+    fn symbol_prefixes_outrank_name_matches() {
+        // In Stooq's own order for "appl".
+        let found = vec![
+            ticker("APLE", "Apple Hospitality REIT, Inc.", "NYSE", "Stooq"),
+            ticker("APPLX", "Appleseed Fund", "Nasdaq", "Stooq"),
+            ticker("AAPL", "Apple Inc.", "NASDAQ", "Stooq"),
+            ticker("APPL.L", "Applied Graphene", "LSE", "Stooq"),
+            ticker("MAPPL", "Map Pleasant Holdings", "OTC", "Stooq"),
+        ];
+        assert_eq!(
+            symbols(&rank("appl", found, 10)),
+            ["APPLX", "APPL.L", "AAPL", "APLE", "MAPPL"]
+        );
+    }
+
+    #[test]
+    fn exact_symbols_come_first_and_shorter_prefixes_next() {
+        let found = vec![
+            ticker("BTC-EUR", "Bitcoin EUR", "CCC", "Yahoo Finance"),
+            ticker("WBTC", "Wrapped Bitcoin", "#15", "CoinGecko"),
+            ticker("BTC-USD", "Bitcoin USD", "CCC", "Yahoo Finance"),
+            ticker("BTCS", "BTCS Inc.", "NASDAQ", "Yahoo Finance"),
+            ticker("BTC", "Bitcoin", "#1", "CoinGecko"),
+        ];
+        assert_eq!(
+            symbols(&rank("btc", found, 4)),
+            ["BTC", "BTCS", "BTC-EUR", "BTC-USD"]
+        );
+    }
+
+    #[test]
+    fn name_prefixes_and_words_beat_plain_substrings() {
+        let found = vec![
+            ticker(
+                "PINE",
+                "Alpine Income Property Trust",
+                "NYSE",
+                "Yahoo Finance",
+            ),
+            ticker("TSLA", "Tesla, Inc.", "NASDAQ", "Yahoo Finance"),
+            ticker("NIO", "NIO Inc. (Tesla rival)", "NYSE", "Yahoo Finance"),
+        ];
+        assert_eq!(symbols(&rank("tesla", found, 10)), ["TSLA", "NIO", "PINE"]);
+        assert_eq!(score("pine", &ticker("PINE", "Alpine", "NYSE", "")), 1000.0);
+        assert_eq!(
+            score("income", &ticker("PINE", "Alpine Income", "NYSE", "")),
+            593.0
+        );
+        assert_eq!(score("lpine", &ticker("PINE", "Alpine", "NYSE", "")), 499.0);
+    }
+
+    #[test]
+    fn typos_fall_back_to_trigram_similarity() {
+        let found = vec![
+            ticker("ETH", "Ethereum", "#2", "CoinGecko"),
+            ticker("MSFT", "Microsoft Corporation", "NASDAQ", "Yahoo Finance"),
+            ticker(
+                "MSTR",
+                "MicroStrategy Incorporated",
+                "NASDAQ",
+                "Yahoo Finance",
+            ),
+        ];
+        assert_eq!(
+            symbols(&rank("microsfot", found, 10)),
+            ["MSFT", "MSTR", "ETH"]
+        );
+        assert_eq!(score("", &ticker("ETH", "Ethereum", "#2", "")), 0.0);
+        assert_eq!(score("zzzz", &ticker("ETH", "Ethereum", "#2", "")), 0.0);
+    }
+
+    #[test]
+    fn duplicates_merge_providers_and_ties_keep_their_order() {
+        let found = vec![
+            ticker("AAPL", "Apple Inc.", "NASDAQ", "Yahoo Finance"),
+            ticker("AAPL.DE", "Apple Inc.", "XETRA", "Yahoo Finance"),
+            ticker("aapl", "Apple Inc", "Nasdaq", "Stooq"),
+            ticker("AAPL", "Apple Inc.", "NASDAQ", "Yahoo Finance"),
+        ];
+        let ranked = rank("aapl", found, 10);
+        assert_eq!(symbols(&ranked), ["AAPL", "AAPL.DE"]);
+        assert_eq!(ranked[0].provider, "Yahoo Finance, Stooq");
+        assert_eq!(ranked[0].name, "Apple Inc.");
+    }
+
+    #[test]
+    fn append_provider_name_adds_unique_values() {
+        let mut provider = "Yahoo Finance".to_string();
+        append_provider_name(&mut provider, "Stooq");
+        append_provider_name(&mut provider, "stooq");
+        assert_eq!(provider, "Yahoo Finance, Stooq");
+    }
+
+    #[test]
+    fn candidate_pool_widens_the_limit_up_to_the_cap() {
+        assert_eq!(candidate_pool(1), 3);
+        assert_eq!(candidate_pool(10), 30);
+        assert_eq!(candidate_pool(20), 50);
+        assert_eq!(candidate_pool(50), 50);
+    }
+}
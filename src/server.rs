@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::error::{Error, Result};
+use crate::provider::CoinPrice;
+
+/// Default minimum delay between provider refreshes triggered by scrapes.
+pub const DEFAULT_MIN_REFRESH: Duration = Duration::from_secs(30);
+
+const METRICS_PATH: &str = "/metrics";
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Callback that fetches a fresh set of prices for the exporter.
+pub type PriceFetcher = Box<dyn Fn() -> BoxFuture<'static, Result<Vec<CoinPrice>>> + Send + Sync>;
+
+#[derive(Default)]
+struct ExporterState {
+    /// Last known price per (symbol, currency, provider), kept across failed refreshes.
+    prices: BTreeMap<(String, String, String), CoinPrice>,
+    last_refresh: Option<Instant>,
+    refresh_errors: u64,
+}
+
+/// Prometheus exporter that refreshes prices lazily on scrape.
+pub struct MetricsExporter {
+    fetch: PriceFetcher,
+    min_refresh: Duration,
+    state: Mutex<ExporterState>,
+}
+
+impl MetricsExporter {
+    pub fn new(fetch: PriceFetcher, min_refresh: Duration) -> Self {
+        Self {
+            fetch,
+            min_refresh,
+            state: Mutex::new(ExporterState::default()),
+        }
+    }
+
+    /// Refresh prices if the min-refresh interval has passed, then render all metrics.
+    ///
+    /// Provider failures keep the previous values and bump the error counter.
+    pub async fn scrape(&self) -> String {
+        let mut state = self.state.lock().await;
+
+        let due = state
+            .last_refresh
+            .is_none_or(|at| at.elapsed() >= self.min_refresh);
+        if due {
+            state.last_refresh = Some(Instant::now());
+            match (self.fetch)().await {
+                Ok(prices) => {
+                    debug!(count = prices.len(), "refreshed exporter prices");
+                    for price in prices {
+                        let key = (
+                            price.symbol.clone(),
+                            price.currency.clone(),
+                            price.provider.clone(),
+                        );
+                        state.prices.insert(key, price);
+                    }
+                }
+                Err(err) => {
+                    warn!(error = %err, "exporter price refresh failed; serving last values");
+                    state.refresh_errors += 1;
+                }
+            }
+        }
+
+        let prices: Vec<&CoinPrice> = state.prices.values().collect();
+        render_metrics(&prices, state.refresh_errors)
+    }
+}
+
+/// Serve `/metrics` on `addr` until the process is stopped.
+pub async fn serve(addr: SocketAddr, exporter: MetricsExporter) -> Result<()> {
+    let listener = TcpListener::bind(addr).await.map_err(|e| {
+        Error::Config(format!(
+            "failed to bind metrics listener on {}: {}",
+            addr, e
+        ))
+    })?;
+    info!(addr = %addr, "serving Prometheus metrics on {}", METRICS_PATH);
+
+    let exporter = Arc::new(exporter);
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(error = %err, "failed to accept metrics connection");
+                continue;
+            }
+        };
+
+        let exporter = Arc::clone(&exporter);
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle(req, Arc::clone(&exporter)));
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                debug!(peer = %peer, error = %err, "metrics connection closed with error");
+            }
+        });
+    }
+}
+
+async fn handle(
+    req: Request<Incoming>,
+    exporter: Arc<MetricsExporter>,
+) -> std::result::Result<Response<Full<Bytes>>, Infallible> {
+    let response = if req.method() == Method::GET && req.uri().path() == METRICS_PATH {
+        let body = exporter.scrape().await;
+        Response::builder()
+            .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE)
+            .body(Full::new(Bytes::from(body)))
+    } else {
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Full::new(Bytes::from_static(b"not found\n")))
+    };
+
+    Ok(response.expect("static response parts are valid"))
+}
+
+/// Render prices and the refresh error counter in the Prometheus text format.
+pub fn render_metrics(prices: &[&CoinPrice], refresh_errors: u64) -> String {
+    let mut out = String::new();
+
+    write_gauge(
+        &mut out,
+        "pricr_price",
+        "Latest price in the quote currency.",
+        prices,
+        |p| Some(p.price),
+    );
+    write_gauge(
+        &mut out,
+        "pricr_change_24h",
+        "Price change over the last 24 hours, in percent.",
+        prices,
+        |p| p.change_24h,
+    );
+    write_gauge(
+        &mut out,
+        "pricr_market_cap",
+        "Market capitalization in the quote currency.",
+        prices,
+        |p| p.market_cap,
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP pricr_refresh_errors_total Failed provider refreshes since startup."
+    );
+    let _ = writeln!(out, "# TYPE pricr_refresh_errors_total counter");
+    let _ = writeln!(out, "pricr_refresh_errors_total {}", refresh_errors);
+
+    out
+}
+
+fn write_gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    prices: &[&CoinPrice],
+    value: impl Fn(&CoinPrice) -> Option<f64>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    for price in prices {
+        if let Some(v) = value(price) {
+            let _ = writeln!(
+                out,
+                "{}{{symbol=\"{}\",currency=\"{}\",provider=\"{}\"}} {}",
+                name,
+                escape_label(&price.symbol),
+                escape_label(&price.currency.to_uppercase()),
+                escape_label(&price.provider),
+                v
+            );
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn price(symbol: &str, price: f64, change_24h: Option<f64>) -> CoinPrice {
+        CoinPrice {
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            price,
+            change_24h,
+            change_7d: None,
+            change_30d: None,
+            market_cap: None,
+            volume_24h: None,
+            circulating_supply: None,
+            rank: None,
+            currency: "usd".to_string(),
+            provider: "CoinGecko".to_string(),
+            timestamp: chrono::Utc::now(),
+            quote_time_estimated: false,
+            converted: false,
+            fx_rate: None,
+            ath: None,
+            ath_date: None,
+            atl: None,
+            atl_date: None,
+            bid: None,
+            ask: None,
+            since_date: None,
+            since_price: None,
+            since_change_pct: None,
+            delta_pct: None,
+            delta_since: None,
+            attribution: None,
+            prices: Vec::new(),
+            extended: None,
+        }
+    }
+
+    #[test]
+    fn render_metrics_writes_labeled_gauges_and_counter() {
+        let btc = price("BTC", 67_231.5, Some(-1.25));
+        let rendered = render_metrics(&[&btc], 3);
+
+        assert!(rendered.contains("# TYPE pricr_price gauge"));
+        assert!(rendered.contains(
+            "pricr_price{symbol=\"BTC\",currency=\"USD\",provider=\"CoinGecko\"} 67231.5"
+        ));
+        assert!(rendered.contains(
+            "pricr_change_24h{symbol=\"BTC\",currency=\"USD\",provider=\"CoinGecko\"} -1.25"
+        ));
+        assert!(!rendered.contains("pricr_market_cap{"));
+        assert!(rendered.contains("pricr_refresh_errors_total 3"));
+    }
+
+    #[test]
+    fn escape_label_escapes_quotes_and_backslashes() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[tokio::test]
+    async fn scrape_keeps_last_values_on_failure() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let fetch: PriceFetcher = Box::new(move || {
+            let call = counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if call == 0 {
+                    Ok(vec![price("BTC", 100.0, None)])
+                } else {
+                    Err(Error::api("CoinGecko", "rate limited"))
+                }
+            })
+        });
+        let exporter = MetricsExporter::new(fetch, Duration::ZERO);
+
+        let first = exporter.scrape().await;
+        assert!(first.contains("pricr_refresh_errors_total 0"));
+
+        let second = exporter.scrape().await;
+        assert!(
+            second.contains(
+                "pricr_price{symbol=\"BTC\",currency=\"USD\",provider=\"CoinGecko\"} 100"
+            )
+        );
+        assert!(second.contains("pricr_refresh_errors_total 1"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn scrape_respects_min_refresh_interval() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let fetch: PriceFetcher = Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(vec![price("ETH", 3_000.0, Some(0.5))]) })
+        });
+        let exporter = MetricsExporter::new(fetch, Duration::from_secs(3600));
+
+        exporter.scrape().await;
+        exporter.scrape().await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
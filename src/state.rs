@@ -0,0 +1,400 @@
+//! Data kept between runs: prices for `--delta` and CoinMarketCap credit usage.
+//!
+//! The last price of each symbol, currency, and provider is kept in
+//! `$XDG_STATE_HOME/pricr/delta.json` (or `~/.local/state/pricr/delta.json`). The
+//! file is replaced atomically and holds at most [`MAX_ENTRIES`] prices, dropping
+//! the ones looked up longest ago.
+//!
+//! CoinMarketCap credits are summed per UTC calendar month in [`CREDITS_FILE`] next
+//! to it. Each addition holds an exclusive lock on a sibling `.lock` file, so
+//! concurrent runs never lose each other's counts. Delta saves take the same kind
+//! of lock, so two runs never write one temporary file at once.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::provider::CoinPrice;
+
+/// Most prices the state file keeps.
+pub const MAX_ENTRIES: usize = 500;
+/// File name inside [`state_dir`].
+pub const DELTA_FILE: &str = "delta.json";
+/// CoinMarketCap credit ledger inside [`state_dir`].
+pub const CREDITS_FILE: &str = "cmc_credits.json";
+/// Months of credit usage the ledger keeps.
+pub const CREDIT_MONTHS_KEPT: usize = 12;
+
+/// A remembered price and when it was seen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub price: f64,
+    pub at: DateTime<Utc>,
+}
+
+/// Change from a remembered price to the current one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delta {
+    pub change_pct: f64,
+    pub since: DateTime<Utc>,
+}
+
+/// Last seen prices keyed by `SYMBOL:CURRENCY:provider`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeltaState {
+    #[serde(default)]
+    entries: BTreeMap<String, Entry>,
+}
+
+impl DeltaState {
+    /// Read the state at `path`; a missing file is an empty state, and an unreadable
+    /// one is ignored with a warning.
+    pub fn load(path: &Path) -> Self {
+        let raw = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(err) => {
+                warn!(path = %path.display(), error = %err, "ignoring unreadable delta state");
+                return Self::default();
+            }
+        };
+        serde_json::from_str(&raw).unwrap_or_else(|err| {
+            warn!(path = %path.display(), error = %err, "ignoring corrupt delta state");
+            Self::default()
+        })
+    }
+
+    /// Write the state to `path` through a temporary file and a rename, so readers
+    /// never see a partial file, holding an exclusive lock on `<path>.lock`.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let serialized = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        let _lock = lock(path)?;
+        replace(path, &serialized)
+    }
+
+    /// The remembered price for `price`'s symbol, currency, and provider.
+    pub fn get(&self, price: &CoinPrice) -> Option<Entry> {
+        self.entries.get(&key(price)).copied()
+    }
+
+    /// Change from the remembered price to `price`; `None` without a usable one.
+    pub fn delta(&self, price: &CoinPrice) -> Option<Delta> {
+        let entry = self.get(price).filter(|entry| entry.price > 0.0)?;
+        Some(Delta {
+            change_pct: (price.price - entry.price) / entry.price * 100.0,
+            since: entry.at,
+        })
+    }
+
+    /// Remember `prices` as seen at `at`, then drop the oldest entries beyond
+    /// [`MAX_ENTRIES`].
+    pub fn record(&mut self, prices: &[CoinPrice], at: DateTime<Utc>) {
+        for price in prices {
+            self.entries.insert(
+                key(price),
+                Entry {
+                    price: price.price,
+                    at,
+                },
+            );
+        }
+        if self.entries.len() > MAX_ENTRIES {
+            let mut by_age: Vec<(DateTime<Utc>, String)> = self
+                .entries
+                .iter()
+                .map(|(key, entry)| (entry.at, key.clone()))
+                .collect();
+            by_age.sort();
+            let excess = self.entries.len() - MAX_ENTRIES;
+            for (_, key) in by_age.into_iter().take(excess) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn key(price: &CoinPrice) -> String {
+    format!(
+        "{}:{}:{}",
+        price.symbol.to_ascii_uppercase(),
+        price.currency.to_ascii_uppercase(),
+        price.provider
+    )
+}
+
+/// Remove the state file at `path`; a missing file is already reset.
+pub fn reset(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Credits used per month, keyed `YYYY-MM` in UTC.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CreditLedger {
+    #[serde(default)]
+    months: BTreeMap<String, u64>,
+}
+
+impl CreditLedger {
+    /// Read the ledger at `path`; missing or unreadable files count as no usage.
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|err| {
+                warn!(path = %path.display(), error = %err, "ignoring corrupt credit ledger");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Credits recorded for the month containing `at`.
+    pub fn used(&self, at: DateTime<Utc>) -> u64 {
+        self.months.get(&month_key(at)).copied().unwrap_or(0)
+    }
+}
+
+/// Credit usage for one month against the configured `monthly_credits`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaUsage {
+    pub provider: String,
+    /// `YYYY-MM`, UTC.
+    pub month: String,
+    pub credits_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_credits: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_pct: Option<f64>,
+}
+
+impl QuotaUsage {
+    pub fn new(provider: &str, at: DateTime<Utc>, used: u64, limit: Option<u64>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            month: month_key(at),
+            credits_used: used,
+            monthly_credits: limit,
+            used_pct: limit
+                .filter(|&limit| limit > 0)
+                .map(|limit| used as f64 / limit as f64 * 100.0),
+        }
+    }
+}
+
+/// Add `credits` to the month containing `at` in the ledger at `path` and return the
+/// month's new total.
+///
+/// The read, update, and atomic replace happen under an exclusive lock on
+/// `<path>.lock`; only the [`CREDIT_MONTHS_KEPT`] latest months are kept.
+pub fn add_credits(path: &Path, credits: u64, at: DateTime<Utc>) -> std::io::Result<u64> {
+    let _lock = lock(path)?;
+    let mut ledger = CreditLedger::load(path);
+    let total = ledger.months.entry(month_key(at)).or_insert(0);
+    *total += credits;
+    let total = *total;
+    while ledger.months.len() > CREDIT_MONTHS_KEPT {
+        ledger.months.pop_first();
+    }
+    let serialized = serde_json::to_vec(&ledger).map_err(std::io::Error::other)?;
+    replace(path, &serialized)?;
+    Ok(total)
+}
+
+/// Create `path`'s directory and take an exclusive lock on `<path>.lock`, held until
+/// the returned file is dropped.
+fn lock(path: &Path) -> std::io::Result<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("lock"))?;
+    lock.lock()?;
+    Ok(lock)
+}
+
+/// Replace `path` with `contents` through `<path>.json.tmp` and a rename; callers hold
+/// [`lock`] so no other run writes the same temporary file.
+fn replace(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn month_key(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m").to_string()
+}
+
+/// Path of the CoinMarketCap credit ledger.
+pub fn credits_path() -> Option<PathBuf> {
+    Some(state_dir()?.join(CREDITS_FILE))
+}
+
+/// Path of the `--delta` state file.
+pub fn delta_path() -> Option<PathBuf> {
+    Some(state_dir()?.join(DELTA_FILE))
+}
+
+/// `$XDG_STATE_HOME/pricr` or `~/.local/state/pricr`.
+pub fn state_dir() -> Option<PathBuf> {
+    if let Ok(xdg_state_home) = std::env::var("XDG_STATE_HOME")
+        && !xdg_state_home.trim().is_empty()
+    {
+        return Some(PathBuf::from(xdg_state_home).join("pricr"));
+    }
+
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".local/state/pricr"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(symbol: &str, price: f64) -> CoinPrice {
+        CoinPrice {
+            symbol: symbol.into(),
+            price,
+            currency: "USD".into(),
+            provider: "CoinGecko".into(),
+            ..Default::default()
+        }
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pricr-state-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("nested").join(DELTA_FILE)
+    }
+
+    #[test]
+    fn delta_compares_with_the_same_symbol_currency_and_provider() {
+        let mut state = DeltaState::default();
+        assert_eq!(state.delta(&price("BTC", 101.0)), None);
+
+        state.record(&[price("btc", 100.0)], at(0));
+        let delta = state.delta(&price("BTC", 101.0)).unwrap();
+        assert!((delta.change_pct - 1.0).abs() < 1e-9);
+        assert_eq!(delta.since, at(0));
+
+        let eur = CoinPrice {
+            currency: "EUR".into(),
+            ..price("BTC", 90.0)
+        };
+        assert_eq!(state.delta(&eur), None);
+        let yahoo = CoinPrice {
+            provider: "Yahoo Finance".into(),
+            ..price("BTC", 90.0)
+        };
+        assert_eq!(state.delta(&yahoo), None);
+    }
+
+    #[test]
+    fn save_and_load_round_trip_atomically() {
+        let path = temp_path("roundtrip");
+        assert!(DeltaState::load(&path).is_empty());
+
+        let mut state = DeltaState::default();
+        state.record(&[price("BTC", 100.0), price("ETH", 5.0)], at(0));
+        state.save(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        assert_eq!(DeltaState::load(&path), state);
+
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(DeltaState::load(&path).is_empty());
+
+        reset(&path).unwrap();
+        assert!(!path.exists());
+        reset(&path).unwrap();
+    }
+
+    #[test]
+    fn record_evicts_the_least_recently_seen() {
+        let mut state = DeltaState::default();
+        for i in 0..MAX_ENTRIES as i64 {
+            state.record(&[price(&format!("C{i}"), 1.0)], at(i));
+        }
+        // Seen again, so it outlives the entries recorded after it.
+        state.record(&[price("C0", 2.0)], at(MAX_ENTRIES as i64));
+        state.record(&[price("NEW", 1.0)], at(MAX_ENTRIES as i64 + 1));
+
+        assert_eq!(state.len(), MAX_ENTRIES);
+        assert!(state.get(&price("C1", 0.0)).is_none());
+        assert_eq!(state.get(&price("C0", 0.0)).unwrap().price, 2.0);
+        assert!(state.get(&price("NEW", 0.0)).is_some());
+    }
+
+    #[test]
+    fn concurrent_saves_each_leave_a_whole_file() {
+        let path = temp_path("concurrent");
+        let savers: Vec<_> = (0..8)
+            .map(|n| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let mut state = DeltaState::default();
+                    state.record(&[price(&format!("C{}", n), 1.0)], at(0));
+                    for _ in 0..25 {
+                        state.save(&path).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for saver in savers {
+            saver.join().unwrap();
+        }
+        let raw = std::fs::read_to_string(&path).unwrap();
+        let saved: DeltaState = serde_json::from_str(&raw).unwrap();
+        assert_eq!(saved.entries.len(), 1);
+    }
+
+    #[test]
+    fn credits_add_up_per_month_across_concurrent_writers() {
+        let path = temp_path("credits").with_file_name(CREDITS_FILE);
+        let october = DateTime::parse_from_rfc3339("2026-10-31T23:59:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let november = october + chrono::Duration::minutes(2);
+
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        add_credits(&path, 2, october).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(add_credits(&path, 3, november).unwrap(), 3);
+
+        let ledger = CreditLedger::load(&path);
+        assert_eq!(ledger.used(october), 400);
+        assert_eq!(ledger.used(november), 3);
+
+        let usage = QuotaUsage::new("cmc", november, 8_500, Some(10_000));
+        assert_eq!(usage.month, "2026-11");
+        assert_eq!(usage.used_pct, Some(85.0));
+        assert_eq!(QuotaUsage::new("cmc", november, 5, None).used_pct, None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Wall-clock breakdown of one run for `--timings`.
+//!
+//! Collection is process-wide and off by default; [`enable`] turns it on, phases and
+//! HTTP requests record themselves, and [`take`] hands the result to whoever reports it.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+static TIMINGS: Mutex<Option<Collector>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Collector {
+    started: Instant,
+    phases: Vec<PhaseTiming>,
+    requests: Vec<RequestTiming>,
+}
+
+/// Where the time of a run went.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Timings {
+    /// Named steps of the run in the order they finished.
+    pub phases: Vec<PhaseTiming>,
+    /// Every provider HTTP request, in the order responses arrived.
+    pub requests: Vec<RequestTiming>,
+    /// Time since collection was enabled.
+    pub total_ms: f64,
+}
+
+/// One step of a run, e.g. `config` or `fetch`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub ms: f64,
+}
+
+/// One HTTP request to a provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestTiming {
+    pub provider: String,
+    /// Request URL without its query string.
+    pub url: String,
+    pub status: u16,
+    /// Time until the response headers arrived, including DNS, connect, and TLS.
+    pub ttfb_ms: f64,
+    /// Time spent downloading the body, when it was read as text.
+    pub body_ms: Option<f64>,
+}
+
+/// Start collecting timings for this process.
+pub fn enable() {
+    *lock() = Some(Collector {
+        started: Instant::now(),
+        phases: Vec::new(),
+        requests: Vec::new(),
+    });
+}
+
+/// Record a finished phase that started at `started`.
+pub fn record_phase(name: &str, started: Instant) {
+    if let Some(collector) = lock().as_mut() {
+        collector.phases.push(PhaseTiming {
+            name: name.to_string(),
+            ms: millis(started.elapsed()),
+        });
+    }
+}
+
+/// Record a request whose response headers arrived after `ttfb`.
+pub fn record_request(provider: &str, url: &reqwest::Url, status: u16, ttfb: Duration) {
+    if let Some(collector) = lock().as_mut() {
+        collector.requests.push(RequestTiming {
+            provider: provider.to_string(),
+            url: strip_query(url),
+            status,
+            ttfb_ms: millis(ttfb),
+            body_ms: None,
+        });
+    }
+}
+
+/// Attach a body download time to the latest request for `url` that has none yet.
+pub fn record_body(url: &reqwest::Url, elapsed: Duration) {
+    if let Some(collector) = lock().as_mut() {
+        let url = strip_query(url);
+        if let Some(request) = collector
+            .requests
+            .iter_mut()
+            .rev()
+            .find(|request| request.url == url && request.body_ms.is_none())
+        {
+            request.body_ms = Some(millis(elapsed));
+        }
+    }
+}
+
+/// Stop collecting and return what was recorded, or `None` when collection is off or
+/// the timings were already taken.
+pub fn take() -> Option<Timings> {
+    lock().take().map(|collector| Timings {
+        phases: collector.phases,
+        requests: collector.requests,
+        total_ms: millis(collector.started.elapsed()),
+    })
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Collector>> {
+    TIMINGS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 10_000.0).round() / 10.0
+}
+
+fn strip_query(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_query_drops_parameters_and_keeps_path() {
+        let url =
+            reqwest::Url::parse("https://api.example.com/v3/coins/markets?ids=bitcoin#x").unwrap();
+        assert_eq!(
+            strip_query(&url),
+            "https://api.example.com/v3/coins/markets"
+        );
+        assert_eq!(millis(Duration::from_micros(12_345)), 12.3);
+    }
+}
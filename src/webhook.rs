@@ -0,0 +1,191 @@
+//! `--webhook`: POST watch refreshes and triggered alerts as JSON.
+//!
+//! Payloads reuse the `--json` structs with an `event` field of `refresh` or
+//! `alert`. Failed deliveries are retried with backoff and then only logged, so a
+//! dead endpoint never stops the watch loop.
+
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Url};
+use serde::Serialize;
+use sha2::Sha256;
+use tracing::{debug, warn};
+
+use crate::alerts::AlertCondition;
+use crate::calc::PortfolioValue;
+use crate::config::WebhookConfig;
+use crate::error::{Error, Result};
+use crate::provider::CoinPrice;
+
+/// Header carrying `sha256=<hex HMAC of the body>` when `[webhook] secret` is set.
+pub const SIGNATURE_HEADER: &str = "X-Pricr-Signature";
+/// Retries after the first failed attempt.
+pub const MAX_RETRIES: u32 = 3;
+/// Wait before the first retry; each further retry doubles it.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// One webhook payload, tagged with its `event` name.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum Event<'a> {
+    /// A watch refresh, shaped like the `--json` lookup envelope.
+    Refresh {
+        prices: &'a [CoinPrice],
+        #[serde(skip_serializing_if = "<[_]>::is_empty")]
+        missing: &'a [String],
+        #[serde(skip_serializing_if = "Option::is_none")]
+        portfolio: Option<&'a PortfolioValue>,
+    },
+    /// An alert condition that started holding, with the price that triggered it.
+    Alert { alert: String, price: &'a CoinPrice },
+}
+
+impl<'a> Event<'a> {
+    /// The alert event for `condition` firing on `price`.
+    pub fn alert(condition: &AlertCondition, price: &'a CoinPrice) -> Self {
+        Self::Alert {
+            alert: condition.to_string(),
+            price,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Refresh { .. } => "refresh",
+            Self::Alert { .. } => "alert",
+        }
+    }
+}
+
+/// A configured webhook endpoint.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    client: Client,
+    url: Url,
+    headers: HeaderMap,
+    secret: Option<String>,
+    retry_delay: Duration,
+}
+
+impl Webhook {
+    /// Check `url` and the `[webhook]` headers; invalid ones are configuration errors.
+    pub fn new(client: Client, url: &str, config: &WebhookConfig) -> Result<Self> {
+        let url = Url::parse(url)
+            .ok()
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .ok_or_else(|| {
+                Error::Config(format!(
+                    "invalid --webhook URL '{}' -- expected an http:// or https:// URL",
+                    url
+                ))
+            })?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let invalid = |reason: &str| {
+                Error::Config(format!("invalid [webhook] header '{}': {}", name, reason))
+            };
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| invalid("not a valid header name"))?;
+            let value =
+                HeaderValue::from_str(value).map_err(|_| invalid("value is not valid text"))?;
+            headers.insert(header, value);
+        }
+
+        Ok(Self {
+            client,
+            url,
+            headers,
+            secret: config.secret.clone().filter(|secret| !secret.is_empty()),
+            retry_delay: DEFAULT_RETRY_DELAY,
+        })
+    }
+
+    /// Use `delay` before the first retry instead of [`DEFAULT_RETRY_DELAY`].
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Deliver `event`, logging a warning when every attempt failed.
+    pub async fn notify(&self, event: &Event<'_>) {
+        if let Err(err) = self.post(event).await {
+            warn!(
+                event = event.name(),
+                error = %err,
+                "webhook delivery failed; giving up on this event"
+            );
+        }
+    }
+
+    /// POST `event`, retrying up to [`MAX_RETRIES`] times on errors and non-2xx answers.
+    pub async fn post(&self, event: &Event<'_>) -> Result<()> {
+        let body = serde_json::to_vec(event)
+            .map_err(|e| Error::Parse(format!("JSON serialize: {}", e)))?;
+        let mut headers = self.headers.clone();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(signature) = self.signature(&body) {
+            headers.insert(
+                SIGNATURE_HEADER,
+                HeaderValue::from_str(&signature).expect("hex is a valid header value"),
+            );
+        }
+
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            match self.attempt(&headers, &body).await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < MAX_RETRIES => {
+                    attempt += 1;
+                    debug!(
+                        event = event.name(),
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %err,
+                        "webhook delivery failed; retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn attempt(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .headers(headers.clone())
+            .body(body.to_vec())
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(Error::Api {
+            provider: "webhook".to_string(),
+            status: Some(status.as_u16()),
+            message: format!("webhook {} returned {}", self.url, status),
+        })
+    }
+
+    /// `sha256=<hex>` HMAC of `body` under the configured secret.
+    pub fn signature(&self, body: &[u8]) -> Option<String> {
+        let secret = self.secret.as_deref()?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(body);
+        let digest: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Some(format!("sha256={}", digest))
+    }
+}
@@ -0,0 +1,215 @@
+//! `--auto-provider` end to end: crypto, stock and fiat symbols go to separate mock
+//! servers standing in for CoinGecko, Yahoo/Stooq and Frankfurter.
+
+mod common;
+
+use common::{Home, config_home, run_pricr, yahoo_chart};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+struct Servers {
+    crypto: MockServer,
+    stocks: MockServer,
+    fiat: MockServer,
+}
+
+impl Servers {
+    async fn start() -> Self {
+        Self {
+            crypto: MockServer::start().await,
+            stocks: MockServer::start().await,
+            fiat: MockServer::start().await,
+        }
+    }
+
+    fn config_home(&self, name: &str, extra: &str) -> Home {
+        config_home(
+            name,
+            &format!(
+                "[defaults]\nprovider_order = [\"coingecko\", \"yahoo\", \"stooq\", \"frankfurter\"]\n\n\
+                 [provider.coingecko]\nbase_url = \"{crypto}/api/v3\"\n\n\
+                 [provider.cmc]\nbase_url = \"{crypto}/cmc\"\n\n\
+                 [provider.yahoo]\nbase_url = \"{stocks}\"\n\n\
+                 [provider.stooq]\nbase_url = \"{stocks}\"\n\n\
+                 [provider.frankfurter]\nbase_url = \"{fiat}/v1\"\n\n{extra}",
+                crypto = self.crypto.uri(),
+                stocks = self.stocks.uri(),
+                fiat = self.fiat.uri(),
+            ),
+        )
+    }
+}
+
+#[tokio::test]
+async fn auto_provider_routes_each_asset_class_and_keeps_request_order() {
+    let servers = Servers::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("ids", "bitcoin"))
+        .and(query_param("vs_currency", "usd"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 60000.0 }
+        ])))
+        .expect(1)
+        .mount(&servers.crypto)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_chart(
+            "Apple Inc.",
+            190.0,
+            &[1735689600],
+            &[190.0],
+        )))
+        .expect(1)
+        .mount(&servers.stocks)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "EUR"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "date": "2026-02-20",
+            "rates": { "EUR": 0.8 }
+        })))
+        .expect(1)
+        .mount(&servers.fiat)
+        .await;
+    let home = servers.config_home("auto-provider", "");
+
+    let output = run_pricr(
+        &home,
+        &[
+            "--auto-provider",
+            "--json",
+            "-c",
+            "usd",
+            "aapl",
+            "eur",
+            "btc",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rows: Vec<(&str, &str)> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row["symbol"].as_str().unwrap(),
+                row["provider"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("AAPL", "Yahoo Finance"),
+            ("EUR", "Frankfurter/ECB"),
+            ("BTC", "CoinGecko"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn auto_provider_alias_overrides_crypto_preference() {
+    let servers = Servers::start().await;
+    Mock::given(method("GET"))
+        .and(path("/q/l/"))
+        .and(query_param("s", "link.us"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("LINK.US,20260220,220000,30.10,30.60,29.90,30.45,120000,"),
+        )
+        .expect(1)
+        .mount(&servers.stocks)
+        .await;
+    let home = servers.config_home(
+        "auto-provider-alias",
+        "[aliases]\nlink = { stooq = \"link.us\" }\n",
+    );
+
+    let output = run_pricr(&home, &["--auto-provider", "--json", "link"]).await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["symbol"], "LINK");
+    assert_eq!(json[0]["provider"], "Stooq");
+    assert!(servers.crypto.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn auto_provider_conflicts_with_explicit_provider() {
+    let servers = Servers::start().await;
+    let home = servers.config_home("auto-provider-conflict", "");
+
+    let output = run_pricr(&home, &["--auto-provider", "-p", "yahoo", "aapl"]).await;
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("--auto-provider"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[tokio::test]
+async fn attribution_credits_only_providers_that_ask_for_it() {
+    let servers = Servers::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 60000.0 },
+            { "id": "ethereum", "symbol": "eth", "name": "Ethereum", "current_price": 3000.0 }
+        ])))
+        .mount(&servers.crypto)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_chart(
+            "Apple Inc.",
+            190.0,
+            &[1735689600],
+            &[190.0],
+        )))
+        .mount(&servers.stocks)
+        .await;
+    let home = servers.config_home("attribution", "");
+    let args = ["--auto-provider", "btc", "eth", "aapl"];
+
+    let table = run_pricr(&home, &args).await;
+    let stdout = String::from_utf8_lossy(&table.stdout);
+    assert!(
+        table.status.success(),
+        "{}",
+        String::from_utf8_lossy(&table.stderr)
+    );
+    assert_eq!(stdout.matches("Data by CoinGecko").count(), 1, "{stdout}");
+    assert!(stdout.trim_end().ends_with("Data by CoinGecko"), "{stdout}");
+
+    let json = run_pricr(&home, &[&args[..], &["--json"]].concat()).await;
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(json[0]["attribution"], "Data by CoinGecko");
+    assert!(json[2].get("attribution").is_none(), "{json}");
+
+    let hidden = run_pricr(&home, &[&args[..], &["--no-attribution"]].concat()).await;
+    assert!(!String::from_utf8_lossy(&hidden.stdout).contains("Data by"));
+
+    let home = servers.config_home("attribution-config", "[display]\nattribution = false\n");
+    let json = run_pricr(&home, &[&args[..], &["--json"]].concat()).await;
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert!(json[0].get("attribution").is_none(), "{json}");
+}
@@ -0,0 +1,521 @@
+//! The whole `pricr` pipeline, from argument parsing to rendered output, run as a
+//! binary against mock servers standing in for CoinGecko, Yahoo Finance and
+//! Frankfurter. Provider URLs come from a `--config` file.
+
+mod common;
+
+use assert_cmd::Command;
+use common::{Home, config_home, std_command};
+use predicates::prelude::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+struct Servers {
+    coingecko: MockServer,
+    yahoo: MockServer,
+    frankfurter: MockServer,
+    home: Home,
+}
+
+impl Servers {
+    async fn start(name: &str) -> Self {
+        Self::start_with(name, "").await
+    }
+
+    /// Servers whose config file ends with the TOML in `extra`.
+    async fn start_with(name: &str, extra: &str) -> Self {
+        let coingecko = MockServer::start().await;
+        let yahoo = MockServer::start().await;
+        let frankfurter = MockServer::start().await;
+        let home = config_home(
+            &format!("e2e-{}", name),
+            &format!(
+                "[provider.coingecko]\nbase_url = \"{}/api/v3\"\n\n\
+                 [provider.yahoo]\nbase_url = \"{}\"\n\n\
+                 [provider.frankfurter]\nbase_url = \"{}/v1\"\n\n{}",
+                coingecko.uri(),
+                yahoo.uri(),
+                frankfurter.uri(),
+                extra,
+            ),
+        );
+        Self {
+            coingecko,
+            yahoo,
+            frankfurter,
+            home,
+        }
+    }
+
+    fn pricr(&self) -> Command {
+        let mut cmd = Command::from_std(std_command(&self.home));
+        cmd.arg("--config").arg(self.home.join("pricr.toml")).args([
+            "--no-cache",
+            "--color",
+            "never",
+        ]);
+        cmd
+    }
+
+    async fn mount_markets(&self, ids: &str, rows: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/coins/markets"))
+            .and(query_param("ids", ids))
+            .and(query_param("vs_currency", "usd"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(rows))
+            .mount(&self.coingecko)
+            .await;
+    }
+}
+
+fn bitcoin() -> serde_json::Value {
+    serde_json::json!({
+        "id": "bitcoin",
+        "symbol": "btc",
+        "name": "Bitcoin",
+        "current_price": 60000.0,
+        "price_change_percentage_24h": 2.5,
+        "market_cap": 1_200_000_000_000.0_f64
+    })
+}
+
+fn ethereum() -> serde_json::Value {
+    serde_json::json!({
+        "id": "ethereum",
+        "symbol": "eth",
+        "name": "Ethereum",
+        "current_price": 3000.0,
+        "price_change_percentage_24h": -1.25,
+        "market_cap": 360_000_000_000.0_f64
+    })
+}
+
+fn stdout_of(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn yahoo_daily_closes(days: &[(u32, f64)]) -> serde_json::Value {
+    let timestamps: Vec<i64> = days
+        .iter()
+        .map(|(day, _)| {
+            chrono::NaiveDate::from_ymd_opt(2025, 6, *day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        })
+        .collect();
+    let closes: Vec<f64> = days.iter().map(|(_, close)| *close).collect();
+    serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": { "currency": "USD", "shortName": "Apple Inc." },
+                    "timestamp": timestamps,
+                    "indicators": { "quote": [{ "close": closes }] }
+                }
+            ],
+            "error": null
+        }
+    })
+}
+
+#[tokio::test]
+async fn lookup_prints_a_table_in_request_order() {
+    let servers = Servers::start("table").await;
+    servers
+        .mount_markets(
+            "bitcoin,ethereum",
+            serde_json::json!([ethereum(), bitcoin()]),
+        )
+        .await;
+
+    let output = servers
+        .pricr()
+        .args(["-p", "coingecko", "btc", "eth"])
+        .assert()
+        .success()
+        .stderr("")
+        .get_output()
+        .clone();
+
+    let stdout = stdout_of(&output);
+    let rows: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("│ "))
+        .collect();
+    assert_eq!(rows.len(), 3, "{stdout}");
+    assert!(
+        rows[0].contains("Symbol") && rows[0].contains("24h Change"),
+        "{stdout}"
+    );
+    assert!(
+        rows[1].contains("BTC") && rows[1].contains("$60,000.00"),
+        "{stdout}"
+    );
+    assert!(
+        rows[1].contains("+2.50%") && rows[1].contains("$1.20T"),
+        "{stdout}"
+    );
+    assert!(
+        rows[2].contains("ETH") && rows[2].contains("-1.25%"),
+        "{stdout}"
+    );
+    assert_eq!(stdout.lines().last(), Some("Data by CoinGecko"));
+}
+
+#[tokio::test]
+async fn json_lookup_prints_a_plain_array_of_rows() {
+    let servers = Servers::start("json").await;
+    servers
+        .mount_markets(
+            "ethereum,bitcoin",
+            serde_json::json!([bitcoin(), ethereum()]),
+        )
+        .await;
+
+    let output = servers
+        .pricr()
+        .args(["-p", "coingecko", "--json", "eth", "btc"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rows = json.as_array().expect("complete lookups print an array");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["symbol"], "ETH");
+    assert_eq!(rows[0]["price"], 3000.0);
+    assert_eq!(rows[1]["symbol"], "BTC");
+    assert_eq!(rows[1]["change_24h"], 2.5);
+    assert_eq!(rows[1]["currency"], "USD");
+    assert_eq!(rows[1]["provider"], "CoinGecko");
+}
+
+#[tokio::test]
+async fn partial_json_lookup_reports_missing_symbols_and_strict_fails() {
+    let servers = Servers::start("missing").await;
+    servers
+        .mount_markets("bitcoin,typoxyz", serde_json::json!([bitcoin()]))
+        .await;
+
+    let output = servers
+        .pricr()
+        .args(["-p", "coingecko", "--json", "btc", "typoxyz"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("no price found for TYPOXYZ"))
+        .get_output()
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["prices"][0]["symbol"], "BTC");
+    assert_eq!(json["missing"], serde_json::json!(["TYPOXYZ"]));
+
+    servers
+        .pricr()
+        .args(["-p", "coingecko", "--strict", "btc", "typoxyz"])
+        .assert()
+        .code(4);
+}
+
+#[tokio::test]
+async fn calc_mode_converts_into_fiat_and_crypto_targets() {
+    let servers = Servers::start("calc").await;
+    servers
+        .mount_markets("bitcoin", serde_json::json!([bitcoin()]))
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "EUR"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "date": "2025-06-02",
+            "rates": { "EUR": 0.8 }
+        })))
+        .mount(&servers.frankfurter)
+        .await;
+
+    let output = servers
+        .pricr()
+        .args(["-p", "coingecko", "--json", "300usd", "eur", "btc"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let conversions = json.as_array().expect("conversions print an array");
+    assert_eq!(conversions.len(), 2);
+    assert_eq!(conversions[0]["to_symbol"], "EUR");
+    assert_eq!(conversions[0]["to_amount"], 240.0);
+    assert_eq!(conversions[1]["to_symbol"], "BTC");
+    assert_eq!(conversions[1]["to_amount"], 0.005);
+    assert_eq!(conversions[1]["from_currency"], "USD");
+
+    let output = servers
+        .pricr()
+        .args(["-p", "coingecko", "300usd", "eur", "btc"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let stdout = stdout_of(&output);
+    assert!(stdout.contains("->"), "{stdout}");
+    assert!(stdout.contains("0.005"), "{stdout}");
+}
+
+#[tokio::test]
+async fn chart_json_keeps_only_points_inside_the_date_window() {
+    let servers = Servers::start("chart").await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(yahoo_daily_closes(&[
+                (1, 100.0),
+                (2, 101.0),
+                (3, 102.0),
+                (4, 103.0),
+                (5, 104.0),
+            ])),
+        )
+        .mount(&servers.yahoo)
+        .await;
+
+    let output = servers
+        .pricr()
+        .args([
+            "-p",
+            "yahoo",
+            "--chart",
+            "--json",
+            "--start-date",
+            "2025-06-02",
+            "--end-date",
+            "2025-06-04",
+            "aapl",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let closes: Vec<f64> = json[0]["points"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|point| point["price"].as_f64().unwrap())
+        .collect();
+    assert_eq!(closes, [101.0, 102.0, 103.0]);
+    assert_eq!(json[0]["stats"]["start"], 101.0);
+    assert_eq!(json[0]["stats"]["end"], 103.0);
+}
+
+#[tokio::test]
+async fn fallback_rows_take_their_sparkline_from_the_provider_that_answered() {
+    let servers = Servers::start_with(
+        "fallback-sparkline",
+        "[defaults]\nprovider_order = [\"coingecko\", \"yahoo\"]\n",
+    )
+    .await;
+    let mut chart = yahoo_daily_closes(&[(1, 100.0), (2, 90.0), (3, 110.0)]);
+    let now = chrono::Utc::now().timestamp();
+    chart["chart"]["result"][0]["timestamp"] =
+        serde_json::json!([now - 2 * 86_400, now - 86_400, now - 60]);
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart))
+        .mount(&servers.yahoo)
+        .await;
+
+    let output = servers
+        .pricr()
+        .args(["--sparkline", "aapl"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+
+    let stdout = stdout_of(&output);
+    let row = stdout.lines().find(|line| line.contains("AAPL")).unwrap();
+    let history_requests = servers
+        .yahoo
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.query().is_some_and(|q| q.contains("period1")))
+        .count();
+    assert_eq!(history_requests, 1);
+    assert!(!row.contains("n/a"), "{stdout}");
+}
+
+#[tokio::test]
+async fn chart_rejects_windows_ending_in_the_future() {
+    let servers = Servers::start("chart-future").await;
+
+    servers
+        .pricr()
+        .args(["-p", "yahoo", "--chart", "--end-date", "2999-01-01", "aapl"])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "chart end date cannot be in the future",
+        ));
+    assert!(servers.yahoo.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_provider_is_a_usage_error() {
+    let servers = Servers::start("unknown-provider").await;
+
+    servers
+        .pricr()
+        .args(["-p", "nope", "btc"])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("unknown provider 'nope'"));
+    assert!(
+        servers
+            .coingecko
+            .received_requests()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn list_providers_json_reports_capabilities() {
+    let servers = Servers::start("list-providers").await;
+
+    let output = servers
+        .pricr()
+        .args(["--list-providers", "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stooq = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["id"] == "stooq")
+        .unwrap();
+    assert_eq!(stooq["name"], "Stooq");
+    assert_eq!(stooq["capabilities"]["prices"], true);
+    assert_eq!(stooq["capabilities"]["history_window"], true);
+    assert_eq!(stooq["capabilities"]["top"], false);
+}
+
+#[tokio::test]
+async fn unsupported_capability_fails_before_any_request() {
+    let servers = Servers::start("unsupported-capability").await;
+
+    servers
+        .pricr()
+        .args(["-p", "yahoo", "--top", "5"])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "provider 'yahoo' does not support top coins -- try coingecko or cmc",
+        ));
+    assert!(servers.yahoo.received_requests().await.unwrap().is_empty());
+    assert!(
+        servers
+            .coingecko
+            .received_requests()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn search_ranks_a_wider_pool_before_the_limit() {
+    let servers = Servers::start("search-ranking").await;
+    let quote = |symbol: &str, name: &str| {
+        serde_json::json!({
+            "symbol": symbol,
+            "longname": name,
+            "exchDisp": "NASDAQ",
+            "typeDisp": "Equity"
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/v1/finance/search"))
+        .and(query_param("q", "appl"))
+        .and(query_param("quotesCount", "6"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "quotes": [
+                quote("APLE", "Apple Hospitality REIT, Inc."),
+                quote("MAPPL", "Map Pleasant Holdings"),
+                quote("AAPL", "Apple Inc."),
+                quote("APPLX", "Appleseed Fund"),
+            ]
+        })))
+        .mount(&servers.yahoo)
+        .await;
+
+    let output = servers
+        .pricr()
+        .args(["-p", "yahoo", "--search", "appl", "--search-limit", "2"])
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let symbols: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| row["symbol"].as_str().unwrap())
+        .collect();
+    assert_eq!(symbols, ["APPLX", "AAPL"]);
+}
+
+#[tokio::test]
+async fn search_all_is_only_for_searches_across_providers() {
+    let servers = Servers::start("search-all").await;
+
+    servers
+        .pricr()
+        .args(["--search-all", "btc"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "--search-all only applies to ticker search",
+        ));
+    servers
+        .pricr()
+        .args(["--search-all", "-p", "yahoo", "--search", "btc"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+    assert!(servers.yahoo.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn provider_failure_exits_with_the_api_error_code() {
+    let servers = Servers::start("provider-error").await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("upstream down"))
+        .mount(&servers.coingecko)
+        .await;
+
+    servers
+        .pricr()
+        .args(["-p", "coingecko", "--json-errors", "btc"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(r#""kind":"api""#))
+        .stderr(predicate::str::contains("Error:"));
+}
@@ -0,0 +1,136 @@
+//! Harness shared by the CLI tests: a throwaway config home per test and a `pricr`
+//! command cut off from the developer's environment (API keys, keyring, state).
+
+// Each test binary compiles its own copy and uses a different subset.
+#![allow(dead_code)]
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// API keys and log filters a developer shell may export; a test that needs one sets it.
+const INHERITED_VARS: &[&str] = &["RUST_LOG", "COINGECKO_API_KEY", "COINMARKETCAP_API_KEY"];
+
+/// A temp directory holding `pricr.toml`, used as `XDG_CONFIG_HOME` and, under
+/// `state/` and `cache/`, `XDG_STATE_HOME` and `XDG_CACHE_HOME`. Removed on drop.
+pub struct Home(PathBuf);
+
+impl Home {
+    pub fn state_dir(&self) -> PathBuf {
+        self.0.join("state")
+    }
+}
+
+impl Deref for Home {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for Home {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Home {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Creates a fresh home for `name` whose `pricr.toml` is `config`. Leftovers from an
+/// earlier run with the same pid are removed first.
+pub fn config_home(name: &str, config: &str) -> Home {
+    let dir = std::env::temp_dir().join(format!("pricr-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("pricr.toml"), config).unwrap();
+    Home(dir)
+}
+
+/// A `[provider.<id>]` section pointing the provider at `base_url`.
+pub fn provider_config(id: &str, base_url: &str) -> String {
+    format!("[provider.{}]\nbase_url = \"{}\"\n", id, base_url)
+}
+
+/// A home whose Yahoo Finance requests go to `base_url`.
+pub fn yahoo_home(name: &str, base_url: &str) -> Home {
+    config_home(name, &provider_config("yahoo", base_url))
+}
+
+/// The `pricr` binary with `home` as its config, state and cache home, no keyring
+/// and none of [`INHERITED_VARS`].
+pub fn std_command(home: &Path) -> std::process::Command {
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_pricr"));
+    command
+        .env("XDG_CONFIG_HOME", home)
+        .env("XDG_STATE_HOME", home.join("state"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("PRICR_NO_KEYRING", "1");
+    for var in INHERITED_VARS {
+        command.env_remove(var);
+    }
+    command
+}
+
+/// [`std_command`] for async tests.
+pub fn command(home: &Path) -> tokio::process::Command {
+    std_command(home).into()
+}
+
+/// Runs `pricr <args> --no-cache --color never` in `home`; the flags trail so `args`
+/// may start with a subcommand.
+pub async fn run_pricr(home: &Path, args: &[&str]) -> Output {
+    command(home)
+        .args(args)
+        .args(["--no-cache", "--color", "never"])
+        .output()
+        .await
+        .expect("pricr should run")
+}
+
+/// A Yahoo Finance chart response for `name` quoting `price`, with one close per
+/// timestamp.
+pub fn yahoo_chart(
+    name: &str,
+    price: f64,
+    timestamps: &[i64],
+    closes: &[f64],
+) -> serde_json::Value {
+    serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": "USD",
+                        "shortName": name,
+                        "regularMarketPrice": price
+                    },
+                    "timestamp": timestamps,
+                    "indicators": { "quote": [{ "close": closes }] }
+                }
+            ],
+            "error": null
+        }
+    })
+}
+
+/// Serves Apple at 190.5 from the Yahoo Finance chart endpoint.
+pub async fn mount_aapl(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_chart(
+            "Apple Inc.",
+            190.5,
+            &[1735689600],
+            &[190.5],
+        )))
+        .mount(server)
+        .await;
+}
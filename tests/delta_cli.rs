@@ -0,0 +1,60 @@
+//! `--delta` across runs, with the state directory in a temp dir and a mock standing
+//! in for Yahoo Finance.
+
+mod common;
+
+use common::{Home, run_pricr, yahoo_chart, yahoo_home};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Runs `pricr aapl --json -p yahoo` with `args` and returns the parsed output.
+async fn run_delta(home: &Home, args: &[&str]) -> serde_json::Value {
+    let args = [args, &["aapl", "--json", "-p", "yahoo"]].concat();
+    let output = run_pricr(home, &args).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn aapl(price: f64) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(yahoo_chart(
+        "Apple Inc.",
+        price,
+        &[1735689600],
+        &[price],
+    ))
+}
+
+#[tokio::test]
+async fn delta_compares_with_the_previous_run_until_reset() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(aapl(200.0))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(aapl(202.0))
+        .mount(&server)
+        .await;
+    let home = yahoo_home("delta", &server.uri());
+
+    let first = run_delta(&home, &["--delta"]).await;
+    assert_eq!(first[0]["symbol"], "AAPL");
+    assert!(first[0].get("delta_pct").is_none(), "{first}");
+    assert!(home.state_dir().join("pricr/delta.json").exists());
+
+    let second = run_delta(&home, &["--delta"]).await;
+    let pct = second[0]["delta_pct"].as_f64().unwrap();
+    assert!((pct - 1.0).abs() < 1e-9, "{second}");
+    assert!(second[0]["delta_since"].is_string());
+
+    let reset = run_delta(&home, &["--delta", "--delta-reset"]).await;
+    assert!(reset[0].get("delta_pct").is_none(), "{reset}");
+}
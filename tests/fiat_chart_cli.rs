@@ -0,0 +1,167 @@
+//! Fiat chart mode end to end: the binary resolves Frankfurter through the provider
+//! registry, so a `[provider.frankfurter] base_url` override points it at a mock.
+
+mod common;
+
+use common::{config_home, provider_config, run_pricr};
+use wiremock::matchers::{method, path_regex, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// USD to EUR at 0.87, 0.88 and 0.89 from 2025-06-02.
+async fn mount_usd_eur(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/v1/\d{4}-\d{2}-\d{2}\.\.\d{4}-\d{2}-\d{2}$"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "EUR"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "start_date": "2025-06-02",
+            "end_date": "2025-06-04",
+            "rates": {
+                "2025-06-02": { "EUR": 0.87 },
+                "2025-06-03": { "EUR": 0.88 },
+                "2025-06-04": { "EUR": 0.89 }
+            }
+        })))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn fiat_chart_reads_rates_from_configured_frankfurter() {
+    let server = MockServer::start().await;
+    mount_usd_eur(&server).await;
+    let home = config_home(
+        "fiat-chart",
+        &provider_config("frankfurter", &format!("{}/v1", server.uri())),
+    );
+
+    let output = run_pricr(
+        &home,
+        &[
+            "--chart",
+            "--json",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-05",
+            "usd",
+            "eur",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let series = &json[0];
+    assert_eq!(series["symbol"], "EUR");
+    assert_eq!(series["currency"], "USD");
+    assert_eq!(series["points"].as_array().unwrap().len(), 3);
+    assert_eq!(series["points"][2]["price"], 0.89);
+}
+
+#[tokio::test]
+async fn fiat_chart_rejects_providers_without_fiat_history() {
+    let server = MockServer::start().await;
+    let home = config_home(
+        "fiat-chart-yahoo",
+        &provider_config("frankfurter", &format!("{}/v1", server.uri())),
+    );
+
+    let output = run_pricr(&home, &["--chart", "--provider", "yahoo", "usd", "eur"]).await;
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("provider 'yahoo' does not support fiat history -- try frankfurter"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn fiat_chart_markdown_prints_a_stats_row_instead_of_points() {
+    let server = MockServer::start().await;
+    mount_usd_eur(&server).await;
+    let home = config_home(
+        "fiat-chart-markdown",
+        &provider_config("frankfurter", &format!("{}/v1", server.uri())),
+    );
+
+    let output = run_pricr(
+        &home,
+        &[
+            "--chart",
+            "--format",
+            "markdown",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-05",
+            "usd",
+            "eur",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "{stdout}");
+    assert!(
+        lines[0].starts_with("| Symbol | Start | End | Change |"),
+        "{stdout}"
+    );
+    assert!(lines[2].starts_with("| EUR | "), "{stdout}");
+    assert!(lines[2].contains("+2.30%"), "{stdout}");
+}
+
+#[tokio::test]
+async fn fiat_chart_exports_an_html_report_with_an_svg_chart() {
+    let server = MockServer::start().await;
+    mount_usd_eur(&server).await;
+    let home = config_home(
+        "fiat-chart-html",
+        &provider_config("frankfurter", &format!("{}/v1", server.uri())),
+    );
+    let report = home.join("report.html");
+    let _ = std::fs::remove_file(&report);
+
+    let output = run_pricr(
+        &home,
+        &[
+            "--chart",
+            "--json",
+            "--export-html",
+            report.to_str().unwrap(),
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-05",
+            "usd",
+            "eur",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let html = std::fs::read_to_string(&report).unwrap();
+    assert_eq!(html.matches("<svg ").count(), 1);
+    assert!(html.contains("Frankfurter/ECB"), "{html}");
+    assert!(html.contains("Range 2025-06-01..2025-06-05"), "{html}");
+}
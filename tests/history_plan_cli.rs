@@ -0,0 +1,56 @@
+//! `--sparkline` and `--since` together, counting the requests a mock standing in for
+//! Yahoo Finance receives.
+
+mod common;
+
+use chrono::{Duration, NaiveTime, Utc};
+use common::{run_pricr, yahoo_chart, yahoo_home};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A chart with one noon close for each of the last seven days.
+fn aapl_week() -> ResponseTemplate {
+    let today = Utc::now().date_naive();
+    let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+    let timestamps: Vec<i64> = (1..=7)
+        .rev()
+        .map(|ago| {
+            (today - Duration::days(ago))
+                .and_time(noon)
+                .and_utc()
+                .timestamp()
+        })
+        .collect();
+    let closes: Vec<f64> = (0..7).map(|day| 190.0 + f64::from(day)).collect();
+    ResponseTemplate::new(200).set_body_json(yahoo_chart("Apple Inc.", 200.0, &timestamps, &closes))
+}
+
+#[tokio::test]
+async fn since_inside_the_sparkline_window_shares_its_history_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(aapl_week())
+        .mount(&server)
+        .await;
+    let home = yahoo_home("history-plan", &server.uri());
+    let since = (Utc::now().date_naive() - Duration::days(3)).to_string();
+
+    let output = run_pricr(
+        &home,
+        &["aapl", "--sparkline", "--since", &since, "-p", "yahoo"],
+    )
+    .await;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains(&format!("Price on {since}")), "{stdout}");
+    assert!(stdout.contains("194"), "{stdout}");
+
+    // One quote request and one sparkline history; the since close comes from the latter.
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2, "{requests:#?}");
+}
@@ -0,0 +1,67 @@
+//! Ctrl-C during a slow chart fetch: the binary gets `SIGINT` while its requests to a
+//! mock standing in for Yahoo Finance are still waiting for an answer.
+#![cfg(unix)]
+
+mod common;
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use common::{command, yahoo_home};
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn sigint_aborts_in_flight_requests_and_exits_130() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path_regex(r"^/v8/finance/chart/"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "chart": { "result": [], "error": null } }))
+                .set_delay(Duration::from_secs(30)),
+        )
+        .mount(&server)
+        .await;
+    let home = yahoo_home("interrupt", &server.uri());
+
+    let child = command(&home)
+        .args(["--chart", "aapl", "msft", "nvda", "-p", "yahoo"])
+        .args(["--no-cache", "--color", "never"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("pricr should start");
+
+    // Interrupt only once the requests are in flight.
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while server.received_requests().await.unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("pricr should reach the mock");
+    let pid = child.id().unwrap().to_string();
+    let status = std::process::Command::new("kill")
+        .args(["-INT", &pid])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = tokio::time::timeout(Duration::from_secs(5), child.wait_with_output())
+        .await
+        .expect("pricr should stop well before the mock answers")
+        .unwrap();
+    assert_eq!(output.status.code(), Some(130));
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert!(
+        !String::from_utf8_lossy(&output.stderr).contains("Error:"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
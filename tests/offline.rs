@@ -0,0 +1,82 @@
+//! `--offline`: requests are answered from the response cache or fail without ever
+//! reaching the network. Offline mode is process-wide, so it has its own test binary.
+
+use pricr::config::HttpConfig;
+use pricr::error::Error;
+use pricr::provider::PriceProvider;
+use pricr::provider::frankfurter::Frankfurter;
+use pricr::provider::yahoo::YahooFinance;
+use pricr::provider::{cache, http};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn offline_serves_cache_and_sends_no_requests() {
+    let dir = std::env::temp_dir().join(format!("pricr-offline-test-{}", std::process::id()));
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "date": chrono::Utc::now().date_naive().format("%Y-%m-%d").to_string(),
+            "rates": { "EUR": 0.9 }
+        })))
+        .mount(&server)
+        .await;
+    let client = http::HttpClient::default().with_cache_dir(&dir);
+    let frankfurter = Frankfurter::with_client(client.clone(), format!("{}/v1", server.uri()));
+    let eur = vec!["eur".to_string()];
+
+    // Warm the cache online.
+    frankfurter.get_rates("usd", &eur).await.unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    cache::set_offline(true);
+    let rates = frankfurter.get_rates("usd", &eur).await.unwrap();
+    assert_eq!(rates["EUR"], 0.9);
+
+    let err = frankfurter
+        .get_rates("usd", &["gbp".to_string()])
+        .await
+        .unwrap_err();
+    match err {
+        Error::Config(message) => {
+            assert!(
+                message.starts_with("offline: no cached data for frankfurter/latest:"),
+                "{message}"
+            );
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    let yahoo = YahooFinance::with_client(client, server.uri());
+    let err = yahoo
+        .get_prices(&["aapl".to_string()], "usd")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("needs a copy under"), "{err}");
+
+    // Concurrent misses each name their own entry.
+    let (btc, eth) = (vec!["btc".to_string()], vec!["eth".to_string()]);
+    let (btc, eth) = tokio::join!(yahoo.get_prices(&btc, "usd"), yahoo.get_prices(&eth, "usd"));
+    assert!(btc.unwrap_err().to_string().contains(":BTC-USD "));
+    assert!(eth.unwrap_err().to_string().contains(":ETH-USD "));
+
+    // No connection pool is built offline, so the proxy is never even parsed and
+    // requests fail before one is built.
+    let client = http::build_client(&HttpConfig {
+        proxy: Some("not a proxy url".into()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(client.client().is_none());
+    let err = client.get("Yahoo Finance", server.uri()).unwrap_err();
+    assert!(
+        err.to_string().contains("this request is never cached"),
+        "{err}"
+    );
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
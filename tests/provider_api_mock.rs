@@ -1,35 +1,66 @@
+use pricr::config::HttpConfig;
 use pricr::error::Error;
-use pricr::provider::coingecko::CoinGecko;
-use pricr::provider::coinmarketcap::CoinMarketCap;
+use pricr::provider::coingecko::{self, ApiTier, CoinGecko};
+use pricr::provider::coinmarketcap::{self, CoinMarketCap};
+use pricr::provider::fiat::FiatRateSource;
 use pricr::provider::frankfurter::Frankfurter;
+use pricr::provider::http;
+use pricr::provider::open_er_api::OpenErApi;
 use pricr::provider::stooq::Stooq;
 use pricr::provider::yahoo::YahooFinance;
-use pricr::provider::{HistoryInterval, PriceProvider};
+use pricr::provider::{
+    HistoryEventKind, HistoryInterval, PriceProvider, ResolvedInterval, with_currency_fallback,
+};
 use wiremock::matchers::{header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
+async fn mock_server() -> MockServer {
+    // wiremock reuses pooled servers, so cached responses keyed by base URL would leak between tests.
+    pricr::provider::set_cache_enabled(false);
+    MockServer::start().await
+}
+
 #[tokio::test]
 async fn coingecko_provider_fetches_and_parses_mocked_response() {
-    let server = MockServer::start().await;
-    let response = serde_json::json!({
-        "bitcoin": {
-            "usd": 50000.0,
-            "usd_24h_change": 1.5,
-            "usd_market_cap": 999999999.0
+    let server = mock_server().await;
+    // Markets are returned by market cap, not request order.
+    let response = serde_json::json!([
+        {
+            "id": "ethereum",
+            "symbol": "eth",
+            "name": "Ethereum",
+            "current_price": 3000.0,
+            "market_cap": 500000000.0,
+            "price_change_percentage_24h": -0.5,
+            "price_change_percentage_24h_in_currency": -0.5,
+            "price_change_percentage_7d_in_currency": 4.25,
+            "price_change_percentage_30d_in_currency": null
         },
-        "ethereum": {
-            "usd": 3000.0,
-            "usd_24h_change": -0.5,
-            "usd_market_cap": 500000000.0
+        {
+            "id": "bitcoin",
+            "symbol": "btc",
+            "name": "Bitcoin",
+            "current_price": 50000.0,
+            "market_cap": 999999999.0,
+            "market_cap_rank": 1,
+            "total_volume": 25000000.0,
+            "circulating_supply": 19700000.0,
+            "price_change_percentage_24h": 1.5,
+            "price_change_percentage_24h_in_currency": 1.5,
+            "price_change_percentage_7d_in_currency": -3.0,
+            "price_change_percentage_30d_in_currency": 12.5,
+            "ath": 73738.0,
+            "ath_date": "2024-03-14T07:10:36.635Z",
+            "atl": 67.81,
+            "atl_date": "2013-07-06T00:00:00.000Z"
         }
-    });
+    ]);
 
     Mock::given(method("GET"))
-        .and(path("/api/v3/simple/price"))
+        .and(path("/api/v3/coins/markets"))
         .and(query_param("ids", "bitcoin,ethereum"))
-        .and(query_param("vs_currencies", "usd"))
-        .and(query_param("include_24hr_change", "true"))
-        .and(query_param("include_market_cap", "true"))
+        .and(query_param("vs_currency", "usd"))
+        .and(query_param("price_change_percentage", "24h,7d,30d"))
         .respond_with(ResponseTemplate::new(200).set_body_json(response))
         .mount(&server)
         .await;
@@ -43,7 +74,19 @@ async fn coingecko_provider_fetches_and_parses_mocked_response() {
     assert_eq!(prices[0].name, "Bitcoin");
     assert!((prices[0].price - 50000.0).abs() < f64::EPSILON);
     assert_eq!(prices[0].change_24h, Some(1.5));
+    assert_eq!(prices[0].change_7d, Some(-3.0));
+    assert_eq!(prices[0].change_30d, Some(12.5));
     assert_eq!(prices[0].market_cap, Some(999999999.0));
+    assert_eq!(prices[0].volume_24h, Some(25000000.0));
+    assert_eq!(prices[0].circulating_supply, Some(19700000.0));
+    assert_eq!(prices[0].rank, Some(1));
+    assert_eq!(prices[0].ath, Some(73738.0));
+    assert_eq!(
+        prices[0].ath_date.map(|date| date.to_rfc3339()),
+        Some("2024-03-14T07:10:36.635+00:00".to_string())
+    );
+    assert_eq!(prices[0].atl, Some(67.81));
+    assert!(prices[0].atl_date.is_some());
     assert_eq!(prices[0].currency, "USD");
     assert_eq!(prices[0].provider, "CoinGecko");
 
@@ -51,35 +94,304 @@ async fn coingecko_provider_fetches_and_parses_mocked_response() {
     assert_eq!(prices[1].name, "Ethereum");
     assert!((prices[1].price - 3000.0).abs() < f64::EPSILON);
     assert_eq!(prices[1].change_24h, Some(-0.5));
+    assert_eq!(prices[1].change_7d, Some(4.25));
+    assert_eq!(prices[1].change_30d, None);
     assert_eq!(prices[1].market_cap, Some(500000000.0));
+    assert_eq!(prices[1].volume_24h, None);
+    assert_eq!(prices[1].rank, None);
+    assert_eq!(prices[1].ath, None);
     assert_eq!(prices[1].currency, "USD");
     assert_eq!(prices[1].provider, "CoinGecko");
 }
 
 #[tokio::test]
-async fn coingecko_provider_returns_api_error_on_non_success_status() {
-    let server = MockServer::start().await;
+async fn coingecko_provider_reports_rate_limit_with_retry_after() {
+    let server = mock_server().await;
 
     Mock::given(method("GET"))
-        .and(path("/api/v3/simple/price"))
+        .and(path("/api/v3/coins/markets"))
         .and(query_param("ids", "bitcoin"))
-        .and(query_param("vs_currencies", "usd"))
-        .and(query_param("include_24hr_change", "true"))
-        .and(query_param("include_market_cap", "true"))
-        .respond_with(ResponseTemplate::new(429).set_body_string("rate limited"))
+        .and(query_param("vs_currency", "usd"))
+        .and(query_param("price_change_percentage", "24h,7d,30d"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "30")
+                .set_body_string("rate limited"),
+        )
         .mount(&server)
         .await;
 
     let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
     let symbols = vec!["btc".to_string()];
-    let result = provider.get_prices(&symbols, "usd").await;
+    let err = provider.get_prices(&symbols, "usd").await.unwrap_err();
+
+    assert!(
+        matches!(
+            err,
+            Error::RateLimited { ref provider, retry_after: Some(wait) }
+                if provider == "CoinGecko" && wait == std::time::Duration::from_secs(30)
+        ),
+        "{err:?}"
+    );
+    assert_eq!(
+        err.to_string(),
+        "API error: CoinGecko returned 429 Too Many Requests (retry after 30s)"
+    );
+    assert_eq!(
+        err.hint().as_deref(),
+        Some("try --provider yahoo or wait 30s")
+    );
+}
+
+/// Records when each request reached the mock server.
+struct RecordArrival {
+    arrivals: std::sync::Arc<std::sync::Mutex<Vec<std::time::Instant>>>,
+    body: serde_json::Value,
+}
+
+impl wiremock::Respond for RecordArrival {
+    fn respond(&self, _: &wiremock::Request) -> ResponseTemplate {
+        self.arrivals
+            .lock()
+            .unwrap()
+            .push(std::time::Instant::now());
+        ResponseTemplate::new(200).set_body_json(self.body.clone())
+    }
+}
+
+#[tokio::test]
+async fn coingecko_provider_spaces_requests_under_client_rate_limit() {
+    let server = mock_server().await;
+    let arrivals = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(RecordArrival {
+            arrivals: arrivals.clone(),
+            body: serde_json::json!([
+                { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 50000.0 }
+            ]),
+        })
+        .mount(&server)
+        .await;
+
+    // 600/min without burst: one request every 100ms.
+    let client = http::HttpClient::default().with_rate_limit(http::RateLimit {
+        per_min: 600,
+        burst: 1,
+        max_wait: std::time::Duration::from_secs(1),
+    });
+    let provider = CoinGecko::with_client(client, format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string()];
+    for _ in 0..3 {
+        provider.get_prices(&symbols, "usd").await.unwrap();
+    }
+
+    let arrivals = arrivals.lock().unwrap();
+    assert_eq!(arrivals.len(), 3);
+    for pair in arrivals.windows(2) {
+        let gap = pair[1].duration_since(pair[0]);
+        assert!(
+            gap >= std::time::Duration::from_millis(90),
+            "requests only {gap:?} apart"
+        );
+    }
+}
+
+#[tokio::test]
+async fn coingecko_provider_fails_fast_when_rate_limit_wait_is_too_long() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 50000.0 }
+        ])))
+        .mount(&server)
+        .await;
+
+    let client = http::HttpClient::default().with_rate_limit(http::RateLimit {
+        per_min: 1,
+        burst: 1,
+        max_wait: std::time::Duration::from_secs(5),
+    });
+    let provider = CoinGecko::with_client(client, format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string()];
+    provider.get_prices(&symbols, "usd").await.unwrap();
+    let err = provider.get_prices(&symbols, "usd").await.unwrap_err();
+
+    assert!(
+        matches!(err, Error::RateLimited { ref provider, retry_after: Some(_) } if provider == "CoinGecko"),
+        "{err:?}"
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn coingecko_provider_sends_api_key_header_for_tier() {
+    for (tier, header_name) in [
+        (ApiTier::Demo, "x-cg-demo-api-key"),
+        (ApiTier::Pro, "x-cg-pro-api-key"),
+    ] {
+        let server = mock_server().await;
+        let response = serde_json::json!([
+            { "id": "bitcoin", "name": "Bitcoin", "current_price": 50000.0 }
+        ]);
+
+        Mock::given(method("GET"))
+            .and(path("/api/v3/coins/markets"))
+            .and(header(header_name, "CG-test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(response))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()))
+            .with_api_key("CG-test-key", tier);
+        let prices = provider
+            .get_prices(&["btc".to_string()], "usd")
+            .await
+            .unwrap_or_else(|err| panic!("{tier:?}: {err}"));
+
+        assert_eq!(prices[0].price, 50000.0);
+    }
+}
+
+#[tokio::test]
+async fn provider_registry_applies_currency_key_and_base_url() {
+    let server = mock_server().await;
+    let response = serde_json::json!([
+        { "id": "bitcoin", "name": "Bitcoin", "current_price": 45000.0 }
+    ]);
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("vs_currency", "eur"))
+        .and(header("x-cg-demo-api-key", "CG-test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let registry = pricr::ProviderRegistry::builder()
+        .currency("EUR")
+        .api_key("coingecko", "CG-test-key")
+        .base_url("coingecko", format!("{}/api/v3", server.uri()))
+        .build()
+        .unwrap();
+    let prices = registry
+        .get_prices("coingecko", &["btc".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(prices[0].price, 45000.0);
+
+    let err = registry
+        .get_prices("nope", &["btc".to_string()])
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Config(_)), "{err}");
+}
+
+#[tokio::test]
+async fn provider_registry_rejects_bad_settings_instead_of_panicking() {
+    let bad_url = pricr::ProviderRegistry::builder()
+        .base_url("yahoo", "localhost:8080")
+        .build();
+    assert!(matches!(bad_url, Err(Error::Config(_))));
+
+    let bad_proxy = pricr::ProviderRegistry::builder()
+        .http(HttpConfig {
+            proxy: Some("not a proxy url".into()),
+            ..HttpConfig::default()
+        })
+        .build();
+    assert!(matches!(bad_proxy, Err(Error::Config(_))));
+
+    let empty_currency = pricr::ProviderRegistry::builder().currency(" ").build();
+    assert!(matches!(empty_currency, Err(Error::Config(_))));
+}
+
+#[tokio::test]
+async fn coingecko_provider_reports_timeout_as_api_error() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!([]))
+                .set_delay(std::time::Duration::from_secs(3)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = http::build_client(&HttpConfig {
+        timeout_secs: Some(1),
+        ..Default::default()
+    })
+    .unwrap();
+    let provider = CoinGecko::with_client(client, format!("{}/api/v3", server.uri()));
+    let result = provider.get_prices(&["btc".to_string()], "usd").await;
+
+    assert!(
+        matches!(result, Err(Error::Api { ref message, status: None, .. }) if message == "request to CoinGecko timed out after 1s"),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn provider_requests_send_configured_user_agent() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(header(
+            "user-agent",
+            "pricr-test/1.0 (+https://example.com/contact)",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = http::build_client(&HttpConfig {
+        user_agent: Some("pricr-test/1.0".to_string()),
+        contact: Some("https://example.com/contact".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    let provider = CoinGecko::with_client(client, format!("{}/api/v3", server.uri()));
+    let _ = provider.get_prices(&["btc".to_string()], "usd").await;
+}
+
+#[tokio::test]
+async fn timings_record_each_provider_request() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .mount(&server)
+        .await;
 
-    assert!(matches!(result, Err(Error::Api(ref msg)) if msg.contains("429")));
+    pricr::timings::enable();
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let _ = provider.get_prices(&["btc".to_string()], "usd").await;
+    let timings = pricr::timings::take().expect("timings were enabled");
+
+    // Other tests may run concurrently, so look only for this server's request.
+    let request = timings
+        .requests
+        .iter()
+        .find(|request| request.url.starts_with(&server.uri()))
+        .expect("request to the mock server was recorded");
+    assert_eq!(request.provider, "CoinGecko");
+    assert_eq!(request.status, 200);
+    assert!(request.url.ends_with("/api/v3/coins/markets"));
+    assert!(request.body_ms.is_some());
 }
 
 #[tokio::test]
 async fn coingecko_provider_fetches_history_for_chart_mode() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "prices": [
             [1700000000000_i64, 40000.0],
@@ -112,9 +424,390 @@ async fn coingecko_provider_fetches_history_for_chart_mode() {
     assert!((history[0].points[2].price - 40500.0).abs() < f64::EPSILON);
 }
 
+#[tokio::test]
+async fn coingecko_provider_requests_only_recent_window_days() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "prices": [[1700000000000_i64, 40000.0]]
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/bitcoin/market_chart"))
+        .and(query_param("days", "10"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string()];
+    let end = chrono::Utc::now();
+    let start = (end.date_naive() - chrono::Duration::days(10))
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+    provider
+        .get_price_history_window(&symbols, "usd", Some(start), end, HistoryInterval::Daily)
+        .await
+        .expect("recent window should use market_chart days");
+}
+
+#[tokio::test]
+async fn coingecko_provider_fetches_past_window_by_range() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "prices": [
+            [1577836800000_i64, 7200.0],
+            [1609372800000_i64, 28900.0]
+        ]
+    });
+    let start = chrono::NaiveDate::from_ymd_opt(2020, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+    let end = chrono::NaiveDate::from_ymd_opt(2020, 12, 31)
+        .unwrap()
+        .and_hms_opt(23, 59, 59)
+        .unwrap()
+        .and_utc();
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/bitcoin/market_chart/range"))
+        .and(query_param("vs_currency", "usd"))
+        .and(query_param("from", start.timestamp().to_string()))
+        .and(query_param("to", end.timestamp().to_string()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string()];
+    let history = provider
+        .get_price_history_window(&symbols, "usd", Some(start), end, HistoryInterval::Daily)
+        .await
+        .expect("past window should use market_chart/range");
+
+    assert_eq!(history[0].points.len(), 2);
+    assert!((history[0].points[1].price - 28900.0).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn coingecko_provider_quotes_extra_currencies_in_one_request() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("vs_currency", "usd"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 60000.0 },
+            { "id": "ethereum", "symbol": "eth", "name": "Ethereum", "current_price": 3000.0 }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/simple/price"))
+        .and(query_param("ids", "bitcoin,ethereum"))
+        .and(query_param("vs_currencies", "jpy,eur"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "bitcoin": { "jpy": 9000000.0, "eur": 55000.0 },
+            "ethereum": { "jpy": 450000.0 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string(), "eth".to_string()];
+    let currencies = vec!["usd".to_string(), "jpy".to_string(), "eur".to_string()];
+    let prices = provider.get_prices_in(&symbols, &currencies).await.unwrap();
+
+    assert_eq!(prices.len(), 2);
+    assert_eq!(
+        prices[0].prices,
+        vec![
+            ("USD".to_string(), Some(60000.0)),
+            ("JPY".to_string(), Some(9000000.0)),
+            ("EUR".to_string(), Some(55000.0)),
+        ]
+    );
+    assert_eq!(prices[1].prices[2], ("EUR".to_string(), None));
+
+    // JSON keeps the requested currency order.
+    let json = serde_json::to_string(&prices[1]).unwrap();
+    assert!(
+        json.contains(r#""prices":{"USD":3000.0,"JPY":450000.0,"EUR":null}"#),
+        "{json}"
+    );
+}
+
+#[tokio::test]
+async fn coingecko_provider_lists_top_coins_capped_at_250() {
+    let server = mock_server().await;
+    let response = serde_json::json!([
+        {
+            "id": "bitcoin",
+            "symbol": "btc",
+            "name": "Bitcoin",
+            "current_price": 97000.0,
+            "market_cap": 1900000000000.0,
+            "market_cap_rank": 1,
+            "price_change_percentage_24h_in_currency": 1.2
+        },
+        {
+            "id": "ethereum",
+            "symbol": "eth",
+            "name": "Ethereum",
+            "current_price": 3000.0,
+            "market_cap": 360000000000.0,
+            "market_cap_rank": 2,
+            "price_change_percentage_24h_in_currency": -0.4
+        }
+    ]);
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("vs_currency", "eur"))
+        .and(query_param("order", "market_cap_desc"))
+        .and(query_param("per_page", "250"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let coins = provider.get_top_coins(500, "EUR").await.unwrap();
+
+    assert_eq!(coins.len(), 2);
+    assert_eq!(coins[0].symbol, "BTC");
+    assert_eq!(coins[0].rank, Some(1));
+    assert_eq!(coins[0].currency, "EUR");
+    assert_eq!(coins[1].symbol, "ETH");
+    assert_eq!(coins[1].change_24h, Some(-0.4));
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_lists_top_coins() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "status": { "error_message": null },
+        "data": [
+            {
+                "name": "Bitcoin",
+                "symbol": "BTC",
+                "cmc_rank": 1,
+                "quote": { "USD": { "price": 97000.0, "percent_change_24h": 1.2, "market_cap": 1.9e12 } }
+            },
+            {
+                "name": "Ethereum",
+                "symbol": "ETH",
+                "cmc_rank": 2,
+                "quote": { "USD": { "price": 3000.0, "percent_change_24h": -0.4, "market_cap": 3.6e11 } }
+            }
+        ]
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/listings/latest"))
+        .and(query_param("start", "1"))
+        .and(query_param("limit", "2"))
+        .and(query_param("convert", "USD"))
+        .and(header("X-CMC_PRO_API_KEY", "test-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let coins = provider.get_top_coins(2, "usd").await.unwrap();
+
+    assert_eq!(coins.len(), 2);
+    assert_eq!(coins[0].symbol, "BTC");
+    assert_eq!(coins[0].rank, Some(1));
+    assert_eq!(coins[1].market_cap, Some(3.6e11));
+}
+
+#[tokio::test]
+async fn coingecko_provider_fetches_global_metrics() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "data": {
+            "active_cryptocurrencies": 14203,
+            "total_market_cap": { "usd": 2.45e12, "eur": 2.25e12 },
+            "total_volume": { "usd": 9.81e10, "eur": 9.0e10 },
+            "market_cap_percentage": { "btc": 52.31, "eth": 16.92, "usdt": 4.1 },
+            "updated_at": 1771200000
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/global"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let metrics = provider.get_global_metrics("EUR").await.unwrap();
+
+    assert_eq!(metrics.total_market_cap, Some(2.25e12));
+    assert_eq!(metrics.total_volume_24h, Some(9.0e10));
+    assert_eq!(metrics.btc_dominance, Some(52.31));
+    assert_eq!(metrics.eth_dominance, Some(16.92));
+    assert_eq!(metrics.active_coins, Some(14203));
+    assert_eq!(metrics.currency, "EUR");
+    assert_eq!(metrics.timestamp.timestamp(), 1771200000);
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_fetches_global_metrics() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "status": { "error_message": null },
+        "data": {
+            "active_cryptocurrencies": 9412,
+            "btc_dominance": 52.1,
+            "eth_dominance": 16.8,
+            "quote": { "USD": { "total_market_cap": 2.4e12, "total_volume_24h": 9.5e10 } }
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v1/global-metrics/quotes/latest"))
+        .and(query_param("convert", "USD"))
+        .and(header("X-CMC_PRO_API_KEY", "test-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let metrics = provider.get_global_metrics("usd").await.unwrap();
+
+    assert_eq!(metrics.total_market_cap, Some(2.4e12));
+    assert_eq!(metrics.total_volume_24h, Some(9.5e10));
+    assert_eq!(metrics.btc_dominance, Some(52.1));
+    assert_eq!(metrics.active_coins, Some(9412));
+    assert_eq!(metrics.provider, "CoinMarketCap");
+}
+
+#[tokio::test]
+async fn coingecko_provider_lists_trending_coins_with_prices() {
+    let server = mock_server().await;
+    let trending = serde_json::json!({
+        "coins": [
+            { "item": { "id": "pepe", "symbol": "pepe", "name": "Pepe", "market_cap_rank": 25 } },
+            { "item": { "id": "render-token", "symbol": "rndr", "name": "Render", "market_cap_rank": 40 } },
+            { "item": { "id": "tiny-coin", "symbol": "tiny", "name": "Tiny", "market_cap_rank": null } }
+        ],
+        "nfts": [],
+        "categories": []
+    });
+    let markets = serde_json::json!([
+        { "id": "render-token", "symbol": "rndr", "name": "Render", "current_price": 7.5, "price_change_percentage_24h_in_currency": 3.2 },
+        { "id": "pepe", "symbol": "pepe", "name": "Pepe", "current_price": 0.0000123, "price_change_percentage_24h_in_currency": -1.1 }
+    ]);
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/search/trending"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(trending))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("ids", "pepe,render-token,tiny-coin"))
+        .and(query_param("vs_currency", "eur"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(markets))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let coins = provider.get_trending("EUR").await.unwrap();
+
+    assert_eq!(coins.len(), 3);
+    assert_eq!(coins[0].id, "pepe");
+    assert_eq!(coins[0].symbol, "PEPE");
+    assert_eq!(coins[0].market_cap_rank, Some(25));
+    assert_eq!(coins[0].price, Some(0.0000123));
+    assert_eq!(coins[1].id, "render-token");
+    assert_eq!(coins[1].change_24h, Some(3.2));
+    assert_eq!(coins[1].currency, "EUR");
+    // Listed even though the markets call had no quote for it.
+    assert_eq!(coins[2].price, None);
+}
+
+#[tokio::test]
+async fn coingecko_provider_lists_exchange_markets_by_volume() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "name": "Bitcoin",
+        "tickers": [
+            {
+                "base": "BTC", "target": "USD", "market": { "name": "Coinbase Exchange" },
+                "last": 97010.0, "converted_volume": { "usd": 8.0e8 }, "trust_score": "green"
+            },
+            {
+                "base": "BTC", "target": "USDT", "market": { "name": "Binance" },
+                "last": 97000.5, "converted_volume": { "usd": 1.5e9 }, "trust_score": "green"
+            },
+            {
+                "base": "BTC", "target": "USDT", "market": { "name": "Binance" },
+                "last": 97001.0, "converted_volume": { "usd": 2.0e7 }, "trust_score": "green"
+            },
+            {
+                "base": "BTC", "target": "EUR", "market": { "name": "Kraken" },
+                "last": 89000.0, "converted_volume": { "usd": 3.0e8 }, "trust_score": null
+            }
+        ]
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/bitcoin/tickers"))
+        .and(query_param("order", "volume_desc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let markets = provider.get_markets("btc", 2).await.unwrap();
+
+    assert_eq!(markets.len(), 2);
+    assert_eq!(markets[0].exchange, "Binance");
+    assert_eq!(markets[0].pair, "BTC/USDT");
+    assert_eq!(markets[0].last_price, Some(97000.5));
+    assert_eq!(markets[0].trust_score.as_deref(), Some("green"));
+    assert_eq!(markets[1].exchange, "Coinbase Exchange");
+
+    // The duplicate Binance BTC/USDT row is dropped before the limit applies.
+    let markets = provider.get_markets("btc", 10).await.unwrap();
+    let pairs: Vec<(&str, &str)> = markets
+        .iter()
+        .map(|m| (m.exchange.as_str(), m.pair.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ("Binance", "BTC/USDT"),
+            ("Coinbase Exchange", "BTC/USD"),
+            ("Kraken", "BTC/EUR")
+        ]
+    );
+    assert_eq!(markets[2].trust_score, None);
+}
+
+#[tokio::test]
+async fn providers_without_listings_reject_top_coins() {
+    let err = YahooFinance::new()
+        .get_top_coins(10, "usd")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Config(_)), "{err:?}");
+    assert!(err.to_string().contains("--provider coingecko"));
+}
+
 #[tokio::test]
 async fn coinmarketcap_provider_fetches_history_for_chart_mode() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "status": { "error_message": null },
         "data": {
@@ -167,7 +860,7 @@ async fn coinmarketcap_provider_fetches_history_for_chart_mode() {
 
 #[tokio::test]
 async fn coinmarketcap_provider_fetches_history_from_web_chart_endpoint() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "data": {
             "points": [
@@ -210,7 +903,7 @@ async fn coinmarketcap_provider_fetches_history_from_web_chart_endpoint() {
 
 #[tokio::test]
 async fn coinmarketcap_provider_resolves_coin_id_from_coin_catalog() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
 
     let catalog = serde_json::json!([
         {
@@ -268,7 +961,7 @@ async fn coinmarketcap_provider_resolves_coin_id_from_coin_catalog() {
 
 #[tokio::test]
 async fn frankfurter_provider_fetches_history_for_fiat_chart_mode() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "amount": 1.0,
         "base": "USD",
@@ -301,16 +994,89 @@ async fn frankfurter_provider_fetches_history_for_fiat_chart_mode() {
 }
 
 #[tokio::test]
-async fn coingecko_provider_returns_parse_error_on_malformed_json() {
-    let server = MockServer::start().await;
+async fn frankfurter_provider_quotes_fiat_symbols_in_currency() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "amount": 1.0,
+        "base": "USD",
+        "date": "2026-02-20",
+        "rates": { "EUR": 0.8, "GBP": 0.5 }
+    });
 
     Mock::given(method("GET"))
-        .and(path("/api/v3/simple/price"))
-        .and(query_param("ids", "bitcoin"))
-        .and(query_param("vs_currencies", "usd"))
-        .and(query_param("include_24hr_change", "true"))
-        .and(query_param("include_market_cap", "true"))
-        .respond_with(ResponseTemplate::new(200).set_body_string("{not-json"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "EUR,GBP"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = Frankfurter::with_base_url(format!("{}/v1", server.uri()));
+    let symbols = vec!["eur".to_string(), "gbp".to_string(), "usd".to_string()];
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    assert_eq!(prices.len(), 3);
+    assert_eq!(prices[0].symbol, "EUR");
+    assert_eq!(prices[0].name, "Euro");
+    assert_eq!(prices[0].currency, "USD");
+    assert!((prices[0].price - 1.25).abs() < 1e-9);
+    assert!((prices[1].price - 2.0).abs() < 1e-9);
+    assert_eq!(prices[2].price, 1.0);
+    assert_eq!(provider.id(), "frankfurter");
+}
+
+#[tokio::test]
+async fn fiat_rates_use_fallback_for_non_ecb_codes_and_report_skipped() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "EUR"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "rates": { "EUR": 0.9 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v6/latest/USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": "success",
+            "base_code": "USD",
+            "rates": { "USD": 1.0, "EUR": 0.91, "NGN": 1500.0 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let source = FiatRateSource::new(
+        Frankfurter::with_base_url(format!("{}/v1", server.uri())),
+        OpenErApi::with_client(http::HttpClient::default(), format!("{}/v6", server.uri())),
+    );
+    let targets = vec!["eur".to_string(), "ngn".to_string(), "vnd".to_string()];
+    let rates = source.get_rates("usd", &targets).await.unwrap();
+
+    assert_eq!(rates.rates["EUR"].rate, 0.9);
+    assert_eq!(rates.rates["EUR"].provider, "Frankfurter/ECB");
+    assert_eq!(rates.rates["NGN"].rate, 1500.0);
+    assert_eq!(rates.rates["NGN"].provider, "ExchangeRate-API");
+    assert_eq!(rates.skipped, vec!["VND"]);
+}
+
+#[tokio::test]
+async fn coingecko_provider_returns_parse_error_on_malformed_json() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("ids", "bitcoin"))
+        .and(query_param("vs_currency", "usd"))
+        .and(query_param("price_change_percentage", "24h,7d,30d"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{not-json"))
         .mount(&server)
         .await;
 
@@ -323,15 +1089,14 @@ async fn coingecko_provider_returns_parse_error_on_malformed_json() {
 
 #[tokio::test]
 async fn coingecko_provider_returns_no_results_when_response_is_empty() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
 
     Mock::given(method("GET"))
-        .and(path("/api/v3/simple/price"))
+        .and(path("/api/v3/coins/markets"))
         .and(query_param("ids", "bitcoin"))
-        .and(query_param("vs_currencies", "usd"))
-        .and(query_param("include_24hr_change", "true"))
-        .and(query_param("include_market_cap", "true"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+        .and(query_param("vs_currency", "usd"))
+        .and(query_param("price_change_percentage", "24h,7d,30d"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
         .mount(&server)
         .await;
 
@@ -339,12 +1104,199 @@ async fn coingecko_provider_returns_no_results_when_response_is_empty() {
     let symbols = vec!["btc".to_string()];
     let result = provider.get_prices(&symbols, "usd").await;
 
-    assert!(matches!(result, Err(Error::NoResults)));
+    assert!(
+        matches!(result, Err(Error::NoResults { ref provider, ref symbols, ref currency })
+            if provider == "CoinGecko" && symbols == &["BTC"] && currency == "USD"),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn coingecko_provider_requests_duplicate_symbols_once() {
+    let server = mock_server().await;
+    let response = serde_json::json!([
+        {
+            "id": "bitcoin",
+            "symbol": "btc",
+            "name": "Bitcoin",
+            "current_price": 50000.0
+        }
+    ]);
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("ids", "bitcoin"))
+        .and(query_param("vs_currency", "usd"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string(), "BTC".to_string(), " Btc ".to_string()];
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices[0].symbol, "BTC");
+}
+
+/// `count` made-up symbols `coin0`, `coin1`, ... that no provider maps to another id.
+fn many_symbols(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("coin{}", i)).collect()
+}
+
+fn coingecko_markets(ids: &[String]) -> serde_json::Value {
+    // Reversed, since markets come back by market cap rather than request order.
+    ids.iter()
+        .rev()
+        .map(|id| serde_json::json!({ "id": id, "symbol": id, "name": id, "current_price": 1.0 }))
+        .collect()
+}
+
+#[tokio::test]
+async fn coingecko_provider_splits_long_id_lists_and_merges_in_order() {
+    let server = mock_server().await;
+    let symbols = many_symbols(60);
+    let (first, second) = symbols.split_at(coingecko::MAX_IDS_PER_REQUEST);
+    for chunk in [first, second] {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/coins/markets"))
+            .and(query_param("ids", chunk.join(",")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(coingecko_markets(chunk)))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/api/v3/simple/price"))
+        .and(query_param("ids", first.join(",")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "coin0": { "eur": 0.9 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/simple/price"))
+        .and(query_param("ids", second.join(",")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "coin59": { "eur": 0.8 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let currencies = vec!["usd".to_string(), "eur".to_string()];
+    let prices = provider.get_prices_in(&symbols, &currencies).await.unwrap();
+
+    let returned: Vec<String> = prices.iter().map(|p| p.symbol.to_lowercase()).collect();
+    assert_eq!(returned, symbols);
+    assert_eq!(prices[0].prices[1], ("EUR".to_string(), Some(0.9)));
+    assert_eq!(prices[59].prices[1], ("EUR".to_string(), Some(0.8)));
+}
+
+#[tokio::test]
+async fn coingecko_provider_keeps_other_chunks_when_one_fails() {
+    let server = mock_server().await;
+    let symbols = many_symbols(60);
+    let (first, second) = symbols.split_at(coingecko::MAX_IDS_PER_REQUEST);
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("ids", first.join(",")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(coingecko_markets(first)))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("ids", second.join(",")))
+        .respond_with(ResponseTemplate::new(414))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    assert_eq!(prices.len(), first.len());
+    assert_eq!(prices.last().unwrap().symbol, "COIN49");
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_splits_long_symbol_lists() {
+    let server = mock_server().await;
+    let symbols: Vec<String> = many_symbols(150)
+        .iter()
+        .map(|symbol| symbol.to_uppercase())
+        .collect();
+    let (first, second) = symbols.split_at(coinmarketcap::MAX_SYMBOLS_PER_REQUEST);
+    for chunk in [first, second] {
+        let data: serde_json::Map<String, serde_json::Value> = chunk
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.clone(),
+                    serde_json::json!({
+                        "name": symbol,
+                        "symbol": symbol,
+                        "quote": { "USD": { "price": 2.0 } }
+                    }),
+                )
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/v1/cryptocurrency/quotes/latest"))
+            .and(query_param("symbol", chunk.join(",")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "status": { "error_message": null },
+                "data": data
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    let returned: Vec<&str> = prices.iter().map(|p| p.symbol.as_str()).collect();
+    assert_eq!(returned, symbols);
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_requests_duplicate_symbols_once() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "status": { "error_message": null },
+        "data": {
+            "BTC": { "name": "Bitcoin", "symbol": "BTC", "quote": { "USD": { "price": 50000.0 } } },
+            "ETH": { "name": "Ethereum", "symbol": "ETH", "quote": { "USD": { "price": 3000.0 } } }
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/quotes/latest"))
+        .and(query_param("symbol", "BTC,ETH"))
+        .and(query_param("convert", "USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let symbols = vec!["btc".to_string(), "eth".to_string(), "BTC".to_string()];
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices[0].symbol, "BTC");
+    assert_eq!(prices[1].symbol, "ETH");
 }
 
 #[tokio::test]
 async fn coinmarketcap_provider_fetches_and_parses_mocked_response() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "status": {
             "error_message": null
@@ -353,11 +1305,17 @@ async fn coinmarketcap_provider_fetches_and_parses_mocked_response() {
             "BTC": {
                 "name": "Bitcoin",
                 "symbol": "BTC",
+                "cmc_rank": 1,
+                "circulating_supply": 19700000.0,
                 "quote": {
                     "USD": {
                         "price": 50000.0,
                         "percent_change_24h": 2.25,
-                        "market_cap": 1000000000.0
+                        "percent_change_7d": 6.5,
+                        "percent_change_30d": -10.0,
+                        "market_cap": 1000000000.0,
+                        "volume_24h": 30000000.0,
+                        "last_updated": "2026-02-20T21:58:00.000Z"
                     }
                 }
             },
@@ -394,9 +1352,19 @@ async fn coinmarketcap_provider_fetches_and_parses_mocked_response() {
     assert_eq!(prices[0].name, "Bitcoin");
     assert!((prices[0].price - 50000.0).abs() < f64::EPSILON);
     assert_eq!(prices[0].change_24h, Some(2.25));
+    assert_eq!(prices[0].change_7d, Some(6.5));
+    assert_eq!(prices[0].change_30d, Some(-10.0));
     assert_eq!(prices[0].market_cap, Some(1000000000.0));
+    assert_eq!(prices[0].volume_24h, Some(30000000.0));
+    assert_eq!(prices[0].circulating_supply, Some(19700000.0));
+    assert_eq!(prices[0].rank, Some(1));
     assert_eq!(prices[0].currency, "USD");
     assert_eq!(prices[0].provider, "CoinMarketCap");
+    assert_eq!(
+        prices[0].timestamp.to_rfc3339(),
+        "2026-02-20T21:58:00+00:00"
+    );
+    assert!(!prices[0].quote_time_estimated);
 
     assert_eq!(prices[1].symbol, "ETH");
     assert_eq!(prices[1].name, "Ethereum");
@@ -405,11 +1373,13 @@ async fn coinmarketcap_provider_fetches_and_parses_mocked_response() {
     assert_eq!(prices[1].market_cap, Some(500000000.0));
     assert_eq!(prices[1].currency, "USD");
     assert_eq!(prices[1].provider, "CoinMarketCap");
+    // No `last_updated`: the fetch time stands in and is flagged as such.
+    assert!(prices[1].quote_time_estimated);
 }
 
 #[tokio::test]
 async fn coinmarketcap_provider_returns_api_error_on_non_success_status() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
 
     Mock::given(method("GET"))
         .and(path("/v1/cryptocurrency/quotes/latest"))
@@ -425,12 +1395,44 @@ async fn coinmarketcap_provider_returns_api_error_on_non_success_status() {
     let symbols = vec!["btc".to_string()];
     let result = provider.get_prices(&symbols, "usd").await;
 
-    assert!(matches!(result, Err(Error::Api(ref msg)) if msg.contains("500")));
+    assert!(matches!(
+        result,
+        Err(Error::Api { ref provider, status: Some(500), ref message })
+            if provider == "CoinMarketCap" && message.contains("500")
+    ));
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_unauthorized_error_carries_status_and_hint() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/quotes/latest"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "status": { "error_code": 1002, "error_message": "API key missing." }
+        })))
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("bad-key".to_string(), format!("{}/v1", server.uri()));
+    let err = provider
+        .get_prices(&["btc".to_string()], "usd")
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.status(), Some(401));
+    assert_eq!(err.provider(), Some("CoinMarketCap"));
+    assert!(
+        err.to_string()
+            .starts_with("API error: CoinMarketCap returned 401 Unauthorized")
+    );
+    assert!(err.hint().unwrap().contains("COINMARKETCAP_API_KEY"));
 }
 
 #[tokio::test]
 async fn coinmarketcap_provider_returns_parse_error_on_malformed_json() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
 
     Mock::given(method("GET"))
         .and(path("/v1/cryptocurrency/quotes/latest"))
@@ -451,7 +1453,7 @@ async fn coinmarketcap_provider_returns_parse_error_on_malformed_json() {
 
 #[tokio::test]
 async fn coinmarketcap_provider_returns_no_results_when_response_has_no_data() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "status": {
             "error_message": null
@@ -473,12 +1475,16 @@ async fn coinmarketcap_provider_returns_no_results_when_response_has_no_data() {
     let symbols = vec!["btc".to_string()];
     let result = provider.get_prices(&symbols, "usd").await;
 
-    assert!(matches!(result, Err(Error::NoResults)));
+    assert!(
+        matches!(result, Err(Error::NoResults { ref provider, ref symbols, ref currency })
+            if provider == "CoinMarketCap" && symbols == &["BTC"] && currency == "USD"),
+        "{result:?}"
+    );
 }
 
 #[tokio::test]
 async fn stooq_provider_fetches_and_parses_mocked_response() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let aapl_response = "AAPL.US,20260220,220019,190.00,194.10,189.70,193.80,42070499,";
     let msft_response = "MSFT.US,20260220,220019,420.00,427.00,418.40,425.77,34015249,";
 
@@ -503,16 +1509,22 @@ async fn stooq_provider_fetches_and_parses_mocked_response() {
     let prices = provider.get_prices(&symbols, "usd").await.unwrap();
 
     assert_eq!(prices.len(), 2);
-    assert_eq!(prices[0].symbol, "AAPL");
-    assert_eq!(prices[0].name, "AAPL");
+    assert_eq!(prices[0].symbol, "AAPL.US");
+    assert_eq!(prices[0].name, "AAPL.US");
     assert!((prices[0].price - 193.80).abs() < f64::EPSILON);
     assert!((prices[0].change_24h.unwrap() - 2.0).abs() < 1e-6);
     assert_eq!(prices[0].market_cap, None);
     assert_eq!(prices[0].currency, "USD");
     assert_eq!(prices[0].provider, "Stooq");
-
-    assert_eq!(prices[1].symbol, "MSFT");
-    assert_eq!(prices[1].name, "MSFT");
+    // 22:00:19 Warsaw time (CET) on 2026-02-20.
+    assert_eq!(
+        prices[0].timestamp.to_rfc3339(),
+        "2026-02-20T21:00:19+00:00"
+    );
+    assert!(!prices[0].quote_time_estimated);
+
+    assert_eq!(prices[1].symbol, "MSFT.US");
+    assert_eq!(prices[1].name, "MSFT.US");
     assert!((prices[1].price - 425.77).abs() < f64::EPSILON);
     assert!((prices[1].change_24h.unwrap() - 1.3738095238095238).abs() < 1e-9);
     assert_eq!(prices[1].market_cap, None);
@@ -520,9 +1532,62 @@ async fn stooq_provider_fetches_and_parses_mocked_response() {
     assert_eq!(prices[1].provider, "Stooq");
 }
 
+#[tokio::test]
+async fn stooq_history_fetches_symbols_concurrently_up_to_limit() {
+    let server = mock_server().await;
+    let response = "Date,Open,High,Low,Close,Volume\n2026-02-20,192.5,196.2,192.0,195.7,41000000\n";
+    let delay = std::time::Duration::from_millis(300);
+
+    Mock::given(method("GET"))
+        .and(path("/q/d/l/"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(response)
+                .set_delay(delay),
+        )
+        .mount(&server)
+        .await;
+
+    let symbols: Vec<String> = ["aapl", "msft", "nvda", "tsla"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let client_with_limit = |limit| {
+        http::build_client(&HttpConfig {
+            max_concurrency: Some(limit),
+            ..Default::default()
+        })
+        .unwrap()
+    };
+
+    let sequential = Stooq::with_client(client_with_limit(1), server.uri());
+    let started = std::time::Instant::now();
+    sequential
+        .get_price_history(&symbols, "usd", 30, HistoryInterval::Daily)
+        .await
+        .unwrap();
+    let sequential_elapsed = started.elapsed();
+
+    let concurrent = Stooq::with_client(client_with_limit(4), server.uri());
+    let started = std::time::Instant::now();
+    let histories = concurrent
+        .get_price_history(&symbols, "usd", 30, HistoryInterval::Daily)
+        .await
+        .unwrap();
+    let concurrent_elapsed = started.elapsed();
+
+    let order: Vec<&str> = histories.iter().map(|h| h.symbol.as_str()).collect();
+    assert_eq!(order, vec!["AAPL.US", "MSFT.US", "NVDA.US", "TSLA.US"]);
+    assert!(sequential_elapsed >= delay * 4, "{sequential_elapsed:?}");
+    assert!(
+        concurrent_elapsed < delay * 2,
+        "{concurrent_elapsed:?} vs {sequential_elapsed:?}"
+    );
+}
+
 #[tokio::test]
 async fn stooq_provider_fetches_history_for_chart_mode() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = "Date,Open,High,Low,Close,Volume\n2026-02-18,190.0,194.1,189.7,193.8,42070499\n2026-02-19,193.8,195.0,191.0,192.5,39000000\n2026-02-20,192.5,196.2,192.0,195.7,41000000\n";
 
     Mock::given(method("GET"))
@@ -541,18 +1606,236 @@ async fn stooq_provider_fetches_history_for_chart_mode() {
         .expect("history should parse");
 
     assert_eq!(history.len(), 1);
-    assert_eq!(history[0].symbol, "AAPL");
-    assert_eq!(history[0].name, "AAPL");
+    assert_eq!(history[0].symbol, "AAPL.US");
+    assert_eq!(history[0].name, "AAPL.US");
     assert_eq!(history[0].currency, "USD");
     assert_eq!(history[0].provider, "Stooq");
     assert_eq!(history[0].points.len(), 3);
+    assert_eq!(history[0].granularity, None);
     assert!((history[0].points[0].price - 193.8).abs() < f64::EPSILON);
     assert!((history[0].points[2].price - 195.7).abs() < f64::EPSILON);
 }
 
+#[tokio::test]
+async fn stooq_provider_fetches_history_window_and_clamps_points() {
+    let server = mock_server().await;
+    let response = "Date,Open,High,Low,Close,Volume\n2026-02-17,188.0,190.0,187.5,189.9,40000000\n2026-02-18,190.0,194.1,189.7,193.8,42070499\n2026-02-19,193.8,195.0,191.0,192.5,39000000\n2026-02-20,192.5,196.2,192.0,195.7,41000000\n";
+
+    Mock::given(method("GET"))
+        .and(path("/q/d/l/"))
+        .and(query_param("s", "msft.us"))
+        .and(query_param("i", "d"))
+        .and(query_param("d1", "20260218"))
+        .and(query_param("d2", "20260219"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(response))
+        .mount(&server)
+        .await;
+
+    let provider = Stooq::with_base_url(server.uri());
+    let start = chrono::NaiveDate::from_ymd_opt(2026, 2, 18)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc());
+    let end = chrono::NaiveDate::from_ymd_opt(2026, 2, 19)
+        .and_then(|d| d.and_hms_opt(23, 59, 59))
+        .unwrap()
+        .and_utc();
+    let history = provider
+        .get_price_history_window(
+            &["msft".to_string()],
+            "usd",
+            start,
+            end,
+            HistoryInterval::Auto,
+        )
+        .await
+        .expect("window history should parse");
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].symbol, "MSFT.US");
+    assert_eq!(history[0].points.len(), 2);
+    // Auto asks for hourly points on a two-day window; Stooq only has daily closes.
+    assert_eq!(history[0].granularity, Some(ResolvedInterval::Daily));
+    assert!((history[0].points[0].price - 193.8).abs() < f64::EPSILON);
+    assert!((history[0].points[1].price - 192.5).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn stooq_provider_appends_market_suffix_and_passes_through_explicit_ones() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/q/l/"))
+        .and(query_param("s", "sap.de"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("SAP.DE,20260220,173500,180.00,183.00,179.50,181.80,1520000,"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/q/l/"))
+        .and(query_param("s", "7203.jp"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("7203.JP,20260220,150000,2900,2950,2890,2940,8100000,"),
+        )
+        .mount(&server)
+        .await;
+
+    let provider = Stooq::with_base_url(server.uri()).with_market("de");
+    let symbols = vec!["sap".to_string(), "7203.JP".to_string()];
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices[0].symbol, "SAP.DE");
+    assert_eq!(prices[0].currency, "EUR");
+    assert_eq!(prices[1].symbol, "7203.JP");
+    assert_eq!(prices[1].currency, "JPY");
+}
+
+#[tokio::test]
+async fn stooq_history_uses_market_suffix() {
+    let server = mock_server().await;
+    let response = "Date,Open,High,Low,Close,Volume\n2026-02-19,180.0,182.0,179.0,181.0,1500000\n2026-02-20,181.0,183.0,179.5,181.8,1520000\n";
+
+    Mock::given(method("GET"))
+        .and(path("/q/d/l/"))
+        .and(query_param("s", "sap.de"))
+        .and(query_param("i", "d"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(response))
+        .mount(&server)
+        .await;
+
+    let provider = Stooq::with_base_url(server.uri()).with_market("de");
+    let history = provider
+        .get_price_history(&["sap".to_string()], "usd", 30, HistoryInterval::Daily)
+        .await
+        .expect("suffixed history should parse");
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].symbol, "SAP.DE");
+    assert_eq!(history[0].currency, "EUR");
+    assert_eq!(history[0].points.len(), 2);
+}
+
+#[tokio::test]
+async fn stooq_provider_maps_index_and_commodity_symbols() {
+    let server = mock_server().await;
+    for (ticker, row) in [
+        (
+            "^spx",
+            "^SPX,20260220,220000,6080.1,6110.0,6070.2,6101.5,0,",
+        ),
+        (
+            "^dji",
+            "^DJI,20260220,220000,44500.0,44700.0,44400.0,44650.0,0,",
+        ),
+        (
+            "xauusd",
+            "XAUUSD,20260220,220000,2940.0,2960.0,2935.0,2950.0,0,",
+        ),
+        ("cl.f", "CL.F,20260220,220000,71.0,72.0,70.5,71.5,0,"),
+    ] {
+        Mock::given(method("GET"))
+            .and(path("/q/l/"))
+            .and(query_param("s", ticker))
+            .respond_with(ResponseTemplate::new(200).set_body_string(row))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let provider = Stooq::with_base_url(server.uri());
+    let symbols: Vec<String> = ["^spx", ".dji", "gold", "oil"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let prices = provider.get_prices(&symbols, "eur").await.unwrap();
+
+    let rows: Vec<(&str, &str, &str)> = prices
+        .iter()
+        .map(|p| (p.symbol.as_str(), p.name.as_str(), p.currency.as_str()))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("^SPX", "S&P 500", "EUR"),
+            ("^DJI", "Dow Jones Industrial Average", "EUR"),
+            ("XAUUSD", "Gold", "USD"),
+            ("CL.F", "Crude Oil (WTI)", "USD"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stooq_history_maps_index_prefix() {
+    let server = mock_server().await;
+    let response = "Date,Open,High,Low,Close,Volume\n2026-02-19,6050.0,6090.0,6040.0,6080.1,0\n2026-02-20,6080.1,6110.0,6070.2,6101.5,0\n";
+
+    Mock::given(method("GET"))
+        .and(path("/q/d/l/"))
+        .and(query_param("s", "^spx"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(response))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = Stooq::with_base_url(server.uri()).with_market("de");
+    let history = provider
+        .get_price_history(
+            &["index:spx".to_string()],
+            "usd",
+            30,
+            HistoryInterval::Daily,
+        )
+        .await
+        .expect("index history should parse");
+
+    assert_eq!(history[0].symbol, "^SPX");
+    assert_eq!(history[0].name, "S&P 500");
+    assert_eq!(history[0].points.len(), 2);
+}
+
+#[tokio::test]
+async fn stooq_provider_rejects_hourly_history() {
+    let provider = Stooq::with_base_url("http://127.0.0.1:9");
+    let result = provider
+        .get_price_history_window(
+            &["aapl".to_string()],
+            "usd",
+            None,
+            chrono::Utc::now(),
+            HistoryInterval::Hourly,
+        )
+        .await;
+
+    assert!(matches!(result, Err(Error::Config(ref msg)) if msg.contains("daily history only")));
+}
+
+#[tokio::test]
+async fn stooq_provider_returns_parse_error_on_malformed_csv() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/q/d/l/"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("Date,Open,High,Low,Close,Volume\n2026-02-18,190.0,oops\n"),
+        )
+        .mount(&server)
+        .await;
+
+    let provider = Stooq::with_base_url(server.uri());
+    let result = provider
+        .get_price_history(&["aapl".to_string()], "usd", 30, HistoryInterval::Daily)
+        .await;
+
+    assert!(matches!(result, Err(Error::Parse(ref msg)) if msg.contains("Stooq CSV")));
+}
+
 #[tokio::test]
 async fn stooq_provider_searches_tickers() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "quotes": [
             {
@@ -593,7 +1876,7 @@ async fn stooq_provider_searches_tickers() {
 
 #[tokio::test]
 async fn yahoo_provider_fetches_and_parses_mocked_response() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response = serde_json::json!({
         "chart": {
             "result": [
@@ -638,35 +1921,791 @@ async fn yahoo_provider_fetches_and_parses_mocked_response() {
     assert_eq!(prices[0].market_cap, None);
     assert_eq!(prices[0].currency, "EUR");
     assert_eq!(prices[0].provider, "Yahoo Finance");
+    assert_eq!(prices[0].extended, None);
 }
 
 #[tokio::test]
-async fn yahoo_provider_fetches_history_with_explicit_window() {
-    let server = MockServer::start().await;
-    let response = serde_json::json!({
-        "chart": {
-            "result": [
-                {
-                    "meta": {
-                        "currency": "EUR",
-                        "shortName": "Amundi MSCI World Swap UCITS ET"
-                    },
-                    "timestamp": [1735689600_i64, 1735776000_i64, 1735862400_i64],
-                    "indicators": {
-                        "quote": [
-                            {
-                                "close": [610.0, 615.5, 618.2]
-                            }
-                        ]
-                    }
-                }
-            ],
-            "error": null
-        }
-    });
-
+async fn yahoo_provider_bootstraps_cookie_and_crumb_when_asked() {
+    let server = mock_server().await;
     Mock::given(method("GET"))
-        .and(path("/v8/finance/chart/CW8.PA"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .and(query_param("crumb", "Ab1/cD2"))
+        .and(header("cookie", "A3=d=session"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Apple Inc.",
+            "USD",
+            200.0,
+        )))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "finance": {
+                "result": null,
+                "error": { "code": "Unauthorized", "description": "Invalid Crumb" }
+            }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/quote/AAPL"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("set-cookie", "A3=d=session; Path=/")
+                .set_body_string(
+                    r#"<html><script>{"CrumbStore":{"crumb":"Ab1\u002FcD2"}}</script></html>"#,
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "date": "2026-10-16",
+            "rates": { "EUR": 0.9 }
+        })))
+        .mount(&server)
+        .await;
+
+    let client = http::HttpClient::default();
+    let provider = YahooFinance::with_client(client.clone(), server.uri());
+    let symbols = vec!["aapl".to_string()];
+    for _ in 0..2 {
+        let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+        assert_eq!(prices[0].symbol, "AAPL");
+        assert_eq!(prices[0].price, 200.0);
+    }
+
+    // The session cookie stays with Yahoo's requests.
+    Frankfurter::with_client(client, format!("{}/v1", server.uri()))
+        .get_rates("usd", &["eur".to_string()])
+        .await
+        .unwrap();
+    let requests = server.received_requests().await.unwrap();
+    let rates = requests
+        .iter()
+        .find(|request| request.url.path() == "/v1/latest")
+        .unwrap();
+    assert!(!rates.headers.contains_key("cookie"));
+}
+
+#[tokio::test]
+async fn yahoo_provider_follows_consent_redirect_to_getcrumb() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .and(query_param("crumb", "xYz9"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Apple Inc.",
+            "USD",
+            200.0,
+        )))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(
+            ResponseTemplate::new(302)
+                .insert_header("location", format!("{}/consent/collect", server.uri())),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/consent/collect"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("<html>Before you continue</html>"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/quote/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>no crumb here</html>"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/test/getcrumb"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("xYz9"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let prices = provider
+        .get_prices(&["aapl".to_string()], "usd")
+        .await
+        .unwrap();
+    assert_eq!(prices[0].price, 200.0);
+}
+
+#[tokio::test]
+async fn yahoo_provider_reports_failed_crumb_bootstrap() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("Invalid Cookie"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/quote/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/test/getcrumb"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let err = provider
+        .get_prices(&["aapl".to_string()], "usd")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Api { .. }), "{err:?}");
+    assert_eq!(
+        err.to_string(),
+        "API error: Yahoo requires consent/crumb; bootstrap failed: getcrumb returned 404 Not Found"
+    );
+}
+
+fn yahoo_crypto_chart(name: &str, currency: &str, price: f64) -> serde_json::Value {
+    serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": currency,
+                        "shortName": name,
+                        "regularMarketPrice": price
+                    },
+                    "timestamp": [1735689600_i64],
+                    "indicators": { "quote": [{ "close": [price] }] }
+                }
+            ],
+            "error": null
+        }
+    })
+}
+
+#[tokio::test]
+async fn yahoo_provider_maps_bare_crypto_tickers_to_currency_pairs() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/BTC-USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Bitcoin USD",
+            "USD",
+            60000.0,
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/ETH-EUR"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Ethereum EUR",
+            "EUR",
+            2500.0,
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let prices = provider
+        .get_prices(&["btc".to_string(), "ETH-EUR".to_string()], "usd")
+        .await
+        .unwrap();
+
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices[0].symbol, "BTC");
+    assert_eq!(prices[0].currency, "USD");
+    assert_eq!(prices[0].price, 60000.0);
+    // An explicit pair is passed through as-is.
+    assert_eq!(prices[1].symbol, "ETH-EUR");
+    assert_eq!(prices[1].currency, "EUR");
+}
+
+#[tokio::test]
+async fn yahoo_provider_falls_back_to_usd_pair_and_converts() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/SOL-CHF"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "chart": {
+                "result": null,
+                "error": { "code": "Not Found", "description": "No data found, symbol may be delisted" }
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/SOL-USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Solana USD",
+            "USD",
+            150.0,
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "CHF"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "rates": { "CHF": 0.9 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = with_currency_fallback(
+        Box::new(YahooFinance::with_base_url(server.uri())),
+        mock_fiat_rates(&server),
+    );
+    let prices = provider
+        .get_prices(&["solana".to_string()], "chf")
+        .await
+        .unwrap();
+
+    assert_eq!(prices[0].symbol, "SOLANA");
+    assert_eq!(prices[0].currency, "CHF");
+    assert!((prices[0].price - 135.0).abs() < 1e-9);
+    assert_eq!(prices[0].provider, "Yahoo Finance\u{2192}ECB");
+}
+
+#[tokio::test]
+async fn yahoo_provider_maps_index_and_commodity_symbols() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/^GSPC"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart("S&P 500", "USD", 6100.0)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/GC=F"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Gold Apr 26",
+            "USD",
+            2950.0,
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let prices = provider
+        .get_prices(&["index:spx".to_string(), "gold".to_string()], "usd")
+        .await
+        .unwrap();
+
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices[0].symbol, "^GSPC");
+    assert_eq!(prices[0].name, "S&P 500");
+    assert_eq!(prices[1].symbol, "GC=F");
+    assert_eq!(prices[1].name, "Gold");
+    assert_eq!(prices[1].price, 2950.0);
+}
+
+#[tokio::test]
+async fn yahoo_history_maps_commodity_symbols() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/CL=F"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_crypto_chart(
+            "Crude Oil Apr 26",
+            "USD",
+            71.5,
+        )))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let history = provider
+        .get_price_history_window(
+            &["oil".to_string()],
+            "usd",
+            None,
+            chrono::Utc::now(),
+            HistoryInterval::Daily,
+        )
+        .await
+        .expect("commodity history should parse");
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].symbol, "CL=F");
+    assert_eq!(history[0].name, "Crude Oil (WTI)");
+    assert_eq!(history[0].points.len(), 1);
+}
+
+#[tokio::test]
+async fn yahoo_provider_parses_extended_quote_fields() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": "USD",
+                        "shortName": "Apple Inc.",
+                        "regularMarketPrice": 189.84,
+                        "regularMarketTime": 1735761600_i64,
+                        "chartPreviousClose": 187.44,
+                        "postMarketPrice": 190.31,
+                        "fiftyTwoWeekHigh": 199.62,
+                        "fiftyTwoWeekLow": 164.08,
+                        "regularMarketVolume": 53_297_400.0
+                    },
+                    "timestamp": [1735689600_i64, 1735776000_i64],
+                    "indicators": {
+                        "quote": [
+                            {
+                                "close": [187.44, 189.84]
+                            }
+                        ]
+                    }
+                }
+            ],
+            "error": null
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let prices = provider
+        .get_prices(&["aapl".to_string()], "usd")
+        .await
+        .unwrap();
+
+    let extended = prices[0].extended.as_ref().expect("extended quote");
+    assert_eq!(extended.pre_market_price, None);
+    assert_eq!(extended.post_market_price, Some(190.31));
+    assert_eq!(extended.fifty_two_week_high, Some(199.62));
+    assert_eq!(extended.fifty_two_week_low, Some(164.08));
+    assert_eq!(extended.regular_market_volume, Some(53_297_400.0));
+    assert_eq!(prices[0].timestamp.timestamp(), 1735761600);
+    assert!(!prices[0].quote_time_estimated);
+}
+
+#[tokio::test]
+async fn yahoo_provider_reads_bid_and_ask_when_quoted() {
+    let server = mock_server().await;
+    let chart = |symbol: &str, bid: serde_json::Value, ask: serde_json::Value| {
+        serde_json::json!({
+            "chart": {
+                "result": [
+                    {
+                        "meta": {
+                            "currency": "USD",
+                            "shortName": symbol,
+                            "regularMarketPrice": 100.0,
+                            "bid": bid,
+                            "ask": ask
+                        },
+                        "timestamp": [1735776000_i64],
+                        "indicators": { "quote": [ { "close": [100.0] } ] }
+                    }
+                ],
+                "error": null
+            }
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart(
+            "Apple Inc.",
+            serde_json::json!(99.95),
+            serde_json::json!(100.05),
+        )))
+        .mount(&server)
+        .await;
+    // Outside trading hours Yahoo reports zeros.
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/MSFT"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart(
+            "Microsoft",
+            serde_json::json!(0.0),
+            serde_json::Value::Null,
+        )))
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let prices = provider
+        .get_prices(&["aapl".to_string(), "msft".to_string()], "usd")
+        .await
+        .unwrap();
+
+    assert_eq!(prices[0].bid, Some(99.95));
+    assert_eq!(prices[0].ask, Some(100.05));
+    assert_eq!(prices[1].bid, None);
+    assert_eq!(prices[1].ask, None);
+    let json = serde_json::to_value(&prices[1]).unwrap();
+    assert!(json["bid"].is_null() && json["ask"].is_null());
+}
+
+fn mock_fiat_rates(server: &MockServer) -> std::sync::Arc<FiatRateSource> {
+    std::sync::Arc::new(FiatRateSource::new(
+        Frankfurter::with_base_url(format!("{}/v1", server.uri())),
+        OpenErApi::with_client(http::HttpClient::default(), format!("{}/v6", server.uri())),
+    ))
+}
+
+#[tokio::test]
+async fn currency_fallback_converts_listing_currency_rows() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": "EUR",
+                        "shortName": "Amundi MSCI World Swap UCITS ET",
+                        "regularMarketPrice": 600.0,
+                        "chartPreviousClose": 590.0
+                    },
+                    "timestamp": [1735689600_i64],
+                    "indicators": { "quote": [{ "close": [600.0] }] }
+                }
+            ],
+            "error": null
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/CW8.PA"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "EUR"))
+        .and(query_param("to", "USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "EUR",
+            "rates": { "USD": 1.1 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = with_currency_fallback(
+        Box::new(YahooFinance::with_base_url(server.uri())),
+        mock_fiat_rates(&server),
+    );
+    let symbols = vec!["cw8.pa".to_string()];
+    let prices = provider.get_prices(&symbols, "usd").await.unwrap();
+
+    assert_eq!(prices.len(), 1);
+    assert!((prices[0].price - 660.0).abs() < 1e-9);
+    assert_eq!(prices[0].currency, "USD");
+    assert!(prices[0].converted);
+    assert_eq!(prices[0].fx_rate, Some(1.1));
+    assert_eq!(prices[0].provider, "Yahoo Finance\u{2192}ECB");
+
+    let json = serde_json::to_value(&prices[0]).unwrap();
+    assert_eq!(json["converted"], true);
+    assert_eq!(json["fx_rate"], 1.1);
+}
+
+#[tokio::test]
+async fn currency_fallback_fills_other_currencies_by_conversion() {
+    let server = mock_server().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/CW8.PA"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "chart": {
+                "result": [{
+                    "meta": {
+                        "currency": "EUR",
+                        "symbol": "CW8.PA",
+                        "regularMarketPrice": 600.0,
+                        "chartPreviousClose": 590.0
+                    },
+                    "timestamp": [1735689600_i64],
+                    "indicators": { "quote": [{ "close": [600.0] }] }
+                }],
+                "error": null
+            }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "EUR"))
+        .and(query_param("to", "USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "EUR",
+            "rates": { "USD": 1.1 }
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = with_currency_fallback(
+        Box::new(YahooFinance::with_base_url(server.uri())),
+        mock_fiat_rates(&server),
+    );
+    let currencies = vec!["usd".to_string(), "eur".to_string()];
+    let prices = provider
+        .get_prices_in(&["cw8.pa".to_string()], &currencies)
+        .await
+        .unwrap();
+
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices[0].currency, "USD");
+    assert!((prices[0].price - 660.0).abs() < 1e-9);
+    assert_eq!(prices[0].prices[0].0, "USD");
+    assert!((prices[0].prices[0].1.unwrap() - 660.0).abs() < 1e-9);
+    assert_eq!(prices[0].prices[1], ("EUR".to_string(), Some(600.0)));
+}
+
+#[tokio::test]
+async fn currency_fallback_retries_rejected_currency_in_usd() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("vs_currency", "ngn"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({"error": "invalid vs_currency"})),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("vs_currency", "usd"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 50000.0, "market_cap": 1000.0 }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v6/latest/USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": "success",
+            "base_code": "USD",
+            "rates": { "USD": 1.0, "NGN": 1500.0 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = with_currency_fallback(
+        Box::new(CoinGecko::with_base_url(format!("{}/api/v3", server.uri()))),
+        mock_fiat_rates(&server),
+    );
+    let symbols = vec!["btc".to_string()];
+    let prices = provider.get_prices(&symbols, "ngn").await.unwrap();
+
+    assert_eq!(prices[0].price, 75_000_000.0);
+    assert_eq!(prices[0].market_cap, Some(1_500_000.0));
+    assert_eq!(prices[0].currency, "NGN");
+    assert_eq!(prices[0].provider, "CoinGecko\u{2192}ExchangeRate-API");
+}
+
+#[tokio::test]
+async fn coingecko_provider_rejects_unsupported_currency_with_suggestion() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/simple/supported_vs_currencies"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!(["usd", "eur", "usdc"])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(format!("{}/api/v3", server.uri()));
+    let symbols = vec!["btc".to_string()];
+    // The second lookup reuses the list fetched by the first.
+    for _ in 0..2 {
+        let err = provider.get_prices(&symbols, "usdd").await.unwrap_err();
+        assert!(
+            matches!(err, Error::Config(ref message)
+                if message == "currency 'USDD' not supported by coingecko; did you mean USD?"),
+            "{err:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn currency_fallback_retries_currency_coingecko_does_not_list_in_usd() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/api/v3/simple/supported_vs_currencies"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!(["usd", "eur"])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .and(query_param("vs_currency", "usd"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "bitcoin", "symbol": "btc", "name": "Bitcoin", "current_price": 50000.0 }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v6/latest/USD"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": "success",
+            "base_code": "USD",
+            "rates": { "USD": 1.0, "EGP": 50.0 }
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = with_currency_fallback(
+        Box::new(CoinGecko::with_base_url(format!("{}/api/v3", server.uri()))),
+        mock_fiat_rates(&server),
+    );
+    let prices = provider
+        .get_prices(&["btc".to_string()], "egp")
+        .await
+        .unwrap();
+
+    assert_eq!(prices[0].price, 2_500_000.0);
+    assert_eq!(prices[0].currency, "EGP");
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_checks_convert_against_fiat_map() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/fiat/map"))
+        .and(query_param("include_metals", "true"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": [
+                { "id": 2781, "name": "United States Dollar", "sign": "$", "symbol": "USD" },
+                { "id": 3575, "name": "Gold Troy Ounce", "code": "XAU", "symbol": "" },
+                { "id": 2834, "name": "Kenyan Shilling", "sign": "Sh", "symbol": "KES" }
+            ],
+            "status": { "error_code": 0, "error_message": null }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/map"))
+        .and(query_param("symbol", "KESS"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+            "status": { "error_code": 400, "error_message": "Invalid value for \"symbol\": \"KESS\"" }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/quotes/latest"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": {}
+        })))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let err = provider
+        .get_prices(&["btc".to_string()], "kess")
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err, Error::Config(ref message)
+            if message == "currency 'KESS' not supported by cmc; did you mean KES?"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn frankfurter_provider_checks_quote_against_currency_list() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/currencies"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "EUR": "Euro",
+            "USD": "United States Dollar"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = Frankfurter::with_base_url(format!("{}/v1", server.uri()));
+    let err = provider
+        .get_prices(&["eur".to_string()], "uds")
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err, Error::Config(ref message)
+            if message == "currency 'UDS' not supported by frankfurter; did you mean USD?"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn yahoo_provider_fetches_history_with_explicit_window() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": "EUR",
+                        "shortName": "Amundi MSCI World Swap UCITS ET"
+                    },
+                    "timestamp": [1735689600_i64, 1735776000_i64, 1735862400_i64],
+                    "indicators": {
+                        "quote": [
+                            {
+                                "close": [610.0, 615.5, 618.2]
+                            }
+                        ]
+                    }
+                }
+            ],
+            "error": null
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/CW8.PA"))
         .and(query_param("period1", "1735689600"))
         .and(query_param("period2", "1735948799"))
         .and(query_param("interval", "1d"))
@@ -692,3 +2731,307 @@ async fn yahoo_provider_fetches_history_with_explicit_window() {
     assert!((history[0].points[0].price - 610.0).abs() < f64::EPSILON);
     assert!((history[0].points[2].price - 618.2).abs() < f64::EPSILON);
 }
+
+#[tokio::test]
+async fn yahoo_provider_parses_dividend_and_split_events() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": "USD",
+                        "shortName": "Apple Inc."
+                    },
+                    "timestamp": [1596461400_i64, 1598880600_i64, 1604932200_i64],
+                    "indicators": {
+                        "quote": [
+                            {
+                                "close": [435.75, 129.04, 116.32]
+                            }
+                        ]
+                    },
+                    "events": {
+                        "dividends": {
+                            "1596807000": { "amount": 0.82, "date": 1596807000_i64 },
+                            "1604673000": { "amount": 0.205, "date": 1604673000_i64 }
+                        },
+                        "splits": {
+                            "1598880600": {
+                                "date": 1598880600_i64,
+                                "numerator": 4.0,
+                                "denominator": 1.0,
+                                "splitRatio": "4:1"
+                            }
+                        }
+                    }
+                }
+            ],
+            "error": null
+        }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .and(query_param("events", "div,splits"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    pricr::provider::set_history_events(true);
+    let provider = YahooFinance::with_base_url(server.uri());
+    let start = chrono::DateTime::<chrono::Utc>::from_timestamp(1596240000, 0).unwrap();
+    let end = chrono::DateTime::<chrono::Utc>::from_timestamp(1605000000, 0).unwrap();
+    let history = provider
+        .get_price_history_window(
+            &["aapl".to_string()],
+            "usd",
+            Some(start),
+            end,
+            HistoryInterval::Daily,
+        )
+        .await
+        .expect("history should parse");
+
+    let events = &history[0].events;
+    let kinds: Vec<HistoryEventKind> = events.iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            HistoryEventKind::Dividend,
+            HistoryEventKind::Split,
+            HistoryEventKind::Dividend,
+        ]
+    );
+    assert!((events[0].value - 0.82).abs() < f64::EPSILON);
+    assert!((events[1].value - 4.0).abs() < f64::EPSILON);
+    assert_eq!(events[1].timestamp.timestamp(), 1598880600);
+    assert!((events[2].value - 0.205).abs() < f64::EPSILON);
+
+    let json = serde_json::to_value(&history[0]).unwrap();
+    assert_eq!(json["events"][1]["kind"], "split");
+}
+
+#[tokio::test]
+async fn yahoo_provider_searches_tickers_including_crypto() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "quotes": [
+            {
+                "symbol": "BTC-USD",
+                "shortname": "Bitcoin USD",
+                "exchange": "CCC",
+                "exchDisp": "CCC",
+                "quoteType": "CRYPTOCURRENCY",
+                "typeDisp": "Cryptocurrency"
+            },
+            {
+                "symbol": "BITO",
+                "shortname": "ProShares Bitcoin ETF",
+                "longname": "ProShares Bitcoin Strategy ETF",
+                "exchange": "PCX",
+                "quoteType": "ETF"
+            },
+            {
+                "symbol": "MSTR",
+                "shortname": "MicroStrategy",
+                "exchange": "NMS",
+                "exchDisp": "NASDAQ",
+                "quoteType": "EQUITY"
+            }
+        ]
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v1/finance/search"))
+        .and(query_param("q", "bitcoin"))
+        .and(query_param("quotesCount", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let matches = provider.search_tickers("bitcoin", 2).await.unwrap();
+
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].symbol, "BTC-USD");
+    assert_eq!(matches[0].name, "Bitcoin USD");
+    assert_eq!(matches[0].exchange, "CCC");
+    assert_eq!(matches[0].asset_type, "CRYPTOCURRENCY");
+    assert_eq!(matches[0].provider, "Yahoo Finance");
+    assert_eq!(matches[1].symbol, "BITO");
+    assert_eq!(matches[1].name, "ProShares Bitcoin Strategy ETF");
+    assert_eq!(matches[1].exchange, "PCX");
+    assert_eq!(matches[1].asset_type, "ETF");
+}
+
+#[tokio::test]
+async fn yahoo_provider_search_returns_no_results_for_empty_quotes() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/v1/finance/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "quotes": [] })))
+        .mount(&server)
+        .await;
+
+    let provider = YahooFinance::with_base_url(server.uri());
+    let result = provider.search_tickers("zzzz", 5).await;
+
+    assert!(
+        matches!(result, Err(Error::NoResults { ref provider, .. }) if provider == "Yahoo Finance"),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn coingecko_provider_searches_tickers() {
+    let server = mock_server().await;
+    let response = serde_json::json!({
+        "coins": [
+            {
+                "id": "solana",
+                "name": "Solana",
+                "api_symbol": "solana",
+                "symbol": "SOL",
+                "market_cap_rank": 5
+            },
+            {
+                "id": "solana-name-service",
+                "name": "Solana Name Service",
+                "api_symbol": "solana-name-service",
+                "symbol": "SNS",
+                "market_cap_rank": null
+            },
+            {
+                "id": "wrapped-solana",
+                "name": "Wrapped SOL",
+                "api_symbol": "wrapped-solana",
+                "symbol": "SOL",
+                "market_cap_rank": 900
+            }
+        ],
+        "exchanges": [],
+        "categories": []
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .and(query_param("query", "solana"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(server.uri());
+    let matches = provider.search_tickers("solana", 2).await.unwrap();
+
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].symbol, "SOL");
+    assert_eq!(matches[0].name, "Solana");
+    assert_eq!(matches[0].exchange, "#5");
+    assert_eq!(matches[0].asset_type, "crypto");
+    assert_eq!(matches[0].provider, "CoinGecko");
+    assert_eq!(matches[1].symbol, "SNS");
+    assert_eq!(matches[1].exchange, "");
+
+    let suggestions = provider.suggest_ids("solana", 3).await;
+    assert_eq!(
+        suggestions,
+        vec![
+            "solana (SOL)",
+            "solana-name-service (SNS)",
+            "wrapped-solana (SOL)"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn coingecko_provider_search_returns_no_results_for_empty_coins() {
+    let server = mock_server().await;
+
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "coins": [] })))
+        .mount(&server)
+        .await;
+
+    let provider = CoinGecko::with_base_url(server.uri());
+    let result = provider.search_tickers("zzzz", 5).await;
+
+    assert!(
+        matches!(result, Err(Error::NoResults { ref provider, .. }) if provider == "CoinGecko"),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_searches_tickers_with_api_key() {
+    let server = mock_server().await;
+    let listing = serde_json::json!({
+        "data": [
+            { "id": 5426, "rank": 5, "name": "Solana", "symbol": "SOL", "slug": "solana" },
+            { "id": 1, "rank": 1, "name": "Bitcoin", "symbol": "BTC", "slug": "bitcoin" },
+            { "id": 9999, "rank": null, "name": "Solana Doge", "symbol": "SDOGE", "slug": "solana-doge" },
+            { "id": 7777, "rank": 400, "name": "Solana Name Service", "symbol": "SNS", "slug": "sns" }
+        ],
+        "status": { "error_code": 0, "error_message": null }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/map"))
+        .and(query_param("listing_status", "active"))
+        .and(header("X-CMC_PRO_API_KEY", "test-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(listing))
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let matches = provider.search_tickers("solana", 10).await.unwrap();
+
+    let symbols: Vec<&str> = matches.iter().map(|m| m.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["SOL", "SNS", "SDOGE"]);
+    assert_eq!(matches[0].name, "Solana");
+    assert_eq!(matches[0].exchange, "#5");
+    assert_eq!(matches[0].asset_type, "crypto");
+    assert_eq!(matches[0].provider, "CoinMarketCap");
+    assert_eq!(matches[2].exchange, "");
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_search_uses_symbol_filter_for_tickers() {
+    let server = mock_server().await;
+    let filtered = serde_json::json!({
+        "data": [
+            { "id": 1, "rank": 1, "name": "Bitcoin", "symbol": "BTC", "slug": "bitcoin" }
+        ],
+        "status": { "error_code": 0, "error_message": null }
+    });
+
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/map"))
+        .and(query_param("symbol", "BTC"))
+        .and(header("X-CMC_PRO_API_KEY", "test-api-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(filtered))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider =
+        CoinMarketCap::with_base_url("test-api-key".to_string(), format!("{}/v1", server.uri()));
+    let matches = provider.search_tickers("btc", 5).await.unwrap();
+
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].symbol, "BTC");
+    assert_eq!(matches[0].name, "Bitcoin");
+}
+
+#[tokio::test]
+async fn coinmarketcap_provider_search_requires_api_key() {
+    let provider = CoinMarketCap::without_key();
+    let result = provider.search_tickers("bitcoin", 5).await;
+
+    assert!(
+        matches!(result, Err(Error::Config(ref msg)) if msg.contains("API key required for search"))
+    );
+}
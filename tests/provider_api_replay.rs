@@ -5,20 +5,25 @@ use pricr::provider::coinmarketcap::CoinMarketCap;
 use wiremock::matchers::{header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
+async fn mock_server() -> MockServer {
+    // wiremock reuses pooled servers, so cached responses keyed by base URL would leak between tests.
+    pricr::provider::set_cache_enabled(false);
+    MockServer::start().await
+}
+
 #[tokio::test]
 async fn coingecko_replay_fixture_parses_like_real_response() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response: serde_json::Value = serde_json::from_str(include_str!(
-        "fixtures/coingecko/simple_price_btc_eth_usd.json",
+        "fixtures/coingecko/coins_markets_btc_eth_usd.json",
     ))
     .expect("coingecko fixture must be valid JSON");
 
     Mock::given(method("GET"))
-        .and(path("/api/v3/simple/price"))
+        .and(path("/api/v3/coins/markets"))
         .and(query_param("ids", "bitcoin,ethereum"))
-        .and(query_param("vs_currencies", "usd"))
-        .and(query_param("include_24hr_change", "true"))
-        .and(query_param("include_market_cap", "true"))
+        .and(query_param("vs_currency", "usd"))
+        .and(query_param("price_change_percentage", "24h,7d,30d"))
         .respond_with(ResponseTemplate::new(200).set_body_json(response))
         .mount(&server)
         .await;
@@ -33,13 +38,15 @@ async fn coingecko_replay_fixture_parses_like_real_response() {
     assert_eq!(prices.len(), 2);
     assert_eq!(prices[0].symbol, "BTC");
     assert_eq!(prices[0].provider, "CoinGecko");
+    assert_eq!(prices[0].change_7d, Some(3.4121));
+    assert_eq!(prices[0].rank, Some(1));
     assert_eq!(prices[1].symbol, "ETH");
     assert_eq!(prices[1].provider, "CoinGecko");
 }
 
 #[tokio::test]
 async fn coinmarketcap_replay_fixture_parses_like_real_response() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response: serde_json::Value = serde_json::from_str(include_str!(
         "fixtures/coinmarketcap/quotes_latest_btc_eth_usd.json",
     ))
@@ -71,7 +78,7 @@ async fn coinmarketcap_replay_fixture_parses_like_real_response() {
 
 #[tokio::test]
 async fn coinmarketcap_replay_error_fixture_returns_api_error() {
-    let server = MockServer::start().await;
+    let server = mock_server().await;
     let response: serde_json::Value = serde_json::from_str(include_str!(
         "fixtures/coinmarketcap/quotes_latest_error.json",
     ))
@@ -92,7 +99,56 @@ async fn coinmarketcap_replay_error_fixture_returns_api_error() {
     let result = provider.get_prices(&symbols, "usd").await;
 
     assert!(
-        matches!(result, Err(Error::Api(ref msg)) if msg.contains("invalid")),
+        matches!(result, Err(Error::Api { ref message, .. }) if message.contains("invalid")),
         "expected API error from replay fixture, got: {result:?}"
     );
 }
+
+#[cfg(feature = "devtools")]
+#[tokio::test]
+async fn recorded_responses_replay_without_network() {
+    use pricr::provider::fixtures::Fixtures;
+    use pricr::provider::http::HttpClient;
+
+    let server = mock_server().await;
+    let response: serde_json::Value = serde_json::from_str(include_str!(
+        "fixtures/coingecko/coins_markets_btc_eth_usd.json",
+    ))
+    .expect("coingecko fixture must be valid JSON");
+    Mock::given(method("GET"))
+        .and(path("/api/v3/coins/markets"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(response))
+        .mount(&server)
+        .await;
+
+    let dir = std::env::temp_dir().join(format!("pricr-record-{}", std::process::id()));
+    let base_url = format!("{}/api/v3", server.uri());
+    let symbols = vec!["btc".to_string(), "eth".to_string()];
+
+    let recorder = HttpClient::default().with_fixtures(Fixtures::record(&dir).unwrap());
+    let recorded = CoinGecko::with_client(recorder, base_url.clone())
+        .get_prices(&symbols, "usd")
+        .await
+        .unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+    let replayer = HttpClient::default().with_fixtures(Fixtures::replay(&dir).unwrap());
+    let provider = CoinGecko::with_client(replayer, base_url);
+    let replayed = provider.get_prices(&symbols, "usd").await.unwrap();
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    assert_eq!(replayed.len(), recorded.len());
+    assert_eq!(replayed[0].symbol, "BTC");
+    assert_eq!(replayed[0].price, recorded[0].price);
+
+    let err = provider
+        .get_prices(&["sol".to_string()], "usd")
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Api { .. })
+            && err.to_string().contains("no recorded response for GET"),
+        "{err:?}"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,68 @@
+//! Provider responses served from the on-disk cache. These tests enable the cache, so
+//! they live apart from `provider_api_mock.rs`, which turns it off process-wide.
+
+use pricr::provider::cache;
+use pricr::provider::frankfurter::Frankfurter;
+use pricr::provider::http::HttpClient;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn frankfurter_rates_are_cached_until_next_publication() {
+    let dir = std::env::temp_dir().join(format!("pricr-cache-test-{}", std::process::id()));
+    let server = MockServer::start().await;
+    let today = chrono::Utc::now().date_naive();
+
+    Mock::given(method("GET"))
+        .and(path("/v1/latest"))
+        .and(query_param("from", "USD"))
+        .and(query_param("to", "EUR"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "amount": 1.0,
+            "base": "USD",
+            "date": today.format("%Y-%m-%d").to_string(),
+            "rates": { "EUR": 0.9 }
+        })))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let provider = Frankfurter::with_client(
+        HttpClient::default().with_cache_dir(&dir),
+        format!("{}/v1", server.uri()),
+    );
+    let targets = vec!["eur".to_string()];
+    assert_eq!(
+        provider.get_rates("usd", &targets).await.unwrap()["EUR"],
+        0.9
+    );
+    // Today's rates are not superseded before tomorrow's publication.
+    assert_eq!(
+        provider.get_rates("usd", &targets).await.unwrap()["EUR"],
+        0.9
+    );
+    let entries = cache::list_entries(&dir);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].provider, "frankfurter");
+
+    // A registry with its own cache directory misses the entry above and leaves it alone.
+    let other = dir.join("registry");
+    let registry = pricr::ProviderRegistry::builder()
+        .base_url("frankfurter", format!("{}/v1", server.uri()))
+        .cache_dir(&other)
+        .build()
+        .unwrap();
+    let prices = registry
+        .get_prices("frankfurter", &["eur".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(prices[0].symbol, "EUR");
+    assert_eq!(cache::list_entries(&other).len(), 1);
+    assert_eq!(cache::list_entries(&dir).len(), 1);
+
+    // `--no-cache` goes to the network again.
+    pricr::provider::set_cache_enabled(false);
+    provider.get_rates("usd", &targets).await.unwrap();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
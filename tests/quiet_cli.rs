@@ -0,0 +1,106 @@
+//! `--quiet` prints bare values, checked byte for byte against a mock standing in
+//! for Yahoo Finance.
+
+mod common;
+
+use common::{run_pricr, yahoo_home};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn chart(price: f64, previous_close: Option<f64>) -> serde_json::Value {
+    serde_json::json!({
+        "chart": {
+            "result": [
+                {
+                    "meta": {
+                        "currency": "USD",
+                        "regularMarketPrice": price,
+                        "chartPreviousClose": previous_close
+                    },
+                    "timestamp": [1735689600_i64],
+                    "indicators": { "quote": [{ "close": [price] }] }
+                }
+            ],
+            "error": null
+        }
+    })
+}
+
+async fn yahoo() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart(200.0, Some(160.0))))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/MSFT"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chart(410.25, None)))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn quiet_prints_one_bare_value_per_symbol_in_input_order() {
+    let server = yahoo().await;
+    let home = yahoo_home("quiet-values", &server.uri());
+
+    let output = run_pricr(&home, &["-p", "yahoo", "msft", "aapl", "--quiet"]).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"410.25\n200\n");
+
+    let output = run_pricr(
+        &home,
+        &["-p", "yahoo", "aapl", "msft", "-q", "--field", "change_24h"],
+    )
+    .await;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"25\n\n");
+
+    let output = run_pricr(&home, &["-p", "yahoo", "aapl", "-q", "--field", "symbol"]).await;
+    assert_eq!(output.stdout, b"AAPL\n");
+}
+
+#[tokio::test]
+async fn quiet_prints_converted_amounts_in_calc_mode() {
+    let server = yahoo().await;
+    let home = yahoo_home("quiet-calc", &server.uri());
+
+    let output = run_pricr(&home, &["-p", "yahoo", "100usd", "aapl", "msft", "--quiet"]).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let expected = format!("0.5\n{}\n", 100.0 / 410.25);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+
+    let output = run_pricr(
+        &home,
+        &["-p", "yahoo", "100usd", "aapl", "-q", "--field", "price"],
+    )
+    .await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+}
+
+#[tokio::test]
+async fn unknown_fields_are_rejected_before_any_request() {
+    let server = yahoo().await;
+    let home = yahoo_home("quiet-fields", &server.uri());
+
+    let output = run_pricr(&home, &["-p", "yahoo", "aapl", "-q", "--field", "nope"]).await;
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown field 'nope'"));
+
+    let output = run_pricr(&home, &["-p", "yahoo", "aapl", "--field", "price"]).await;
+    assert_eq!(output.status.code(), Some(2));
+
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
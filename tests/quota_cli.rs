@@ -0,0 +1,74 @@
+//! `--quota` after CoinMarketCap lookups, with the state directory in a temp dir and a
+//! mock standing in for the CoinMarketCap API.
+
+mod common;
+
+use common::{Home, config_home, run_pricr};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn run_ok(home: &Home, args: &[&str]) -> std::process::Output {
+    let output = run_pricr(home, args).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn quotes(credit_count: u64) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "status": { "error_code": 0, "error_message": null, "credit_count": credit_count },
+        "data": {
+            "BTC": {
+                "name": "Bitcoin",
+                "symbol": "BTC",
+                "quote": { "USD": { "price": 60000.0 } }
+            }
+        }
+    }))
+}
+
+#[tokio::test]
+async fn quota_sums_the_credits_each_lookup_used() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/cryptocurrency/quotes/latest"))
+        .and(query_param("symbol", "BTC"))
+        .respond_with(quotes(3))
+        .mount(&server)
+        .await;
+    let home = config_home(
+        "quota",
+        &format!(
+            "[provider.cmc]\nbase_url = \"{}/v1\"\napi_key = \"test\"\nmonthly_credits = 10\n",
+            server.uri()
+        ),
+    );
+
+    let before = run_ok(&home, &["--quota", "--json"]).await;
+    let before: serde_json::Value = serde_json::from_slice(&before.stdout).unwrap();
+    assert_eq!(before["credits_used"], 0);
+
+    run_ok(&home, &["-p", "cmc", "btc", "--json"]).await;
+    run_ok(&home, &["-p", "cmc", "btc", "--json"]).await;
+
+    let after = run_ok(&home, &["--quota", "--json"]).await;
+    let after: serde_json::Value = serde_json::from_slice(&after.stdout).unwrap();
+    assert_eq!(after["provider"], "cmc");
+    assert_eq!(after["credits_used"], 6);
+    assert_eq!(after["monthly_credits"], 10);
+    assert_eq!(after["used_pct"], 60.0);
+    assert_eq!(
+        after["month"],
+        chrono::Utc::now().format("%Y-%m").to_string()
+    );
+
+    let table = run_ok(&home, &["--quota"]).await;
+    assert!(
+        String::from_utf8_lossy(&table.stdout).contains("6 / 10 (60.0%)"),
+        "{}",
+        String::from_utf8_lossy(&table.stdout)
+    );
+}
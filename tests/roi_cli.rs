@@ -0,0 +1,115 @@
+//! `pricr roi` end to end against a mock standing in for Yahoo Finance, which serves
+//! both the historical buy price and the current quote from its chart endpoint.
+
+mod common;
+
+use common::{run_pricr, yahoo_chart, yahoo_home};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Daily closes 100, 110, 125, 120, 130 from 2025-06-02, and a current price of 150.
+async fn mount_aapl(server: &MockServer) {
+    let start = 1_748_822_400_i64;
+    let timestamps: Vec<i64> = (0..5).map(|day| start + day * 86_400).collect();
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_chart(
+            "Apple Inc.",
+            150.0,
+            &timestamps,
+            &[100.0, 110.0, 125.0, 120.0, 130.0],
+        )))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn roi_buys_at_the_closest_day_and_values_at_the_current_price() {
+    let server = MockServer::start().await;
+    mount_aapl(&server).await;
+    let home = yahoo_home("roi-json", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "roi",
+            "aapl",
+            "--amount",
+            "1000usd",
+            "--start-date",
+            "2025-06-04",
+            "--json",
+            "-p",
+            "yahoo",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let row = &json[0];
+    assert_eq!(row["symbol"], "AAPL");
+    assert_eq!(row["currency"], "USD");
+    assert_eq!(row["invested"], 1000.0);
+    assert_eq!(row["bought_at"], "2025-06-04T00:00:00Z");
+    assert_eq!(row["buy_price"], 125.0);
+    assert_eq!(row["units"], 8.0);
+    assert_eq!(row["value"], 1200.0);
+    assert_eq!(row["gain"], 200.0);
+    assert_eq!(row["gain_pct"], 20.0);
+    assert!(row["cagr_pct"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn roi_before_the_first_data_point_names_the_earliest_date() {
+    let server = MockServer::start().await;
+    mount_aapl(&server).await;
+    let home = yahoo_home("roi-too-early", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "roi",
+            "aapl",
+            "--amount",
+            "$1000",
+            "--start-date",
+            "2025-05-20",
+            "-p",
+            "yahoo",
+        ],
+    )
+    .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("the earliest available date is 2025-06-02"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[tokio::test]
+async fn roi_requires_an_amount_and_a_start_date() {
+    let server = MockServer::start().await;
+    let home = yahoo_home("roi-missing", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &["roi", "aapl", "--amount", "1000usd", "-p", "yahoo"],
+    )
+    .await;
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("--start-date"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
@@ -0,0 +1,78 @@
+//! `--schema v1` and `--print-schema` end to end, with a mock standing in for Yahoo
+//! Finance.
+
+mod common;
+
+use common::{command, config_home, run_pricr, yahoo_chart, yahoo_home};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn schema_v1_wraps_prices_in_a_versioned_envelope() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v8/finance/chart/AAPL"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(yahoo_chart(
+            "Apple Inc.",
+            200.0,
+            &[1735689600],
+            &[200.0],
+        )))
+        .mount(&server)
+        .await;
+    let home = yahoo_home("schema", &server.uri());
+
+    let output = run_pricr(&home, &["-p", "yahoo", "aapl", "--json", "--schema", "v1"]).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let envelope: pricr::output::json::Envelope =
+        serde_json::from_slice(&output.stdout).expect("stdout should be a v1 envelope");
+    assert_eq!(envelope.schema, "cryptoprice/v1");
+    assert_eq!(envelope.meta.provider.as_deref(), Some("yahoo"));
+    assert_eq!(envelope.meta.currency.as_deref(), Some("usd"));
+    let pricr::output::json::EnvelopeData::Prices(prices) = envelope.data else {
+        panic!("expected prices");
+    };
+    assert_eq!(prices[0].symbol, "AAPL");
+    assert_eq!(prices[0].price, 200.0);
+
+    let legacy = run_pricr(&home, &["-p", "yahoo", "aapl", "--json"]).await;
+    let legacy: serde_json::Value = serde_json::from_slice(&legacy.stdout).unwrap();
+    assert!(legacy.is_array(), "{legacy}");
+}
+
+#[tokio::test]
+async fn schema_v1_needs_json_and_a_supported_mode() {
+    let home = yahoo_home("schema-flags", "http://127.0.0.1:9");
+
+    let table = run_pricr(&home, &["-p", "yahoo", "aapl", "--schema", "v1"]).await;
+    assert_eq!(table.status.code(), Some(2));
+
+    let trending = run_pricr(
+        &home,
+        &["-p", "yahoo", "--trending", "--json", "--schema", "v1"],
+    )
+    .await;
+    assert_eq!(trending.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&trending.stderr).contains("--schema v1"));
+}
+
+#[tokio::test]
+async fn print_schema_dumps_a_json_schema_document() {
+    let home = config_home("print-schema", "");
+    let output = command(&home)
+        .arg("--print-schema")
+        .output()
+        .await
+        .expect("pricr should run");
+    assert!(output.status.success());
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(schema["properties"]["schema"]["const"], "cryptoprice/v1");
+}
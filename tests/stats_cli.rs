@@ -0,0 +1,185 @@
+//! `pricr stats` and `--correlation` end to end against a mock standing in for Yahoo Finance.
+
+mod common;
+
+use common::{run_pricr, yahoo_home};
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Serve `closes` for `ticker`, one per day from 2025-06-02.
+async fn mount_closes(server: &MockServer, ticker: &str, closes: &[f64]) {
+    let start = 1_748_822_400_i64;
+    let timestamps: Vec<i64> = (0..closes.len() as i64)
+        .map(|day| start + day * 86_400)
+        .collect();
+    Mock::given(method("GET"))
+        .and(path(format!("/v8/finance/chart/{}", ticker)))
+        .and(query_param("interval", "1d"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "chart": {
+                "result": [
+                    {
+                        "meta": { "currency": "USD", "shortName": ticker },
+                        "timestamp": timestamps,
+                        "indicators": { "quote": [{ "close": closes }] }
+                    }
+                ],
+                "error": null
+            }
+        })))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+async fn mount_daily_history(server: &MockServer) {
+    mount_closes(server, "AAPL", &[100.0, 110.0, 99.0, 99.0, 108.9]).await;
+}
+
+#[tokio::test]
+async fn stats_command_prints_metrics_as_json_numbers_and_warns_on_short_series() {
+    let server = MockServer::start().await;
+    mount_daily_history(&server).await;
+    let home = yahoo_home("stats-json", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "stats",
+            "-p",
+            "yahoo",
+            "--json",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-07",
+            "aapl",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let row = &json[0];
+    assert_eq!(row["symbol"], "AAPL");
+    assert_eq!(row["days"], 5);
+    assert!((row["mean_daily_return_pct"].as_f64().unwrap() - 2.5).abs() < 1e-9);
+    assert!((row["worst_day_pct"].as_f64().unwrap() + 10.0).abs() < 1e-9);
+    assert!((row["max_drawdown_pct"].as_f64().unwrap() + 10.0).abs() < 1e-9);
+    assert!(row["annualized_volatility_pct"].is_f64());
+    assert!(row["downside_deviation_pct"].is_f64());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("fewer than 30 daily closes for AAPL; the estimates are unreliable"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[tokio::test]
+async fn stats_flag_prints_a_table_instead_of_a_chart() {
+    let server = MockServer::start().await;
+    mount_daily_history(&server).await;
+    let home = yahoo_home("stats-table", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "--stats",
+            "-p",
+            "yahoo",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-07",
+            "aapl",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Daily return statistics (2025-06-01..2025-06-07)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Volatility (ann.)"), "{stdout}");
+    assert!(stdout.contains("+2.50%"), "{stdout}");
+    assert!(stdout.contains("-10.00%"), "{stdout}");
+}
+
+#[tokio::test]
+async fn stats_rejects_chart_only_flags() {
+    let server = MockServer::start().await;
+    let home = yahoo_home("stats-conflict", &server.uri());
+
+    let output = run_pricr(&home, &["stats", "--sampling", "weekly", "aapl"]).await;
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("--sampling"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn correlation_prints_a_nested_matrix_and_excludes_short_series() {
+    let server = MockServer::start().await;
+    let aapl: Vec<f64> = (0..14).map(|day| 100.0 + (day % 3) as f64).collect();
+    let msft: Vec<f64> = aapl.iter().map(|price| price * 4.0).collect();
+    let nvda: Vec<f64> = aapl.iter().map(|price| 10_000.0 / price).collect();
+    mount_closes(&server, "AAPL", &aapl).await;
+    mount_closes(&server, "MSFT", &msft).await;
+    mount_closes(&server, "NVDA", &nvda).await;
+    mount_closes(&server, "TSLA", &aapl[..6]).await;
+    let home = yahoo_home("correlation-json", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "--correlation",
+            "-p",
+            "yahoo",
+            "--json",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-20",
+            "aapl",
+            "tsla",
+            "msft",
+            "nvda",
+        ],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let matrix = &json["correlation"];
+    assert!((matrix["AAPL"]["MSFT"].as_f64().unwrap() - 1.0).abs() < 1e-9);
+    assert!((matrix["MSFT"]["AAPL"].as_f64().unwrap() - 1.0).abs() < 1e-9);
+    assert!((matrix["AAPL"]["NVDA"].as_f64().unwrap() + 1.0).abs() < 1e-9);
+    assert!(matrix.get("TSLA").is_none());
+    assert_eq!(json["observations"], 13);
+    assert_eq!(json["excluded"], serde_json::json!(["TSLA"]));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("left out of the correlation matrix: TSLA"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,59 @@
+//! `--template-file` and `--template-str` end to end against a mock standing in for Yahoo Finance.
+#![cfg(feature = "templates")]
+
+mod common;
+
+use common::{mount_aapl, run_pricr, yahoo_home};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn builtin_markdown_table_renders_price_lookups() {
+    let server = MockServer::start().await;
+    mount_aapl(&server).await;
+    let home = yahoo_home("template-markdown", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &["-p", "yahoo", "aapl", "--template-file", "markdown-table"],
+    )
+    .await;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("| AAPL | Apple Inc. | $190.50 |"),
+        "{stdout}"
+    );
+}
+
+#[tokio::test]
+async fn inline_template_errors_name_the_line_and_column_before_fetching() {
+    let server = MockServer::start().await;
+    mount_aapl(&server).await;
+    let home = yahoo_home("template-error", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "-p",
+            "yahoo",
+            "aapl",
+            "--template-str",
+            "{{#each prices}}{{/if}}",
+        ],
+    )
+    .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("template '--template-str' line 1, column"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
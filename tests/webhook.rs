@@ -0,0 +1,243 @@
+//! `--webhook` delivery: payload shape, configured headers, the HMAC signature,
+//! and retries, against a mock that also stands in for Yahoo Finance.
+
+mod common;
+
+use std::time::Duration;
+
+use common::{Home, config_home, mount_aapl, provider_config, run_pricr};
+use hmac::{Hmac, Mac};
+use pricr::config::WebhookConfig;
+use pricr::provider::CoinPrice;
+use pricr::webhook::{Event, Webhook};
+use sha2::Sha256;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const SECRET: &str = "s3cret";
+
+fn webhook_home(name: &str, base_url: &str) -> Home {
+    config_home(
+        name,
+        &format!(
+            "{}\n[webhook]\nsecret = \"{}\"\nheaders = {{ Authorization = \"Bearer token\" }}\n",
+            provider_config("yahoo", base_url),
+            SECRET
+        ),
+    )
+}
+
+fn expected_signature(body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+async fn hook_requests(server: &MockServer) -> Vec<Request> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.path() == "/hook")
+        .collect()
+}
+
+fn aapl() -> CoinPrice {
+    CoinPrice {
+        symbol: "AAPL".into(),
+        name: "Apple Inc.".into(),
+        price: 190.5,
+        currency: "USD".into(),
+        provider: "Yahoo Finance".into(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn watch_posts_signed_refresh_and_alert_events() {
+    let server = MockServer::start().await;
+    mount_aapl(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    let home = webhook_home("webhook-watch", &server.uri());
+    let hook = format!("{}/hook", server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "-p",
+            "yahoo",
+            "aapl",
+            "--watch",
+            "--alert",
+            "aapl>100",
+            "--alert-once",
+            "--webhook",
+            &hook,
+        ],
+    )
+    .await;
+
+    assert_eq!(
+        output.status.code(),
+        Some(10),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let requests = hook_requests(&server).await;
+    assert_eq!(requests.len(), 2);
+
+    let refresh: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(refresh["event"], "refresh");
+    assert_eq!(refresh["prices"][0]["symbol"], "AAPL");
+    assert_eq!(refresh["prices"][0]["price"], 190.5);
+    assert!(refresh.get("missing").is_none(), "{refresh}");
+
+    let alert: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(alert["event"], "alert");
+    assert_eq!(alert["alert"], "AAPL>100");
+    assert_eq!(alert["price"]["symbol"], "AAPL");
+
+    for request in &requests {
+        assert_eq!(header(request, "content-type"), Some("application/json"));
+        assert_eq!(header(request, "authorization"), Some("Bearer token"));
+        assert_eq!(
+            header(request, "x-pricr-signature"),
+            Some(expected_signature(&request.body).as_str())
+        );
+    }
+}
+
+#[tokio::test]
+async fn webhook_needs_watch_or_alert() {
+    let server = MockServer::start().await;
+    let home = webhook_home("webhook-usage", &server.uri());
+
+    let output = run_pricr(
+        &home,
+        &[
+            "-p",
+            "yahoo",
+            "aapl",
+            "--webhook",
+            "http://127.0.0.1:9/hook",
+        ],
+    )
+    .await;
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("--webhook needs --watch or --alert"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_retried_three_times() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let webhook = Webhook::new(
+        reqwest::Client::new(),
+        &format!("{}/hook", server.uri()),
+        &WebhookConfig::default(),
+    )
+    .unwrap()
+    .with_retry_delay(Duration::from_millis(1));
+    let price = aapl();
+
+    webhook
+        .post(&Event::Refresh {
+            prices: std::slice::from_ref(&price),
+            missing: &["XYZ".to_string()],
+            portfolio: None,
+        })
+        .await
+        .unwrap();
+
+    let requests = hook_requests(&server).await;
+    assert_eq!(requests.len(), 3);
+    assert!(header(&requests[0], "x-pricr-signature").is_none());
+    let body: serde_json::Value = serde_json::from_slice(&requests[2].body).unwrap();
+    assert_eq!(body["missing"], serde_json::json!(["XYZ"]));
+}
+
+#[tokio::test]
+async fn delivery_gives_up_after_the_last_retry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let webhook = Webhook::new(
+        reqwest::Client::new(),
+        &format!("{}/hook", server.uri()),
+        &WebhookConfig::default(),
+    )
+    .unwrap()
+    .with_retry_delay(Duration::from_millis(1));
+    let price = aapl();
+
+    let err = webhook
+        .post(&Event::Refresh {
+            prices: std::slice::from_ref(&price),
+            missing: &[],
+            portfolio: None,
+        })
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("500"), "{err}");
+    assert_eq!(hook_requests(&server).await.len(), 4);
+}
+
+#[test]
+fn invalid_urls_and_headers_are_configuration_errors() {
+    let client = reqwest::Client::new();
+    assert!(
+        Webhook::new(
+            client.clone(),
+            "ftp://example.com",
+            &WebhookConfig::default()
+        )
+        .is_err()
+    );
+    let config = WebhookConfig {
+        headers: [("bad header".to_string(), "x".to_string())].into(),
+        secret: None,
+    };
+    let err = Webhook::new(client, "https://example.com/hook", &config).unwrap_err();
+    assert!(
+        err.to_string().contains("[webhook] header 'bad header'"),
+        "{err}"
+    );
+}